@@ -0,0 +1,495 @@
+//! The aggregation of the proofs of the SHA2-256, BLAKE2F and RIPEMD-160
+//! circuits into a single proof, the way the [`zkEVM`] aggregates the proofs
+//! of its chunks so that a verifier, e.g. a contract on L1, only has a single
+//! proof to check no matter how many circuits it covers.
+//!
+//! A KZG proof is verified by a cheap part, which reads the transcript and
+//! derives the commitments and evaluations to open, and an expensive part,
+//! the pairing check of the opening. The openings of several proofs are
+//! accumulated into a single pair of G1 points by a random linear
+//! combination, so that a single pairing check verifies all of them.
+//!
+//! `AggregationCircuit` runs the cheap part of the verifier of every proof
+//! in-circuit, with the loader of `snark-verifier` over the non-native
+//! arithmetic of the ECC chip of `halo2wrong`, and accumulates their
+//! openings in-circuit. Its instances are the limbs of the accumulator,
+//! followed by the instances of the proofs, so that its verifier checks its
+//! proof and then the single pairing check of the accumulator, see
+//! [`decide`]. The instances of the proofs are those of the circuits
+//! exposing their tables, the public inputs of their calls, so that the
+//! proof of the aggregation attests to the calls and not only to the
+//! validity of some proofs. The proofs it aggregates are proven over the
+//! Poseidon transcript, whose hash is cheap to constrain, see [`snark`].
+//!
+//! [`zkEVM`]: https://github.com/scroll-tech/zkevm-circuits/tree/develop/aggregator
+
+pub mod snark;
+
+pub use snark::{gen_snark, prove_snark, verify_snarks, Snark};
+
+use std::rc::Rc;
+
+use halo2_proofs::{
+    arithmetic::CurveAffine,
+    circuit::{Layouter, SimpleFloorPlanner, Value},
+    halo2curves::bn256::{Bn256, Fq, Fr, G1Affine},
+    plonk::{Circuit, ConstraintSystem, Error},
+    poly::{commitment::ParamsProver, kzg::commitment::ParamsKZG},
+};
+use rand_core::RngCore;
+use snark_verifier::{
+    loader::{
+        self,
+        halo2::halo2_wrong_ecc::{
+            self,
+            integer::rns::Rns,
+            maingate::{
+                AssignedValue, MainGate, MainGateConfig, MainGateInstructions, RangeChip,
+                RangeConfig, RangeInstructions, RegionCtx,
+            },
+            EccConfig,
+        },
+        native::NativeLoader,
+    },
+    pcs::{
+        kzg::{
+            Bdfg21, KzgAccumulator, KzgAs, KzgDecidingKey, KzgSuccinctVerifyingKey, LimbsEncoding,
+            LimbsEncodingInstructions,
+        },
+        AccumulationDecider, AccumulationScheme, AccumulationSchemeProver,
+    },
+    system,
+    util::arithmetic::{fe_from_limbs, fe_to_limbs},
+    verifier::{self, plonk::PlonkProtocol, SnarkVerifier},
+};
+
+/// Number of the limbs of a coordinate of the accumulator.
+const LIMBS: usize = 4;
+/// Bits of a limb of a coordinate of the accumulator.
+const BITS: usize = 68;
+/// Number of the limbs of the accumulator, which lead the instances of
+/// [`AggregationCircuit`].
+pub const ACCUMULATOR_LIMBS: usize = 4 * LIMBS;
+
+/// The accumulation scheme of the KZG openings of SHPLONK, which is that of
+/// BDFG21.
+type As = KzgAs<Bn256, Bdfg21>;
+/// The verifier of a proof up to the pairing check of its opening, which it
+/// outputs as an accumulator instead.
+type PlonkSuccinctVerifier = verifier::plonk::PlonkSuccinctVerifier<As, LimbsEncoding<LIMBS, BITS>>;
+type Svk = KzgSuccinctVerifyingKey<G1Affine>;
+type BaseFieldEccChip = halo2_wrong_ecc::BaseFieldEccChip<G1Affine, LIMBS, BITS>;
+type Halo2Loader<'a> = loader::halo2::Halo2Loader<'a, G1Affine, BaseFieldEccChip>;
+
+/// The Poseidon transcript of the proofs the circuit aggregates, and of the
+/// accumulation of their openings.
+pub type PoseidonTranscript<L, S> =
+    system::halo2::transcript::halo2::PoseidonTranscript<G1Affine, L, S, 5, 4, 8, 60>;
+
+/// A [`Snark`] as the witness of the circuit, whose protocol is known to its
+/// keys.
+#[derive(Clone, Debug)]
+struct SnarkWitness {
+    protocol: PlonkProtocol<G1Affine>,
+    instances: Vec<Vec<Value<Fr>>>,
+    proof: Value<Vec<u8>>,
+}
+
+impl SnarkWitness {
+    fn new(snark: Snark) -> Self {
+        Self {
+            protocol: snark.protocol,
+            instances: snark
+                .instances
+                .into_iter()
+                .map(|column| column.into_iter().map(Value::known).collect())
+                .collect(),
+            proof: Value::known(snark.proof),
+        }
+    }
+
+    fn without_witnesses(&self) -> Self {
+        Self {
+            protocol: self.protocol.clone(),
+            instances: self
+                .instances
+                .iter()
+                .map(|column| vec![Value::unknown(); column.len()])
+                .collect(),
+            proof: Value::unknown(),
+        }
+    }
+
+    fn proof(&self) -> Value<&[u8]> {
+        self.proof.as_ref().map(Vec::as_slice)
+    }
+}
+
+#[derive(Clone)]
+pub struct AggregationConfig {
+    main_gate: MainGateConfig,
+    range: RangeConfig,
+}
+
+impl AggregationConfig {
+    pub fn configure(meta: &mut ConstraintSystem<Fr>) -> Self {
+        let main_gate = MainGate::<Fr>::configure(meta);
+        let range = RangeChip::<Fr>::configure(
+            meta,
+            &main_gate,
+            vec![BITS / LIMBS],
+            Rns::<Fq, Fr, LIMBS, BITS>::construct().overflow_lengths(),
+        );
+
+        Self { main_gate, range }
+    }
+
+    fn main_gate(&self) -> MainGate<Fr> {
+        MainGate::new(self.main_gate.clone())
+    }
+
+    fn ecc_chip(&self) -> BaseFieldEccChip {
+        BaseFieldEccChip::new(EccConfig::new(self.range.clone(), self.main_gate.clone()))
+    }
+}
+
+/// A circuit verifying proofs up to the pairing checks of their openings,
+/// which it accumulates into the one of its instances.
+#[derive(Clone, Debug)]
+pub struct AggregationCircuit {
+    svk: Svk,
+    snarks: Vec<SnarkWitness>,
+    instances: Vec<Fr>,
+    as_proof: Value<Vec<u8>>,
+}
+
+impl AggregationCircuit {
+    /// Builds the witness, accumulating the openings of `snarks` natively,
+    /// and fails if one of them cannot be read or if the accumulator does not
+    /// pass the pairing check.
+    pub fn new(
+        params: &ParamsKZG<Bn256>,
+        snarks: Vec<Snark>,
+        rng: impl RngCore,
+    ) -> Result<Self, Error> {
+        let svk: Svk = params.get_g()[0].into();
+        let mut accumulators = vec![];
+        for snark in &snarks {
+            let mut transcript = PoseidonTranscript::<NativeLoader, _>::new(&snark.proof[..]);
+            let proof = PlonkSuccinctVerifier::read_proof(
+                &svk,
+                &snark.protocol,
+                &snark.instances,
+                &mut transcript,
+            )
+            .map_err(|_| Error::Opening)?;
+            accumulators.extend(
+                PlonkSuccinctVerifier::verify(&svk, &snark.protocol, &snark.instances, &proof)
+                    .map_err(|_| Error::Opening)?,
+            );
+        }
+
+        let mut transcript = PoseidonTranscript::<NativeLoader, _>::new(vec![]);
+        let accumulator =
+            As::create_proof(&Default::default(), &accumulators, &mut transcript, rng)
+                .map_err(|_| Error::Opening)?;
+        As::decide(&deciding_key(params), accumulator.clone()).map_err(|_| Error::Opening)?;
+
+        let KzgAccumulator { lhs, rhs } = accumulator;
+        let mut instances = [lhs.x, lhs.y, rhs.x, rhs.y]
+            .map(fe_to_limbs::<_, _, LIMBS, BITS>)
+            .concat();
+        instances.extend(snarks.iter().flat_map(|snark| snark.instances.concat()));
+        Ok(Self {
+            svk,
+            snarks: snarks.into_iter().map(SnarkWitness::new).collect(),
+            instances,
+            as_proof: Value::known(transcript.finalize()),
+        })
+    }
+
+    /// Instances of the circuit: the limbs of the accumulator, followed by
+    /// the instances of the proofs, column after column.
+    pub fn instances(&self) -> Vec<Vec<Fr>> {
+        vec![self.instances.clone()]
+    }
+
+    fn as_proof(&self) -> Value<&[u8]> {
+        self.as_proof.as_ref().map(Vec::as_slice)
+    }
+
+    /// Verifies the proofs up to their pairing checks in-circuit, and
+    /// accumulates their openings with the proof of the accumulation: the
+    /// accumulator, along with the cells of the instances of the proofs.
+    #[allow(clippy::type_complexity)]
+    fn aggregate<'a>(
+        &self,
+        loader: &Rc<Halo2Loader<'a>>,
+        main_gate: &MainGate<Fr>,
+    ) -> Result<
+        (
+            KzgAccumulator<G1Affine, Rc<Halo2Loader<'a>>>,
+            Vec<AssignedValue<Fr>>,
+        ),
+        Error,
+    > {
+        let mut accumulators = vec![];
+        let mut instance_cells = vec![];
+        for snark in &self.snarks {
+            let protocol = snark.protocol.loaded(loader);
+            let mut instances = vec![];
+            for column in &snark.instances {
+                let mut loaded = vec![];
+                for instance in column {
+                    let cell = main_gate.assign_value(&mut loader.ctx_mut(), *instance)?;
+                    loaded.push(loader.scalar_from_assigned(cell.clone()));
+                    instance_cells.push(cell);
+                }
+                instances.push(loaded);
+            }
+
+            let mut transcript =
+                PoseidonTranscript::<Rc<Halo2Loader>, _>::new(loader, snark.proof());
+            let proof = PlonkSuccinctVerifier::read_proof(
+                &self.svk,
+                &protocol,
+                &instances,
+                &mut transcript,
+            )
+            .map_err(|_| Error::Synthesis)?;
+            accumulators.extend(
+                PlonkSuccinctVerifier::verify(&self.svk, &protocol, &instances, &proof)
+                    .map_err(|_| Error::Synthesis)?,
+            );
+        }
+
+        let mut transcript = PoseidonTranscript::<Rc<Halo2Loader>, _>::new(loader, self.as_proof());
+        let proof = As::read_proof(&Default::default(), &accumulators, &mut transcript)
+            .map_err(|_| Error::Synthesis)?;
+        let accumulator =
+            As::verify(&Default::default(), &accumulators, &proof).map_err(|_| Error::Synthesis)?;
+        Ok((accumulator, instance_cells))
+    }
+}
+
+impl Circuit<Fr> for AggregationCircuit {
+    type Config = AggregationConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self {
+            svk: self.svk,
+            snarks: self
+                .snarks
+                .iter()
+                .map(SnarkWitness::without_witnesses)
+                .collect(),
+            instances: vec![],
+            as_proof: Value::unknown(),
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+        AggregationConfig::configure(meta)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<Fr>,
+    ) -> Result<(), Error> {
+        let main_gate = config.main_gate();
+        RangeChip::<Fr>::new(config.range.clone()).load_table(&mut layouter)?;
+
+        let cells = layouter.assign_region(
+            || "aggregation",
+            |region| {
+                let loader = Halo2Loader::new(config.ecc_chip(), RegionCtx::new(region, 0));
+                let (accumulator, instance_cells) = self.aggregate(&loader, &main_gate)?;
+
+                let mut cells = vec![];
+                for point in [accumulator.lhs, accumulator.rhs] {
+                    cells.extend(
+                        loader
+                            .ecc_chip()
+                            .assign_ec_point_to_limbs(&mut loader.ctx_mut(), point.assigned())?,
+                    );
+                }
+                cells.extend(instance_cells);
+                Ok(cells)
+            },
+        )?;
+
+        for (row, cell) in cells.into_iter().enumerate() {
+            main_gate.expose_public(layouter.namespace(|| "instance"), cell, row)?;
+        }
+        Ok(())
+    }
+}
+
+/// The key of the pairing check of the accumulators of proofs over
+/// `params`.
+fn deciding_key(params: &ParamsKZG<Bn256>) -> KzgDecidingKey<Bn256> {
+    (params.get_g()[0], params.g2(), params.s_g2()).into()
+}
+
+/// The pairing check of the accumulator of the `instances` of a proof of
+/// [`AggregationCircuit`] over `params`, which the verifier runs once it
+/// has verified the proof. Fails if the instances do not start with the
+/// limbs of two points of G1.
+pub fn decide(params: &ParamsKZG<Bn256>, instances: &[Vec<Fr>]) -> bool {
+    let Some(limbs) = instances
+        .first()
+        .and_then(|column| column.get(..ACCUMULATOR_LIMBS))
+    else {
+        return false;
+    };
+    let coordinates: Vec<Fq> = limbs
+        .chunks(LIMBS)
+        .map(|chunk| {
+            fe_from_limbs::<Fr, Fq, LIMBS, BITS>(chunk.try_into().expect("chunk of LIMBS limbs"))
+        })
+        .collect();
+    let point = |x: Fq, y: Fq| Option::<G1Affine>::from(G1Affine::from_xy(x, y));
+    match (
+        point(coordinates[0], coordinates[1]),
+        point(coordinates[2], coordinates[3]),
+    ) {
+        (Some(lhs), Some(rhs)) => {
+            As::decide(&deciding_key(params), KzgAccumulator::new(lhs, rhs)).is_ok()
+        }
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use blake2f_circuit::Blake2fChip;
+    use gadgets::{
+        batch::{BatchCircuit, BatchWitness},
+        rows, HashCircuitExt,
+    };
+    use halo2_proofs::dev::MockProver;
+    use rand_xorshift::XorShiftRng;
+    use ripemd160_circuit::{Ripemd160Chip, Ripemd160Witness};
+    use sha2_256_circuit::{Sha2Chip, Sha2Witness};
+    use std::marker::PhantomData;
+
+    use super::*;
+
+    fn rng() -> XorShiftRng {
+        use rand_core::SeedableRng;
+        XorShiftRng::from_seed([0x42; 16])
+    }
+
+    /// Smallest `k` of the parameters the three circuits of [`snarks`] fit
+    /// in.
+    fn k() -> u32 {
+        let sha2 = Sha2Witness {
+            inputs: sha2_256_circuit::dev::INPUTS_OUTPUTS.0.clone(),
+            _marker: PhantomData,
+        };
+        let ripemd160 = Ripemd160Witness {
+            inputs: ripemd160_circuit::dev::INPUTS_OUTPUTS.0.clone(),
+            _marker: PhantomData,
+        };
+        [
+            Sha2Chip::<Fr>::min_k(&sha2),
+            Blake2fChip::<Fr>::min_k(&blake2f_circuit::dev::INPUTS_OUTPUTS.0),
+            Ripemd160Chip::<Fr>::min_k(&ripemd160),
+        ]
+        .into_iter()
+        .max()
+        .expect("there are circuits")
+    }
+
+    /// The proof of the calls of `witness` by the circuit of `H` exposing its
+    /// table, with the public inputs of the calls as its instances.
+    fn snark<H: HashCircuitExt<Fr>>(params: &ParamsKZG<Bn256>, witness: H::Witness) -> Snark
+    where
+        H::Witness: BatchWitness + Default,
+    {
+        let instances = H::public_inputs(&witness);
+        let circuit = BatchCircuit::<Fr, H>::new(vec![witness]);
+        gen_snark(params, circuit, instances, rng()).unwrap()
+    }
+
+    fn snarks(params: &ParamsKZG<Bn256>) -> Vec<Snark> {
+        let sha2 = Sha2Witness {
+            inputs: sha2_256_circuit::dev::INPUTS_OUTPUTS.0.clone(),
+            _marker: PhantomData,
+        };
+        let ripemd160 = Ripemd160Witness {
+            inputs: ripemd160_circuit::dev::INPUTS_OUTPUTS.0.clone(),
+            _marker: PhantomData,
+        };
+        vec![
+            snark::<Sha2Chip<Fr>>(params, sha2),
+            snark::<Blake2fChip<Fr>>(params, blake2f_circuit::dev::INPUTS_OUTPUTS.0.clone()),
+            snark::<Ripemd160Chip<Fr>>(params, ripemd160),
+        ]
+    }
+
+    #[test]
+    fn test_verify_snarks() {
+        let params = ParamsKZG::<Bn256>::setup(k(), rng());
+        let mut snarks = snarks(&params);
+        assert!(snarks.iter().all(|snark| snark.verify(&params)));
+        assert!(verify_snarks(&params, &snarks));
+        assert!(snarks
+            .iter()
+            .all(|snark| !snark.instances.concat().is_empty()));
+        let circuit = AggregationCircuit::new(&params, snarks.clone(), rng()).unwrap();
+        assert!(decide(&params, &circuit.instances()));
+        // the instances of the proofs follow the accumulator.
+        let instances: Vec<_> = snarks
+            .iter()
+            .flat_map(|snark| snark.instances.concat())
+            .collect();
+        assert_eq!(circuit.instances()[0][ACCUMULATOR_LIMBS..], instances);
+
+        // a proof is not that of other instances, e.g. another digest of the
+        // first call.
+        let mut wrong = snarks.clone();
+        wrong[0].instances[0][3] += Fr::one();
+        assert!(!wrong[0].verify(&params));
+        assert!(!verify_snarks(&params, &wrong));
+        assert!(AggregationCircuit::new(&params, wrong, rng()).is_err());
+
+        // a proof that does not verify fails the whole accumulation.
+        snarks[1].proof[0] ^= 1;
+        assert!(!snarks[1].verify(&params));
+        assert!(!verify_snarks(&params, &snarks));
+        assert!(AggregationCircuit::new(&params, snarks, rng()).is_err());
+
+        // an accumulator of other limbs fails the pairing check.
+        let mut instances = circuit.instances();
+        instances[0][0] += Fr::one();
+        assert!(!decide(&params, &instances));
+        assert!(!decide(&params, &[vec![]]));
+    }
+
+    #[test]
+    #[ignore = "verifies three proofs in-circuit, run with --ignored"]
+    fn test_aggregation_circuit() {
+        let params = ParamsKZG::<Bn256>::setup(k(), rng());
+        let circuit = AggregationCircuit::new(&params, snarks(&params), rng()).unwrap();
+        let instances = circuit.instances();
+
+        let k = rows::min_k(&circuit);
+        let prover = MockProver::run(k, &circuit, instances.clone()).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+
+        // the accumulator does not match the one of the proofs.
+        let mut other = instances.clone();
+        other[0][0] += Fr::one();
+        let prover = MockProver::run(k, &circuit, other).unwrap();
+        assert!(prover.verify().is_err());
+
+        // nor do the instances of the proofs, e.g. the digest of a call.
+        let mut other = instances;
+        other[0][ACCUMULATOR_LIMBS + 3] += Fr::one();
+        let prover = MockProver::run(k, &circuit, other).unwrap();
+        assert!(prover.verify().is_err());
+    }
+}
@@ -0,0 +1,144 @@
+//! Generation and native verification of the proofs that
+//! `AggregationCircuit` takes as its witness. They are proven over the
+//! Poseidon transcript of `snark-verifier`, whose hash is cheap to
+//! constrain, so that the aggregation circuit reads them in-circuit.
+
+use halo2_proofs::{
+    halo2curves::bn256::{Bn256, Fr, G1Affine},
+    plonk::{
+        create_proof, keygen_pk, keygen_vk, verify_proof, Circuit, Error, ProvingKey, VerifyingKey,
+    },
+    poly::{
+        kzg::{
+            commitment::{KZGCommitmentScheme, ParamsKZG},
+            multiopen::{ProverSHPLONK, VerifierSHPLONK},
+            strategy::{AccumulatorStrategy, SingleStrategy},
+        },
+        VerificationStrategy,
+    },
+    transcript::{TranscriptReadBuffer, TranscriptWriterBuffer},
+};
+use rand_core::RngCore;
+use snark_verifier::{
+    loader::native::NativeLoader,
+    system::halo2::{compile, transcript::halo2::ChallengeScalar, Config},
+    verifier::plonk::PlonkProtocol,
+};
+
+use crate::PoseidonTranscript;
+
+/// A proof of a circuit, along with its verifying key, its protocol, which
+/// the aggregation circuit verifies it by, and its instances.
+#[derive(Clone, Debug)]
+pub struct Snark {
+    pub vk: VerifyingKey<G1Affine>,
+    pub protocol: PlonkProtocol<G1Affine>,
+    pub instances: Vec<Vec<Fr>>,
+    pub proof: Vec<u8>,
+}
+
+impl Snark {
+    fn instances(&self) -> Vec<&[Fr]> {
+        self.instances.iter().map(Vec::as_slice).collect()
+    }
+
+    /// Runs the verifier over the proof on top of `strategy`.
+    fn verify_with<'params, S>(
+        &self,
+        params: &'params ParamsKZG<Bn256>,
+        strategy: S,
+    ) -> Result<S::Output, Error>
+    where
+        S: VerificationStrategy<
+            'params,
+            KZGCommitmentScheme<Bn256>,
+            VerifierSHPLONK<'params, Bn256>,
+        >,
+    {
+        let mut transcript = PoseidonTranscript::<NativeLoader, _>::init(&self.proof[..]);
+        verify_proof::<
+            KZGCommitmentScheme<Bn256>,
+            VerifierSHPLONK<'_, Bn256>,
+            ChallengeScalar<G1Affine>,
+            PoseidonTranscript<NativeLoader, &[u8]>,
+            S,
+        >(
+            params,
+            &self.vk,
+            strategy,
+            &[&self.instances()],
+            &mut transcript,
+        )
+    }
+
+    /// Verifies the proof on its own, with a pairing check of its own.
+    pub fn verify(&self, params: &ParamsKZG<Bn256>) -> bool {
+        self.verify_with(params, SingleStrategy::new(params))
+            .is_ok()
+    }
+}
+
+/// A proof of `circuit` with `pk`, whose instance columns are `instances`.
+pub fn prove_snark<C: Circuit<Fr>>(
+    params: &ParamsKZG<Bn256>,
+    pk: &ProvingKey<G1Affine>,
+    circuit: C,
+    instances: Vec<Vec<Fr>>,
+    rng: impl RngCore,
+) -> Result<Snark, Error> {
+    let protocol = compile(
+        params,
+        pk.get_vk(),
+        Config::kzg().with_num_instance(instances.iter().map(Vec::len).collect()),
+    );
+
+    let mut transcript = PoseidonTranscript::<NativeLoader, _>::init(vec![]);
+    let instance_slices = instances.iter().map(Vec::as_slice).collect::<Vec<_>>();
+    create_proof::<
+        KZGCommitmentScheme<Bn256>,
+        ProverSHPLONK<'_, Bn256>,
+        ChallengeScalar<G1Affine>,
+        _,
+        PoseidonTranscript<NativeLoader, Vec<u8>>,
+        C,
+    >(
+        params,
+        pk,
+        &[circuit],
+        &[&instance_slices],
+        rng,
+        &mut transcript,
+    )?;
+
+    Ok(Snark {
+        vk: pk.get_vk().clone(),
+        protocol,
+        instances,
+        proof: transcript.finalize(),
+    })
+}
+
+/// Generates the keys of `circuit` and a proof of it, see [`prove_snark`].
+pub fn gen_snark<C: Circuit<Fr>>(
+    params: &ParamsKZG<Bn256>,
+    circuit: C,
+    instances: Vec<Vec<Fr>>,
+    rng: impl RngCore,
+) -> Result<Snark, Error> {
+    let vk = keygen_vk(params, &circuit)?;
+    let pk = keygen_pk(params, vk, &circuit)?;
+    prove_snark(params, &pk, circuit, instances, rng)
+}
+
+/// Verifies all of `snarks` by accumulation: the opening of each proof is
+/// accumulated into a single MSM, which is checked by a single pairing check
+/// at the end, instead of one pairing check per proof.
+pub fn verify_snarks(params: &ParamsKZG<Bn256>, snarks: &[Snark]) -> bool {
+    snarks
+        .iter()
+        .try_fold(AccumulatorStrategy::new(params), |strategy, snark| {
+            snark.verify_with(params, strategy)
+        })
+        .map(VerificationStrategy::<_, VerifierSHPLONK<'_, Bn256>>::finalize)
+        .unwrap_or(false)
+}
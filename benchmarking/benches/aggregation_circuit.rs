@@ -0,0 +1,146 @@
+use aggregation_circuit::{decide, gen_snark, verify_snarks, AggregationCircuit, Snark};
+use benchmarking::{bench_prover, criterion, degrees, phases, report, rng, setup, Phase};
+use blake2f_circuit::Blake2fChip;
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use gadgets::{
+    batch::{BatchCircuit, BatchWitness},
+    rows, HashCircuitExt,
+};
+use halo2_proofs::{
+    halo2curves::bn256::{Bn256, Fr},
+    poly::kzg::commitment::ParamsKZG,
+};
+use ripemd160_circuit::{Ripemd160Chip, Ripemd160Witness};
+use sha2_256_circuit::{Sha2Chip, Sha2Witness};
+use std::marker::PhantomData;
+
+/// Counts the bytes the proofs allocate, for their peak memory.
+#[global_allocator]
+static ALLOCATOR: benchmarking::PeakAllocator = benchmarking::PeakAllocator;
+
+fn sha2_witness() -> Sha2Witness<Fr> {
+    Sha2Witness {
+        inputs: sha2_256_circuit::dev::INPUTS_OUTPUTS.0.clone(),
+        _marker: PhantomData,
+    }
+}
+
+fn ripemd160_witness() -> Ripemd160Witness<Fr> {
+    Ripemd160Witness {
+        inputs: ripemd160_circuit::dev::INPUTS_OUTPUTS.0.clone(),
+        _marker: PhantomData,
+    }
+}
+
+/// Smallest degree the three circuits whose proofs are aggregated fit in.
+fn min_k() -> u32 {
+    [
+        Sha2Chip::<Fr>::min_k(&sha2_witness()),
+        Blake2fChip::<Fr>::min_k(&blake2f_circuit::dev::INPUTS_OUTPUTS.0),
+        Ripemd160Chip::<Fr>::min_k(&ripemd160_witness()),
+    ]
+    .into_iter()
+    .max()
+    .expect("there are circuits")
+}
+
+/// The proof of the calls of `witness` by the circuit of `H` exposing its
+/// table, with the public inputs of the calls as its instances.
+fn snark<H: HashCircuitExt<Fr>>(params: &ParamsKZG<Bn256>, witness: H::Witness) -> Snark
+where
+    H::Witness: BatchWitness + Default,
+{
+    let instances = H::public_inputs(&witness);
+    gen_snark(
+        params,
+        BatchCircuit::<Fr, H>::new(vec![witness]),
+        instances,
+        rng(),
+    )
+    .expect("proof generation should not fail")
+}
+
+/// Only benchmarked over KZG, even with the `ipa` feature, as the proofs are
+/// verified by the accumulation of their KZG openings into a pairing check.
+/// The proof is the one of the [`AggregationCircuit`] of the proofs, at the
+/// degree it fits in, whose verification is that of its proof, benchmarked
+/// by [`bench_prover`], and the pairing check of its accumulator.
+fn bench_aggregation_circuit(c: &mut Criterion) {
+    const NAME: &str = "Aggregation Circuit";
+    // The circuit aggregates proofs, which need the parameters.
+    if !phases().iter().any(Phase::proves) {
+        return;
+    }
+
+    let mut group = c.benchmark_group(NAME);
+    let mut rows = vec![];
+    for degree in degrees(min_k()) {
+        let params = setup(degree);
+
+        // Prove the SHA2-256, BLAKE2F and RIPEMD-160 circuits with their test vectors,
+        // exposing their calls.
+        let snarks = vec![
+            snark::<Sha2Chip<Fr>>(&params, sha2_witness()),
+            snark::<Blake2fChip<Fr>>(&params, blake2f_circuit::dev::INPUTS_OUTPUTS.0.clone()),
+            snark::<Ripemd160Chip<Fr>>(&params, ripemd160_witness()),
+        ];
+
+        if phases().contains(&Phase::Verify) {
+            // The verification of the proofs one by one, with a pairing check each.
+            group.bench_function(BenchmarkId::new("verify each", degree), |b| {
+                b.iter(|| {
+                    for snark in snarks.iter() {
+                        assert!(snark.verify(&params), "failed to verify proof");
+                    }
+                })
+            });
+
+            // The verification of the proofs by accumulation, with a single pairing check.
+            group.bench_function(BenchmarkId::new("verify accumulated", degree), |b| {
+                b.iter(|| {
+                    assert!(
+                        verify_snarks(&params, &snarks),
+                        "failed to verify accumulated proofs"
+                    )
+                })
+            });
+        }
+
+        let circuit = || {
+            AggregationCircuit::new(&params, snarks.clone(), rng())
+                .expect("the proofs should verify")
+        };
+        let instances = circuit().instances();
+        if phases().contains(&Phase::Verify) {
+            // The pairing check of the accumulator of the aggregated proofs.
+            group.bench_function(BenchmarkId::new("decide aggregated", degree), |b| {
+                b.iter(|| {
+                    assert!(
+                        decide(&params, &instances),
+                        "failed to decide the accumulator"
+                    )
+                })
+            });
+        }
+
+        let aggregation_params = setup(rows::min_k(&circuit()));
+        rows.extend(bench_prover(
+            &mut group,
+            NAME,
+            &aggregation_params,
+            circuit,
+            &instances,
+        ));
+    }
+    group.finish();
+    if !rows.is_empty() {
+        println!("{NAME}\n{}", report(&rows));
+    }
+}
+
+criterion_group! {
+    name = benches;
+    config = criterion();
+    targets = bench_aggregation_circuit
+}
+criterion_main!(benches);
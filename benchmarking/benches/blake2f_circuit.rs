@@ -0,0 +1,144 @@
+use benchmarking::{
+    criterion, keygen, mock_verify, overhead::bench_overhead, phases, prove, run_full_bench, setup,
+    BenchCircuit, Multiopen, Phase,
+};
+use blake2f_circuit::{
+    dev::INPUTS_OUTPUTS, native, Blake2fChip, Blake2fTestCircuit, Blake2fWitness,
+};
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use gadgets::HashCircuitExt;
+use halo2_proofs::{arithmetic::FieldExt, dev::MockProver, halo2curves::bn256::Fr};
+use std::{env::var, marker::PhantomData};
+
+/// Counts the bytes the proofs allocate, for their peak memory.
+#[global_allocator]
+static ALLOCATOR: benchmarking::PeakAllocator = benchmarking::PeakAllocator;
+
+/// Rounds of the compressions of the rounds benchmark if the
+/// `BLAKE2F_ROUNDS` env var is unset, from a single round to the 12 of
+/// BLAKE2b and to the larger counts EIP-152 lets a call request for its gas.
+const ROUNDS: [u32; 4] = [1, 12, 100, 1000];
+
+/// The pathological rounds of a call, whose circuit of about 130 rows per
+/// round needs `k = 24`: only its degree is reported, unless the
+/// `BLAKE2F_ROUNDS` env var asks for it, e.g. `BLAKE2F_ROUNDS=100000
+/// PHASES=witness`, as its keys and proof take more memory than most hosts
+/// have.
+const LARGE_ROUNDS: u32 = 100_000;
+
+/// Rounds of the `BLAKE2F_ROUNDS` env var, a comma-separated list, or
+/// [`ROUNDS`] if unset.
+fn rounds() -> Vec<u32> {
+    var("BLAKE2F_ROUNDS")
+        .map(|rounds| {
+            rounds
+                .split(',')
+                .map(|count| count.trim().parse().ok())
+                .collect::<Option<_>>()
+                .expect("Cannot parse BLAKE2F_ROUNDS env var as rounds")
+        })
+        .unwrap_or_else(|_| ROUNDS.to_vec())
+}
+
+/// The test vector of the rounds benchmark, compressed `rounds` times.
+fn rounds_inputs(rounds: u32) -> Vec<Blake2fWitness> {
+    vec![Blake2fWitness {
+        rounds,
+        ..INPUTS_OUTPUTS.0[0].clone()
+    }]
+}
+
+/// The BLAKE2F circuit with some test vectors.
+struct Blake2fBench;
+
+impl BenchCircuit for Blake2fBench {
+    type Circuit<F: FieldExt> = Blake2fTestCircuit<F>;
+
+    const NAME: &'static str = "BLAKE2 Compression Function Circuit";
+
+    fn min_k(&self) -> u32 {
+        Blake2fChip::<Fr>::min_k(&INPUTS_OUTPUTS.0)
+    }
+
+    fn circuit<F: FieldExt>(&self) -> impl Fn() -> Blake2fTestCircuit<F> + Sync {
+        let (inputs, outputs) = INPUTS_OUTPUTS.clone();
+        move || Blake2fTestCircuit {
+            inputs: inputs.clone(),
+            outputs: outputs.clone(),
+            _marker: PhantomData,
+        }
+    }
+}
+
+fn bench_blake2f_circuit(c: &mut Criterion) {
+    let degrees = run_full_bench(c, &Blake2fBench);
+    let circuit = Blake2fBench.circuit();
+    bench_overhead(c, Blake2fBench::NAME, &degrees, circuit, &[], || {
+        for input in INPUTS_OUTPUTS.0.iter() {
+            black_box(native::blake2f(input));
+        }
+    });
+}
+
+/// Benchmarks the witness, its check and the proof of the compression of
+/// the test vector at every count of [`rounds`], and prints the degree the
+/// circuit needs at each, and at [`LARGE_ROUNDS`]. The throughput of the
+/// group is the number of rounds, so that criterion reports the time per
+/// round.
+fn bench_blake2f_rounds(c: &mut Criterion) {
+    let name = format!("{} rounds", Blake2fBench::NAME);
+    let mut group = c.benchmark_group(&name);
+    let phases = phases();
+    let mut lines = vec![
+        String::from("| rounds | k |"),
+        String::from("| --- | --- |"),
+    ];
+    let rounds = rounds();
+    for &rounds in rounds.iter() {
+        let inputs = rounds_inputs(rounds);
+        let k = Blake2fChip::<Fr>::min_k(&inputs);
+        // Without outputs, `check_digests` skips the check of the digests,
+        // whose test vectors are of 12 rounds.
+        let circuit = || Blake2fTestCircuit::<Fr> {
+            inputs: inputs.clone(),
+            outputs: vec![],
+            _marker: PhantomData,
+        };
+        group.throughput(Throughput::Elements(rounds as u64));
+
+        if phases.contains(&Phase::Witness) {
+            group.bench_function(BenchmarkId::new("witness", rounds), |b| {
+                b.iter(|| {
+                    MockProver::run(k, &circuit(), vec![])
+                        .expect("witness synthesis should not fail")
+                })
+            });
+        }
+        if phases.contains(&Phase::Mock) {
+            group.bench_function(BenchmarkId::new("mock", rounds), |b| {
+                b.iter(|| mock_verify(k, &circuit(), vec![]))
+            });
+        }
+        if phases.contains(&Phase::Proof) {
+            let params = setup(k);
+            let pk = keygen(&params, &circuit());
+            group.bench_function(BenchmarkId::new("proof", rounds), |b| {
+                b.iter(|| prove(&params, &pk, circuit(), &[], Multiopen::Shplonk))
+            });
+        }
+        lines.push(format!("| {rounds} | {k} |"));
+    }
+    if !rounds.contains(&LARGE_ROUNDS) {
+        let k = Blake2fChip::<Fr>::min_k(&rounds_inputs(LARGE_ROUNDS));
+        lines.push(format!("| {LARGE_ROUNDS} | {k} |"));
+    }
+    group.finish();
+    println!("{name}\n{}", lines.join("\n"));
+}
+
+criterion_group! {
+    name = benches;
+    config = criterion();
+    targets = bench_blake2f_circuit, bench_blake2f_rounds
+}
+criterion_main!(benches);
@@ -0,0 +1,46 @@
+use benchmarking::{criterion, run_full_bench, BenchCircuit};
+use criterion::{criterion_group, criterion_main, Criterion};
+use gadgets::rows;
+use halo2_proofs::{arithmetic::FieldExt, halo2curves::bn256::Fr};
+use merkle_circuit::{dev::MerkleTestCircuit, MerkleTree};
+use poseidon_circuit::PoseidonChip;
+use std::marker::PhantomData;
+
+/// Counts the bytes the proofs allocate, for their peak memory.
+#[global_allocator]
+static ALLOCATOR: benchmarking::PeakAllocator = benchmarking::PeakAllocator;
+
+/// The Merkle circuit with a path of a Poseidon tree of depth 3.
+struct MerkleBench;
+
+impl BenchCircuit for MerkleBench {
+    type Circuit<F: FieldExt> = MerkleTestCircuit<F, PoseidonChip<F, 3>>;
+
+    const NAME: &'static str = "Merkle Circuit";
+
+    fn min_k(&self) -> u32 {
+        rows::min_k(&self.circuit::<Fr>()())
+    }
+
+    /// The tree is hashed once, outside of the benchmarked phases.
+    fn circuit<F: FieldExt>(&self) -> impl Fn() -> Self::Circuit<F> + Sync {
+        let leaves = (1..=8).map(F::from).collect();
+        let tree = MerkleTree::new::<F, PoseidonChip<F, 3>>(leaves);
+        let paths = vec![tree.path(5)];
+        move || MerkleTestCircuit {
+            paths: paths.clone(),
+            _marker: PhantomData,
+        }
+    }
+}
+
+fn bench_merkle_circuit(c: &mut Criterion) {
+    run_full_bench(c, &MerkleBench);
+}
+
+criterion_group! {
+    name = benches;
+    config = criterion();
+    targets = bench_merkle_circuit
+}
+criterion_main!(benches);
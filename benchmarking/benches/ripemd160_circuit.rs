@@ -0,0 +1,78 @@
+use benchmarking::{
+    criterion, overhead::bench_overhead, run_full_bench, scaling::bench_hash_scaling, BenchCircuit,
+};
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use gadgets::HashCircuitExt;
+use halo2_proofs::{arithmetic::FieldExt, halo2curves::bn256::Fr};
+use ripemd160_circuit::{
+    dev::INPUTS_OUTPUTS, native, Ripemd160Chip, Ripemd160TestCircuit, Ripemd160Witness,
+};
+use std::marker::PhantomData;
+
+/// Counts the bytes the proofs allocate, for their peak memory.
+#[global_allocator]
+static ALLOCATOR: benchmarking::PeakAllocator = benchmarking::PeakAllocator;
+
+/// The RIPEMD-160 circuit with some test vectors.
+struct Ripemd160Bench;
+
+impl BenchCircuit for Ripemd160Bench {
+    type Circuit<F: FieldExt> = Ripemd160TestCircuit<F>;
+
+    const NAME: &'static str = "RIPEMD-160 Circuit";
+
+    fn min_k(&self) -> u32 {
+        Ripemd160Chip::<Fr>::min_k(&Ripemd160Witness {
+            inputs: INPUTS_OUTPUTS.0.clone(),
+            _marker: PhantomData,
+        })
+    }
+
+    fn circuit<F: FieldExt>(&self) -> impl Fn() -> Ripemd160TestCircuit<F> + Sync {
+        let (inputs, outputs) = INPUTS_OUTPUTS.clone();
+        move || Ripemd160TestCircuit {
+            inputs: inputs.clone(),
+            outputs: outputs.clone(),
+            _marker: PhantomData,
+        }
+    }
+}
+
+fn bench_ripemd160_circuit(c: &mut Criterion) {
+    let degrees = run_full_bench(c, &Ripemd160Bench);
+    let circuit = Ripemd160Bench.circuit();
+    bench_overhead(c, Ripemd160Bench::NAME, &degrees, circuit, &[], || {
+        for input in INPUTS_OUTPUTS.0.iter() {
+            black_box(native::ripemd160(input));
+        }
+    });
+}
+
+/// Benchmarks the circuit at growing inputs. The digests are not part of the
+/// witness, so the circuit is built without them.
+fn bench_ripemd160_scaling(c: &mut Criterion) {
+    bench_hash_scaling(
+        c,
+        Ripemd160Bench::NAME,
+        native::BLOCK_BYTES,
+        native::pad,
+        |inputs| {
+            Ripemd160Chip::<Fr>::min_k(&Ripemd160Witness {
+                inputs: inputs.to_vec(),
+                _marker: PhantomData,
+            })
+        },
+        |inputs| Ripemd160TestCircuit {
+            inputs,
+            outputs: vec![],
+            _marker: PhantomData,
+        },
+    );
+}
+
+criterion_group! {
+    name = benches;
+    config = criterion();
+    targets = bench_ripemd160_circuit, bench_ripemd160_scaling
+}
+criterion_main!(benches);
@@ -0,0 +1,82 @@
+use benchmarking::{
+    criterion, overhead::bench_overhead, run_full_bench, scaling::bench_hash_scaling, BenchCircuit,
+};
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use gadgets::HashCircuitExt;
+use halo2_proofs::{arithmetic::FieldExt, halo2curves::bn256::Fr};
+use sha2_256_circuit::{dev::INPUTS_OUTPUTS, native, Sha2Chip, Sha2TestCircuit, Sha2Witness};
+use std::marker::PhantomData;
+
+/// Counts the bytes the proofs allocate, for their peak memory.
+#[global_allocator]
+static ALLOCATOR: benchmarking::PeakAllocator = benchmarking::PeakAllocator;
+
+/// The SHA2-256 circuit with some test vectors.
+struct Sha2Bench;
+
+impl BenchCircuit for Sha2Bench {
+    type Circuit<F: FieldExt> = Sha2TestCircuit<F>;
+
+    const NAME: &'static str = "SHA2-256 Circuit";
+
+    fn min_k(&self) -> u32 {
+        Sha2Chip::<Fr>::min_k(&Sha2Witness {
+            inputs: INPUTS_OUTPUTS.0.clone(),
+            _marker: PhantomData,
+        })
+    }
+
+    fn circuit<F: FieldExt>(&self) -> impl Fn() -> Sha2TestCircuit<F> + Sync {
+        let (inputs, outputs) = INPUTS_OUTPUTS.clone();
+        move || Sha2TestCircuit {
+            inputs: inputs.clone(),
+            outputs: outputs.clone(),
+            _marker: PhantomData,
+        }
+    }
+}
+
+fn bench_sha2_256_circuit(c: &mut Criterion) {
+    let degrees = run_full_bench(c, &Sha2Bench);
+    bench_overhead(
+        c,
+        Sha2Bench::NAME,
+        &degrees,
+        Sha2Bench.circuit(),
+        &[],
+        || {
+            for input in INPUTS_OUTPUTS.0.iter() {
+                black_box(native::sha256(input));
+            }
+        },
+    );
+}
+
+/// Benchmarks the circuit at growing inputs. The digests are not part of the
+/// witness, so the circuit is built without them.
+fn bench_sha2_256_scaling(c: &mut Criterion) {
+    bench_hash_scaling(
+        c,
+        Sha2Bench::NAME,
+        native::BLOCK_BYTES,
+        native::pad,
+        |inputs| {
+            Sha2Chip::<Fr>::min_k(&Sha2Witness {
+                inputs: inputs.to_vec(),
+                _marker: PhantomData,
+            })
+        },
+        |inputs| Sha2TestCircuit {
+            inputs,
+            outputs: vec![],
+            _marker: PhantomData,
+        },
+    );
+}
+
+criterion_group! {
+    name = benches;
+    config = criterion();
+    targets = bench_sha2_256_circuit, bench_sha2_256_scaling
+}
+criterion_main!(benches);
@@ -0,0 +1,61 @@
+use benchmarking::{criterion, overhead::bench_overhead, run_full_bench, BenchCircuit};
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use gadgets::HashCircuitExt;
+use halo2_proofs::{arithmetic::FieldExt, halo2curves::bn256::Fr};
+use sha512_circuit::{
+    dev::{Sha512TestCircuit, INPUTS_OUTPUTS},
+    native, Sha512Chip, Sha512Witness,
+};
+use std::marker::PhantomData;
+
+/// Counts the bytes the proofs allocate, for their peak memory.
+#[global_allocator]
+static ALLOCATOR: benchmarking::PeakAllocator = benchmarking::PeakAllocator;
+
+/// The SHA-512 circuit with some test vectors.
+struct Sha512Bench;
+
+impl BenchCircuit for Sha512Bench {
+    type Circuit<F: FieldExt> = Sha512TestCircuit<F>;
+
+    const NAME: &'static str = "SHA-512 Circuit";
+
+    fn min_k(&self) -> u32 {
+        Sha512Chip::<Fr>::min_k(&Sha512Witness {
+            inputs: INPUTS_OUTPUTS.0.clone(),
+            _marker: PhantomData,
+        })
+    }
+
+    fn circuit<F: FieldExt>(&self) -> impl Fn() -> Sha512TestCircuit<F> + Sync {
+        let (inputs, outputs) = INPUTS_OUTPUTS.clone();
+        move || Sha512TestCircuit {
+            inputs: inputs.clone(),
+            outputs: outputs.clone(),
+            _marker: PhantomData,
+        }
+    }
+}
+
+fn bench_sha512_circuit(c: &mut Criterion) {
+    let degrees = run_full_bench(c, &Sha512Bench);
+    bench_overhead(
+        c,
+        Sha512Bench::NAME,
+        &degrees,
+        Sha512Bench.circuit(),
+        &[],
+        || {
+            for input in INPUTS_OUTPUTS.0.iter() {
+                black_box(native::sha512(input));
+            }
+        },
+    );
+}
+
+criterion_group! {
+    name = benches;
+    config = criterion();
+    targets = bench_sha512_circuit
+}
+criterion_main!(benches);
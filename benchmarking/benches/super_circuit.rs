@@ -0,0 +1,268 @@
+use aggregation_circuit::{decide, prove_snark, AggregationCircuit, Snark};
+use benchmarking::{
+    bench_circuit, criterion, degrees, elapsed, keygen, phases, prove, rng, run_full_bench, setup,
+    strategy::{strategy_report, Strategy, StrategyCost},
+    verify,
+    workload::{workload_seed, WorkloadGenerator},
+    BenchCircuit, Multiopen, Phase,
+};
+use blake2f_circuit::Blake2fChip;
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use gadgets::{
+    batch::{BatchCircuit, BatchWitness},
+    rows, HashCircuitExt,
+};
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    halo2curves::bn256::{Bn256, Fr, G1Affine},
+    plonk::{Circuit, ProvingKey},
+    poly::kzg::commitment::ParamsKZG,
+};
+use precompile_super_circuit::{Precompile, PrecompileCall, SuperCircuit};
+use ripemd160_circuit::{Ripemd160Chip, Ripemd160Witness};
+use sha2_256_circuit::{Sha2Chip, Sha2Witness};
+use std::marker::PhantomData;
+
+/// Counts the bytes the proofs allocate, for their peak memory.
+#[global_allocator]
+static ALLOCATOR: benchmarking::PeakAllocator = benchmarking::PeakAllocator;
+
+/// The precompile super-circuit with the test vectors of its SHA2-256,
+/// BLAKE2F and RIPEMD-160 circuits, and the digest rows of a call of the
+/// caller to each, proven with the public inputs of the calls.
+struct SuperCircuitBench;
+
+impl BenchCircuit for SuperCircuitBench {
+    type Circuit<F: FieldExt> = SuperCircuit<F>;
+
+    const NAME: &'static str = "Precompile Super Circuit";
+
+    fn min_k(&self) -> u32 {
+        self.circuit::<Fr>()().min_k()
+    }
+
+    fn circuit<F: FieldExt>(&self) -> impl Fn() -> SuperCircuit<F> + Sync {
+        // The first call of every precompile.
+        let calls = [
+            (
+                Precompile::Sha2,
+                sha2_256_circuit::dev::INPUTS_OUTPUTS.0[0].clone(),
+            ),
+            (
+                Precompile::Blake2f,
+                blake2f_circuit::dev::INPUTS_OUTPUTS.0[0].to_input(),
+            ),
+            (
+                Precompile::Ripemd160,
+                ripemd160_circuit::dev::INPUTS_OUTPUTS.0[0].clone(),
+            ),
+        ]
+        .iter()
+        .map(|(precompile, input)| PrecompileCall::new(*precompile, 1, input))
+        .collect::<Vec<_>>();
+        move || SuperCircuit {
+            sha2_inputs: sha2_256_circuit::dev::INPUTS_OUTPUTS.0.clone(),
+            blake2f_inputs: blake2f_circuit::dev::INPUTS_OUTPUTS.0.clone(),
+            ripemd160_inputs: ripemd160_circuit::dev::INPUTS_OUTPUTS.0.clone(),
+            calls: calls.clone(),
+            _marker: PhantomData,
+        }
+    }
+
+    fn instances<F: FieldExt>(&self) -> Vec<Vec<F>> {
+        self.circuit::<F>()().public_inputs()
+    }
+}
+
+fn bench_super_circuit(c: &mut Criterion) {
+    run_full_bench(c, &SuperCircuitBench);
+}
+
+/// Number of the random workloads of [`bench_random_workloads`].
+const RANDOM_WORKLOADS: usize = 4;
+
+/// Most rows of the super-circuit of a random workload, which is far more
+/// than the bounds of the [`WorkloadGenerator`] need.
+const MAX_K: u32 = 24;
+
+/// Benchmarks every phase of the super-circuit of [`RANDOM_WORKLOADS`]
+/// random workloads of the seed of [`workload_seed`], each as a group, and
+/// prints them along with their seed.
+fn bench_random_workloads(c: &mut Criterion) {
+    let mut generator = WorkloadGenerator::from_env();
+    for i in 0..RANDOM_WORKLOADS {
+        let name = format!("{} random workload {i}", SuperCircuitBench::NAME);
+        let workload = generator.workload();
+        println!("{name} of WORKLOAD_SEED={}: {workload}", workload_seed());
+        let circuit = workload
+            .builder(MAX_K)
+            .build::<Fr>()
+            .expect("the workload should fit the super-circuit");
+        let degrees = degrees(circuit.min_k());
+        let instances = circuit.public_inputs();
+        bench_circuit(c, &name, &degrees, || circuit.clone(), instances);
+    }
+}
+
+/// The circuit of the calls of `witness` by the chip `H` alone, exposing
+/// its table, and the public inputs of the calls, its instances.
+fn exposed<H: HashCircuitExt<Fr>>(witness: H::Witness) -> (BatchCircuit<Fr, H>, Vec<Vec<Fr>>)
+where
+    H::Witness: BatchWitness,
+{
+    let instances = H::public_inputs(&witness);
+    (BatchCircuit::new(vec![witness]), instances)
+}
+
+/// A SHPLONK proof of `circuit` with `pk` for `instances`, to be aggregated.
+fn snark<C: Circuit<Fr>>(
+    params: &ParamsKZG<Bn256>,
+    pk: &ProvingKey<G1Affine>,
+    (circuit, instances): (C, Vec<Vec<Fr>>),
+) -> Snark {
+    prove_snark(params, pk, circuit, instances, rng()).expect("proof generation should not fail")
+}
+
+/// Benchmarks the proof and the verification of the workloads of the
+/// super-circuit by every [`Strategy`], with the keys generated beforehand,
+/// and prints a table comparing them at every degree. Both strategies prove
+/// the calls with their public inputs: the super-circuit those of its
+/// sub-circuits, and the separate proofs those of the circuits of the chips
+/// alone, exposing their tables.
+///
+/// The proofs of [`Strategy::SeparateProofs`] are aggregated by the proof of
+/// the `AggregationCircuit`, at the degree it fits in, whose verification
+/// also runs the pairing check of its accumulator.
+fn bench_strategies(c: &mut Criterion) {
+    let name = format!("{} strategies", SuperCircuitBench::NAME);
+    let phases = phases();
+    if !phases.contains(&Phase::Proof) && !phases.contains(&Phase::Verify) {
+        return;
+    }
+
+    let mut group = c.benchmark_group(&name);
+    let mut costs = vec![];
+    for degree in degrees(SuperCircuitBench.min_k()) {
+        let params = setup(degree);
+
+        let super_circuit = SuperCircuitBench.circuit::<Fr>();
+        let pk = keygen(&params, &super_circuit());
+        let super_instances = SuperCircuitBench.instances::<Fr>();
+        let prove_super = || {
+            prove(
+                &params,
+                &pk,
+                super_circuit(),
+                &super_instances,
+                Multiopen::Shplonk,
+            )
+        };
+        let proof = prove_super();
+        let verify_super = || {
+            assert!(
+                verify(
+                    &params,
+                    pk.get_vk(),
+                    &proof,
+                    &super_instances,
+                    Multiopen::Shplonk
+                ),
+                "failed to verify super-circuit proof"
+            )
+        };
+
+        let sha2 = || {
+            exposed::<Sha2Chip<Fr>>(Sha2Witness {
+                inputs: sha2_256_circuit::dev::INPUTS_OUTPUTS.0.clone(),
+                _marker: PhantomData,
+            })
+        };
+        let blake2f = || exposed::<Blake2fChip<Fr>>(blake2f_circuit::dev::INPUTS_OUTPUTS.0.clone());
+        let ripemd160 = || {
+            exposed::<Ripemd160Chip<Fr>>(Ripemd160Witness {
+                inputs: ripemd160_circuit::dev::INPUTS_OUTPUTS.0.clone(),
+                _marker: PhantomData,
+            })
+        };
+        let pks = (
+            keygen(&params, &sha2().0),
+            keygen(&params, &blake2f().0),
+            keygen(&params, &ripemd160().0),
+        );
+        let prove_each = || {
+            vec![
+                snark(&params, &pks.0, sha2()),
+                snark(&params, &pks.1, blake2f()),
+                snark(&params, &pks.2, ripemd160()),
+            ]
+        };
+        let aggregation_circuit = |snarks| {
+            AggregationCircuit::new(&params, snarks, rng()).expect("the proofs should verify")
+        };
+        let circuit = aggregation_circuit(prove_each());
+        let aggregation_params = setup(rows::min_k(&circuit));
+        let aggregation_pk = keygen(&aggregation_params, &circuit);
+        let instances = circuit.instances();
+        let prove_separate = || {
+            let circuit = aggregation_circuit(prove_each());
+            let instances = circuit.instances();
+            prove(
+                &aggregation_params,
+                &aggregation_pk,
+                circuit,
+                &instances,
+                Multiopen::Shplonk,
+            )
+        };
+        let aggregation_proof = prove_separate();
+        let verify_separate = || {
+            assert!(
+                verify(
+                    &aggregation_params,
+                    aggregation_pk.get_vk(),
+                    &aggregation_proof,
+                    &instances,
+                    Multiopen::Shplonk
+                ) && decide(&params, &instances),
+                "failed to verify the aggregated proofs"
+            )
+        };
+
+        for strategy in Strategy::ALL {
+            let (prove, verify, proof): (&dyn Fn() -> Vec<u8>, &dyn Fn(), _) = match strategy {
+                Strategy::SuperCircuit => (&prove_super, &verify_super, &proof),
+                Strategy::SeparateProofs => (&prove_separate, &verify_separate, &aggregation_proof),
+            };
+            if phases.contains(&Phase::Proof) {
+                group.bench_function(BenchmarkId::new(format!("proof {strategy}"), degree), |b| {
+                    b.iter(prove)
+                });
+            }
+            if phases.contains(&Phase::Verify) {
+                group.bench_function(
+                    BenchmarkId::new(format!("verify {strategy}"), degree),
+                    |b| b.iter(verify),
+                );
+            }
+
+            let cost = StrategyCost {
+                degree,
+                strategy,
+                prover: elapsed(prove),
+                proof_bytes: proof.len(),
+                verify: elapsed(verify),
+            };
+            println!("{name} {cost}");
+            costs.push(cost);
+        }
+    }
+    group.finish();
+    println!("{name}\n{}", strategy_report(&costs));
+}
+
+criterion_group! {
+    name = benches;
+    config = criterion();
+    targets = bench_super_circuit, bench_strategies, bench_random_workloads
+}
+criterion_main!(benches);
@@ -0,0 +1,164 @@
+//! Cost of serializing the artifacts of a prover, i.e. the parameters of the
+//! commitment scheme and the proving and verifying keys of a circuit, as
+//! deployments load them from disk when they start and the time it takes
+//! delays their first proof.
+//!
+//! The artifacts are written to and read from memory in the format of
+//! [`SERDE_FORMAT`], so that the timings measure their encoding and decoding
+//! rather than the disk they would be stored on.
+
+use std::{fmt, time::Duration};
+
+use criterion::{measurement::WallTime, BenchmarkGroup, BenchmarkId};
+use halo2_proofs::{
+    arithmetic::CurveAffine,
+    plonk::{Circuit, ProvingKey, VerifyingKey},
+    poly::commitment::Params,
+};
+pub use prover::SERDE_FORMAT;
+
+use crate::elapsed;
+
+/// Artifact of a prover.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Artifact {
+    Params,
+    VerifyingKey,
+    ProvingKey,
+}
+
+impl Artifact {
+    pub const ALL: [Self; 3] = [Self::Params, Self::VerifyingKey, Self::ProvingKey];
+}
+
+impl fmt::Display for Artifact {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Params => write!(f, "params"),
+            Self::VerifyingKey => write!(f, "vk"),
+            Self::ProvingKey => write!(f, "pk"),
+        }
+    }
+}
+
+/// Size of an artifact serialized at a degree, and the times of its
+/// serialization and deserialization.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Serialization {
+    pub degree: u32,
+    pub artifact: Artifact,
+    pub bytes: usize,
+    pub write: Duration,
+    pub read: Duration,
+}
+
+impl fmt::Display for Serialization {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let ms = |time: Duration| time.as_secs_f64() * 1e3;
+        write!(
+            f,
+            "with degree = {}: {} of {} bytes written in {:.3} ms, read in {:.3} ms",
+            self.degree,
+            self.artifact,
+            self.bytes,
+            ms(self.write),
+            ms(self.read)
+        )
+    }
+}
+
+/// Serializes `artifact` of `params` and `pk`.
+fn write<C: CurveAffine, P: for<'params> Params<'params, C>>(
+    artifact: Artifact,
+    params: &P,
+    pk: &ProvingKey<C>,
+) -> Vec<u8> {
+    let mut bytes = vec![];
+    match artifact {
+        Artifact::Params => params.write(&mut bytes),
+        Artifact::VerifyingKey => pk.get_vk().write(&mut bytes, SERDE_FORMAT),
+        Artifact::ProvingKey => pk.write(&mut bytes, SERDE_FORMAT),
+    }
+    .expect("writing to a vector should not fail");
+    bytes
+}
+
+/// Deserializes `artifact` of the circuit `Concrete` from `bytes`.
+fn read<C: CurveAffine, P: for<'params> Params<'params, C>, Concrete: Circuit<C::ScalarExt>>(
+    artifact: Artifact,
+    mut bytes: &[u8],
+) {
+    let read = match artifact {
+        Artifact::Params => P::read(&mut bytes).map(drop),
+        Artifact::VerifyingKey => {
+            VerifyingKey::<C>::read::<_, Concrete>(&mut bytes, SERDE_FORMAT).map(drop)
+        }
+        Artifact::ProvingKey => {
+            ProvingKey::<C>::read::<_, Concrete>(&mut bytes, SERDE_FORMAT).map(drop)
+        }
+    };
+    read.expect("failed to read bench artifact");
+}
+
+/// Benchmarks the serialization and deserialization of every [`Artifact`]
+/// of `params` and `pk`, the key of the circuit `Concrete`, within `group`,
+/// and prints and returns their sizes and times.
+pub fn bench_artifacts<
+    C: CurveAffine,
+    P: for<'params> Params<'params, C>,
+    Concrete: Circuit<C::ScalarExt>,
+>(
+    group: &mut BenchmarkGroup<'_, WallTime>,
+    name: &str,
+    params: &P,
+    pk: &ProvingKey<C>,
+) -> Vec<Serialization> {
+    let degree = params.k();
+    Artifact::ALL
+        .into_iter()
+        .map(|artifact| {
+            group.bench_function(BenchmarkId::new(format!("write {artifact}"), degree), |b| {
+                b.iter(|| write(artifact, params, pk))
+            });
+            let bytes = write(artifact, params, pk);
+            group.bench_function(BenchmarkId::new(format!("read {artifact}"), degree), |b| {
+                b.iter(|| read::<C, P, Concrete>(artifact, &bytes))
+            });
+
+            let serialization = Serialization {
+                degree,
+                artifact,
+                bytes: bytes.len(),
+                write: elapsed(|| write(artifact, params, pk)),
+                read: elapsed(|| read::<C, P, Concrete>(artifact, &bytes)),
+            };
+            println!("{name} {serialization}");
+            serialization
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{
+        halo2curves::bn256::{Bn256, Fr, G1Affine},
+        poly::kzg::commitment::ParamsKZG,
+    };
+    use sha2_256_circuit::Sha2TestCircuit;
+
+    use super::*;
+    use crate::{keygen, setup, sha2_test_circuit, vk_bytes};
+
+    #[test]
+    fn test_artifacts() {
+        let (k, circuit) = sha2_test_circuit();
+        let params = setup(k);
+        let pk = keygen(&params, &circuit());
+        let vk = write(Artifact::VerifyingKey, &params, &pk);
+        assert_eq!(vk.len(), vk_bytes(pk.get_vk()));
+        for artifact in Artifact::ALL {
+            let bytes = write(artifact, &params, &pk);
+            read::<G1Affine, ParamsKZG<Bn256>, Sha2TestCircuit<Fr>>(artifact, &bytes);
+        }
+    }
+}
@@ -0,0 +1,226 @@
+//! Cost of verifying proofs of a circuit in batches, as the zkEVM verifies
+//! many proofs at once: the proofs of a batch are accumulated by the
+//! `AccumulatorStrategy` of KZG into a single pair of MSMs, checked by a
+//! single pairing, rather than by a pairing check each.
+//!
+//! The proofs of a batch are copies of a single proof, as the cost of the
+//! verification does not depend on the witness, proven with the transcript
+//! of [`crate::prove`], [`Transcript::DEFAULT`]. The sizes of the batches are
+//! those of the `BATCH_SIZES` env var, a comma-separated list, or
+//! [`BATCH_SIZES`] if unset.
+
+use std::{env::var, fmt, time::Duration};
+
+use criterion::{measurement::WallTime, BenchmarkGroup, BenchmarkId};
+use halo2_proofs::{
+    halo2curves::bn256::{Bn256, Fr, G1Affine},
+    plonk::{verify_proof, VerifyingKey},
+    poly::{
+        commitment::{Params, ParamsProver, Verifier},
+        kzg::{
+            commitment::{KZGCommitmentScheme, ParamsKZG},
+            multiopen::{VerifierGWC, VerifierSHPLONK},
+            strategy::AccumulatorStrategy,
+        },
+        VerificationStrategy,
+    },
+    transcript::{Blake2bRead, Challenge255, Keccak256Read, TranscriptReadBuffer},
+};
+use tracing::info_span;
+
+use crate::{elapsed, verify, Multiopen, Transcript};
+
+/// Numbers of proofs of the batches if the `BATCH_SIZES` env var is unset.
+pub const BATCH_SIZES: [usize; 2] = [4, 16];
+
+/// Sizes of the batches of the `BATCH_SIZES` env var, or [`BATCH_SIZES`] if
+/// unset.
+pub fn batch_sizes() -> Vec<usize> {
+    var("BATCH_SIZES")
+        .map(|sizes| parse_batch_sizes(&sizes).expect("Cannot parse BATCH_SIZES env var"))
+        .unwrap_or_else(|_| BATCH_SIZES.to_vec())
+}
+
+/// Parses a comma-separated list of non-zero batch sizes.
+fn parse_batch_sizes(sizes: &str) -> Option<Vec<usize>> {
+    sizes
+        .split(',')
+        .map(|size| size.trim().parse().ok().filter(|&size| size > 0))
+        .collect()
+}
+
+/// Verifies all of `proofs`, each with the instances `instances` and
+/// proven with [`Transcript::DEFAULT`], by their accumulation into a single
+/// check.
+pub fn verify_batch(
+    params: &ParamsKZG<Bn256>,
+    vk: &VerifyingKey<G1Affine>,
+    proofs: &[Vec<u8>],
+    instances: &[Vec<Fr>],
+    multiopen: Multiopen,
+) -> bool {
+    type Blake2b<'proof> = Blake2bRead<&'proof [u8], G1Affine, Challenge255<G1Affine>>;
+    type Keccak256<'proof> = Keccak256Read<&'proof [u8], G1Affine, Challenge255<G1Affine>>;
+    let _span = info_span!("verify batch", proofs = proofs.len(), %multiopen).entered();
+    match (multiopen, Transcript::DEFAULT) {
+        (Multiopen::Shplonk, Transcript::Blake2b) => {
+            verify_batch_with::<VerifierSHPLONK<'_, Bn256>, Blake2b>(params, vk, proofs, instances)
+        }
+        (Multiopen::Shplonk, Transcript::Keccak256) => verify_batch_with::<
+            VerifierSHPLONK<'_, Bn256>,
+            Keccak256,
+        >(params, vk, proofs, instances),
+        (Multiopen::Gwc, Transcript::Blake2b) => {
+            verify_batch_with::<VerifierGWC<'_, Bn256>, Blake2b>(params, vk, proofs, instances)
+        }
+        (Multiopen::Gwc, Transcript::Keccak256) => {
+            verify_batch_with::<VerifierGWC<'_, Bn256>, Keccak256>(params, vk, proofs, instances)
+        }
+    }
+}
+
+fn verify_batch_with<
+    'params,
+    'proofs,
+    V: Verifier<'params, KZGCommitmentScheme<Bn256>>,
+    T: TranscriptReadBuffer<&'proofs [u8], G1Affine, Challenge255<G1Affine>>,
+>(
+    params: &'params ParamsKZG<Bn256>,
+    vk: &VerifyingKey<G1Affine>,
+    proofs: &'proofs [Vec<u8>],
+    instances: &[Vec<Fr>],
+) -> bool
+where
+    AccumulatorStrategy<'params, Bn256>: VerificationStrategy<
+        'params,
+        KZGCommitmentScheme<Bn256>,
+        V,
+        Output = AccumulatorStrategy<'params, Bn256>,
+    >,
+{
+    let instances = instances.iter().map(Vec::as_slice).collect::<Vec<_>>();
+    let mut strategy = AccumulatorStrategy::new(params);
+    for proof in proofs {
+        let mut transcript = T::init(proof.as_slice());
+        strategy = match verify_proof::<
+            KZGCommitmentScheme<Bn256>,
+            V,
+            Challenge255<G1Affine>,
+            T,
+            AccumulatorStrategy<'params, Bn256>,
+        >(
+            params.verifier_params(),
+            vk,
+            strategy,
+            &[&instances],
+            &mut transcript,
+        ) {
+            Ok(strategy) => strategy,
+            Err(_) => return false,
+        };
+    }
+    VerificationStrategy::<'params, KZGCommitmentScheme<Bn256>, V>::finalize(strategy)
+}
+
+/// Time of the verification of a batch of proofs, against that of the
+/// verification of one of its proofs on its own.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BatchVerification {
+    pub degree: u32,
+    pub multiopen: Multiopen,
+    pub proofs: usize,
+    pub single: Duration,
+    pub batch: Duration,
+}
+
+impl BatchVerification {
+    /// Time of the verification of the batch per proof.
+    pub fn amortized(&self) -> Duration {
+        self.batch / self.proofs as u32
+    }
+}
+
+impl fmt::Display for BatchVerification {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let ms = |time: Duration| time.as_secs_f64() * 1e3;
+        write!(
+            f,
+            "with degree = {} and {}: {} proofs verified in {:.3} ms per proof, against {:.3} ms alone",
+            self.degree,
+            self.multiopen,
+            self.proofs,
+            ms(self.amortized()),
+            ms(self.single)
+        )
+    }
+}
+
+/// Benchmarks the verification of batches of copies of `proof`, of every size
+/// of [`batch_sizes`], within `group`, and prints and returns their time per
+/// proof.
+pub fn bench_batch_verify(
+    group: &mut BenchmarkGroup<'_, WallTime>,
+    name: &str,
+    params: &ParamsKZG<Bn256>,
+    vk: &VerifyingKey<G1Affine>,
+    proof: &[u8],
+    instances: &[Vec<Fr>],
+    multiopen: Multiopen,
+) -> Vec<BatchVerification> {
+    let degree = params.k();
+    let single = elapsed(|| verify(params, vk, proof, instances, multiopen));
+    batch_sizes()
+        .into_iter()
+        .map(|size| {
+            let proofs = vec![proof.to_vec(); size];
+            let verify = || {
+                assert!(
+                    verify_batch(params, vk, &proofs, instances, multiopen),
+                    "failed to verify batch of bench proofs"
+                )
+            };
+            group.bench_function(
+                BenchmarkId::new(format!("verify {size} batched {multiopen}"), degree),
+                |b| b.iter(verify),
+            );
+            let batch = BatchVerification {
+                degree,
+                multiopen,
+                proofs: size,
+                single,
+                batch: elapsed(verify),
+            };
+            println!("{name} {batch}");
+            batch
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{keygen, prove, setup, sha2_test_circuit};
+
+    #[test]
+    fn test_verify_batch() {
+        assert_eq!(parse_batch_sizes("1, 64"), Some(vec![1, 64]));
+        assert_eq!(parse_batch_sizes("0"), None);
+
+        let (k, circuit) = sha2_test_circuit();
+        let params = setup(k);
+        let pk = keygen(&params, &circuit());
+        for multiopen in Multiopen::ALL {
+            let proof = prove(&params, &pk, circuit(), &[], multiopen);
+            let proofs = vec![proof; 3];
+            assert!(verify_batch(&params, pk.get_vk(), &proofs, &[], multiopen));
+            // a proof with the instances of another circuit fails the batch.
+            assert!(!verify_batch(
+                &params,
+                pk.get_vk(),
+                &proofs,
+                &[vec![Fr::from(1)]],
+                multiopen
+            ));
+        }
+    }
+}
@@ -0,0 +1,86 @@
+//! Compares the results of the latest run of the benchmarks against those of
+//! a baseline saved by criterion, prints the change of every benchmark of
+//! both as a markdown table, and exits with an error if any regressed by
+//! more than the threshold, in percent, see [`benchmarking::history`]:
+//!
+//! ```text
+//! cargo run --bin bench_regressions -- <baseline> [--threshold <percent>] [--current <baseline>]
+//! ```
+
+use std::{env::args, process::exit};
+
+use benchmarking::history::{
+    compare, criterion_dir, delta_report, read_results, CURRENT, THRESHOLD,
+};
+
+const USAGE: &str =
+    "usage: bench_regressions <baseline> [--threshold <percent>] [--current <baseline>]";
+
+/// Baselines to compare and threshold of the regressions.
+struct Args {
+    previous: String,
+    current: String,
+    threshold: f64,
+}
+
+fn parse_args(mut args: impl Iterator<Item = String>) -> Option<Args> {
+    let mut previous = None;
+    let mut current = CURRENT.to_string();
+    let mut threshold = THRESHOLD;
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--threshold" => threshold = args.next()?.parse().ok()?,
+            "--current" => current = args.next()?,
+            _ if previous.is_none() && !arg.starts_with('-') => previous = Some(arg),
+            _ => return None,
+        }
+    }
+    Some(Args {
+        previous: previous?,
+        current,
+        threshold,
+    })
+}
+
+fn main() {
+    let Some(args) = parse_args(args().skip(1)) else {
+        eprintln!("{USAGE}");
+        exit(2);
+    };
+
+    let dir = criterion_dir();
+    let read = |baseline: &str| {
+        read_results(&dir, baseline).unwrap_or_else(|err| {
+            eprintln!("Cannot read the results in {}: {err}", dir.display());
+            exit(2);
+        })
+    };
+    let deltas = compare(&read(&args.previous), &read(&args.current));
+    if deltas.is_empty() {
+        eprintln!(
+            "No benchmark of {} in both {} and {}",
+            dir.display(),
+            args.previous,
+            args.current
+        );
+        exit(2);
+    }
+    println!("{}", delta_report(&deltas));
+
+    let regressions = deltas
+        .iter()
+        .filter(|delta| delta.regressed(args.threshold))
+        .collect::<Vec<_>>();
+    if !regressions.is_empty() {
+        println!();
+        for delta in &regressions {
+            println!(
+                "{} regressed by {:.1}% since {}",
+                delta.key,
+                delta.change(),
+                args.previous
+            );
+        }
+        exit(1);
+    }
+}
@@ -0,0 +1,59 @@
+//! Converts the SRS of a `.ptau` file, or halo2 parameters of a larger
+//! degree, to the halo2 parameters of a degree, see [`prover::srs`],
+//! so that the benchmarks and the provers load those of a production setup
+//! without computing their Lagrange basis:
+//!
+//! ```text
+//! cargo run --release --bin convert_srs -- <input> <degree> <output>
+//! ```
+
+use std::{env::args, fs::File, io::BufWriter, path::PathBuf, process::exit};
+
+use halo2_proofs::poly::commitment::Params;
+use prover::srs::read_srs;
+
+const USAGE: &str = "usage: convert_srs <input> <degree> <output>";
+
+/// Files to convert from and to, and degree of the parameters.
+struct Args {
+    input: PathBuf,
+    degree: u32,
+    output: PathBuf,
+}
+
+fn parse_args(args: &[String]) -> Option<Args> {
+    let [input, degree, output] = args else {
+        return None;
+    };
+    Some(Args {
+        input: input.into(),
+        degree: degree.parse().ok()?,
+        output: output.into(),
+    })
+}
+
+fn main() {
+    let Some(args) = parse_args(&args().skip(1).collect::<Vec<_>>()) else {
+        eprintln!("{USAGE}");
+        exit(2);
+    };
+
+    let params = read_srs(&args.input, args.degree).unwrap_or_else(|err| {
+        eprintln!("Cannot read the SRS in {}: {err}", args.input.display());
+        exit(2);
+    });
+    File::create(&args.output)
+        .and_then(|file| params.write(&mut BufWriter::new(file)))
+        .unwrap_or_else(|err| {
+            eprintln!(
+                "Cannot write the params to {}: {err}",
+                args.output.display()
+            );
+            exit(2);
+        });
+    println!(
+        "Wrote the params of degree {} to {}",
+        args.degree,
+        args.output.display()
+    );
+}
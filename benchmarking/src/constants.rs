@@ -1,3 +0,0 @@
-pub const SETUP_PREFIX: &str = "[Setup generation]";
-pub const PROOFGEN_PREFIX: &str = "[Proof generation]";
-pub const PROOFVER_PREFIX: &str = "[Proof verification]";
@@ -0,0 +1,101 @@
+//! Analytic cost of the circuits, without their keys nor a proof: the
+//! [`CircuitStats`] of their configuration, i.e. their columns, gates,
+//! lookups and degree, the rows their regions use, measured by
+//! [`RowUsage`] as their chips assign them, and the size of their proof,
+//! estimated by the `CircuitCost` of halo2 from their layout.
+//!
+//! It is the `cost` phase of the benchmarks, printed before the parameters
+//! are set up, so that `PHASES=cost` checks the cost of a change to the
+//! constraints in the time of a witness synthesis.
+
+use std::fmt;
+
+use gadgets::{rows::RowUsage, CircuitStats};
+use halo2_proofs::{
+    arithmetic::FieldExt, dev::CircuitCost, halo2curves::group::prime::PrimeGroup, plonk::Circuit,
+};
+use tracing::debug;
+
+/// Configuration and proof size of a circuit, see [`estimate`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CostEstimate {
+    pub degree: u32,
+    pub stats: CircuitStats,
+    /// Number of the regions of the witness, and of the rows they use.
+    pub regions: usize,
+    pub rows: usize,
+    /// Estimated size of a proof of a single instance of the circuit.
+    pub proof_bytes: usize,
+    /// Estimated size each further instance of the circuit adds to a proof.
+    pub marginal_proof_bytes: usize,
+}
+
+impl fmt::Display for CostEstimate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "with degree = {}: {} advice, {} fixed and {} instance columns, {} lookups, \
+             degree {}, {} regions of {} rows, estimated proof of {} bytes, {} bytes per \
+             further instance",
+            self.degree,
+            self.stats.advice_columns,
+            self.stats.fixed_columns,
+            self.stats.instance_columns,
+            self.stats.lookups,
+            self.stats.degree,
+            self.regions,
+            self.rows,
+            self.proof_bytes,
+            self.marginal_proof_bytes
+        )
+    }
+}
+
+/// Estimates the cost of `circuit` at `degree` over the group `G` of the
+/// commitments, and prints it as the group of the benchmarks `name`, with
+/// the rows of every region of the circuit at the `debug` level.
+///
+/// Panics if the circuit does not fit in `2^degree` rows.
+pub fn estimate<G: PrimeGroup, C: Circuit<G::Scalar>>(
+    name: &str,
+    degree: u32,
+    circuit: &C,
+) -> CostEstimate
+where
+    G::Scalar: FieldExt,
+{
+    let cost = CircuitCost::<G, C>::measure(degree, circuit);
+    let stats = CircuitStats::of(C::configure);
+    let usage = RowUsage::measure(degree, circuit, vec![vec![]; stats.instance_columns])
+        .expect("the circuit fits in the degree");
+    let estimate = CostEstimate {
+        degree,
+        stats,
+        regions: usage.regions.len(),
+        rows: usage.total(),
+        proof_bytes: cost.proof_size(1).into(),
+        marginal_proof_bytes: cost.marginal_proof_size().into(),
+    };
+    println!("{name} {estimate}");
+    debug!("rows of the regions of {name}:\n{}", usage.report());
+    estimate
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::halo2curves::bn256::G1;
+
+    use super::*;
+
+    #[test]
+    fn test_estimate() {
+        let (k, circuit) = crate::sha2_test_circuit();
+        let estimate = estimate::<G1, _>("SHA2-256 Circuit", k, &circuit());
+        assert_eq!(estimate.degree, k);
+        assert!(estimate.stats.advice_columns > 0);
+        assert!(estimate.proof_bytes > estimate.marginal_proof_bytes);
+        assert!(estimate
+            .to_string()
+            .starts_with(&format!("with degree = {k}: ")));
+    }
+}
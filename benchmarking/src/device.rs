@@ -0,0 +1,125 @@
+//! Device the benchmarks run on, printed by every bench target before its
+//! groups, so that the results of runs on different machines, or with and
+//! without the `asm` feature, are compared knowing what proved them.
+//!
+//! The `asm` feature enables the `asm` feature of halo2, the assembly
+//! arithmetic of the BN254 fields of `halo2curves`, which accelerates the
+//! MSMs and FFTs of the prover on x86-64 CPUs with the ADX and BMI2
+//! extensions. The fork of halo2 the circuits are built on has no GPU
+//! backend, so the prover otherwise runs its MSMs in parallel on the CPU
+//! threads of [`DeviceInfo::threads`]. CPU and accelerated proving are
+//! compared by saving a baseline of a run without the feature and comparing
+//! a run with it, see the [`crate::history`] module:
+//!
+//! ```text
+//! cargo bench -- --save-baseline cpu
+//! cargo bench --features asm
+//! cargo run --bin bench_regressions -- cpu
+//! ```
+
+use std::{fmt, fs};
+
+/// CPU extensions the assembly arithmetic relies on, or which speed up the
+/// arithmetic of the prover otherwise.
+#[cfg(target_arch = "x86_64")]
+const CPU_FEATURES: [&str; 3] = ["adx", "bmi2", "avx2"];
+
+/// Architecture, CPU and threads of the device, and whether the arithmetic
+/// of the prover is accelerated.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DeviceInfo {
+    pub arch: &'static str,
+    /// Model of the CPU, if the OS reports it.
+    pub cpu: Option<String>,
+    /// Extensions of the CPU that speed up the arithmetic of the prover.
+    pub cpu_features: Vec<&'static str>,
+    /// Threads the prover runs on.
+    pub threads: usize,
+    /// Whether the `asm` feature is enabled.
+    pub asm: bool,
+}
+
+impl DeviceInfo {
+    /// Information of the device the benchmarks run on.
+    pub fn detect() -> Self {
+        Self {
+            arch: std::env::consts::ARCH,
+            cpu: cpu_model(),
+            cpu_features: cpu_features(),
+            threads: rayon::current_num_threads(),
+            asm: cfg!(feature = "asm"),
+        }
+    }
+}
+
+impl fmt::Display for DeviceInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} CPU {}",
+            self.arch,
+            self.cpu.as_deref().unwrap_or("of unknown model")
+        )?;
+        if !self.cpu_features.is_empty() {
+            write!(f, " with {}", self.cpu_features.join(", "))?;
+        }
+        write!(f, ", {} threads", self.threads)?;
+        if self.asm {
+            write!(f, ", assembly arithmetic")
+        } else {
+            write!(f, ", no acceleration")
+        }
+    }
+}
+
+/// Model of the CPU, as reported by `/proc/cpuinfo` on Linux.
+fn cpu_model() -> Option<String> {
+    let cpuinfo = fs::read_to_string("/proc/cpuinfo").ok()?;
+    cpuinfo
+        .lines()
+        .find_map(|line| line.strip_prefix("model name")?.split_once(':'))
+        .map(|(_, model)| model.trim().to_string())
+}
+
+#[cfg(target_arch = "x86_64")]
+fn cpu_features() -> Vec<&'static str> {
+    let detected = [
+        std::arch::is_x86_feature_detected!("adx"),
+        std::arch::is_x86_feature_detected!("bmi2"),
+        std::arch::is_x86_feature_detected!("avx2"),
+    ];
+    CPU_FEATURES
+        .into_iter()
+        .zip(detected)
+        .filter_map(|(feature, detected)| detected.then_some(feature))
+        .collect()
+}
+
+#[cfg(not(target_arch = "x86_64"))]
+fn cpu_features() -> Vec<&'static str> {
+    vec![]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_device_info() {
+        let device = DeviceInfo::detect();
+        assert!(device.threads > 0);
+        assert_eq!(device.asm, cfg!(feature = "asm"));
+
+        let device = DeviceInfo {
+            arch: "x86_64",
+            cpu: Some("AMD EPYC 7R13 Processor".to_string()),
+            cpu_features: vec!["adx", "bmi2"],
+            threads: 8,
+            asm: true,
+        };
+        assert_eq!(
+            device.to_string(),
+            "x86_64 CPU AMD EPYC 7R13 Processor with adx, bmi2, 8 threads, assembly arithmetic"
+        );
+    }
+}
@@ -0,0 +1,232 @@
+//! History of the results of the benchmarks, to compare a run against a
+//! previous one before submitting a change to the constraints.
+//!
+//! Criterion saves the results of every benchmark as JSON files in the
+//! `criterion` directory of the target directory, in a directory per
+//! baseline: `new` holds those of the latest run, and a run with
+//! `--save-baseline <name>` also saves them as the baseline `name`.
+//! [`read_results`] reads the mean times of the benchmarks of a baseline,
+//! keyed by their [`BenchKey`], and [`compare`] the changes between two of
+//! them, which the `bench_regressions` binary reports, exiting with an error
+//! if any benchmark regressed by more than a threshold, [`THRESHOLD`] by
+//! default:
+//!
+//! ```text
+//! cargo bench -- --save-baseline main
+//! # apply the change to the constraints
+//! cargo bench
+//! cargo run --bin bench_regressions -- main --threshold 10
+//! ```
+
+use std::{
+    collections::BTreeMap,
+    fmt, fs, io,
+    path::{Path, PathBuf},
+};
+
+use serde_json::Value;
+
+use crate::target_dir;
+
+/// Slowdown of a benchmark, in percent, above which it is a regression.
+pub const THRESHOLD: f64 = 5.0;
+
+/// Baseline of the latest run of the benchmarks.
+pub const CURRENT: &str = "new";
+
+/// Benchmark a result is for: the circuit of its group, its phase and the
+/// degree it was run at, or the parameter of the benchmarks that sweep
+/// another one, e.g. the rounds of BLAKE2F.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct BenchKey {
+    pub circuit: String,
+    pub phase: String,
+    pub degree: Option<String>,
+}
+
+impl fmt::Display for BenchKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.circuit, self.phase)?;
+        if let Some(degree) = &self.degree {
+            write!(f, "/{degree}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Mean times of the benchmarks of a baseline, in nanoseconds.
+pub type Results = BTreeMap<BenchKey, f64>;
+
+/// Directory of the results of the benchmarks saved by criterion.
+pub fn criterion_dir() -> PathBuf {
+    target_dir().join("criterion")
+}
+
+/// Reads the results of every benchmark under `dir` saved as the baseline
+/// `baseline`.
+pub fn read_results(dir: &Path, baseline: &str) -> io::Result<Results> {
+    let mut results = Results::new();
+    read_results_into(dir, baseline, &mut results)?;
+    Ok(results)
+}
+
+fn read_results_into(dir: &Path, baseline: &str, results: &mut Results) -> io::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if !path.is_dir() {
+            continue;
+        }
+        if path.file_name() == Some(baseline.as_ref()) && path.join("estimates.json").is_file() {
+            let (key, mean) = read_result(&path)?;
+            results.insert(key, mean);
+        } else {
+            read_results_into(&path, baseline, results)?;
+        }
+    }
+    Ok(())
+}
+
+/// Reads the benchmark and the mean time of the result saved in `dir`.
+fn read_result(dir: &Path) -> io::Result<(BenchKey, f64)> {
+    let benchmark = read_json(&dir.join("benchmark.json"))?;
+    let estimates = read_json(&dir.join("estimates.json"))?;
+    let invalid = || {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("invalid results in {}", dir.display()),
+        )
+    };
+    let key = BenchKey {
+        circuit: benchmark["group_id"]
+            .as_str()
+            .ok_or_else(invalid)?
+            .to_string(),
+        phase: benchmark["function_id"]
+            .as_str()
+            .unwrap_or_default()
+            .to_string(),
+        degree: benchmark["value_str"].as_str().map(str::to_string),
+    };
+    let mean = estimates["mean"]["point_estimate"]
+        .as_f64()
+        .ok_or_else(invalid)?;
+    Ok((key, mean))
+}
+
+fn read_json(path: &Path) -> io::Result<Value> {
+    let json = fs::read_to_string(path)?;
+    serde_json::from_str(&json).map_err(|err| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("{}: {err}", path.display()),
+        )
+    })
+}
+
+/// Change of the mean time of a benchmark between two baselines.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Delta {
+    pub key: BenchKey,
+    pub previous: f64,
+    pub current: f64,
+}
+
+impl Delta {
+    /// Change of the time of the benchmark, in percent of its previous time.
+    pub fn change(&self) -> f64 {
+        (self.current / self.previous - 1.0) * 100.0
+    }
+
+    /// Whether the benchmark slowed down by more than `threshold` percent.
+    pub fn regressed(&self, threshold: f64) -> bool {
+        self.change() > threshold
+    }
+}
+
+/// Changes of the benchmarks of both `previous` and `current`, in the order
+/// of their keys.
+pub fn compare(previous: &Results, current: &Results) -> Vec<Delta> {
+    previous
+        .iter()
+        .filter_map(|(key, &previous)| {
+            current.get(key).map(|&current| Delta {
+                key: key.clone(),
+                previous,
+                current,
+            })
+        })
+        .collect()
+}
+
+/// Markdown table of `deltas`, one row each, with their times in
+/// milliseconds.
+pub fn delta_report(deltas: &[Delta]) -> String {
+    let mut lines = vec![
+        "| circuit | phase | degree | previous (ms) | current (ms) | change |".to_string(),
+        "| --- | --- | --- | --- | --- | --- |".to_string(),
+    ];
+    lines.extend(deltas.iter().map(|delta| {
+        format!(
+            "| {} | {} | {} | {:.3} | {:.3} | {:+.1}% |",
+            delta.key.circuit,
+            delta.key.phase,
+            delta.key.degree.as_deref().unwrap_or("-"),
+            delta.previous / 1e6,
+            delta.current / 1e6,
+            delta.change()
+        )
+    }));
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Saves a result of `mean` nanoseconds as criterion does under `dir`.
+    fn save(dir: &Path, baseline: &str, id: [&str; 3], value: Option<&str>, mean: f64) {
+        let dir = dir.join(id.join("/")).join(baseline);
+        fs::create_dir_all(&dir).unwrap();
+        let value = value.map_or("null".to_string(), |value| format!("\"{value}\""));
+        let benchmark = format!(
+            r#"{{"group_id": "{}", "function_id": "{}", "value_str": {value}}}"#,
+            id[0], id[1]
+        );
+        let estimates = format!(r#"{{"mean": {{"point_estimate": {mean}}}}}"#);
+        fs::write(dir.join("benchmark.json"), benchmark).unwrap();
+        fs::write(dir.join("estimates.json"), estimates).unwrap();
+    }
+
+    #[test]
+    fn test_compare() {
+        let dir = std::env::temp_dir().join(format!("bench_history_{}", std::process::id()));
+        let proof = ["SHA2-256 Circuit", "proof SHPLONK", "8"];
+        let native = ["SHA2-256 Circuit overhead", "native", "native"];
+        save(&dir, "main", proof, Some("8"), 2e9);
+        save(&dir, CURRENT, proof, Some("8"), 2.5e9);
+        save(&dir, "main", native, None, 1e3);
+        save(&dir, CURRENT, native, None, 1e3);
+        // a benchmark of a single run is not compared.
+        save(&dir, CURRENT, ["MD5", "witness", "8"], Some("8"), 1e6);
+
+        let previous = read_results(&dir, "main").unwrap();
+        let current = read_results(&dir, CURRENT).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(current.len(), 3);
+
+        let deltas = compare(&previous, &current);
+        assert_eq!(deltas.len(), 2);
+        assert_eq!(
+            deltas[0].key.to_string(),
+            "SHA2-256 Circuit/proof SHPLONK/8"
+        );
+        assert_eq!(deltas[0].change(), 25.0);
+        assert!(deltas[0].regressed(THRESHOLD) && !deltas[0].regressed(30.0));
+        assert_eq!(deltas[1].key.degree, None);
+        assert!(!deltas[1].regressed(THRESHOLD));
+        assert_eq!(
+            delta_report(&deltas).lines().nth(2),
+            Some("| SHA2-256 Circuit | proof SHPLONK | 8 | 2000.000 | 2500.000 | +25.0% |")
+        );
+    }
+}
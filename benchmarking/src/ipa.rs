@@ -0,0 +1,219 @@
+//! The benchmarks of the circuits over the IPA commitment scheme on the
+//! Pasta curves, enabled by the `ipa` feature, to compare a transparent
+//! setup with the trusted one of KZG for the same circuits. The circuits are
+//! proven over the scalar field of Eq, i.e. the base field of Pallas.
+//!
+//! The groups are those of the crate, named after the circuit with an
+//! `(IPA)` suffix, and their parameters are cached the same way, as the
+//! setup of IPA, although transparent, still computes a generator per row.
+
+use criterion::{measurement::WallTime, BenchmarkGroup, BenchmarkId, Criterion};
+use halo2_proofs::{
+    halo2curves::pasta::{Eq, EqAffine, Fp},
+    plonk::{create_proof, keygen_pk, keygen_vk, verify_proof, Circuit, ProvingKey, VerifyingKey},
+    poly::{
+        commitment::{Params, ParamsProver},
+        ipa::{
+            commitment::{IPACommitmentScheme, ParamsIPA},
+            multiopen::{ProverIPA, VerifierIPA},
+            strategy::SingleStrategy,
+        },
+    },
+    transcript::{
+        Blake2bRead, Blake2bWrite, Challenge255, TranscriptReadBuffer, TranscriptWriterBuffer,
+    },
+    SerdeFormat,
+};
+use rand_xorshift::XorShiftRng;
+
+use crate::{
+    artifacts::bench_artifacts, bench_witness, cached_params, cost::estimate, peak_memory, phases,
+    profile::profiled, report, rng, PeakMemory, Phase, ProofSizes, ProvingScheme,
+};
+
+/// Parameters of the commitment scheme for circuits of `2^degree` rows,
+/// cached as described in [`crate::setup`].
+pub fn setup(degree: u32) -> ParamsIPA<EqAffine> {
+    cached_params(&format!("ipa_pasta_{degree}.params"), || {
+        ParamsIPA::<EqAffine>::new(degree)
+    })
+}
+
+pub fn keygen<C: Circuit<Fp>>(params: &ParamsIPA<EqAffine>, circuit: &C) -> ProvingKey<EqAffine> {
+    let vk = keygen_vk(params, circuit).expect("keygen_vk should not fail");
+    keygen_pk(params, vk, circuit).expect("keygen_pk should not fail")
+}
+
+pub fn prove<C: Circuit<Fp>>(
+    params: &ParamsIPA<EqAffine>,
+    pk: &ProvingKey<EqAffine>,
+    circuit: C,
+    instances: &[Vec<Fp>],
+) -> Vec<u8> {
+    let instances = instances.iter().map(Vec::as_slice).collect::<Vec<_>>();
+    let mut transcript = Blake2bWrite::<_, EqAffine, Challenge255<_>>::init(vec![]);
+    create_proof::<
+        IPACommitmentScheme<EqAffine>,
+        ProverIPA<'_, EqAffine>,
+        Challenge255<EqAffine>,
+        XorShiftRng,
+        Blake2bWrite<Vec<u8>, EqAffine, Challenge255<EqAffine>>,
+        C,
+    >(
+        params,
+        pk,
+        &[circuit],
+        &[&instances],
+        rng(),
+        &mut transcript,
+    )
+    .expect("proof generation should not fail");
+    transcript.finalize()
+}
+
+pub fn verify(
+    params: &ParamsIPA<EqAffine>,
+    vk: &VerifyingKey<EqAffine>,
+    proof: &[u8],
+    instances: &[Vec<Fp>],
+) -> bool {
+    let instances = instances.iter().map(Vec::as_slice).collect::<Vec<_>>();
+    let mut transcript = Blake2bRead::<_, EqAffine, Challenge255<_>>::init(proof);
+    verify_proof::<
+        IPACommitmentScheme<EqAffine>,
+        VerifierIPA<'_, EqAffine>,
+        Challenge255<EqAffine>,
+        Blake2bRead<&[u8], EqAffine, Challenge255<EqAffine>>,
+        SingleStrategy<'_, EqAffine>,
+    >(
+        params,
+        vk,
+        SingleStrategy::new(params),
+        &[&instances],
+        &mut transcript,
+    )
+    .is_ok()
+}
+
+/// Length of `vk` serialized in the raw bytes format.
+pub fn vk_bytes(vk: &VerifyingKey<EqAffine>) -> usize {
+    let mut bytes = vec![];
+    vk.write(&mut bytes, SerdeFormat::RawBytes)
+        .expect("writing to a vector should not fail");
+    bytes.len()
+}
+
+/// Benchmarks the key generation of the circuit built by `circuit` within
+/// `group`, then its proof and the verification of the proof, at the phases
+/// selected by [`phases`], as [`crate::bench_prover`] does over KZG.
+pub fn bench_prover<C: Circuit<Fp>>(
+    group: &mut BenchmarkGroup<'_, WallTime>,
+    name: &str,
+    params: &ParamsIPA<EqAffine>,
+    circuit: impl Fn() -> C,
+    instances: &[Vec<Fp>],
+) -> Vec<(ProofSizes, PeakMemory)> {
+    let degree = params.k();
+    let phases = phases();
+    if !phases.iter().any(Phase::proves) {
+        return vec![];
+    }
+    if phases.contains(&Phase::Keygen) {
+        group.bench_function(BenchmarkId::new("keygen", degree), |b| {
+            b.iter(|| keygen(params, &circuit()))
+        });
+    }
+    let (pk, keygen_bytes) = peak_memory(|| keygen(params, &circuit()));
+    if phases.contains(&Phase::Serialize) {
+        bench_artifacts::<_, _, C>(group, name, params, &pk);
+    }
+    if !phases.contains(&Phase::Proof) && !phases.contains(&Phase::Verify) {
+        return vec![];
+    }
+
+    if phases.contains(&Phase::Proof) {
+        group.bench_function(BenchmarkId::new("proof IPA", degree), |b| {
+            b.iter(|| prove(params, &pk, circuit(), instances))
+        });
+    }
+    let (proof, proof_bytes) = profiled(&format!("{name} proof IPA {degree}"), || {
+        peak_memory(|| prove(params, &pk, circuit(), instances))
+    });
+
+    if phases.contains(&Phase::Verify) {
+        group.bench_function(BenchmarkId::new("verify IPA", degree), |b| {
+            b.iter(|| {
+                assert!(
+                    verify(params, pk.get_vk(), &proof, instances),
+                    "failed to verify bench circuit"
+                )
+            })
+        });
+    }
+
+    let scheme = ProvingScheme::Ipa;
+    let sizes = ProofSizes::new(degree, scheme, vk_bytes(pk.get_vk()), &proof, instances);
+    let memory = PeakMemory {
+        degree,
+        scheme,
+        keygen_bytes,
+        proof_bytes,
+    };
+    println!("{name} {sizes}");
+    println!("{name} {memory}");
+    vec![(sizes, memory)]
+}
+
+/// Benchmarks every phase of the proof of the circuit built by `circuit` at
+/// every degree of `degrees`, as [`crate::bench_circuit`] does over KZG.
+pub fn bench_circuit<C: Circuit<Fp>>(
+    c: &mut Criterion,
+    name: &str,
+    degrees: &[u32],
+    circuit: impl Fn() -> C,
+    instances: Vec<Vec<Fp>>,
+) -> Vec<(ProofSizes, PeakMemory)> {
+    let name = format!("{name} (IPA)");
+    let mut group = c.benchmark_group(&name);
+    let phases = phases();
+    let mut rows = vec![];
+    for &degree in degrees {
+        if phases.contains(&Phase::Cost) {
+            estimate::<Eq, _>(&name, degree, &circuit());
+        }
+        bench_witness(&mut group, degree, &circuit, &instances);
+        if !phases.iter().any(Phase::proves) {
+            continue;
+        }
+
+        let params = setup(degree);
+        rows.extend(bench_prover(
+            &mut group, &name, &params, &circuit, &instances,
+        ));
+    }
+    group.finish();
+    if !rows.is_empty() {
+        println!("{name}\n{}", report(&rows));
+    }
+    rows
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ipa() {
+        let (inputs, outputs) = sha2_256_circuit::dev::INPUTS_OUTPUTS.clone();
+        let circuit = || sha2_256_circuit::Sha2TestCircuit::<Fp> {
+            inputs: inputs.clone(),
+            outputs: outputs.clone(),
+            _marker: std::marker::PhantomData,
+        };
+        let params = setup(8);
+        let pk = keygen(&params, &circuit());
+        let proof = prove(&params, &pk, circuit(), &[]);
+        assert!(verify(&params, pk.get_vk(), &proof, &[]));
+        assert!(vk_bytes(pk.get_vk()) > 0);
+    }
+}
@@ -1,11 +1,1056 @@
-#[cfg(test)]
-pub mod blake2f_circuit_bench;
+//! Harness of the criterion benchmarks of the circuits, one bench target per
+//! circuit in `benches/`.
+//!
+//! A bench target implements [`BenchCircuit`] for its circuit, i.e. its name,
+//! its smallest degree and the builder of its witness over any field, and
+//! benchmarks it with [`run_full_bench`]:
+//!
+//! ```ignore
+//! fn bench_sha2_256_circuit(c: &mut Criterion) {
+//!     run_full_bench(c, &Sha2Bench);
+//! }
+//! ```
+//!
+//! Every circuit is a benchmark group named after it, with a benchmark per
+//! [`Phase`] of its proof, identified by the phase and the degree, e.g.
+//! `keygen/17` or `proof SHPLONK/17`. The modules of the crate hold the
+//! other benchmarks of the targets, e.g. [`batch`] or [`scaling`], and are
+//! configured by env vars rather than flags, as criterion rejects the flags
+//! it does not know:
+//!
+//! ```text
+//! DEGREE=17 cargo bench --bench sha2_256_circuit -- --sample-size 20
+//! DEGREE=18..=22 cargo bench
+//! PHASES=cost,mock DEGREE=20 cargo bench
+//! FORCE_SETUP=1 DEGREE=20 cargo bench
+//! RUST_LOG=info PHASES=proof DEGREE=20 cargo bench --bench sha2_256_circuit
+//! ```
+
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    env::var,
+    fmt,
+    fs::{self, File},
+    io::{BufReader, BufWriter},
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicUsize, Ordering},
+    time::{Duration, Instant},
+};
+
+use artifacts::bench_artifacts;
+use batch::bench_batch_verify;
+use cost::estimate;
+use criterion::{measurement::WallTime, BenchmarkGroup, BenchmarkId, Criterion};
+use device::DeviceInfo;
+use halo2_proofs::{
+    arithmetic::{CurveAffine, FieldExt},
+    dev::MockProver,
+    halo2curves::bn256::{Bn256, Fr, G1Affine, G1},
+    plonk::{create_proof, keygen_pk, keygen_vk, verify_proof, Circuit, ProvingKey, VerifyingKey},
+    poly::{
+        commitment::{Params, ParamsProver, Prover, Verifier},
+        kzg::{
+            commitment::{KZGCommitmentScheme, ParamsKZG},
+            multiopen::{ProverGWC, ProverSHPLONK, VerifierGWC, VerifierSHPLONK},
+            strategy::SingleStrategy,
+        },
+        VerificationStrategy,
+    },
+    transcript::{
+        Blake2bRead, Blake2bWrite, Challenge255, Keccak256Read, Keccak256Write,
+        TranscriptReadBuffer, TranscriptWriterBuffer,
+    },
+    SerdeFormat,
+};
+use profile::profiled;
+pub use prover::init_tracing;
+use prover::srs::read_srs;
+use rand::SeedableRng;
+use rand_xorshift::XorShiftRng;
+use threads::{bench_threads, thread_counts};
+use tracing::{info, info_span};
+use transcript::bench_transcripts;
+
+pub mod artifacts;
+pub mod batch;
+pub mod cost;
+pub mod device;
+pub mod history;
+#[cfg(feature = "ipa")]
+pub mod ipa;
+pub mod overhead;
+pub mod profile;
+pub mod scaling;
+pub mod strategy;
+pub mod threads;
+pub mod transcript;
+pub mod workload;
+
+/// Degree of the circuits if the `DEGREE` env var is unset.
+pub const DEFAULT_DEGREE: u32 = 12;
+
+/// Number of samples of every phase, the smallest criterion accepts, as a
+/// proof of a circuit takes seconds.
+pub const SAMPLE_SIZE: usize = 10;
+
+/// The criterion configuration of the bench targets, whose settings are
+/// overridden by those given on the command line. Prints the device the
+/// benchmarks run on, see [`DeviceInfo`], and installs the subscriber of
+/// [`init_tracing`].
+pub fn criterion() -> Criterion {
+    init_tracing();
+    println!("Benchmarking on {}", DeviceInfo::detect());
+    Criterion::default().sample_size(SAMPLE_SIZE)
+}
+
+/// Degrees a circuit that fits in no fewer than `2^min_k` rows is benchmarked
+/// at: those of the `DEGREE` env var, or [`DEFAULT_DEGREE`] if unset, raised
+/// to `min_k`, in increasing order. The env var holds a degree, a range of
+/// degrees or a comma-separated list of either, e.g. `18..=22` or `12,16`,
+/// so that a single run sweeps the degrees and prints a table comparing
+/// them, see [`report`].
+pub fn degrees(min_k: u32) -> Vec<u32> {
+    let mut degrees = var("DEGREE")
+        .map(|degrees| parse_degrees(&degrees).expect("Cannot parse DEGREE env var as degrees"))
+        .unwrap_or_else(|_| vec![DEFAULT_DEGREE])
+        .into_iter()
+        .map(|degree| degree.max(min_k))
+        .collect::<Vec<_>>();
+    degrees.sort_unstable();
+    degrees.dedup();
+    degrees
+}
+
+/// Parses a degree, a range of degrees `18..22` or `18..=22`, or a
+/// comma-separated list of either.
+fn parse_degrees(degrees: &str) -> Option<Vec<u32>> {
+    let mut parsed = vec![];
+    for part in degrees.split(',').map(str::trim) {
+        if let Some((start, end)) = part.split_once("..=") {
+            parsed.extend(start.parse::<u32>().ok()?..=end.parse().ok()?);
+        } else if let Some((start, end)) = part.split_once("..") {
+            parsed.extend(start.parse::<u32>().ok()?..end.parse().ok()?);
+        } else {
+            parsed.push(part.parse().ok()?);
+        }
+    }
+    (!parsed.is_empty()).then_some(parsed)
+}
+
+/// A phase of the proof of a circuit, benchmarked on its own, see
+/// [`phases`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Phase {
+    /// The estimate of the cost of the circuit and its proof size by halo2,
+    /// untimed, see [`cost`].
+    Cost,
+    /// The synthesis of the witness, by the `MockProver`.
+    Witness,
+    /// The check of the witness by the `MockProver`, without the parameters,
+    /// see [`mock_verify`].
+    Mock,
+    /// The generation of the proving and verifying keys.
+    Keygen,
+    /// The writing and reading of the params and keys, see [`artifacts`].
+    Serialize,
+    /// The generation of a proof, with every [`Multiopen`] argument.
+    Proof,
+    /// The verification of the proof.
+    Verify,
+}
+
+impl Phase {
+    pub const ALL: [Self; 7] = [
+        Self::Cost,
+        Self::Witness,
+        Self::Mock,
+        Self::Keygen,
+        Self::Serialize,
+        Self::Proof,
+        Self::Verify,
+    ];
+
+    /// Whether the phase needs the parameters of the commitment scheme.
+    pub fn proves(&self) -> bool {
+        matches!(
+            self,
+            Self::Keygen | Self::Serialize | Self::Proof | Self::Verify
+        )
+    }
+}
+
+impl fmt::Display for Phase {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Cost => write!(f, "cost"),
+            Self::Witness => write!(f, "witness"),
+            Self::Mock => write!(f, "mock"),
+            Self::Keygen => write!(f, "keygen"),
+            Self::Serialize => write!(f, "serialize"),
+            Self::Proof => write!(f, "proof"),
+            Self::Verify => write!(f, "verify"),
+        }
+    }
+}
+
+/// Phases the circuits are benchmarked at: those of the `PHASES` env var, a
+/// comma-separated list, e.g. `witness` or `keygen,proof`, or all of them if
+/// unset, so that a regression is measured on its own phase. The phases a
+/// selected one depends on still run, once and untimed, e.g. the key
+/// generation and the proof before the verification.
+///
+/// `PHASES=cost,mock` neither sets up the parameters nor proves, so that a
+/// run reports the rows a workload uses and whether its witness satisfies
+/// the constraints within seconds, and skips the benchmarks that only
+/// measure proofs, e.g. the overhead and the aggregation.
+pub fn phases() -> Vec<Phase> {
+    var("PHASES")
+        .map(|phases| parse_phases(&phases).expect("Cannot parse PHASES env var as phases"))
+        .unwrap_or_else(|_| Phase::ALL.to_vec())
+}
+
+/// Parses a comma-separated list of phases, named as they are displayed.
+fn parse_phases(phases: &str) -> Option<Vec<Phase>> {
+    phases
+        .split(',')
+        .map(|phase| {
+            Phase::ALL
+                .into_iter()
+                .find(|known| known.to_string() == phase.trim())
+        })
+        .collect()
+}
+
+/// The random number generator of the benchmarks, seeded so that every run
+/// proves the same way.
+pub fn rng() -> XorShiftRng {
+    XorShiftRng::from_seed([
+        0x59, 0x62, 0xbe, 0x5d, 0x76, 0x3d, 0x31, 0x8d, 0x17, 0xdb, 0x37, 0x32, 0x54, 0x06, 0xbc,
+        0xe5,
+    ])
+}
+
+/// Target directory of the workspace.
+pub fn target_dir() -> PathBuf {
+    var("CARGO_TARGET_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_else(|_| Path::new(env!("CARGO_MANIFEST_DIR")).join("../target"))
+}
+
+/// Directory of the cached parameters of the commitment scheme.
+pub fn params_dir() -> PathBuf {
+    target_dir().join("params")
+}
+
+/// Path of the SRS of the `SRS_PATH` env var, if set.
+pub fn srs_path() -> Option<PathBuf> {
+    var("SRS_PATH").ok().map(PathBuf::from)
+}
+
+/// Parameters of the commitment scheme for circuits of `2^degree` rows, read
+/// from the cache of [`params_dir`] unless the `FORCE_SETUP` env var is set,
+/// e.g. `FORCE_SETUP=1`, the escape hatch of a stale or corrupted cache, as
+/// criterion rejects a `--force-setup` flag. They are generated, or read
+/// from the SRS of the `SRS_PATH` env var if set, see [`prover::srs`], and
+/// cached if they are not, or if the cached ones cannot be read.
+pub fn setup(degree: u32) -> ParamsKZG<Bn256> {
+    let _span = info_span!("setup", degree).entered();
+    match srs_path() {
+        Some(path) => {
+            let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+            cached_params(&format!("srs_{stem}_{degree}.params"), || {
+                read_srs(&path, degree)
+                    .unwrap_or_else(|err| panic!("Cannot read the SRS of SRS_PATH: {err}"))
+            })
+        }
+        None => cached_params(&format!("kzg_bn256_{degree}.params"), || {
+            ParamsKZG::<Bn256>::setup(degree, rng())
+        }),
+    }
+}
+
+/// The parameters cached in the file `name` of [`params_dir`], or those
+/// generated by `generate`, which are then cached, as described in
+/// [`setup`].
+fn cached_params<C: CurveAffine, P: for<'params> Params<'params, C>>(
+    name: &str,
+    generate: impl FnOnce() -> P,
+) -> P {
+    let path = params_dir().join(name);
+    if var("FORCE_SETUP").is_err() {
+        let cached = File::open(&path).and_then(|file| P::read(&mut BufReader::new(file)));
+        if let Ok(params) = cached {
+            return params;
+        }
+    }
+
+    let params = generate();
+    // Written next to the cache and moved in place, so that an interrupted
+    // run does not leave truncated parameters behind.
+    let partial = path.with_extension("partial");
+    fs::create_dir_all(params_dir())
+        .and_then(|()| File::create(&partial))
+        .and_then(|file| params.write(&mut BufWriter::new(file)))
+        .and_then(|()| fs::rename(&partial, &path))
+        .expect("Cannot cache the params");
+    params
+}
+
+pub fn keygen<C: Circuit<Fr>>(params: &ParamsKZG<Bn256>, circuit: &C) -> ProvingKey<G1Affine> {
+    let rows = 1usize << params.k();
+    let vk = info_span!("keygen_vk", rows)
+        .in_scope(|| keygen_vk(params, circuit).expect("keygen_vk should not fail"));
+    info_span!("keygen_pk", rows)
+        .in_scope(|| keygen_pk(params, vk, circuit).expect("keygen_pk should not fail"))
+}
+
+/// The multiopen arguments of the KZG commitment scheme, which open the
+/// commitments of a proof at its points.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Multiopen {
+    /// The argument of [BDFG20], with a smaller proof.
+    ///
+    /// [BDFG20]: https://eprint.iacr.org/2020/081
+    Shplonk,
+    /// The argument of [GWC19], with a faster prover.
+    ///
+    /// [GWC19]: https://eprint.iacr.org/2019/953
+    Gwc,
+}
+
+impl Multiopen {
+    pub const ALL: [Self; 2] = [Self::Shplonk, Self::Gwc];
+}
+
+impl fmt::Display for Multiopen {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Shplonk => write!(f, "SHPLONK"),
+            Self::Gwc => write!(f, "GWC"),
+        }
+    }
+}
+
+/// A commitment scheme, along with its multiopen argument, which the
+/// circuits are proven with.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ProvingScheme {
+    /// KZG over BN254, with a trusted setup.
+    Kzg(Multiopen),
+    /// IPA over the Pasta curves, without a trusted setup, see the `ipa`
+    /// module.
+    Ipa,
+}
+
+impl fmt::Display for ProvingScheme {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Kzg(multiopen) => write!(f, "KZG {multiopen}"),
+            Self::Ipa => write!(f, "IPA"),
+        }
+    }
+}
+
+/// The transcripts the challenges of a proof are derived from, by hashing
+/// the commitments and evaluations of the prover.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Transcript {
+    /// BLAKE2b, the faster to compute natively.
+    Blake2b,
+    /// Keccak-256, which the verifiers on the EVM need, as it has a
+    /// precompile there.
+    Keccak256,
+}
+
+impl Transcript {
+    pub const ALL: [Self; 2] = [Self::Blake2b, Self::Keccak256];
+
+    /// The transcript of [`prove`] and [`verify`], and thus of the
+    /// benchmarks: Keccak-256 with the `evm` feature, BLAKE2b otherwise.
+    pub const DEFAULT: Self = if cfg!(feature = "evm") {
+        Self::Keccak256
+    } else {
+        Self::Blake2b
+    };
+
+    /// The transcript the benchmarks are compared with, see
+    /// [`bench_transcripts`].
+    pub fn other(self) -> Self {
+        match self {
+            Self::Blake2b => Self::Keccak256,
+            Self::Keccak256 => Self::Blake2b,
+        }
+    }
+}
+
+impl fmt::Display for Transcript {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Blake2b => write!(f, "Blake2b"),
+            Self::Keccak256 => write!(f, "Keccak256"),
+        }
+    }
+}
+
+/// Proof of the circuit `circuit` with [`Transcript::DEFAULT`], see
+/// [`prove_with_transcript`].
+pub fn prove<C: Circuit<Fr>>(
+    params: &ParamsKZG<Bn256>,
+    pk: &ProvingKey<G1Affine>,
+    circuit: C,
+    instances: &[Vec<Fr>],
+    multiopen: Multiopen,
+) -> Vec<u8> {
+    prove_with_transcript(
+        params,
+        pk,
+        circuit,
+        instances,
+        multiopen,
+        Transcript::DEFAULT,
+    )
+}
+
+/// Proof of the circuit `circuit` for the verifiers on the EVM, with the
+/// Keccak-256 transcript and the SHPLONK argument, whose proof is the
+/// smaller calldata, whatever the `evm` feature.
+pub fn prove_evm<C: Circuit<Fr>>(
+    params: &ParamsKZG<Bn256>,
+    pk: &ProvingKey<G1Affine>,
+    circuit: C,
+    instances: &[Vec<Fr>],
+) -> Vec<u8> {
+    prove_with_transcript(
+        params,
+        pk,
+        circuit,
+        instances,
+        Multiopen::Shplonk,
+        Transcript::Keccak256,
+    )
+}
+
+pub fn prove_with_transcript<C: Circuit<Fr>>(
+    params: &ParamsKZG<Bn256>,
+    pk: &ProvingKey<G1Affine>,
+    circuit: C,
+    instances: &[Vec<Fr>],
+    multiopen: Multiopen,
+    transcript: Transcript,
+) -> Vec<u8> {
+    type Blake2b = Blake2bWrite<Vec<u8>, G1Affine, Challenge255<G1Affine>>;
+    type Keccak256 = Keccak256Write<Vec<u8>, G1Affine, Challenge255<G1Affine>>;
+    let rows = 1usize << params.k();
+    let _span = info_span!("prove", rows, %multiopen, %transcript).entered();
+    let proof = match (multiopen, transcript) {
+        (Multiopen::Shplonk, Transcript::Blake2b) => {
+            prove_with::<ProverSHPLONK<'_, Bn256>, Blake2b, C>(params, pk, circuit, instances)
+        }
+        (Multiopen::Shplonk, Transcript::Keccak256) => {
+            prove_with::<ProverSHPLONK<'_, Bn256>, Keccak256, C>(params, pk, circuit, instances)
+        }
+        (Multiopen::Gwc, Transcript::Blake2b) => {
+            prove_with::<ProverGWC<'_, Bn256>, Blake2b, C>(params, pk, circuit, instances)
+        }
+        (Multiopen::Gwc, Transcript::Keccak256) => {
+            prove_with::<ProverGWC<'_, Bn256>, Keccak256, C>(params, pk, circuit, instances)
+        }
+    };
+    info!(bytes = proof.len(), "proof generated");
+    proof
+}
+
+fn prove_with<
+    'params,
+    P: Prover<'params, KZGCommitmentScheme<Bn256>>,
+    T: TranscriptWriterBuffer<Vec<u8>, G1Affine, Challenge255<G1Affine>>,
+    C: Circuit<Fr>,
+>(
+    params: &'params ParamsKZG<Bn256>,
+    pk: &ProvingKey<G1Affine>,
+    circuit: C,
+    instances: &[Vec<Fr>],
+) -> Vec<u8> {
+    let instances = instances.iter().map(Vec::as_slice).collect::<Vec<_>>();
+    let mut transcript = T::init(vec![]);
+    create_proof::<KZGCommitmentScheme<Bn256>, P, Challenge255<G1Affine>, XorShiftRng, T, C>(
+        params,
+        pk,
+        &[circuit],
+        &[&instances],
+        rng(),
+        &mut transcript,
+    )
+    .expect("proof generation should not fail");
+    transcript.finalize()
+}
+
+/// Verification of a proof with [`Transcript::DEFAULT`], see
+/// [`verify_with_transcript`].
+pub fn verify(
+    params: &ParamsKZG<Bn256>,
+    vk: &VerifyingKey<G1Affine>,
+    proof: &[u8],
+    instances: &[Vec<Fr>],
+    multiopen: Multiopen,
+) -> bool {
+    verify_with_transcript(params, vk, proof, instances, multiopen, Transcript::DEFAULT)
+}
+
+/// Verification of a proof of [`prove_evm`], as the verifiers on the EVM
+/// check it.
+pub fn verify_evm(
+    params: &ParamsKZG<Bn256>,
+    vk: &VerifyingKey<G1Affine>,
+    proof: &[u8],
+    instances: &[Vec<Fr>],
+) -> bool {
+    verify_with_transcript(
+        params,
+        vk,
+        proof,
+        instances,
+        Multiopen::Shplonk,
+        Transcript::Keccak256,
+    )
+}
+
+/// Verifies `proof`, which should have been proven with the same multiopen
+/// argument and transcript.
+pub fn verify_with_transcript(
+    params: &ParamsKZG<Bn256>,
+    vk: &VerifyingKey<G1Affine>,
+    proof: &[u8],
+    instances: &[Vec<Fr>],
+    multiopen: Multiopen,
+    transcript: Transcript,
+) -> bool {
+    type Blake2b<'proof> = Blake2bRead<&'proof [u8], G1Affine, Challenge255<G1Affine>>;
+    type Keccak256<'proof> = Keccak256Read<&'proof [u8], G1Affine, Challenge255<G1Affine>>;
+    let _span = info_span!("verify", bytes = proof.len(), %multiopen, %transcript).entered();
+    match (multiopen, transcript) {
+        (Multiopen::Shplonk, Transcript::Blake2b) => {
+            verify_with::<VerifierSHPLONK<'_, Bn256>, Blake2b>(params, vk, proof, instances)
+        }
+        (Multiopen::Shplonk, Transcript::Keccak256) => {
+            verify_with::<VerifierSHPLONK<'_, Bn256>, Keccak256>(params, vk, proof, instances)
+        }
+        (Multiopen::Gwc, Transcript::Blake2b) => {
+            verify_with::<VerifierGWC<'_, Bn256>, Blake2b>(params, vk, proof, instances)
+        }
+        (Multiopen::Gwc, Transcript::Keccak256) => {
+            verify_with::<VerifierGWC<'_, Bn256>, Keccak256>(params, vk, proof, instances)
+        }
+    }
+}
 
+fn verify_with<
+    'params,
+    'proof,
+    V: Verifier<'params, KZGCommitmentScheme<Bn256>>,
+    T: TranscriptReadBuffer<&'proof [u8], G1Affine, Challenge255<G1Affine>>,
+>(
+    params: &'params ParamsKZG<Bn256>,
+    vk: &VerifyingKey<G1Affine>,
+    proof: &'proof [u8],
+    instances: &[Vec<Fr>],
+) -> bool
+where
+    SingleStrategy<'params, Bn256>: VerificationStrategy<'params, KZGCommitmentScheme<Bn256>, V>,
+{
+    let instances = instances.iter().map(Vec::as_slice).collect::<Vec<_>>();
+    let mut transcript = T::init(proof);
+    verify_proof::<
+        KZGCommitmentScheme<Bn256>,
+        V,
+        Challenge255<G1Affine>,
+        T,
+        SingleStrategy<'params, Bn256>,
+    >(
+        params.verifier_params(),
+        vk,
+        SingleStrategy::new(params),
+        &[&instances],
+        &mut transcript,
+    )
+    .is_ok()
+}
+
+/// Sizes of what the verifier of a proof of a circuit receives, which make
+/// up the cost of its verification by the zkEVM along with the verifier time.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ProofSizes {
+    pub degree: u32,
+    pub scheme: ProvingScheme,
+    pub proof_bytes: usize,
+    /// Length of the verifying key serialized in the raw bytes format.
+    pub vk_bytes: usize,
+    /// Number of instance values, over all the instance columns.
+    pub instances: usize,
+}
+
+impl ProofSizes {
+    /// Sizes of `proof`, with a verifying key of `vk_bytes` bytes, see
+    /// [`vk_bytes`].
+    pub fn new<F>(
+        degree: u32,
+        scheme: ProvingScheme,
+        vk_bytes: usize,
+        proof: &[u8],
+        instances: &[Vec<F>],
+    ) -> Self {
+        Self {
+            degree,
+            scheme,
+            proof_bytes: proof.len(),
+            vk_bytes,
+            instances: instances.iter().map(Vec::len).sum(),
+        }
+    }
+}
+
+/// Length of `vk` serialized in the raw bytes format.
+pub fn vk_bytes(vk: &VerifyingKey<G1Affine>) -> usize {
+    let mut bytes = vec![];
+    vk.write(&mut bytes, SerdeFormat::RawBytes)
+        .expect("writing to a vector should not fail");
+    bytes.len()
+}
+
+impl fmt::Display for ProofSizes {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "with degree = {} and {}: proof of {} bytes, verifying key of {} bytes, {} instance values",
+            self.degree, self.scheme, self.proof_bytes, self.vk_bytes, self.instances
+        )
+    }
+}
+
+/// The system allocator, counting the bytes allocated at any time and their
+/// peak since [`peak_memory`] last reset it. The bench targets install it as
+/// their global allocator, so that they print the [`PeakMemory`] of their
+/// proofs:
+///
+/// ```ignore
+/// #[global_allocator]
+/// static ALLOCATOR: PeakAllocator = PeakAllocator;
+/// ```
+///
+/// The crate does not install it itself, which would impose it on every
+/// binary depending on the crate, so [`peak_memory`] counts nothing in a
+/// binary which does not.
+pub struct PeakAllocator;
+
+/// Bytes allocated through [`PeakAllocator`], and their peak.
+static ALLOCATED: AtomicUsize = AtomicUsize::new(0);
+static PEAK: AtomicUsize = AtomicUsize::new(0);
+
+impl PeakAllocator {
+    fn add(&self, size: usize) {
+        let allocated = ALLOCATED.fetch_add(size, Ordering::Relaxed) + size;
+        PEAK.fetch_max(allocated, Ordering::Relaxed);
+    }
+
+    fn sub(&self, size: usize) {
+        ALLOCATED.fetch_sub(size, Ordering::Relaxed);
+    }
+}
+
+unsafe impl GlobalAlloc for PeakAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc(layout);
+        if !ptr.is_null() {
+            self.add(layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc_zeroed(layout);
+        if !ptr.is_null() {
+            self.add(layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+        self.sub(layout.size());
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let new_ptr = System.realloc(ptr, layout, new_size);
+        if !new_ptr.is_null() {
+            self.add(new_size);
+            self.sub(layout.size());
+        }
+        new_ptr
+    }
+}
+
+/// Runs `f` and returns its result along with the peak of the bytes it
+/// allocated on top of those allocated before it, or `0` if the
+/// [`PeakAllocator`] is not the global allocator. The allocations are those
+/// of the whole process, so `f` should not run alongside other work.
+pub fn peak_memory<T>(f: impl FnOnce() -> T) -> (T, usize) {
+    let before = ALLOCATED.load(Ordering::Relaxed);
+    PEAK.store(before, Ordering::Relaxed);
+    let result = f();
+    let peak = PEAK.load(Ordering::Relaxed);
+    (result, peak.saturating_sub(before))
+}
+
+/// Wall time of a single run of `f`.
+pub fn elapsed<T>(f: impl FnOnce() -> T) -> Duration {
+    let start = Instant::now();
+    f();
+    start.elapsed()
+}
+
+/// Peak memory allocated by the prover of a circuit.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PeakMemory {
+    pub degree: u32,
+    pub scheme: ProvingScheme,
+    /// Peak bytes allocated by `keygen_vk` and `keygen_pk`.
+    pub keygen_bytes: usize,
+    /// Peak bytes allocated by `create_proof`.
+    pub proof_bytes: usize,
+}
+
+impl fmt::Display for PeakMemory {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mib = |bytes: usize| bytes as f64 / (1 << 20) as f64;
+        write!(
+            f,
+            "with degree = {} and {}: peak memory of {:.1} MiB for keygen, {:.1} MiB for the proof",
+            self.degree,
+            self.scheme,
+            mib(self.keygen_bytes),
+            mib(self.proof_bytes)
+        )
+    }
+}
+
+/// Markdown table comparing the sizes and the peak memory of the proofs of a
+/// circuit at every benchmarked degree and proving scheme, one row each.
+pub fn report(rows: &[(ProofSizes, PeakMemory)]) -> String {
+    let mib = |bytes: usize| format!("{:.1}", bytes as f64 / (1 << 20) as f64);
+    let mut lines = vec![
+        String::from(
+            "| degree | scheme | proof bytes | vk bytes | instances | keygen MiB | proof MiB |",
+        ),
+        String::from("| --- | --- | --- | --- | --- | --- | --- |"),
+    ];
+    lines.extend(rows.iter().map(|(sizes, memory)| {
+        format!(
+            "| {} | {} | {} | {} | {} | {} | {} |",
+            sizes.degree,
+            sizes.scheme,
+            sizes.proof_bytes,
+            sizes.vk_bytes,
+            sizes.instances,
+            mib(memory.keygen_bytes),
+            mib(memory.proof_bytes)
+        )
+    }));
+    lines.join("\n")
+}
+
+/// Benchmarks the key generation of the circuit built by `circuit` at
+/// `degree` within `group`, then its proof and the verification of the proof
+/// with every multiopen argument, at the phases selected by [`phases`], and
+/// prints and returns their sizes and peak memory. The verification is also
+/// benchmarked in batches, see [`bench_batch_verify`], and both with the
+/// other transcript than [`Transcript::DEFAULT`], see [`bench_transcripts`].
+pub fn bench_prover<C: Circuit<Fr>>(
+    group: &mut BenchmarkGroup<'_, WallTime>,
+    name: &str,
+    params: &ParamsKZG<Bn256>,
+    circuit: impl Fn() -> C,
+    instances: &[Vec<Fr>],
+) -> Vec<(ProofSizes, PeakMemory)> {
+    let degree = params.k();
+    let phases = phases();
+    if !phases.iter().any(Phase::proves) {
+        return vec![];
+    }
+    if phases.contains(&Phase::Keygen) {
+        group.bench_function(BenchmarkId::new("keygen", degree), |b| {
+            b.iter(|| keygen(params, &circuit()))
+        });
+    }
+    let (pk, keygen_bytes) = peak_memory(|| keygen(params, &circuit()));
+    if phases.contains(&Phase::Serialize) {
+        bench_artifacts::<_, _, C>(group, name, params, &pk);
+    }
+    if !phases.contains(&Phase::Proof) && !phases.contains(&Phase::Verify) {
+        return vec![];
+    }
+
+    let mut rows = vec![];
+    for multiopen in Multiopen::ALL {
+        if phases.contains(&Phase::Proof) {
+            group.bench_function(
+                BenchmarkId::new(format!("proof {multiopen}"), degree),
+                |b| b.iter(|| prove(params, &pk, circuit(), instances, multiopen)),
+            );
+        }
+        let (proof, proof_bytes) = profiled(&format!("{name} proof {multiopen} {degree}"), || {
+            peak_memory(|| prove(params, &pk, circuit(), instances, multiopen))
+        });
+
+        if phases.contains(&Phase::Verify) {
+            group.bench_function(
+                BenchmarkId::new(format!("verify {multiopen}"), degree),
+                |b| {
+                    b.iter(|| {
+                        assert!(
+                            verify(params, pk.get_vk(), &proof, instances, multiopen),
+                            "failed to verify bench circuit"
+                        )
+                    })
+                },
+            );
+            bench_batch_verify(
+                group,
+                name,
+                params,
+                pk.get_vk(),
+                &proof,
+                instances,
+                multiopen,
+            );
+        }
+
+        let scheme = ProvingScheme::Kzg(multiopen);
+        let sizes = ProofSizes::new(degree, scheme, vk_bytes(pk.get_vk()), &proof, instances);
+        let memory = PeakMemory {
+            degree,
+            scheme,
+            keygen_bytes,
+            proof_bytes,
+        };
+        println!("{name} {sizes}");
+        println!("{name} {memory}");
+        rows.push((sizes, memory));
+    }
+    bench_transcripts(group, name, params, &pk, &circuit, instances);
+    rows
+}
+
+/// Synthesizes the witness of `circuit` at `degree` and checks that it
+/// satisfies the constraints, panicking with the failures otherwise.
+pub fn mock_verify<F: FieldExt, C: Circuit<F>>(degree: u32, circuit: &C, instances: Vec<Vec<F>>) {
+    let _span = info_span!("mock", degree).entered();
+    MockProver::run(degree, circuit, instances)
+        .expect("witness synthesis should not fail")
+        .assert_satisfied();
+}
+
+/// Benchmarks the synthesis of the witness of the circuit built by `circuit`
+/// at `degree` within `group`, and its check by [`mock_verify`], over any
+/// field, as they do not depend on the commitment scheme.
+pub fn bench_witness<F: FieldExt, C: Circuit<F>>(
+    group: &mut BenchmarkGroup<'_, WallTime>,
+    degree: u32,
+    circuit: impl Fn() -> C,
+    instances: &[Vec<F>],
+) {
+    let phases = phases();
+    if phases.contains(&Phase::Witness) {
+        group.bench_function(BenchmarkId::new("witness", degree), |b| {
+            b.iter(|| {
+                MockProver::run(degree, &circuit(), instances.to_vec())
+                    .expect("witness synthesis should not fail")
+            })
+        });
+    }
+    if phases.contains(&Phase::Mock) {
+        group.bench_function(BenchmarkId::new("mock", degree), |b| {
+            b.iter(|| mock_verify(degree, &circuit(), instances.to_vec()))
+        });
+    }
+}
+
+/// Benchmarks every phase of the proof of the circuit built by `circuit` at
+/// every degree of `degrees`, as the group `name`, and prints and returns the
+/// sizes and the peak memory of its proof at each, with every multiopen
+/// argument. The witness and the proof are also benchmarked at the thread
+/// counts of [`thread_counts`], if any.
+pub fn bench_circuit<C: Circuit<Fr> + Send>(
+    c: &mut Criterion,
+    name: &str,
+    degrees: &[u32],
+    circuit: impl Fn() -> C + Sync,
+    instances: Vec<Vec<Fr>>,
+) -> Vec<(ProofSizes, PeakMemory)> {
+    let mut group = c.benchmark_group(name);
+    let phases = phases();
+    let mut rows = vec![];
+    for &degree in degrees {
+        if phases.contains(&Phase::Cost) {
+            estimate::<G1, _>(name, degree, &circuit());
+        }
+        bench_witness(&mut group, degree, &circuit, &instances);
+        if !phases.iter().any(Phase::proves) {
+            continue;
+        }
+
+        let params = setup(degree);
+        rows.extend(bench_prover(
+            &mut group, name, &params, &circuit, &instances,
+        ));
+        if let Some(counts) = thread_counts() {
+            bench_threads(&mut group, name, &params, &circuit, &instances, &counts);
+        }
+    }
+    group.finish();
+    if !rows.is_empty() {
+        println!("{name}\n{}", report(&rows));
+    }
+    rows
+}
+
+/// Circuit of a bench target, built over any field so that
+/// [`run_full_bench`] benchmarks it over every commitment scheme.
+pub trait BenchCircuit {
+    /// Circuit built over the field `F`.
+    type Circuit<F: FieldExt>: Circuit<F> + Send;
+
+    /// Name of the benchmark groups of the circuit.
+    const NAME: &'static str;
+
+    /// Smallest degree the circuit fits in.
+    fn min_k(&self) -> u32;
+
+    /// Builder of the circuit over `F`, called for every run of a phase: it
+    /// should only clone a witness prepared beforehand.
+    fn circuit<F: FieldExt>(&self) -> impl Fn() -> Self::Circuit<F> + Sync;
+
+    /// Instance values of the circuit, none by default.
+    fn instances<F: FieldExt>(&self) -> Vec<Vec<F>> {
+        vec![]
+    }
+}
+
+/// Benchmarks every phase of the proof of `bench` at the [`degrees`] of its
+/// [`BenchCircuit::min_k`], see [`bench_circuit`], and over IPA as well with
+/// the `ipa` feature. Returns the degrees, for the other benchmarks of the
+/// target.
+pub fn run_full_bench<B: BenchCircuit>(c: &mut Criterion, bench: &B) -> Vec<u32> {
+    let degrees = degrees(bench.min_k());
+    bench_circuit(
+        c,
+        B::NAME,
+        &degrees,
+        bench.circuit::<Fr>(),
+        bench.instances(),
+    );
+    #[cfg(feature = "ipa")]
+    ipa::bench_circuit(c, B::NAME, &degrees, bench.circuit(), bench.instances());
+    degrees
+}
+
+/// A SHA2-256 test circuit of a single call, and the smallest degree it fits
+/// in, for the tests of the proving helpers.
 #[cfg(test)]
-pub mod ripemd160_circuit_bench;
+pub(crate) fn sha2_test_circuit() -> (u32, impl Fn() -> sha2_256_circuit::Sha2TestCircuit<Fr>) {
+    use gadgets::HashCircuitExt;
+    use sha2_256_circuit::{Sha2Chip, Sha2TestCircuit, Sha2Witness};
+
+    let (inputs, outputs) = sha2_256_circuit::dev::INPUTS_OUTPUTS.clone();
+    let (inputs, outputs) = (inputs[..1].to_vec(), outputs[..1].to_vec());
+    let k = Sha2Chip::<Fr>::min_k(&Sha2Witness {
+        inputs: inputs.clone(),
+        _marker: std::marker::PhantomData,
+    });
+    let circuit = move || Sha2TestCircuit {
+        inputs: inputs.clone(),
+        outputs: outputs.clone(),
+        _marker: std::marker::PhantomData,
+    };
+    (k, circuit)
+}
 
 #[cfg(test)]
-pub mod sha2_256_circuit_bench;
+#[global_allocator]
+static ALLOCATOR: PeakAllocator = PeakAllocator;
 
 #[cfg(test)]
-mod constants;
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_setup_cache() {
+        let path = params_dir().join("kzg_bn256_4.params");
+        let params = setup(4);
+        assert!(path.exists());
+        assert_eq!(params.k(), 4);
+
+        // the cached params are read back.
+        assert_eq!(setup(4).k(), 4);
+    }
+
+    #[test]
+    fn test_proof_sizes() {
+        let (k, circuit) = sha2_test_circuit();
+        let params = setup(k);
+        let pk = keygen(&params, &circuit());
+        let instances = vec![vec![Fr::from(1); 3], vec![Fr::from(0)]];
+        let scheme = ProvingScheme::Kzg(Multiopen::Gwc);
+        let sizes = ProofSizes::new(8, scheme, vk_bytes(pk.get_vk()), &[0; 42], &instances);
+        assert_eq!(sizes.proof_bytes, 42);
+        assert_eq!(sizes.instances, 4);
+        assert!(sizes.vk_bytes > 0);
+        assert!(sizes
+            .to_string()
+            .starts_with("with degree = 8 and KZG GWC: proof of 42 bytes"));
+    }
+
+    #[test]
+    fn test_multiopen() {
+        let (k, circuit) = sha2_test_circuit();
+        let params = setup(k);
+        let pk = keygen(&params, &circuit());
+        for multiopen in Multiopen::ALL {
+            let proof = prove(&params, &pk, circuit(), &[], multiopen);
+            assert!(verify(&params, pk.get_vk(), &proof, &[], multiopen));
+        }
+    }
+
+    #[test]
+    fn test_parse_phases() {
+        assert_eq!(parse_phases("keygen"), Some(vec![Phase::Keygen]));
+        assert_eq!(
+            parse_phases("witness, verify"),
+            Some(vec![Phase::Witness, Phase::Verify])
+        );
+        assert_eq!(parse_phases("keygen,setup"), None);
+        assert!(!Phase::Witness.proves() && !Phase::Cost.proves() && !Phase::Mock.proves());
+    }
+
+    #[test]
+    fn test_parse_degrees() {
+        assert_eq!(parse_degrees("17"), Some(vec![17]));
+        assert_eq!(parse_degrees("18..21"), Some(vec![18, 19, 20]));
+        assert_eq!(parse_degrees("18..=20"), Some(vec![18, 19, 20]));
+        assert_eq!(parse_degrees("12, 16..=17"), Some(vec![12, 16, 17]));
+        assert_eq!(parse_degrees("12..12"), None);
+        assert_eq!(parse_degrees("k"), None);
+    }
+
+    #[test]
+    fn test_peak_memory() {
+        let (bytes, peak) = peak_memory(|| {
+            let large = vec![0u8; 1 << 20];
+            drop(large);
+            vec![1u8; 1 << 10]
+        });
+        assert_eq!(bytes.len(), 1 << 10);
+        // the peak covers the vector freed within the closure, up to what
+        // the tests running alongside free meanwhile.
+        assert!(peak >= 1 << 19);
+    }
+}
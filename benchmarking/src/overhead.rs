@@ -0,0 +1,116 @@
+//! Overhead of proving the hashes of a circuit over computing them natively,
+//! the headline number of the cost of a circuit: the time of its proof
+//! divided by the time the native implementation of its crate takes to hash
+//! the same inputs.
+
+use std::{
+    fmt,
+    time::{Duration, Instant},
+};
+
+use criterion::Criterion;
+use halo2_proofs::{halo2curves::bn256::Fr, plonk::Circuit};
+
+use crate::{elapsed, keygen, phases, prove, setup, Multiopen, Phase};
+
+/// Wall time the native hashing is repeated for, so that a mean over many
+/// runs measures it despite it taking microseconds.
+const NATIVE_TIME: Duration = Duration::from_millis(100);
+
+/// Time of a proof, with the SHPLONK multiopen argument, against the time of
+/// the native hashing of its inputs.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Overhead {
+    pub degree: u32,
+    pub native: Duration,
+    pub proof: Duration,
+}
+
+impl Overhead {
+    /// Time of the proof divided by the time of the native hashing.
+    pub fn factor(&self) -> f64 {
+        self.proof.as_secs_f64() / self.native.as_secs_f64()
+    }
+}
+
+impl fmt::Display for Overhead {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "with degree = {}: proof overhead of {:.0}x over native hashing ({:.3} s against {:.3} µs)",
+            self.degree,
+            self.factor(),
+            self.proof.as_secs_f64(),
+            self.native.as_secs_f64() * 1e6
+        )
+    }
+}
+
+/// Mean time of a run of `f`, over as many runs as last [`NATIVE_TIME`].
+fn mean_time(mut f: impl FnMut()) -> Duration {
+    let start = Instant::now();
+    let mut runs = 0;
+    while start.elapsed() < NATIVE_TIME {
+        f();
+        runs += 1;
+    }
+    start.elapsed() / runs
+}
+
+/// Benchmarks `native`, the native hashing of the inputs of the circuit
+/// built by `circuit`, as the group `name` suffixed with `overhead`, and
+/// prints and returns the overhead of the proof of the circuit over it at
+/// every degree of `degrees`. Nothing is benchmarked unless the proof is
+/// selected by [`phases`].
+pub fn bench_overhead<C: Circuit<Fr>>(
+    c: &mut Criterion,
+    name: &str,
+    degrees: &[u32],
+    circuit: impl Fn() -> C,
+    instances: &[Vec<Fr>],
+    native: impl Fn(),
+) -> Vec<Overhead> {
+    if !phases().contains(&Phase::Proof) {
+        return vec![];
+    }
+    let name = format!("{name} overhead");
+    let mut group = c.benchmark_group(&name);
+    group.bench_function("native", |b| b.iter(&native));
+    group.finish();
+
+    let native = mean_time(native);
+    degrees
+        .iter()
+        .map(|&degree| {
+            let params = setup(degree);
+            let pk = keygen(&params, &circuit());
+            let overhead = Overhead {
+                degree,
+                native,
+                proof: elapsed(|| prove(&params, &pk, circuit(), instances, Multiopen::Shplonk)),
+            };
+            println!("{name} {overhead}");
+            overhead
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_overhead() {
+        let overhead = Overhead {
+            degree: 17,
+            native: Duration::from_micros(2),
+            proof: Duration::from_secs(3),
+        };
+        assert_eq!(overhead.factor().round(), 1_500_000.0);
+        assert_eq!(
+            overhead.to_string(),
+            "with degree = 17: proof overhead of 1500000x over native hashing (3.000 s against 2.000 µs)"
+        );
+        assert!(mean_time(|| ()) < NATIVE_TIME);
+    }
+}
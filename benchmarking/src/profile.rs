@@ -0,0 +1,92 @@
+//! Flamegraphs of the proofs of the benchmarks, enabled by the `profile`
+//! feature, to see which phases of the prover, e.g. the FFTs, the MSMs or
+//! the lookup arguments, dominate the proof of a circuit.
+//!
+//! The untimed proof of every group is sampled by `pprof`, and its
+//! flamegraph is written as an SVG to the `profiles` directory of the target
+//! directory, named after the group, the proof and the degree:
+//!
+//! ```text
+//! cargo bench --features profile --bench sha2_256_circuit
+//! open ../target/profiles/SHA2-256_Circuit_proof_SHPLONK_12.svg
+//! ```
+//!
+//! Without the feature, [`profiled`] only runs the proof.
+
+use std::path::PathBuf;
+
+#[cfg(feature = "profile")]
+use std::fs::{self, File};
+
+use crate::target_dir;
+
+/// Frequency of the samples of the profiler, in Hz.
+#[cfg(feature = "profile")]
+const FREQUENCY: i32 = 1000;
+
+/// Directory of the flamegraphs.
+pub fn profiles_dir() -> PathBuf {
+    target_dir().join("profiles")
+}
+
+/// Path of the flamegraph of the run `name`, whose characters that are not
+/// alphanumeric or a dash are replaced by underscores.
+pub fn profile_path(name: &str) -> PathBuf {
+    let name = name
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect::<String>();
+    profiles_dir().join(format!("{name}.svg"))
+}
+
+/// Runs `f`, and writes the flamegraph of its run to [`profile_path`] of
+/// `name` with the `profile` feature.
+#[cfg(feature = "profile")]
+pub fn profiled<T>(name: &str, f: impl FnOnce() -> T) -> T {
+    let guard = pprof::ProfilerGuardBuilder::default()
+        .frequency(FREQUENCY)
+        .blocklist(&["libc", "libgcc", "pthread", "vdso"])
+        .build()
+        .expect("Cannot start the profiler");
+    let result = f();
+
+    let path = profile_path(name);
+    let report = guard.report().build().expect("Cannot build the profile");
+    fs::create_dir_all(profiles_dir())
+        .and_then(|()| File::create(&path))
+        .map_err(|err| err.to_string())
+        .and_then(|file| report.flamegraph(file).map_err(|err| err.to_string()))
+        .expect("Cannot write the flamegraph");
+    println!("{name} profiled in {}", path.display());
+    result
+}
+
+/// Runs `f`, and writes the flamegraph of its run to [`profile_path`] of
+/// `name` with the `profile` feature.
+#[cfg(not(feature = "profile"))]
+pub fn profiled<T>(_name: &str, f: impl FnOnce() -> T) -> T {
+    f()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_profiled() {
+        let path = profile_path("SHA2-256 Circuit proof SHPLONK 8");
+        assert_eq!(
+            path.file_name().unwrap(),
+            "SHA2-256_Circuit_proof_SHPLONK_8.svg"
+        );
+        assert_eq!(profiled("test profiled", || 42), 42);
+        #[cfg(feature = "profile")]
+        assert!(profile_path("test profiled").exists());
+    }
+}
@@ -0,0 +1,216 @@
+//! Benchmarks of the hash circuits at growing inputs, to characterize their
+//! throughput rather than their cost on the fixed test vectors.
+//!
+//! Every [`HashWorkload`] hashes a number of messages of a number of bytes,
+//! as given by the `HASH_WORKLOADS` env var, e.g. `16x64,1x65536`, or
+//! [`HASH_WORKLOADS`] if unset. The circuit of a workload is proven at the
+//! smallest degree it fits in, and criterion reports the time per hashed
+//! byte of its witness and its proof, as the throughput of the group is the
+//! number of bytes of the workload.
+
+use std::{env::var, fmt};
+
+use criterion::{BenchmarkId, Criterion, Throughput};
+use halo2_proofs::{dev::MockProver, halo2curves::bn256::Fr, plonk::Circuit};
+use rand::RngCore;
+
+use crate::{keygen, mock_verify, phases, prove, rng, setup, Multiopen, Phase};
+
+/// Messages of the same length hashed by a circuit.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct HashWorkload {
+    pub messages: usize,
+    pub message_bytes: usize,
+}
+
+/// Workloads of the hash circuits if the `HASH_WORKLOADS` env var is unset:
+/// growing numbers of single-block messages, then a single growing message.
+pub const HASH_WORKLOADS: [HashWorkload; 6] = [
+    HashWorkload::new(1, 64),
+    HashWorkload::new(16, 64),
+    HashWorkload::new(256, 64),
+    HashWorkload::new(1, 1 << 10),
+    HashWorkload::new(1, 1 << 14),
+    HashWorkload::new(1, 1 << 16),
+];
+
+impl HashWorkload {
+    pub const fn new(messages: usize, message_bytes: usize) -> Self {
+        Self {
+            messages,
+            message_bytes,
+        }
+    }
+
+    /// Bytes hashed over all the messages.
+    pub fn total_bytes(&self) -> usize {
+        self.messages * self.message_bytes
+    }
+
+    /// The messages, of random bytes drawn from the seeded [`rng`].
+    pub fn inputs(&self) -> Vec<Vec<u8>> {
+        let mut rng = rng();
+        (0..self.messages)
+            .map(|_| {
+                let mut message = vec![0; self.message_bytes];
+                rng.fill_bytes(&mut message);
+                message
+            })
+            .collect()
+    }
+}
+
+impl fmt::Display for HashWorkload {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}x{}", self.messages, self.message_bytes)
+    }
+}
+
+/// Workloads of the `HASH_WORKLOADS` env var, or [`HASH_WORKLOADS`] if unset.
+pub fn hash_workloads() -> Vec<HashWorkload> {
+    var("HASH_WORKLOADS")
+        .map(|workloads| {
+            parse_hash_workloads(&workloads).expect("Cannot parse HASH_WORKLOADS env var")
+        })
+        .unwrap_or_else(|_| HASH_WORKLOADS.to_vec())
+}
+
+/// Parses a comma-separated list of workloads, as they are displayed.
+fn parse_hash_workloads(workloads: &str) -> Option<Vec<HashWorkload>> {
+    workloads
+        .split(',')
+        .map(|workload| {
+            let (messages, message_bytes) = workload.trim().split_once('x')?;
+            Some(HashWorkload::new(
+                messages.parse().ok()?,
+                message_bytes.parse().ok()?,
+            ))
+        })
+        .collect()
+}
+
+/// Layout of the circuit of a hash workload.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ScalingRow {
+    pub workload: HashWorkload,
+    /// Number of compressed blocks, over all the padded messages.
+    pub blocks: usize,
+    /// Smallest degree the circuit fits in, i.e. the circuit uses at most
+    /// `2^k` rows.
+    pub k: u32,
+}
+
+/// Markdown table of the layout of the circuit of every workload, one row
+/// each.
+pub fn scaling_report(rows: &[ScalingRow]) -> String {
+    let mut lines = vec![
+        String::from("| workload | bytes | blocks | k | rows |"),
+        String::from("| --- | --- | --- | --- | --- |"),
+    ];
+    lines.extend(rows.iter().map(|row| {
+        format!(
+            "| {} | {} | {} | {} | {} |",
+            row.workload,
+            row.workload.total_bytes(),
+            row.blocks,
+            row.k,
+            1u64 << row.k
+        )
+    }));
+    lines.join("\n")
+}
+
+/// Benchmarks the witness, its check and the proof of the circuit built by
+/// `circuit` from the messages of every workload of [`hash_workloads`], as
+/// the group `name` suffixed with `scaling`, at the phases selected by
+/// [`phases`], and prints and returns the layout of the circuit of each. `min_k` is the
+/// smallest degree the messages fit in, and `pad` pads a message to blocks
+/// of `block_bytes` bytes.
+pub fn bench_hash_scaling<C: Circuit<Fr>>(
+    c: &mut Criterion,
+    name: &str,
+    block_bytes: usize,
+    pad: impl Fn(&[u8]) -> Vec<u8>,
+    min_k: impl Fn(&[Vec<u8>]) -> u32,
+    circuit: impl Fn(Vec<Vec<u8>>) -> C,
+) -> Vec<ScalingRow> {
+    let name = format!("{name} scaling");
+    let mut group = c.benchmark_group(&name);
+    let phases = phases();
+    let mut rows = vec![];
+    for workload in hash_workloads() {
+        let inputs = workload.inputs();
+        let k = min_k(&inputs);
+        group.throughput(Throughput::Bytes(workload.total_bytes() as u64));
+
+        if phases.contains(&Phase::Witness) {
+            group.bench_function(BenchmarkId::new("witness", workload), |b| {
+                b.iter(|| {
+                    MockProver::run(k, &circuit(inputs.clone()), vec![])
+                        .expect("witness synthesis should not fail")
+                })
+            });
+        }
+        if phases.contains(&Phase::Mock) {
+            group.bench_function(BenchmarkId::new("mock", workload), |b| {
+                b.iter(|| mock_verify(k, &circuit(inputs.clone()), vec![]))
+            });
+        }
+        if phases.contains(&Phase::Proof) {
+            let params = setup(k);
+            let pk = keygen(&params, &circuit(inputs.clone()));
+            group.bench_function(BenchmarkId::new("proof", workload), |b| {
+                b.iter(|| {
+                    prove(
+                        &params,
+                        &pk,
+                        circuit(inputs.clone()),
+                        &[],
+                        Multiopen::Shplonk,
+                    )
+                })
+            });
+        }
+
+        rows.push(ScalingRow {
+            workload,
+            blocks: inputs
+                .iter()
+                .map(|input| pad(input).len() / block_bytes)
+                .sum(),
+            k,
+        });
+    }
+    group.finish();
+    println!("{name}\n{}", scaling_report(&rows));
+    rows
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hash_workloads() {
+        assert_eq!(
+            parse_hash_workloads("16x64, 1x65536"),
+            Some(vec![HashWorkload::new(16, 64), HashWorkload::new(1, 65536)])
+        );
+        assert_eq!(parse_hash_workloads("16"), None);
+
+        let workload = HashWorkload::new(3, 100);
+        let inputs = workload.inputs();
+        assert_eq!(inputs.len(), 3);
+        assert!(inputs.iter().all(|input| input.len() == 100));
+        assert_ne!(inputs[0], inputs[1]);
+        assert_eq!(workload.total_bytes(), 300);
+        assert_eq!(workload.to_string(), "3x100");
+
+        let report = scaling_report(&[ScalingRow {
+            workload,
+            blocks: 6,
+            k: 8,
+        }]);
+        assert_eq!(report.lines().nth(2), Some("| 3x100 | 300 | 6 | 8 | 256 |"));
+    }
+}
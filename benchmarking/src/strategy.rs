@@ -0,0 +1,97 @@
+//! Cost of the strategies of proving the hash workloads of a block: all of
+//! them in a single proof of the precompile super-circuit, or a proof of the
+//! circuit of every precompile, aggregated by a proof of the
+//! `AggregationCircuit` verifying them in-circuit.
+
+use std::{fmt, time::Duration};
+
+/// Strategy of proving the circuits of several precompiles.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Strategy {
+    /// A single proof of the super-circuit.
+    SuperCircuit,
+    /// A proof of every circuit, and a proof of the `AggregationCircuit`
+    /// aggregating them.
+    SeparateProofs,
+}
+
+impl Strategy {
+    pub const ALL: [Self; 2] = [Self::SuperCircuit, Self::SeparateProofs];
+}
+
+impl fmt::Display for Strategy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::SuperCircuit => write!(f, "super-circuit"),
+            Self::SeparateProofs => write!(f, "separate proofs, aggregated"),
+        }
+    }
+}
+
+/// Total time of the proofs of a strategy at a degree, the size of the proof
+/// the verifier receives and the time of its verification.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct StrategyCost {
+    pub degree: u32,
+    pub strategy: Strategy,
+    pub prover: Duration,
+    pub proof_bytes: usize,
+    pub verify: Duration,
+}
+
+impl fmt::Display for StrategyCost {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "with degree = {} and {}: proved in {:.3} s, proof of {} bytes verified in {:.3} ms",
+            self.degree,
+            self.strategy,
+            self.prover.as_secs_f64(),
+            self.proof_bytes,
+            self.verify.as_secs_f64() * 1e3
+        )
+    }
+}
+
+/// Markdown table of the costs of the strategies, one row each.
+pub fn strategy_report(costs: &[StrategyCost]) -> String {
+    let mut lines = vec![
+        "| k | strategy | prover (s) | proof bytes | verify (ms) |".to_string(),
+        "| --- | --- | --- | --- | --- |".to_string(),
+    ];
+    lines.extend(costs.iter().map(|cost| {
+        format!(
+            "| {} | {} | {:.3} | {} | {:.3} |",
+            cost.degree,
+            cost.strategy,
+            cost.prover.as_secs_f64(),
+            cost.proof_bytes,
+            cost.verify.as_secs_f64() * 1e3
+        )
+    }));
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_strategy_report() {
+        let cost = StrategyCost {
+            degree: 18,
+            strategy: Strategy::SeparateProofs,
+            prover: Duration::from_millis(12_500),
+            proof_bytes: 4_000,
+            verify: Duration::from_micros(5_250),
+        };
+        assert_eq!(
+            cost.to_string(),
+            "with degree = 18 and separate proofs, aggregated: proved in 12.500 s, proof of 4000 bytes verified in 5.250 ms"
+        );
+        assert_eq!(
+            strategy_report(&[cost]).lines().nth(2),
+            Some("| 18 | separate proofs, aggregated | 12.500 | 4000 | 5.250 |")
+        );
+    }
+}
@@ -0,0 +1,176 @@
+//! Scaling of the prover with the number of threads, to pick the hardware of
+//! a prover and to spot the serial phases of the witness assignment.
+//!
+//! The mode is enabled by the `THREAD_COUNTS` env var, a comma-separated
+//! list of thread counts, e.g. `THREAD_COUNTS=1,2,4,8`. The witness and the
+//! proof of every circuit are then benchmarked again on a thread pool of
+//! each count, as `witness {n} threads` and `proof {n} threads`, and the
+//! group prints the speedup of each count over the first one. Only the
+//! phases selected by [`phases`] are benchmarked and timed, e.g.
+//! `PHASES=witness` neither generates the keys nor proves.
+//!
+//! The pools are built by the harness, as the `RAYON_NUM_THREADS` env var only
+//! sizes the global pool of rayon, once per process, whereas a pool installed
+//! around a phase runs all the parallel iterators of the phase.
+
+use std::{env::var, time::Duration};
+
+use criterion::{measurement::WallTime, BenchmarkGroup, BenchmarkId};
+use halo2_proofs::{
+    dev::MockProver,
+    halo2curves::bn256::{Bn256, Fr},
+    plonk::Circuit,
+    poly::{commitment::Params, kzg::commitment::ParamsKZG},
+};
+use rayon::ThreadPoolBuilder;
+
+use crate::{elapsed, keygen, phases, prove, Multiopen, Phase};
+
+/// Thread counts of the `THREAD_COUNTS` env var, `None` if it is unset.
+pub fn thread_counts() -> Option<Vec<usize>> {
+    var("THREAD_COUNTS")
+        .ok()
+        .map(|counts| parse_thread_counts(&counts).expect("Cannot parse THREAD_COUNTS env var"))
+}
+
+/// Parses a comma-separated list of non-zero thread counts.
+fn parse_thread_counts(counts: &str) -> Option<Vec<usize>> {
+    counts
+        .split(',')
+        .map(|count| count.trim().parse().ok().filter(|&count| count > 0))
+        .collect()
+}
+
+/// Time of the phases of the proof of a circuit on a pool of `threads`
+/// threads, each measured over a single run, `None` if the phase is not
+/// selected.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ThreadScaling {
+    pub degree: u32,
+    pub threads: usize,
+    pub witness: Option<Duration>,
+    pub proof: Option<Duration>,
+}
+
+/// Markdown table of the time of the phases at every thread count, along
+/// with their speedup over the first row, `-` for the phases not timed.
+pub fn thread_report(rows: &[ThreadScaling]) -> String {
+    let cells = |base: Option<Duration>, time: Option<Duration>| match (base, time) {
+        (Some(base), Some(time)) => format!(
+            "{:.3} | {:.2}",
+            time.as_secs_f64(),
+            base.as_secs_f64() / time.as_secs_f64()
+        ),
+        _ => String::from("- | -"),
+    };
+    let mut lines = vec![
+        String::from("| degree | threads | witness s | speedup | proof s | speedup |"),
+        String::from("| --- | --- | --- | --- | --- | --- |"),
+    ];
+    if let Some(base) = rows.first() {
+        lines.extend(rows.iter().map(|row| {
+            format!(
+                "| {} | {} | {} | {} |",
+                row.degree,
+                row.threads,
+                cells(base.witness, row.witness),
+                cells(base.proof, row.proof)
+            )
+        }));
+    }
+    lines.join("\n")
+}
+
+/// Benchmarks the witness and the proof of the circuit built by `circuit`
+/// at the degree of `params` within `group` on a pool of each of `counts`
+/// threads, with the keys generated once beforehand if the proof is
+/// selected, at the phases selected by [`phases`], and prints and returns
+/// their time at each count.
+pub fn bench_threads<C: Circuit<Fr> + Send>(
+    group: &mut BenchmarkGroup<'_, WallTime>,
+    name: &str,
+    params: &ParamsKZG<Bn256>,
+    circuit: impl Fn() -> C + Sync,
+    instances: &[Vec<Fr>],
+    counts: &[usize],
+) -> Vec<ThreadScaling> {
+    let degree = params.k();
+    let phases = phases();
+    let pk = phases
+        .contains(&Phase::Proof)
+        .then(|| keygen(params, &circuit()));
+    let mut rows = vec![];
+    for &threads in counts {
+        let pool = ThreadPoolBuilder::new()
+            .num_threads(threads)
+            .build()
+            .expect("Cannot build the thread pool");
+        let witness = || {
+            pool.install(|| {
+                MockProver::run(degree, &circuit(), instances.to_vec())
+                    .expect("witness synthesis should not fail")
+            })
+        };
+
+        let mut row = ThreadScaling {
+            degree,
+            threads,
+            witness: None,
+            proof: None,
+        };
+        if phases.contains(&Phase::Witness) {
+            group.bench_function(
+                BenchmarkId::new(format!("witness {threads} threads"), degree),
+                |b| b.iter(witness),
+            );
+            row.witness = Some(elapsed(witness));
+        }
+        if let Some(pk) = &pk {
+            let proof =
+                || pool.install(|| prove(params, pk, circuit(), instances, Multiopen::Shplonk));
+            group.bench_function(
+                BenchmarkId::new(format!("proof {threads} threads"), degree),
+                |b| b.iter(proof),
+            );
+            row.proof = Some(elapsed(proof));
+        }
+        rows.push(row);
+    }
+    println!("{name}\n{}", thread_report(&rows));
+    rows
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_thread_scaling() {
+        assert_eq!(parse_thread_counts("1, 2,8"), Some(vec![1, 2, 8]));
+        assert_eq!(parse_thread_counts("0"), None);
+        assert_eq!(parse_thread_counts("all"), None);
+
+        let row = |threads, secs| ThreadScaling {
+            degree: 12,
+            threads,
+            witness: Some(Duration::from_secs(1)),
+            proof: Some(Duration::from_secs(secs)),
+        };
+        let report = thread_report(&[row(1, 8), row(4, 2)]);
+        assert_eq!(
+            report.lines().nth(3),
+            Some("| 12 | 4 | 1.000 | 1.00 | 2.000 | 4.00 |")
+        );
+
+        // the proof is not selected, e.g. `PHASES=witness`.
+        let untimed = |threads| ThreadScaling {
+            proof: None,
+            ..row(threads, 1)
+        };
+        let report = thread_report(&[untimed(1), untimed(4)]);
+        assert_eq!(
+            report.lines().nth(3),
+            Some("| 12 | 4 | 1.000 | 1.00 | - | - |")
+        );
+    }
+}
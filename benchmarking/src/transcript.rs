@@ -0,0 +1,202 @@
+//! Cost of the transcript a proof derives its challenges from: the BLAKE2b
+//! transcript of the other benchmarks, or the Keccak-256 one, which the
+//! verifiers on the EVM need, as Keccak-256 is the hash the EVM computes
+//! cheaply. A circuit whose proofs are verified on-chain is proven with the
+//! Keccak-256 transcript, so that its prover and verifier times are those of
+//! [`Transcript::Keccak256`] rather than the ones of the `proof` and `verify`
+//! benchmarks, unless they run with the `evm` feature: they then prove with
+//! the Keccak-256 transcript, and the BLAKE2b one is benchmarked here, see
+//! [`Transcript::DEFAULT`].
+//!
+//! The transcripts are only compared with the SHPLONK multiopen argument,
+//! whose proof is the smaller of the two and thus the cheaper calldata.
+
+use std::{fmt, time::Duration};
+
+use criterion::{measurement::WallTime, BenchmarkGroup, BenchmarkId};
+use halo2_proofs::{
+    halo2curves::bn256::{Bn256, Fr, G1Affine},
+    plonk::{Circuit, ProvingKey},
+    poly::{commitment::Params, kzg::commitment::ParamsKZG},
+};
+
+use crate::{
+    elapsed, phases, prove_with_transcript, verify_with_transcript, Multiopen, Phase, Transcript,
+};
+
+/// Times of the proof of a circuit at a degree and of its verification with
+/// a transcript.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TranscriptCost {
+    pub degree: u32,
+    pub transcript: Transcript,
+    pub prover: Duration,
+    pub verifier: Duration,
+}
+
+impl TranscriptCost {
+    /// Changes of the prover and the verifier times over those of `baseline`,
+    /// in percent.
+    pub fn change(&self, baseline: &Self) -> (f64, f64) {
+        let change = |time: Duration, baseline: Duration| {
+            (time.as_secs_f64() / baseline.as_secs_f64() - 1.0) * 100.0
+        };
+        (
+            change(self.prover, baseline.prover),
+            change(self.verifier, baseline.verifier),
+        )
+    }
+}
+
+impl fmt::Display for TranscriptCost {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "with degree = {} and {} transcript: proved in {:.3} s, verified in {:.3} ms",
+            self.degree,
+            self.transcript,
+            self.prover.as_secs_f64(),
+            self.verifier.as_secs_f64() * 1e3
+        )
+    }
+}
+
+/// Markdown table of the costs of the transcripts, one row each, with the
+/// changes of their times over the `baseline` transcript at the same degree.
+pub fn transcript_report(costs: &[TranscriptCost], baseline: Transcript) -> String {
+    let mut lines = vec![
+        "| k | transcript | prover (s) | verify (ms) | prover change | verify change |".to_string(),
+        "| --- | --- | --- | --- | --- | --- |".to_string(),
+    ];
+    lines.extend(costs.iter().map(|cost| {
+        let baseline = costs
+            .iter()
+            .find(|other| other.degree == cost.degree && other.transcript == baseline);
+        let (prover, verifier) = baseline.map_or(("-".to_string(), "-".to_string()), |baseline| {
+            let (prover, verifier) = cost.change(baseline);
+            (format!("{prover:+.1}%"), format!("{verifier:+.1}%"))
+        });
+        format!(
+            "| {} | {} | {:.3} | {:.3} | {prover} | {verifier} |",
+            cost.degree,
+            cost.transcript,
+            cost.prover.as_secs_f64(),
+            cost.verifier.as_secs_f64() * 1e3
+        )
+    }));
+    lines.join("\n")
+}
+
+/// Benchmarks the proof of the circuit built by `circuit` and its
+/// verification with the other transcript than [`Transcript::DEFAULT`]
+/// within `group`, e.g. `proof SHPLONK Keccak256`, at the phases selected by
+/// [`phases`], and prints and returns the cost of every [`Transcript`], the
+/// default one being benchmarked as the `proof` and `verify` phases.
+pub fn bench_transcripts<C: Circuit<Fr>>(
+    group: &mut BenchmarkGroup<'_, WallTime>,
+    name: &str,
+    params: &ParamsKZG<Bn256>,
+    pk: &ProvingKey<G1Affine>,
+    circuit: impl Fn() -> C,
+    instances: &[Vec<Fr>],
+) -> Vec<TranscriptCost> {
+    let degree = params.k();
+    let phases = phases();
+    let multiopen = Multiopen::Shplonk;
+    let prove =
+        |transcript| prove_with_transcript(params, pk, circuit(), instances, multiopen, transcript);
+    let verify = |proof: &[u8], transcript| {
+        assert!(
+            verify_with_transcript(params, pk.get_vk(), proof, instances, multiopen, transcript),
+            "failed to verify bench circuit"
+        )
+    };
+
+    let transcript = Transcript::DEFAULT.other();
+    if phases.contains(&Phase::Proof) {
+        group.bench_function(
+            BenchmarkId::new(format!("proof {multiopen} {transcript}"), degree),
+            |b| b.iter(|| prove(transcript)),
+        );
+    }
+    if phases.contains(&Phase::Verify) {
+        let proof = prove(transcript);
+        group.bench_function(
+            BenchmarkId::new(format!("verify {multiopen} {transcript}"), degree),
+            |b| b.iter(|| verify(&proof, transcript)),
+        );
+    }
+
+    let costs = Transcript::ALL
+        .into_iter()
+        .map(|transcript| {
+            let proof = prove(transcript);
+            TranscriptCost {
+                degree,
+                transcript,
+                prover: elapsed(|| prove(transcript)),
+                verifier: elapsed(|| verify(&proof, transcript)),
+            }
+        })
+        .collect::<Vec<_>>();
+    for cost in &costs {
+        println!("{name} {cost}");
+    }
+    println!("{name}\n{}", transcript_report(&costs, Transcript::DEFAULT));
+    costs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{keygen, prove_evm, setup, sha2_test_circuit, verify_evm};
+
+    #[test]
+    fn test_transcripts() {
+        let (k, circuit) = sha2_test_circuit();
+        let params = setup(k);
+        let pk = keygen(&params, &circuit());
+        for multiopen in Multiopen::ALL {
+            let proof = prove_with_transcript(
+                &params,
+                &pk,
+                circuit(),
+                &[],
+                multiopen,
+                Transcript::Keccak256,
+            );
+            assert!(verify_with_transcript(
+                &params,
+                pk.get_vk(),
+                &proof,
+                &[],
+                multiopen,
+                Transcript::Keccak256
+            ));
+        }
+        let proof = prove_evm(&params, &pk, circuit(), &[]);
+        assert!(verify_evm(&params, pk.get_vk(), &proof, &[]));
+
+        let cost = |transcript, prover, verifier| TranscriptCost {
+            degree: 8,
+            transcript,
+            prover: Duration::from_millis(prover),
+            verifier: Duration::from_millis(verifier),
+        };
+        let costs = [
+            cost(Transcript::Blake2b, 2_000, 4),
+            cost(Transcript::Keccak256, 2_500, 5),
+        ];
+        assert_eq!(costs[1].change(&costs[0]).0, 25.0);
+        assert_eq!(
+            costs[1].to_string(),
+            "with degree = 8 and Keccak256 transcript: proved in 2.500 s, verified in 5.000 ms"
+        );
+        assert_eq!(
+            transcript_report(&costs, Transcript::Blake2b)
+                .lines()
+                .nth(3),
+            Some("| 8 | Keccak256 | 2.500 | 5.000 | +25.0% | +25.0% |")
+        );
+    }
+}
@@ -0,0 +1,193 @@
+//! Seeded generator of random mixed workloads of the precompiles, to
+//! benchmark and soak test the circuits on inputs closer to those of a block
+//! than the few test vectors of their crates.
+//!
+//! A [`Workload`] is a trace of calls to the SHA2-256, RIPEMD-160 and BLAKE2F
+//! precompiles in a random order, with random numbers of calls, lengths of
+//! messages and rounds, bounded by a [`WorkloadGenerator`]. The generator is
+//! seeded by the `WORKLOAD_SEED` env var, or [`WORKLOAD_SEED`] if unset, so
+//! that a workload that fails a soak test is generated again from the seed
+//! it was printed with.
+
+use std::{env::var, fmt};
+
+use blake2f_circuit::Blake2fWitness;
+use precompile_super_circuit::{CircuitInputBuilder, Precompile};
+use rand::{seq::SliceRandom, Rng, RngCore, SeedableRng};
+use rand_xorshift::XorShiftRng;
+
+/// Seed of the workloads if the `WORKLOAD_SEED` env var is unset.
+pub const WORKLOAD_SEED: u64 = 0;
+
+/// Precompiles the calls of a workload are drawn from.
+const PRECOMPILES: [Precompile; 3] = [Precompile::Sha2, Precompile::Ripemd160, Precompile::Blake2f];
+
+/// Rounds of BLAKE2b, which most calls to the BLAKE2F precompile request.
+const BLAKE2B_ROUNDS: u32 = 12;
+
+/// Seed of the `WORKLOAD_SEED` env var, or [`WORKLOAD_SEED`] if unset.
+pub fn workload_seed() -> u64 {
+    var("WORKLOAD_SEED")
+        .map(|seed| seed.parse().expect("Cannot parse WORKLOAD_SEED env var"))
+        .unwrap_or(WORKLOAD_SEED)
+}
+
+/// Calls to the precompiles, in the order of a trace.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Workload {
+    pub calls: Vec<(Precompile, Vec<u8>)>,
+}
+
+impl Workload {
+    /// Inputs of the calls to `precompile`, in their order.
+    pub fn inputs(&self, precompile: Precompile) -> Vec<Vec<u8>> {
+        self.calls
+            .iter()
+            .filter(|(called, _)| *called == precompile)
+            .map(|(_, input)| input.clone())
+            .collect()
+    }
+
+    /// Builder of the witness of the super-circuit of at most `2^max_k` rows
+    /// from the calls.
+    pub fn builder(&self, max_k: u32) -> CircuitInputBuilder {
+        let mut builder = CircuitInputBuilder::new(max_k);
+        for (precompile, input) in &self.calls {
+            builder
+                .call(precompile.address(), input)
+                .expect("the generated calls are valid");
+        }
+        builder
+    }
+}
+
+impl fmt::Display for Workload {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let bytes = |precompile| -> usize { self.inputs(precompile).iter().map(Vec::len).sum() };
+        let blake2f = self.inputs(Precompile::Blake2f);
+        let rounds: u64 = blake2f
+            .iter()
+            .filter_map(|input| Blake2fWitness::from_input(input).ok())
+            .map(|witness| witness.rounds as u64)
+            .sum();
+        write!(
+            f,
+            "{} SHA2-256 calls of {} bytes, {} RIPEMD-160 calls of {} bytes, {} BLAKE2F calls of {} rounds",
+            self.inputs(Precompile::Sha2).len(),
+            bytes(Precompile::Sha2),
+            self.inputs(Precompile::Ripemd160).len(),
+            bytes(Precompile::Ripemd160),
+            blake2f.len(),
+            rounds
+        )
+    }
+}
+
+/// Generator of random [`Workload`]s, whose bounds may be changed after it
+/// is seeded.
+#[derive(Clone, Debug)]
+pub struct WorkloadGenerator {
+    rng: XorShiftRng,
+    /// Most calls of a workload, which has at least one.
+    pub max_calls: usize,
+    /// Most bytes of a message to SHA2-256 or RIPEMD-160.
+    pub max_message_bytes: usize,
+    /// Most rounds of a call to BLAKE2F that does not request the 12 rounds
+    /// of BLAKE2b.
+    pub max_rounds: u32,
+}
+
+impl WorkloadGenerator {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            rng: XorShiftRng::seed_from_u64(seed),
+            max_calls: 16,
+            max_message_bytes: 1 << 10,
+            max_rounds: 64,
+        }
+    }
+
+    /// Generator seeded by [`workload_seed`].
+    pub fn from_env() -> Self {
+        Self::new(workload_seed())
+    }
+
+    /// The next workload of the generator. The lengths of the messages are
+    /// spread over their orders of magnitude, as short messages are the most
+    /// common, and most calls to BLAKE2F request the rounds of BLAKE2b.
+    pub fn workload(&mut self) -> Workload {
+        let calls = self.rng.gen_range(1..=self.max_calls);
+        Workload {
+            calls: (0..calls)
+                .map(|_| {
+                    let precompile = *PRECOMPILES.choose(&mut self.rng).expect("not empty");
+                    let input = match precompile {
+                        Precompile::Blake2f => self.blake2f_input(),
+                        Precompile::Sha2 | Precompile::Ripemd160 => self.message(),
+                    };
+                    (precompile, input)
+                })
+                .collect(),
+        }
+    }
+
+    fn message(&mut self) -> Vec<u8> {
+        let magnitude = self.max_message_bytes.checked_ilog2().unwrap_or(0);
+        let max_bytes = self
+            .max_message_bytes
+            .min(1usize << self.rng.gen_range(0..=magnitude));
+        let mut message = vec![0; self.rng.gen_range(0..=max_bytes)];
+        self.rng.fill_bytes(&mut message);
+        message
+    }
+
+    fn blake2f_input(&mut self) -> Vec<u8> {
+        let rounds = if self.rng.gen_bool(0.75) {
+            BLAKE2B_ROUNDS
+        } else {
+            self.rng.gen_range(0..=self.max_rounds)
+        };
+        Blake2fWitness {
+            rounds,
+            h: self.rng.gen(),
+            m: self.rng.gen(),
+            t: self.rng.gen(),
+            f: self.rng.gen(),
+        }
+        .to_input()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{dev::MockProver, halo2curves::bn256::Fr};
+
+    use super::*;
+
+    #[test]
+    fn test_workload_generator() {
+        let mut generator = WorkloadGenerator::new(WORKLOAD_SEED);
+        generator.max_calls = 8;
+        generator.max_message_bytes = 100;
+        let workloads = (0..16).map(|_| generator.workload()).collect::<Vec<_>>();
+        let mut again = WorkloadGenerator::new(WORKLOAD_SEED);
+        again.max_calls = 8;
+        again.max_message_bytes = 100;
+        assert_eq!(again.workload(), workloads[0]);
+        assert_ne!(WorkloadGenerator::new(1).workload(), workloads[0]);
+
+        for workload in &workloads {
+            assert!((1..=8).contains(&workload.calls.len()));
+            for precompile in [Precompile::Sha2, Precompile::Ripemd160] {
+                assert!(workload
+                    .inputs(precompile)
+                    .iter()
+                    .all(|input| input.len() <= 100));
+            }
+            let circuit = workload.builder(16).build::<Fr>().unwrap();
+            let prover =
+                MockProver::run(circuit.min_k(), &circuit, circuit.public_inputs()).unwrap();
+            assert_eq!(prover.verify(), Ok(()), "{workload}");
+        }
+    }
+}
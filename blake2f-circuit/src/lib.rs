@@ -1,49 +1,121 @@
-#![allow(dead_code)]
-#![allow(unused_variables)]
-#![allow(unreachable_code)]
+//! The BLAKE2F circuit verifies calls to the compression function `F` of
+//! [`BLAKE2b`], the precompiled contract at address `0x09` of [EIP-152].
+//!
+//! The rounds are assigned on a [`WordOpsChip`] of 64-bit words: the mixing
+//! function `G` adds its words and the message words modulo `2^64`, and XORs
+//! and rotates them right by 32, 24, 16 and 63 bits, a row each. A call lays
+//! out the words of `h`, `m` and `t` along with their little-endian bytes,
+//! and the bytes of the number of rounds and of the final block flag. A
+//! round is only applied while a flag is set, which the rounds of the call
+//! clear in turn after its number of rounds, so that a call lays out the
+//! `MAX_ROUNDS` rounds of the capacity whatever its number of rounds, and
+//! the flags count them. The 12 rounds of the EIP-152 example take 1437
+//! rows, the bytes of the output included.
+//!
+//! The input and output of every call are laid out in a slot of a
+//! [`CallSlotsConfig`] of 213 bytes, without padding, and the circuit
+//! exposes the calls through `Blake2fTable`, a [`HashTable`] with one row per
+//! call of the RLC and length of its input and of its output, in the layout
+//! of the `KeccakTable`.
+//!
+//! [`BLAKE2b`]: https://www.rfc-editor.org/rfc/rfc7693
+//! [EIP-152]: https://eips.ethereum.org/EIPS/eip-152
+//! [`WordOpsChip`]: gadgets::WordOpsChip
+//! [`CallSlotsConfig`]: gadgets::CallSlotsConfig
+//! [`HashTable`]: gadgets::HashTable
+
+pub mod native;
 
 use std::marker::PhantomData;
 
+use ethers_core::types::H512;
+use gadgets::{
+    batch::{self, BatchCircuit},
+    capacity::{check_capacity, padded, Bounded, UNBOUNDED},
+    context::{assign_context, Context},
+    public_inputs::PublicInputs,
+    tables::FixedTables,
+    AssignedBytes, AssignedCall, BitFn, BitShift, CallSlotsConfig, Endianness, HashCircuitExt,
+    HashTable, HashTableConfig, SlotCells, SlotPadding, WordCell, WordOpsChip, WordOpsConfig,
+};
 use halo2_proofs::{
     arithmetic::FieldExt,
-    circuit::Layouter,
-    plonk::{Advice, Any, Column, ConstraintSystem, Error},
+    circuit::{Layouter, SimpleFloorPlanner},
+    plonk::{Any, Challenge, Circuit, Column, ConstraintSystem, Error, FirstPhase, ProvingKey},
+    poly::commitment::{CommitmentScheme, Prover},
+    transcript::{EncodedChallenge, TranscriptWrite},
 };
+use rand_core::RngCore;
+use tracing::info_span;
 
-#[derive(Clone, Debug)]
-pub struct Blake2fTable {
-    id: Column<Advice>,
-}
-
-impl Blake2fTable {
-    pub fn construct<F: FieldExt>(meta: &mut ConstraintSystem<F>) -> Self {
-        Self {
-            id: meta.advice_column(),
-        }
-    }
+pub type Blake2fTable = HashTable;
 
-    pub fn columns(&self) -> Vec<Column<Any>> {
-        vec![self.id.into()]
-    }
-
-    pub fn annotations(&self) -> Vec<String> {
-        vec![String::from("id")]
-    }
-}
+/// The rotations of the mixing function `G`, in their order.
+const ROTATIONS: [BitShift; 4] = [
+    BitShift::RotateRight(32),
+    BitShift::RotateRight(24),
+    BitShift::RotateRight(16),
+    BitShift::RotateRight(63),
+];
 
+/// The config of the circuit, of at most `MAX_CALLS` calls of at most
+/// `MAX_ROUNDS` rounds each, as the input of a call is of a fixed length, or
+/// sized at run time by default, see [`gadgets::capacity`].
 #[derive(Clone, Debug)]
-pub struct Blake2fConfig<F> {
-    table: Blake2fTable,
+pub struct Blake2fConfig<F, const MAX_CALLS: usize = UNBOUNDED, const MAX_ROUNDS: usize = UNBOUNDED>
+{
+    table: HashTableConfig,
+    word_ops: WordOpsConfig<64>,
+    slots: CallSlotsConfig,
     _marker: PhantomData<F>,
 }
 
-impl<F: FieldExt> Blake2fConfig<F> {
+impl<F: FieldExt, const MAX_CALLS: usize, const MAX_ROUNDS: usize>
+    Blake2fConfig<F, MAX_CALLS, MAX_ROUNDS>
+{
+    /// Fails the build of a config of no calls.
+    const HAS_CAPACITY: () = assert!(MAX_CALLS > 0, "a circuit of no calls");
+
+    /// Configures the circuit over `table`, constructed with
+    /// `Blake2fTable::construct(meta, native::DIGEST_BYTES)`, and a
+    /// challenge of its own for the RLCs of the table.
     pub fn configure(meta: &mut ConstraintSystem<F>, table: Blake2fTable) -> Self {
+        let () = Self::HAS_CAPACITY;
+        let _span = info_span!("configure", circuit = "blake2f").entered();
+        let challenge = meta.challenge_usable_after(FirstPhase);
+        let table = HashTableConfig::configure(meta, table, challenge);
+        let shifts = ROTATIONS.map(|rotation| vec![rotation]);
+        let word_ops = WordOpsConfig::configure(meta, &shifts, &[BitFn::XOR, BitFn::XOR3]);
+        let slots = CallSlotsConfig::configure(
+            meta,
+            &table,
+            Blake2fWitness::INPUT_BYTES,
+            native::DIGEST_BYTES,
+            SlotPadding::None,
+        );
         Self {
             table,
+            word_ops,
+            slots,
             _marker: PhantomData,
         }
     }
+
+    /// The table of the calls.
+    pub fn table(&self) -> &Blake2fTable {
+        &self.table.table
+    }
+
+    /// The challenge of the RLCs of [`Self::table`].
+    pub fn challenge(&self) -> Challenge {
+        self.table.challenge
+    }
+
+    /// Exposes [`Self::table`] and the input bytes of its calls in two
+    /// instance columns, see [`HashTableConfig::expose`].
+    pub fn expose(&mut self, meta: &mut ConstraintSystem<F>) {
+        self.table.expose(meta);
+    }
 }
 
 #[derive(Clone, Debug, Default)]
@@ -55,137 +127,731 @@ pub struct Blake2fWitness {
     pub f: bool,
 }
 
+impl Blake2fWitness {
+    /// Length of the input of the BLAKE2F precompile.
+    pub const INPUT_BYTES: usize = 213;
+
+    /// Decodes the input of a call to the BLAKE2F precompile, as specified by
+    /// [EIP-152]: the big-endian number of rounds, then the little-endian
+    /// words of `h`, `m` and `t`, then the final block flag. An
+    /// [`gadgets::Error::Encoding`] if the input is not 213 bytes long or
+    /// the flag is neither 0 nor 1.
+    ///
+    /// [EIP-152]: https://eips.ethereum.org/EIPS/eip-152#specification
+    pub fn from_input(input: &[u8]) -> Result<Self, gadgets::Error> {
+        let invalid = |reason| gadgets::Error::Encoding {
+            precompile: "BLAKE2F",
+            reason,
+        };
+        if input.len() != Self::INPUT_BYTES {
+            return Err(invalid(format!(
+                "it is {} bytes long rather than {}",
+                input.len(),
+                Self::INPUT_BYTES
+            )));
+        }
+        if input[212] > 1 {
+            return Err(invalid(format!(
+                "its final block flag is {} rather than 0 or 1",
+                input[212]
+            )));
+        }
+        let word = |i: usize| {
+            let offset = 4 + 8 * i;
+            u64::from_le_bytes(input[offset..offset + 8].try_into().unwrap())
+        };
+        Ok(Self {
+            rounds: u32::from_be_bytes(input[0..4].try_into().unwrap()),
+            h: std::array::from_fn(word),
+            m: std::array::from_fn(|i| word(8 + i)),
+            t: std::array::from_fn(|i| word(24 + i)),
+            f: input[212] == 1,
+        })
+    }
+
+    /// Encodes the witness as the input of a call to the BLAKE2F precompile,
+    /// the inverse of [`Self::from_input`].
+    pub fn to_input(&self) -> Vec<u8> {
+        let words = self.h.iter().chain(&self.m).chain(&self.t);
+        self.rounds
+            .to_be_bytes()
+            .into_iter()
+            .chain(words.flat_map(|word| word.to_le_bytes()))
+            .chain([self.f as u8])
+            .collect()
+    }
+}
+
 #[derive(Clone, Debug)]
-pub struct Blake2fChip<F> {
-    config: Blake2fConfig<F>,
+pub struct Blake2fChip<F, const MAX_CALLS: usize = UNBOUNDED, const MAX_ROUNDS: usize = UNBOUNDED> {
+    config: Blake2fConfig<F, MAX_CALLS, MAX_ROUNDS>,
     data: Vec<Blake2fWitness>,
 }
 
-impl<F: FieldExt> Blake2fChip<F> {
-    pub fn construct(config: Blake2fConfig<F>, data: Vec<Blake2fWitness>) -> Self {
+impl<F: FieldExt, const MAX_CALLS: usize, const MAX_ROUNDS: usize>
+    Blake2fChip<F, MAX_CALLS, MAX_ROUNDS>
+{
+    pub fn construct(
+        config: Blake2fConfig<F, MAX_CALLS, MAX_ROUNDS>,
+        data: Vec<Blake2fWitness>,
+    ) -> Self {
         Self { config, data }
     }
 
+    /// Loads the calls of the witness, failing if they exceed the capacity
+    /// of the config.
     pub fn load(&self, layouter: &mut impl Layouter<F>) -> Result<(), Error> {
+        self.assign_calls(layouter).map(|_| ())
+    }
+
+    /// Loads the calls of the witness as [`Self::load`] does, and returns the
+    /// cells of their inputs, the 213 bytes of the input of the precompile,
+    /// and of their outputs.
+    pub fn assign_calls(
+        &self,
+        layouter: &mut impl Layouter<F>,
+    ) -> Result<Vec<AssignedCall<F>>, Error> {
+        let rounds: usize = self.data.iter().map(|call| call.rounds as usize).sum();
+        let _span =
+            info_span!("load", circuit = "blake2f", calls = self.data.len(), rounds).entered();
+        Self::check_witness(&self.data)?;
+
+        // A bounded capacity pads the layout to it with disabled calls of no
+        // rounds, whatever the calls.
+        let chip = WordOpsChip::construct(self.config.word_ops.clone());
+        let disabled = Blake2fWitness::default();
+        let (mut calls, cells): (Vec<_>, Vec<_>) = (0..padded(self.data.len(), MAX_CALLS))
+            .map(|i| {
+                let call = self.data.get(i);
+                let name = match call {
+                    Some(_) => format!("blake2f call {}", i + 1),
+                    None => "blake2f disabled call".to_string(),
+                };
+                assign_context(layouter, &name, |ctx| {
+                    Self::assign_call(&chip, ctx, call.unwrap_or(&disabled), call.is_some())
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?
+            .into_iter()
+            .unzip();
+        calls.truncate(self.data.len());
+
+        let lens = vec![Blake2fWitness::INPUT_BYTES; self.data.len()];
+        let slots = &self.config.slots;
+        let plan = slots.plan(&lens, cells.len());
+        slots.assign(
+            layouter,
+            "blake2f",
+            &self.config.table,
+            &lens,
+            &plan,
+            &cells,
+        )?;
+        let table_calls: Vec<_> = self
+            .data
+            .iter()
+            .map(|call| (call.to_input(), native::blake2f(call).to_vec()))
+            .collect();
+        self.config.table.assign(
+            layouter,
+            "blake2f",
+            padded(self.data.len(), MAX_CALLS),
+            &table_calls,
+        )?;
+        Ok(calls)
+    }
+
+    /// Assigns the input bytes of `call`, the rounds of its compression and
+    /// the bytes of its output on the next rows of `ctx`, returning them
+    /// along with the cells of its slot, which is disabled unless
+    /// `is_enabled`.
+    ///
+    /// The call lays out `MAX_ROUNDS` rounds, or its own rounds if the
+    /// capacity is unbounded, each of which leaves the state as is once the
+    /// rounds of the call are done.
+    fn assign_call(
+        chip: &WordOpsChip<F, 64>,
+        ctx: &mut Context<'_, '_, F>,
+        call: &Blake2fWitness,
+        is_enabled: bool,
+    ) -> Result<(AssignedCall<F>, SlotCells<F>), Error> {
+        let is_first = chip.assign(ctx, is_enabled as u64)?;
+        let mut input = Vec::with_capacity(Blake2fWitness::INPUT_BYTES);
+        let (rounds, rounds_bytes) =
+            chip.load_bytes(ctx, &call.rounds.to_be_bytes(), Endianness::Big)?;
+        input.extend(rounds_bytes);
+        let mut load_words = |ctx: &mut Context<'_, '_, F>, words: &[u64]| {
+            words
+                .iter()
+                .map(|word| {
+                    let (word, bytes) =
+                        chip.load_bytes(ctx, &word.to_le_bytes(), Endianness::Little)?;
+                    input.extend(bytes);
+                    Ok(word)
+                })
+                .collect::<Result<Vec<_>, Error>>()
+        };
+        let h = load_words(ctx, &call.h)?;
+        let m = load_words(ctx, &call.m)?;
+        let t = load_words(ctx, &call.t)?;
+        let (f, f_bytes) = chip.load_bytes(ctx, &[call.f as u8], Endianness::Little)?;
+        input.extend(f_bytes);
+
+        let mut v = h.clone();
+        for word in &native::IV[..4] {
+            v.push(chip.constant(ctx, *word)?);
+        }
+        for (word, t) in native::IV[4..6].iter().zip(&t) {
+            let iv = chip.constant(ctx, *word)?;
+            v.push(chip.apply(ctx, BitFn::XOR, &[&iv, t])?);
+        }
+        let iv6 = chip.constant(ctx, native::IV[6])?;
+        let not_iv6 = chip.constant(ctx, !native::IV[6])?;
+        v.push(chip.select(ctx, &f, &not_iv6, &iv6)?);
+        v.push(chip.constant(ctx, native::IV[7])?);
+
+        // The flag of a round is that of the previous round and the one
+        // assigned for it, so that the flags clear at most once, and their
+        // count is the number of rounds of the call.
+        let zero = chip.constant(ctx, 0)?;
+        let mut count = zero.clone();
+        let mut is_active: Option<WordCell<F>> = None;
+        for round in 0..padded(call.rounds as usize, MAX_ROUNDS) {
+            let flag = chip.assign(ctx, (round < call.rounds as usize) as u64)?;
+            let flag = match &is_active {
+                Some(is_active) => chip.select(ctx, is_active, &flag, &zero)?,
+                None => flag,
+            };
+            let s = &native::SIGMA[round % 10];
+            let mut mixed = v.clone();
+            for (i, indices) in native::MIXINGS.iter().enumerate() {
+                Self::assign_g(
+                    chip,
+                    ctx,
+                    &mut mixed,
+                    *indices,
+                    &m[s[2 * i]],
+                    &m[s[2 * i + 1]],
+                )?;
+            }
+            for (word, mixed) in v.iter_mut().zip(&mixed) {
+                *word = chip.select(ctx, &flag, mixed, word)?;
+            }
+            count = chip.add(ctx, &[&count, &flag], 0)?;
+            is_active = Some(flag);
+        }
+        ctx.region()
+            .constrain_equal(count.cell.cell(), rounds.cell.cell())?;
+
+        let h = (0..8)
+            .map(|i| chip.apply(ctx, BitFn::XOR3, &[&h[i], &v[i], &v[i + 8]]))
+            .collect::<Result<Vec<_>, _>>()?;
+        let mut output = Vec::with_capacity(Self::DIGEST_BYTES);
+        for word in &h {
+            output.extend(chip.bytes(ctx, word, Endianness::Little)?);
+        }
+        let slot = SlotCells {
+            is_first: is_first.cell,
+            block: input.clone(),
+            digest: output.clone(),
+        };
+        let call = AssignedCall {
+            input: AssignedBytes::new(input),
+            digest: AssignedBytes::new(output),
+        };
+        Ok((call, slot))
+    }
+
+    /// Assigns the mixing function `G` of the words `x` and `y` into the
+    /// words `a`, `b`, `c` and `d` of `v`, in twelve rows.
+    fn assign_g(
+        chip: &WordOpsChip<F, 64>,
+        ctx: &mut Context<'_, '_, F>,
+        v: &mut [WordCell<F>],
+        [a, b, c, d]: [usize; 4],
+        x: &WordCell<F>,
+        y: &WordCell<F>,
+    ) -> Result<(), Error> {
+        for (i, m) in [x, y].into_iter().enumerate() {
+            v[a] = chip.add(ctx, &[&v[a], &v[b], m], 0)?;
+            let xor = chip.apply(ctx, BitFn::XOR, &[&v[d], &v[a]])?;
+            v[d] = chip.shift(ctx, &[ROTATIONS[2 * i]], &xor)?;
+            v[c] = chip.add(ctx, &[&v[c], &v[d]], 0)?;
+            let xor = chip.apply(ctx, BitFn::XOR, &[&v[b], &v[c]])?;
+            v[b] = chip.shift(ctx, &[ROTATIONS[2 * i + 1]], &xor)?;
+        }
         Ok(())
     }
 }
 
-#[cfg(any(feature = "test", test))]
-pub mod dev {
-    use super::*;
+impl<F: FieldExt, const MAX_CALLS: usize, const MAX_ROUNDS: usize> HashCircuitExt<F>
+    for Blake2fChip<F, MAX_CALLS, MAX_ROUNDS>
+{
+    type Config = Blake2fConfig<F, MAX_CALLS, MAX_ROUNDS>;
+    type Witness = Vec<Blake2fWitness>;
 
-    use ethers_core::{types::H512, utils::hex::FromHex};
-    use halo2_proofs::{arithmetic::FieldExt, circuit::SimpleFloorPlanner, plonk::Circuit};
-    use std::{marker::PhantomData, str::FromStr};
+    const DIGEST_BYTES: usize = native::DIGEST_BYTES;
 
-    lazy_static::lazy_static! {
-        // https://eips.ethereum.org/EIPS/eip-152#example-usage-in-solidity
-        pub static ref INPUTS_OUTPUTS: (Vec<Blake2fWitness>, Vec<H512>) = {
-            let (h1, h2) = (
-                <[u8; 32]>::from_hex("48c9bdf267e6096a3ba7ca8485ae67bb2bf894fe72f36e3cf1361d5f3af54fa5").expect(""),
-                <[u8; 32]>::from_hex("d182e6ad7f520e511f6c3e2b8c68059b6bbd41fbabd9831f79217e1319cde05b").expect(""),
-            );
-            let (m1, m2, m3, m4) = (
-                <[u8; 32]>::from_hex("6162630000000000000000000000000000000000000000000000000000000000").expect(""),
-                <[u8; 32]>::from_hex("0000000000000000000000000000000000000000000000000000000000000000").expect(""),
-                <[u8; 32]>::from_hex("0000000000000000000000000000000000000000000000000000000000000000").expect(""),
-                <[u8; 32]>::from_hex("0000000000000000000000000000000000000000000000000000000000000000").expect(""),
-            );
-            (
-                vec![
-                    Blake2fWitness {
-                        rounds: 12,
-                        h: [
-                            u64::from_le_bytes(h1[0x00..0x08].try_into().expect("")),
-                            u64::from_le_bytes(h1[0x08..0x10].try_into().expect("")),
-                            u64::from_le_bytes(h1[0x10..0x18].try_into().expect("")),
-                            u64::from_le_bytes(h1[0x18..0x20].try_into().expect("")),
-                            u64::from_le_bytes(h2[0x00..0x08].try_into().expect("")),
-                            u64::from_le_bytes(h2[0x08..0x10].try_into().expect("")),
-                            u64::from_le_bytes(h2[0x10..0x18].try_into().expect("")),
-                            u64::from_le_bytes(h2[0x18..0x20].try_into().expect("")),
-                        ],
-                        m: [
-                            u64::from_le_bytes(m1[0x00..0x08].try_into().expect("")),
-                            u64::from_le_bytes(m1[0x08..0x10].try_into().expect("")),
-                            u64::from_le_bytes(m1[0x10..0x18].try_into().expect("")),
-                            u64::from_le_bytes(m1[0x18..0x20].try_into().expect("")),
-                            u64::from_le_bytes(m2[0x00..0x08].try_into().expect("")),
-                            u64::from_le_bytes(m2[0x08..0x10].try_into().expect("")),
-                            u64::from_le_bytes(m2[0x10..0x18].try_into().expect("")),
-                            u64::from_le_bytes(m2[0x18..0x20].try_into().expect("")),
-                            u64::from_le_bytes(m3[0x00..0x08].try_into().expect("")),
-                            u64::from_le_bytes(m3[0x08..0x10].try_into().expect("")),
-                            u64::from_le_bytes(m3[0x10..0x18].try_into().expect("")),
-                            u64::from_le_bytes(m3[0x18..0x20].try_into().expect("")),
-                            u64::from_le_bytes(m4[0x00..0x08].try_into().expect("")),
-                            u64::from_le_bytes(m4[0x08..0x10].try_into().expect("")),
-                            u64::from_le_bytes(m4[0x10..0x18].try_into().expect("")),
-                            u64::from_le_bytes(m4[0x18..0x20].try_into().expect("")),
-                        ],
-                        t: [3, 0],
-                        f: true,
-                    }
-                ],
-                vec![
-                    H512::from_str("ba80a53f981c4d0d6a2797b69f12f6e94c212f14685ac4b74b12bb6fdbffa2d17d87c5392aab792dc252d5de4533cc9518d38aa8dbf1925ab92386edd4009923")
-                    .expect("BLAKE2F compression function output is 64-bytes")
-                ],
-            )
-        };
+    /// Configures the circuit over its table, exposed in its instance
+    /// column.
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let mut config = Self::configure_with_tables(meta, &mut FixedTables::default());
+        config.expose(meta);
+        config
     }
 
-    #[derive(Default)]
-    pub struct Blake2fTestCircuit<F> {
-        pub inputs: Vec<Blake2fWitness>,
-        pub outputs: Vec<H512>,
-        pub _marker: PhantomData<F>,
+    /// Configures the circuit over its table, not exposed, the circuit
+    /// having no fixed tables.
+    fn configure_with_tables(
+        meta: &mut ConstraintSystem<F>,
+        _tables: &mut FixedTables,
+    ) -> Self::Config {
+        let table = Blake2fTable::construct(meta, native::DIGEST_BYTES);
+        Blake2fConfig::configure(meta, table)
     }
 
-    impl<F: FieldExt> Circuit<F> for Blake2fTestCircuit<F> {
-        type Config = Blake2fConfig<F>;
-        type FloorPlanner = SimpleFloorPlanner;
+    /// At most `MAX_CALLS` calls of at most `MAX_ROUNDS` rounds each, the
+    /// `len` of a [`gadgets::Error::Capacity`] being the most rounds of a
+    /// call.
+    fn check_witness(witness: &Self::Witness) -> Result<(), gadgets::Error> {
+        let rounds = witness
+            .iter()
+            .map(|call| call.rounds as usize)
+            .max()
+            .unwrap_or(0);
+        check_capacity(witness.len(), rounds, MAX_CALLS, MAX_ROUNDS)
+    }
 
-        fn without_witnesses(&self) -> Self {
-            Self::default()
+    /// The calls of `witness`, in their order, of inputs of
+    /// [`Blake2fWitness::INPUT_BYTES`] bytes, padded to the capacity as the
+    /// layout is.
+    fn public_inputs(witness: &Self::Witness) -> Vec<Vec<F>> {
+        let mut public_inputs = PublicInputs::new(
+            native::DIGEST_BYTES,
+            Blake2fWitness::INPUT_BYTES,
+            SlotPadding::None,
+        );
+        for call in witness {
+            public_inputs.call(&call.to_input(), &native::blake2f(call));
         }
+        public_inputs.build_padded(MAX_CALLS, MAX_ROUNDS)
+    }
 
-        fn configure(meta: &mut halo2_proofs::plonk::ConstraintSystem<F>) -> Self::Config {
-            let blake2f_table = Blake2fTable::construct(meta);
-            Blake2fConfig::configure(meta, blake2f_table)
-        }
+    fn construct(config: Self::Config, witness: Self::Witness) -> Self {
+        Blake2fChip::construct(config, witness)
+    }
 
-        fn synthesize(
-            &self,
-            config: Self::Config,
-            mut layouter: impl Layouter<F>,
-        ) -> Result<(), Error> {
-            let chip = Blake2fChip::construct(config, self.inputs.clone());
-            chip.load(&mut layouter)
-        }
+    fn load(&self, layouter: &mut impl Layouter<F>) -> Result<(), Error> {
+        Blake2fChip::load(self, layouter)
+    }
+
+    fn table_columns(config: &Self::Config) -> Vec<Column<Any>> {
+        config.table().columns()
+    }
+
+    fn annotations(config: &Self::Config) -> Vec<String> {
+        config.table().annotations()
+    }
+
+    fn assigned_digest(
+        &self,
+        layouter: &mut impl Layouter<F>,
+    ) -> Result<Vec<AssignedBytes<F>>, Error> {
+        let calls = self.assign_calls(layouter)?;
+        Ok(calls.into_iter().map(|call| call.digest).collect())
+    }
+}
+
+/// The circuit of the calls of many witness sets, see [`gadgets::batch`],
+/// whose keys are generated from its default, as its layout is that of its
+/// capacity, which is bounded.
+pub type Blake2fBatchCircuit<F, const MAX_CALLS: usize, const MAX_ROUNDS: usize> =
+    BatchCircuit<F, Blake2fChip<F, MAX_CALLS, MAX_ROUNDS>>;
+
+/// Proves the calls of all of `witnesses` with a single proof of a
+/// [`Blake2fBatchCircuit`], or fails with an [`gadgets::Error::Capacity`] if they
+/// exceed `MAX_CALLS` calls of `MAX_ROUNDS` rounds each, see
+/// [`batch::prove_batch`]. The build fails if the capacity is unbounded.
+pub fn prove_batch<'params, Scheme, P, E, R, T, const MAX_CALLS: usize, const MAX_ROUNDS: usize>(
+    params: &'params Scheme::ParamsProver,
+    pk: &ProvingKey<Scheme::Curve>,
+    witnesses: Vec<Vec<Blake2fWitness>>,
+    rng: R,
+    transcript: &mut T,
+) -> Result<(), gadgets::Error>
+where
+    Scheme: CommitmentScheme,
+    P: Prover<'params, Scheme>,
+    E: EncodedChallenge<Scheme::Curve>,
+    R: RngCore,
+    T: TranscriptWrite<Scheme::Curve, E>,
+{
+    let () = Bounded::<MAX_CALLS, MAX_ROUNDS>::CHECK;
+    batch::prove_batch::<Scheme, P, E, R, T, Blake2fChip<Scheme::Scalar, MAX_CALLS, MAX_ROUNDS>>(
+        params, pk, witnesses, rng, transcript,
+    )
+}
+
+/// The circuit of the calls of BLAKE2F proven on their own, whose digests
+/// are checked against `outputs`, without exposing its table, e.g. by the
+/// benchmarks.
+#[derive(Default)]
+pub struct Blake2fTestCircuit<F> {
+    pub inputs: Vec<Blake2fWitness>,
+    pub outputs: Vec<H512>,
+    pub _marker: PhantomData<F>,
+}
+
+impl<F: FieldExt> Blake2fTestCircuit<F> {
+    /// Smallest `k` that fits the calls of the circuit, see
+    /// [`HashCircuitExt::min_k`].
+    pub fn min_k(&self) -> u32 {
+        Blake2fChip::<F>::min_k(&self.inputs)
+    }
+
+    /// Checks the witness of the circuit before it is assigned: that it
+    /// fits the capacity of the chip, and that the digests of the calls
+    /// are those of [`native::blake2f`], see [`gadgets::validate`].
+    pub fn check_witness(&self) -> Result<(), gadgets::Error> {
+        Blake2fChip::<F>::check_witness(&self.inputs)?;
+        gadgets::validate::check_digests("BLAKE2F", &self.inputs, &self.outputs, native::blake2f)
+    }
+}
+
+impl<F: FieldExt> Circuit<F> for Blake2fTestCircuit<F> {
+    type Config = Blake2fConfig<F>;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(meta: &mut halo2_proofs::plonk::ConstraintSystem<F>) -> Self::Config {
+        let blake2f_table = Blake2fTable::construct(meta, native::DIGEST_BYTES);
+        Blake2fConfig::configure(meta, blake2f_table)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        self.check_witness()?;
+        let chip = Blake2fChip::construct(config, self.inputs.clone());
+        chip.load(&mut layouter)
+    }
+}
+
+/// The keys of [`Blake2fBatchCircuit`], prefixed with the version of its
+/// constraint system, see [`gadgets::keys`], which is that of any capacity.
+pub mod keys {
+    use super::*;
+
+    use halo2_proofs::{
+        arithmetic::CurveAffine,
+        plonk::{ProvingKey, VerifyingKey},
+        SerdeFormat,
+    };
+    use std::io;
+
+    /// Writes the proving key of [`Blake2fBatchCircuit`] prefixed with the version of its
+    /// constraint system, see [`gadgets::keys`].
+    pub fn write_pk<C: CurveAffine>(
+        writer: &mut impl io::Write,
+        pk: &ProvingKey<C>,
+        format: SerdeFormat,
+    ) -> io::Result<()>
+    where
+        C::ScalarExt: FieldExt,
+    {
+        gadgets::keys::write_pk::<C, BatchCircuit<C::ScalarExt, Blake2fChip<C::ScalarExt>>>(
+            writer, pk, format,
+        )
+    }
+
+    /// Reads a proving key written by [`write_pk`], refusing that of another
+    /// version of [`Blake2fBatchCircuit`].
+    pub fn read_pk<C: CurveAffine>(
+        reader: &mut impl io::Read,
+        format: SerdeFormat,
+    ) -> io::Result<ProvingKey<C>>
+    where
+        C::ScalarExt: FieldExt,
+    {
+        gadgets::keys::read_pk::<C, BatchCircuit<C::ScalarExt, Blake2fChip<C::ScalarExt>>>(
+            reader, format,
+        )
+    }
+
+    /// Writes the verifying key of [`Blake2fBatchCircuit`] prefixed with the version of
+    /// its constraint system, see [`gadgets::keys`].
+    pub fn write_vk<C: CurveAffine>(
+        writer: &mut impl io::Write,
+        vk: &VerifyingKey<C>,
+        format: SerdeFormat,
+    ) -> io::Result<()>
+    where
+        C::ScalarExt: FieldExt,
+    {
+        gadgets::keys::write_vk::<C, BatchCircuit<C::ScalarExt, Blake2fChip<C::ScalarExt>>>(
+            writer, vk, format,
+        )
+    }
+
+    /// Reads a verifying key written by [`write_vk`], refusing that of
+    /// another version of [`Blake2fBatchCircuit`].
+    pub fn read_vk<C: CurveAffine>(
+        reader: &mut impl io::Read,
+        format: SerdeFormat,
+    ) -> io::Result<VerifyingKey<C>>
+    where
+        C::ScalarExt: FieldExt,
+    {
+        gadgets::keys::read_vk::<C, BatchCircuit<C::ScalarExt, Blake2fChip<C::ScalarExt>>>(
+            reader, format,
+        )
+    }
+}
+
+#[cfg(any(feature = "test", test))]
+pub mod dev {
+    use super::*;
+
+    lazy_static::lazy_static! {
+        /// The calls of the fixtures of BLAKE2F, see [`test_utils`], the
+        /// example of EIP-152:
+        /// https://eips.ethereum.org/EIPS/eip-152#example-usage-in-solidity
+        pub static ref INPUTS_OUTPUTS: (Vec<Blake2fWitness>, Vec<H512>) = {
+            let (inputs, outputs) = test_utils::inputs_outputs("blake2f");
+            (
+                inputs
+                    .iter()
+                    .map(|input| {
+                        Blake2fWitness::from_input(input).expect("the fixtures are EIP-152 inputs")
+                    })
+                    .collect(),
+                outputs.iter().map(|output| H512::from_slice(output)).collect(),
+            )
+        };
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use halo2_proofs::{dev::MockProver, halo2curves::bn256::Fr};
+    use gadgets::{DigestCircuit, HashCircuitExt};
+    use halo2_proofs::{
+        arithmetic::FieldExt,
+        dev::MockProver,
+        halo2curves::{
+            bn256::{Bn256, Fr, G1Affine},
+            pasta::pallas,
+        },
+        plonk::{keygen_pk, keygen_vk, verify_proof, ConstraintSystem},
+        poly::{
+            commitment::ParamsProver,
+            kzg::{
+                commitment::{KZGCommitmentScheme, ParamsKZG},
+                multiopen::{ProverSHPLONK, VerifierSHPLONK},
+                strategy::SingleStrategy,
+            },
+        },
+        transcript::{
+            Blake2bRead, Blake2bWrite, Challenge255, TranscriptReadBuffer, TranscriptWriterBuffer,
+        },
+    };
+    use rand_xorshift::XorShiftRng;
     use std::marker::PhantomData;
 
-    use crate::dev::{Blake2fTestCircuit, INPUTS_OUTPUTS};
+    use crate::{
+        dev::INPUTS_OUTPUTS, native, prove_batch, Blake2fBatchCircuit, Blake2fChip,
+        Blake2fTestCircuit, Blake2fWitness,
+    };
 
     #[test]
-    fn test_blake2f_circuit() {
+    fn test_blake2f_native() {
+        let (inputs, outputs) = INPUTS_OUTPUTS.clone();
+        for (input, output) in inputs.iter().zip(outputs) {
+            let encoded = input.to_input();
+            assert_eq!(encoded.len(), Blake2fWitness::INPUT_BYTES);
+            assert_eq!(
+                Blake2fWitness::from_input(&encoded)
+                    .map(|decoded| decoded.to_input())
+                    .ok(),
+                Some(encoded)
+            );
+            assert_eq!(native::blake2f(input), output.0);
+        }
+
+        let mut input = INPUTS_OUTPUTS.0[0].to_input();
+        let err = Blake2fWitness::from_input(&input[1..]).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "the input is not that of a BLAKE2F call: it is 212 bytes long rather than 213"
+        );
+        input[212] = 2;
+        assert!(matches!(
+            Blake2fWitness::from_input(&input),
+            Err(gadgets::Error::Encoding { .. })
+        ));
+    }
+
+    fn test_blake2f_circuit_over<F: FieldExt>() {
         let (inputs, outputs) = INPUTS_OUTPUTS.clone();
 
-        let circuit: Blake2fTestCircuit<Fr> = Blake2fTestCircuit {
+        let circuit: Blake2fTestCircuit<F> = Blake2fTestCircuit {
             inputs,
             outputs,
             _marker: PhantomData,
         };
 
-        let k = 8;
-        let prover = MockProver::run(k, &circuit, vec![]).unwrap();
+        let prover = MockProver::run(circuit.min_k(), &circuit, vec![]).unwrap();
         assert_eq!(prover.verify(), Ok(()));
     }
+
+    #[test]
+    fn test_blake2f_check_witness() {
+        let (mut inputs, outputs) = INPUTS_OUTPUTS.clone();
+        inputs[0].f = !inputs[0].f;
+        let circuit = Blake2fTestCircuit::<Fr> {
+            inputs,
+            outputs,
+            _marker: PhantomData,
+        };
+        let err = circuit.check_witness().unwrap_err();
+        assert!(err
+            .to_string()
+            .starts_with("the witness of the BLAKE2F call 0 is invalid: its digest is"));
+    }
+
+    #[test]
+    fn test_blake2f_assigned_digest() {
+        let (inputs, outputs) = INPUTS_OUTPUTS.clone();
+        let verify = |digests: Vec<Vec<u8>>| {
+            let circuit = DigestCircuit::<Fr, Blake2fChip<Fr>> {
+                witness: inputs.clone(),
+                digests,
+                _marker: PhantomData,
+            };
+            let k = Blake2fChip::<Fr>::min_k(&inputs);
+            let instances = Blake2fChip::<Fr>::public_inputs(&inputs);
+            MockProver::run(k, &circuit, instances).unwrap().verify()
+        };
+        let mut digests: Vec<_> = outputs.iter().map(|output| output.0.to_vec()).collect();
+        assert_eq!(verify(digests.clone()), Ok(()));
+
+        // a byte of an output is not the one of the call.
+        digests[0][63] ^= 1;
+        assert!(verify(digests).is_err());
+    }
+
+    #[test]
+    fn test_blake2f_capacity() {
+        type Chip = Blake2fChip<Fr, 2, 12>;
+        let mut call = INPUTS_OUTPUTS.0[0].clone();
+        call.rounds = 3;
+        let witness = vec![INPUTS_OUTPUTS.0[0].clone(), call.clone()];
+        // the layout is that of the capacity, whatever the calls.
+        assert_eq!(Chip::layout_rows(&witness), Chip::layout_rows(&vec![]));
+        let k = Chip::min_k(&vec![]);
+        let instances = Chip::public_inputs(&witness);
+        let verify = |instances| {
+            let circuit = Blake2fBatchCircuit::<Fr, 2, 12>::new(vec![witness.clone()]);
+            MockProver::run(k, &circuit, instances).unwrap().verify()
+        };
+        assert_eq!(verify(instances.clone()), Ok(()));
+        // the length of a call is its input bytes, not its rounds.
+        let mut wrong = instances;
+        wrong[0][2 + 6] = Fr::from(3);
+        assert!(verify(wrong).is_err());
+
+        call.rounds = 13;
+        assert!(matches!(
+            Chip::check_witness(&vec![call]),
+            Err(gadgets::Error::Capacity { len: 13, .. })
+        ));
+    }
+
+    #[test]
+    fn test_blake2f_hash_circuit_ext() {
+        let mut meta = ConstraintSystem::<Fr>::default();
+        let config = Blake2fChip::<Fr>::configure(&mut meta);
+        assert_eq!(
+            Blake2fChip::<Fr>::table_columns(&config).len(),
+            Blake2fChip::<Fr>::annotations(&config).len()
+        );
+        assert_eq!(Blake2fChip::<Fr>::DIGEST_BYTES, 64);
+    }
+
+    #[test]
+    fn test_blake2f_circuit() {
+        test_blake2f_circuit_over::<Fr>();
+        test_blake2f_circuit_over::<pallas::Base>();
+    }
+
+    /// The capacity of the soak test, of hundreds of calls of up to 12
+    /// rounds, whose layout fits in [`test_utils::SOAK_K`].
+    const SOAK_CALLS: usize = 256;
+    const SOAK_ROUNDS: usize = 12;
+
+    #[test]
+    #[ignore = "proves hundreds of calls at k = 19, run with --ignored"]
+    fn test_blake2f_soak() {
+        use rand_core::{RngCore, SeedableRng};
+        use test_utils::{random_blake2f_input, SOAK_K};
+
+        let mut rng = XorShiftRng::from_seed([0x42; 16]);
+        let inputs: Vec<_> = (0..SOAK_CALLS)
+            .map(|_| {
+                let rounds = rng.next_u32() % 13;
+                let input = random_blake2f_input(&mut rng, rounds);
+                Blake2fWitness::from_input(&input).unwrap()
+            })
+            .collect();
+        // the calls of the transactions of a block, a few each.
+        let witnesses: Vec<_> = inputs.chunks(7).map(<[_]>::to_vec).collect();
+        let circuit = Blake2fBatchCircuit::<Fr, SOAK_CALLS, SOAK_ROUNDS>::new(witnesses.clone());
+        // the layout of the capacity, that of the calls and of the keys.
+        assert!(Blake2fChip::<Fr, SOAK_CALLS, SOAK_ROUNDS>::min_k(&vec![]) <= SOAK_K);
+        let instances = Blake2fChip::<Fr, SOAK_CALLS, SOAK_ROUNDS>::public_inputs(&circuit.witness);
+        MockProver::run(SOAK_K, &circuit, instances.clone())
+            .unwrap()
+            .assert_satisfied();
+
+        let params = ParamsKZG::<Bn256>::setup(SOAK_K, rng.clone());
+        let circuit = Blake2fBatchCircuit::<Fr, SOAK_CALLS, SOAK_ROUNDS>::default();
+        let vk = keygen_vk(&params, &circuit).unwrap();
+        let pk = keygen_pk(&params, vk, &circuit).unwrap();
+        let prove = |witnesses| {
+            let mut transcript = Blake2bWrite::<_, G1Affine, Challenge255<_>>::init(vec![]);
+            prove_batch::<
+                KZGCommitmentScheme<Bn256>,
+                ProverSHPLONK<_>,
+                _,
+                _,
+                _,
+                SOAK_CALLS,
+                SOAK_ROUNDS,
+            >(&params, &pk, witnesses, rng.clone(), &mut transcript)
+            .map(|()| transcript.finalize())
+        };
+
+        let proof = prove(witnesses.clone()).unwrap();
+        let mut transcript = Blake2bRead::<_, G1Affine, Challenge255<_>>::init(&proof[..]);
+        assert!(verify_proof::<_, VerifierSHPLONK<_>, _, _, _>(
+            params.verifier_params(),
+            pk.get_vk(),
+            SingleStrategy::new(&params),
+            &[&[&instances[0], &instances[1]]],
+            &mut transcript,
+        )
+        .is_ok());
+
+        // one call more than the capacity, even of no rounds.
+        let mut witnesses = witnesses;
+        witnesses.push(vec![Blake2fWitness::from_input(&[0; 213]).unwrap()]);
+        assert!(matches!(
+            prove(witnesses),
+            Err(gadgets::Error::Capacity { calls, .. }) if calls == SOAK_CALLS + 1
+        ));
+    }
 }
@@ -0,0 +1,101 @@
+//! Native implementation of the BLAKE2 compression function F, as called by
+//! the precompile, used as the reference the circuit is tested against.
+
+use crate::Blake2fWitness;
+
+/// Size of the output of the precompile in bytes.
+pub const DIGEST_BYTES: usize = 64;
+
+/// Initialization vector of BLAKE2b, i.e. the first 64 bits of the
+/// fractional parts of the square roots of the first 8 primes.
+pub const IV: [u64; 8] = [
+    0x6a09e667f3bcc908,
+    0xbb67ae8584caa73b,
+    0x3c6ef372fe94f82b,
+    0xa54ff53a5f1d36f1,
+    0x510e527fade682d1,
+    0x9b05688c2b3e6c1f,
+    0x1f83d9abfb41bd6b,
+    0x5be0cd19137e2179,
+];
+
+/// Permutations of the message words of every round, the round `i` using
+/// the permutation `i % 10`.
+pub const SIGMA: [[usize; 16]; 10] = [
+    [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15],
+    [14, 10, 4, 8, 9, 15, 13, 6, 1, 12, 0, 2, 11, 7, 5, 3],
+    [11, 8, 12, 0, 5, 2, 15, 13, 10, 14, 3, 6, 7, 1, 9, 4],
+    [7, 9, 3, 1, 13, 12, 11, 14, 2, 6, 5, 10, 4, 0, 15, 8],
+    [9, 0, 5, 7, 2, 4, 10, 15, 14, 1, 11, 12, 6, 8, 3, 13],
+    [2, 12, 6, 10, 0, 11, 8, 3, 4, 13, 7, 5, 15, 14, 1, 9],
+    [12, 5, 1, 15, 14, 13, 4, 10, 0, 7, 6, 3, 9, 2, 8, 11],
+    [13, 11, 7, 14, 12, 1, 3, 9, 5, 0, 15, 4, 8, 6, 2, 10],
+    [6, 15, 14, 9, 11, 3, 0, 8, 12, 2, 13, 7, 1, 4, 10, 5],
+    [10, 2, 8, 4, 7, 6, 1, 5, 15, 11, 9, 14, 3, 12, 13, 0],
+];
+
+/// The words of the working vector mixed by the eight mixings of a round.
+pub const MIXINGS: [[usize; 4]; 8] = [
+    [0, 4, 8, 12],
+    [1, 5, 9, 13],
+    [2, 6, 10, 14],
+    [3, 7, 11, 15],
+    [0, 5, 10, 15],
+    [1, 6, 11, 12],
+    [2, 7, 8, 13],
+    [3, 4, 9, 14],
+];
+
+/// The mixing function G of BLAKE2b, mixing the words `x` and `y` into the
+/// words `a`, `b`, `c` and `d` of the working vector.
+fn g(v: &mut [u64; 16], [a, b, c, d]: [usize; 4], x: u64, y: u64) {
+    v[a] = v[a].wrapping_add(v[b]).wrapping_add(x);
+    v[d] = (v[d] ^ v[a]).rotate_right(32);
+    v[c] = v[c].wrapping_add(v[d]);
+    v[b] = (v[b] ^ v[c]).rotate_right(24);
+    v[a] = v[a].wrapping_add(v[b]).wrapping_add(y);
+    v[d] = (v[d] ^ v[a]).rotate_right(16);
+    v[c] = v[c].wrapping_add(v[d]);
+    v[b] = (v[b] ^ v[c]).rotate_right(63);
+}
+
+/// Compresses the message block `m` into the state `h` over `rounds` rounds,
+/// with the offset counter `t` and the final block flag `f`, as specified by
+/// [RFC 7693] and [EIP-152].
+///
+/// [RFC 7693]: https://www.rfc-editor.org/rfc/rfc7693#section-3.2
+/// [EIP-152]: https://eips.ethereum.org/EIPS/eip-152#specification
+pub fn compress(rounds: u32, h: &mut [u64; 8], m: &[u64; 16], t: [u64; 2], f: bool) {
+    let mut v = [0u64; 16];
+    v[..8].copy_from_slice(h);
+    v[8..].copy_from_slice(&IV);
+    v[12] ^= t[0];
+    v[13] ^= t[1];
+    if f {
+        v[14] = !v[14];
+    }
+
+    for round in 0..rounds as usize {
+        let s = &SIGMA[round % 10];
+        for (i, indices) in MIXINGS.iter().enumerate() {
+            g(&mut v, *indices, m[s[2 * i]], m[s[2 * i + 1]]);
+        }
+    }
+
+    for (i, word) in h.iter_mut().enumerate() {
+        *word ^= v[i] ^ v[i + 8];
+    }
+}
+
+/// Computes the output of the precompile called with `witness`: the
+/// compressed state, as little-endian words.
+pub fn blake2f(witness: &Blake2fWitness) -> [u8; DIGEST_BYTES] {
+    let mut h = witness.h;
+    compress(witness.rounds, &mut h, &witness.m, witness.t, witness.f);
+
+    let mut output = [0u8; DIGEST_BYTES];
+    for (bytes, word) in output.chunks_mut(8).zip(h) {
+        bytes.copy_from_slice(&word.to_le_bytes());
+    }
+    output
+}
@@ -0,0 +1,646 @@
+//! The BLAKE3 circuit verifies calls to the [`BLAKE3`] hash function in its
+//! default hashing mode, with a 32-byte digest. BLAKE3 is not part of the EVM,
+//! but its compression function is a reduced BLAKE2s, with 7 rounds of the
+//! same 32-bit `G` function.
+//!
+//! An input is split into chunks of 1024 bytes, each compressed block by
+//! block, and the chaining values of the chunks are merged by parent
+//! compressions into a binary tree. The root of the tree is compressed with
+//! the `ROOT` flag and its output is the digest.
+//!
+//! The compressions are assigned on a [`WordOpsChip`], an operation per row:
+//! each `G` function takes four additions, four XORs and four rotations. The
+//! message words of a chunk are decomposed into the little-endian bytes of
+//! the input, and the message words of a parent are the chaining values of
+//! its children. The counters, block lengths and flags only depend on the
+//! length of the input, and are constants of the layout.
+//!
+//! The blocks of the chunks of the calls are laid out in the slots of a
+//! [`CallSlotsConfig`], which constrains the bytes past the end of an input
+//! to zero, and pins the lengths of the inputs to those the block lengths
+//! of the layout are of. The circuit exposes the calls through
+//! `Blake3Table`, a [`HashTable`] with one row per call of the RLC and
+//! length of its input and of its digest, whose digest is the little-endian
+//! bytes of the output of the root.
+//!
+//! [`BLAKE3`]: https://github.com/BLAKE3-team/BLAKE3-specs/blob/master/blake3.pdf
+//! [`WordOpsChip`]: gadgets::WordOpsChip
+//! [`CallSlotsConfig`]: gadgets::CallSlotsConfig
+//! [`HashTable`]: gadgets::HashTable
+
+pub mod native;
+
+use std::marker::PhantomData;
+
+use gadgets::{
+    context::{assign_context, Context},
+    public_inputs::PublicInputs,
+    tables::FixedTables,
+    util::AssignedValue,
+    AssignedBytes, AssignedCall, BitFn, BitShift, CallSlotsConfig, Endianness, HashCircuitExt,
+    HashTable, HashTableConfig, SlotCells, SlotPadding, WordCell, WordOpsChip, WordOpsConfig,
+};
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::Layouter,
+    plonk::{Any, Challenge, Column, ConstraintSystem, Error, FirstPhase},
+};
+
+pub type Blake3Table = HashTable;
+
+#[derive(Clone, Debug)]
+pub struct Blake3Config<F> {
+    table: HashTableConfig,
+    word_ops: WordOpsConfig<32>,
+    slots: CallSlotsConfig,
+    _marker: PhantomData<F>,
+}
+
+impl<F: FieldExt> Blake3Config<F> {
+    /// Configures the circuit over `table`, constructed with
+    /// `Blake3Table::construct(meta, native::DIGEST_BYTES)`, and a challenge
+    /// of its own for the RLCs of the table.
+    pub fn configure(meta: &mut ConstraintSystem<F>, table: Blake3Table) -> Self {
+        let challenge = meta.challenge_usable_after(FirstPhase);
+        let table = HashTableConfig::configure(meta, table, challenge);
+        let shifts = native::ROTATIONS.map(|n| vec![BitShift::RotateRight(n as usize)]);
+        let word_ops = WordOpsConfig::configure(meta, &shifts, &[BitFn::XOR]);
+        let slots = CallSlotsConfig::configure(
+            meta,
+            &table,
+            native::BLOCK_BYTES,
+            native::DIGEST_BYTES,
+            SlotPadding::Zeros,
+        );
+        Self {
+            table,
+            word_ops,
+            slots,
+            _marker: PhantomData,
+        }
+    }
+
+    /// The table of the calls.
+    pub fn table(&self) -> &Blake3Table {
+        &self.table.table
+    }
+
+    /// The challenge of the RLCs of [`Self::table`].
+    pub fn challenge(&self) -> Challenge {
+        self.table.challenge
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Blake3Witness<F> {
+    pub inputs: Vec<Vec<u8>>,
+    pub _marker: PhantomData<F>,
+}
+
+#[derive(Clone, Debug)]
+pub struct Blake3Chip<F> {
+    config: Blake3Config<F>,
+    data: Blake3Witness<F>,
+}
+
+impl<F: FieldExt> Blake3Chip<F> {
+    pub fn construct(config: Blake3Config<F>, data: Blake3Witness<F>) -> Self {
+        Self { config, data }
+    }
+
+    fn word_ops(&self) -> WordOpsChip<F, 32> {
+        WordOpsChip::construct(self.config.word_ops.clone())
+    }
+
+    /// Assigns the `G` function mixing `mx` and `my` into the words `[a, b,
+    /// c, d]` of `state`, on 12 rows of `ctx`.
+    fn assign_g(
+        &self,
+        ctx: &mut Context<'_, '_, F>,
+        state: &mut [WordCell<F>],
+        [a, b, c, d]: [usize; 4],
+        mx: &WordCell<F>,
+        my: &WordCell<F>,
+    ) -> Result<(), Error> {
+        let chip = self.word_ops();
+        let rotate =
+            |ctx: &mut Context<'_, '_, F>, i: usize, lhs: &WordCell<F>, rhs: &WordCell<F>| {
+                let xor = chip.apply(ctx, BitFn::XOR, &[lhs, rhs])?;
+                let rotation = BitShift::RotateRight(native::ROTATIONS[i] as usize);
+                chip.shift(ctx, &[rotation], &xor)
+            };
+        for (i, m) in [mx, my].into_iter().enumerate() {
+            state[a] = chip.add(ctx, &[&state[a], &state[b], m], 0)?;
+            state[d] = rotate(ctx, 2 * i, &state[d], &state[a])?;
+            state[c] = chip.add(ctx, &[&state[c], &state[d]], 0)?;
+            state[b] = rotate(ctx, 2 * i + 1, &state[b], &state[c])?;
+        }
+        Ok(())
+    }
+
+    /// Assigns a compression of `block` on the next rows of `ctx`, and
+    /// returns the first half of its output, the chaining value of the node.
+    /// The last 8 words of the initial state are constants, and the message
+    /// words of the rounds are permuted as in [`native::Compression::rounds`].
+    fn assign_compression(
+        &self,
+        ctx: &mut Context<'_, '_, F>,
+        chaining_value: &[WordCell<F>],
+        block: &[WordCell<F>],
+        counter: u64,
+        block_len: usize,
+        flags: u32,
+    ) -> Result<Vec<WordCell<F>>, Error> {
+        let chip = self.word_ops();
+        let mut state = chaining_value.to_vec();
+        let words = native::IV[..4].iter().map(|word| *word as u64);
+        for word in words.chain([
+            counter & 0xffffffff,
+            counter >> 32,
+            block_len as u64,
+            flags as u64,
+        ]) {
+            state.push(chip.constant(ctx, word)?);
+        }
+
+        let mut block = block.to_vec();
+        for r in 0..native::NUM_ROUNDS {
+            for (i, words) in native::MIX.into_iter().enumerate() {
+                self.assign_g(ctx, &mut state, words, &block[2 * i], &block[2 * i + 1])?;
+            }
+            if r + 1 < native::NUM_ROUNDS {
+                block = native::MSG_PERMUTATION.map(|i| block[i].clone()).to_vec();
+            }
+        }
+
+        (0..8)
+            .map(|i| chip.apply(ctx, BitFn::XOR, &[&state[i], &state[i + 8]]))
+            .collect()
+    }
+
+    /// Assigns the compressions of `chunk`, the `counter`-th chunk of the
+    /// input, and returns the cells of the bytes of its blocks, padded with
+    /// zeros, and its chaining value. The last block carries the `ROOT` flag
+    /// if the chunk is the whole input.
+    fn assign_chunk(
+        &self,
+        ctx: &mut Context<'_, '_, F>,
+        iv: &[WordCell<F>],
+        chunk: &[u8],
+        counter: u64,
+        root: bool,
+    ) -> Result<(Vec<Vec<AssignedValue<F>>>, Vec<WordCell<F>>), Error> {
+        let chip = self.word_ops();
+        let blocks: Vec<&[u8]> = if chunk.is_empty() {
+            vec![&[]]
+        } else {
+            chunk.chunks(native::BLOCK_BYTES).collect()
+        };
+        let mut block_cells = Vec::with_capacity(blocks.len());
+        let mut chaining_value = iv.to_vec();
+        for (i, bytes) in blocks.iter().enumerate() {
+            let mut padded = bytes.to_vec();
+            padded.resize(native::BLOCK_BYTES, 0);
+            let mut block = Vec::with_capacity(16);
+            let mut cells = Vec::with_capacity(native::BLOCK_BYTES);
+            for word in padded.chunks(4) {
+                let (word, word_cells) = chip.load_bytes(ctx, word, Endianness::Little)?;
+                cells.extend(word_cells);
+                block.push(word);
+            }
+            block_cells.push(cells);
+
+            let mut flags = 0;
+            if i == 0 {
+                flags |= native::CHUNK_START;
+            }
+            if i + 1 == blocks.len() {
+                flags |= native::CHUNK_END;
+                if root {
+                    flags |= native::ROOT;
+                }
+            }
+            chaining_value =
+                self.assign_compression(ctx, &chaining_value, &block, counter, bytes.len(), flags)?;
+        }
+        Ok((block_cells, chaining_value))
+    }
+
+    /// Assigns the tree of `input` on the next rows of `ctx`, chunk by chunk
+    /// and parent by parent as [`native::compressions`] lists them, followed
+    /// by the bytes of the digest, and returns the cells of the call and of
+    /// the bytes of its blocks, padded with zeros.
+    fn assign_call(
+        &self,
+        ctx: &mut Context<'_, '_, F>,
+        input: &[u8],
+    ) -> Result<(AssignedCall<F>, Vec<Vec<AssignedValue<F>>>), Error> {
+        let chip = self.word_ops();
+        let iv = native::IV
+            .iter()
+            .map(|word| chip.constant(ctx, *word as u64))
+            .collect::<Result<Vec<_>, _>>()?;
+        let parent = |ctx: &mut Context<'_, '_, F>,
+                      left: &[WordCell<F>],
+                      right: &[WordCell<F>],
+                      root: bool| {
+            let block: Vec<_> = left.iter().chain(right).cloned().collect();
+            let flags = native::PARENT | if root { native::ROOT } else { 0 };
+            self.assign_compression(ctx, &iv, &block, 0, native::BLOCK_BYTES, flags)
+        };
+
+        let chunks: Vec<&[u8]> = if input.is_empty() {
+            vec![&[]]
+        } else {
+            input.chunks(native::CHUNK_BYTES).collect()
+        };
+        let mut blocks = Vec::with_capacity(input.len().div_ceil(native::BLOCK_BYTES));
+        let mut stack: Vec<Vec<WordCell<F>>> = Vec::new();
+        for (i, chunk) in chunks[..chunks.len() - 1].iter().enumerate() {
+            let (cells, mut chaining_value) =
+                self.assign_chunk(ctx, &iv, chunk, i as u64, false)?;
+            blocks.extend(cells);
+
+            let mut total_chunks = i as u64 + 1;
+            while total_chunks.is_multiple_of(2) {
+                let left = stack.pop().expect("the subtree has a left child");
+                chaining_value = parent(ctx, &left, &chaining_value, false)?;
+                total_chunks >>= 1;
+            }
+            stack.push(chaining_value);
+        }
+
+        let (cells, mut output) = self.assign_chunk(
+            ctx,
+            &iv,
+            chunks[chunks.len() - 1],
+            chunks.len() as u64 - 1,
+            stack.is_empty(),
+        )?;
+        blocks.extend(cells);
+        while let Some(left) = stack.pop() {
+            output = parent(ctx, &left, &output, stack.is_empty())?;
+        }
+
+        let mut digest = Vec::with_capacity(32);
+        for word in output.iter() {
+            digest.extend(chip.bytes(ctx, word, Endianness::Little)?);
+        }
+        let call = AssignedCall {
+            input: AssignedBytes::new(blocks.concat()[..input.len()].to_vec()),
+            digest: AssignedBytes::new(digest),
+        };
+        Ok((call, blocks))
+    }
+
+    /// Assigns the calls of the witness on the next rows of `ctx`, and
+    /// returns their cells and those of their blocks, a slot each, whose
+    /// digest is that of their call.
+    fn assign_slots(
+        &self,
+        ctx: &mut Context<'_, '_, F>,
+    ) -> Result<(Vec<AssignedCall<F>>, Vec<SlotCells<F>>), Error> {
+        let chip = self.word_ops();
+        let is_first = [chip.constant(ctx, 0)?.cell, chip.constant(ctx, 1)?.cell];
+        let mut calls = Vec::with_capacity(self.data.inputs.len());
+        let mut slots = Vec::new();
+        for input in &self.data.inputs {
+            let (call, blocks) = self.assign_call(ctx, input)?;
+            slots.extend(blocks.into_iter().enumerate().map(|(i, block)| SlotCells {
+                is_first: is_first[(i == 0) as usize].clone(),
+                block,
+                digest: call.digest.cells().to_vec(),
+            }));
+            calls.push(call);
+        }
+        Ok((calls, slots))
+    }
+
+    pub fn load(&self, layouter: &mut impl Layouter<F>) -> Result<(), Error> {
+        self.assign_calls(layouter).map(|_| ())
+    }
+
+    /// Loads the calls of the witness as [`Self::load`] does, and returns the
+    /// cells of their inputs and digests.
+    pub fn assign_calls(
+        &self,
+        layouter: &mut impl Layouter<F>,
+    ) -> Result<Vec<AssignedCall<F>>, Error> {
+        let inputs = &self.data.inputs;
+        let lens: Vec<_> = inputs.iter().map(Vec::len).collect();
+        let slots = &self.config.slots;
+        let blocks = lens.iter().map(|len| slots.blocks(*len)).sum();
+        let plan = slots.plan(&lens, blocks);
+        let (calls, cells) =
+            assign_context(layouter, "blake3 blocks", |ctx| self.assign_slots(ctx))?;
+        slots.assign(layouter, "blake3", &self.config.table, &lens, &plan, &cells)?;
+        let table_calls: Vec<_> = inputs
+            .iter()
+            .map(|input| (input.clone(), native::blake3(input).to_vec()))
+            .collect();
+        self.config
+            .table
+            .assign(layouter, "blake3", inputs.len(), &table_calls)?;
+        Ok(calls)
+    }
+}
+
+impl<F: FieldExt> HashCircuitExt<F> for Blake3Chip<F> {
+    type Config = Blake3Config<F>;
+    type Witness = Blake3Witness<F>;
+
+    const DIGEST_BYTES: usize = native::DIGEST_BYTES;
+
+    /// Configures the circuit over its table, exposed in its instance
+    /// columns.
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let mut config = Self::configure_with_tables(meta, &mut FixedTables::default());
+        config.table.expose(meta);
+        config
+    }
+
+    /// Configures the circuit over its table, not exposed, the circuit
+    /// having no fixed tables.
+    fn configure_with_tables(
+        meta: &mut ConstraintSystem<F>,
+        _tables: &mut FixedTables,
+    ) -> Self::Config {
+        let table = Blake3Table::construct(meta, native::DIGEST_BYTES);
+        Blake3Config::configure(meta, table)
+    }
+
+    /// The calls of the inputs of `witness`, in their order, whose inputs
+    /// are padded with zeros to their blocks.
+    fn public_inputs(witness: &Self::Witness) -> Vec<Vec<F>> {
+        let mut public_inputs = PublicInputs::new(
+            native::DIGEST_BYTES,
+            native::BLOCK_BYTES,
+            SlotPadding::Zeros,
+        );
+        for input in &witness.inputs {
+            public_inputs.call(input, &native::blake3(input));
+        }
+        public_inputs.build()
+    }
+
+    fn construct(config: Self::Config, witness: Self::Witness) -> Self {
+        Blake3Chip::construct(config, witness)
+    }
+
+    fn load(&self, layouter: &mut impl Layouter<F>) -> Result<(), Error> {
+        Blake3Chip::load(self, layouter)
+    }
+
+    fn table_columns(config: &Self::Config) -> Vec<Column<Any>> {
+        config.table().columns()
+    }
+
+    fn annotations(config: &Self::Config) -> Vec<String> {
+        config.table().annotations()
+    }
+
+    fn assigned_digest(
+        &self,
+        layouter: &mut impl Layouter<F>,
+    ) -> Result<Vec<AssignedBytes<F>>, Error> {
+        let calls = self.assign_calls(layouter)?;
+        Ok(calls.into_iter().map(|call| call.digest).collect())
+    }
+}
+
+#[cfg(any(feature = "test", test))]
+pub mod dev {
+    use super::*;
+
+    use ethers_core::{types::H256, utils::hex::FromHex};
+    use halo2_proofs::{circuit::SimpleFloorPlanner, plonk::Circuit};
+    use std::str::FromStr;
+
+    /// The input of length `len` of the official test vectors, the bytes
+    /// `0, 1, ..., 250` repeated.
+    pub fn test_vector_input(len: usize) -> Vec<u8> {
+        (0..len).map(|i| (i % 251) as u8).collect()
+    }
+
+    lazy_static::lazy_static! {
+        // "abc", followed by the vectors of test_vectors.json in the BLAKE3
+        // repository, which cover a single block, a full chunk, and the
+        // parents of two and three chunks.
+        pub static ref INPUTS_OUTPUTS: (Vec<Vec<u8>>, Vec<H256>) = {
+            let mut inputs = vec![Vec::from_hex("616263").expect("input is hex-encoded")];
+            inputs.extend([0, 1, 1023, 1024, 1025, 2048, 2049, 3072].map(test_vector_input));
+            let outputs = [
+                "6437b3ac38465133ffb63b75273a8db548c558465d79db03fd359c6cd5bd9d85",
+                "af1349b9f5f9a1a6a0404dea36dcc9499bcb25c9adc112b7cc9a93cae41f3262",
+                "2d3adedff11b61f14c886e35afa036736dcd87a74d27b5c1510225d0f592e213",
+                "10108970eeda3eb932baac1428c7a2163b0e924c9a9e25b35bba72b28f70bd11",
+                "42214739f095a406f3fc83deb889744ac00df831c10daa55189b5d121c855af7",
+                "d00278ae47eb27b34faecf67b4fe263f82d5412916c1ffd97c8cb7fb814b8444",
+                "e776b6028c7cd22a4d0ba182a8bf62205d2ef576467e838ed6f2529b85fba24a",
+                "5f4d72f40d7a5f82b15ca2b2e44b1de3c2ef86c426c95c1af0b6879522563030",
+                "b98cb0ff3623be03326b373de6b9095218513e64f1ee2edd2525c7ad1e5cffd2",
+            ]
+            .iter()
+            .map(|output| H256::from_str(output).expect("BLAKE3 hash is 32-bytes"))
+            .collect();
+            (inputs, outputs)
+        };
+    }
+
+    #[derive(Default)]
+    pub struct Blake3TestCircuit<F> {
+        pub inputs: Vec<Vec<u8>>,
+        pub outputs: Vec<H256>,
+        pub _marker: PhantomData<F>,
+    }
+
+    impl<F: FieldExt> Circuit<F> for Blake3TestCircuit<F> {
+        type Config = Blake3Config<F>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            let blake3_table = Blake3Table::construct(meta, native::DIGEST_BYTES);
+            Blake3Config::configure(meta, blake3_table)
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            let chip = Blake3Chip::construct(
+                config,
+                Blake3Witness {
+                    inputs: self.inputs.clone(),
+                    _marker: PhantomData,
+                },
+            );
+            chip.load(&mut layouter)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use gadgets::{DigestCircuit, HashCircuitExt};
+    use halo2_proofs::{
+        arithmetic::FieldExt,
+        dev::MockProver,
+        halo2curves::{bn256::Fr, pasta::pallas},
+        plonk::ConstraintSystem,
+    };
+    use std::marker::PhantomData;
+
+    use crate::{
+        dev::{Blake3TestCircuit, INPUTS_OUTPUTS},
+        native, Blake3Chip, Blake3Witness,
+    };
+
+    #[test]
+    fn test_blake3_native() {
+        let (inputs, outputs) = INPUTS_OUTPUTS.clone();
+        for (input, output) in inputs.iter().zip(outputs) {
+            assert_eq!(
+                native::blake3(input),
+                output.0,
+                "input of {} bytes",
+                input.len()
+            );
+        }
+    }
+
+    fn test_blake3_circuit_over<F: FieldExt>() {
+        // The inputs of up to two chunks only, as every compression takes 704
+        // rows.
+        let (inputs, outputs) = INPUTS_OUTPUTS.clone();
+        let (inputs, outputs): (Vec<_>, Vec<_>) = inputs
+            .into_iter()
+            .zip(outputs)
+            .filter(|(input, _)| input.len() <= native::CHUNK_BYTES + 1)
+            .unzip();
+        let k = Blake3Chip::<F>::min_k(&Blake3Witness {
+            inputs: inputs.clone(),
+            _marker: PhantomData,
+        });
+
+        let circuit: Blake3TestCircuit<F> = Blake3TestCircuit {
+            inputs,
+            outputs,
+            _marker: PhantomData,
+        };
+
+        let prover = MockProver::run(k, &circuit, vec![]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+    }
+
+    #[test]
+    fn test_blake3_assigned_digest() {
+        let (inputs, outputs) = INPUTS_OUTPUTS.clone();
+        let witness = Blake3Witness::<Fr> {
+            inputs: inputs[..3].to_vec(),
+            _marker: PhantomData,
+        };
+        let verify = |digests: Vec<Vec<u8>>| {
+            let circuit = DigestCircuit::<Fr, Blake3Chip<Fr>> {
+                witness: witness.clone(),
+                digests,
+                _marker: PhantomData,
+            };
+            let k = Blake3Chip::<Fr>::min_k(&witness);
+            let instances = Blake3Chip::<Fr>::public_inputs(&witness);
+            MockProver::run(k, &circuit, instances).unwrap().verify()
+        };
+        let mut digests: Vec<_> = outputs[..3]
+            .iter()
+            .map(|output| output.0.to_vec())
+            .collect();
+        assert_eq!(verify(digests.clone()), Ok(()));
+        // a byte of a digest is not the one of the call.
+        digests[1][0] ^= 1;
+        assert!(verify(digests).is_err());
+    }
+
+    #[test]
+    fn test_blake3_instance() {
+        let (inputs, outputs) = INPUTS_OUTPUTS.clone();
+        let witness = Blake3Witness::<Fr> {
+            inputs: inputs[..3].to_vec(),
+            _marker: PhantomData,
+        };
+        let circuit = DigestCircuit::<Fr, Blake3Chip<Fr>> {
+            witness: witness.clone(),
+            digests: outputs[..3]
+                .iter()
+                .map(|output| output.0.to_vec())
+                .collect(),
+            _marker: PhantomData,
+        };
+        let k = Blake3Chip::<Fr>::min_k(&witness);
+        let verify =
+            |instances: Vec<Vec<Fr>>| MockProver::run(k, &circuit, instances).unwrap().verify();
+        let instances = Blake3Chip::<Fr>::public_inputs(&witness);
+        assert_eq!(verify(instances.clone()), Ok(()));
+
+        // the length of "abc", its last byte, the 3rd of the first chunk of
+        // its block, and the last of the zeros padding the block.
+        for (column, row, delta) in [(0, 2, 1), (1, 0, 1 << 104), (1, 3, 1)] {
+            let mut instances = instances.clone();
+            instances[column][row] += Fr::from_u128(delta);
+            assert!(verify(instances).is_err(), "instance {column} {row}");
+        }
+    }
+
+    #[test]
+    fn test_blake3_row_usage() {
+        let witness = Blake3Witness::<Fr> {
+            inputs: vec![b"abc".to_vec(), vec![0; native::CHUNK_BYTES + 1]],
+            _marker: PhantomData,
+        };
+        let usage =
+            Blake3Chip::<Fr>::row_usage(Blake3Chip::<Fr>::min_k(&witness), &witness).unwrap();
+        // the flags of the slots, then per call the initial chaining value,
+        // 8 constants, 56 G functions of 12 rows and 8 output rows per
+        // compression, 16 message rows per block of a chunk and the bytes of
+        // the digest, then a row per byte of every block and a row per call
+        // in the table, between their sentinel rows.
+        let compression = 8 + 56 * 12 + 8;
+        let regions: Vec<_> = usage
+            .regions
+            .iter()
+            .map(|region| (region.name.as_str(), region.rows))
+            .collect();
+        assert_eq!(
+            regions,
+            [
+                (
+                    "blake3 blocks",
+                    2 + (8 + 16 + compression + 8)
+                        + (8 + 17 * (16 + compression) + compression + 8)
+                ),
+                ("blake3 slots", 1 + 18 * 64 + 1),
+                ("blake3 table", 1 + 2)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_blake3_hash_circuit_ext() {
+        let mut meta = ConstraintSystem::<Fr>::default();
+        let config = Blake3Chip::<Fr>::configure(&mut meta);
+        assert_eq!(
+            Blake3Chip::<Fr>::table_columns(&config).len(),
+            Blake3Chip::<Fr>::annotations(&config).len()
+        );
+        assert_eq!(Blake3Chip::<Fr>::DIGEST_BYTES, 32);
+    }
+
+    #[test]
+    fn test_blake3_circuit() {
+        test_blake3_circuit_over::<Fr>();
+        test_blake3_circuit_over::<pallas::Base>();
+    }
+}
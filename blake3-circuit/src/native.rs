@@ -0,0 +1,272 @@
+//! Native implementation of BLAKE3 in its default hashing mode, used to
+//! compute the witness of the circuit and as the reference it is tested
+//! against.
+//!
+//! Besides the digest, [`compressions`] lists every call to the compression
+//! function made over the tree of an input, so that the circuit can lay out
+//! one compression after the other.
+
+/// Size of a message block in bytes.
+pub const BLOCK_BYTES: usize = 64;
+
+/// Size of a digest in bytes.
+pub const DIGEST_BYTES: usize = 32;
+
+/// Size of a chunk, the leaves of the tree, in bytes.
+pub const CHUNK_BYTES: usize = 1024;
+
+/// Number of rounds of the compression function.
+pub const NUM_ROUNDS: usize = 7;
+
+/// Initial chaining value, the one of SHA2-256.
+pub const IV: [u32; 8] = [
+    0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f, 0x9b05688c, 0x1f83d9ab, 0x5be0cd19,
+];
+
+/// Rotations to the right of the four XORs of the `G` function.
+pub const ROTATIONS: [u32; 4] = [16, 12, 8, 7];
+
+/// Indices of the state words `a, b, c, d` of the 8 `G` functions of a
+/// round: the columns of the state, then its diagonals.
+pub const MIX: [[usize; 4]; 8] = [
+    [0, 4, 8, 12],
+    [1, 5, 9, 13],
+    [2, 6, 10, 14],
+    [3, 7, 11, 15],
+    [0, 5, 10, 15],
+    [1, 6, 11, 12],
+    [2, 7, 8, 13],
+    [3, 4, 9, 14],
+];
+
+/// Permutation of the message words applied after each round.
+pub const MSG_PERMUTATION: [usize; 16] = [2, 6, 3, 10, 7, 0, 4, 13, 1, 11, 12, 5, 9, 14, 15, 8];
+
+/// Domain separation flags of the compression function.
+pub const CHUNK_START: u32 = 1 << 0;
+pub const CHUNK_END: u32 = 1 << 1;
+pub const PARENT: u32 = 1 << 2;
+pub const ROOT: u32 = 1 << 3;
+
+/// The inputs of a call to the compression function.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Compression {
+    pub chaining_value: [u32; 8],
+    pub block: [u32; 16],
+    pub counter: u64,
+    pub block_len: u32,
+    pub flags: u32,
+}
+
+impl Compression {
+    /// The state after the 7 rounds, before the halves are XORed together.
+    pub fn rounds(&self) -> [u32; 16] {
+        let cv = self.chaining_value;
+        let mut state = [
+            cv[0],
+            cv[1],
+            cv[2],
+            cv[3],
+            cv[4],
+            cv[5],
+            cv[6],
+            cv[7],
+            IV[0],
+            IV[1],
+            IV[2],
+            IV[3],
+            self.counter as u32,
+            (self.counter >> 32) as u32,
+            self.block_len,
+            self.flags,
+        ];
+        let mut block = self.block;
+        for r in 0..NUM_ROUNDS {
+            round(&mut state, &block);
+            if r + 1 < NUM_ROUNDS {
+                block = MSG_PERMUTATION.map(|i| block[i]);
+            }
+        }
+        state
+    }
+
+    /// The output of the compression function: `v_i ^ v_{i + 8}` followed by
+    /// `v_{i + 8} ^ h_i`, the first half being the chaining value of a node.
+    pub fn output(&self) -> [u32; 16] {
+        let state = self.rounds();
+        let mut output = [0u32; 16];
+        for i in 0..8 {
+            output[i] = state[i] ^ state[i + 8];
+            output[i + 8] = state[i + 8] ^ self.chaining_value[i];
+        }
+        output
+    }
+
+    pub fn chaining_value(&self) -> [u32; 8] {
+        let output = self.output();
+        output[..8].try_into().expect("output has 16 words")
+    }
+}
+
+/// The quarter-round mixing `m_x` and `m_y` into the words `[a, b, c, d]`
+/// of the state.
+fn g(state: &mut [u32; 16], [a, b, c, d]: [usize; 4], mx: u32, my: u32) {
+    state[a] = state[a].wrapping_add(state[b]).wrapping_add(mx);
+    state[d] = (state[d] ^ state[a]).rotate_right(ROTATIONS[0]);
+    state[c] = state[c].wrapping_add(state[d]);
+    state[b] = (state[b] ^ state[c]).rotate_right(ROTATIONS[1]);
+    state[a] = state[a].wrapping_add(state[b]).wrapping_add(my);
+    state[d] = (state[d] ^ state[a]).rotate_right(ROTATIONS[2]);
+    state[c] = state[c].wrapping_add(state[d]);
+    state[b] = (state[b] ^ state[c]).rotate_right(ROTATIONS[3]);
+}
+
+/// A round: the columns of the state are mixed, then its diagonals, the
+/// `i`-th `G` function with the message words `2i` and `2i + 1`.
+fn round(state: &mut [u32; 16], m: &[u32; 16]) {
+    for (i, words) in MIX.into_iter().enumerate() {
+        g(state, words, m[2 * i], m[2 * i + 1]);
+    }
+}
+
+/// Reads a block of at most [`BLOCK_BYTES`] bytes as 16 little-endian words,
+/// padded with zeros.
+pub fn block_words(block: &[u8]) -> [u32; 16] {
+    assert!(block.len() <= BLOCK_BYTES);
+    let mut bytes = [0u8; BLOCK_BYTES];
+    bytes[..block.len()].copy_from_slice(block);
+    let mut words = [0u32; 16];
+    for (word, bytes) in words.iter_mut().zip(bytes.chunks(4)) {
+        *word = u32::from_le_bytes(bytes.try_into().expect("words are 4 bytes"));
+    }
+    words
+}
+
+/// The compressions of a chunk, the last one being the output of the chunk
+/// without the [`ROOT`] flag.
+fn chunk_compressions(chunk: &[u8], counter: u64) -> Vec<Compression> {
+    let blocks: Vec<&[u8]> = if chunk.is_empty() {
+        vec![&[]]
+    } else {
+        chunk.chunks(BLOCK_BYTES).collect()
+    };
+    let mut chaining_value = IV;
+    let mut compressions = Vec::with_capacity(blocks.len());
+    for (i, block) in blocks.iter().enumerate() {
+        let mut flags = 0;
+        if i == 0 {
+            flags |= CHUNK_START;
+        }
+        if i + 1 == blocks.len() {
+            flags |= CHUNK_END;
+        }
+        let compression = Compression {
+            chaining_value,
+            block: block_words(block),
+            counter,
+            block_len: block.len() as u32,
+            flags,
+        };
+        chaining_value = compression.chaining_value();
+        compressions.push(compression);
+    }
+    compressions
+}
+
+fn parent_compression(left: &[u32; 8], right: &[u32; 8]) -> Compression {
+    let mut block = [0u32; 16];
+    block[..8].copy_from_slice(left);
+    block[8..].copy_from_slice(right);
+    Compression {
+        chaining_value: IV,
+        block,
+        counter: 0,
+        block_len: BLOCK_BYTES as u32,
+        flags: PARENT,
+    }
+}
+
+/// Every compression over the tree of `input`, in the order they are
+/// computed. The last one is the root of the tree and carries the [`ROOT`]
+/// flag.
+///
+/// The chaining values of the completed subtrees are kept on a stack: after
+/// the `n`-th chunk, as many parents are merged as there are trailing zeros in
+/// `n`, and the remaining subtrees are merged from right to left with the last
+/// chunk at the end.
+pub fn compressions(input: &[u8]) -> Vec<Compression> {
+    let chunks: Vec<&[u8]> = if input.is_empty() {
+        vec![&[]]
+    } else {
+        input.chunks(CHUNK_BYTES).collect()
+    };
+
+    let mut compressions = Vec::new();
+    let mut stack: Vec<[u32; 8]> = Vec::new();
+    for (i, chunk) in chunks[..chunks.len() - 1].iter().enumerate() {
+        let chunk_compressions = chunk_compressions(chunk, i as u64);
+        let mut chaining_value = chunk_compressions
+            .last()
+            .expect("a chunk has a block")
+            .chaining_value();
+        compressions.extend(chunk_compressions);
+
+        let mut total_chunks = i as u64 + 1;
+        while total_chunks.is_multiple_of(2) {
+            let left = stack.pop().expect("the subtree has a left child");
+            let parent = parent_compression(&left, &chaining_value);
+            chaining_value = parent.chaining_value();
+            compressions.push(parent);
+            total_chunks >>= 1;
+        }
+        stack.push(chaining_value);
+    }
+
+    compressions.extend(chunk_compressions(
+        chunks[chunks.len() - 1],
+        chunks.len() as u64 - 1,
+    ));
+    while let Some(left) = stack.pop() {
+        let right = compressions
+            .last()
+            .expect("the last chunk is compressed")
+            .chaining_value();
+        compressions.push(parent_compression(&left, &right));
+    }
+    compressions.last_mut().expect("the tree has a root").flags |= ROOT;
+    compressions
+}
+
+/// Computes the 32-byte BLAKE3 digest of `input`.
+pub fn blake3(input: &[u8]) -> [u8; DIGEST_BYTES] {
+    let root = compressions(input)
+        .pop()
+        .expect("the tree has a root")
+        .output();
+    let mut digest = [0u8; 32];
+    for (bytes, word) in digest.chunks_mut(4).zip(root) {
+        bytes.copy_from_slice(&word.to_le_bytes());
+    }
+    digest
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compressions() {
+        let count = |len: usize| compressions(&vec![0; len]).len();
+        assert_eq!(count(0), 1);
+        assert_eq!(count(BLOCK_BYTES), 1);
+        assert_eq!(count(BLOCK_BYTES + 1), 2);
+        assert_eq!(count(CHUNK_BYTES), 16);
+        // Two chunks of 16 and 1 blocks, and their parent.
+        assert_eq!(count(CHUNK_BYTES + 1), 18);
+        // Three chunks, the parent of the first two, and the root.
+        assert_eq!(count(2 * CHUNK_BYTES + 1), 16 + 16 + 1 + 1 + 1);
+
+        let root = compressions(&[]).pop().unwrap();
+        assert_eq!(root.flags, CHUNK_START | CHUNK_END | ROOT);
+    }
+}
@@ -0,0 +1,801 @@
+//! The `precompile-prover` binary, which proves the calls of a precompile
+//! with its circuit end to end, one subcommand per step, from the
+//! parameters of the commitment scheme to the verification of a proof:
+//!
+//! ```text
+//! precompile-prover setup --k 12 --params params
+//! precompile-prover keygen --circuit sha2 --params params --keys keys
+//! precompile-prover prove --circuit sha2 --params params --keys keys --inputs calls.json --proof proof
+//! precompile-prover verify --circuit sha2 --params params --keys keys --inputs calls.json --proof proof
+//! precompile-prover verify-contract --circuit sha2 --params params --keys keys --inputs calls.json --proof proof --contract contract
+//! ```
+//!
+//! `--circuit` is one of `sha2`, `blake2f` and `ripemd160`. The inputs of
+//! the calls are read from a `.json` file, an array of hex strings, or from
+//! a file of any other extension, a hex string per line, an empty line
+//! being an empty input; the `0x` prefix is optional. A call of BLAKE2F is
+//! the 213 bytes of its EIP-152 input.
+//!
+//! `setup` generates the parameters of degree `k` from a local secret, so
+//! that anyone knowing it could forge proofs, or reads those of a
+//! production setup from `--srs`, as the `convert_srs` binary of the
+//! `benchmarking` crate does. The keys are written to the `vk` and `pk`
+//! files of their directory, in the [`SERDE_FORMAT`] of the benchmarks and
+//! prefixed with the version of the constraint system of their circuit, see
+//! [`gadgets::keys`], so that `prove` and `verify` refuse the keys of a
+//! circuit whose gates changed since `keygen`, and the proofs are the SHPLONK
+//! proofs of [`prover::prove`], over the BLAKE2b transcript, or Keccak-256
+//! with the `evm` feature.
+//!
+//! `verify-contract`, of the `evm` feature, generates the verifier contract
+//! of the verifying key in `--keys` with the EVM loader of `snark-verifier`,
+//! for the instance columns of the circuit, writes it and the calldata of
+//! the proof and of the public inputs of the calls of `--inputs` to the
+//! `Verifier.sol` and `calldata` files of the directory `--contract`, and
+//! calls the contract,
+//! compiled by the `solc` of the `PATH`, with the calldata in `revm`, see
+//! the `evm` module of `prover`.
+//!
+//! The circuits are of a bounded capacity, of [`MAX_CALLS`] calls of
+//! [`MAX_INPUT_BYTES`] input bytes in total, or of [`MAX_ROUNDS`] rounds each
+//! for BLAKE2F, and their layout is that of their capacity whatever their
+//! calls, see [`gadgets::capacity`]. The keys of a circuit are generated from
+//! its layout, so that `keygen` needs no inputs: the proof of any calls
+//! within the capacity verifies with them, and calls exceeding it are
+//! refused with an [`Error::Capacity`]. The instances of a proof are the
+//! public inputs of its calls, the digests and input bytes of every call as
+//! [`gadgets::public_inputs`] lays them out, padded to the capacity, so that
+//! `verify` reads the inputs of the calls the proof is checked against.
+//!
+//! The subcommands fail with a [`gadgets::Error`], whose message says which
+//! file, input or key is refused and why, e.g. the bytes of a BLAKE2F input
+//! of another length than 213, rather than the `plonk::Error` of halo2.
+//!
+//! `witness` prints the hash of the witness of the calls of an inputs file,
+//! which needs neither parameters nor keys and does not depend on `--k`, so
+//! that two machines or versions compare their witness generation when a
+//! proof only fails on one of them.
+
+use std::{
+    collections::HashMap,
+    fmt,
+    fs::{self, File},
+    io::{self, BufReader, BufWriter, Write},
+    marker::PhantomData,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use blake2f_circuit::{native::blake2f, Blake2fBatchCircuit, Blake2fChip, Blake2fWitness};
+use ethers_core::utils::hex;
+use gadgets::{
+    keys::{read_pk, read_vk, write_pk, write_vk},
+    witness::witness_hash,
+    Error, HashCircuitExt,
+};
+use halo2_proofs::{
+    halo2curves::bn256::{Bn256, Fr, G1Affine},
+    plonk::{Circuit, VerifyingKey},
+    poly::{commitment::Params, kzg::commitment::ParamsKZG},
+};
+use prover::{srs::read_srs, SERDE_FORMAT};
+use rand::rngs::OsRng;
+use ripemd160_circuit::{
+    native::ripemd160, Ripemd160BatchCircuit, Ripemd160Chip, Ripemd160Witness,
+};
+use sha2_256_circuit::{native::sha256, Sha2BatchCircuit, Sha2Chip, Sha2Witness};
+
+/// Capacity of the circuits: the calls of a proof, and their input bytes in
+/// total, or the rounds of a call of BLAKE2F.
+pub const MAX_CALLS: usize = 4;
+pub const MAX_INPUT_BYTES: usize = 256;
+pub const MAX_ROUNDS: usize = 12;
+
+type Sha2Circuit = Sha2BatchCircuit<Fr, MAX_CALLS, MAX_INPUT_BYTES>;
+type Blake2fCircuit = Blake2fBatchCircuit<Fr, MAX_CALLS, MAX_ROUNDS>;
+type Ripemd160Circuit = Ripemd160BatchCircuit<Fr, MAX_CALLS, MAX_INPUT_BYTES>;
+
+/// Files of the keys in their directory.
+pub const VK_FILE: &str = "vk";
+pub const PK_FILE: &str = "pk";
+
+/// Files of the verifier contract and of the calldata of a proof, the hex
+/// string of its bytes, in their directory.
+#[cfg(feature = "evm")]
+pub const CONTRACT_FILE: &str = "Verifier.sol";
+#[cfg(feature = "evm")]
+pub const CALLDATA_FILE: &str = "calldata";
+
+/// The precompile a circuit proves the calls of.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Precompile {
+    Sha2,
+    Blake2f,
+    Ripemd160,
+}
+
+impl FromStr for Precompile {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "sha2" => Ok(Self::Sha2),
+            "blake2f" => Ok(Self::Blake2f),
+            "ripemd160" => Ok(Self::Ripemd160),
+            _ => Err(format!("unknown circuit {s}")),
+        }
+    }
+}
+
+impl fmt::Display for Precompile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Sha2 => write!(f, "sha2"),
+            Self::Blake2f => write!(f, "blake2f"),
+            Self::Ripemd160 => write!(f, "ripemd160"),
+        }
+    }
+}
+
+/// A subcommand of the command line, and the files it reads and writes.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Command {
+    Setup {
+        k: u32,
+        srs: Option<PathBuf>,
+        params: PathBuf,
+    },
+    Keygen {
+        circuit: Precompile,
+        params: PathBuf,
+        keys: PathBuf,
+    },
+    Prove {
+        circuit: Precompile,
+        params: PathBuf,
+        keys: PathBuf,
+        inputs: PathBuf,
+        proof: PathBuf,
+    },
+    Verify {
+        circuit: Precompile,
+        params: PathBuf,
+        keys: PathBuf,
+        inputs: PathBuf,
+        proof: PathBuf,
+    },
+    Witness {
+        circuit: Precompile,
+        k: u32,
+        inputs: PathBuf,
+    },
+    #[cfg(feature = "evm")]
+    VerifyContract {
+        circuit: Precompile,
+        params: PathBuf,
+        keys: PathBuf,
+        inputs: PathBuf,
+        proof: PathBuf,
+        contract: PathBuf,
+    },
+}
+
+impl Command {
+    /// The command of `args`, a subcommand followed by its `--name value`
+    /// flags in any order, or `None` if a flag is missing, repeated or
+    /// unknown.
+    pub fn parse(args: &[String]) -> Option<Self> {
+        let (command, args) = args.split_first()?;
+        let mut flags = HashMap::new();
+        for flag in args.chunks(2) {
+            let [name, value] = flag else {
+                return None;
+            };
+            if flags
+                .insert(name.strip_prefix("--")?, value.as_str())
+                .is_some()
+            {
+                return None;
+            }
+        }
+        let mut flag = |name| flags.remove(name);
+        let command = match command.as_str() {
+            "setup" => Self::Setup {
+                k: flag("k")?.parse().ok()?,
+                srs: flag("srs").map(PathBuf::from),
+                params: flag("params")?.into(),
+            },
+            "keygen" => Self::Keygen {
+                circuit: flag("circuit")?.parse().ok()?,
+                params: flag("params")?.into(),
+                keys: flag("keys")?.into(),
+            },
+            "prove" => Self::Prove {
+                circuit: flag("circuit")?.parse().ok()?,
+                params: flag("params")?.into(),
+                keys: flag("keys")?.into(),
+                inputs: flag("inputs")?.into(),
+                proof: flag("proof")?.into(),
+            },
+            "verify" => Self::Verify {
+                circuit: flag("circuit")?.parse().ok()?,
+                params: flag("params")?.into(),
+                keys: flag("keys")?.into(),
+                inputs: flag("inputs")?.into(),
+                proof: flag("proof")?.into(),
+            },
+            "witness" => Self::Witness {
+                circuit: flag("circuit")?.parse().ok()?,
+                k: flag("k")?.parse().ok()?,
+                inputs: flag("inputs")?.into(),
+            },
+            #[cfg(feature = "evm")]
+            "verify-contract" => Self::VerifyContract {
+                circuit: flag("circuit")?.parse().ok()?,
+                params: flag("params")?.into(),
+                keys: flag("keys")?.into(),
+                inputs: flag("inputs")?.into(),
+                proof: flag("proof")?.into(),
+                contract: flag("contract")?.into(),
+            },
+            _ => return None,
+        };
+        flags.is_empty().then_some(command)
+    }
+}
+
+/// The circuit proving the calls of a precompile, of a bounded capacity,
+/// with no calls by default.
+trait PrecompileCircuit: Circuit<Fr> + Default {
+    /// The circuit of the calls of `inputs`, or an [`Error::Encoding`] if
+    /// one of them is not the input of a call, or an [`Error::Capacity`] if
+    /// they exceed the capacity of the circuit.
+    fn from_inputs(inputs: Vec<Vec<u8>>) -> Result<Self, Error>;
+
+    /// The outputs of the calls, computed natively.
+    fn outputs(&self) -> Vec<Vec<u8>>;
+
+    /// The instances of the proof of the calls, whose lengths are those of
+    /// the capacity whatever the calls.
+    fn public_inputs(&self) -> Vec<Vec<Fr>>;
+
+    /// Smallest degree the layout of the circuit fits in.
+    fn min_k(&self) -> u32;
+}
+
+impl PrecompileCircuit for Sha2Circuit {
+    fn from_inputs(inputs: Vec<Vec<u8>>) -> Result<Self, Error> {
+        let witness = Sha2Witness {
+            inputs,
+            _marker: PhantomData,
+        };
+        Sha2Chip::<Fr, MAX_CALLS, MAX_INPUT_BYTES>::check_witness(&witness)?;
+        Ok(Self::new(vec![witness]))
+    }
+
+    fn outputs(&self) -> Vec<Vec<u8>> {
+        self.witness
+            .inputs
+            .iter()
+            .map(|input| sha256(input).to_vec())
+            .collect()
+    }
+
+    fn public_inputs(&self) -> Vec<Vec<Fr>> {
+        Sha2Chip::<Fr, MAX_CALLS, MAX_INPUT_BYTES>::public_inputs(&self.witness)
+    }
+
+    fn min_k(&self) -> u32 {
+        Sha2Chip::<Fr, MAX_CALLS, MAX_INPUT_BYTES>::min_k(&self.witness)
+    }
+}
+
+impl PrecompileCircuit for Blake2fCircuit {
+    fn from_inputs(inputs: Vec<Vec<u8>>) -> Result<Self, Error> {
+        let witness = inputs
+            .iter()
+            .enumerate()
+            .map(|(i, input)| {
+                Blake2fWitness::from_input(input).map_err(|err| match err {
+                    Error::Encoding { precompile, reason } => Error::Encoding {
+                        precompile,
+                        reason: format!("{reason}, in input {i}"),
+                    },
+                    err => err,
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Blake2fChip::<Fr, MAX_CALLS, MAX_ROUNDS>::check_witness(&witness)?;
+        Ok(Self::new(vec![witness]))
+    }
+
+    fn outputs(&self) -> Vec<Vec<u8>> {
+        self.witness
+            .iter()
+            .map(|call| blake2f(call).to_vec())
+            .collect()
+    }
+
+    fn public_inputs(&self) -> Vec<Vec<Fr>> {
+        Blake2fChip::<Fr, MAX_CALLS, MAX_ROUNDS>::public_inputs(&self.witness)
+    }
+
+    fn min_k(&self) -> u32 {
+        Blake2fChip::<Fr, MAX_CALLS, MAX_ROUNDS>::min_k(&self.witness)
+    }
+}
+
+impl PrecompileCircuit for Ripemd160Circuit {
+    fn from_inputs(inputs: Vec<Vec<u8>>) -> Result<Self, Error> {
+        let witness = Ripemd160Witness {
+            inputs,
+            _marker: PhantomData,
+        };
+        Ripemd160Chip::<Fr, MAX_CALLS, MAX_INPUT_BYTES>::check_witness(&witness)?;
+        Ok(Self::new(vec![witness]))
+    }
+
+    fn outputs(&self) -> Vec<Vec<u8>> {
+        self.witness
+            .inputs
+            .iter()
+            .map(|input| ripemd160(input).to_vec())
+            .collect()
+    }
+
+    fn public_inputs(&self) -> Vec<Vec<Fr>> {
+        Ripemd160Chip::<Fr, MAX_CALLS, MAX_INPUT_BYTES>::public_inputs(&self.witness)
+    }
+
+    fn min_k(&self) -> u32 {
+        Ripemd160Chip::<Fr, MAX_CALLS, MAX_INPUT_BYTES>::min_k(&self.witness)
+    }
+}
+
+fn invalid_data(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+/// Prefixes the errors of the file at `path` with its path.
+fn at(path: &Path) -> impl FnOnce(io::Error) -> io::Error + '_ {
+    move |err| io::Error::new(err.kind(), format!("{}: {err}", path.display()))
+}
+
+/// The inputs of the calls in `text`, a JSON array of hex strings if
+/// `json`, or a hex string per line otherwise.
+fn parse_inputs(text: &str, json: bool) -> io::Result<Vec<Vec<u8>>> {
+    let inputs = if json {
+        let value = serde_json::from_str(text).map_err(|err| invalid_data(err.to_string()))?;
+        value
+            .as_array()
+            .ok_or_else(|| invalid_data("the inputs are not a JSON array".to_string()))?
+            .iter()
+            .enumerate()
+            .map(|(i, input)| {
+                input
+                    .as_str()
+                    .map(str::to_string)
+                    .ok_or_else(|| invalid_data(format!("input {i} is not a string")))
+            })
+            .collect::<io::Result<Vec<_>>>()?
+    } else {
+        text.lines().map(str::to_string).collect()
+    };
+    inputs
+        .iter()
+        .enumerate()
+        .map(|(i, input)| {
+            let input = input.trim();
+            hex::decode(input.strip_prefix("0x").unwrap_or(input))
+                .map_err(|err| invalid_data(format!("input {i} is not hex: {err}")))
+        })
+        .collect()
+}
+
+/// Reads the inputs of the calls in the file at `path`, see [`parse_inputs`].
+pub fn read_inputs(path: &Path) -> io::Result<Vec<Vec<u8>>> {
+    let text = fs::read_to_string(path).map_err(at(path))?;
+    let json = path
+        .extension()
+        .is_some_and(|extension| extension == "json");
+    parse_inputs(&text, json).map_err(at(path))
+}
+
+/// Creates the file at `path`, and writes it with `write`.
+fn create(
+    path: &Path,
+    write: impl FnOnce(&mut BufWriter<File>) -> io::Result<()>,
+) -> io::Result<()> {
+    File::create(path)
+        .map(BufWriter::new)
+        .and_then(|mut file| {
+            write(&mut file)?;
+            file.flush()
+        })
+        .map_err(at(path))
+}
+
+fn read_params(path: &Path) -> io::Result<ParamsKZG<Bn256>> {
+    File::open(path)
+        .and_then(|file| ParamsKZG::read(&mut BufReader::new(file)))
+        .map_err(at(path))
+}
+
+/// Writes the parameters of degree `k` to `params`, read from the SRS at
+/// `srs` if any, or generated from a local secret otherwise.
+pub fn setup(k: u32, srs: Option<&Path>, params: &Path) -> Result<(), Error> {
+    let generated = match srs {
+        Some(srs) => read_srs(srs, k).map_err(at(srs))?,
+        None => ParamsKZG::<Bn256>::setup(k, OsRng),
+    };
+    Ok(create(params, |file| generated.write(file))?)
+}
+
+fn keygen_with<C: PrecompileCircuit>(params: &Path, keys: &Path) -> Result<(), Error> {
+    let params = read_params(params)?;
+    let pk = prover::keygen(&params, &C::default())?;
+
+    fs::create_dir_all(keys).map_err(at(keys))?;
+    create(&keys.join(VK_FILE), |file| {
+        write_vk::<_, C>(file, pk.get_vk(), SERDE_FORMAT)
+    })?;
+    Ok(create(&keys.join(PK_FILE), |file| {
+        write_pk::<_, C>(file, &pk, SERDE_FORMAT)
+    })?)
+}
+
+/// Generates the keys of the circuit of `circuit` with the parameters at
+/// `params`, and writes them to the directory `keys`.
+pub fn keygen(circuit: Precompile, params: &Path, keys: &Path) -> Result<(), Error> {
+    match circuit {
+        Precompile::Sha2 => keygen_with::<Sha2Circuit>(params, keys),
+        Precompile::Blake2f => keygen_with::<Blake2fCircuit>(params, keys),
+        Precompile::Ripemd160 => keygen_with::<Ripemd160Circuit>(params, keys),
+    }
+}
+
+fn prove_with<C: PrecompileCircuit>(
+    params: &Path,
+    keys: &Path,
+    inputs: &Path,
+    proof: &Path,
+) -> Result<Vec<Vec<u8>>, Error> {
+    let circuit = C::from_inputs(read_inputs(inputs)?)?;
+    let outputs = circuit.outputs();
+    let instances = circuit.public_inputs();
+    let params = read_params(params)?;
+    let pk_path = keys.join(PK_FILE);
+    let pk = File::open(&pk_path)
+        .and_then(|file| read_pk::<G1Affine, C>(&mut BufReader::new(file), SERDE_FORMAT))
+        .map_err(at(&pk_path))?;
+    let bytes = prover::prove(&params, &pk, circuit, &instances)?;
+    fs::write(proof, bytes).map_err(at(proof))?;
+    Ok(outputs)
+}
+
+/// Proves the calls of the inputs at `inputs` with the parameters at
+/// `params` and the proving key in `keys`, writes the proof to `proof`, and
+/// returns the outputs of the calls.
+pub fn prove(
+    circuit: Precompile,
+    params: &Path,
+    keys: &Path,
+    inputs: &Path,
+    proof: &Path,
+) -> Result<Vec<Vec<u8>>, Error> {
+    match circuit {
+        Precompile::Sha2 => prove_with::<Sha2Circuit>(params, keys, inputs, proof),
+        Precompile::Blake2f => prove_with::<Blake2fCircuit>(params, keys, inputs, proof),
+        Precompile::Ripemd160 => prove_with::<Ripemd160Circuit>(params, keys, inputs, proof),
+    }
+}
+
+/// Reads the verifying key of `C` in the directory `keys`.
+fn read_vk_in<C: PrecompileCircuit>(keys: &Path) -> io::Result<VerifyingKey<G1Affine>> {
+    let vk_path = keys.join(VK_FILE);
+    File::open(&vk_path)
+        .and_then(|file| read_vk::<G1Affine, C>(&mut BufReader::new(file), SERDE_FORMAT))
+        .map_err(at(&vk_path))
+}
+
+fn verify_with<C: PrecompileCircuit>(
+    params: &Path,
+    keys: &Path,
+    inputs: &Path,
+    proof: &Path,
+) -> Result<bool, Error> {
+    let instances = C::from_inputs(read_inputs(inputs)?)?.public_inputs();
+    let params = read_params(params)?;
+    let vk = read_vk_in::<C>(keys)?;
+    let proof = fs::read(proof).map_err(at(proof))?;
+    Ok(prover::verify(&params, &vk, &proof, &instances))
+}
+
+/// Whether the proof at `proof` is valid for the calls of the inputs at
+/// `inputs`, verified with the parameters at `params` and the verifying key
+/// in `keys`.
+pub fn verify(
+    circuit: Precompile,
+    params: &Path,
+    keys: &Path,
+    inputs: &Path,
+    proof: &Path,
+) -> Result<bool, Error> {
+    match circuit {
+        Precompile::Sha2 => verify_with::<Sha2Circuit>(params, keys, inputs, proof),
+        Precompile::Blake2f => verify_with::<Blake2fCircuit>(params, keys, inputs, proof),
+        Precompile::Ripemd160 => verify_with::<Ripemd160Circuit>(params, keys, inputs, proof),
+    }
+}
+
+#[cfg(feature = "evm")]
+fn verify_contract_with<C: PrecompileCircuit>(
+    params: &Path,
+    keys: &Path,
+    inputs: &Path,
+    proof: &Path,
+    contract: &Path,
+) -> Result<Option<u64>, Error> {
+    let instances = C::from_inputs(read_inputs(inputs)?)?.public_inputs();
+    let params = read_params(params)?;
+    let vk = read_vk_in::<C>(keys)?;
+    let proof = fs::read(proof).map_err(at(proof))?;
+    // the lengths of the instances are those of the capacity whatever the
+    // calls, so that the contract verifies the proof of any calls.
+    let num_instance = instances.iter().map(Vec::len).collect();
+    let source = prover::evm::verifier_contract(&params, &vk, num_instance);
+    let calldata = prover::evm::calldata(&instances, &proof);
+
+    fs::create_dir_all(contract).map_err(at(contract))?;
+    let contract_path = contract.join(CONTRACT_FILE);
+    fs::write(&contract_path, &source).map_err(at(&contract_path))?;
+    let calldata_path = contract.join(CALLDATA_FILE);
+    fs::write(&calldata_path, format!("0x{}", hex::encode(&calldata)))
+        .map_err(at(&calldata_path))?;
+    Ok(prover::evm::call_verifier(
+        prover::evm::compile(&source),
+        calldata,
+    ))
+}
+
+/// Writes the verifier contract of the verifying key in `keys` and the
+/// calldata of the proof at `proof` for the calls of the inputs at `inputs`
+/// to the directory `contract`, and calls the contract with the calldata in
+/// `revm`: the gas of the call if the contract accepts the proof, or `None`
+/// if it reverts.
+#[cfg(feature = "evm")]
+pub fn verify_contract(
+    circuit: Precompile,
+    params: &Path,
+    keys: &Path,
+    inputs: &Path,
+    proof: &Path,
+    contract: &Path,
+) -> Result<Option<u64>, Error> {
+    match circuit {
+        Precompile::Sha2 => {
+            verify_contract_with::<Sha2Circuit>(params, keys, inputs, proof, contract)
+        }
+        Precompile::Blake2f => {
+            verify_contract_with::<Blake2fCircuit>(params, keys, inputs, proof, contract)
+        }
+        Precompile::Ripemd160 => {
+            verify_contract_with::<Ripemd160Circuit>(params, keys, inputs, proof, contract)
+        }
+    }
+}
+
+fn witness_with<C: PrecompileCircuit>(k: u32, inputs: &Path) -> Result<u64, Error> {
+    let circuit = C::from_inputs(read_inputs(inputs)?)?;
+    let instances = circuit.public_inputs();
+    Ok(witness_hash(k, &circuit, instances)?)
+}
+
+/// The hash of the witness of the calls of the inputs at `inputs` at degree
+/// `k`, which does not depend on `k`, to compare the witness generation of
+/// two machines or versions, see [`witness_hash`].
+pub fn witness(circuit: Precompile, k: u32, inputs: &Path) -> Result<u64, Error> {
+    match circuit {
+        Precompile::Sha2 => witness_with::<Sha2Circuit>(k, inputs),
+        Precompile::Blake2f => witness_with::<Blake2fCircuit>(k, inputs),
+        Precompile::Ripemd160 => witness_with::<Ripemd160Circuit>(k, inputs),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(line: &str) -> Vec<String> {
+        line.split_whitespace().map(str::to_string).collect()
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(
+            Command::parse(&args("setup --params params --k 12")),
+            Some(Command::Setup {
+                k: 12,
+                srs: None,
+                params: "params".into(),
+            })
+        );
+        assert_eq!(
+            Command::parse(&args(
+                "prove --circuit blake2f --params p --keys k --inputs i.json --proof proof"
+            )),
+            Some(Command::Prove {
+                circuit: Precompile::Blake2f,
+                params: "p".into(),
+                keys: "k".into(),
+                inputs: "i.json".into(),
+                proof: "proof".into(),
+            })
+        );
+        #[cfg(feature = "evm")]
+        assert_eq!(
+            Command::parse(&args(
+                "verify-contract --circuit sha2 --params p --keys k --inputs i --proof proof --contract c"
+            )),
+            Some(Command::VerifyContract {
+                circuit: Precompile::Sha2,
+                params: "p".into(),
+                keys: "k".into(),
+                inputs: "i".into(),
+                proof: "proof".into(),
+                contract: "c".into(),
+            })
+        );
+        for line in [
+            "keygen --circuit sha3 --params p --keys k",
+            "keygen --circuit sha2 --params p",
+            "keygen --circuit sha2 --params p --keys k --keys l",
+            "keygen --circuit sha2 --params p --keys k --proof proof",
+            "keygen --circuit sha2 --params p --keys",
+            "setup --k twelve --params p",
+            "verify --circuit sha2 --params p --keys k --proof proof",
+            "verify --circuit sha2 --params p --keys k --inputs i --proof proof --transcript k",
+            "witness --circuit sha2 --inputs i.json",
+            "aggregate --circuit sha2",
+        ] {
+            assert_eq!(Command::parse(&args(line)), None, "{line}");
+        }
+    }
+
+    #[test]
+    fn test_parse_inputs() {
+        let inputs = vec![vec![], b"abc".to_vec()];
+        assert_eq!(parse_inputs(r#"["", "0x616263"]"#, true).unwrap(), inputs);
+        assert_eq!(parse_inputs("\n616263\n", false).unwrap(), inputs);
+        assert!(parse_inputs("[1]", true).is_err());
+        assert!(parse_inputs("61626", false).is_err());
+
+        let call = Blake2fWitness::default().to_input();
+        assert!(Blake2fCircuit::from_inputs(vec![call.clone()]).is_ok());
+        let err = Blake2fCircuit::from_inputs(vec![call.clone(), call[1..].to_vec()])
+            .err()
+            .unwrap();
+        assert_eq!(
+            err.to_string(),
+            "the input is not that of a BLAKE2F call: it is 212 bytes long rather than 213, in \
+             input 1"
+        );
+    }
+
+    #[test]
+    fn test_capacity() {
+        let inputs = vec![vec![0; MAX_INPUT_BYTES / MAX_CALLS]; MAX_CALLS];
+        assert!(Sha2Circuit::from_inputs(inputs).is_ok());
+        for inputs in [
+            vec![vec![]; MAX_CALLS + 1],
+            vec![vec![0; MAX_INPUT_BYTES + 1]],
+        ] {
+            let err = Ripemd160Circuit::from_inputs(inputs).err().unwrap();
+            assert!(matches!(err, Error::Capacity { .. }));
+        }
+
+        // the instances are of the lengths of the capacity.
+        let lens = |circuit: Sha2Circuit| {
+            circuit
+                .public_inputs()
+                .iter()
+                .map(Vec::len)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            lens(Sha2Circuit::default()),
+            lens(Sha2Circuit::from_inputs(vec![b"abc".to_vec()]).unwrap())
+        );
+    }
+
+    #[test]
+    fn test_prove_and_verify() {
+        let dir = std::env::temp_dir().join(format!("precompile_prover_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let [params, keys, inputs, other_inputs, proof] =
+            ["params", "keys", "inputs.json", "other.json", "proof"].map(|name| dir.join(name));
+        // the smallest degree the layout of every circuit, that of its
+        // capacity, fits in.
+        let k = [
+            Sha2Circuit::default().min_k(),
+            Blake2fCircuit::default().min_k(),
+            Ripemd160Circuit::default().min_k(),
+        ]
+        .into_iter()
+        .max()
+        .unwrap();
+        setup(k, None, &params).unwrap();
+
+        // the calls of the fixtures of every circuit, last that of SHA2-256.
+        for circuit in test_utils::CIRCUITS.into_iter().rev() {
+            let fixtures = test_utils::fixtures(circuit);
+            let calls: Vec<_> = fixtures
+                .iter()
+                .map(|fixture| format!("\"{}\"", hex::encode(&fixture.input)))
+                .collect();
+            fs::write(&inputs, format!("[{}]", calls.join(", "))).unwrap();
+            let circuit: Precompile = circuit.parse().unwrap();
+
+            keygen(circuit, &params, &keys).unwrap();
+            let outputs = prove(circuit, &params, &keys, &inputs, &proof).unwrap();
+            for (output, fixture) in outputs.iter().zip(&fixtures) {
+                assert_eq!(output, &fixture.output, "{circuit}");
+            }
+            assert!(verify(circuit, &params, &keys, &inputs, &proof).unwrap());
+            // the proof is not that of the calls but the last.
+            fs::write(
+                &other_inputs,
+                format!("[{}]", calls[..calls.len() - 1].join(", ")),
+            )
+            .unwrap();
+            assert!(!verify(circuit, &params, &keys, &other_inputs, &proof).unwrap());
+            assert_eq!(
+                witness(circuit, k, &inputs).unwrap(),
+                witness(circuit, k + 2, &inputs).unwrap()
+            );
+        }
+
+        // the verifier contract of the SHA2-256 keys accepts their proof of
+        // the calls, and refuses it for other instances.
+        #[cfg(feature = "evm")]
+        {
+            let contract = dir.join("contract");
+            assert!(
+                verify_contract(Precompile::Sha2, &params, &keys, &inputs, &proof, &contract)
+                    .unwrap()
+                    .is_some()
+            );
+            assert!(verify_contract(
+                Precompile::Sha2,
+                &params,
+                &keys,
+                &other_inputs,
+                &proof,
+                &contract
+            )
+            .unwrap()
+            .is_none());
+        }
+
+        // the keys of another constraint system are refused.
+        let err = verify(Precompile::Ripemd160, &params, &keys, &inputs, &proof).unwrap_err();
+        assert!(matches!(err, Error::Io(err) if err.kind() == io::ErrorKind::InvalidData));
+
+        // a truncated proof does not verify.
+        let bytes = fs::read(&proof).unwrap();
+        fs::write(&proof, &bytes[..bytes.len() / 2]).unwrap();
+        assert!(!verify(Precompile::Sha2, &params, &keys, &inputs, &proof).unwrap());
+        #[cfg(feature = "evm")]
+        assert!(verify_contract(
+            Precompile::Sha2,
+            &params,
+            &keys,
+            &inputs,
+            &proof,
+            &dir.join("contract")
+        )
+        .unwrap()
+        .is_none());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
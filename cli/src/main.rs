@@ -0,0 +1,95 @@
+//! Proves the calls of a precompile end to end, see [`cli`]:
+//!
+//! ```text
+//! cargo run --release --bin precompile-prover -- prove --circuit sha2 --params params --keys keys --inputs calls.json --proof proof
+//! ```
+//!
+//! The `RUST_LOG` env var prints the spans of the phases to stderr, see
+//! [`prover::init_tracing`].
+
+use std::{env::args, process::exit};
+
+#[cfg(feature = "evm")]
+use cli::verify_contract;
+use cli::{keygen, prove, setup, verify, witness, Command};
+use ethers_core::utils::hex;
+
+const USAGE: &str = "usage: precompile-prover setup --k <k> --params <file> [--srs <file>]
+       precompile-prover keygen --circuit <circuit> --params <file> --keys <dir>
+       precompile-prover prove --circuit <circuit> --params <file> --keys <dir> --inputs <file> --proof <file>
+       precompile-prover verify --circuit <circuit> --params <file> --keys <dir> --inputs <file> --proof <file>
+       precompile-prover witness --circuit <circuit> --k <k> --inputs <file>
+       precompile-prover verify-contract --circuit <circuit> --params <file> --keys <dir> --inputs <file> --proof <file> --contract <dir>
+with <circuit> one of sha2, blake2f and ripemd160, of 4 calls of 256 input bytes in total or 12
+rounds each at most, and verify-contract of the evm feature";
+
+fn main() {
+    let Some(command) = Command::parse(&args().skip(1).collect::<Vec<_>>()) else {
+        eprintln!("{USAGE}");
+        exit(2);
+    };
+    prover::init_tracing();
+
+    let result = match &command {
+        Command::Setup { k, srs, params } => setup(*k, srs.as_deref(), params).map(|()| {
+            println!("Wrote the params of degree {k} to {}", params.display());
+        }),
+        Command::Keygen {
+            circuit,
+            params,
+            keys,
+        } => keygen(*circuit, params, keys).map(|()| {
+            println!("Wrote the keys of {circuit} to {}", keys.display());
+        }),
+        Command::Prove {
+            circuit,
+            params,
+            keys,
+            inputs,
+            proof,
+        } => prove(*circuit, params, keys, inputs, proof).map(|outputs| {
+            for output in outputs {
+                println!("{}", hex::encode(output));
+            }
+            println!("Wrote the proof of {circuit} to {}", proof.display());
+        }),
+        Command::Verify {
+            circuit,
+            params,
+            keys,
+            inputs,
+            proof,
+        } => verify(*circuit, params, keys, inputs, proof).map(|valid| {
+            if !valid {
+                println!("The proof of {circuit} is not valid");
+                exit(1);
+            }
+            println!("The proof of {circuit} is valid");
+        }),
+        Command::Witness { circuit, k, inputs } => witness(*circuit, *k, inputs).map(|hash| {
+            println!("The witness hash of {circuit} is {hash:016x}");
+        }),
+        #[cfg(feature = "evm")]
+        Command::VerifyContract {
+            circuit,
+            params,
+            keys,
+            inputs,
+            proof,
+            contract,
+        } => verify_contract(*circuit, params, keys, inputs, proof, contract).map(|gas| {
+            let Some(gas) = gas else {
+                println!("The verifier contract of {circuit} rejects the proof");
+                exit(1);
+            };
+            println!(
+                "The verifier contract of {circuit}, written to {}, accepts the proof for {gas} gas",
+                contract.display()
+            );
+        }),
+    };
+    if let Err(err) = result {
+        eprintln!("Cannot run the command: {err}");
+        exit(2);
+    }
+}
@@ -0,0 +1,319 @@
+//! A constraint builder in the style of [`zkevm-circuits`], to write the
+//! constraints of a gate as a list of named, optionally conditional,
+//! requirements instead of hand-rolled polynomials.
+//!
+//! ```
+//! # use constraint_builder::{BaseConstraintBuilder, Expr};
+//! # use halo2_proofs::{halo2curves::bn256::Fr, plonk::ConstraintSystem, poly::Rotation};
+//! # let mut meta = ConstraintSystem::<Fr>::default();
+//! # let id = meta.advice_column();
+//! meta.create_gate("validity check over all rows", |meta| {
+//!     let mut cb = BaseConstraintBuilder::default();
+//!     cb.require_equal(
+//!         "id field is incremental, i.e. id::cur + 1 == id::next",
+//!         meta.query_advice(id, Rotation::cur()) + 1.expr(),
+//!         meta.query_advice(id, Rotation::next()),
+//!     );
+//!     cb.gate(1.expr()) // enable this gate over all rows.
+//! });
+//! ```
+//!
+//! [`zkevm-circuits`]: https://github.com/scroll-tech/zkevm-circuits
+
+use halo2_proofs::{arithmetic::FieldExt, plonk::Expression};
+
+/// Conversion of a value into a constant [`Expression`].
+pub trait Expr<F: FieldExt> {
+    fn expr(&self) -> Expression<F>;
+}
+
+macro_rules! impl_expr {
+    ($type:ty) => {
+        impl<F: FieldExt> Expr<F> for $type {
+            #[inline]
+            fn expr(&self) -> Expression<F> {
+                Expression::Constant(F::from(*self as u64))
+            }
+        }
+    };
+}
+
+impl_expr!(bool);
+impl_expr!(u8);
+impl_expr!(u16);
+impl_expr!(u32);
+impl_expr!(u64);
+impl_expr!(usize);
+
+impl<F: FieldExt> Expr<F> for i32 {
+    #[inline]
+    fn expr(&self) -> Expression<F> {
+        let value = F::from(self.unsigned_abs() as u64);
+        Expression::Constant(if *self < 0 { -value } else { value })
+    }
+}
+
+impl<F: FieldExt> Expr<F> for Expression<F> {
+    #[inline]
+    fn expr(&self) -> Expression<F> {
+        self.clone()
+    }
+}
+
+impl<F: FieldExt> Expr<F> for &Expression<F> {
+    #[inline]
+    fn expr(&self) -> Expression<F> {
+        (*self).clone()
+    }
+}
+
+/// Collects the named constraints of a gate.
+///
+/// Every constraint added within [`BaseConstraintBuilder::condition`] is
+/// multiplied by the condition, and by those of the enclosing conditions when
+/// they are nested, and [`BaseConstraintBuilder::gate`] multiplies
+/// all of them by the selector of the gate. When a `max_degree` is set, the
+/// degree of each constraint is checked against it as it is added, and again
+/// once multiplied by the selector.
+#[derive(Clone, Debug, Default)]
+pub struct BaseConstraintBuilder<F> {
+    pub constraints: Vec<(&'static str, Expression<F>)>,
+    /// Upper bound on the degree of the constraints, or `0` for no bound.
+    pub max_degree: usize,
+    /// Conditions of the enclosing [`BaseConstraintBuilder::condition`] calls,
+    /// innermost last.
+    pub conditions: Vec<Expression<F>>,
+}
+
+impl<F: FieldExt> BaseConstraintBuilder<F> {
+    pub fn new(max_degree: usize) -> Self {
+        Self {
+            constraints: vec![],
+            max_degree,
+            conditions: vec![],
+        }
+    }
+
+    pub fn require_zero(&mut self, name: &'static str, constraint: Expression<F>) {
+        self.add_constraint(name, constraint);
+    }
+
+    pub fn require_equal(&mut self, name: &'static str, lhs: Expression<F>, rhs: Expression<F>) {
+        self.add_constraint(name, lhs - rhs);
+    }
+
+    pub fn require_boolean(&mut self, name: &'static str, value: Expression<F>) {
+        self.add_constraint(name, value.clone() * (1.expr() - value));
+    }
+
+    /// Requires `value` to equal one of the expressions in `set`.
+    pub fn require_in_set(
+        &mut self,
+        name: &'static str,
+        value: Expression<F>,
+        set: Vec<Expression<F>>,
+    ) {
+        self.add_constraint(
+            name,
+            set.iter()
+                .fold(1.expr(), |acc, item| acc * (value.clone() - item.clone())),
+        );
+    }
+
+    /// Applies `condition` to every constraint added within `constraint`.
+    /// Conditions nest: a constraint added within several of them is
+    /// multiplied by all of them.
+    pub fn condition<R>(
+        &mut self,
+        condition: Expression<F>,
+        constraint: impl FnOnce(&mut Self) -> R,
+    ) -> R {
+        self.conditions.push(condition);
+        let ret = constraint(self);
+        self.conditions.pop();
+        ret
+    }
+
+    pub fn add_constraints(&mut self, constraints: Vec<(&'static str, Expression<F>)>) {
+        for (name, constraint) in constraints {
+            self.add_constraint(name, constraint);
+        }
+    }
+
+    pub fn add_constraint(&mut self, name: &'static str, constraint: Expression<F>) {
+        let constraint = match self.conditions.iter().cloned().reduce(|acc, c| acc * c) {
+            Some(condition) => condition * constraint,
+            None => constraint,
+        };
+        self.validate_degree(constraint.degree(), name);
+        self.constraints.push((name, constraint));
+    }
+
+    pub fn validate_degree(&self, degree: usize, name: &'static str) {
+        if self.max_degree > 0 {
+            assert!(
+                degree <= self.max_degree,
+                "Expression {} degree too high: {} > {}",
+                name,
+                degree,
+                self.max_degree,
+            );
+        }
+    }
+
+    /// Highest degree among the constraints added so far.
+    pub fn degree(&self) -> usize {
+        self.constraints
+            .iter()
+            .map(|(_, constraint)| constraint.degree())
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Returns the constraints multiplied by `selector`, to be returned from
+    /// `create_gate`.
+    pub fn gate(&self, selector: Expression<F>) -> Vec<(&'static str, Expression<F>)> {
+        self.constraints
+            .iter()
+            .map(|(name, constraint)| {
+                let constraint = selector.clone() * constraint.clone();
+                self.validate_degree(constraint.degree(), name);
+                (*name, constraint)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use halo2_proofs::{
+        circuit::{Layouter, SimpleFloorPlanner, Value},
+        dev::MockProver,
+        halo2curves::bn256::Fr,
+        plonk::{Advice, Circuit, Column, ConstraintSystem, Error, Selector},
+        poly::Rotation,
+    };
+    use std::marker::PhantomData;
+
+    #[derive(Clone, Debug)]
+    struct TestConfig {
+        q_enable: Selector,
+        flag: Column<Advice>,
+        value: Column<Advice>,
+    }
+
+    #[derive(Default)]
+    struct TestCircuit<F> {
+        // (flag, value): value must be in {1, 2, 3}, equal 3 if flag is set,
+        // and equal 1 or 3 otherwise.
+        rows: Vec<(bool, u64)>,
+        _marker: PhantomData<F>,
+    }
+
+    impl<F: FieldExt> Circuit<F> for TestCircuit<F> {
+        type Config = TestConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            let q_enable = meta.selector();
+            let flag = meta.advice_column();
+            let value = meta.advice_column();
+
+            meta.create_gate("flag and value are well-formed", |meta| {
+                let mut cb = BaseConstraintBuilder::new(4);
+                let flag = meta.query_advice(flag, Rotation::cur());
+                let value = meta.query_advice(value, Rotation::cur());
+
+                cb.require_boolean("flag is boolean", flag.clone());
+                cb.require_in_set(
+                    "value is 1, 2 or 3",
+                    value.clone(),
+                    vec![1.expr(), 2.expr(), 3.expr()],
+                );
+                cb.condition(flag.clone(), |cb| {
+                    cb.require_equal("value is 3 if flag is set", value.clone(), 3.expr());
+                });
+                cb.condition(1.expr() - flag, |cb| {
+                    cb.condition(value.clone() - 1.expr(), |cb| {
+                        cb.require_equal("value is 1 or 3 if flag is unset", value, 3.expr());
+                    });
+                });
+                assert_eq!(cb.degree(), 3);
+
+                cb.gate(meta.query_selector(q_enable))
+            });
+
+            TestConfig {
+                q_enable,
+                flag,
+                value,
+            }
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            layouter.assign_region(
+                || "rows",
+                |mut region| {
+                    for (offset, (flag, value)) in self.rows.iter().enumerate() {
+                        config.q_enable.enable(&mut region, offset)?;
+                        region.assign_advice(
+                            || "flag",
+                            config.flag,
+                            offset,
+                            || Value::known(F::from(*flag as u64)),
+                        )?;
+                        region.assign_advice(
+                            || "value",
+                            config.value,
+                            offset,
+                            || Value::known(F::from(*value)),
+                        )?;
+                    }
+                    Ok(())
+                },
+            )
+        }
+    }
+
+    #[test]
+    fn test_base_constraint_builder() {
+        let run = |rows: Vec<(bool, u64)>| {
+            let circuit = TestCircuit::<Fr> {
+                rows,
+                _marker: PhantomData,
+            };
+            MockProver::run(8, &circuit, vec![]).unwrap().verify()
+        };
+
+        assert_eq!(run(vec![(false, 1), (false, 3), (true, 3)]), Ok(()));
+        assert!(run(vec![(false, 4)]).is_err());
+        assert!(run(vec![(true, 2)]).is_err());
+        assert!(run(vec![(false, 2)]).is_err());
+    }
+
+    #[test]
+    #[should_panic(expected = "degree too high")]
+    fn test_max_degree() {
+        let mut meta = ConstraintSystem::<Fr>::default();
+        let value = meta.advice_column();
+        meta.create_gate("degree too high", |meta| {
+            let mut cb = BaseConstraintBuilder::new(2);
+            let value = meta.query_advice(value, Rotation::cur());
+            cb.require_in_set(
+                "value is 1, 2 or 3",
+                value,
+                vec![1.expr(), 2.expr(), 3.expr()],
+            );
+            cb.gate(1.expr())
+        });
+    }
+}
@@ -0,0 +1,1170 @@
+//! The ecrecover circuit verifies calls to the [`ECRECOVER`] precompiled
+//! contract at address `0x01`, which recovers the address of the signer of
+//! the hash of a message from its secp256k1 signature `(v, r, s)`.
+//!
+//! The public key of the signer is `Q = u1 * G + u2 * R`, where `R` is the
+//! point of x-coordinate `r` whose y-coordinate has the parity `v - 27`,
+//! `u1 = -h / r` and `u2 = s / r` modulo the order `n` of the group, and `h`
+//! is the message hash modulo `n`. The arithmetic modulo `n` and modulo the
+//! base field `p` is that of two [`NonNativeChip`]s, and a call is laid out
+//! in four regions:
+//! - its scalars, modulo `n`: `r` and `s` are assigned reduced, and are not
+//!   zero as they are inverted, and the bits of `u1` and `u2`, most
+//!   significant first, are accumulated into their limbs, a row per bit;
+//! - its points, modulo `p`: `R` is constrained to be on the curve, and `Q`
+//!   is computed by a joint double-and-add over the bits of `u1` and `u2`.
+//!   For every bit, the accumulator is doubled, then `G` and `R` are added to
+//!   it, each sum selected limb by limb by its bit. The accumulator starts at
+//!   a point whose discrete logarithm nobody knows, which is taken back out
+//!   at the end, so that the incomplete addition, whose operands must have
+//!   different x-coordinates, holds for all but a negligible fraction of the
+//!   signatures;
+//! - its words: the message hash, `r`, `s` and the coordinates of `Q` are
+//!   packed from their limbs into `WordLoHi`s, the coordinates of `Q` into
+//!   their 64 big-endian bytes, and the parity of the y-coordinate of `R` is
+//!   checked against `v`;
+//! - its digest: the RLC of the bytes of `Q`, accumulated by an [`RlcChip`]
+//!   with the challenge of the Keccak-256 circuit, is looked up in its
+//!   `KeccakTable` along with the digest, whose last 20 bytes are the
+//!   address.
+//!
+//! The circuit exposes the calls through `EcrecoverTable`:
+//! - `is_enabled`: whether the row holds a call;
+//! - `msg_hash`: the message hash, as a `WordLoHi`;
+//! - `v`: `27` or `28`;
+//! - `r` and `s`: the signature, as `WordLoHi`s;
+//! - `address`: the address of the signer.
+//!
+//! The following tasks are expected to be done:
+//! - Support the calls whose signature is invalid, for which the precompile
+//!   returns no output: the chip refuses to load them.
+//! - Share the multiples of `G` across the calls rather than adding `G` bit
+//!   by bit.
+//!
+//! [`ECRECOVER`]: https://www.evm.codes/precompiled#0x01
+
+pub mod native;
+
+use std::marker::PhantomData;
+
+use constraint_builder::{BaseConstraintBuilder, Expr};
+use gadgets::{
+    tables::{FixedTables, RangeTable},
+    util::{pow_of_two, AssignedValue},
+    AssignedBytes, AssignedWord, DecomposeChip, DecomposeConfig, WordChip, WordConfig, WordLoHi,
+};
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{Layouter, Region, Value},
+    plonk::{
+        Advice, Any, Challenge, Column, ConstraintSystem, Error, Expression, SecondPhase, Selector,
+        VirtualCells,
+    },
+    poly::Rotation,
+};
+use keccak256_circuit::KeccakTable;
+use non_native_field::{
+    native::{Limbs, LIMB_BITS, NUM_LIMBS, SECP256K1_N, SECP256K1_P, ZERO},
+    AssignedInteger, NonNativeChip, NonNativeConfig,
+};
+use rlc_chip::{RlcChip, RlcConfig};
+
+use native::{EcrecoverCall, Point, SCALAR_BITS};
+
+/// Bytes of a public key, the input of the Keccak-256 call of its address.
+const PUBKEY_BYTES: usize = 2 * native::WORD_BYTES;
+
+#[derive(Clone, Debug)]
+pub struct EcrecoverTable {
+    is_enabled: Column<Advice>,
+    msg_hash: WordLoHi<Column<Advice>>,
+    v: Column<Advice>,
+    r: WordLoHi<Column<Advice>>,
+    s: WordLoHi<Column<Advice>>,
+    address: Column<Advice>,
+}
+
+impl EcrecoverTable {
+    pub fn construct<F: FieldExt>(meta: &mut ConstraintSystem<F>) -> Self {
+        let table = Self {
+            is_enabled: meta.advice_column(),
+            msg_hash: WordLoHi::construct(meta),
+            v: meta.advice_column(),
+            r: WordLoHi::construct(meta),
+            s: WordLoHi::construct(meta),
+            address: meta.advice_column(),
+        };
+        for column in &table.advice_columns()[1..] {
+            meta.enable_equality(*column);
+        }
+        table
+    }
+
+    fn advice_columns(&self) -> [Column<Advice>; 9] {
+        [
+            self.is_enabled,
+            *self.msg_hash.lo(),
+            *self.msg_hash.hi(),
+            self.v,
+            *self.r.lo(),
+            *self.r.hi(),
+            *self.s.lo(),
+            *self.s.hi(),
+            self.address,
+        ]
+    }
+
+    pub fn columns(&self) -> Vec<Column<Any>> {
+        self.advice_columns()
+            .into_iter()
+            .map(|column| column.into())
+            .collect()
+    }
+
+    pub fn annotations(&self) -> Vec<String> {
+        vec![
+            String::from("is_enabled"),
+            String::from("msg_hash_lo"),
+            String::from("msg_hash_hi"),
+            String::from("v"),
+            String::from("r_lo"),
+            String::from("r_hi"),
+            String::from("s_lo"),
+            String::from("s_hi"),
+            String::from("address"),
+        ]
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct EcrecoverConfig<F> {
+    table: EcrecoverTable,
+    keccak_table: KeccakTable,
+    /// The fixed tables the chip loads itself, i.e. none if they are shared
+    /// with other circuits.
+    tables: FixedTables,
+    base: NonNativeConfig,   // modulo the base field p.
+    scalar: NonNativeConfig, // modulo the order n.
+    limb_range: DecomposeConfig<8, 4>,
+    word: WordConfig,
+    rlc: RlcConfig,
+    q_table: Selector,
+    q_bit_first: Selector, // enabled on the first bit of a limb of a scalar.
+    q_bit: Selector,       // enabled on the other bits.
+    q_select: Selector,    // enabled on every selected coordinate.
+    q_word: Selector,      // enabled on every word packed from its limbs.
+    q_keccak: Selector,    // enabled on the digest of every call.
+    q_parity: Selector,    // enabled on the parity check of every call.
+    bit: Column<Advice>,
+    bits: Column<Advice>,
+    lhs: [Column<Advice>; NUM_LIMBS],
+    rhs: [Column<Advice>; NUM_LIMBS],
+    out: [Column<Advice>; NUM_LIMBS],
+    limbs: [Column<Advice>; NUM_LIMBS],
+    packed: WordLoHi<Column<Advice>>,
+    pubkey_rlc: Column<Advice>,
+    address: Column<Advice>,
+    v: Column<Advice>,
+    half: Column<Advice>,
+    _marker: PhantomData<F>,
+}
+
+fn query_limbs<F: FieldExt>(
+    meta: &mut VirtualCells<'_, F>,
+    columns: &[Column<Advice>; NUM_LIMBS],
+) -> [Expression<F>; NUM_LIMBS] {
+    columns.map(|column| meta.query_advice(column, Rotation::cur()))
+}
+
+/// Native value of the little-endian `limbs`.
+fn pack<F: FieldExt>(limbs: &[Expression<F>]) -> Expression<F> {
+    limbs.iter().enumerate().fold(0.expr(), |acc, (i, limb)| {
+        acc + limb.clone() * Expression::Constant(pow_of_two::<F>(i * LIMB_BITS))
+    })
+}
+
+impl<F: FieldExt> EcrecoverConfig<F> {
+    /// Configures the circuit over the `keccak_table` of the Keccak-256
+    /// circuit, whose RLCs are over `challenge`, see
+    /// `KeccakConfig::challenge`.
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        table: EcrecoverTable,
+        keccak_table: KeccakTable,
+        challenge: Challenge,
+    ) -> Self {
+        let mut tables = FixedTables::default();
+        let config = Self::configure_with_tables(meta, table, keccak_table, challenge, &mut tables);
+        Self { tables, ..config }
+    }
+
+    /// Configures the circuit over the fixed tables requested from `tables`,
+    /// which the caller loads once for all the circuits sharing them.
+    pub fn configure_with_tables(
+        meta: &mut ConstraintSystem<F>,
+        table: EcrecoverTable,
+        keccak_table: KeccakTable,
+        challenge: Challenge,
+        tables: &mut FixedTables,
+    ) -> Self {
+        let byte_table: RangeTable<8> = tables.range(meta);
+        let base = NonNativeConfig::configure(meta, byte_table, SECP256K1_P);
+        let scalar = NonNativeConfig::configure(meta, byte_table, SECP256K1_N);
+        let limb_range = DecomposeConfig::configure(meta, byte_table);
+        let word = WordConfig::configure(meta, byte_table);
+        let rlc = RlcConfig::configure_with_challenge(meta, byte_table, challenge);
+
+        let [q_table, q_bit_first, q_bit, q_select, q_word, q_parity] =
+            [0; 6].map(|_| meta.selector());
+        let q_keccak = meta.complex_selector();
+        let [bit, bits, address, v, half] = [0; 5].map(|_| meta.advice_column());
+        let [lhs, rhs, out, limbs] = [0; 4].map(|_| [0; NUM_LIMBS].map(|_| meta.advice_column()));
+        let packed = WordLoHi::construct(meta);
+        let pubkey_rlc = meta.advice_column_in(SecondPhase);
+        for column in [lhs, rhs, out, limbs]
+            .iter()
+            .flatten()
+            .chain([bit, bits, address, v, half, pubkey_rlc].iter())
+            .chain(packed.to_lo_hi().iter())
+        {
+            meta.enable_equality(*column);
+        }
+        // The chips assign their constants, and the RLC chip its flags, from
+        // constants.
+        let constants = meta.fixed_column();
+        meta.enable_constant(constants);
+
+        meta.create_gate("first bit of a limb", |meta| {
+            let mut cb = BaseConstraintBuilder::default();
+            let bit = meta.query_advice(bit, Rotation::cur());
+            cb.require_boolean("bit is boolean", bit.clone());
+            cb.require_equal("bits == bit", meta.query_advice(bits, Rotation::cur()), bit);
+            cb.gate(meta.query_selector(q_bit_first))
+        });
+
+        meta.create_gate("bits accumulate the bits of a limb", |meta| {
+            let mut cb = BaseConstraintBuilder::default();
+            let bit = meta.query_advice(bit, Rotation::cur());
+            cb.require_boolean("bit is boolean", bit.clone());
+            cb.require_equal(
+                "bits == 2 * bits::prev + bit",
+                meta.query_advice(bits, Rotation::cur()),
+                meta.query_advice(bits, Rotation::prev()) * 2.expr() + bit,
+            );
+            cb.gate(meta.query_selector(q_bit))
+        });
+
+        meta.create_gate("out == bit ? lhs : rhs", |meta| {
+            let mut cb = BaseConstraintBuilder::default();
+            let bit = meta.query_advice(bit, Rotation::cur());
+            let [lhs, rhs, out] = [lhs, rhs, out].map(|limbs| query_limbs(meta, &limbs));
+
+            cb.require_boolean("bit is boolean", bit.clone());
+            for i in 0..NUM_LIMBS {
+                cb.require_equal(
+                    "out == bit * lhs + (1 - bit) * rhs",
+                    out[i].clone(),
+                    bit.clone() * lhs[i].clone() + (1.expr() - bit.clone()) * rhs[i].clone(),
+                );
+            }
+            cb.gate(meta.query_selector(q_select))
+        });
+
+        meta.create_gate("word is its limbs", |meta| {
+            let mut cb = BaseConstraintBuilder::default();
+            let limbs = query_limbs(meta, &limbs);
+            let packed = packed.query(meta, Rotation::cur());
+            cb.require_equal("lo == limbs[0..4]", packed.lo().clone(), pack(&limbs[..4]));
+            cb.require_equal("hi == limbs[4..8]", packed.hi().clone(), pack(&limbs[4..]));
+            cb.gate(meta.query_selector(q_word))
+        });
+
+        meta.create_gate("address is the last 20 bytes of the digest", |meta| {
+            let mut cb = BaseConstraintBuilder::default();
+            // The lowest 32 bits of hi are bytes 12 to 16 of the digest.
+            cb.require_equal(
+                "address == lo + limbs[4] * 2^128",
+                meta.query_advice(address, Rotation::cur()),
+                meta.query_advice(*packed.lo(), Rotation::cur())
+                    + meta.query_advice(limbs[4], Rotation::cur())
+                        * Expression::Constant(pow_of_two::<F>(128)),
+            );
+            cb.gate(meta.query_selector(q_keccak))
+        });
+
+        meta.lookup_any("keccak256(pubkey) == digest", |meta| {
+            let q_keccak = meta.query_selector(q_keccak);
+            let pubkey_rlc = meta.query_advice(pubkey_rlc, Rotation::cur());
+            let digest = packed.query(meta, Rotation::cur());
+            let output = keccak_table.output.query(meta, Rotation::cur());
+
+            vec![
+                (
+                    q_keccak.clone(),
+                    meta.query_advice(keccak_table.is_enabled, Rotation::cur()),
+                ),
+                (
+                    q_keccak.clone() * pubkey_rlc,
+                    meta.query_advice(keccak_table.input_rlc, Rotation::cur()),
+                ),
+                (
+                    q_keccak.clone() * PUBKEY_BYTES.expr(),
+                    meta.query_advice(keccak_table.input_len, Rotation::cur()),
+                ),
+                (q_keccak.clone() * digest.lo().clone(), output.lo().clone()),
+                (q_keccak * digest.hi().clone(), output.hi().clone()),
+            ]
+        });
+
+        meta.create_gate("y of R has the parity v - 27", |meta| {
+            let mut cb = BaseConstraintBuilder::default();
+            let parity = meta.query_advice(v, Rotation::cur()) - 27.expr();
+            cb.require_boolean("v is 27 or 28", parity.clone());
+            cb.require_equal(
+                "y_0 == 2 * half + v - 27",
+                meta.query_advice(limbs[0], Rotation::cur()),
+                meta.query_advice(half, Rotation::cur()) * 2.expr() + parity,
+            );
+            cb.gate(meta.query_selector(q_parity))
+        });
+
+        meta.create_gate("validity check over the table rows", |meta| {
+            let mut cb = BaseConstraintBuilder::default();
+            let [is_enabled, columns @ ..] = table.advice_columns();
+            let is_enabled = meta.query_advice(is_enabled, Rotation::cur());
+
+            cb.require_boolean("is_enabled is boolean", is_enabled.clone());
+            cb.condition(1.expr() - is_enabled, |cb| {
+                for column in columns {
+                    cb.require_zero(
+                        "column == 0 if !is_enabled",
+                        meta.query_advice(column, Rotation::cur()),
+                    );
+                }
+            });
+
+            cb.gate(meta.query_selector(q_table))
+        });
+
+        Self {
+            table,
+            keccak_table,
+            tables: FixedTables::default(),
+            base,
+            scalar,
+            limb_range,
+            word,
+            rlc,
+            q_table,
+            q_bit_first,
+            q_bit,
+            q_select,
+            q_word,
+            q_keccak,
+            q_parity,
+            bit,
+            bits,
+            lhs,
+            rhs,
+            out,
+            limbs,
+            packed,
+            pubkey_rlc,
+            address,
+            v,
+            half,
+            _marker: PhantomData,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct EcrecoverWitness<F> {
+    pub calls: Vec<EcrecoverCall>,
+    pub _marker: PhantomData<F>,
+}
+
+impl<F> EcrecoverWitness<F> {
+    /// The inputs of the Keccak-256 calls the circuit looks up, i.e. the
+    /// public keys of the signers, or `None` if a signature is invalid.
+    pub fn keccak_inputs(&self) -> Option<Vec<Vec<u8>>> {
+        self.calls
+            .iter()
+            .map(|call| {
+                call.recover_pubkey()
+                    .map(|pubkey| native::pubkey_bytes(&pubkey))
+            })
+            .collect()
+    }
+}
+
+/// Cell of a bit of a scalar, along with its native value.
+#[derive(Clone, Debug)]
+struct AssignedBit<F: FieldExt> {
+    cell: AssignedValue<F>,
+    value: bool,
+}
+
+/// Cells of the coordinates of an affine point.
+#[derive(Clone, Debug)]
+struct AssignedPoint<F: FieldExt> {
+    x: AssignedInteger<F>,
+    y: AssignedInteger<F>,
+}
+
+impl<F: FieldExt> AssignedPoint<F> {
+    fn value(&self) -> Point {
+        Point {
+            x: self.x.value,
+            y: self.y.value,
+        }
+    }
+}
+
+/// Cells of the scalars of a call.
+struct ScalarCells<F: FieldExt> {
+    /// The range-checked limbs of the message hash.
+    msg_hash: Vec<AssignedValue<F>>,
+    r: AssignedInteger<F>,
+    s: AssignedInteger<F>,
+    u1: Vec<AssignedBit<F>>,
+    u2: Vec<AssignedBit<F>>,
+}
+
+#[derive(Clone, Debug)]
+pub struct EcrecoverChip<F> {
+    config: EcrecoverConfig<F>,
+    data: EcrecoverWitness<F>,
+}
+
+impl<F: FieldExt> EcrecoverChip<F> {
+    pub fn construct(config: EcrecoverConfig<F>, data: EcrecoverWitness<F>) -> Self {
+        Self { config, data }
+    }
+
+    /// Loads the calls of the witness, or fails with [`Error::Synthesis`] if
+    /// the signature of a call is invalid.
+    pub fn load(&self, layouter: &mut impl Layouter<F>) -> Result<(), Error> {
+        let config = &self.config;
+        config.tables.load(layouter)?;
+        let calls = self
+            .data
+            .calls
+            .iter()
+            .enumerate()
+            .map(|(id, call)| self.assign_call(layouter, id, call))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let table = &config.table;
+        let columns = table.advice_columns();
+        layouter.assign_region(
+            || "ecrecover table",
+            |mut region| {
+                config.q_table.enable(&mut region, 0)?;
+                for (annotation, column) in table.annotations().iter().zip(columns) {
+                    region.assign_advice(|| annotation, column, 0, || Value::known(F::zero()))?;
+                }
+
+                for (i, cells) in calls.iter().enumerate() {
+                    let offset = i + 1;
+                    config.q_table.enable(&mut region, offset)?;
+                    region.assign_advice(
+                        || "is_enabled",
+                        table.is_enabled,
+                        offset,
+                        || Value::known(F::one()),
+                    )?;
+                    for ((annotation, column), cell) in
+                        table.annotations().iter().zip(columns).skip(1).zip(cells)
+                    {
+                        cell.copy_advice(|| annotation, &mut region, column, offset)?;
+                    }
+                }
+                Ok(())
+            },
+        )
+    }
+
+    /// Assigns the `id`-th call, returning the cells of its row of the table
+    /// but `is_enabled`.
+    fn assign_call(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        id: usize,
+        call: &EcrecoverCall,
+    ) -> Result<[AssignedValue<F>; 8], Error> {
+        let pubkey = call.recover_pubkey().ok_or(Error::Synthesis)?;
+        let point = call.signature_point().ok_or(Error::Synthesis)?;
+
+        let scalars = self.assign_scalars(layouter, id, call)?;
+        let (y, q) = self.assign_points(layouter, id, &scalars, &point)?;
+        debug_assert_eq!(q.value(), pubkey);
+        let ([msg_hash, r, s], v, bytes) =
+            self.assign_words(layouter, id, call, &scalars, &y, &q)?;
+        let pubkey_rlc = RlcChip::construct(self.config.rlc).assign_cells(layouter, &[bytes])?;
+        let address = self.assign_digest(layouter, id, &pubkey, &pubkey_rlc[0])?;
+
+        let [msg_hash_lo, msg_hash_hi] = msg_hash.to_lo_hi();
+        let [r_lo, r_hi] = r.to_lo_hi();
+        let [s_lo, s_hi] = s.to_lo_hi();
+        Ok([msg_hash_lo, msg_hash_hi, v, r_lo, r_hi, s_lo, s_hi, address])
+    }
+
+    /// The offset of the next operation of `ops` times
+    /// [`NonNativeChip::ROWS`] rows from `offset`, which is moved past them.
+    fn next(offset: &mut usize, ops: usize) -> usize {
+        let next = *offset;
+        *offset += ops * NonNativeChip::<F>::ROWS;
+        next
+    }
+
+    /// Assigns `r` and `s` reduced modulo `n`, and the bits of `u1` and
+    /// `u2` from the first row of the region.
+    fn assign_scalars(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        id: usize,
+        call: &EcrecoverCall,
+    ) -> Result<ScalarCells<F>, Error> {
+        let n = NonNativeChip::construct(self.config.scalar.clone());
+        let limb_range = DecomposeChip::construct(self.config.limb_range);
+        let [h, r, s] = [call.msg_hash, call.r, call.s].map(|word| native::from_be_bytes(&word));
+
+        layouter.assign_region(
+            || format!("ecrecover call {} scalars", id),
+            |mut region| {
+                let msg_hash = h
+                    .iter()
+                    .enumerate()
+                    .map(|(i, limb)| {
+                        limb_range
+                            .assign(&mut region, i, *limb)
+                            .map(|(cell, _)| cell)
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+
+                let mut offset = 0;
+                let r = n.assign(&mut region, Self::next(&mut offset, 1), &r)?;
+                let s = n.assign(&mut region, Self::next(&mut offset, 1), &s)?;
+                let h = n.reduce(&mut region, Self::next(&mut offset, 1), &msg_hash, &h)?;
+                let r_inv = n.inv(&mut region, Self::next(&mut offset, 2), &r)?;
+                // s is not zero.
+                n.inv(&mut region, Self::next(&mut offset, 2), &s)?;
+                let zero = n.assign_constant(&mut region, Self::next(&mut offset, 1), &ZERO)?;
+                let h_over_r = n.mul(&mut region, Self::next(&mut offset, 1), &h, &r_inv)?;
+                let u1 = n.sub(&mut region, Self::next(&mut offset, 2), &zero, &h_over_r)?;
+                let u2 = n.mul(&mut region, Self::next(&mut offset, 1), &s, &r_inv)?;
+
+                let u1_bits = self.assign_bits(&mut region, 0, &u1)?;
+                let u2_bits = self.assign_bits(&mut region, SCALAR_BITS, &u2)?;
+                Ok(ScalarCells {
+                    msg_hash,
+                    r,
+                    s,
+                    u1: u1_bits,
+                    u2: u2_bits,
+                })
+            },
+        )
+    }
+
+    /// Assigns the bits of `scalar` from `offset`, the most significant
+    /// first, and accumulates them into its limbs.
+    fn assign_bits(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        scalar: &AssignedInteger<F>,
+    ) -> Result<Vec<AssignedBit<F>>, Error> {
+        let config = &self.config;
+        let values = native::bits(&scalar.value);
+        let mut bits = Vec::with_capacity(SCALAR_BITS);
+        for limb in scalar.limbs.iter().rev() {
+            let mut acc = 0;
+            for j in 0..LIMB_BITS {
+                let row = offset + bits.len();
+                let value = values[bits.len()];
+                if j == 0 {
+                    config.q_bit_first.enable(region, row)?;
+                } else {
+                    config.q_bit.enable(region, row)?;
+                }
+                acc = 2 * acc + value as u64;
+
+                let cell = region.assign_advice(
+                    || "bit",
+                    config.bit,
+                    row,
+                    || Value::known(F::from(value as u64)),
+                )?;
+                let bits_cell = region.assign_advice(
+                    || "bits",
+                    config.bits,
+                    row,
+                    || Value::known(F::from(acc)),
+                )?;
+                if j == LIMB_BITS - 1 {
+                    region.constrain_equal(bits_cell.cell(), limb.cell())?;
+                }
+                bits.push(AssignedBit { cell, value });
+            }
+        }
+        Ok(bits)
+    }
+
+    /// Assigns `R` of x-coordinate `r` on the curve, and computes
+    /// `Q = u1 * G + u2 * R`, returning the y-coordinate of `R` and `Q`. The
+    /// selections are laid out from the first row of the region.
+    fn assign_points(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        id: usize,
+        scalars: &ScalarCells<F>,
+        point: &Point,
+    ) -> Result<(AssignedInteger<F>, AssignedPoint<F>), Error> {
+        let p = NonNativeChip::construct(self.config.base.clone());
+
+        layouter.assign_region(
+            || format!("ecrecover call {} points", id),
+            |mut region| {
+                let region = &mut region;
+                let mut offset = 0;
+                let mut select_offset = 0;
+
+                let x = p.assign(region, Self::next(&mut offset, 1), &scalars.r.value)?;
+                p.constrain_equal(region, &x, &scalars.r)?;
+                let y = p.assign(region, Self::next(&mut offset, 1), &point.y)?;
+                let yy = p.mul(region, Self::next(&mut offset, 1), &y, &y)?;
+                let xx = p.mul(region, Self::next(&mut offset, 1), &x, &x)?;
+                let xxx = p.mul(region, Self::next(&mut offset, 1), &xx, &x)?;
+                let b = p.assign_constant(region, Self::next(&mut offset, 1), &native::B)?;
+                let x3_b = p.add(region, Self::next(&mut offset, 1), &xxx, &b)?;
+                p.constrain_equal(region, &yy, &x3_b)?;
+                let r = AssignedPoint { x, y };
+
+                let g = Self::assign_constant_point(&p, region, &mut offset, &native::GENERATOR)?;
+                let mut acc =
+                    Self::assign_constant_point(&p, region, &mut offset, &native::offset_point())?;
+                for (u1, u2) in scalars.u1.iter().zip(&scalars.u2) {
+                    acc = Self::double(&p, region, &mut offset, &acc)?;
+                    let sum = Self::add(&p, region, &mut offset, &acc, &g)?;
+                    acc = self.select(region, &mut select_offset, u1, &sum, &acc)?;
+                    let sum = Self::add(&p, region, &mut offset, &acc, &r)?;
+                    acc = self.select(region, &mut select_offset, u2, &sum, &acc)?;
+                }
+                let correction = Self::assign_constant_point(
+                    &p,
+                    region,
+                    &mut offset,
+                    &native::offset_correction(),
+                )?;
+                let q = Self::add(&p, region, &mut offset, &acc, &correction)?;
+                Ok((r.y, q))
+            },
+        )
+    }
+
+    fn assign_constant_point(
+        p: &NonNativeChip<F>,
+        region: &mut Region<'_, F>,
+        offset: &mut usize,
+        point: &Point,
+    ) -> Result<AssignedPoint<F>, Error> {
+        Ok(AssignedPoint {
+            x: p.assign_constant(region, Self::next(offset, 1), &point.x)?,
+            y: p.assign_constant(region, Self::next(offset, 1), &point.y)?,
+        })
+    }
+
+    /// Assigns `a + b`, whose x-coordinates must differ for the constraints
+    /// to hold.
+    fn add(
+        p: &NonNativeChip<F>,
+        region: &mut Region<'_, F>,
+        offset: &mut usize,
+        a: &AssignedPoint<F>,
+        b: &AssignedPoint<F>,
+    ) -> Result<AssignedPoint<F>, Error> {
+        let dx = p.sub(region, Self::next(offset, 2), &b.x, &a.x)?;
+        let dy = p.sub(region, Self::next(offset, 2), &b.y, &a.y)?;
+        let dx_inv = p.inv(region, Self::next(offset, 2), &dx)?;
+        let lambda = p.mul(region, Self::next(offset, 1), &dy, &dx_inv)?;
+        Self::with_slope(p, region, offset, a, b, &lambda)
+    }
+
+    /// Assigns `2 * a`.
+    fn double(
+        p: &NonNativeChip<F>,
+        region: &mut Region<'_, F>,
+        offset: &mut usize,
+        a: &AssignedPoint<F>,
+    ) -> Result<AssignedPoint<F>, Error> {
+        let xx = p.mul(region, Self::next(offset, 1), &a.x, &a.x)?;
+        let xx2 = p.add(region, Self::next(offset, 1), &xx, &xx)?;
+        let xx3 = p.add(region, Self::next(offset, 1), &xx2, &xx)?;
+        let y2 = p.add(region, Self::next(offset, 1), &a.y, &a.y)?;
+        let y2_inv = p.inv(region, Self::next(offset, 2), &y2)?;
+        let lambda = p.mul(region, Self::next(offset, 1), &xx3, &y2_inv)?;
+        Self::with_slope(p, region, offset, a, a, &lambda)
+    }
+
+    /// Assigns the sum of `a` and `b` from the slope `lambda` of the line
+    /// through them.
+    fn with_slope(
+        p: &NonNativeChip<F>,
+        region: &mut Region<'_, F>,
+        offset: &mut usize,
+        a: &AssignedPoint<F>,
+        b: &AssignedPoint<F>,
+        lambda: &AssignedInteger<F>,
+    ) -> Result<AssignedPoint<F>, Error> {
+        let lambda2 = p.mul(region, Self::next(offset, 1), lambda, lambda)?;
+        let lambda2_a = p.sub(region, Self::next(offset, 2), &lambda2, &a.x)?;
+        let x = p.sub(region, Self::next(offset, 2), &lambda2_a, &b.x)?;
+        let dx = p.sub(region, Self::next(offset, 2), &a.x, &x)?;
+        let lambda_dx = p.mul(region, Self::next(offset, 1), lambda, &dx)?;
+        let y = p.sub(region, Self::next(offset, 2), &lambda_dx, &a.y)?;
+        Ok(AssignedPoint { x, y })
+    }
+
+    /// Assigns `bit ? lhs : rhs` on two rows from `offset`, which is moved
+    /// past them.
+    fn select(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: &mut usize,
+        bit: &AssignedBit<F>,
+        lhs: &AssignedPoint<F>,
+        rhs: &AssignedPoint<F>,
+    ) -> Result<AssignedPoint<F>, Error> {
+        let x = self.select_integer(region, *offset, bit, &lhs.x, &rhs.x)?;
+        let y = self.select_integer(region, *offset + 1, bit, &lhs.y, &rhs.y)?;
+        *offset += 2;
+        Ok(AssignedPoint { x, y })
+    }
+
+    fn select_integer(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        bit: &AssignedBit<F>,
+        lhs: &AssignedInteger<F>,
+        rhs: &AssignedInteger<F>,
+    ) -> Result<AssignedInteger<F>, Error> {
+        let config = &self.config;
+        config.q_select.enable(region, offset)?;
+        bit.cell.copy_advice(|| "bit", region, config.bit, offset)?;
+        for (annotation, columns, integer) in [("lhs", &config.lhs, lhs), ("rhs", &config.rhs, rhs)]
+        {
+            for (cell, column) in integer.limbs.iter().zip(columns) {
+                cell.copy_advice(|| annotation, region, *column, offset)?;
+            }
+        }
+
+        let value = if bit.value { lhs.value } else { rhs.value };
+        let limbs = config
+            .out
+            .iter()
+            .zip(value)
+            .map(|(column, limb)| {
+                region.assign_advice(|| "out", *column, offset, || Value::known(F::from(limb)))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(AssignedInteger { limbs, value })
+    }
+
+    /// Assigns the limbs of an integer of 256 bits at `offset`, copied from
+    /// `limbs`, and the word they pack into.
+    fn assign_word(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        limbs: &[AssignedValue<F>],
+        value: &Limbs,
+    ) -> Result<AssignedWord<F>, Error> {
+        let config = &self.config;
+        config.q_word.enable(region, offset)?;
+        for (cell, column) in limbs.iter().zip(config.limbs) {
+            cell.copy_advice(|| "limb", region, column, offset)?;
+        }
+        let half = |limbs: &[u64]| {
+            let half = limbs
+                .iter()
+                .rev()
+                .fold(0u128, |acc, limb| (acc << LIMB_BITS) | *limb as u128);
+            Value::known(F::from_u128(half))
+        };
+        WordLoHi::new([half(&value[..4]), half(&value[4..])]).assign_advice(
+            region,
+            "packed",
+            config.packed,
+            offset,
+        )
+    }
+
+    /// Assigns the words of the message hash, `r` and `s` on the first three
+    /// rows, the coordinates of `Q` on the next two along with their bytes,
+    /// and the parity check of the y-coordinate `y` of `R` on the last one.
+    /// Returns the words, the cell of `v` and the bytes of `Q`.
+    fn assign_words(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        id: usize,
+        call: &EcrecoverCall,
+        scalars: &ScalarCells<F>,
+        y: &AssignedInteger<F>,
+        q: &AssignedPoint<F>,
+    ) -> Result<([AssignedWord<F>; 3], AssignedValue<F>, AssignedBytes<F>), Error> {
+        let config = &self.config;
+        let limb_range = DecomposeChip::construct(config.limb_range);
+        let word_chip = WordChip::construct(config.word);
+
+        layouter.assign_region(
+            || format!("ecrecover call {} words", id),
+            |mut region| {
+                let h = native::from_be_bytes(&call.msg_hash);
+                let msg_hash = self.assign_word(&mut region, 0, &scalars.msg_hash, &h)?;
+                let r = self.assign_word(&mut region, 1, &scalars.r.limbs, &scalars.r.value)?;
+                let s = self.assign_word(&mut region, 2, &scalars.s.limbs, &scalars.s.value)?;
+
+                let mut bytes = vec![];
+                for (i, coordinate) in [&q.x, &q.y].into_iter().enumerate() {
+                    let offset = 3 + i;
+                    let word = self.assign_word(
+                        &mut region,
+                        offset,
+                        &coordinate.limbs,
+                        &coordinate.value,
+                    )?;
+                    let (coordinate_bytes, packed) = word_chip.pack(
+                        &mut region,
+                        offset,
+                        &native::to_be_bytes(&coordinate.value),
+                    )?;
+                    for (lhs, rhs) in word.to_lo_hi().iter().zip(packed.to_lo_hi()) {
+                        region.constrain_equal(lhs.cell(), rhs.cell())?;
+                    }
+                    bytes.extend(coordinate_bytes);
+                }
+
+                let offset = 5;
+                config.q_parity.enable(&mut region, offset)?;
+                y.limbs[0].copy_advice(|| "y_0", &mut region, config.limbs[0], offset)?;
+                let v = region.assign_advice(
+                    || "v",
+                    config.v,
+                    offset,
+                    || Value::known(F::from(call.v as u64)),
+                )?;
+                let half = region.assign_advice(
+                    || "half",
+                    config.half,
+                    offset,
+                    || Value::known(F::from(y.value[0] >> 1)),
+                )?;
+                limb_range.copy(&mut region, 0, &half)?;
+
+                Ok(([msg_hash, r, s], v, AssignedBytes::new(bytes)))
+            },
+        )
+    }
+
+    /// Assigns the digest of `pubkey`, looked up along with the RLC of its
+    /// bytes, and returns the cell of the address.
+    fn assign_digest(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        id: usize,
+        pubkey: &Point,
+        pubkey_rlc: &AssignedValue<F>,
+    ) -> Result<AssignedValue<F>, Error> {
+        let config = &self.config;
+        let limb_range = DecomposeChip::construct(config.limb_range);
+        let digest = keccak256_circuit::native::keccak256(&native::pubkey_bytes(pubkey));
+        let digest = native::from_be_bytes(&digest);
+
+        layouter.assign_region(
+            || format!("ecrecover call {} digest", id),
+            |mut region| {
+                let limbs = digest
+                    .iter()
+                    .enumerate()
+                    .map(|(i, limb)| {
+                        limb_range
+                            .assign(&mut region, i, *limb)
+                            .map(|(cell, _)| cell)
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+                let word = self.assign_word(&mut region, 0, &limbs, &digest)?;
+                config.q_keccak.enable(&mut region, 0)?;
+                pubkey_rlc.copy_advice(|| "pubkey_rlc", &mut region, config.pubkey_rlc, 0)?;
+
+                let address = word
+                    .lo()
+                    .value()
+                    .map(|lo| *lo + F::from(digest[4]) * pow_of_two::<F>(128));
+                region.assign_advice(|| "address", config.address, 0, || address)
+            },
+        )
+    }
+}
+
+#[cfg(any(feature = "test", test))]
+pub mod dev {
+    use super::*;
+
+    use halo2_proofs::{circuit::SimpleFloorPlanner, plonk::Circuit};
+    use keccak256_circuit::{KeccakChip, KeccakConfig, KeccakWitness};
+    use native::{EcrecoverCall, ADDRESS_BYTES};
+
+    lazy_static::lazy_static! {
+        /// Signatures of the hashes of a few messages by the private keys
+        /// one and two, along with the addresses of their signers.
+        pub static ref CALLS_ADDRESSES: (Vec<EcrecoverCall>, Vec<[u8; ADDRESS_BYTES]>) = {
+        [
+            (1, 2, "abc"),
+            (1, 0xdead_beef, ""),
+            (2, 3, "ecrecover"),
+        ]
+            .iter()
+            .map(|(d, k, message)| {
+                let d = [*d, 0, 0, 0, 0, 0, 0, 0];
+                let msg_hash = keccak256_circuit::native::keccak256(message.as_bytes());
+                let call = native::sign(&d, &[*k, 0, 0, 0, 0, 0, 0, 0], msg_hash)
+                    .expect("the nonces are valid");
+                let pubkey = native::mul(&d, &native::GENERATOR).expect("d is not zero");
+                (call, native::address(&pubkey))
+            })
+            .unzip()
+        };
+    }
+
+    #[derive(Default)]
+    pub struct EcrecoverTestCircuit<F> {
+        pub calls: Vec<EcrecoverCall>,
+        pub _marker: PhantomData<F>,
+    }
+
+    /// The ecrecover circuit along with the Keccak-256 circuit it looks up,
+    /// sharing their fixed tables.
+    impl<F: FieldExt> Circuit<F> for EcrecoverTestCircuit<F> {
+        type Config = (FixedTables, KeccakConfig<F>, EcrecoverConfig<F>);
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            let mut tables = FixedTables::default();
+            let keccak_table = KeccakTable::construct(meta);
+            let keccak = KeccakConfig::configure_with_tables(meta, keccak_table, &mut tables);
+            let ecrecover_table = EcrecoverTable::construct(meta);
+            let config = EcrecoverConfig::configure_with_tables(
+                meta,
+                ecrecover_table,
+                keccak.table().clone(),
+                keccak.challenge(),
+                &mut tables,
+            );
+            (tables, keccak, config)
+        }
+
+        fn synthesize(
+            &self,
+            (tables, keccak, config): Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            tables.load(&mut layouter)?;
+            let witness = EcrecoverWitness {
+                calls: self.calls.clone(),
+                _marker: PhantomData,
+            };
+            let inputs = witness.keccak_inputs().ok_or(Error::Synthesis)?;
+            KeccakChip::construct(
+                keccak,
+                KeccakWitness {
+                    inputs,
+                    _marker: PhantomData,
+                },
+            )
+            .load(&mut layouter)?;
+            EcrecoverChip::construct(config, witness).load(&mut layouter)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use gadgets::rows;
+    use halo2_proofs::{
+        circuit::{Layouter, SimpleFloorPlanner},
+        dev::MockProver,
+        halo2curves::bn256::Fr,
+        plonk::{Circuit, ConstraintSystem, Error, FirstPhase},
+    };
+    use keccak256_circuit::KeccakTable;
+    use non_native_field::NonNativeChip;
+    use std::marker::PhantomData;
+
+    use crate::{
+        dev::{EcrecoverTestCircuit, CALLS_ADDRESSES},
+        native::{self, Point, GENERATOR},
+        EcrecoverChip, EcrecoverConfig, EcrecoverTable, EcrecoverWitness,
+    };
+
+    #[derive(Clone, Copy, Debug)]
+    enum PointOp {
+        Add(Point, Point),
+        Double(Point),
+    }
+
+    /// Runs every operation on the points of the chip, and checks its result
+    /// against the given one.
+    #[derive(Default)]
+    struct PointsCircuit {
+        ops: Vec<(PointOp, Point)>,
+    }
+
+    impl Circuit<Fr> for PointsCircuit {
+        type Config = EcrecoverConfig<Fr>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+            let table = EcrecoverTable::construct(meta);
+            let keccak_table = KeccakTable::construct(meta);
+            let challenge = meta.challenge_usable_after(FirstPhase);
+            EcrecoverConfig::configure(meta, table, keccak_table, challenge)
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<Fr>,
+        ) -> Result<(), Error> {
+            config.tables.load(&mut layouter)?;
+            let p = NonNativeChip::construct(config.base.clone());
+            layouter.assign_region(
+                || "points",
+                |mut region| {
+                    let region = &mut region;
+                    let mut offset = 0;
+                    for (op, expected) in self.ops.iter() {
+                        let result = match op {
+                            PointOp::Add(a, b) => {
+                                let a = EcrecoverChip::assign_constant_point(
+                                    &p,
+                                    region,
+                                    &mut offset,
+                                    a,
+                                )?;
+                                let b = EcrecoverChip::assign_constant_point(
+                                    &p,
+                                    region,
+                                    &mut offset,
+                                    b,
+                                )?;
+                                EcrecoverChip::add(&p, region, &mut offset, &a, &b)?
+                            }
+                            PointOp::Double(a) => {
+                                let a = EcrecoverChip::assign_constant_point(
+                                    &p,
+                                    region,
+                                    &mut offset,
+                                    a,
+                                )?;
+                                EcrecoverChip::double(&p, region, &mut offset, &a)?
+                            }
+                        };
+                        let expected = EcrecoverChip::assign_constant_point(
+                            &p,
+                            region,
+                            &mut offset,
+                            expected,
+                        )?;
+                        p.constrain_equal(region, &result.x, &expected.x)?;
+                        p.constrain_equal(region, &result.y, &expected.y)?;
+                    }
+                    Ok(())
+                },
+            )
+        }
+    }
+
+    fn verify_points(ops: Vec<(PointOp, Point)>) -> bool {
+        let circuit = PointsCircuit { ops };
+        let prover = MockProver::run(rows::min_k(&circuit), &circuit, vec![]).unwrap();
+        prover.verify().is_ok()
+    }
+
+    #[test]
+    fn test_ecrecover_native() {
+        let (calls, addresses) = CALLS_ADDRESSES.clone();
+        for (call, address) in calls.iter().zip(addresses) {
+            assert_eq!(call.recover(), Some(address));
+            assert!(call.signature_point().unwrap().is_on_curve());
+        }
+    }
+
+    #[test]
+    fn test_ecrecover_points() {
+        let two = GENERATOR.double();
+        let three = two.add(&GENERATOR).unwrap();
+        let offset = native::offset_point();
+        assert!(verify_points(vec![
+            (PointOp::Double(GENERATOR), two),
+            (PointOp::Add(two, GENERATOR), three),
+            (PointOp::Add(GENERATOR, two), three),
+            (PointOp::Double(offset), offset.double()),
+            (
+                PointOp::Add(offset, three.neg()),
+                offset.add(&three.neg()).unwrap()
+            ),
+        ]));
+
+        // the result is another point.
+        assert!(!verify_points(vec![(PointOp::Add(two, GENERATOR), two)]));
+        assert!(!verify_points(vec![(PointOp::Double(GENERATOR), three)]));
+
+        // the incomplete addition of points of the same x-coordinate.
+        assert!(!verify_points(vec![(
+            PointOp::Add(GENERATOR, GENERATOR),
+            two
+        )]));
+    }
+
+    #[test]
+    fn test_ecrecover_invalid_signature() {
+        let (calls, _) = CALLS_ADDRESSES.clone();
+        let invalid = native::EcrecoverCall { v: 29, ..calls[0] };
+        let circuit: EcrecoverTestCircuit<Fr> = EcrecoverTestCircuit {
+            calls: vec![invalid],
+            _marker: PhantomData,
+        };
+        assert!(MockProver::run(12, &circuit, vec![]).is_err());
+        assert_eq!(
+            EcrecoverWitness::<Fr> {
+                calls: vec![invalid],
+                _marker: PhantomData,
+            }
+            .keccak_inputs(),
+            None
+        );
+    }
+
+    #[test]
+    #[ignore = "recovers the signers of a few calls at k = 19, run with --ignored"]
+    fn test_ecrecover_circuit() {
+        let (calls, _) = CALLS_ADDRESSES.clone();
+        let circuit: EcrecoverTestCircuit<Fr> = EcrecoverTestCircuit {
+            calls,
+            _marker: PhantomData,
+        };
+        let prover = MockProver::run(rows::min_k(&circuit), &circuit, vec![]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+    }
+}
@@ -0,0 +1,357 @@
+//! Native recovery of the signer of a secp256k1 signature, used to compute
+//! the witness of the circuit and as the reference it is tested against.
+//!
+//! Points are affine, the point at infinity being `None` where it can occur.
+
+use keccak256_circuit::native::keccak256;
+use non_native_field::native::{
+    Limbs, Modulus, LIMB_BITS, NUM_LIMBS, SECP256K1_N, SECP256K1_P, ZERO,
+};
+
+/// Number of bytes of a word, e.g. of the message hash or of `r`.
+pub const WORD_BYTES: usize = 32;
+
+/// Number of bytes of an address.
+pub const ADDRESS_BYTES: usize = 20;
+
+/// Number of bits of a scalar.
+pub const SCALAR_BITS: usize = NUM_LIMBS * LIMB_BITS;
+
+/// The base field of secp256k1, of the coordinates of its points.
+pub const P: Modulus = Modulus(SECP256K1_P);
+
+/// The order of the group of secp256k1, the modulus of its scalars.
+pub const N: Modulus = Modulus(SECP256K1_N);
+
+/// The coefficient `b` of the curve `y^2 = x^3 + b`.
+pub const B: Limbs = [7, 0, 0, 0, 0, 0, 0, 0];
+
+/// `(p + 1) / 4`, the exponent of the square roots modulo `p`, which is `3`
+/// modulo `4`.
+const SQRT_EXPONENT: Limbs = [
+    0xBFFF_FF0C,
+    0xFFFF_FFFF,
+    0xFFFF_FFFF,
+    0xFFFF_FFFF,
+    0xFFFF_FFFF,
+    0xFFFF_FFFF,
+    0xFFFF_FFFF,
+    0x3FFF_FFFF,
+];
+
+/// The generator `G` of secp256k1.
+pub const GENERATOR: Point = Point {
+    x: [
+        0x16F8_1798,
+        0x59F2_815B,
+        0x2DCE_28D9,
+        0x029B_FCDB,
+        0xCE87_0B07,
+        0x55A0_6295,
+        0xF9DC_BBAC,
+        0x79BE_667E,
+    ],
+    y: [
+        0xFB10_D4B8,
+        0x9C47_D08F,
+        0xA685_5419,
+        0xFD17_B448,
+        0x0E11_08A8,
+        0x5DA4_FBFC,
+        0x26A3_C465,
+        0x483A_DA77,
+    ],
+};
+
+/// An affine point of secp256k1.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Point {
+    pub x: Limbs,
+    pub y: Limbs,
+}
+
+impl Point {
+    /// The point of x-coordinate `x` whose y-coordinate is odd if `odd`, or
+    /// `None` if `x` is not the x-coordinate of a point.
+    pub fn from_x(x: &Limbs, odd: bool) -> Option<Self> {
+        let y2 = P.add(&P.mul(&P.mul(x, x), x), &B);
+        let y = P.pow(&y2, &SQRT_EXPONENT);
+        if P.mul(&y, &y) != y2 {
+            return None;
+        }
+        let y = if (y[0] & 1 == 1) == odd { y } else { P.neg(&y) };
+        Some(Self { x: *x, y })
+    }
+
+    pub fn is_on_curve(&self) -> bool {
+        let x3 = P.mul(&P.mul(&self.x, &self.x), &self.x);
+        P.mul(&self.y, &self.y) == P.add(&x3, &B)
+    }
+
+    pub fn neg(&self) -> Self {
+        Self {
+            x: self.x,
+            y: P.neg(&self.y),
+        }
+    }
+
+    /// `self + other` by the incomplete formula of the circuit, or `None` if
+    /// their x-coordinates are the same, i.e. for `other = ±self`.
+    pub fn add(&self, other: &Self) -> Option<Self> {
+        let dx = P.sub(&other.x, &self.x);
+        let lambda = P.mul(&P.sub(&other.y, &self.y), &P.inv(&dx)?);
+        Some(self.with_slope(other, &lambda))
+    }
+
+    /// `2 * self`, which is never at infinity as the order of the group is
+    /// odd.
+    pub fn double(&self) -> Self {
+        let xx = P.mul(&self.x, &self.x);
+        let two_y = P.add(&self.y, &self.y);
+        let inv = P.inv(&two_y).expect("no point of secp256k1 has a zero y");
+        let lambda = P.mul(&P.add(&P.add(&xx, &xx), &xx), &inv);
+        self.with_slope(self, &lambda)
+    }
+
+    /// The third point of the line of slope `lambda` through `self` and
+    /// `other`, reflected, i.e. their sum.
+    fn with_slope(&self, other: &Self, lambda: &Limbs) -> Self {
+        let x = P.sub(&P.sub(&P.mul(lambda, lambda), &self.x), &other.x);
+        let y = P.sub(&P.mul(lambda, &P.sub(&self.x, &x)), &self.y);
+        Self { x, y }
+    }
+}
+
+/// `a + b` of points that may be at infinity.
+pub fn add(a: Option<Point>, b: Option<Point>) -> Option<Point> {
+    match (a, b) {
+        (None, b) => b,
+        (a, None) => a,
+        (Some(a), Some(b)) if a == b => Some(a.double()),
+        (Some(a), Some(b)) => a.add(&b),
+    }
+}
+
+/// `scalar * point` by double-and-add, or `None` at infinity.
+pub fn mul(scalar: &Limbs, point: &Point) -> Option<Point> {
+    bits(scalar).into_iter().fold(None, |acc, bit| {
+        let acc = add(acc, acc);
+        if bit {
+            add(acc, Some(*point))
+        } else {
+            acc
+        }
+    })
+}
+
+/// The [`SCALAR_BITS`] bits of `scalar`, the most significant first.
+pub fn bits(scalar: &Limbs) -> Vec<bool> {
+    (0..SCALAR_BITS)
+        .rev()
+        .map(|i| (scalar[i / LIMB_BITS] >> (i % LIMB_BITS)) & 1 == 1)
+        .collect()
+}
+
+/// The point of the smallest positive x-coordinate and of an even
+/// y-coordinate, whose discrete logarithm nobody knows. The circuit starts
+/// its double-and-add from it, so that no intermediate sum is at infinity.
+pub fn offset_point() -> Point {
+    (1..)
+        .find_map(|x| Point::from_x(&[x, 0, 0, 0, 0, 0, 0, 0], false))
+        .expect("half of the x-coordinates are those of points")
+}
+
+/// `-2^256` times the [`offset_point`], which the circuit adds to the result
+/// of its double-and-add to take the offset back out.
+pub fn offset_correction() -> Point {
+    (0..SCALAR_BITS)
+        .fold(offset_point(), |acc, _| acc.double())
+        .neg()
+}
+
+/// Little-endian limbs of a big-endian word.
+pub fn from_be_bytes(bytes: &[u8; WORD_BYTES]) -> Limbs {
+    let mut limbs = ZERO;
+    for (limb, chunk) in limbs.iter_mut().zip(bytes.rchunks(LIMB_BITS / 8)) {
+        *limb = chunk.iter().fold(0, |acc, byte| (acc << 8) | *byte as u64);
+    }
+    limbs
+}
+
+/// Big-endian word of little-endian limbs.
+pub fn to_be_bytes(limbs: &Limbs) -> [u8; WORD_BYTES] {
+    let mut bytes = [0; WORD_BYTES];
+    for (chunk, limb) in bytes.rchunks_mut(LIMB_BITS / 8).zip(limbs) {
+        chunk.copy_from_slice(&(*limb as u32).to_be_bytes());
+    }
+    bytes
+}
+
+/// A call to the precompile: the hash of a message and its signature
+/// `(v, r, s)`, whose `v` is `27` or `28`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct EcrecoverCall {
+    pub msg_hash: [u8; WORD_BYTES],
+    pub v: u8,
+    pub r: [u8; WORD_BYTES],
+    pub s: [u8; WORD_BYTES],
+}
+
+impl EcrecoverCall {
+    /// The point `R` of the signature, of x-coordinate `r` and of a
+    /// y-coordinate of the parity `v - 27`, or `None` if there is none.
+    pub fn signature_point(&self) -> Option<Point> {
+        let odd = match self.v {
+            27 => false,
+            28 => true,
+            _ => return None,
+        };
+        let r = from_be_bytes(&self.r);
+        if P.add(&r, &ZERO) != r {
+            return None;
+        }
+        Point::from_x(&r, odd)
+    }
+
+    /// The scalars `(u1, u2) = (-h / r, s / r)` modulo the order of the
+    /// group, `h` being the message hash modulo the order, of the public key
+    /// `u1 * G + u2 * R`, or `None` if `r` or `s` is not in `[1, n)`.
+    pub fn scalars(&self) -> Option<(Limbs, Limbs)> {
+        let [h, r, s] = [self.msg_hash, self.r, self.s].map(|word| from_be_bytes(&word));
+        for scalar in [r, s] {
+            if scalar == ZERO || N.add(&scalar, &ZERO) != scalar {
+                return None;
+            }
+        }
+        let r_inv = N.inv(&r)?;
+        let h = N.add(&h, &ZERO);
+        Some((N.neg(&N.mul(&h, &r_inv)), N.mul(&s, &r_inv)))
+    }
+
+    /// The public key of the signer, or `None` if the signature is invalid.
+    pub fn recover_pubkey(&self) -> Option<Point> {
+        let (u1, u2) = self.scalars()?;
+        let point = self.signature_point()?;
+        add(mul(&u1, &GENERATOR), mul(&u2, &point))
+    }
+
+    /// The address of the signer, or `None` if the signature is invalid,
+    /// for which the precompile returns no output.
+    pub fn recover(&self) -> Option<[u8; ADDRESS_BYTES]> {
+        self.recover_pubkey().map(|pubkey| address(&pubkey))
+    }
+}
+
+/// The big-endian coordinates of `pubkey`, which its address is the hash of.
+pub fn pubkey_bytes(pubkey: &Point) -> Vec<u8> {
+    let mut bytes = to_be_bytes(&pubkey.x).to_vec();
+    bytes.extend(to_be_bytes(&pubkey.y));
+    bytes
+}
+
+/// The address of `pubkey`: the last 20 bytes of the Keccak-256 digest of
+/// its coordinates.
+pub fn address(pubkey: &Point) -> [u8; ADDRESS_BYTES] {
+    let digest = keccak256(&pubkey_bytes(pubkey));
+    digest[WORD_BYTES - ADDRESS_BYTES..]
+        .try_into()
+        .expect("an address is 20 bytes")
+}
+
+/// Signs `msg_hash` with the private key `d` and the nonce `k`, e.g. for the
+/// tests, or returns `None` if `R` has an x-coordinate of at least `n`,
+/// which the `v` of the precompile cannot encode.
+pub fn sign(d: &Limbs, k: &Limbs, msg_hash: [u8; WORD_BYTES]) -> Option<EcrecoverCall> {
+    let point = mul(k, &GENERATOR)?;
+    if N.add(&point.x, &ZERO) != point.x {
+        return None;
+    }
+    let h = N.add(&from_be_bytes(&msg_hash), &ZERO);
+    let s = N.mul(&N.inv(k)?, &N.add(&h, &N.mul(&point.x, d)));
+    Some(EcrecoverCall {
+        msg_hash,
+        v: 27 + (point.y[0] & 1) as u8,
+        r: to_be_bytes(&point.x),
+        s: to_be_bytes(&s),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use non_native_field::native::ONE;
+
+    #[test]
+    fn test_curve() {
+        assert!(GENERATOR.is_on_curve());
+        assert_eq!(Point::from_x(&GENERATOR.x, false), Some(GENERATOR));
+        assert_eq!(Point::from_x(&GENERATOR.x, true), Some(GENERATOR.neg()));
+        // n * G is at infinity.
+        assert_eq!(mul(&N.max(), &GENERATOR), Some(GENERATOR.neg()));
+        assert_eq!(add(mul(&N.max(), &GENERATOR), Some(GENERATOR)), None);
+
+        let two = GENERATOR.double();
+        assert!(two.is_on_curve());
+        assert_eq!(
+            two.add(&GENERATOR),
+            mul(&[3, 0, 0, 0, 0, 0, 0, 0], &GENERATOR)
+        );
+        assert_eq!(GENERATOR.add(&GENERATOR), None);
+
+        let offset = offset_point();
+        assert!(offset.is_on_curve());
+        assert_eq!(offset.y[0] & 1, 0);
+        assert!(offset_correction().is_on_curve());
+    }
+
+    #[test]
+    fn test_words() {
+        let mut word = [0; WORD_BYTES];
+        word[WORD_BYTES - 1] = 1;
+        assert_eq!(from_be_bytes(&word), ONE);
+        assert_eq!(to_be_bytes(&GENERATOR.x)[..4], [0x79, 0xBE, 0x66, 0x7E]);
+        assert_eq!(from_be_bytes(&to_be_bytes(&GENERATOR.y)), GENERATOR.y);
+    }
+
+    #[test]
+    fn test_recover() {
+        // the address of the private key one, whose public key is G.
+        let expected =
+            ethers_core::utils::hex::decode("7e5f4552091a69125d5dfcb7b8c2659029395bdf").unwrap();
+        assert_eq!(address(&GENERATOR).to_vec(), expected);
+
+        let msg_hash = keccak256(b"ecrecover");
+        for k in [2, 3, 0xdead_beef] {
+            let call = sign(&ONE, &[k, 0, 0, 0, 0, 0, 0, 0], msg_hash).unwrap();
+            assert_eq!(call.recover_pubkey(), Some(GENERATOR));
+            assert_eq!(call.recover().unwrap().to_vec(), expected);
+
+            // the other parity recovers another signer.
+            let other = EcrecoverCall {
+                v: 55 - call.v,
+                ..call
+            };
+            assert!(!matches!(other.recover_pubkey(), Some(GENERATOR) | None));
+        }
+
+        // a signature with an invalid v, a zero r or a zero s.
+        let call = sign(&ONE, &[2, 0, 0, 0, 0, 0, 0, 0], msg_hash).unwrap();
+        for wrong in [
+            EcrecoverCall { v: 29, ..call },
+            EcrecoverCall {
+                r: [0; WORD_BYTES],
+                ..call
+            },
+            EcrecoverCall {
+                s: [0; WORD_BYTES],
+                ..call
+            },
+            EcrecoverCall {
+                s: to_be_bytes(&N.0),
+                ..call
+            },
+        ] {
+            assert_eq!(wrong.recover(), None);
+        }
+    }
+}
@@ -0,0 +1,68 @@
+//! Accumulates the toy KZG openings of a polynomial at points, and checks
+//! the accumulator, see the `accumulation` module:
+//!
+//! ```text
+//! cargo run --example accumulation -- 4 5 0 11
+//! ```
+//!
+//! The polynomial is `1 + 2X + 3X^2`, and the secret of the toy setup a
+//! constant of the example.
+
+use examples::{
+    claim_instances, mock_prove, parse_args, toy_open, AccumulationChip, AccumulationConfig, Claim,
+};
+use halo2_proofs::{
+    circuit::{Layouter, SimpleFloorPlanner, Value},
+    halo2curves::bn256::Fr,
+    plonk::{Circuit, ConstraintSystem, Error},
+};
+
+/// The secret of the toy setup.
+const TAU: u64 = 0x1234_5678;
+
+#[derive(Clone)]
+struct AccumulationCircuit {
+    proofs: Vec<Fr>,
+}
+
+impl Circuit<Fr> for AccumulationCircuit {
+    type Config = AccumulationConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        self.clone()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+        AccumulationConfig::configure(meta, Fr::from(TAU))
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<Fr>,
+    ) -> Result<(), Error> {
+        let proofs = self
+            .proofs
+            .iter()
+            .copied()
+            .map(Value::known)
+            .collect::<Vec<_>>();
+        AccumulationChip::new(config).assign(&mut layouter, &proofs)
+    }
+}
+
+fn main() {
+    let (k, points) = parse_args::<u64>("accumulation", "<point>...", 1);
+    let coeffs = [1, 2, 3].map(Fr::from);
+    let claims = points
+        .into_iter()
+        .map(|point| toy_open(Fr::from(TAU), &coeffs, Fr::from(point)))
+        .collect::<Vec<Claim<Fr>>>();
+    let proofs = claims.iter().map(|claim| claim.proof).collect();
+    mock_prove(
+        k,
+        &AccumulationCircuit { proofs },
+        vec![claim_instances(&claims)],
+    );
+}
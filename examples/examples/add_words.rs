@@ -0,0 +1,50 @@
+//! Adds two 256-bit words, each its 128-bit halves `lo` and `hi`, with the
+//! overflow flag, see the `add_words` module:
+//!
+//! ```text
+//! cargo run --example add_words -- 9 340282366920938463463374607431768211455 0 1 0
+//! ```
+
+use examples::{add_words, mock_prove, parse_args, AddWordsChip, AddWordsConfig};
+use halo2_proofs::{
+    circuit::{Layouter, SimpleFloorPlanner},
+    halo2curves::bn256::Fr,
+    plonk::{Circuit, ConstraintSystem, Error},
+};
+
+#[derive(Clone)]
+struct AddWordsCircuit {
+    addends: [[u128; 2]; 2],
+}
+
+impl Circuit<Fr> for AddWordsCircuit {
+    type Config = AddWordsConfig<2>;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        self.clone()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+        AddWordsConfig::configure(meta, false)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<Fr>,
+    ) -> Result<(), Error> {
+        let chip = AddWordsChip::new(config);
+        chip.load_table(&mut layouter)?;
+        chip.assign(&mut layouter, self.addends)?;
+        Ok(())
+    }
+}
+
+fn main() {
+    let (k, halves) = parse_args::<u128>("add_words", "<a.lo> <a.hi> <b.lo> <b.hi>", 4);
+    let addends = [[halves[0], halves[1]], [halves[2], halves[3]]];
+    let ([lo, hi], overflow) = add_words(&addends);
+    println!("sum.lo = {lo}, sum.hi = {hi}, overflow = {overflow}");
+    mock_prove(k, &AddWordsCircuit { addends }, vec![]);
+}
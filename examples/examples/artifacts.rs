@@ -0,0 +1,87 @@
+//! Proves the Fibonacci circuit, writes its artifacts to a directory in
+//! every `SerdeFormat`, and verifies the proof from the artifacts read
+//! back, see the `artifacts` module:
+//!
+//! ```text
+//! cargo run --example artifacts -- 5 10 artifacts
+//! ```
+//!
+//! The directory is a temporary one if not given.
+
+use std::{env::temp_dir, path::PathBuf, process::exit};
+
+use examples::{fibonacci, parse_args, Artifacts, FibonacciChip, FibonacciConfig};
+use halo2_proofs::{
+    arithmetic::Field,
+    circuit::{Layouter, SimpleFloorPlanner},
+    halo2curves::bn256::Fr,
+    plonk::{Circuit, ConstraintSystem, Error},
+    SerdeFormat,
+};
+
+#[derive(Clone)]
+struct FibonacciCircuit {
+    n: usize,
+}
+
+impl Circuit<Fr> for FibonacciCircuit {
+    type Config = FibonacciConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        self.clone()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+        FibonacciConfig::configure(meta)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<Fr>,
+    ) -> Result<(), Error> {
+        let chip = FibonacciChip::new(config);
+        let output = chip.assign(&mut layouter, self.n)?;
+        chip.expose_output(&mut layouter, &output)
+    }
+}
+
+fn main() {
+    let (k, inputs) = parse_args::<String>("artifacts", "<n> [<dir>]", 1);
+    let Ok(n) = inputs[0].parse::<usize>() else {
+        eprintln!("The index n is a number");
+        exit(2);
+    };
+    let n = n.max(2);
+    let dir = inputs
+        .get(1)
+        .map(PathBuf::from)
+        .unwrap_or_else(|| temp_dir().join("examples_artifacts"));
+    let instances = vec![vec![
+        Fr::one(),
+        Fr::one(),
+        fibonacci(Fr::one(), Fr::one(), n),
+    ]];
+
+    let artifacts = Artifacts::prove(k, FibonacciCircuit { n }, &instances);
+    for format in [
+        SerdeFormat::Processed,
+        SerdeFormat::RawBytes,
+        SerdeFormat::RawBytesUnchecked,
+    ] {
+        let dir = dir.join(format!("{format:?}"));
+        let read = artifacts
+            .write(&dir, format)
+            .and_then(|()| Artifacts::read::<FibonacciCircuit>(&dir, format))
+            .unwrap_or_else(|err| {
+                eprintln!("Cannot write the artifacts to {}: {err}", dir.display());
+                exit(1);
+            });
+        println!(
+            "{format:?} in {}: the proof verifies: {}",
+            dir.display(),
+            read.verify(&instances)
+        );
+    }
+}
@@ -0,0 +1,51 @@
+//! Proves the 512-bit product of two 256-bit integers, four 64-bit limbs
+//! each, least significant first, see the `bigint` module:
+//!
+//! ```text
+//! cargo run --example bigint -- 9 18446744073709551615 1 0 0 18446744073709551615 0 0 0
+//! ```
+
+use examples::{mock_prove, mul, parse_args, BigMulChip, BigMulConfig, NUM_LIMBS};
+use halo2_proofs::{
+    circuit::{Layouter, SimpleFloorPlanner},
+    halo2curves::bn256::Fr,
+    plonk::{Circuit, ConstraintSystem, Error},
+};
+
+#[derive(Clone)]
+struct BigMulCircuit {
+    a: [u64; NUM_LIMBS],
+    b: [u64; NUM_LIMBS],
+}
+
+impl Circuit<Fr> for BigMulCircuit {
+    type Config = BigMulConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        self.clone()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+        BigMulConfig::configure(meta)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<Fr>,
+    ) -> Result<(), Error> {
+        let chip = BigMulChip::new(config);
+        chip.load_table(&mut layouter)?;
+        chip.assign(&mut layouter, self.a, self.b)?;
+        Ok(())
+    }
+}
+
+fn main() {
+    let (k, limbs) = parse_args::<u64>("bigint", "<a_0> .. <a_3> <b_0> .. <b_3>", 2 * NUM_LIMBS);
+    let a = limbs[..NUM_LIMBS].try_into().unwrap();
+    let b = limbs[NUM_LIMBS..2 * NUM_LIMBS].try_into().unwrap();
+    println!("{:?}", mul(a, b));
+    mock_prove(k, &BigMulCircuit { a, b }, vec![]);
+}
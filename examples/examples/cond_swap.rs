@@ -0,0 +1,70 @@
+//! Swaps a pair of values if a bit is 1, see the `cond_swap` module:
+//!
+//! ```text
+//! cargo run --example cond_swap -- 4 2 3 1
+//! ```
+
+use examples::{cond_swap, mock_prove, parse_args, CondSwapChip, CondSwapConfig};
+use halo2_proofs::{
+    circuit::{Layouter, SimpleFloorPlanner, Value},
+    halo2curves::bn256::Fr,
+    plonk::{Advice, Circuit, Column, ConstraintSystem, Error},
+};
+
+#[derive(Clone)]
+struct CondSwapCircuit {
+    a: u64,
+    b: u64,
+    swap: bool,
+}
+
+impl Circuit<Fr> for CondSwapCircuit {
+    type Config = (CondSwapConfig, Column<Advice>);
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        self.clone()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+        let value = meta.advice_column();
+        meta.enable_equality(value);
+        (CondSwapConfig::configure(meta), value)
+    }
+
+    fn synthesize(
+        &self,
+        (config, value): Self::Config,
+        mut layouter: impl Layouter<Fr>,
+    ) -> Result<(), Error> {
+        let [a, b] = layouter.assign_region(
+            || "pair",
+            |mut region| {
+                let a = Value::known(Fr::from(self.a));
+                let b = Value::known(Fr::from(self.b));
+                Ok([
+                    region.assign_advice(|| "a", value, 0, || a)?,
+                    region.assign_advice(|| "b", value, 1, || b)?,
+                ])
+            },
+        )?;
+        CondSwapChip::new(config).swap(&mut layouter, (&a, &b), Value::known(self.swap))?;
+        Ok(())
+    }
+}
+
+fn main() {
+    let (k, inputs) = parse_args::<u64>("cond_swap", "<a> <b> <0|1>", 3);
+    let [a, b, swap] = [inputs[0], inputs[1], inputs[2]];
+    let (left, right) = cond_swap(swap == 1, a, b);
+    println!("({left}, {right})");
+    mock_prove(
+        k,
+        &CondSwapCircuit {
+            a,
+            b,
+            swap: swap == 1,
+        },
+        vec![],
+    );
+}
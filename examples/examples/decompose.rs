@@ -0,0 +1,59 @@
+//! Decomposes values into bytes, each looked up in a fixed table, see the
+//! `decompose` module:
+//!
+//! ```text
+//! cargo run --example decompose -- 9 2 258 65535
+//! ```
+
+use examples::{limbs, mock_prove, parse_args, DecomposeChip, DecomposeConfig};
+use halo2_proofs::{
+    circuit::{Layouter, SimpleFloorPlanner},
+    halo2curves::bn256::Fr,
+    plonk::{Circuit, ConstraintSystem, Error},
+};
+
+#[derive(Clone)]
+struct DecomposeCircuit {
+    num_bytes: usize,
+    values: Vec<u64>,
+}
+
+impl Circuit<Fr> for DecomposeCircuit {
+    type Config = DecomposeConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        self.clone()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+        DecomposeConfig::configure_bytes(meta)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<Fr>,
+    ) -> Result<(), Error> {
+        let chip = DecomposeChip::<Fr>::new(config);
+        chip.load_table(&mut layouter)?;
+        for value in &self.values {
+            chip.assign(&mut layouter, *value, self.num_bytes)?;
+        }
+        Ok(())
+    }
+}
+
+fn main() {
+    let (k, inputs) = parse_args::<u64>("decompose", "<num_bytes> <value>...", 2);
+    let num_bytes = inputs[0] as usize;
+    if !(1..=8).contains(&num_bytes) {
+        eprintln!("The values are of 1 to 8 bytes");
+        std::process::exit(2);
+    }
+    let values = inputs[1..].to_vec();
+    for value in &values {
+        println!("{value} = {:?}", limbs(*value, 8, num_bytes));
+    }
+    mock_prove(k, &DecomposeCircuit { num_bytes, values }, vec![]);
+}
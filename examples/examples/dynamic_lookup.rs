@@ -0,0 +1,60 @@
+//! Looks the claims `y = x^2` of a caller up in the table of a callee
+//! squaring the `x` claimed, see the `dynamic_lookup` module:
+//!
+//! ```text
+//! cargo run --example dynamic_lookup -- 5 3 9 2 4 3 9
+//! ```
+
+use examples::{
+    mock_prove, parse_args, CallerChip, CallerConfig, SquareChip, SquareConfig, SquareTable,
+};
+use halo2_proofs::{
+    circuit::{Layouter, SimpleFloorPlanner},
+    halo2curves::bn256::Fr,
+    plonk::{Circuit, ConstraintSystem, Error},
+};
+
+#[derive(Clone)]
+struct DynamicLookupCircuit {
+    inputs: Vec<u64>,
+    claims: Vec<(u64, u64)>,
+}
+
+impl Circuit<Fr> for DynamicLookupCircuit {
+    type Config = (SquareConfig, CallerConfig);
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        self.clone()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+        let table = SquareTable::construct(meta);
+        (
+            SquareConfig::configure(meta, table),
+            CallerConfig::configure(meta, table),
+        )
+    }
+
+    fn synthesize(
+        &self,
+        (square, caller): Self::Config,
+        mut layouter: impl Layouter<Fr>,
+    ) -> Result<(), Error> {
+        SquareChip::new(square).assign(&mut layouter, &self.inputs)?;
+        CallerChip::new(caller).assign(&mut layouter, &self.claims)
+    }
+}
+
+fn main() {
+    let (k, inputs) = parse_args::<u64>("dynamic_lookup", "<x> <y> [<x> <y>]...", 2);
+    let claims = inputs
+        .chunks(2)
+        .map(|claim| (claim[0], claim.get(1).copied().unwrap_or_default()))
+        .collect::<Vec<_>>();
+    // The callee squares every `x` claimed, once.
+    let mut inputs = claims.iter().map(|(x, _)| *x).collect::<Vec<_>>();
+    inputs.sort_unstable();
+    inputs.dedup();
+    mock_prove(k, &DynamicLookupCircuit { inputs, claims }, vec![]);
+}
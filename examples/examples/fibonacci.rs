@@ -0,0 +1,54 @@
+//! Proves the `n`-th number of the Fibonacci sequence from its first two
+//! numbers, see the `fibonacci` module:
+//!
+//! ```text
+//! cargo run --example fibonacci -- 5 1 1 10
+//! ```
+
+use examples::{fibonacci, mock_prove, parse_args, FibonacciChip, FibonacciConfig};
+use halo2_proofs::{
+    circuit::{Layouter, SimpleFloorPlanner},
+    halo2curves::bn256::Fr,
+    plonk::{Circuit, ConstraintSystem, Error},
+};
+
+#[derive(Clone)]
+struct FibonacciCircuit {
+    n: usize,
+}
+
+impl Circuit<Fr> for FibonacciCircuit {
+    type Config = FibonacciConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        self.clone()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+        FibonacciConfig::configure(meta)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<Fr>,
+    ) -> Result<(), Error> {
+        let chip = FibonacciChip::new(config);
+        let output = chip.assign(&mut layouter, self.n)?;
+        chip.expose_output(&mut layouter, &output)
+    }
+}
+
+fn main() {
+    let (k, inputs) = parse_args::<u64>("fibonacci", "<first> <second> <n>", 3);
+    let [first, second] = [inputs[0], inputs[1]].map(Fr::from);
+    let n = inputs[2].max(2) as usize;
+    let output = fibonacci(first, second, n);
+    println!("f({n}) = {output:?}");
+    mock_prove(
+        k,
+        &FibonacciCircuit { n },
+        vec![vec![first, second, output]],
+    );
+}
@@ -0,0 +1,39 @@
+//! Prints the rows `SimpleFloorPlanner` and `V1` lay the same regions out
+//! on, see the `floor_planner` module of the examples:
+//!
+//! ```text
+//! cargo run --example floor_planners -- 16
+//! ```
+//!
+//! The argument is the number of values on each side, 4 by default.
+
+use std::{env::args, marker::PhantomData, process::exit};
+
+use examples::{row_usage, PackingCircuit};
+use halo2_proofs::{
+    circuit::{floor_planner::V1, SimpleFloorPlanner},
+    halo2curves::bn256::Fr,
+};
+
+fn main() {
+    let n = match args().nth(1).map(|n| n.parse()) {
+        None => 4,
+        Some(Ok(n)) => n,
+        Some(Err(_)) => {
+            eprintln!("usage: cargo run --example floor_planners -- [<values>]");
+            exit(2);
+        }
+    };
+    let simple = PackingCircuit::<Fr, SimpleFloorPlanner> {
+        n,
+        _marker: PhantomData,
+    };
+    let v1 = PackingCircuit::<Fr, V1> {
+        n,
+        _marker: PhantomData,
+    };
+    let simple = row_usage(&simple).expect("the layout should not fail");
+    let v1 = row_usage(&v1).expect("the layout should not fail");
+    print!("SimpleFloorPlanner: {simple}");
+    print!("V1: {v1}");
+}
@@ -0,0 +1,58 @@
+//! Selects `a + b` or `a * b` by whether a value is zero, see the `is_zero`
+//! module:
+//!
+//! ```text
+//! cargo run --example is_zero -- 4 0 2 3 5 2 3
+//! ```
+
+use examples::{mock_prove, parse_args, SelectChip, SelectConfig};
+use halo2_proofs::{
+    circuit::{Layouter, SimpleFloorPlanner, Value},
+    halo2curves::bn256::Fr,
+    plonk::{Circuit, ConstraintSystem, Error},
+};
+
+#[derive(Clone)]
+struct IsZeroCircuit {
+    rows: Vec<[Fr; 3]>,
+}
+
+impl Circuit<Fr> for IsZeroCircuit {
+    type Config = SelectConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        self.clone()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+        SelectConfig::configure(meta)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<Fr>,
+    ) -> Result<(), Error> {
+        let rows = self
+            .rows
+            .iter()
+            .map(|row| row.map(Value::known).into())
+            .collect::<Vec<_>>();
+        SelectChip::new(config).assign(&mut layouter, &rows)?;
+        Ok(())
+    }
+}
+
+fn main() {
+    let (k, inputs) = parse_args::<u64>("is_zero", "<value> <a> <b> [<value> <a> <b>]...", 3);
+    if inputs.len() % 3 != 0 {
+        eprintln!("The rows are triples of a value, a and b");
+        std::process::exit(2);
+    }
+    let rows = inputs
+        .chunks(3)
+        .map(|row| [row[0], row[1], row[2]].map(Fr::from))
+        .collect::<Vec<_>>();
+    mock_prove(k, &IsZeroCircuit { rows }, vec![]);
+}
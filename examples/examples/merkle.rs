@@ -0,0 +1,66 @@
+//! Proves the root of a Merkle tree from a leaf and its path, see the
+//! `merkle` module:
+//!
+//! ```text
+//! cargo run --example merkle -- 4 1 2 0 3 1
+//! ```
+//!
+//! The path is a sibling per level, and whether the node is the right
+//! child, 1, or the left one, 0.
+
+use examples::{merkle_root, mock_prove, parse_args, MerkleChip, MerkleConfig};
+use halo2_proofs::{
+    circuit::{Layouter, SimpleFloorPlanner, Value},
+    halo2curves::bn256::Fr,
+    plonk::{Circuit, ConstraintSystem, Error},
+};
+
+#[derive(Clone)]
+struct MerkleCircuit {
+    leaf: Fr,
+    path: Vec<(Fr, bool)>,
+}
+
+impl Circuit<Fr> for MerkleCircuit {
+    type Config = MerkleConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        self.clone()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+        MerkleConfig::configure(meta)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<Fr>,
+    ) -> Result<(), Error> {
+        let chip = MerkleChip::new(config);
+        let path = self
+            .path
+            .iter()
+            .map(|(sibling, is_right)| (Value::known(*sibling), Value::known(*is_right)))
+            .collect::<Vec<_>>();
+        let root = chip.assign(&mut layouter, Value::known(self.leaf), &path)?;
+        chip.expose_root(&mut layouter, &root)
+    }
+}
+
+fn main() {
+    let (k, inputs) = parse_args::<u64>("merkle", "<leaf> <sibling> <0|1> [<sibling> <0|1>]...", 3);
+    if inputs.len() % 2 != 1 {
+        eprintln!("The path is pairs of a sibling and a bit");
+        std::process::exit(2);
+    }
+    let leaf = Fr::from(inputs[0]);
+    let path = inputs[1..]
+        .chunks(2)
+        .map(|level| (Fr::from(level[0]), level[1] == 1))
+        .collect::<Vec<_>>();
+    let root = merkle_root(leaf, &path);
+    println!("root = {root:?}");
+    mock_prove(k, &MerkleCircuit { leaf, path }, vec![vec![root]]);
+}
@@ -0,0 +1,50 @@
+//! Sorts bytes, the sort being a permutation of the bytes, see the
+//! `permutation` module:
+//!
+//! ```text
+//! cargo run --example permutation -- 9 3 1 2 1
+//! ```
+
+use examples::{mock_prove, parse_args, SortChip, SortConfig};
+use halo2_proofs::{
+    circuit::{Layouter, SimpleFloorPlanner},
+    halo2curves::bn256::Fr,
+    plonk::{Circuit, ConstraintSystem, Error},
+};
+
+#[derive(Clone)]
+struct SortCircuit {
+    values: Vec<u8>,
+}
+
+impl Circuit<Fr> for SortCircuit {
+    type Config = SortConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        self.clone()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+        SortConfig::configure(meta)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<Fr>,
+    ) -> Result<(), Error> {
+        let chip = SortChip::new(config);
+        chip.load_table(&mut layouter)?;
+        chip.assign(&mut layouter, &self.values)?;
+        Ok(())
+    }
+}
+
+fn main() {
+    let (k, values) = parse_args::<u8>("permutation", "<byte>...", 1);
+    let mut sorted = values.clone();
+    sorted.sort_unstable();
+    println!("{sorted:?}");
+    mock_prove(k, &SortCircuit { values }, vec![]);
+}
@@ -0,0 +1,50 @@
+//! Checks that values are bytes by a lookup into a fixed table, see the
+//! `range_check` module:
+//!
+//! ```text
+//! cargo run --example range_check -- 9 0 17 255
+//! ```
+
+use examples::{mock_prove, parse_args, RangeCheckChip, RangeCheckConfig};
+use halo2_proofs::{
+    circuit::{Layouter, SimpleFloorPlanner},
+    halo2curves::bn256::Fr,
+    plonk::{Circuit, ConstraintSystem, Error},
+};
+
+/// Bits of the values.
+const BITS: usize = 8;
+
+#[derive(Clone)]
+struct RangeCheckCircuit {
+    values: Vec<u64>,
+}
+
+impl Circuit<Fr> for RangeCheckCircuit {
+    type Config = RangeCheckConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        self.clone()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+        RangeCheckConfig::configure(meta, BITS)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<Fr>,
+    ) -> Result<(), Error> {
+        let chip = RangeCheckChip::<Fr>::new(config);
+        chip.load_table(&mut layouter)?;
+        chip.assign(&mut layouter, &self.values)?;
+        Ok(())
+    }
+}
+
+fn main() {
+    let (k, values) = parse_args("range_check", "<value>...", 1);
+    mock_prove(k, &RangeCheckCircuit { values }, vec![]);
+}
@@ -0,0 +1,54 @@
+//! Checks a private string against a public one by their random linear
+//! combinations, see the `rlc` module:
+//!
+//! ```text
+//! cargo run --example rlc -- 5 abc abc
+//! ```
+//!
+//! The public string is the private one if not given.
+
+use examples::{mock_prove, parse_args, RlcChip, RlcConfig};
+use halo2_proofs::{
+    circuit::{Layouter, SimpleFloorPlanner},
+    halo2curves::bn256::Fr,
+    plonk::{Circuit, ConstraintSystem, Error},
+};
+
+#[derive(Clone)]
+struct RlcCircuit {
+    bytes: Vec<u8>,
+}
+
+impl Circuit<Fr> for RlcCircuit {
+    type Config = RlcConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        self.clone()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+        RlcConfig::configure(meta)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<Fr>,
+    ) -> Result<(), Error> {
+        RlcChip::new(config).assign(&mut layouter, &self.bytes)?;
+        Ok(())
+    }
+}
+
+fn main() {
+    let (k, strings) = parse_args::<String>("rlc", "<private> [<public>]", 1);
+    let bytes = strings[0].as_bytes().to_vec();
+    let public = strings.get(1).unwrap_or(&strings[0]).bytes();
+    let instance = public.map(|byte| Fr::from(byte as u64)).collect();
+    if bytes.is_empty() {
+        eprintln!("The string has a first byte");
+        std::process::exit(2);
+    }
+    mock_prove(k, &RlcCircuit { bytes }, vec![instance]);
+}
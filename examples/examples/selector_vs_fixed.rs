@@ -0,0 +1,38 @@
+//! Checks products `c = a * b` with the gate enabled by a `Selector` and by
+//! a fixed column, and prints the constraint system of both, see the
+//! `selector_vs_fixed` module:
+//!
+//! ```text
+//! cargo run --example selector_vs_fixed -- 4 2 3 6 4 5 20
+//! ```
+
+use std::{marker::PhantomData, process::exit};
+
+use examples::{config_stats, mock_prove, parse_args, Flag, MulCircuit};
+use halo2_proofs::{
+    halo2curves::bn256::Fr,
+    plonk::{Column, Fixed, Selector},
+};
+
+fn prove<Q: Flag>(name: &str, k: u32, rows: &[(Fr, Fr, Fr)]) {
+    println!("{name}: {}", config_stats::<Fr, MulCircuit<Fr, Q>>());
+    let circuit = MulCircuit::<Fr, Q> {
+        rows: rows.to_vec(),
+        _marker: PhantomData,
+    };
+    mock_prove(k, &circuit, vec![]);
+}
+
+fn main() {
+    let (k, inputs) = parse_args::<u64>("selector_vs_fixed", "<a> <b> <c> [<a> <b> <c>]...", 3);
+    if inputs.len() % 3 != 0 {
+        eprintln!("The rows are triples of a, b and c");
+        exit(2);
+    }
+    let rows = inputs
+        .chunks(3)
+        .map(|row| (Fr::from(row[0]), Fr::from(row[1]), Fr::from(row[2])))
+        .collect::<Vec<_>>();
+    prove::<Selector>("Selector", k, &rows);
+    prove::<Column<Fixed>>("Column<Fixed>", k, &rows);
+}
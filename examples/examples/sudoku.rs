@@ -0,0 +1,72 @@
+//! Proves the solution of a public Sudoku puzzle, see the `sudoku` module:
+//!
+//! ```text
+//! cargo run --example sudoku -- 7 \
+//!     530070000600195000098000060800060003400803001700020006060000280000419005000080079 \
+//!     534678912672195348198342567859761423426853791713924856961537284287419635345286179
+//! ```
+//!
+//! The grids are their 81 digits, row by row, the blanks of the puzzle 0.
+
+use examples::{mock_prove, parse_args, puzzle_instance, solves, Grid, SudokuChip, SudokuConfig};
+use halo2_proofs::{
+    circuit::{Layouter, SimpleFloorPlanner},
+    halo2curves::bn256::Fr,
+    plonk::{Circuit, ConstraintSystem, Error},
+};
+
+#[derive(Clone)]
+struct SudokuCircuit {
+    solution: Grid,
+}
+
+impl Circuit<Fr> for SudokuCircuit {
+    type Config = SudokuConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        self.clone()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+        SudokuConfig::configure(meta)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<Fr>,
+    ) -> Result<(), Error> {
+        let chip = SudokuChip::new(config);
+        chip.load_table(&mut layouter)?;
+        chip.assign(&mut layouter, &self.solution)?;
+        Ok(())
+    }
+}
+
+/// The grid of 81 digits, row by row.
+fn grid(digits: &str) -> Option<Grid> {
+    let digits = digits
+        .chars()
+        .map(|digit| digit.to_digit(10).map(|digit| digit as u8))
+        .collect::<Option<Vec<_>>>()?;
+    let mut grid = [[0; 9]; 9];
+    for (row, digits) in grid.iter_mut().zip(digits.chunks(9)) {
+        *row = digits.try_into().ok()?;
+    }
+    (digits.len() == 81).then_some(grid)
+}
+
+fn main() {
+    let (k, grids) = parse_args::<String>("sudoku", "<puzzle> <solution>", 2);
+    let (Some(puzzle), Some(solution)) = (grid(&grids[0]), grid(&grids[1])) else {
+        eprintln!("The grids are of 81 digits");
+        std::process::exit(2);
+    };
+    println!("The solution is a solution: {}", solves(&puzzle, &solution));
+    mock_prove(
+        k,
+        &SudokuCircuit { solution },
+        vec![puzzle_instance(&puzzle)],
+    );
+}
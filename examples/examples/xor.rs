@@ -0,0 +1,50 @@
+//! XORs two 32-bit words byte by byte, each byte looked up in the table of
+//! the XOR of the bytes, see the `xor` module:
+//!
+//! ```text
+//! cargo run --example xor -- 17 19088743 2309737967
+//! ```
+
+use examples::{mock_prove, parse_args, XorChip, XorConfig};
+use halo2_proofs::{
+    circuit::{Layouter, SimpleFloorPlanner},
+    halo2curves::bn256::Fr,
+    plonk::{Circuit, ConstraintSystem, Error},
+};
+
+#[derive(Clone)]
+struct XorCircuit {
+    a: u32,
+    b: u32,
+}
+
+impl Circuit<Fr> for XorCircuit {
+    type Config = XorConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        self.clone()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+        XorConfig::configure(meta)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<Fr>,
+    ) -> Result<(), Error> {
+        let chip = XorChip::new(config);
+        chip.load_table(&mut layouter)?;
+        chip.assign(&mut layouter, self.a as u64, self.b as u64, 4)?;
+        Ok(())
+    }
+}
+
+fn main() {
+    let (k, words) = parse_args::<u32>("xor", "<a> <b>", 2);
+    let (a, b) = (words[0], words[1]);
+    println!("{a:#010x} ^ {b:#010x} = {:#010x}", a ^ b);
+    mock_prove(k, &XorCircuit { a, b }, vec![]);
+}
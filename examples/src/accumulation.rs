@@ -0,0 +1,369 @@
+//! The verification of many polynomial openings inside a circuit, folded
+//! into a single check by an accumulator: the idea of the aggregation of
+//! proofs, whose verifier circuit checks the openings of the proofs it
+//! aggregates, and defers their pairings to a single one, out of it.
+//!
+//! A KZG commitment to a polynomial `p` is `C = [p(τ)]`, for a secret `τ`
+//! of the parameters, and the proof that `p(z) = y` is `π = [q(τ)]`, with
+//! `q = (p - y) / (X - z)`, which holds if the pairings
+//! `e(C - [y] + z * π, [1]) = e(π, [τ])`. The points of a real verifier are
+//! in a field other than that of the circuit, and the example leaves them
+//! out: its toy commitments are the scalars `p(τ)` themselves, which reveal
+//! their discrete logarithms, and its pairing check is
+//! `C - y + z * π = τ * π`, with `τ` a constant of the gates. Anyone knowing
+//! `τ` forges proofs, so the toy is not sound, but the accumulation is that
+//! of the real scheme.
+//!
+//! The claims `(C_i, z_i, y_i)` are public, and their proofs private. The
+//! circuit folds the two sides of every check with a challenge `r`, drawn
+//! once the proofs are committed to, as RLCs, see `rlc`:
+//! `lhs = sum_i r^(n-1-i) * (C_i - y_i + z_i * π_i)` and
+//! `rhs = sum_i r^(n-1-i) * π_i`, and checks `lhs = τ * rhs` once. A wrong
+//! proof makes the difference of the two sides a non-zero polynomial in
+//! `r`, which is zero at a random `r` with a negligible probability only. A
+//! real verifier circuit exposes `(lhs, rhs)` instead, as points, for the
+//! final pairing `e(lhs, [1]) = e(rhs, [τ])`, the only one of all the
+//! proofs.
+//!
+//! ```text
+//! | row   | commitment | point   | value   | proof   | lhs     | rhs     | q_first | q_next | q_last |
+//! | ----- | ---------- | ------- | ------- | ------- | ------- | ------- | ------- | ------ | ------ |
+//! | 0     | C_0        | z_0     | y_0     | π_0     | lhs_0   | rhs_0   | 1       | 0      | 0      |
+//! | 1     | C_1        | z_1     | y_1     | π_1     | lhs_1   | rhs_1   | 0       | 1      | 0      |
+//! | ...   |            |         |         |         |         |         |         |        |        |
+//! | n - 1 | C_{n-1}    | z_{n-1} | y_{n-1} | π_{n-1} | lhs     | rhs     | 0       | 1      | 1      |
+//! ```
+//!
+//! The claims are copied from the instance column, three rows per claim.
+
+use std::marker::PhantomData;
+
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{Layouter, Value},
+    plonk::{
+        Advice, Challenge, Column, ConstraintSystem, Error, Expression, FirstPhase, Instance,
+        SecondPhase, Selector, VirtualCells,
+    },
+    poly::Rotation,
+};
+
+/// The claim of an opening `p(point) = value` of a toy commitment, and its
+/// proof.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Claim<F> {
+    pub commitment: F,
+    pub point: F,
+    pub value: F,
+    pub proof: F,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct AccumulationConfig {
+    q_first: Selector, // enabled at the first claim.
+    q_next: Selector,  // enabled at every other claim.
+    q_last: Selector,  // enabled at the last claim.
+    commitment: Column<Advice>,
+    point: Column<Advice>,
+    value: Column<Advice>,
+    proof: Column<Advice>,
+    lhs: Column<Advice>,
+    rhs: Column<Advice>,
+    instance: Column<Instance>, // the commitment, the point and the value of every claim.
+    challenge: Challenge,
+}
+
+pub struct AccumulationChip<F> {
+    config: AccumulationConfig,
+    _marker: PhantomData<F>,
+}
+
+/// The value at `x` of the polynomial of `coeffs`, lowest degree first.
+fn eval<F: FieldExt>(coeffs: &[F], x: F) -> F {
+    coeffs
+        .iter()
+        .rev()
+        .fold(F::zero(), |acc, coeff| acc * x + coeff)
+}
+
+/// The toy commitment to the polynomial of `coeffs`, lowest degree first.
+pub fn toy_commit<F: FieldExt>(tau: F, coeffs: &[F]) -> F {
+    eval(coeffs, tau)
+}
+
+/// The claim of the opening at `point` of the polynomial of `coeffs`, with
+/// its proof, the quotient of the polynomial by `X - point`.
+pub fn toy_open<F: FieldExt>(tau: F, coeffs: &[F], point: F) -> Claim<F> {
+    // The quotient, highest degree first, by synthetic division.
+    let mut quotient = vec![];
+    let mut carry = F::zero();
+    for coeff in coeffs.iter().skip(1).rev() {
+        carry = *coeff + point * carry;
+        quotient.push(carry);
+    }
+    quotient.reverse();
+    Claim {
+        commitment: toy_commit(tau, coeffs),
+        point,
+        value: eval(coeffs, point),
+        proof: eval(&quotient, tau),
+    }
+}
+
+/// Whether the proof of `claim` holds, out-of-circuit.
+pub fn toy_verify<F: FieldExt>(tau: F, claim: &Claim<F>) -> bool {
+    claim.commitment - claim.value + claim.point * claim.proof == tau * claim.proof
+}
+
+/// The accumulator `(lhs, rhs)` of `claims` with the challenge `r`.
+pub fn accumulate<F: FieldExt>(claims: &[Claim<F>], r: F) -> (F, F) {
+    claims
+        .iter()
+        .fold((F::zero(), F::zero()), |(lhs, rhs), claim| {
+            (
+                lhs * r + claim.commitment - claim.value + claim.point * claim.proof,
+                rhs * r + claim.proof,
+            )
+        })
+}
+
+/// The instance column of `claims`, three rows per claim.
+pub fn claim_instances<F: FieldExt>(claims: &[Claim<F>]) -> Vec<F> {
+    claims
+        .iter()
+        .flat_map(|claim| [claim.commitment, claim.point, claim.value])
+        .collect()
+}
+
+impl AccumulationConfig {
+    /// Configures the accumulation of the claims of the toy commitments of
+    /// the secret `tau`.
+    pub fn configure<F: FieldExt>(meta: &mut ConstraintSystem<F>, tau: F) -> Self {
+        let q_first = meta.selector();
+        let q_next = meta.selector();
+        let q_last = meta.selector();
+        let [commitment, point, value, proof] = [0; 4].map(|_| meta.advice_column_in(FirstPhase));
+        let challenge = meta.challenge_usable_after(FirstPhase);
+        let lhs = meta.advice_column_in(SecondPhase);
+        let rhs = meta.advice_column_in(SecondPhase);
+        let instance = meta.instance_column();
+        for column in [commitment, point, value] {
+            meta.enable_equality(column);
+        }
+        meta.enable_equality(instance);
+
+        // The sides of the check of the claim of a row.
+        let sides = |meta: &mut VirtualCells<'_, F>| {
+            let commitment = meta.query_advice(commitment, Rotation::cur());
+            let point = meta.query_advice(point, Rotation::cur());
+            let value = meta.query_advice(value, Rotation::cur());
+            let proof = meta.query_advice(proof, Rotation::cur());
+            (commitment - value + point * proof.clone(), proof)
+        };
+        meta.create_gate("lhs = C - y + z * proof, rhs = proof", |meta| {
+            let q_first = meta.query_selector(q_first);
+            let (claim_lhs, claim_rhs) = sides(meta);
+            let lhs = meta.query_advice(lhs, Rotation::cur());
+            let rhs = meta.query_advice(rhs, Rotation::cur());
+            vec![
+                q_first.clone() * (lhs - claim_lhs),
+                q_first * (rhs - claim_rhs),
+            ]
+        });
+        meta.create_gate(
+            "lhs = lhs_prev * r + C - y + z * proof, rhs = rhs_prev * r + proof",
+            |meta| {
+                let q_next = meta.query_selector(q_next);
+                let r = meta.query_challenge(challenge);
+                let (claim_lhs, claim_rhs) = sides(meta);
+                let lhs_prev = meta.query_advice(lhs, Rotation::prev());
+                let rhs_prev = meta.query_advice(rhs, Rotation::prev());
+                let lhs = meta.query_advice(lhs, Rotation::cur());
+                let rhs = meta.query_advice(rhs, Rotation::cur());
+                vec![
+                    q_next.clone() * (lhs - (lhs_prev * r.clone() + claim_lhs)),
+                    q_next * (rhs - (rhs_prev * r + claim_rhs)),
+                ]
+            },
+        );
+        meta.create_gate("lhs = tau * rhs", |meta| {
+            let q_last = meta.query_selector(q_last);
+            let lhs = meta.query_advice(lhs, Rotation::cur());
+            let rhs = meta.query_advice(rhs, Rotation::cur());
+            vec![q_last * (lhs - Expression::Constant(tau) * rhs)]
+        });
+
+        Self {
+            q_first,
+            q_next,
+            q_last,
+            commitment,
+            point,
+            value,
+            proof,
+            lhs,
+            rhs,
+            instance,
+            challenge,
+        }
+    }
+}
+
+impl<F: FieldExt> AccumulationChip<F> {
+    pub fn new(config: AccumulationConfig) -> Self {
+        Self {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Assigns the claims of the instance column with their private
+    /// `proofs`, a claim per proof, and checks their accumulator.
+    pub fn assign(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        proofs: &[Value<F>],
+    ) -> Result<(), Error> {
+        assert!(!proofs.is_empty(), "the accumulator has a first claim");
+        let config = self.config;
+        let r = layouter.get_challenge(config.challenge);
+        layouter.assign_region(
+            || "accumulation",
+            |mut region| {
+                let mut lhs = Value::known(F::zero());
+                let mut rhs = Value::known(F::zero());
+                for (row, proof) in proofs.iter().enumerate() {
+                    if row == 0 {
+                        config.q_first.enable(&mut region, row)?;
+                    } else {
+                        config.q_next.enable(&mut region, row)?;
+                    }
+                    if row == proofs.len() - 1 {
+                        config.q_last.enable(&mut region, row)?;
+                    }
+                    let mut copy = |name, column, i| {
+                        region
+                            .assign_advice_from_instance(
+                                || name,
+                                config.instance,
+                                3 * row + i,
+                                column,
+                                row,
+                            )
+                            .map(|cell| cell.value().copied())
+                    };
+                    let commitment = copy("commitment", config.commitment, 0)?;
+                    let point = copy("point", config.point, 1)?;
+                    let value = copy("value", config.value, 2)?;
+                    region.assign_advice(|| "proof", config.proof, row, || *proof)?;
+
+                    lhs = lhs * r + commitment - value + point * *proof;
+                    rhs = rhs * r + *proof;
+                    region.assign_advice(|| "lhs", config.lhs, row, || lhs)?;
+                    region.assign_advice(|| "rhs", config.rhs, row, || rhs)?;
+                }
+                Ok(())
+            },
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use halo2_proofs::{
+        arithmetic::Field, circuit::SimpleFloorPlanner, dev::MockProver, halo2curves::bn256::Fr,
+        plonk::Circuit,
+    };
+
+    fn tau() -> Fr {
+        Fr::from(0x1234_5678)
+    }
+
+    struct TestCircuit {
+        proofs: Vec<Fr>,
+    }
+
+    impl Circuit<Fr> for TestCircuit {
+        type Config = AccumulationConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self {
+                proofs: vec![Fr::zero(); self.proofs.len()],
+            }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+            AccumulationConfig::configure(meta, tau())
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<Fr>,
+        ) -> Result<(), Error> {
+            let proofs = self
+                .proofs
+                .iter()
+                .copied()
+                .map(Value::known)
+                .collect::<Vec<_>>();
+            AccumulationChip::new(config).assign(&mut layouter, &proofs)
+        }
+    }
+
+    /// The claims of three polynomials, at three points.
+    fn claims() -> Vec<Claim<Fr>> {
+        [(vec![1, 2, 3], 5), (vec![7], 0), (vec![0, 0, 0, 1, 9], 11)]
+            .into_iter()
+            .map(|(coeffs, point)| {
+                let coeffs = coeffs.into_iter().map(Fr::from).collect::<Vec<_>>();
+                toy_open(tau(), &coeffs, Fr::from(point))
+            })
+            .collect()
+    }
+
+    fn verify(claims: &[Claim<Fr>]) -> bool {
+        let circuit = TestCircuit {
+            proofs: claims.iter().map(|claim| claim.proof).collect(),
+        };
+        MockProver::run(4, &circuit, vec![claim_instances(claims)])
+            .unwrap()
+            .verify()
+            .is_ok()
+    }
+
+    #[test]
+    fn test_toy_kzg() {
+        // `p = 1 + 2X + 3X^2` at 5, and its quotient `17 + 3X`.
+        let claim = claims()[0];
+        assert_eq!(claim.value, Fr::from(86));
+        assert_eq!(claim.proof, Fr::from(17) + Fr::from(3) * tau());
+        assert!(claims().iter().all(|claim| toy_verify(tau(), claim)));
+
+        // the check of the accumulator holds for valid claims only.
+        let r = Fr::from(1000);
+        let (lhs, rhs) = accumulate(&claims(), r);
+        assert_eq!(lhs, tau() * rhs);
+        let mut wrong = claims();
+        wrong[1].value += Fr::one();
+        assert!(!toy_verify(tau(), &wrong[1]));
+        let (lhs, rhs) = accumulate(&wrong, r);
+        assert_ne!(lhs, tau() * rhs);
+    }
+
+    #[test]
+    fn test_accumulation() {
+        assert!(verify(&claims()));
+        assert!(verify(&claims()[..1]));
+
+        // a wrong value, and a wrong proof.
+        let mut wrong = claims();
+        wrong[2].value += Fr::one();
+        assert!(!verify(&wrong));
+        let mut wrong = claims();
+        wrong[0].proof += Fr::one();
+        assert!(!verify(&wrong));
+    }
+}
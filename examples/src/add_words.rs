@@ -0,0 +1,377 @@
+//! The sum of `N` words of 256 bits, each as two halves of 128 bits, the
+//! `AddWordsGadget` of the zkevm-circuits, and the layout the workspace
+//! packs its digests in, see `WordLoHi` in the `gadgets` crate. A word does
+//! not fit in a field element, but its halves do, with room for the sum of
+//! a few of them.
+//!
+//! The halves are added with a carry from `lo` into `hi`, and a carry out
+//! of `hi`, the overflow:
+//!
+//! ```text
+//! sum(addends.lo)            = sum.lo + carry_lo * 2^128
+//! sum(addends.hi) + carry_lo = sum.hi + carry_hi * 2^128
+//! ```
+//!
+//! The halves of the addends and of the sum are range checked to 128 bits,
+//! a byte decomposition each, see `decompose`, and the carries to `[0, N)`
+//! by `prod_{i < N} (carry - i) = 0`, a bit for two addends. Both sides are
+//! then below `N * 2^128`, far below the modulus, so that the equations hold
+//! over the integers, and `carry_hi` is the number of times the sum wraps
+//! around `2^256`: for two addends, the overflow flag. The carries are not
+//! redundant with the range checks of the halves: a wrong `sum.lo` is
+//! absorbed in the field by a `carry_lo` of `(sum(addends.lo) - sum.lo) /
+//! 2^128`, a value out of range, and `sum.hi` by as wrong a `carry_hi`.
+//!
+//! With `check_overflow`, `carry_hi = 0` is constrained too, for the sums
+//! which must not wrap around, e.g. an offset plus a length, as the
+//! `CHECK_OVERFLOW` of the gadget.
+//!
+//! ```text
+//! | row | a_0.lo a_0.hi .. | sum.lo sum.hi | carry_lo | carry_hi | q_add |
+//! | --- | ---------------- | ------------- | -------- | -------- | ----- |
+//! | 0   | addends          | sum           | 0 or 1   | overflow | 1     |
+//! ```
+
+use std::marker::PhantomData;
+
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{AssignedCell, Layouter, Value},
+    plonk::{Advice, Column, ConstraintSystem, Error, Expression, Selector},
+    poly::Rotation,
+};
+
+use crate::decompose::{DecomposeChip, DecomposeConfig};
+
+/// Bits of a half of a word.
+pub const HALF_BITS: usize = 128;
+
+#[derive(Clone, Copy, Debug)]
+pub struct AddWordsConfig<const N: usize> {
+    q_add: Selector,
+    addends: [[Column<Advice>; 2]; N], // lo and hi of every addend.
+    sum: [Column<Advice>; 2],
+    carry_lo: Column<Advice>,
+    carry_hi: Column<Advice>, // the overflow.
+    bytes: DecomposeConfig,
+}
+
+pub struct AddWordsChip<F, const N: usize> {
+    config: AddWordsConfig<N>,
+    _marker: PhantomData<F>,
+}
+
+/// The cells of a sum of words.
+pub struct WordSum<F: FieldExt> {
+    pub sum: [AssignedCell<F, F>; 2], // lo and hi.
+    pub overflow: AssignedCell<F, F>, // `carry_hi`.
+}
+
+/// `2^128`, the radix of the halves.
+fn radix<F: FieldExt>() -> F {
+    F::from_u128(u128::MAX) + F::one()
+}
+
+/// The sum of the `addends`, each its `[lo, hi]`, modulo `2^256`, and the
+/// carries `[carry_lo, carry_hi]`, out-of-circuit.
+fn sum_with_carries(addends: &[[u128; 2]]) -> ([u128; 2], [u128; 2]) {
+    let mut sum = [0u128; 2];
+    let mut carries = [0u128; 2];
+    for half in 0..2 {
+        if half == 1 {
+            sum[1] = carries[0];
+        }
+        for addend in addends {
+            let (half_sum, carry) = sum[half].overflowing_add(addend[half]);
+            sum[half] = half_sum;
+            carries[half] += carry as u128;
+        }
+    }
+    (sum, carries)
+}
+
+/// The sum of the `addends`, each its `[lo, hi]`, modulo `2^256`, and the
+/// number of times it wraps around, out-of-circuit.
+pub fn add_words(addends: &[[u128; 2]]) -> ([u128; 2], u128) {
+    let (sum, [_, overflow]) = sum_with_carries(addends);
+    (sum, overflow)
+}
+
+impl<const N: usize> AddWordsConfig<N> {
+    pub fn configure<F: FieldExt>(meta: &mut ConstraintSystem<F>, check_overflow: bool) -> Self {
+        assert!(N > 0, "the sum has a first addend");
+        let q_add = meta.selector();
+        let mut advice_column = || {
+            let column = meta.advice_column();
+            meta.enable_equality(column);
+            column
+        };
+        let addends = [0; N].map(|_| [0; 2].map(|_| advice_column()));
+        let sum = [0; 2].map(|_| advice_column());
+        let carry_hi = advice_column();
+        let carry_lo = meta.advice_column();
+        let bytes = DecomposeConfig::configure_bytes(meta);
+
+        meta.create_gate("sum + carry_hi * 2^128 = sum(addends) + carry_lo", |meta| {
+            let q_add = meta.query_selector(q_add);
+            let addends =
+                addends.map(|addend| addend.map(|half| meta.query_advice(half, Rotation::cur())));
+            let [sum_lo, sum_hi] = sum.map(|half| meta.query_advice(half, Rotation::cur()));
+            let carry_lo = meta.query_advice(carry_lo, Rotation::cur());
+            let carry_hi = meta.query_advice(carry_hi, Rotation::cur());
+            let radix = Expression::Constant(radix::<F>());
+            let [addends_lo, addends_hi] = [0, 1].map(|half| {
+                addends
+                    .iter()
+                    .fold(Expression::Constant(F::zero()), |acc, addend| {
+                        acc + addend[half].clone()
+                    })
+            });
+            vec![
+                q_add.clone() * (sum_lo + carry_lo.clone() * radix.clone() - addends_lo),
+                q_add * (sum_hi + carry_hi * radix - addends_hi - carry_lo),
+            ]
+        });
+
+        meta.create_gate("carries are in [0, N)", |meta| {
+            let q_add = meta.query_selector(q_add);
+            [carry_lo, carry_hi]
+                .map(|carry| {
+                    let carry = meta.query_advice(carry, Rotation::cur());
+                    (0..N).fold(q_add.clone(), |acc, i| {
+                        acc * (carry.clone() - Expression::Constant(F::from(i as u64)))
+                    })
+                })
+                .to_vec()
+        });
+
+        if check_overflow {
+            meta.create_gate("carry_hi = 0", |meta| {
+                let q_add = meta.query_selector(q_add);
+                vec![q_add * meta.query_advice(carry_hi, Rotation::cur())]
+            });
+        }
+
+        Self {
+            q_add,
+            addends,
+            sum,
+            carry_lo,
+            carry_hi,
+            bytes,
+        }
+    }
+}
+
+impl<F: FieldExt, const N: usize> AddWordsChip<F, N> {
+    pub fn new(config: AddWordsConfig<N>) -> Self {
+        Self {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Loads the table of the bytes, once per circuit.
+    pub fn load_table(&self, layouter: &mut impl Layouter<F>) -> Result<(), Error> {
+        DecomposeChip::new(self.config.bytes).load_table(layouter)
+    }
+
+    /// Assigns the sum of the `addends`, each its `[lo, hi]`, and returns
+    /// the cells of the sum and of the overflow.
+    pub fn assign(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        addends: [[u128; 2]; N],
+    ) -> Result<WordSum<F>, Error> {
+        let (sum, carries) = sum_with_carries(&addends);
+        self.assign_rows(layouter, &addends, sum, carries.map(F::from_u128))
+    }
+
+    /// Range checks the halves of the addends and of the sum given, assigns
+    /// the row of the sum with the carries given, and returns its cells.
+    fn assign_rows(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        addends: &[[u128; 2]; N],
+        sum: [u128; 2],
+        [carry_lo, carry_hi]: [F; 2],
+    ) -> Result<WordSum<F>, Error> {
+        let config = self.config;
+        let decompose = DecomposeChip::new(config.bytes);
+        let mut range_check = |half: u128| {
+            let bytes = half.to_be_bytes().map(|byte| F::from(byte as u64));
+            Ok::<_, Error>(decompose.assign_limbs(layouter, &bytes)?.value)
+        };
+        let mut halves = vec![];
+        for (addend, columns) in addends.iter().zip(config.addends.iter()) {
+            for (half, column) in addend.iter().zip(columns) {
+                halves.push((range_check(*half)?, *column));
+            }
+        }
+        let sum = [range_check(sum[0])?, range_check(sum[1])?];
+
+        layouter.assign_region(
+            || "add words",
+            |mut region| {
+                config.q_add.enable(&mut region, 0)?;
+                for (cell, column) in halves.iter() {
+                    cell.copy_advice(|| "addend", &mut region, *column, 0)?;
+                }
+                for (cell, column) in sum.iter().zip(config.sum) {
+                    cell.copy_advice(|| "sum", &mut region, column, 0)?;
+                }
+                region.assign_advice(
+                    || "carry_lo",
+                    config.carry_lo,
+                    0,
+                    || Value::known(carry_lo),
+                )?;
+                let overflow = region.assign_advice(
+                    || "carry_hi",
+                    config.carry_hi,
+                    0,
+                    || Value::known(carry_hi),
+                )?;
+                Ok(WordSum {
+                    sum: sum.clone(),
+                    overflow,
+                })
+            },
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use halo2_proofs::{
+        arithmetic::Field,
+        circuit::SimpleFloorPlanner,
+        dev::{MockProver, VerifyFailure},
+        halo2curves::bn256::Fr,
+        plonk::{Circuit, Instance},
+    };
+
+    /// Adds two words with the sum and the carries given, and exposes the
+    /// halves of the sum and the overflow as the instances.
+    #[derive(Default)]
+    struct TestCircuit<F, const CHECK_OVERFLOW: bool> {
+        addends: [[u128; 2]; 2],
+        sum: [u128; 2],
+        carries: [F; 2],
+    }
+
+    impl<F: FieldExt, const CHECK_OVERFLOW: bool> Circuit<F> for TestCircuit<F, CHECK_OVERFLOW> {
+        type Config = (AddWordsConfig<2>, Column<Instance>);
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            let config = AddWordsConfig::configure(meta, CHECK_OVERFLOW);
+            let instance = meta.instance_column();
+            meta.enable_equality(instance);
+            (config, instance)
+        }
+
+        fn synthesize(
+            &self,
+            (config, instance): Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            let chip = AddWordsChip::new(config);
+            chip.load_table(&mut layouter)?;
+            let sum = chip.assign_rows(&mut layouter, &self.addends, self.sum, self.carries)?;
+            for (row, cell) in sum.sum.iter().chain([&sum.overflow]).enumerate() {
+                layouter.constrain_instance(cell.cell(), instance, row)?;
+            }
+            Ok(())
+        }
+    }
+
+    fn circuit<const CHECK_OVERFLOW: bool>(
+        addends: [[u128; 2]; 2],
+    ) -> TestCircuit<Fr, CHECK_OVERFLOW> {
+        let (sum, carries) = sum_with_carries(&addends);
+        TestCircuit {
+            addends,
+            sum,
+            carries: carries.map(Fr::from_u128),
+        }
+    }
+
+    fn verify<const CHECK_OVERFLOW: bool>(
+        circuit: &TestCircuit<Fr, CHECK_OVERFLOW>,
+    ) -> Result<(), Vec<VerifyFailure>> {
+        let instance = vec![
+            Fr::from_u128(circuit.sum[0]),
+            Fr::from_u128(circuit.sum[1]),
+            circuit.carries[1],
+        ];
+        MockProver::run(9, circuit, vec![instance])
+            .unwrap()
+            .verify()
+    }
+
+    const MAX: u128 = u128::MAX;
+
+    #[test]
+    fn test_add_words() {
+        assert_eq!(add_words(&[[1, 2], [3, 4]]), ([4, 6], 0));
+        // the carry from `lo` into `hi`, and out of `hi`.
+        assert_eq!(add_words(&[[MAX, 0], [1, 0]]), ([0, 1], 0));
+        assert_eq!(add_words(&[[MAX, MAX], [1, 0]]), ([0, 0], 1));
+        assert_eq!(add_words(&[[MAX, MAX]; 3]), ([MAX - 2, MAX], 2));
+    }
+
+    #[test]
+    fn test_add_words_circuit() {
+        for addends in [
+            [[1, 2], [3, 4]],
+            [[MAX, 0], [1, 0]],
+            [[MAX, MAX], [MAX, MAX]],
+        ] {
+            assert_eq!(verify(&circuit::<false>(addends)), Ok(()));
+        }
+
+        // the overflow, with the flag cleared.
+        let mut wrong = circuit::<false>([[MAX, MAX], [1, 0]]);
+        wrong.carries[1] = Fr::zero();
+        assert!(verify(&wrong).is_err());
+
+        // the overflow, which the checked sum rejects.
+        assert_eq!(verify(&circuit::<true>([[MAX, 0], [1, 0]])), Ok(()));
+        let failures = verify(&circuit::<true>([[MAX, MAX], [1, 0]])).unwrap_err();
+        assert!(
+            failures
+                .iter()
+                .all(|failure| failure.to_string().contains("carry_hi = 0")),
+            "{failures:?}"
+        );
+    }
+
+    #[test]
+    fn test_carry_out_of_range() {
+        // `sum.lo + 1`, in range, and the carries which satisfy the
+        // equations in the field, so that only the range of the carries
+        // catches them.
+        let radix_inv = radix::<Fr>().invert().unwrap();
+        let addends = [[5, 7], [6, 8]];
+        let mut forged = circuit::<false>(addends);
+        forged.sum[0] += 1;
+        let carry_lo = -Fr::one() * radix_inv;
+        forged.carries = [carry_lo, carry_lo * radix_inv];
+        let failures = verify(&forged).unwrap_err();
+        assert!(
+            failures.iter().all(|failure| matches!(
+                failure,
+                VerifyFailure::ConstraintNotSatisfied { .. }
+            ) && failure
+                .to_string()
+                .contains("carries are in [0, N)")),
+            "{failures:?}"
+        );
+    }
+}
@@ -0,0 +1,212 @@
+//! The artifacts of a proof written to files and read back, as a prover
+//! loads its parameters and its proving key rather than generating them
+//! for every proof, and a verifier its parameters and its verifying key:
+//! the parameters of the KZG commitment scheme, the keys of the circuit,
+//! and the bytes of the proof.
+//!
+//! The parameters and the keys are written in a `SerdeFormat`:
+//! - `Processed`: the points compressed, and decompressed when read, the
+//!   smallest files;
+//! - `RawBytes`: the coordinates of the points, checked on the curve when
+//!   read;
+//! - `RawBytesUnchecked`: the coordinates, not checked, the fastest to read,
+//!   and only fit for files of a trusted origin.
+//!
+//! The keys are read back for the circuit they were generated for: they
+//! hold the commitments to its fixed columns and to its permutation, but
+//! not its constraint system, which is configured again from the circuit.
+//! The proof is the bytes of its transcript, which need no format.
+
+use std::{
+    fs::{self, File},
+    io::{self, BufReader, BufWriter, Write},
+    path::Path,
+};
+
+use halo2_proofs::{
+    halo2curves::bn256::{Bn256, Fr, G1Affine},
+    plonk::{create_proof, keygen_pk, keygen_vk, verify_proof, Circuit, ProvingKey, VerifyingKey},
+    poly::{
+        commitment::ParamsProver,
+        kzg::{
+            commitment::{KZGCommitmentScheme, ParamsKZG},
+            multiopen::{ProverSHPLONK, VerifierSHPLONK},
+            strategy::SingleStrategy,
+        },
+    },
+    transcript::{
+        Blake2bRead, Blake2bWrite, Challenge255, TranscriptReadBuffer, TranscriptWriterBuffer,
+    },
+    SerdeFormat,
+};
+use rand::rngs::OsRng;
+
+/// Files of the artifacts in their directory.
+pub const PARAMS_FILE: &str = "params";
+pub const VK_FILE: &str = "vk";
+pub const PK_FILE: &str = "pk";
+pub const PROOF_FILE: &str = "proof";
+
+/// The artifacts of a proof of a circuit.
+pub struct Artifacts {
+    pub params: ParamsKZG<Bn256>,
+    pub vk: VerifyingKey<G1Affine>,
+    pub pk: ProvingKey<G1Affine>,
+    pub proof: Vec<u8>,
+}
+
+impl Artifacts {
+    /// Sets up the parameters of degree `k`, generates the keys of `circuit`
+    /// and proves it with `instances`. The parameters are set up from a
+    /// local secret, so that anyone knowing it could forge proofs: a
+    /// deployment loads those of a ceremony instead, see the `convert_srs`
+    /// binary of the `benchmarking` crate.
+    pub fn prove<C: Circuit<Fr>>(k: u32, circuit: C, instances: &[Vec<Fr>]) -> Self {
+        let params = ParamsKZG::<Bn256>::setup(k, OsRng);
+        let vk = keygen_vk(&params, &circuit).expect("keygen_vk should not fail");
+        let pk = keygen_pk(&params, vk.clone(), &circuit).expect("keygen_pk should not fail");
+
+        let instances = instances.iter().map(Vec::as_slice).collect::<Vec<_>>();
+        let mut transcript = Blake2bWrite::<_, G1Affine, Challenge255<_>>::init(vec![]);
+        create_proof::<KZGCommitmentScheme<Bn256>, ProverSHPLONK<_>, _, _, _, _>(
+            &params,
+            &pk,
+            &[circuit],
+            &[&instances],
+            OsRng,
+            &mut transcript,
+        )
+        .expect("proof generation should not fail");
+
+        Self {
+            params,
+            vk,
+            pk,
+            proof: transcript.finalize(),
+        }
+    }
+
+    /// Writes the artifacts to `dir`, the parameters and the keys in
+    /// `format`.
+    pub fn write(&self, dir: &Path, format: SerdeFormat) -> io::Result<()> {
+        fs::create_dir_all(dir)?;
+        let create = |name| File::create(dir.join(name)).map(BufWriter::new);
+        let mut params = create(PARAMS_FILE)?;
+        self.params.write_custom(&mut params, format)?;
+        params.flush()?;
+        let mut vk = create(VK_FILE)?;
+        self.vk.write(&mut vk, format)?;
+        vk.flush()?;
+        let mut pk = create(PK_FILE)?;
+        self.pk.write(&mut pk, format)?;
+        pk.flush()?;
+        fs::write(dir.join(PROOF_FILE), &self.proof)
+    }
+
+    /// Reads the artifacts of a proof of the circuit `C` from `dir`, the
+    /// parameters and the keys in `format`.
+    pub fn read<C: Circuit<Fr>>(dir: &Path, format: SerdeFormat) -> io::Result<Self> {
+        let open = |name| File::open(dir.join(name)).map(BufReader::new);
+        Ok(Self {
+            params: ParamsKZG::read_custom(&mut open(PARAMS_FILE)?, format)?,
+            vk: VerifyingKey::read::<_, C>(&mut open(VK_FILE)?, format)?,
+            pk: ProvingKey::read::<_, C>(&mut open(PK_FILE)?, format)?,
+            proof: fs::read(dir.join(PROOF_FILE))?,
+        })
+    }
+
+    /// Whether the proof is valid for `instances`, verified with the
+    /// parameters and the verifying key only.
+    pub fn verify(&self, instances: &[Vec<Fr>]) -> bool {
+        let instances = instances.iter().map(Vec::as_slice).collect::<Vec<_>>();
+        let mut transcript =
+            Blake2bRead::<_, G1Affine, Challenge255<_>>::init(self.proof.as_slice());
+        verify_proof::<KZGCommitmentScheme<Bn256>, VerifierSHPLONK<_>, _, _, _>(
+            self.params.verifier_params(),
+            &self.vk,
+            SingleStrategy::new(&self.params),
+            &[&instances],
+            &mut transcript,
+        )
+        .is_ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{fibonacci, FibonacciChip, FibonacciConfig};
+    use halo2_proofs::{
+        arithmetic::Field,
+        circuit::{Layouter, SimpleFloorPlanner},
+        plonk::{ConstraintSystem, Error},
+    };
+
+    /// The Fibonacci sequence up to its `n`-th number.
+    #[derive(Clone, Default)]
+    struct FibonacciCircuit {
+        n: usize,
+    }
+
+    impl Circuit<Fr> for FibonacciCircuit {
+        type Config = FibonacciConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            self.clone()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+            FibonacciConfig::configure(meta)
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<Fr>,
+        ) -> Result<(), Error> {
+            let chip = FibonacciChip::new(config);
+            let output = chip.assign(&mut layouter, self.n)?;
+            chip.expose_output(&mut layouter, &output)
+        }
+    }
+
+    #[test]
+    fn test_artifacts() {
+        let n = 10;
+        let instances = vec![vec![
+            Fr::one(),
+            Fr::one(),
+            fibonacci(Fr::one(), Fr::one(), n),
+        ]];
+        let artifacts = Artifacts::prove(5, FibonacciCircuit { n }, &instances);
+        assert!(artifacts.verify(&instances));
+
+        let dir = std::env::temp_dir().join(format!("examples_artifacts_{}", std::process::id()));
+        for format in [
+            SerdeFormat::Processed,
+            SerdeFormat::RawBytes,
+            SerdeFormat::RawBytesUnchecked,
+        ] {
+            artifacts.write(&dir, format).unwrap();
+            let read = Artifacts::read::<FibonacciCircuit>(&dir, format).unwrap();
+            let params_bytes = |params: &ParamsKZG<Bn256>| {
+                let mut bytes = vec![];
+                params.write_custom(&mut bytes, format).unwrap();
+                bytes
+            };
+            assert_eq!(params_bytes(&read.params), params_bytes(&artifacts.params));
+            assert_eq!(read.vk.to_bytes(format), artifacts.vk.to_bytes(format));
+            assert_eq!(read.pk.to_bytes(format), artifacts.pk.to_bytes(format));
+            assert_eq!(read.proof, artifacts.proof);
+
+            // the proof verifies from the artifacts read, for its instances
+            // only.
+            assert!(read.verify(&instances));
+            let mut wrong = instances.clone();
+            wrong[0][2] += Fr::one();
+            assert!(!read.verify(&wrong));
+        }
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,353 @@
+//! The product of two 256-bit integers over limbs of 64 bits, the core of
+//! the modular multiplications of the modexp precompile, and a circuit made
+//! mostly of range checks.
+//!
+//! The limbs of the product are those of the schoolbook multiplication, a
+//! limb per column of the partial products, with a carry into the next one:
+//! `sum_{i+j=k} a_i * b_j + carry_{k-1} = c_k + carry_k * 2^64`. The column
+//! sums are below `4 * 2^128`, and the carries below `2^67`, so that every
+//! equation holds over the integers, not only over the field, as long as the
+//! limbs of `c` are range checked to 64 bits and the carries to 72, a byte
+//! decomposition each. A limb of the product out of range would otherwise
+//! absorb a carry: `c_0 + 2^64` with `carry_0 - 1` satisfies the equation
+//! of the first column. The carry of the last column is the last limb.
+//!
+//! All the limbs and the carries are on a single row, the products costing
+//! no more than the sums in a gate of degree 2. Karatsuba's trick, trading
+//! a multiplication of limbs for additions, saves nothing there, and its
+//! middle terms need wider range checks.
+//!
+//! ```text
+//! | row | a_0 .. a_3 | b_0 .. b_3 | c_0 .. c_7 | carry_0 .. carry_6 | q_mul |
+//! | --- | ---------- | ---------- | ---------- | ------------------ | ----- |
+//! | 0   | a          | b          | a * b      | carries            | 1     |
+//! ```
+//!
+//! Every cell of the row is copied from the last running sum of its byte
+//! decomposition, see `decompose`, least significant limb first.
+
+use std::marker::PhantomData;
+
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{AssignedCell, Layouter},
+    plonk::{Advice, Column, ConstraintSystem, Error, Expression, Selector},
+    poly::Rotation,
+};
+
+use crate::decompose::{DecomposeChip, DecomposeConfig};
+
+/// The limbs of an operand.
+pub const NUM_LIMBS: usize = 4;
+
+/// Bits of a limb.
+pub const LIMB_BITS: usize = 64;
+
+/// Bytes of the range checks of the limbs and of the carries.
+const LIMB_BYTES: usize = LIMB_BITS / 8;
+const CARRY_BYTES: usize = 9;
+
+#[derive(Clone, Copy, Debug)]
+pub struct BigMulConfig {
+    q_mul: Selector,
+    a: [Column<Advice>; NUM_LIMBS],
+    b: [Column<Advice>; NUM_LIMBS],
+    c: [Column<Advice>; 2 * NUM_LIMBS],
+    carry: [Column<Advice>; 2 * NUM_LIMBS - 1],
+    bytes: DecomposeConfig,
+}
+
+pub struct BigMulChip<F> {
+    config: BigMulConfig,
+    _marker: PhantomData<F>,
+}
+
+/// The product of `a` and `b`, out-of-circuit, least significant limb
+/// first.
+pub fn mul(a: [u64; NUM_LIMBS], b: [u64; NUM_LIMBS]) -> [u64; 2 * NUM_LIMBS] {
+    mul_with_carries(a, b).0
+}
+
+/// The product of `a` and `b`, and the carries of its columns.
+fn mul_with_carries(
+    a: [u64; NUM_LIMBS],
+    b: [u64; NUM_LIMBS],
+) -> ([u64; 2 * NUM_LIMBS], [u128; 2 * NUM_LIMBS - 1]) {
+    let mut c = [0; 2 * NUM_LIMBS];
+    let mut carries = [0; 2 * NUM_LIMBS - 1];
+    let mut carry = 0u128;
+    for (k, carry_k) in carries.iter_mut().enumerate() {
+        // The column sum may overflow a `u128`, so its low and high halves
+        // are summed apart.
+        let (mut lo, mut hi) = (carry as u64 as u128, carry >> LIMB_BITS);
+        for i in k.saturating_sub(NUM_LIMBS - 1)..=k.min(NUM_LIMBS - 1) {
+            let product = a[i] as u128 * b[k - i] as u128;
+            lo += product as u64 as u128;
+            hi += product >> LIMB_BITS;
+        }
+        c[k] = lo as u64;
+        carry = hi + (lo >> LIMB_BITS);
+        *carry_k = carry;
+    }
+    c[2 * NUM_LIMBS - 1] = u64::try_from(carry).expect("the product has 512 bits");
+    (c, carries)
+}
+
+/// The `num_bytes` bytes of `value`, most significant first, the most
+/// significant one being the rest of the value, out of range if the value
+/// does not fit.
+fn bytes<F: FieldExt>(value: F, num_bytes: usize) -> Vec<F> {
+    let radix = F::from(256);
+    let mut bytes = value.to_repr().as_ref()[..num_bytes - 1]
+        .iter()
+        .map(|byte| F::from(*byte as u64))
+        .collect::<Vec<_>>();
+    let (low, weight) = bytes
+        .iter()
+        .fold((F::zero(), F::one()), |(low, weight), byte| {
+            (low + *byte * weight, weight * radix)
+        });
+    bytes.push((value - low) * weight.invert().unwrap());
+    bytes.reverse();
+    bytes
+}
+
+impl BigMulConfig {
+    pub fn configure<F: FieldExt>(meta: &mut ConstraintSystem<F>) -> Self {
+        let q_mul = meta.selector();
+        let mut advice_column = || {
+            let column = meta.advice_column();
+            meta.enable_equality(column);
+            column
+        };
+        let a = [0; NUM_LIMBS].map(|_| advice_column());
+        let b = [0; NUM_LIMBS].map(|_| advice_column());
+        let c = [0; 2 * NUM_LIMBS].map(|_| advice_column());
+        let carry = [0; 2 * NUM_LIMBS - 1].map(|_| advice_column());
+        let bytes = DecomposeConfig::configure_bytes(meta);
+
+        meta.create_gate("c_k + carry_k * 2^64 = column_k + carry_{k-1}", |meta| {
+            let q_mul = meta.query_selector(q_mul);
+            let [a, b] =
+                [a, b].map(|limbs| limbs.map(|limb| meta.query_advice(limb, Rotation::cur())));
+            let c = c.map(|limb| meta.query_advice(limb, Rotation::cur()));
+            let carry = carry.map(|carry| meta.query_advice(carry, Rotation::cur()));
+            let radix = Expression::Constant(F::from_u128(1 << LIMB_BITS));
+            (0..2 * NUM_LIMBS)
+                .map(|k| {
+                    let mut column = Expression::Constant(F::zero());
+                    for i in k.saturating_sub(NUM_LIMBS - 1)..=k.min(NUM_LIMBS - 1) {
+                        column = column + a[i].clone() * b[k - i].clone();
+                    }
+                    if k > 0 {
+                        column = column + carry[k - 1].clone();
+                    }
+                    let mut limb = c[k].clone();
+                    if k < 2 * NUM_LIMBS - 1 {
+                        limb = limb + carry[k].clone() * radix.clone();
+                    }
+                    q_mul.clone() * (limb - column)
+                })
+                .collect::<Vec<_>>()
+        });
+
+        Self {
+            q_mul,
+            a,
+            b,
+            c,
+            carry,
+            bytes,
+        }
+    }
+}
+
+impl<F: FieldExt> BigMulChip<F> {
+    pub fn new(config: BigMulConfig) -> Self {
+        Self {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Loads the table of the bytes, once per circuit.
+    pub fn load_table(&self, layouter: &mut impl Layouter<F>) -> Result<(), Error> {
+        DecomposeChip::new(self.config.bytes).load_table(layouter)
+    }
+
+    /// Assigns the product of `a` and `b`, least significant limb first,
+    /// and returns the cells of its limbs.
+    pub fn assign(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        a: [u64; NUM_LIMBS],
+        b: [u64; NUM_LIMBS],
+    ) -> Result<Vec<AssignedCell<F, F>>, Error> {
+        let (c, carries) = mul_with_carries(a, b);
+        self.assign_rows(
+            layouter,
+            &a.map(F::from),
+            &b.map(F::from),
+            &c.map(F::from),
+            &carries.map(F::from_u128),
+        )
+    }
+
+    /// Range checks the limbs and the carries given, assigns the row of the
+    /// product, and returns the cells of its limbs.
+    fn assign_rows(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        a: &[F],
+        b: &[F],
+        c: &[F],
+        carries: &[F],
+    ) -> Result<Vec<AssignedCell<F, F>>, Error> {
+        let config = self.config;
+        let decompose = DecomposeChip::new(config.bytes);
+        let mut range_check = |values: &[F], num_bytes: usize| {
+            values
+                .iter()
+                .map(|value| {
+                    let bytes = bytes(*value, num_bytes);
+                    Ok(decompose.assign_limbs(layouter, &bytes)?.value)
+                })
+                .collect::<Result<Vec<_>, Error>>()
+        };
+        let rows = [
+            (range_check(a, LIMB_BYTES)?, &config.a[..]),
+            (range_check(b, LIMB_BYTES)?, &config.b[..]),
+            (range_check(c, LIMB_BYTES)?, &config.c[..]),
+            (range_check(carries, CARRY_BYTES)?, &config.carry[..]),
+        ];
+
+        layouter.assign_region(
+            || "mul",
+            |mut region| {
+                config.q_mul.enable(&mut region, 0)?;
+                for (cells, columns) in rows.iter() {
+                    for (cell, column) in cells.iter().zip(columns.iter()) {
+                        cell.copy_advice(|| "limb", &mut region, *column, 0)?;
+                    }
+                }
+                Ok(())
+            },
+        )?;
+        let [_, _, (c, _), _] = rows;
+        Ok(c)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use halo2_proofs::{
+        arithmetic::Field,
+        circuit::SimpleFloorPlanner,
+        dev::{MockProver, VerifyFailure},
+        halo2curves::bn256::Fr,
+        plonk::{Circuit, Instance},
+    };
+
+    /// Multiplies the limbs given, with the carries given, and exposes the
+    /// limbs of the product as the instances.
+    #[derive(Default)]
+    struct TestCircuit<F> {
+        a: Vec<F>,
+        b: Vec<F>,
+        c: Vec<F>,
+        carries: Vec<F>,
+    }
+
+    impl<F: FieldExt> Circuit<F> for TestCircuit<F> {
+        type Config = (BigMulConfig, Column<Instance>);
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            let config = BigMulConfig::configure(meta);
+            let instance = meta.instance_column();
+            meta.enable_equality(instance);
+            (config, instance)
+        }
+
+        fn synthesize(
+            &self,
+            (config, instance): Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            let chip = BigMulChip::new(config);
+            chip.load_table(&mut layouter)?;
+            let c = chip.assign_rows(&mut layouter, &self.a, &self.b, &self.c, &self.carries)?;
+            for (row, limb) in c.iter().enumerate() {
+                layouter.constrain_instance(limb.cell(), instance, row)?;
+            }
+            Ok(())
+        }
+    }
+
+    fn circuit(a: [u64; NUM_LIMBS], b: [u64; NUM_LIMBS]) -> TestCircuit<Fr> {
+        let (c, carries) = mul_with_carries(a, b);
+        TestCircuit {
+            a: a.map(Fr::from).to_vec(),
+            b: b.map(Fr::from).to_vec(),
+            c: c.map(Fr::from).to_vec(),
+            carries: carries.map(Fr::from_u128).to_vec(),
+        }
+    }
+
+    fn verify(circuit: &TestCircuit<Fr>) -> Result<(), Vec<VerifyFailure>> {
+        MockProver::run(9, circuit, vec![circuit.c.clone()])
+            .unwrap()
+            .verify()
+    }
+
+    #[test]
+    fn test_mul() {
+        assert_eq!(mul([3, 0, 0, 0], [5, 0, 0, 0]), [15, 0, 0, 0, 0, 0, 0, 0]);
+        assert_eq!(mul([0, 1, 0, 0], [0, 0, 1, 0]), [0, 0, 0, 1, 0, 0, 0, 0]);
+        // `(2^256 - 1)^2 = 2^512 - 2^257 + 1`.
+        let max = [u64::MAX; NUM_LIMBS];
+        assert_eq!(
+            mul(max, max),
+            [1, 0, 0, 0, u64::MAX - 1, u64::MAX, u64::MAX, u64::MAX]
+        );
+    }
+
+    #[test]
+    fn test_big_mul() {
+        let a = [u64::MAX, 0x0123_4567_89ab_cdef, 7, u64::MAX];
+        let b = [u64::MAX; NUM_LIMBS];
+        assert_eq!(verify(&circuit(a, b)), Ok(()));
+
+        // a limb of the product off by one.
+        let mut wrong = circuit(a, b);
+        wrong.c[1] += Fr::one();
+        assert!(verify(&wrong).is_err());
+    }
+
+    #[test]
+    fn test_limb_out_of_range() {
+        // `c_0 + 2^64` and `carry_0 - 1` satisfy the equation of the first
+        // column, and `c_1 - 1` that of the second one, so that only the
+        // range check of `c_0`, its most significant byte being 256 too
+        // large, catches them.
+        let a = [u64::MAX, 1, 0, 0];
+        let b = [u64::MAX, 0, 0, 0];
+        let mut forged = circuit(a, b);
+        forged.c[0] += Fr::from_u128(1 << LIMB_BITS);
+        forged.carries[0] -= Fr::one();
+        forged.c[1] -= Fr::one();
+        let failures = verify(&forged).unwrap_err();
+        assert!(
+            failures.iter().all(|failure| matches!(
+                failure,
+                VerifyFailure::Lookup { name, .. } if name == "byte is in range"
+            )),
+            "{failures:?}"
+        );
+    }
+}
@@ -0,0 +1,58 @@
+//! The command line of the runnable examples, the targets of the `examples`
+//! directory of the crate, one per example but for `verify_failure`, whose
+//! point is its tests, and `residue_pattern`, which has a binary of its own:
+//!
+//! ```text
+//! cargo run --example fibonacci -- 5 1 1 10
+//! ```
+//!
+//! The first argument is the degree `k` of the circuit, `2^k` rows, and the
+//! next ones are the inputs of the example. The circuit is run with the
+//! `MockProver`, which prints the failures of the constraints, if any.
+
+use std::{env::args, process::exit, str::FromStr};
+
+use halo2_proofs::{dev::MockProver, halo2curves::bn256::Fr, plonk::Circuit};
+
+/// The degree and the inputs of the command line, `<k> <input>...` with
+/// `inputs` the usage of the inputs, and at least `min_inputs` of them, or
+/// exits with the usage.
+pub fn parse_args<T: FromStr>(name: &str, inputs: &str, min_inputs: usize) -> (u32, Vec<T>) {
+    let usage = || -> ! {
+        eprintln!("usage: cargo run --example {name} -- <k> {inputs}");
+        exit(2);
+    };
+    let args = args().skip(1).collect::<Vec<_>>();
+    let [k, inputs @ ..] = args.as_slice() else {
+        usage();
+    };
+    let Ok(k) = k.parse() else { usage() };
+    let Some(inputs) = inputs
+        .iter()
+        .map(|input| input.parse().ok())
+        .collect::<Option<Vec<T>>>()
+        .filter(|inputs| inputs.len() >= min_inputs)
+    else {
+        usage();
+    };
+    (k, inputs)
+}
+
+/// Runs `circuit` with `instances` in the `MockProver` of degree `k`, and
+/// prints whether its constraints are satisfied, exiting with 1 if not.
+pub fn mock_prove<C: Circuit<Fr>>(k: u32, circuit: &C, instances: Vec<Vec<Fr>>) {
+    let prover = MockProver::run(k, circuit, instances).unwrap_or_else(|err| {
+        eprintln!("Cannot run the circuit with k = {k}: {err}");
+        exit(2);
+    });
+    match prover.verify() {
+        Ok(()) => println!("The constraints are satisfied"),
+        Err(failures) => {
+            println!("The constraints are not satisfied:");
+            for failure in failures {
+                println!("  {failure}");
+            }
+            exit(1);
+        }
+    }
+}
@@ -0,0 +1,306 @@
+//! The swap of two values depending on a bit, the order of the children of
+//! a Merkle node, or of the words of a final block, depending on a witness.
+//!
+//! A swap is a pair of selections by the bit, `left = a + swap * (b - a)`
+//! and `right = b + swap * (a - b)`, which hold for a boolean bit only, the
+//! bit being constrained by `swap * (1 - swap) = 0`. The selections are
+//! either constrained by a gate of their own, on cells other gates copy, or
+//! inlined as expressions into the gates using them:
+//! - the gate: every swap takes a row of 5 cells, and the gate has the
+//!   degree 3, the selector times the bit times a difference, whatever the
+//!   gates using the cells;
+//! - the expressions: a swap takes no cell, but the degree of the swapped
+//!   expressions is that of the bit plus that of `a` and `b`, so that a gate
+//!   using them grows by a degree per factor: the hash of the `merkle` chip,
+//!   of degree 2 in the children, is of degree 4 in the cells, and 5 with
+//!   its selector.
+//!
+//! ```text
+//! | row | a   | b   | swap | left | right | q_swap |
+//! | --- | --- | --- | ---- | ---- | ----- | ------ |
+//! | 0   | a_0 | b_0 | 0    | a_0  | b_0   | 1      |
+//! | 1   | a_1 | b_1 | 1    | b_1  | a_1   | 1      |
+//! ```
+
+use std::marker::PhantomData;
+
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{AssignedCell, Layouter, Value},
+    plonk::{Advice, Column, ConstraintSystem, Error, Expression, Selector},
+    poly::Rotation,
+};
+
+#[derive(Clone, Copy, Debug)]
+pub struct CondSwapConfig {
+    q_swap: Selector,
+    a: Column<Advice>,
+    b: Column<Advice>,
+    swap: Column<Advice>, // whether `a` and `b` are swapped.
+    left: Column<Advice>,
+    right: Column<Advice>,
+}
+
+pub struct CondSwapChip<F> {
+    config: CondSwapConfig,
+    _marker: PhantomData<F>,
+}
+
+/// The pair `(a, b)`, swapped if `swap` is, out-of-circuit.
+pub fn cond_swap<T>(swap: bool, a: T, b: T) -> (T, T) {
+    if swap {
+        (b, a)
+    } else {
+        (a, b)
+    }
+}
+
+/// The expressions of the pair `(a, b)`, swapped if `swap` is 1, for the
+/// gates inlining the swap. The bit is not constrained boolean, which the
+/// gate using the expressions has to.
+pub fn cond_swap_expr<F: FieldExt>(
+    swap: Expression<F>,
+    a: Expression<F>,
+    b: Expression<F>,
+) -> (Expression<F>, Expression<F>) {
+    let left = a.clone() + swap.clone() * (b.clone() - a.clone());
+    let right = b.clone() + swap * (a - b);
+    (left, right)
+}
+
+impl CondSwapConfig {
+    pub fn configure<F: FieldExt>(meta: &mut ConstraintSystem<F>) -> Self {
+        let q_swap = meta.selector();
+        let [a, b, swap, left, right] = [0; 5].map(|_| {
+            let column = meta.advice_column();
+            meta.enable_equality(column);
+            column
+        });
+
+        meta.create_gate("(left, right) = swap ? (b, a) : (a, b)", |meta| {
+            let q_swap = meta.query_selector(q_swap);
+            let [a, b, swap, left, right] =
+                [a, b, swap, left, right].map(|column| meta.query_advice(column, Rotation::cur()));
+            let one = Expression::Constant(F::one());
+            let (swapped_left, swapped_right) = cond_swap_expr(swap.clone(), a, b);
+            vec![
+                q_swap.clone() * swap.clone() * (one - swap),
+                q_swap.clone() * (left - swapped_left),
+                q_swap * (right - swapped_right),
+            ]
+        });
+
+        Self {
+            q_swap,
+            a,
+            b,
+            swap,
+            left,
+            right,
+        }
+    }
+}
+
+impl<F: FieldExt> CondSwapChip<F> {
+    pub fn new(config: CondSwapConfig) -> Self {
+        Self {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Copies the pair `(a, b)` into a row, and returns the cells of the
+    /// pair swapped if `swap` is.
+    pub fn swap(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        (a, b): (&AssignedCell<F, F>, &AssignedCell<F, F>),
+        swap: Value<bool>,
+    ) -> Result<[AssignedCell<F, F>; 2], Error> {
+        let config = self.config;
+        layouter.assign_region(
+            || "cond_swap",
+            |mut region| {
+                config.q_swap.enable(&mut region, 0)?;
+                let a = a.copy_advice(|| "a", &mut region, config.a, 0)?;
+                let b = b.copy_advice(|| "b", &mut region, config.b, 0)?;
+                let bit = swap.map(|swap| F::from(swap as u64));
+                region.assign_advice(|| "swap", config.swap, 0, || bit)?;
+
+                let swapped = swap
+                    .zip(a.value().copied())
+                    .zip(b.value().copied())
+                    .map(|((swap, a), b)| cond_swap(swap, a, b));
+                let left = swapped.map(|(left, _)| left);
+                let right = swapped.map(|(_, right)| right);
+                let left = region.assign_advice(|| "left", config.left, 0, || left)?;
+                let right = region.assign_advice(|| "right", config.right, 0, || right)?;
+                Ok([left, right])
+            },
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use halo2_proofs::{
+        circuit::SimpleFloorPlanner, dev::MockProver, halo2curves::bn256::Fr, plonk::Circuit,
+    };
+
+    /// Swaps every pair given with the chip, and checks the cells it
+    /// returns against the expected pairs.
+    #[derive(Default)]
+    struct GateCircuit<F> {
+        pairs: Vec<(F, F, bool)>,
+        expected: Vec<(F, F)>,
+    }
+
+    impl<F: FieldExt> Circuit<F> for GateCircuit<F> {
+        type Config = (CondSwapConfig, Column<Advice>);
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            let value = meta.advice_column();
+            meta.enable_equality(value);
+            (CondSwapConfig::configure(meta), value)
+        }
+
+        fn synthesize(
+            &self,
+            (config, value): Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            let chip = CondSwapChip::new(config);
+            for ((a, b, swap), (left, right)) in self.pairs.iter().zip(self.expected.iter()) {
+                let cells = layouter.assign_region(
+                    || "values",
+                    |mut region| {
+                        [a, b, left, right]
+                            .iter()
+                            .enumerate()
+                            .map(|(offset, v)| {
+                                region.assign_advice(
+                                    || "value",
+                                    value,
+                                    offset,
+                                    || Value::known(**v),
+                                )
+                            })
+                            .collect::<Result<Vec<_>, _>>()
+                    },
+                )?;
+                let [left, right] =
+                    chip.swap(&mut layouter, (&cells[0], &cells[1]), Value::known(*swap))?;
+                layouter.assign_region(
+                    || "expected",
+                    |mut region| {
+                        region.constrain_equal(left.cell(), cells[2].cell())?;
+                        region.constrain_equal(right.cell(), cells[3].cell())
+                    },
+                )?;
+            }
+            Ok(())
+        }
+    }
+
+    /// The inlined swap, into the asymmetric gate `out = left^2 + right`
+    /// of degree 2 in the pair, 4 in the cells and 5 with its selector.
+    #[derive(Default)]
+    struct ExprCircuit<F> {
+        rows: Vec<(F, F, F, F)>, // a, b, swap and out.
+    }
+
+    impl<F: FieldExt> Circuit<F> for ExprCircuit<F> {
+        type Config = (Selector, [Column<Advice>; 4]);
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            let q = meta.selector();
+            let columns = [0; 4].map(|_| meta.advice_column());
+            meta.create_gate("out = left^2 + right", |meta| {
+                let q = meta.query_selector(q);
+                let [a, b, swap, out] =
+                    columns.map(|column| meta.query_advice(column, Rotation::cur()));
+                let one = Expression::Constant(F::one());
+                let (left, right) = cond_swap_expr(swap.clone(), a, b);
+                vec![
+                    q.clone() * swap.clone() * (one - swap),
+                    q * (out - (left.clone() * left + right)),
+                ]
+            });
+            (q, columns)
+        }
+
+        fn synthesize(
+            &self,
+            (q, columns): Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            layouter.assign_region(
+                || "rows",
+                |mut region| {
+                    for (offset, (a, b, swap, out)) in self.rows.iter().enumerate() {
+                        q.enable(&mut region, offset)?;
+                        for (column, value) in columns.iter().zip([a, b, swap, out]) {
+                            region.assign_advice(
+                                || "cell",
+                                *column,
+                                offset,
+                                || Value::known(*value),
+                            )?;
+                        }
+                    }
+                    Ok(())
+                },
+            )
+        }
+    }
+
+    #[test]
+    fn test_cond_swap_gate() {
+        let verify = |expected: Vec<(Fr, Fr)>| {
+            let pairs = vec![
+                (Fr::from(2), Fr::from(3), false),
+                (Fr::from(2), Fr::from(3), true),
+            ];
+            let circuit = GateCircuit { pairs, expected };
+            MockProver::run(5, &circuit, vec![]).unwrap().verify()
+        };
+        let [two, three] = [2, 3].map(Fr::from);
+        assert_eq!(cond_swap(true, two, three), (three, two));
+        assert_eq!(verify(vec![(two, three), (three, two)]), Ok(()));
+
+        // the second pair not swapped, and the first one swapped.
+        assert!(verify(vec![(two, three), (two, three)]).is_err());
+        assert!(verify(vec![(three, two), (three, two)]).is_err());
+    }
+
+    #[test]
+    fn test_cond_swap_expr() {
+        let verify = |rows: Vec<(u64, u64, u64, u64)>| {
+            let rows = rows
+                .into_iter()
+                .map(|(a, b, swap, out)| (Fr::from(a), Fr::from(b), Fr::from(swap), Fr::from(out)))
+                .collect();
+            MockProver::run(4, &ExprCircuit { rows }, vec![])
+                .unwrap()
+                .verify()
+        };
+        // `2^2 + 3` and `3^2 + 2`.
+        assert_eq!(verify(vec![(2, 3, 0, 7), (2, 3, 1, 11)]), Ok(()));
+
+        // the outputs of the other order, and a bit of 2, which selects
+        // `a + 2 * (b - a) = 4` and `b + 2 * (a - b) = 1`, `4^2 + 1`.
+        assert!(verify(vec![(2, 3, 0, 11)]).is_err());
+        assert!(verify(vec![(2, 3, 2, 17)]).is_err());
+    }
+}
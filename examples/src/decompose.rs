@@ -0,0 +1,310 @@
+//! The decomposition of a value into limbs, bits or bytes, and its
+//! recomposition, the first step of every hash circuit of the workspace,
+//! which works on the bits or the bytes of its words.
+//!
+//! The prover witnesses the limbs, most significant first, and the circuit
+//! recomposes them in a running sum, `acc = limb` at the first row and
+//! `acc = acc_prev * 2^limb_bits + limb` at the next ones, the last `acc`
+//! being the value. The recomposition alone does not bind the limbs: `0x0102`
+//! is also the recomposition of the bytes `[0, 0x102]`, so every limb is
+//! range checked, a bit by a gate `bit * (1 - bit) = 0`, and a byte by a
+//! lookup into a table of `[0, 256)`, a polynomial of degree 256 being out
+//! of reach of a gate:
+//!
+//! ```text
+//! | row   | limb    | acc     | q_first | q_next | table |
+//! | ----- | ------- | ------- | ------- | ------ | ----- |
+//! | 0     | l_0     | acc_0   | 1       | 0      | 0     |
+//! | 1     | l_1     | acc_1   | 0       | 1      | 1     |
+//! | ...   |         |         |         |        | ...   |
+//! | n - 1 | l_{n-1} | value   | 0       | 1      | 255   |
+//! ```
+//!
+//! The limbs are unique as long as they span fewer bits than the field, the
+//! running sum wrapping around the modulus otherwise.
+
+use std::marker::PhantomData;
+
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{AssignedCell, Layouter, Value},
+    plonk::{Advice, Column, ConstraintSystem, Error, Expression, Selector, TableColumn},
+    poly::Rotation,
+};
+
+#[derive(Clone, Copy, Debug)]
+pub struct DecomposeConfig {
+    q_first: Selector, // enabled at the first limb.
+    q_next: Selector,  // enabled at every other limb.
+    limb: Column<Advice>,
+    acc: Column<Advice>,
+    limb_bits: usize,
+    table: Option<TableColumn>, // the bytes, if the limbs are.
+}
+
+/// The cells of a decomposed value.
+pub struct Decomposed<F: FieldExt> {
+    pub value: AssignedCell<F, F>,      // the last running sum.
+    pub limbs: Vec<AssignedCell<F, F>>, // most significant first.
+}
+
+pub struct DecomposeChip<F> {
+    config: DecomposeConfig,
+    _marker: PhantomData<F>,
+}
+
+/// The `num_limbs` limbs of `limb_bits` bits of `value`, most significant
+/// first.
+pub fn limbs(value: u64, limb_bits: usize, num_limbs: usize) -> Vec<u64> {
+    (0..num_limbs)
+        .rev()
+        .map(|i| (value >> (i * limb_bits)) & ((1 << limb_bits) - 1))
+        .collect()
+}
+
+impl DecomposeConfig {
+    /// Configures the decomposition into bits.
+    pub fn configure_bits<F: FieldExt>(meta: &mut ConstraintSystem<F>) -> Self {
+        let config = Self::configure(meta, 1, None);
+        meta.create_gate("bit is boolean", |meta| {
+            let q_limb = meta.query_selector(config.q_first) + meta.query_selector(config.q_next);
+            let bit = meta.query_advice(config.limb, Rotation::cur());
+            let one = Expression::Constant(F::one());
+            vec![q_limb * bit.clone() * (one - bit)]
+        });
+        config
+    }
+
+    /// Configures the decomposition into bytes.
+    pub fn configure_bytes<F: FieldExt>(meta: &mut ConstraintSystem<F>) -> Self {
+        let table = meta.lookup_table_column();
+        let config = Self::configure(meta, 8, Some(table));
+        meta.lookup("byte is in range", |meta| {
+            let q_limb = meta.query_selector(config.q_first) + meta.query_selector(config.q_next);
+            let byte = meta.query_advice(config.limb, Rotation::cur());
+            vec![(q_limb * byte, table)]
+        });
+        config
+    }
+
+    /// Configures the running sum of the limbs of `limb_bits` bits.
+    fn configure<F: FieldExt>(
+        meta: &mut ConstraintSystem<F>,
+        limb_bits: usize,
+        table: Option<TableColumn>,
+    ) -> Self {
+        // The selectors are queried by the lookup of the bytes, which does
+        // not accept simple selectors.
+        let q_first = meta.complex_selector();
+        let q_next = meta.complex_selector();
+        let limb = meta.advice_column();
+        let acc = meta.advice_column();
+        meta.enable_equality(acc);
+
+        meta.create_gate("acc = limb", |meta| {
+            let q_first = meta.query_selector(q_first);
+            let limb = meta.query_advice(limb, Rotation::cur());
+            let acc = meta.query_advice(acc, Rotation::cur());
+            vec![q_first * (acc - limb)]
+        });
+        meta.create_gate("acc = acc_prev * 2^limb_bits + limb", |meta| {
+            let q_next = meta.query_selector(q_next);
+            let limb = meta.query_advice(limb, Rotation::cur());
+            let acc_prev = meta.query_advice(acc, Rotation::prev());
+            let acc = meta.query_advice(acc, Rotation::cur());
+            let radix = Expression::Constant(F::from(1 << limb_bits));
+            vec![q_next * (acc - (acc_prev * radix + limb))]
+        });
+
+        Self {
+            q_first,
+            q_next,
+            limb,
+            acc,
+            limb_bits,
+            table,
+        }
+    }
+}
+
+impl<F: FieldExt> DecomposeChip<F> {
+    pub fn new(config: DecomposeConfig) -> Self {
+        Self {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Loads the table of the bytes, once per circuit, if the limbs are.
+    pub fn load_table(&self, layouter: &mut impl Layouter<F>) -> Result<(), Error> {
+        let Some(table) = self.config.table else {
+            return Ok(());
+        };
+        layouter.assign_table(
+            || "bytes",
+            |mut table_region| {
+                for byte in 0..1 << self.config.limb_bits {
+                    table_region.assign_cell(
+                        || "byte",
+                        table,
+                        byte,
+                        || Value::known(F::from(byte as u64)),
+                    )?;
+                }
+                Ok(())
+            },
+        )
+    }
+
+    /// Decomposes `value` into `num_limbs` limbs, and returns the cells of
+    /// the value, recomposed, and of the limbs.
+    pub fn assign(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        value: u64,
+        num_limbs: usize,
+    ) -> Result<Decomposed<F>, Error> {
+        assert!(num_limbs * self.config.limb_bits <= 64);
+        let limbs = limbs(value, self.config.limb_bits, num_limbs);
+        self.assign_limbs(
+            layouter,
+            &limbs.into_iter().map(F::from).collect::<Vec<_>>(),
+        )
+    }
+
+    /// Recomposes the `limbs` given, for the callers decomposing a field
+    /// element rather than a `u64`.
+    pub(crate) fn assign_limbs(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        limbs: &[F],
+    ) -> Result<Decomposed<F>, Error> {
+        assert!(!limbs.is_empty(), "the value has a first limb");
+        let config = self.config;
+        let radix = F::from(1 << config.limb_bits);
+        layouter.assign_region(
+            || "decompose",
+            |mut region| {
+                let mut acc = F::zero();
+                let mut cells = vec![];
+                let mut value = None;
+                for (row, limb) in limbs.iter().enumerate() {
+                    if row == 0 {
+                        config.q_first.enable(&mut region, row)?;
+                    } else {
+                        config.q_next.enable(&mut region, row)?;
+                    }
+                    cells.push(region.assign_advice(
+                        || "limb",
+                        config.limb,
+                        row,
+                        || Value::known(*limb),
+                    )?);
+                    acc = acc * radix + limb;
+                    value = Some(region.assign_advice(
+                        || "acc",
+                        config.acc,
+                        row,
+                        || Value::known(acc),
+                    )?);
+                }
+                Ok(Decomposed {
+                    value: value.expect("the value has a first limb"),
+                    limbs: cells,
+                })
+            },
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use halo2_proofs::{
+        circuit::SimpleFloorPlanner,
+        dev::{MockProver, VerifyFailure},
+        halo2curves::bn256::Fr,
+        plonk::{Circuit, Instance},
+    };
+
+    /// Decomposes a value, exposed as the only instance, into the limbs
+    /// given.
+    #[derive(Default)]
+    struct TestCircuit<F, const BYTES: bool> {
+        limbs: Vec<F>,
+    }
+
+    impl<F: FieldExt, const BYTES: bool> Circuit<F> for TestCircuit<F, BYTES> {
+        type Config = (DecomposeConfig, Column<Instance>);
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            let config = if BYTES {
+                DecomposeConfig::configure_bytes(meta)
+            } else {
+                DecomposeConfig::configure_bits(meta)
+            };
+            let instance = meta.instance_column();
+            meta.enable_equality(instance);
+            (config, instance)
+        }
+
+        fn synthesize(
+            &self,
+            (config, instance): Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            let chip = DecomposeChip::new(config);
+            chip.load_table(&mut layouter)?;
+            let decomposed = chip.assign_limbs(&mut layouter, &self.limbs)?;
+            layouter.constrain_instance(decomposed.value.cell(), instance, 0)
+        }
+    }
+
+    fn verify<const BYTES: bool>(value: u64, limbs: &[Fr]) -> Result<(), Vec<VerifyFailure>> {
+        let circuit = TestCircuit::<Fr, BYTES> {
+            limbs: limbs.to_vec(),
+        };
+        MockProver::run(9, &circuit, vec![vec![Fr::from(value)]])
+            .unwrap()
+            .verify()
+    }
+
+    fn fr(limbs: &[u64]) -> Vec<Fr> {
+        limbs.iter().copied().map(Fr::from).collect()
+    }
+
+    #[test]
+    fn test_limbs() {
+        assert_eq!(limbs(0b1011, 1, 6), [0, 0, 1, 0, 1, 1]);
+        assert_eq!(limbs(0x0102_0304, 8, 4), [1, 2, 3, 4]);
+        assert_eq!(limbs(u64::MAX, 8, 8), [0xff; 8]);
+    }
+
+    #[test]
+    fn test_decompose_bits() {
+        let value = 0b1011;
+        assert_eq!(verify::<false>(value, &fr(&limbs(value, 1, 6))), Ok(()));
+
+        // another value, and limbs of the right sum out of range, `2 * 4 + 2 + 1`.
+        assert!(verify::<false>(value, &fr(&limbs(0b1010, 1, 6))).is_err());
+        assert!(verify::<false>(value, &fr(&[0, 0, 0, 2, 1, 1])).is_err());
+    }
+
+    #[test]
+    fn test_decompose_bytes() {
+        let value = 0x0102_0304;
+        assert_eq!(verify::<true>(value, &fr(&limbs(value, 8, 4))), Ok(()));
+
+        // `0x0102 * 256^2 + 0x0304`, and a last byte of `4 - 256` wrapping
+        // around the modulus.
+        assert!(verify::<true>(value, &fr(&[0, 0x0102, 0, 0x0304])).is_err());
+        let mut limbs = fr(&[1, 2, 4, 4]);
+        limbs[3] -= Fr::from(256);
+        assert!(verify::<true>(value, &limbs).is_err());
+    }
+}
@@ -0,0 +1,258 @@
+//! A lookup into the advice columns of another circuit, the pattern of the
+//! sub-circuits of the zkEVM: a caller, e.g. the EVM circuit, outsources an
+//! expensive relation to a callee circuit, e.g. the SHA2-256 circuit, which
+//! verifies it at every row of its table, and the caller only looks up the
+//! `(input, output)` pairs it uses in that table.
+//!
+//! The callee here squares its inputs, a stand-in for a hash. Unlike the
+//! fixed table of a range check, its table is made of advice columns, and
+//! thus of values chosen by the prover, so the lookup is a `lookup_any`, and
+//! only constrains the caller because every enabled row of the table is
+//! constrained by the gate of the callee. The `is_enabled` column tells the
+//! rows of the table the gate holds at from the unconstrained ones, e.g. the
+//! unused rows below the calls, and is looked up along the pair:
+//!
+//! ```text
+//! callee                             caller
+//! | is_enabled | input | output |    | q_lookup | x | y  |
+//! | ---------- | ----- | ------ |    | -------- | - | -- |
+//! | 1          | 2     | 4      |    | 1        | 3 | 9  |
+//! | 1          | 3     | 9      |    | 1        | 2 | 4  |
+//! | 0          |       |        |    | 1        | 3 | 9  |
+//! ```
+
+use std::marker::PhantomData;
+
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{Layouter, Value},
+    plonk::{Advice, Column, ConstraintSystem, Error, Expression, Selector},
+    poly::Rotation,
+};
+
+/// Table of the calls of the callee, which other circuits look up.
+#[derive(Clone, Copy, Debug)]
+pub struct SquareTable {
+    pub is_enabled: Column<Advice>,
+    pub input: Column<Advice>,
+    pub output: Column<Advice>,
+}
+
+impl SquareTable {
+    pub fn construct<F: FieldExt>(meta: &mut ConstraintSystem<F>) -> Self {
+        let [is_enabled, input, output] = [0; 3].map(|_| meta.advice_column());
+        Self {
+            is_enabled,
+            input,
+            output,
+        }
+    }
+}
+
+/// The callee, verifying `output = input^2` at every enabled row of its
+/// table.
+#[derive(Clone, Copy, Debug)]
+pub struct SquareConfig {
+    table: SquareTable,
+}
+
+pub struct SquareChip<F> {
+    config: SquareConfig,
+    _marker: PhantomData<F>,
+}
+
+impl SquareConfig {
+    pub fn configure<F: FieldExt>(meta: &mut ConstraintSystem<F>, table: SquareTable) -> Self {
+        // The gate holds at every row: `is_enabled` plays the part of a
+        // selector, but it is an advice column of the table, so that the
+        // lookups of the caller can query it. It must then be constrained
+        // boolean, or the prover could disable the relation with any other
+        // value.
+        meta.create_gate("output = input^2", |meta| {
+            let is_enabled = meta.query_advice(table.is_enabled, Rotation::cur());
+            let input = meta.query_advice(table.input, Rotation::cur());
+            let output = meta.query_advice(table.output, Rotation::cur());
+            let one = Expression::Constant(F::one());
+            vec![
+                is_enabled.clone() * (one - is_enabled.clone()),
+                is_enabled * (input.clone() * input - output),
+            ]
+        });
+
+        Self { table }
+    }
+}
+
+impl<F: FieldExt> SquareChip<F> {
+    pub fn new(config: SquareConfig) -> Self {
+        Self {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Assigns a row of the table for every input, and its square.
+    pub fn assign(&self, layouter: &mut impl Layouter<F>, inputs: &[u64]) -> Result<(), Error> {
+        let table = self.config.table;
+        layouter.assign_region(
+            || "square table",
+            |mut region| {
+                for (row, input) in inputs.iter().enumerate() {
+                    let input = F::from(*input);
+                    region.assign_advice(
+                        || "is_enabled",
+                        table.is_enabled,
+                        row,
+                        || Value::known(F::one()),
+                    )?;
+                    region.assign_advice(|| "input", table.input, row, || Value::known(input))?;
+                    region.assign_advice(
+                        || "output",
+                        table.output,
+                        row,
+                        || Value::known(input * input),
+                    )?;
+                }
+                Ok(())
+            },
+        )
+    }
+}
+
+/// The caller, claiming `y = x^2` at every row of its lookup, verified by
+/// the lookup of `(x, y)` in the table of the callee.
+#[derive(Clone, Copy, Debug)]
+pub struct CallerConfig {
+    q_lookup: Selector, // enabled at every row of a claim.
+    x: Column<Advice>,
+    y: Column<Advice>,
+}
+
+pub struct CallerChip<F> {
+    config: CallerConfig,
+    _marker: PhantomData<F>,
+}
+
+impl CallerConfig {
+    pub fn configure<F: FieldExt>(meta: &mut ConstraintSystem<F>, table: SquareTable) -> Self {
+        let q_lookup = meta.complex_selector();
+        let [x, y] = [0; 2].map(|_| meta.advice_column());
+
+        // Every input is multiplied by the selector, so that the rows of the
+        // caller without a claim look up the all-zero row, which the unused
+        // rows of the table hold. The rows with a claim look up `1` in
+        // `is_enabled`, and hence only rows the gate of the callee holds at.
+        meta.lookup_any("y = x^2 in the square table", |meta| {
+            let q_lookup = meta.query_selector(q_lookup);
+            let x = meta.query_advice(x, Rotation::cur());
+            let y = meta.query_advice(y, Rotation::cur());
+            vec![
+                (
+                    q_lookup.clone(),
+                    meta.query_advice(table.is_enabled, Rotation::cur()),
+                ),
+                (
+                    q_lookup.clone() * x,
+                    meta.query_advice(table.input, Rotation::cur()),
+                ),
+                (
+                    q_lookup * y,
+                    meta.query_advice(table.output, Rotation::cur()),
+                ),
+            ]
+        });
+
+        Self { q_lookup, x, y }
+    }
+}
+
+impl<F: FieldExt> CallerChip<F> {
+    pub fn new(config: CallerConfig) -> Self {
+        Self {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Assigns a row for every claim `(x, y)`, looked up in the table.
+    pub fn assign(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        claims: &[(u64, u64)],
+    ) -> Result<(), Error> {
+        let config = self.config;
+        layouter.assign_region(
+            || "caller",
+            |mut region| {
+                for (row, (x, y)) in claims.iter().enumerate() {
+                    config.q_lookup.enable(&mut region, row)?;
+                    region.assign_advice(|| "x", config.x, row, || Value::known(F::from(*x)))?;
+                    region.assign_advice(|| "y", config.y, row, || Value::known(F::from(*y)))?;
+                }
+                Ok(())
+            },
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use halo2_proofs::{
+        circuit::SimpleFloorPlanner, dev::MockProver, halo2curves::bn256::Fr, plonk::Circuit,
+    };
+
+    #[derive(Default)]
+    struct TestCircuit<F> {
+        inputs: Vec<u64>,
+        claims: Vec<(u64, u64)>,
+        _marker: PhantomData<F>,
+    }
+
+    impl<F: FieldExt> Circuit<F> for TestCircuit<F> {
+        type Config = (SquareConfig, CallerConfig);
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            let table = SquareTable::construct(meta);
+            (
+                SquareConfig::configure(meta, table),
+                CallerConfig::configure(meta, table),
+            )
+        }
+
+        fn synthesize(
+            &self,
+            (square, caller): Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            SquareChip::new(square).assign(&mut layouter, &self.inputs)?;
+            CallerChip::new(caller).assign(&mut layouter, &self.claims)
+        }
+    }
+
+    #[test]
+    fn test_dynamic_lookup() {
+        let k = 5;
+        let run = |claims: Vec<(u64, u64)>| {
+            let circuit = TestCircuit::<Fr> {
+                inputs: vec![2, 3],
+                claims,
+                _marker: PhantomData,
+            };
+            MockProver::run(k, &circuit, vec![]).unwrap().verify()
+        };
+        // a pair is looked up however many times it is claimed.
+        assert_eq!(run(vec![(3, 9), (2, 4), (3, 9)]), Ok(()));
+
+        // a wrong output, a pair the callee does not compute, and a pair as
+        // the unused rows of the table, which are disabled.
+        for claim in [(3, 10), (4, 16), (0, 0)] {
+            assert!(run(vec![(2, 4), claim]).is_err(), "{claim:?}");
+        }
+    }
+}
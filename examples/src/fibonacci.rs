@@ -0,0 +1,228 @@
+//! The Fibonacci sequence in a circuit, the first exercise before the hash
+//! circuits: it shows the three tools every circuit of the workspace is
+//! built with, i.e. advice columns holding the witness, a selector turning a
+//! gate on at the rows it constrains, and the permutation argument, which
+//! copies cells between rows and to the instance column of the public values.
+//!
+//! The chip proves that the `n`-th number of the sequence starting from two
+//! public numbers is a public output. Every row holds three consecutive
+//! numbers `a, b, c`, and the next row starts from its last two, copied:
+//!
+//! ```text
+//! | row   | a      | b      | c    | q_fib | instance |
+//! | ----- | ------ | ------ | ---- | ----- | -------- |
+//! | 0     | f(0)   | f(1)   | f(2) | 1     | f(0)     |
+//! | 1     | f(1)   | f(2)   | f(3) | 1     | f(1)     |
+//! | 2     | f(2)   | f(3)   | f(4) | 1     | f(n)     |
+//! | ...   |        |        |      |       |          |
+//! | n - 2 | f(n-2) | f(n-1) | f(n) | 1     |          |
+//! ```
+
+use std::marker::PhantomData;
+
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{AssignedCell, Layouter, Value},
+    plonk::{Advice, Column, ConstraintSystem, Error, Instance, Selector},
+    poly::Rotation,
+};
+
+/// Row of the first number of the sequence in the instance column.
+pub const FIRST_ROW: usize = 0;
+/// Row of the second number of the sequence in the instance column.
+pub const SECOND_ROW: usize = 1;
+/// Row of the output, the `n`-th number, in the instance column.
+pub const OUTPUT_ROW: usize = 2;
+
+#[derive(Clone, Copy, Debug)]
+pub struct FibonacciConfig {
+    q_fib: Selector, // enabled at every row of the sequence.
+    a: Column<Advice>,
+    b: Column<Advice>,
+    c: Column<Advice>,
+    instance: Column<Instance>, // the first two numbers and the output.
+}
+
+pub struct FibonacciChip<F> {
+    config: FibonacciConfig,
+    _marker: PhantomData<F>,
+}
+
+/// The `n`-th number of the sequence starting from `first` and `second`.
+pub fn fibonacci<F: FieldExt>(first: F, second: F, n: usize) -> F {
+    (0..n).fold((first, second), |(a, b), _| (b, a + b)).0
+}
+
+impl FibonacciConfig {
+    pub fn configure<F: FieldExt>(meta: &mut ConstraintSystem<F>) -> Self {
+        let q_fib = meta.selector();
+        let [a, b, c] = [0; 3].map(|_| meta.advice_column());
+        let instance = meta.instance_column();
+
+        // The permutation argument only constrains the cells of the columns
+        // it is enabled on: those copied to the next row, and the instance
+        // column the first numbers are read from and the output copied to.
+        for column in [a, b, c] {
+            meta.enable_equality(column);
+        }
+        meta.enable_equality(instance);
+
+        // The only gate, `c = a + b` at every row. A gate is a polynomial
+        // that must be zero at every row, so the constraint is multiplied by
+        // the selector: it is zero, and thus holds, wherever the selector is
+        // disabled, e.g. at the unused rows below the sequence. The gate does
+        // not relate the rows: the copies of `b` and `c` to the `a` and `b`
+        // of the next row do, through the permutation argument.
+        meta.create_gate("c = a + b", |meta| {
+            let q_fib = meta.query_selector(q_fib);
+            let a = meta.query_advice(a, Rotation::cur());
+            let b = meta.query_advice(b, Rotation::cur());
+            let c = meta.query_advice(c, Rotation::cur());
+            vec![q_fib * (a + b - c)]
+        });
+
+        Self {
+            q_fib,
+            a,
+            b,
+            c,
+            instance,
+        }
+    }
+}
+
+impl<F: FieldExt> FibonacciChip<F> {
+    pub fn new(config: FibonacciConfig) -> Self {
+        Self {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Assigns the sequence from the first two numbers of the instance column
+    /// up to its `n`-th number, for `n >= 2`, one row per number after the
+    /// first two, and returns the cell of the `n`-th number.
+    pub fn assign(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        n: usize,
+    ) -> Result<AssignedCell<F, F>, Error> {
+        assert!(n >= 2, "the sequence starts from two numbers");
+        let config = self.config;
+        layouter.assign_region(
+            || "fibonacci",
+            |mut region| {
+                let sum = |a: &AssignedCell<F, F>, b: &AssignedCell<F, F>| -> Value<F> {
+                    a.value().zip(b.value()).map(|(a, b)| *a + *b)
+                };
+
+                // The first row reads the first two numbers from the instance
+                // column, which also constrains them equal to those values.
+                config.q_fib.enable(&mut region, 0)?;
+                let mut a = region.assign_advice_from_instance(
+                    || "f(0)",
+                    config.instance,
+                    FIRST_ROW,
+                    config.a,
+                    0,
+                )?;
+                let mut b = region.assign_advice_from_instance(
+                    || "f(1)",
+                    config.instance,
+                    SECOND_ROW,
+                    config.b,
+                    0,
+                )?;
+                let mut c = region.assign_advice(|| "f(2)", config.c, 0, || sum(&a, &b))?;
+
+                for row in 1..n - 1 {
+                    config.q_fib.enable(&mut region, row)?;
+                    // Without the copies, the prover could start the row from
+                    // any numbers, and the gate would still hold.
+                    a = b.copy_advice(|| "a", &mut region, config.a, row)?;
+                    b = c.copy_advice(|| "b", &mut region, config.b, row)?;
+                    c = region.assign_advice(|| "c", config.c, row, || sum(&a, &b))?;
+                }
+                Ok(c)
+            },
+        )
+    }
+
+    /// Constrains `output` to the output row of the instance column, so that
+    /// the verifier, which knows the instance values, checks the result.
+    pub fn expose_output(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        output: &AssignedCell<F, F>,
+    ) -> Result<(), Error> {
+        layouter.constrain_instance(output.cell(), self.config.instance, OUTPUT_ROW)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use halo2_proofs::{
+        arithmetic::Field, circuit::SimpleFloorPlanner, dev::MockProver, halo2curves::bn256::Fr,
+        plonk::Circuit,
+    };
+
+    #[derive(Clone, Default)]
+    struct TestCircuit<F> {
+        n: usize,
+        _marker: PhantomData<F>,
+    }
+
+    impl<F: FieldExt> Circuit<F> for TestCircuit<F> {
+        type Config = FibonacciConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        // The circuit has no witness but the instance values, only the
+        // length of the sequence, which shapes it.
+        fn without_witnesses(&self) -> Self {
+            self.clone()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            FibonacciConfig::configure(meta)
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            let chip = FibonacciChip::new(config);
+            let output = chip.assign(&mut layouter, self.n)?;
+            chip.expose_output(&mut layouter, &output)
+        }
+    }
+
+    #[test]
+    fn test_fibonacci() {
+        assert_eq!(fibonacci(Fr::one(), Fr::one(), 10), Fr::from(89));
+        assert_eq!(fibonacci(Fr::from(2), Fr::from(1), 2), Fr::from(3));
+        assert_eq!(fibonacci(Fr::from(2), Fr::from(1), 0), Fr::from(2));
+    }
+
+    #[test]
+    fn test_fibonacci_circuit() {
+        let k = 5;
+        for n in [2, 10] {
+            let circuit = TestCircuit::<Fr> {
+                n,
+                _marker: PhantomData,
+            };
+            let output = fibonacci(Fr::one(), Fr::one(), n);
+            let prover =
+                MockProver::run(k, &circuit, vec![vec![Fr::one(), Fr::one(), output]]).unwrap();
+            assert_eq!(prover.verify(), Ok(()));
+
+            // the permutation argument rejects any other output.
+            let wrong = output + Fr::one();
+            let prover =
+                MockProver::run(k, &circuit, vec![vec![Fr::one(), Fr::one(), wrong]]).unwrap();
+            assert!(prover.verify().is_err());
+        }
+    }
+}
@@ -0,0 +1,279 @@
+//! The rows the floor planners lay out the same regions on, the regions of a
+//! circuit being positioned by its `FloorPlanner`, not by its chips.
+//!
+//! `SimpleFloorPlanner` positions the regions in the order they are
+//! assigned, every region at the first row after those any earlier region
+//! uses in its columns. A region spanning the columns of two others waits
+//! for the first one to end, and the second one then waits for it, though
+//! its column was free above. The `V1` floor planner synthesizes the circuit
+//! twice: the first pass measures the columns and the rows of every region,
+//! and the regions of the most cells are then slotted in first, each at the
+//! earliest rows its columns leave free, so that the small regions fill the
+//! gaps between the large ones. With `n` values on each side:
+//!
+//! ```text
+//! SimpleFloorPlanner                  V1
+//!
+//! | row     | a    | b     |      | row   | a    | b     |
+//! | ------- | ---- | ----- |      | ----- | ---- | ----- |
+//! | 0       | left |       |      | 0     | left | right |
+//! | ...     | left |       |      | ...   | left | right |
+//! | n       | both | both  |      | n     | both | both  |
+//! | n + 1   |      | right |
+//! | ...     |      | right |
+//! | 2n      |      | right |
+//! ```
+
+use std::{fmt, marker::PhantomData, ops::Range};
+
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{floor_planner::FloorPlanner, Layouter, Value},
+    plonk::{
+        Advice, Any, Assigned, Assignment, Challenge, Circuit, Column, ConstraintSystem, Error,
+        Fixed, Instance, Selector,
+    },
+};
+
+/// The name and the rows of every region of a circuit, in the order they
+/// are assigned, and the number of rows of its layout.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct RowUsage {
+    pub regions: Vec<(String, Range<usize>)>,
+    pub rows: usize,
+}
+
+impl fmt::Display for RowUsage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{} rows", self.rows)?;
+        for (name, rows) in self.regions.iter() {
+            writeln!(f, "  {name}: rows {}..{}", rows.start, rows.end)?;
+        }
+        Ok(())
+    }
+}
+
+/// Lays `circuit` out with its floor planner, and returns the rows of its
+/// regions.
+pub fn row_usage<F: FieldExt, C: Circuit<F>>(circuit: &C) -> Result<RowUsage, Error> {
+    let mut meta = ConstraintSystem::default();
+    let config = C::configure(&mut meta);
+    let mut recorder = RowRecorder::default();
+    C::FloorPlanner::synthesize(&mut recorder, circuit, config, meta.constants().clone())?;
+
+    let regions = recorder
+        .regions
+        .into_iter()
+        .map(|(name, rows)| (name, rows.unwrap_or(0..0)))
+        .collect::<Vec<_>>();
+    let rows = regions.iter().map(|(_, rows)| rows.end).max().unwrap_or(0);
+    Ok(RowUsage { regions, rows })
+}
+
+/// An `Assignment` recording the rows of the cells of every region, and
+/// nothing else.
+#[derive(Default)]
+struct RowRecorder {
+    regions: Vec<(String, Option<Range<usize>>)>,
+    in_region: bool,
+}
+
+impl RowRecorder {
+    fn use_row(&mut self, row: usize) {
+        let Some((_, rows)) = self.regions.last_mut().filter(|_| self.in_region) else {
+            return;
+        };
+        *rows = Some(match rows.take() {
+            Some(rows) => rows.start.min(row)..rows.end.max(row + 1),
+            None => row..row + 1,
+        });
+    }
+}
+
+impl<F: FieldExt> Assignment<F> for RowRecorder {
+    fn enter_region<NR, N>(&mut self, name_fn: N)
+    where
+        NR: Into<String>,
+        N: FnOnce() -> NR,
+    {
+        self.regions.push((name_fn().into(), None));
+        self.in_region = true;
+    }
+
+    fn exit_region(&mut self) {
+        self.in_region = false;
+    }
+
+    fn enable_selector<A, AR>(&mut self, _: A, _: &Selector, row: usize) -> Result<(), Error>
+    where
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        self.use_row(row);
+        Ok(())
+    }
+
+    fn query_instance(&self, _: Column<Instance>, _: usize) -> Result<Value<F>, Error> {
+        Ok(Value::unknown())
+    }
+
+    fn assign_advice<V, VR, A, AR>(
+        &mut self,
+        _: A,
+        _: Column<Advice>,
+        row: usize,
+        _: V,
+    ) -> Result<(), Error>
+    where
+        V: FnOnce() -> Value<VR>,
+        VR: Into<Assigned<F>>,
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        self.use_row(row);
+        Ok(())
+    }
+
+    fn assign_fixed<V, VR, A, AR>(
+        &mut self,
+        _: A,
+        _: Column<Fixed>,
+        row: usize,
+        _: V,
+    ) -> Result<(), Error>
+    where
+        V: FnOnce() -> Value<VR>,
+        VR: Into<Assigned<F>>,
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        self.use_row(row);
+        Ok(())
+    }
+
+    fn copy(&mut self, _: Column<Any>, _: usize, _: Column<Any>, _: usize) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn fill_from_row(
+        &mut self,
+        _: Column<Fixed>,
+        _: usize,
+        _: Value<Assigned<F>>,
+    ) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn get_challenge(&self, _: Challenge) -> Value<F> {
+        Value::unknown()
+    }
+
+    fn push_namespace<NR, N>(&mut self, _: N)
+    where
+        NR: Into<String>,
+        N: FnOnce() -> NR,
+    {
+    }
+
+    fn pop_namespace(&mut self, _: Option<String>) {}
+}
+
+/// The regions of the layouts above: `n` values in the column `a`, a row
+/// in both columns, and `n` values in the column `b`, laid out by the floor
+/// planner `P`.
+pub struct PackingCircuit<F, P> {
+    pub n: usize,
+    pub _marker: PhantomData<(F, P)>,
+}
+
+impl<F: FieldExt, P: FloorPlanner> Circuit<F> for PackingCircuit<F, P> {
+    type Config = [Column<Advice>; 2];
+    type FloorPlanner = P;
+
+    fn without_witnesses(&self) -> Self {
+        Self {
+            n: self.n,
+            _marker: PhantomData,
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        [meta.advice_column(), meta.advice_column()]
+    }
+
+    fn synthesize(
+        &self,
+        [a, b]: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        let one = Value::known(F::one());
+        for (name, columns, rows) in [
+            ("left", vec![a], self.n),
+            ("both", vec![a, b], 1),
+            ("right", vec![b], self.n),
+        ] {
+            layouter.assign_region(
+                || name,
+                |mut region| {
+                    for row in 0..rows {
+                        for column in columns.iter() {
+                            region.assign_advice(|| "value", *column, row, || one)?;
+                        }
+                    }
+                    Ok(())
+                },
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use halo2_proofs::{
+        circuit::{floor_planner::V1, SimpleFloorPlanner},
+        dev::MockProver,
+        halo2curves::bn256::Fr,
+    };
+
+    fn circuit<P>(n: usize) -> PackingCircuit<Fr, P> {
+        PackingCircuit {
+            n,
+            _marker: PhantomData,
+        }
+    }
+
+    #[test]
+    fn test_row_usage() {
+        let simple = row_usage(&circuit::<SimpleFloorPlanner>(4)).unwrap();
+        assert_eq!(
+            simple.regions,
+            [
+                ("left".to_string(), 0..4),
+                ("both".to_string(), 4..5),
+                ("right".to_string(), 5..9),
+            ]
+        );
+        assert_eq!(simple.rows, 9);
+
+        let v1 = row_usage(&circuit::<V1>(4)).unwrap();
+        assert_eq!(
+            v1.regions,
+            [
+                ("left".to_string(), 0..4),
+                ("both".to_string(), 4..5),
+                ("right".to_string(), 0..4),
+            ]
+        );
+        assert_eq!(v1.rows, 5);
+
+        // both layouts are valid.
+        for prover in [
+            MockProver::run(5, &circuit::<SimpleFloorPlanner>(4), vec![]),
+            MockProver::run(5, &circuit::<V1>(4), vec![]),
+        ] {
+            assert_eq!(prover.unwrap().verify(), Ok(()));
+        }
+    }
+}
@@ -0,0 +1,297 @@
+//! Whether a value is zero, and a branch on it: the pattern of the padding
+//! and of the final blocks of the hash circuits, which hash either an input
+//! byte or a padding byte on a row depending on whether the bytes left are
+//! zero.
+//!
+//! A gate cannot test a value for zero, but it can check a claim of the
+//! prover: `is_zero = 1 - value * value_inv` with `value_inv` the inverse of
+//! the value, or any element if it is zero, which sets `is_zero` to 0 for a
+//! non-zero value, and `value * is_zero = 0`, which sets it to 1 for zero.
+//! The bit then selects between the two computations of the row, both laid
+//! out and constrained, a circuit having no control flow:
+//! `out = is_zero * (a + b) + (1 - is_zero) * a * b`.
+//!
+//! ```text
+//! | row | value | value_inv | is_zero | a  | b  | out     | q_select |
+//! | --- | ----- | --------- | ------- | -- | -- | ------- | -------- |
+//! | 0   | 0     | 0         | 1       | a0 | b0 | a0 + b0 | 1        |
+//! | 1   | v1    | 1 / v1    | 0       | a1 | b1 | a1 * b1 | 1        |
+//! ```
+
+use std::marker::PhantomData;
+
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{AssignedCell, Layouter, Region, Value},
+    plonk::{Advice, Column, ConstraintSystem, Error, Expression, Selector},
+    poly::Rotation,
+};
+
+/// The columns of the witness of `value == 0`, configured over the value
+/// column of a caller.
+#[derive(Clone, Copy, Debug)]
+pub struct IsZeroConfig {
+    value: Column<Advice>,
+    value_inv: Column<Advice>, // the inverse of the value, or 0.
+    pub is_zero: Column<Advice>,
+}
+
+pub struct IsZeroChip<F> {
+    config: IsZeroConfig,
+    _marker: PhantomData<F>,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct SelectConfig {
+    q_select: Selector,
+    is_zero: IsZeroConfig,
+    a: Column<Advice>,
+    b: Column<Advice>,
+    out: Column<Advice>,
+}
+
+pub struct SelectChip<F> {
+    config: SelectConfig,
+    _marker: PhantomData<F>,
+}
+
+/// The output of a row, out-of-circuit: `a + b` if `value` is zero, and
+/// `a * b` otherwise.
+pub fn select<F: FieldExt>(value: F, a: F, b: F) -> F {
+    if value == F::zero() {
+        a + b
+    } else {
+        a * b
+    }
+}
+
+impl IsZeroConfig {
+    /// Configures the bit of whether `value` is zero, on the rows where
+    /// `q_enable` is enabled.
+    pub fn configure<F: FieldExt>(
+        meta: &mut ConstraintSystem<F>,
+        q_enable: Selector,
+        value: Column<Advice>,
+    ) -> Self {
+        let value_inv = meta.advice_column();
+        let is_zero = meta.advice_column();
+
+        // Either constraint alone is not enough: the first one lets a zero
+        // value have an `is_zero` of 0 with any inverse, the second one a
+        // non-zero value have an `is_zero` of 1 with an inverse of 0.
+        meta.create_gate("is_zero = 1 - value * value_inv", |meta| {
+            let q_enable = meta.query_selector(q_enable);
+            let value = meta.query_advice(value, Rotation::cur());
+            let value_inv = meta.query_advice(value_inv, Rotation::cur());
+            let is_zero = meta.query_advice(is_zero, Rotation::cur());
+            let one = Expression::Constant(F::one());
+            vec![
+                q_enable.clone() * (is_zero.clone() - (one - value.clone() * value_inv)),
+                q_enable * value * is_zero,
+            ]
+        });
+
+        Self {
+            value,
+            value_inv,
+            is_zero,
+        }
+    }
+}
+
+impl<F: FieldExt> IsZeroChip<F> {
+    pub fn new(config: IsZeroConfig) -> Self {
+        Self {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Assigns the witness of whether the `value` assigned at `offset` is
+    /// zero, and returns the cell of the bit.
+    pub fn assign(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        value: Value<F>,
+    ) -> Result<AssignedCell<F, F>, Error> {
+        let config = self.config;
+        let value_inv = value.map(|value| value.invert().unwrap_or(F::zero()));
+        region.assign_advice(|| "value_inv", config.value_inv, offset, || value_inv)?;
+        let is_zero = value.map(|value| F::from((value == F::zero()) as u64));
+        region.assign_advice(|| "is_zero", config.is_zero, offset, || is_zero)
+    }
+}
+
+impl SelectConfig {
+    pub fn configure<F: FieldExt>(meta: &mut ConstraintSystem<F>) -> Self {
+        let q_select = meta.selector();
+        let [value, a, b, out] = [0; 4].map(|_| meta.advice_column());
+        let is_zero = IsZeroConfig::configure(meta, q_select, value);
+
+        meta.create_gate("out = is_zero ? a + b : a * b", |meta| {
+            let q_select = meta.query_selector(q_select);
+            let is_zero = meta.query_advice(is_zero.is_zero, Rotation::cur());
+            let a = meta.query_advice(a, Rotation::cur());
+            let b = meta.query_advice(b, Rotation::cur());
+            let out = meta.query_advice(out, Rotation::cur());
+            let one = Expression::Constant(F::one());
+            let selected = is_zero.clone() * (a.clone() + b.clone()) + (one - is_zero) * a * b;
+            vec![q_select * (out - selected)]
+        });
+
+        Self {
+            q_select,
+            is_zero,
+            a,
+            b,
+            out,
+        }
+    }
+}
+
+impl<F: FieldExt> SelectChip<F> {
+    pub fn new(config: SelectConfig) -> Self {
+        Self {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Assigns a row per `(value, a, b)` of `rows`, and returns the cells of
+    /// their outputs.
+    pub fn assign(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        rows: &[(Value<F>, Value<F>, Value<F>)],
+    ) -> Result<Vec<AssignedCell<F, F>>, Error> {
+        let config = self.config;
+        let is_zero = IsZeroChip::new(config.is_zero);
+        layouter.assign_region(
+            || "select",
+            |mut region| {
+                rows.iter()
+                    .enumerate()
+                    .map(|(offset, (value, a, b))| {
+                        config.q_select.enable(&mut region, offset)?;
+                        region.assign_advice(
+                            || "value",
+                            config.is_zero.value,
+                            offset,
+                            || *value,
+                        )?;
+                        region.assign_advice(|| "a", config.a, offset, || *a)?;
+                        region.assign_advice(|| "b", config.b, offset, || *b)?;
+                        is_zero.assign(&mut region, offset, *value)?;
+                        let out = value
+                            .zip(*a)
+                            .zip(*b)
+                            .map(|((value, a), b)| select(value, a, b));
+                        region.assign_advice(|| "out", config.out, offset, || out)
+                    })
+                    .collect()
+            },
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use halo2_proofs::{
+        arithmetic::Field, circuit::SimpleFloorPlanner, dev::MockProver, halo2curves::bn256::Fr,
+        plonk::Circuit,
+    };
+
+    #[derive(Default)]
+    struct TestCircuit<F> {
+        rows: Vec<(F, F, F)>,
+        // the `is_zero` and `value_inv` of the first row, overriding those
+        // of the chip.
+        forged: Option<(F, F)>,
+    }
+
+    impl<F: FieldExt> Circuit<F> for TestCircuit<F> {
+        type Config = SelectConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            SelectConfig::configure(meta)
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            let rows = self
+                .rows
+                .iter()
+                .map(|(value, a, b)| (Value::known(*value), Value::known(*a), Value::known(*b)))
+                .collect::<Vec<_>>();
+            let Some((is_zero, value_inv)) = self.forged else {
+                SelectChip::new(config).assign(&mut layouter, &rows)?;
+                return Ok(());
+            };
+
+            // The output of the first row is that of the other branch, as
+            // the forged bit selects it.
+            let (_, a, b) = self.rows[0];
+            let out = if is_zero == F::one() { a + b } else { a * b };
+            layouter.assign_region(
+                || "select",
+                |mut region| {
+                    let (value, a, b) = rows[0];
+                    config.q_select.enable(&mut region, 0)?;
+                    region.assign_advice(|| "value", config.is_zero.value, 0, || value)?;
+                    region.assign_advice(|| "a", config.a, 0, || a)?;
+                    region.assign_advice(|| "b", config.b, 0, || b)?;
+                    for (column, forged) in [
+                        (config.is_zero.is_zero, is_zero),
+                        (config.is_zero.value_inv, value_inv),
+                        (config.out, out),
+                    ] {
+                        region.assign_advice(|| "forged", column, 0, || Value::known(forged))?;
+                    }
+                    Ok(())
+                },
+            )
+        }
+    }
+
+    #[test]
+    fn test_select() {
+        let k = 4;
+        let verify =
+            |circuit: TestCircuit<Fr>| MockProver::run(k, &circuit, vec![]).unwrap().verify();
+        let [zero, two, three, four] = [0, 2, 3, 4].map(Fr::from);
+        assert_eq!(select(zero, three, four), Fr::from(7));
+        assert_eq!(select(two, three, four), Fr::from(12));
+        assert_eq!(
+            verify(TestCircuit {
+                rows: vec![
+                    (zero, three, four),
+                    (two, three, four),
+                    (-Fr::one(), two, two)
+                ],
+                forged: None,
+            }),
+            Ok(())
+        );
+
+        // a zero value claimed non-zero, with any inverse, and a non-zero
+        // value claimed zero with an inverse of 0, each caught by one of the
+        // two constraints.
+        for (value, forged) in [(zero, (zero, Fr::one())), (two, (Fr::one(), zero))] {
+            let circuit = TestCircuit {
+                rows: vec![(value, three, four)],
+                forged: Some(forged),
+            };
+            assert!(verify(circuit).is_err());
+        }
+    }
+}
@@ -1,3 +1,44 @@
+mod accumulation;
+mod add_words;
+mod artifacts;
+mod bigint;
+mod cli;
+mod cond_swap;
+mod decompose;
+mod dynamic_lookup;
+mod fibonacci;
+mod floor_planner;
+mod is_zero;
+mod merkle;
+mod permutation;
+mod range_check;
 mod residue_pattern;
+mod rlc;
+mod selector_vs_fixed;
+mod sudoku;
+mod verify_failure;
+mod xor;
 
+pub use accumulation::{
+    accumulate, claim_instances, toy_commit, toy_open, toy_verify, AccumulationChip,
+    AccumulationConfig, Claim,
+};
+pub use add_words::{add_words, AddWordsChip, AddWordsConfig, WordSum, HALF_BITS};
+pub use artifacts::{Artifacts, PARAMS_FILE, PK_FILE, PROOF_FILE, VK_FILE};
+pub use bigint::{mul, BigMulChip, BigMulConfig, LIMB_BITS, NUM_LIMBS};
+pub use cli::{mock_prove, parse_args};
+pub use cond_swap::{cond_swap, cond_swap_expr, CondSwapChip, CondSwapConfig};
+pub use decompose::{limbs, DecomposeChip, DecomposeConfig, Decomposed};
+pub use dynamic_lookup::{CallerChip, CallerConfig, SquareChip, SquareConfig, SquareTable};
+pub use fibonacci::{fibonacci, FibonacciChip, FibonacciConfig};
+pub use floor_planner::{row_usage, PackingCircuit, RowUsage};
+pub use is_zero::{select, IsZeroChip, IsZeroConfig, SelectChip, SelectConfig};
+pub use merkle::{merkle_root, toy_hash, MerkleChip, MerkleConfig, ROOT_ROW};
+pub use permutation::{PermutationChip, PermutationConfig, SortChip, SortConfig, RHO};
+pub use range_check::{RangeCheckChip, RangeCheckConfig};
 pub use residue_pattern::{residue_pattern, ResiduePatternChip, ResiduePatternConfig};
+pub use rlc::{rlc, RlcChip, RlcConfig};
+pub use selector_vs_fixed::{config_stats, ConfigStats, Flag, MulCircuit, MulConfig};
+pub use sudoku::{puzzle_instance, solves, Grid, SudokuChip, SudokuConfig};
+pub use verify_failure::{ProductChip, ProductConfig};
+pub use xor::{XorChip, XorConfig};
@@ -0,0 +1,213 @@
+//! Proves and verifies the residue patterns of values with the KZG commitment
+//! scheme, the pipeline the tests of the examples skip by running the
+//! `MockProver`: the setup of the parameters, the key generation, the proof,
+//! written to a file, and its verification from the public patterns alone.
+//!
+//! ```text
+//! cargo run --release --bin residue_pattern -- prove residue 0 2323 3
+//! cargo run --release --bin residue_pattern -- verify residue <pattern> <pattern> <pattern>
+//! ```
+//!
+//! `prove` writes the parameters and the proof to the directory, and prints
+//! the patterns of the values, which stay private. `verify` reads them back,
+//! and checks the proof against the patterns only. The parameters are set up
+//! from a local secret, as the benchmarks do by default, so anyone knowing it
+//! could forge proofs: a deployment loads those of a ceremony instead, see
+//! the `convert_srs` binary of the `benchmarking` crate.
+
+use std::{
+    env::args,
+    fs::{self, File},
+    io::{self, BufReader, BufWriter},
+    path::{Path, PathBuf},
+    process::exit,
+};
+
+use examples::{residue_pattern, ResiduePatternChip, ResiduePatternConfig};
+use halo2_proofs::{
+    arithmetic::Field,
+    circuit::{Layouter, SimpleFloorPlanner},
+    halo2curves::bn256::{Bn256, Fr, G1Affine},
+    plonk::{create_proof, keygen_pk, keygen_vk, verify_proof, Circuit, ConstraintSystem, Error},
+    poly::{
+        commitment::{Params, ParamsProver},
+        kzg::{
+            commitment::{KZGCommitmentScheme, ParamsKZG},
+            multiopen::{ProverSHPLONK, VerifierSHPLONK},
+            strategy::SingleStrategy,
+        },
+    },
+    transcript::{
+        Blake2bRead, Blake2bWrite, Challenge255, TranscriptReadBuffer, TranscriptWriterBuffer,
+    },
+};
+use rand::rngs::OsRng;
+
+const USAGE: &str = "usage: residue_pattern prove <dir> <value>... | verify <dir> <pattern>...";
+
+/// Bits of the pattern of every value.
+const LENGTH: usize = 64;
+
+/// Rows of the usable area of a circuit left for the blinding factors of the
+/// proof, which only needs a few.
+const BLINDING_ROWS: usize = 16;
+
+/// The chip of the residue patterns of `values`, exposing them as instances.
+struct ResiduePatternCircuit {
+    values: Vec<Fr>,
+}
+
+impl Circuit<Fr> for ResiduePatternCircuit {
+    type Config = ResiduePatternConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    // The number of values shapes the circuit, so it is kept, and the
+    // verifier generates its keys from values of its own.
+    fn without_witnesses(&self) -> Self {
+        Self {
+            values: vec![Fr::zero(); self.values.len()],
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+        ResiduePatternConfig::configure(meta, nonresidue(), LENGTH)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<Fr>,
+    ) -> Result<(), Error> {
+        let chip = ResiduePatternChip::new(config, nonresidue());
+        let patterns = chip.assign(&mut layouter, &self.values)?;
+        chip.expose_patterns(&mut layouter, &patterns)
+    }
+}
+
+fn nonresidue() -> Fr {
+    Fr::from(5)
+}
+
+/// Degree of the circuit of `num_values` values.
+fn degree(num_values: usize) -> u32 {
+    (num_values * LENGTH + BLINDING_ROWS)
+        .next_power_of_two()
+        .trailing_zeros()
+}
+
+enum Command {
+    Prove { dir: PathBuf, values: Vec<u64> },
+    Verify { dir: PathBuf, patterns: Vec<u64> },
+}
+
+fn parse_args(args: &[String]) -> Option<Command> {
+    let [command, dir, numbers @ ..] = args else {
+        return None;
+    };
+    let numbers = numbers
+        .iter()
+        .map(|number| number.parse().ok())
+        .collect::<Option<Vec<u64>>>()
+        .filter(|numbers| !numbers.is_empty())?;
+    let dir = dir.into();
+    match command.as_str() {
+        "prove" => Some(Command::Prove {
+            dir,
+            values: numbers,
+        }),
+        "verify" => Some(Command::Verify {
+            dir,
+            patterns: numbers,
+        }),
+        _ => None,
+    }
+}
+
+fn main() {
+    let Some(command) = parse_args(&args().skip(1).collect::<Vec<_>>()) else {
+        eprintln!("{USAGE}");
+        exit(2);
+    };
+    match command {
+        Command::Prove { dir, values } => {
+            let patterns = prove(&dir, &values).unwrap_or_else(|err| {
+                eprintln!("Cannot write the proof to {}: {err}", dir.display());
+                exit(2);
+            });
+            println!("Wrote the proof of the patterns to {}:", dir.display());
+            for pattern in patterns {
+                println!("{pattern}");
+            }
+        }
+        Command::Verify { dir, patterns } => match verify(&dir, &patterns) {
+            Ok(true) => println!("The proof is valid"),
+            Ok(false) => {
+                println!("The proof is invalid");
+                exit(1);
+            }
+            Err(err) => {
+                eprintln!("Cannot read the proof in {}: {err}", dir.display());
+                exit(2);
+            }
+        },
+    }
+}
+
+/// Proves the patterns of `values`, writing the parameters and the proof to
+/// `dir`, and returns the patterns.
+fn prove(dir: &Path, values: &[u64]) -> io::Result<Vec<u64>> {
+    let values = values.iter().copied().map(Fr::from).collect::<Vec<_>>();
+    let patterns = values
+        .iter()
+        .copied()
+        .map(residue_pattern)
+        .collect::<Vec<_>>();
+    let instances = patterns.iter().copied().map(Fr::from).collect::<Vec<_>>();
+    let circuit = ResiduePatternCircuit { values };
+
+    let params = ParamsKZG::<Bn256>::setup(degree(circuit.values.len()), OsRng);
+    let vk = keygen_vk(&params, &circuit).expect("keygen_vk should not fail");
+    let pk = keygen_pk(&params, vk, &circuit).expect("keygen_pk should not fail");
+
+    let mut transcript = Blake2bWrite::<_, G1Affine, Challenge255<_>>::init(vec![]);
+    create_proof::<KZGCommitmentScheme<Bn256>, ProverSHPLONK<_>, _, _, _, _>(
+        &params,
+        &pk,
+        &[circuit],
+        &[&[&instances]],
+        OsRng,
+        &mut transcript,
+    )
+    .expect("proof generation should not fail");
+
+    fs::create_dir_all(dir)?;
+    params.write(&mut BufWriter::new(File::create(dir.join("params"))?))?;
+    fs::write(dir.join("proof"), transcript.finalize())?;
+    Ok(patterns)
+}
+
+/// Verifies the proof in `dir` of `patterns`, with the parameters beside it.
+fn verify(dir: &Path, patterns: &[u64]) -> io::Result<bool> {
+    let params = ParamsKZG::<Bn256>::read(&mut BufReader::new(File::open(dir.join("params"))?))?;
+    let proof = fs::read(dir.join("proof"))?;
+    let instances = patterns.iter().copied().map(Fr::from).collect::<Vec<_>>();
+    let circuit = ResiduePatternCircuit {
+        values: vec![Fr::zero(); patterns.len()],
+    };
+    if params.k() != degree(patterns.len()) {
+        return Ok(false);
+    }
+    let vk = keygen_vk(&params, &circuit).expect("keygen_vk should not fail");
+
+    let mut transcript = Blake2bRead::<_, G1Affine, Challenge255<_>>::init(proof.as_slice());
+    Ok(
+        verify_proof::<KZGCommitmentScheme<Bn256>, VerifierSHPLONK<_>, _, _, _>(
+            params.verifier_params(),
+            &vk,
+            SingleStrategy::new(&params),
+            &[&[&instances]],
+            &mut transcript,
+        )
+        .is_ok(),
+    )
+}
@@ -0,0 +1,259 @@
+//! A Merkle inclusion proof: the chip proves that a private leaf is in a tree
+//! of public root, given the siblings along its path, the stepping stone to
+//! the `merkle-circuit` crate, which hashes with the Poseidon and SHA2-256
+//! circuits instead.
+//!
+//! The hash is a toy one, `hash(x, y) = x^2 + x * y + 2 * y^2`, a single gate
+//! which is not collision resistant, and thus only fit for an example. It is
+//! not symmetric, unlike e.g. `x^2 + x * y + y^2`, so that the order of the
+//! children matters: every level has a bit telling whether the node is the
+//! right child of its parent, in which case the node and its sibling are
+//! swapped before hashing. Every row hashes a level, and the next row starts
+//! from its parent, copied:
+//!
+//! ```text
+//! | row       | node   | sibling | is_right | parent | q_hash | instance |
+//! | --------- | ------ | ------- | -------- | ------ | ------ | -------- |
+//! | 0         | leaf   | s_0     | b_0      | n_1    | 1      | root     |
+//! | 1         | n_1    | s_1     | b_1      | n_2    | 1      |          |
+//! | ...       |        |         |          |        |        |          |
+//! | depth - 1 | n_{-1} | s_{-1}  | b_{-1}   | root   | 1      |          |
+//! ```
+
+use std::marker::PhantomData;
+
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{AssignedCell, Layouter, Value},
+    plonk::{Advice, Column, ConstraintSystem, Error, Expression, Instance, Selector},
+    poly::Rotation,
+};
+
+use crate::cond_swap::cond_swap_expr;
+
+/// Row of the root in the instance column.
+pub const ROOT_ROW: usize = 0;
+
+#[derive(Clone, Copy, Debug)]
+pub struct MerkleConfig {
+    q_hash: Selector, // enabled at every level of the path.
+    node: Column<Advice>,
+    sibling: Column<Advice>,
+    is_right: Column<Advice>, // whether the node is the right child.
+    parent: Column<Advice>,
+    instance: Column<Instance>, // the root.
+}
+
+pub struct MerkleChip<F> {
+    config: MerkleConfig,
+    _marker: PhantomData<F>,
+}
+
+/// The toy hash of the children `left` and `right` of a node.
+pub fn toy_hash<F: FieldExt>(left: F, right: F) -> F {
+    left * left + left * right + F::from(2) * right * right
+}
+
+/// The root of the tree of `leaf`, given the path from it to the root: the
+/// sibling of every node and whether the node is the right child.
+pub fn merkle_root<F: FieldExt>(leaf: F, path: &[(F, bool)]) -> F {
+    path.iter().fold(leaf, |node, (sibling, is_right)| {
+        if *is_right {
+            toy_hash(*sibling, node)
+        } else {
+            toy_hash(node, *sibling)
+        }
+    })
+}
+
+impl MerkleConfig {
+    pub fn configure<F: FieldExt>(meta: &mut ConstraintSystem<F>) -> Self {
+        let q_hash = meta.selector();
+        let [node, sibling, is_right, parent] = [0; 4].map(|_| meta.advice_column());
+        let instance = meta.instance_column();
+        // The node is copied from the parent of the previous level, and the
+        // last parent to the instance column.
+        for column in [node, parent] {
+            meta.enable_equality(column);
+        }
+        meta.enable_equality(instance);
+
+        meta.create_gate("parent = hash(left, right)", |meta| {
+            let q_hash = meta.query_selector(q_hash);
+            let node = meta.query_advice(node, Rotation::cur());
+            let sibling = meta.query_advice(sibling, Rotation::cur());
+            let is_right = meta.query_advice(is_right, Rotation::cur());
+            let parent = meta.query_advice(parent, Rotation::cur());
+            let one = Expression::Constant(F::one());
+
+            // The children are selected by the bit, `left = node` and
+            // `right = sibling` if it is 0, swapped if it is 1, which only
+            // holds if the bit is boolean, see `cond_swap`.
+            let (left, right) = cond_swap_expr(is_right.clone(), node, sibling);
+            let hash = left.clone() * left.clone()
+                + left * right.clone()
+                + Expression::Constant(F::from(2)) * right.clone() * right;
+            vec![
+                q_hash.clone() * is_right.clone() * (one - is_right),
+                q_hash * (parent - hash),
+            ]
+        });
+
+        Self {
+            q_hash,
+            node,
+            sibling,
+            is_right,
+            parent,
+            instance,
+        }
+    }
+}
+
+impl<F: FieldExt> MerkleChip<F> {
+    pub fn new(config: MerkleConfig) -> Self {
+        Self {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Assigns the path from `leaf` to the root, one row per level, and
+    /// returns the cell of the root.
+    pub fn assign(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        leaf: Value<F>,
+        path: &[(Value<F>, Value<bool>)],
+    ) -> Result<AssignedCell<F, F>, Error> {
+        assert!(!path.is_empty(), "the tree has at least two leaves");
+        let config = self.config;
+        layouter.assign_region(
+            || "merkle path",
+            |mut region| {
+                let mut node = region.assign_advice(|| "leaf", config.node, 0, || leaf)?;
+                for (row, (sibling, is_right)) in path.iter().enumerate() {
+                    config.q_hash.enable(&mut region, row)?;
+                    if row > 0 {
+                        node = node.copy_advice(|| "node", &mut region, config.node, row)?;
+                    }
+                    region.assign_advice(|| "sibling", config.sibling, row, || *sibling)?;
+                    region.assign_advice(
+                        || "is_right",
+                        config.is_right,
+                        row,
+                        || is_right.map(|is_right| F::from(is_right as u64)),
+                    )?;
+
+                    let parent = node.value().zip(*sibling).zip(*is_right).map(
+                        |((node, sibling), is_right)| {
+                            if is_right {
+                                toy_hash(sibling, *node)
+                            } else {
+                                toy_hash(*node, sibling)
+                            }
+                        },
+                    );
+                    node = region.assign_advice(|| "parent", config.parent, row, || parent)?;
+                }
+                Ok(node)
+            },
+        )
+    }
+
+    /// Constrains `root` to the root row of the instance column.
+    pub fn expose_root(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        root: &AssignedCell<F, F>,
+    ) -> Result<(), Error> {
+        layouter.constrain_instance(root.cell(), self.config.instance, ROOT_ROW)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use halo2_proofs::{
+        circuit::SimpleFloorPlanner, dev::MockProver, halo2curves::bn256::Fr, plonk::Circuit,
+    };
+
+    #[derive(Default)]
+    struct TestCircuit<F> {
+        leaf: Value<F>,
+        path: Vec<(Value<F>, Value<bool>)>,
+    }
+
+    impl<F: FieldExt> TestCircuit<F> {
+        fn new(leaf: F, path: &[(F, bool)]) -> Self {
+            Self {
+                leaf: Value::known(leaf),
+                path: path
+                    .iter()
+                    .map(|(sibling, is_right)| (Value::known(*sibling), Value::known(*is_right)))
+                    .collect(),
+            }
+        }
+    }
+
+    impl<F: FieldExt> Circuit<F> for TestCircuit<F> {
+        type Config = MerkleConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        // The depth of the tree shapes the circuit, so it is kept.
+        fn without_witnesses(&self) -> Self {
+            Self {
+                leaf: Value::unknown(),
+                path: vec![(Value::unknown(), Value::unknown()); self.path.len()],
+            }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            MerkleConfig::configure(meta)
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            let chip = MerkleChip::new(config);
+            let root = chip.assign(&mut layouter, self.leaf, &self.path)?;
+            chip.expose_root(&mut layouter, &root)
+        }
+    }
+
+    #[test]
+    fn test_merkle_root() {
+        let [a, b, c, d] = [1, 2, 3, 4].map(Fr::from);
+        let root = toy_hash(toy_hash(a, b), toy_hash(c, d));
+        assert_eq!(merkle_root(a, &[(b, false), (toy_hash(c, d), false)]), root);
+        assert_eq!(merkle_root(d, &[(c, true), (toy_hash(a, b), true)]), root);
+        assert_ne!(toy_hash(a, b), toy_hash(b, a));
+    }
+
+    #[test]
+    fn test_merkle_circuit() {
+        let k = 4;
+        let leaf = Fr::from(7);
+        let path = [
+            (Fr::from(3), true),
+            (Fr::from(11), false),
+            (Fr::from(5), true),
+        ];
+        let root = merkle_root(leaf, &path);
+        let verify = |leaf: Fr, path: &[(Fr, bool)]| {
+            let circuit = TestCircuit::new(leaf, path);
+            MockProver::run(k, &circuit, vec![vec![root]])
+                .unwrap()
+                .verify()
+        };
+        assert_eq!(verify(leaf, &path), Ok(()));
+
+        // another leaf, and the leaf with a node swapped with its sibling.
+        assert!(verify(Fr::from(8), &path).is_err());
+        let mut swapped = path;
+        swapped[1].1 = true;
+        assert!(verify(leaf, &swapped).is_err());
+    }
+}
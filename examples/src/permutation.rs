@@ -0,0 +1,351 @@
+//! Two ways to prove a column is a permutation of another, depending on
+//! whether the permutation is known when the circuit is designed.
+//!
+//! The RIPEMD-160 rounds read the message words in orders fixed by the
+//! standard, e.g. `RHO`, so the permutation is part of the circuit: copy
+//! constraints link every word to its row in the permuted column, and are
+//! committed to in the verifying key like the gates.
+//!
+//! ```text
+//! | row | word | permuted     |
+//! | --- | ---- | ------------ |
+//! | 0   | w_0  | w_{RHO[0]}   |
+//! | 1   | w_1  | w_{RHO[1]}   |
+//! | ... |      |              |
+//! | 15  | w_15 | w_{RHO[15]}  |
+//! ```
+//!
+//! A sort of private values has a different permutation for every witness,
+//! which copy constraints cannot express without leaking it into the keys.
+//! A shuffle argument proves instead that the rows of the sorted column are
+//! those of the input column, as multisets, whatever their order, and a
+//! lookup of the differences of consecutive sorted values into a table of
+//! `[0, 2^BITS)` proves the order:
+//!
+//! ```text
+//! | row   | input   | sorted  | q_sort | q_next | table        |
+//! | ----- | ------- | ------- | ------ | ------ | ------------ |
+//! | 0     | v_0     | s_0     | 1      | 0      | 0            |
+//! | 1     | v_1     | s_1     | 1      | 1      | 1            |
+//! | ...   |         |         |        |        | ...          |
+//! | n - 1 | v_{n-1} | s_{n-1} | 1      | 1      | 2^BITS - 1   |
+//! ```
+//!
+//! The differences are bounded to `BITS` bits, which orders the bytes, but
+//! not the values of a field wrapping around: wider values need the range
+//! check of their limbs.
+
+use std::marker::PhantomData;
+
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{AssignedCell, Layouter, Value},
+    plonk::{Advice, Column, ConstraintSystem, Error, Selector, TableColumn},
+    poly::Rotation,
+};
+
+/// The permutation `ρ` of RIPEMD-160: the order of the message words of the
+/// rounds 16 to 31 of its left line, see `LEFT_WORDS` in the
+/// `ripemd160-circuit` crate.
+pub const RHO: [usize; 16] = [7, 4, 13, 1, 10, 6, 15, 3, 12, 0, 9, 5, 2, 14, 11, 8];
+
+/// Bits of the differences of consecutive sorted values.
+const BITS: usize = 8;
+
+#[derive(Clone, Copy, Debug)]
+pub struct PermutationConfig {
+    word: Column<Advice>,
+    permuted: Column<Advice>,
+}
+
+pub struct PermutationChip<F> {
+    config: PermutationConfig,
+    _marker: PhantomData<F>,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct SortConfig {
+    q_sort: Selector, // enabled at every row of the values.
+    q_next: Selector, // enabled at every row but the first one.
+    input: Column<Advice>,
+    sorted: Column<Advice>,
+    table: TableColumn,
+}
+
+pub struct SortChip<F> {
+    config: SortConfig,
+    _marker: PhantomData<F>,
+}
+
+impl PermutationConfig {
+    pub fn configure<F: FieldExt>(meta: &mut ConstraintSystem<F>) -> Self {
+        let word = meta.advice_column();
+        let permuted = meta.advice_column();
+        meta.enable_equality(word);
+        meta.enable_equality(permuted);
+        Self { word, permuted }
+    }
+}
+
+impl<F: FieldExt> PermutationChip<F> {
+    pub fn new(config: PermutationConfig) -> Self {
+        Self {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Assigns the `words` and the `permuted` words, and constrains every
+    /// row `i` of the latter equal to the word `permutation[i]`. A circuit
+    /// computing the permuted words would copy them there instead, with the
+    /// same constraints.
+    pub fn assign(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        words: &[Value<F>],
+        permuted: &[Value<F>],
+        permutation: &[usize],
+    ) -> Result<Vec<AssignedCell<F, F>>, Error> {
+        assert_eq!(words.len(), permutation.len());
+        assert_eq!(permuted.len(), permutation.len());
+        let config = self.config;
+        layouter.assign_region(
+            || "permutation",
+            |mut region| {
+                let words = words
+                    .iter()
+                    .enumerate()
+                    .map(|(row, word)| region.assign_advice(|| "word", config.word, row, || *word))
+                    .collect::<Result<Vec<_>, _>>()?;
+                permuted
+                    .iter()
+                    .zip(permutation)
+                    .enumerate()
+                    .map(|(row, (permuted, index))| {
+                        let permuted = region.assign_advice(
+                            || "permuted",
+                            config.permuted,
+                            row,
+                            || *permuted,
+                        )?;
+                        region.constrain_equal(words[*index].cell(), permuted.cell())?;
+                        Ok(permuted)
+                    })
+                    .collect()
+            },
+        )
+    }
+}
+
+impl SortConfig {
+    pub fn configure<F: FieldExt>(meta: &mut ConstraintSystem<F>) -> Self {
+        // The selectors are queried by the arguments, which do not accept
+        // simple selectors.
+        let q_sort = meta.complex_selector();
+        let q_next = meta.complex_selector();
+        let input = meta.advice_column();
+        let sorted = meta.advice_column();
+        let table = meta.lookup_table_column();
+
+        // The rows out of the values are `(0, 0)`, as many on both sides.
+        meta.shuffle("sorted is a permutation of input", |meta| {
+            let q_sort = meta.query_selector(q_sort);
+            let input = meta.query_advice(input, Rotation::cur());
+            let sorted = meta.query_advice(sorted, Rotation::cur());
+            vec![(q_sort.clone() * input, q_sort * sorted)]
+        });
+        meta.lookup("sorted - sorted_prev is in range", |meta| {
+            let q_next = meta.query_selector(q_next);
+            let sorted_prev = meta.query_advice(sorted, Rotation::prev());
+            let sorted = meta.query_advice(sorted, Rotation::cur());
+            vec![(q_next * (sorted - sorted_prev), table)]
+        });
+
+        Self {
+            q_sort,
+            q_next,
+            input,
+            sorted,
+            table,
+        }
+    }
+}
+
+impl<F: FieldExt> SortChip<F> {
+    pub fn new(config: SortConfig) -> Self {
+        Self {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Loads the table of the differences, once per circuit.
+    pub fn load_table(&self, layouter: &mut impl Layouter<F>) -> Result<(), Error> {
+        layouter.assign_table(
+            || "differences",
+            |mut table| {
+                for value in 0..1 << BITS {
+                    table.assign_cell(
+                        || "difference",
+                        self.config.table,
+                        value,
+                        || Value::known(F::from(value as u64)),
+                    )?;
+                }
+                Ok(())
+            },
+        )
+    }
+
+    /// Assigns the `values` and their sort, and returns the cells of the
+    /// latter.
+    pub fn assign(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        values: &[u8],
+    ) -> Result<Vec<AssignedCell<F, F>>, Error> {
+        let mut sorted = values.to_vec();
+        sorted.sort_unstable();
+        self.assign_rows(layouter, values, &sorted)
+    }
+
+    fn assign_rows(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        input: &[u8],
+        sorted: &[u8],
+    ) -> Result<Vec<AssignedCell<F, F>>, Error> {
+        let config = self.config;
+        layouter.assign_region(
+            || "sort",
+            |mut region| {
+                input
+                    .iter()
+                    .zip(sorted)
+                    .enumerate()
+                    .map(|(row, (input, sorted))| {
+                        config.q_sort.enable(&mut region, row)?;
+                        if row > 0 {
+                            config.q_next.enable(&mut region, row)?;
+                        }
+                        let [input, sorted] =
+                            [input, sorted].map(|v| Value::known(F::from(*v as u64)));
+                        region.assign_advice(|| "input", config.input, row, || input)?;
+                        region.assign_advice(|| "sorted", config.sorted, row, || sorted)
+                    })
+                    .collect()
+            },
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use halo2_proofs::{
+        circuit::SimpleFloorPlanner, dev::MockProver, halo2curves::bn256::Fr, plonk::Circuit,
+    };
+
+    #[derive(Default)]
+    struct PermutationCircuit<F> {
+        words: Vec<F>,
+        permuted: Vec<F>,
+    }
+
+    impl<F: FieldExt> Circuit<F> for PermutationCircuit<F> {
+        type Config = PermutationConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            PermutationConfig::configure(meta)
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            let [words, permuted] = [&self.words, &self.permuted]
+                .map(|v| v.iter().copied().map(Value::known).collect::<Vec<_>>());
+            PermutationChip::new(config).assign(&mut layouter, &words, &permuted, &RHO)?;
+            Ok(())
+        }
+    }
+
+    #[derive(Default)]
+    struct SortCircuit<F> {
+        input: Vec<u8>,
+        sorted: Vec<u8>,
+        _marker: PhantomData<F>,
+    }
+
+    impl<F: FieldExt> Circuit<F> for SortCircuit<F> {
+        type Config = SortConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            SortConfig::configure(meta)
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            let chip = SortChip::new(config);
+            chip.load_table(&mut layouter)?;
+            chip.assign_rows(&mut layouter, &self.input, &self.sorted)?;
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_permutation() {
+        let k = 5;
+        let words = (0..16).map(|i| Fr::from(i * i + 1)).collect::<Vec<_>>();
+        let verify = |permuted: Vec<Fr>| {
+            let circuit = PermutationCircuit {
+                words: words.clone(),
+                permuted,
+            };
+            MockProver::run(k, &circuit, vec![]).unwrap().verify()
+        };
+        let permuted = RHO.iter().map(|index| words[*index]).collect::<Vec<_>>();
+        assert_eq!(verify(permuted.clone()), Ok(()));
+
+        // the words in another order, and a word replaced.
+        let mut swapped = permuted.clone();
+        swapped.swap(0, 1);
+        assert!(verify(swapped).is_err());
+        let mut replaced = permuted;
+        replaced[3] = Fr::from(1000);
+        assert!(verify(replaced).is_err());
+    }
+
+    #[test]
+    fn test_sort() {
+        let k = 9;
+        let verify = |input: &[u8], sorted: &[u8]| {
+            let circuit = SortCircuit::<Fr> {
+                input: input.to_vec(),
+                sorted: sorted.to_vec(),
+                _marker: PhantomData,
+            };
+            MockProver::run(k, &circuit, vec![]).unwrap().verify()
+        };
+        let input = [3, 255, 1, 0, 1, 42];
+        assert_eq!(verify(&input, &[0, 1, 1, 3, 42, 255]), Ok(()));
+
+        // sorted but not a permutation, and a permutation not sorted.
+        assert!(verify(&input, &[0, 1, 3, 3, 42, 255]).is_err());
+        assert!(verify(&input, &[0, 1, 3, 1, 42, 255]).is_err());
+    }
+}
@@ -0,0 +1,190 @@
+//! A range check by a lookup argument, the pattern every hash circuit of the
+//! workspace is built on: their words are split into bytes, spread forms or
+//! limbs, each looked up in a fixed table of the values it may take, so that
+//! a single lookup bounds a value which a gate would only bound by a
+//! polynomial of a degree as large as its range.
+//!
+//! The chip constrains values to `bits` bits. The fixed table holds every
+//! value in `[0, 2^bits)`, loaded once by the prover and committed to in the
+//! verifying key, and every row of the advice column whose selector is
+//! enabled must be one of the rows of the table:
+//!
+//! ```text
+//! | row | value | q_lookup | table      |
+//! | --- | ----- | -------- | ---------- |
+//! | 0   | v0    | 1        | 0          |
+//! | 1   | v1    | 1        | 1          |
+//! | 2   |       | 0        | 2          |
+//! | ... |       |          | ...        |
+//! |     |       |          | 2^bits - 1 |
+//! ```
+
+use std::marker::PhantomData;
+
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{AssignedCell, Layouter, Value},
+    plonk::{Advice, Column, ConstraintSystem, Error, Selector, TableColumn},
+    poly::Rotation,
+};
+
+#[derive(Clone, Copy, Debug)]
+pub struct RangeCheckConfig {
+    q_lookup: Selector, // enabled at every row of a checked value.
+    value: Column<Advice>,
+    table: TableColumn, // every value in `[0, 2^bits)`.
+    bits: usize,
+}
+
+pub struct RangeCheckChip<F> {
+    config: RangeCheckConfig,
+    _marker: PhantomData<F>,
+}
+
+impl RangeCheckConfig {
+    pub fn configure<F: FieldExt>(meta: &mut ConstraintSystem<F>, bits: usize) -> Self {
+        // The selector of a lookup is a complex selector: it is queried in
+        // the input expression of the lookup, which the optimisation of
+        // simple selectors, combining them into fixed columns, does not
+        // support.
+        let q_lookup = meta.complex_selector();
+        let value = meta.advice_column();
+        let table = meta.lookup_table_column();
+        meta.enable_equality(value);
+
+        // The input of the lookup is `q_lookup * value`: at the rows where the
+        // selector is disabled, it is zero, which the table holds, so that
+        // the lookup constrains the checked values only.
+        meta.lookup("value is in range", |meta| {
+            let q_lookup = meta.query_selector(q_lookup);
+            let value = meta.query_advice(value, Rotation::cur());
+            vec![(q_lookup * value, table)]
+        });
+
+        Self {
+            q_lookup,
+            value,
+            table,
+            bits,
+        }
+    }
+}
+
+impl<F: FieldExt> RangeCheckChip<F> {
+    pub fn new(config: RangeCheckConfig) -> Self {
+        Self {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Loads the table of every value in `[0, 2^bits)`, once per circuit
+    /// however many values are checked. A table needs `2^bits` rows, which
+    /// bounds `bits` below the degree of the circuit.
+    pub fn load_table(&self, layouter: &mut impl Layouter<F>) -> Result<(), Error> {
+        let config = self.config;
+        layouter.assign_table(
+            || format!("{}-bit range table", config.bits),
+            |mut table| {
+                for value in 0..(1u64 << config.bits) {
+                    table.assign_cell(
+                        || "value",
+                        config.table,
+                        value as usize,
+                        || Value::known(F::from(value)),
+                    )?;
+                }
+                Ok(())
+            },
+        )
+    }
+
+    /// Assigns `values`, one row each, all constrained to `bits` bits, and
+    /// returns their cells, to be copied to the gates using them.
+    pub fn assign(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        values: &[u64],
+    ) -> Result<Vec<AssignedCell<F, F>>, Error> {
+        let config = self.config;
+        layouter.assign_region(
+            || "range check",
+            |mut region| {
+                values
+                    .iter()
+                    .enumerate()
+                    .map(|(row, value)| {
+                        config.q_lookup.enable(&mut region, row)?;
+                        region.assign_advice(
+                            || "value",
+                            config.value,
+                            row,
+                            || Value::known(F::from(*value)),
+                        )
+                    })
+                    .collect()
+            },
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use halo2_proofs::{
+        circuit::SimpleFloorPlanner, dev::MockProver, halo2curves::bn256::Fr, plonk::Circuit,
+    };
+
+    const BITS: usize = 4;
+
+    #[derive(Default)]
+    struct TestCircuit<F> {
+        values: Vec<u64>,
+        _marker: PhantomData<F>,
+    }
+
+    impl<F: FieldExt> Circuit<F> for TestCircuit<F> {
+        type Config = RangeCheckConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            RangeCheckConfig::configure(meta, BITS)
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            let chip = RangeCheckChip::new(config);
+            chip.load_table(&mut layouter)?;
+            chip.assign(&mut layouter, &self.values)?;
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_range_check() {
+        let k = 6;
+        let circuit = TestCircuit::<Fr> {
+            values: vec![0, 1, 7, 15],
+            _marker: PhantomData,
+        };
+        let prover = MockProver::run(k, &circuit, vec![]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+
+        // the first value out of range, and larger ones.
+        for value in [16, 255, u64::MAX] {
+            let circuit = TestCircuit::<Fr> {
+                values: vec![3, value],
+                _marker: PhantomData,
+            };
+            let prover = MockProver::run(k, &circuit, vec![]).unwrap();
+            assert!(prover.verify().is_err(), "{value}");
+        }
+    }
+}
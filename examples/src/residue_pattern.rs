@@ -1,8 +1,8 @@
 use halo2_proofs::{
     halo2curves::bn256::Fr,
     arithmetic::{FieldExt, Field},
-    circuit::{Layouter, Region, Value},
-    plonk::{Advice, Column, ConstraintSystem, Error, Expression, Fixed, Selector},
+    circuit::{AssignedCell, Layouter, Region, Value},
+    plonk::{Advice, Column, ConstraintSystem, Error, Expression, Fixed, Instance, Selector},
     poly::Rotation,
 };
 
@@ -10,16 +10,20 @@ use halo2_proofs::{
 pub struct ResiduePatternConfig {
     always_enabled: Selector, // This selector is always enabled to avoid ConstraintPoisoned errors.
     index_is_nonzero: Selector, // enabled iff index column is not zero.
+    q_first: Selector,        // enabled at the first row of every value, where index is zero.
+    q_last: Selector,         // enabled at the last row of every value, that of its final pattern.
     index: Column<Fixed>,     // repeats [0..length)
+    length: usize,            // rows of every value, i.e. bits of its pattern.
 
     value: Column<Advice>,       // value we're computing residue pattern for
     is_residue: Column<Advice>,  // binary column that is 1 iff value + index is a quadratic residue
     pattern: Column<Advice>,     // built up bit by bit from is_residue
     square_root: Column<Advice>, // square root of value + index if its a residue or nonresidue * (value + index) otherwise.
+
+    instance: Column<Instance>, // the final pattern of every value, one per row.
 }
 
 pub struct ResiduePatternChip<F> {
-    length: usize,
     nonresidue: F,
     config: ResiduePatternConfig,
 }
@@ -31,10 +35,44 @@ pub fn residue_pattern(x: Fr) -> u64 {
 }
 
 impl ResiduePatternConfig {
-    pub fn configure<F: FieldExt>(meta: &mut ConstraintSystem<F>, nonresidue: F) -> Self {
-        let [always_enabled, index_is_nonzero] = [0; 2].map(|_| meta.selector());
+    pub fn configure<F: FieldExt>(
+        meta: &mut ConstraintSystem<F>,
+        nonresidue: F,
+        length: usize,
+    ) -> Self {
+        assert!(length > 0, "the pattern has at least one bit");
+        let [always_enabled, index_is_nonzero, q_first, q_last] = [0; 4].map(|_| meta.selector());
         let index = meta.fixed_column();
         let [value, is_residue, pattern, square_root] = [0; 4].map(|_| meta.advice_column());
+        let instance = meta.instance_column();
+        meta.enable_equality(pattern);
+        meta.enable_equality(instance);
+
+        // The index is fixed, and thus committed to in the verifying key, but
+        // the gates below only hold if it wraps around to 0 at the first row
+        // of every value, counts up from there, and reaches length - 1 at its
+        // last row, which the selectors mark.
+        meta.create_gate("index is 0 at the first row", |meta| {
+            let q_first = meta.query_selector(q_first);
+            let index = meta.query_fixed(index, Rotation::cur());
+            vec![q_first * index]
+        });
+
+        meta.create_gate("index is length - 1 at the last row", |meta| {
+            let q_last = meta.query_selector(q_last);
+            let index = meta.query_fixed(index, Rotation::cur());
+            vec![q_last * (index - Expression::Constant(F::from(length as u64 - 1)))]
+        });
+
+        meta.create_gate("index increments if index_is_nonzero", |meta| {
+            let index_is_nonzero = meta.query_selector(index_is_nonzero);
+            let index_current = meta.query_fixed(index, Rotation::cur());
+            let index_previous = meta.query_fixed(index, Rotation::prev());
+            vec![
+                index_is_nonzero
+                    * (index_current - index_previous - Expression::Constant(F::one())),
+            ]
+        });
 
         meta.create_gate("value does not change if index is non-zero", |meta| {
             let index = meta.query_fixed(index, Rotation::cur());
@@ -78,6 +116,16 @@ impl ResiduePatternConfig {
             },
         );
 
+        // Without it, the pattern of the first row would be free, and the
+        // final pattern off by any multiple of 2^(length - 1), e.g. carry on
+        // from the pattern of the previous value.
+        meta.create_gate("pattern = is_residue at the first row", |meta| {
+            let q_first = meta.query_selector(q_first);
+            let is_residue = meta.query_advice(is_residue, Rotation::cur());
+            let pattern = meta.query_advice(pattern, Rotation::cur());
+            vec![q_first * (pattern - is_residue)]
+        });
+
         meta.create_gate(
             "current pattern = is_residue + 2 * previous pattern",
             |meta| {
@@ -96,50 +144,97 @@ impl ResiduePatternConfig {
 
         Self {
             index,
+            length,
             value,
             is_residue,
             pattern,
             square_root,
             index_is_nonzero,
             always_enabled,
+            q_first,
+            q_last,
+            instance,
         }
     }
 }
 
 impl<F: FieldExt> ResiduePatternChip<F> {
-    pub fn assign(&self, layouter: &mut impl Layouter<F>, values: &[F]) -> Result<Vec<u64>, Error> {
+    pub fn new(config: ResiduePatternConfig, nonresidue: F) -> Self {
+        Self { nonresidue, config }
+    }
+
+    /// Assigns the residue pattern of every value, `length` rows each, and
+    /// returns the cells of their final patterns.
+    pub fn assign(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        values: &[F],
+    ) -> Result<Vec<AssignedCell<F, F>>, Error> {
         layouter.assign_region(
             || "residue_pattern",
             |mut region| {
-                let mut patterns = vec![];
-                let mut offset = 0;
-                for value in values.iter() {
-                    patterns.push(self.assign_value(&mut region, offset, *value)?);
-                    offset += self.length;
-                }
-                Ok(patterns)
+                self.load_index(&mut region, values.len())?;
+                values
+                    .iter()
+                    .enumerate()
+                    .map(|(i, value)| {
+                        self.assign_value(&mut region, i * self.config.length, *value)
+                    })
+                    .collect()
             },
         )
     }
 
+    /// Constrains the final pattern of the `i`-th value to the `i`-th row of
+    /// the instance column.
+    pub fn expose_patterns(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        patterns: &[AssignedCell<F, F>],
+    ) -> Result<(), Error> {
+        for (row, pattern) in patterns.iter().enumerate() {
+            layouter.constrain_instance(pattern.cell(), self.config.instance, row)?;
+        }
+        Ok(())
+    }
+
+    /// Loads the index column, `0..length` repeated for each of `num_values`
+    /// values, and enables the selectors of their rows, so that the fixed
+    /// columns only depend on the parameters of the circuit.
+    fn load_index(&self, region: &mut Region<'_, F>, num_values: usize) -> Result<(), Error> {
+        let config = self.config;
+        for offset in 0..num_values * config.length {
+            let index = offset % config.length;
+            config.always_enabled.enable(region, offset)?;
+            if index == 0 {
+                config.q_first.enable(region, offset)?;
+            } else {
+                config.index_is_nonzero.enable(region, offset)?;
+            }
+            if index == config.length - 1 {
+                config.q_last.enable(region, offset)?;
+            }
+            region.assign_fixed(
+                || "index",
+                config.index,
+                offset,
+                || Value::known(F::from(index as u64)),
+            )?;
+        }
+        Ok(())
+    }
+
     fn assign_value(
         &self,
         region: &mut Region<'_, F>,
         offset: usize,
         value: F,
-    ) -> Result<u64, Error> {
+    ) -> Result<AssignedCell<F, F>, Error> {
         let config = self.config;
         let mut pattern = 0;
-        let mut offset = offset;
-        for index in 0u64..self.length.try_into().unwrap() {
-            config.always_enabled.enable(region, offset)?;
-            if index != 0 {
-                config.index_is_nonzero.enable(region, offset)?;
-            }
-
+        let mut pattern_cell = None;
+        for (offset, index) in (offset..).zip(0..config.length as u64) {
             let index = F::from(index);
-            region.assign_fixed(|| "index", config.index, offset, || Value::known(index))?;
-
             region.assign_advice(|| "value", config.value, offset, || Value::known(value))?;
 
             let (is_residue, square_root) =
@@ -160,12 +255,12 @@ impl<F: FieldExt> ResiduePatternChip<F> {
             )?;
 
             pattern = 2 * pattern + u64::from(is_residue);
-            region.assign_advice(
+            pattern_cell = Some(region.assign_advice(
                 || "pattern",
                 config.pattern,
                 offset,
                 || Value::known(F::from(pattern)),
-            )?;
+            )?);
 
             region.assign_advice(
                 || "square_root",
@@ -173,10 +268,8 @@ impl<F: FieldExt> ResiduePatternChip<F> {
                 offset,
                 || Value::known(square_root),
             )?;
-
-            offset += 1;
         }
-        Ok(pattern)
+        Ok(pattern_cell.expect("the pattern has at least one bit"))
     }
 }
 
@@ -189,19 +282,18 @@ mod tests {
     };
 
     #[derive(Default)]
-    struct TestCircuit<F> {
+    struct TestCircuit<F, const LENGTH: usize> {
         values: Vec<F>,
-        length: usize,
         nonresidue: F,
     }
 
-    impl<F: FieldExt> TestCircuit<F> {
+    impl<F: FieldExt, const LENGTH: usize> TestCircuit<F, LENGTH> {
         fn nonresidue() -> F {
             F::from(5)
         }
     }
 
-    impl<F: FieldExt> Circuit<F> for TestCircuit<F> {
+    impl<F: FieldExt, const LENGTH: usize> Circuit<F> for TestCircuit<F, LENGTH> {
         type Config = ResiduePatternConfig;
         type FloorPlanner = SimpleFloorPlanner;
 
@@ -210,7 +302,7 @@ mod tests {
         }
 
         fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
-            ResiduePatternConfig::configure(meta, Self::nonresidue())
+            ResiduePatternConfig::configure(meta, Self::nonresidue(), LENGTH)
         }
 
         fn synthesize(
@@ -218,13 +310,72 @@ mod tests {
             config: Self::Config,
             mut layouter: impl Layouter<F>,
         ) -> Result<(), Error> {
-            let chip = ResiduePatternChip {
-                config,
-                length: self.length,
-                nonresidue: self.nonresidue,
-            };
-            chip.assign(&mut layouter, &self.values)?;
-            Ok(())
+            let chip = ResiduePatternChip::new(config, self.nonresidue);
+            let patterns = chip.assign(&mut layouter, &self.values)?;
+            chip.expose_patterns(&mut layouter, &patterns)
+        }
+    }
+
+    /// Assigns the values as the chip does, then adds `2^i * offset` to the
+    /// pattern at index `i` of the `value`-th value, which satisfies the
+    /// accumulation of the pattern at every row but the first.
+    struct MaliciousCircuit {
+        circuit: TestCircuit<Fr, 64>,
+        value: usize,
+        offset: Fr,
+    }
+
+    impl Circuit<Fr> for MaliciousCircuit {
+        type Config = ResiduePatternConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self {
+                circuit: TestCircuit {
+                    values: vec![],
+                    nonresidue: self.circuit.nonresidue,
+                },
+                value: self.value,
+                offset: Fr::zero(),
+            }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+            TestCircuit::<Fr, 64>::configure(meta)
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<Fr>,
+        ) -> Result<(), Error> {
+            let chip = ResiduePatternChip::new(config, self.circuit.nonresidue);
+            let values = &self.circuit.values;
+            let patterns = layouter.assign_region(
+                || "residue_pattern",
+                |mut region| {
+                    chip.load_index(&mut region, values.len())?;
+                    let mut patterns = values
+                        .iter()
+                        .enumerate()
+                        .map(|(i, value)| chip.assign_value(&mut region, i * 64, *value))
+                        .collect::<Result<Vec<_>, _>>()?;
+
+                    let pattern = residue_pattern(values[self.value]);
+                    for index in 0..64 {
+                        let forged = Fr::from(pattern >> (63 - index))
+                            + self.offset * Fr::from(1 << index);
+                        patterns[self.value] = region.assign_advice(
+                            || "pattern",
+                            config.pattern,
+                            self.value * 64 + index,
+                            || Value::known(forged),
+                        )?;
+                    }
+                    Ok(patterns)
+                },
+            )?;
+            chip.expose_patterns(&mut layouter, &patterns)
         }
     }
 
@@ -247,21 +398,72 @@ mod tests {
     #[test]
     fn test_nonresidue() {
         assert_eq!(
-            Option::<Fr>::from(TestCircuit::<Fr>::nonresidue().sqrt()),
+            Option::<Fr>::from(TestCircuit::<Fr, 64>::nonresidue().sqrt()),
             None
         );
     }
 
     #[test]
     fn test_residue_pattern_circuit() {
-        let circuit = TestCircuit {
-            values: vec![0.into(), 2323.into(), 124123123.into(), 3.into()],
-            length: 64,
-            nonresidue: TestCircuit::<Fr>::nonresidue(),
+        let values: Vec<Fr> = vec![0.into(), 2323.into(), 124123123.into(), 3.into()];
+        let mut patterns: Vec<Fr> = values
+            .iter()
+            .map(|value| Fr::from(residue_pattern(*value)))
+            .collect();
+        let circuit = TestCircuit::<Fr, 64> {
+            values,
+            nonresidue: TestCircuit::<Fr, 64>::nonresidue(),
         };
 
         let k = 10;
-        let prover = MockProver::run(k, &circuit, vec![]).unwrap();
+        let prover = MockProver::run(k, &circuit, vec![patterns.clone()]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+
+        // a public pattern is off by one.
+        patterns[2] += Fr::one();
+        let prover = MockProver::run(k, &circuit, vec![patterns]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+
+    #[test]
+    fn test_residue_pattern_first_row() {
+        let values: Vec<Fr> = vec![0.into(), 2323.into()];
+        let patterns: Vec<Fr> = values
+            .iter()
+            .map(|value| Fr::from(residue_pattern(*value)))
+            .collect();
+        // the pattern of a value starts from an arbitrary offset, or carries on
+        // from that of the previous value, as it would at any other row.
+        for (value, offset) in [(0, Fr::one()), (1, patterns[0].double())] {
+            let circuit = MaliciousCircuit {
+                circuit: TestCircuit {
+                    values: values.clone(),
+                    nonresidue: TestCircuit::<Fr, 64>::nonresidue(),
+                },
+                value,
+                offset,
+            };
+            let mut forged = patterns.clone();
+            forged[value] += offset * Fr::from(1 << 63);
+            let prover = MockProver::run(8, &circuit, vec![forged]).unwrap();
+            assert!(prover.verify().is_err(), "{value}");
+        }
+    }
+
+    #[test]
+    fn test_residue_pattern_length() {
+        // a pattern of 8 bits is made of the first 8 bits of the full one.
+        let values: Vec<Fr> = vec![0.into(), 2323.into(), 3.into()];
+        let patterns = values
+            .iter()
+            .map(|value| Fr::from(residue_pattern(*value) >> 56))
+            .collect();
+        let circuit = TestCircuit::<Fr, 8> {
+            values,
+            nonresidue: TestCircuit::<Fr, 8>::nonresidue(),
+        };
+
+        let prover = MockProver::run(6, &circuit, vec![patterns]).unwrap();
         assert_eq!(prover.verify(), Ok(()));
     }
 }
@@ -0,0 +1,246 @@
+//! A random linear combination (RLC) of a byte string, the encoding of the
+//! variable-length inputs and outputs in the tables of the hash circuits:
+//! `rlc(b_0, ..., b_{n-1}) = b_0 * r^(n-1) + ... + b_{n-2} * r + b_{n-1}`,
+//! a single cell which is equal for two strings, but for a negligible
+//! probability, if and only if the strings are.
+//!
+//! The RLC is only binding if the prover cannot choose the bytes knowing
+//! `r`, so `r` is a challenge: the circuit is proved in two phases, the
+//! prover commits to the columns of the first phase, those of the bytes,
+//! then `r` is drawn from the transcript, and the columns of the second
+//! phase, those of the RLCs, are assigned with it. For the same reason, the
+//! public string cannot be given as its RLC, which nobody knows before the
+//! proof: the verifier gives its bytes, copied from the instance column, and
+//! the circuit folds them with the same challenge as the private ones.
+//!
+//! ```text
+//! | row   | byte    | public  | rlc       | public_rlc | instance | q_first | q_next |
+//! | ----- | ------- | ------- | --------- | ---------- | -------- | ------- | ------ |
+//! | 0     | b_0     | p_0     | rlc_0     | prlc_0     | p_0      | 1       | 0      |
+//! | 1     | b_1     | p_1     | rlc_1     | prlc_1     | p_1      | 0       | 1      |
+//! | ...   |         |         |           |            |          |         |        |
+//! | n - 1 | b_{n-1} | p_{n-1} | rlc_{n-1} | prlc_{n-1} | p_{n-1}  | 0       | 1      |
+//! ```
+//!
+//! and `rlc_{n-1} = prlc_{n-1}`. The bytes are not range checked, see the
+//! `range_check` example for the lookup that would.
+
+use std::marker::PhantomData;
+
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{AssignedCell, Layouter, Value},
+    plonk::{
+        Advice, Challenge, Column, ConstraintSystem, Error, FirstPhase, Instance, SecondPhase,
+        Selector,
+    },
+    poly::Rotation,
+};
+
+#[derive(Clone, Copy, Debug)]
+pub struct RlcConfig {
+    q_first: Selector, // enabled at the first row of the string.
+    q_next: Selector,  // enabled at every other row of the string.
+    byte: Column<Advice>,
+    public: Column<Advice>, // the bytes of the instance column, copied.
+    rlc: Column<Advice>,
+    public_rlc: Column<Advice>,
+    instance: Column<Instance>,
+    challenge: Challenge,
+}
+
+pub struct RlcChip<F> {
+    config: RlcConfig,
+    _marker: PhantomData<F>,
+}
+
+/// The RLC of `bytes` with the challenge `r`, out-of-circuit.
+pub fn rlc<F: FieldExt>(bytes: &[u8], r: Value<F>) -> Value<F> {
+    bytes.iter().fold(Value::known(F::zero()), |acc, byte| {
+        acc * r + Value::known(F::from(*byte as u64))
+    })
+}
+
+impl RlcConfig {
+    pub fn configure<F: FieldExt>(meta: &mut ConstraintSystem<F>) -> Self {
+        let q_first = meta.selector();
+        let q_next = meta.selector();
+        // The columns are in the first phase unless put in another one, and
+        // a challenge usable after a phase is drawn once the columns of the
+        // phase are committed to, so it is created after them.
+        let byte = meta.advice_column_in(FirstPhase);
+        let public = meta.advice_column_in(FirstPhase);
+        let challenge = meta.challenge_usable_after(FirstPhase);
+        let rlc = meta.advice_column_in(SecondPhase);
+        let public_rlc = meta.advice_column_in(SecondPhase);
+        let instance = meta.instance_column();
+        for column in [public, rlc, public_rlc] {
+            meta.enable_equality(column);
+        }
+        meta.enable_equality(instance);
+
+        // Both RLCs accumulate along the rows, `rlc = byte` at the first row
+        // and `rlc = rlc_prev * r + byte` at the next ones, the challenge
+        // being queried as a constant of the gates.
+        meta.create_gate("rlc = byte", |meta| {
+            let q_first = meta.query_selector(q_first);
+            [(rlc, byte), (public_rlc, public)]
+                .map(|(rlc, byte)| {
+                    let rlc = meta.query_advice(rlc, Rotation::cur());
+                    let byte = meta.query_advice(byte, Rotation::cur());
+                    q_first.clone() * (rlc - byte)
+                })
+                .to_vec()
+        });
+        meta.create_gate("rlc = rlc_prev * r + byte", |meta| {
+            let q_next = meta.query_selector(q_next);
+            let r = meta.query_challenge(challenge);
+            [(rlc, byte), (public_rlc, public)]
+                .map(|(rlc, byte)| {
+                    let rlc_prev = meta.query_advice(rlc, Rotation::prev());
+                    let rlc = meta.query_advice(rlc, Rotation::cur());
+                    let byte = meta.query_advice(byte, Rotation::cur());
+                    q_next.clone() * (rlc - (rlc_prev * r.clone() + byte))
+                })
+                .to_vec()
+        });
+
+        Self {
+            q_first,
+            q_next,
+            byte,
+            public,
+            rlc,
+            public_rlc,
+            instance,
+            challenge,
+        }
+    }
+}
+
+impl<F: FieldExt> RlcChip<F> {
+    pub fn new(config: RlcConfig) -> Self {
+        Self {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Assigns the RLC of the private `bytes` and of as many bytes of the
+    /// instance column, and constrains them equal. Returns the cell of the
+    /// RLC, e.g. to copy into a table.
+    pub fn assign(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        bytes: &[u8],
+    ) -> Result<AssignedCell<F, F>, Error> {
+        assert!(!bytes.is_empty(), "the string has a first byte");
+        let config = self.config;
+        // The challenge is unknown while the first phase is synthesized, and
+        // so are the cells of the second phase assigned from it.
+        let r = layouter.get_challenge(config.challenge);
+        layouter.assign_region(
+            || "rlc",
+            |mut region| {
+                let mut rlc = Value::known(F::zero());
+                let mut public_rlc = Value::known(F::zero());
+                let mut cells = None;
+                for (row, byte) in bytes.iter().enumerate() {
+                    if row == 0 {
+                        config.q_first.enable(&mut region, row)?;
+                    } else {
+                        config.q_next.enable(&mut region, row)?;
+                    }
+                    let byte = Value::known(F::from(*byte as u64));
+                    region.assign_advice(|| "byte", config.byte, row, || byte)?;
+                    let public = region.assign_advice_from_instance(
+                        || "public",
+                        config.instance,
+                        row,
+                        config.public,
+                        row,
+                    )?;
+
+                    rlc = rlc * r + byte;
+                    public_rlc = public_rlc * r + public.value().copied();
+                    cells = Some((
+                        region.assign_advice(|| "rlc", config.rlc, row, || rlc)?,
+                        region.assign_advice(
+                            || "public_rlc",
+                            config.public_rlc,
+                            row,
+                            || public_rlc,
+                        )?,
+                    ));
+                }
+                let (rlc, public_rlc) = cells.expect("the string has a first byte");
+                region.constrain_equal(rlc.cell(), public_rlc.cell())?;
+                Ok(rlc)
+            },
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use halo2_proofs::{
+        arithmetic::Field, circuit::SimpleFloorPlanner, dev::MockProver, halo2curves::bn256::Fr,
+        plonk::Circuit,
+    };
+
+    #[derive(Default)]
+    struct TestCircuit<F> {
+        bytes: Vec<u8>,
+        _marker: PhantomData<F>,
+    }
+
+    impl<F: FieldExt> Circuit<F> for TestCircuit<F> {
+        type Config = RlcConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            RlcConfig::configure(meta)
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            RlcChip::new(config).assign(&mut layouter, &self.bytes)?;
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_rlc() {
+        let r = Value::known(Fr::from(256));
+        rlc(&[1, 2, 3], r).assert_if_known(|rlc| *rlc == Fr::from(0x010203));
+        rlc(&[0, 1], r).assert_if_known(|rlc| *rlc == Fr::one());
+    }
+
+    #[test]
+    fn test_rlc_circuit() {
+        let k = 5;
+        let verify = |bytes: &[u8], public: &[u8]| {
+            let circuit = TestCircuit::<Fr> {
+                bytes: bytes.to_vec(),
+                _marker: PhantomData,
+            };
+            let instance = public.iter().map(|byte| Fr::from(*byte as u64)).collect();
+            MockProver::run(k, &circuit, vec![instance])
+                .unwrap()
+                .verify()
+        };
+        assert_eq!(verify(b"abc", b"abc"), Ok(()));
+
+        // the bytes are swapped, which a sum of the bytes would not catch.
+        assert!(verify(b"abc", b"acb").is_err());
+        assert!(verify(b"abc", b"abd").is_err());
+    }
+}
@@ -0,0 +1,229 @@
+//! The same gate, `c = a * b`, enabled by a `Selector` and by a flag in a
+//! `Column<Fixed>`, the choice every gate of the hash circuits makes.
+//!
+//! Both are fixed by the circuit, committed to in the verifying key, and
+//! multiply the constraint, so that the gate only holds where they are 1,
+//! at the same degree: a gate of degree 2 in the advice cells is of degree
+//! 3 with either. They differ by what they are before the keys are
+//! generated:
+//! - a `Selector` is a virtual column, a bit per row, turned into fixed
+//!   columns by `keygen_vk`. The simple selectors, those of the gates only,
+//!   are compressed there: the selectors never enabled on the same row share
+//!   a fixed column, each its own value in it, as long as the polynomial
+//!   selecting a value keeps the gates below the degree of the circuit. A
+//!   circuit of many selectors thus costs far fewer fixed columns, and thus
+//!   fewer commitments and openings, than it declares. The selectors of the
+//!   lookups are complex ones, which are not compressed, a lookup of a
+//!   simple selector being rejected;
+//! - a fixed flag is a column of its own, whatever the other flags, but a
+//!   column of any values: it can be queried at another row, e.g. a flag of
+//!   the last row of a block queried from its first row, scale the gate by
+//!   a coefficient, or be the input of a lookup.
+//!
+//! A gate enabled on rows which no other gate shares thus takes a selector,
+//! and a gate needing the values or the rotations of its flag a fixed
+//! column. `config_stats` counts the columns and the selectors of a
+//! circuit, before the compression of its selectors, which the
+//! `selector_vs_fixed` example prints for both flags:
+//!
+//! ```text
+//! | row | a   | b   | c         | q_mul |
+//! | --- | --- | --- | --------- | ----- |
+//! | 0   | a_0 | b_0 | a_0 * b_0 | 1     |
+//! | 1   | a_1 | b_1 | a_1 * b_1 | 1     |
+//! ```
+
+use std::{fmt, marker::PhantomData};
+
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{Layouter, Region, SimpleFloorPlanner, Value},
+    plonk::{
+        Advice, Circuit, Column, ConstraintSystem, Error, Expression, Fixed, Selector, VirtualCells,
+    },
+    poly::Rotation,
+};
+
+/// The flag enabling a gate at a row, a `Selector` or a `Column<Fixed>`.
+pub trait Flag: Copy + fmt::Debug {
+    fn configure<F: FieldExt>(meta: &mut ConstraintSystem<F>) -> Self;
+
+    fn query<F: FieldExt>(&self, meta: &mut VirtualCells<'_, F>) -> Expression<F>;
+
+    fn enable<F: FieldExt>(&self, region: &mut Region<'_, F>, offset: usize) -> Result<(), Error>;
+}
+
+impl Flag for Selector {
+    fn configure<F: FieldExt>(meta: &mut ConstraintSystem<F>) -> Self {
+        meta.selector()
+    }
+
+    fn query<F: FieldExt>(&self, meta: &mut VirtualCells<'_, F>) -> Expression<F> {
+        meta.query_selector(*self)
+    }
+
+    fn enable<F: FieldExt>(&self, region: &mut Region<'_, F>, offset: usize) -> Result<(), Error> {
+        Selector::enable(self, region, offset)
+    }
+}
+
+impl Flag for Column<Fixed> {
+    fn configure<F: FieldExt>(meta: &mut ConstraintSystem<F>) -> Self {
+        meta.fixed_column()
+    }
+
+    fn query<F: FieldExt>(&self, meta: &mut VirtualCells<'_, F>) -> Expression<F> {
+        meta.query_fixed(*self, Rotation::cur())
+    }
+
+    fn enable<F: FieldExt>(&self, region: &mut Region<'_, F>, offset: usize) -> Result<(), Error> {
+        region.assign_fixed(|| "q_mul", *self, offset, || Value::known(F::one()))?;
+        Ok(())
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct MulConfig<Q> {
+    q_mul: Q,
+    a: Column<Advice>,
+    b: Column<Advice>,
+    c: Column<Advice>,
+}
+
+impl<Q: Flag> MulConfig<Q> {
+    pub fn configure<F: FieldExt>(meta: &mut ConstraintSystem<F>) -> Self {
+        let q_mul = Q::configure(meta);
+        let [a, b, c] = [0; 3].map(|_| meta.advice_column());
+        meta.create_gate("c = a * b", |meta| {
+            let q_mul = q_mul.query(meta);
+            let [a, b, c] = [a, b, c].map(|column| meta.query_advice(column, Rotation::cur()));
+            vec![q_mul * (a * b - c)]
+        });
+        Self { q_mul, a, b, c }
+    }
+}
+
+/// The rows `(a, b, c)` checked by the gate enabled by the flag `Q`.
+pub struct MulCircuit<F, Q> {
+    pub rows: Vec<(F, F, F)>,
+    pub _marker: PhantomData<Q>,
+}
+
+impl<F: FieldExt, Q: Flag> Circuit<F> for MulCircuit<F, Q> {
+    type Config = MulConfig<Q>;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self {
+            rows: self.rows.clone(),
+            _marker: PhantomData,
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        MulConfig::configure(meta)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        layouter.assign_region(
+            || "mul",
+            |mut region| {
+                for (offset, (a, b, c)) in self.rows.iter().enumerate() {
+                    config.q_mul.enable(&mut region, offset)?;
+                    for (column, value) in [(config.a, a), (config.b, b), (config.c, c)] {
+                        region.assign_advice(|| "cell", column, offset, || Value::known(*value))?;
+                    }
+                }
+                Ok(())
+            },
+        )
+    }
+}
+
+/// The columns, the selectors and the degree of the constraint system of a
+/// circuit, its selectors not compressed yet.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ConfigStats {
+    pub advice_columns: usize,
+    pub fixed_columns: usize,
+    pub selectors: usize,
+    pub degree: usize,
+}
+
+impl fmt::Display for ConfigStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "advice columns: {}, fixed columns: {}, selectors: {}, degree: {}",
+            self.advice_columns, self.fixed_columns, self.selectors, self.degree
+        )
+    }
+}
+
+/// The stats of the constraint system `C` configures.
+pub fn config_stats<F: FieldExt, C: Circuit<F>>() -> ConfigStats {
+    let mut meta = ConstraintSystem::default();
+    C::configure(&mut meta);
+    ConfigStats {
+        advice_columns: meta.num_advice_columns(),
+        fixed_columns: meta.num_fixed_columns(),
+        selectors: meta.num_selectors(),
+        degree: meta.degree(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use halo2_proofs::{dev::MockProver, halo2curves::bn256::Fr};
+
+    fn verify<Q: Flag>(rows: &[(u64, u64, u64)]) -> bool {
+        let circuit = MulCircuit::<Fr, Q> {
+            rows: rows
+                .iter()
+                .map(|(a, b, c)| (Fr::from(*a), Fr::from(*b), Fr::from(*c)))
+                .collect(),
+            _marker: PhantomData,
+        };
+        MockProver::run(4, &circuit, vec![])
+            .unwrap()
+            .verify()
+            .is_ok()
+    }
+
+    #[test]
+    fn test_flags() {
+        // a wrong product fails whichever the flag.
+        for verify in [verify::<Selector>, verify::<Column<Fixed>>] {
+            assert!(verify(&[(2, 3, 6), (4, 5, 20)]));
+            assert!(!verify(&[(2, 3, 6), (4, 5, 21)]));
+        }
+    }
+
+    #[test]
+    fn test_config_stats() {
+        let selector = config_stats::<Fr, MulCircuit<Fr, Selector>>();
+        let fixed = config_stats::<Fr, MulCircuit<Fr, Column<Fixed>>>();
+        assert_eq!(
+            selector,
+            ConfigStats {
+                advice_columns: 3,
+                fixed_columns: 0,
+                selectors: 1,
+                degree: 3,
+            }
+        );
+        assert_eq!(
+            fixed,
+            ConfigStats {
+                fixed_columns: 1,
+                selectors: 0,
+                ..selector
+            }
+        );
+    }
+}
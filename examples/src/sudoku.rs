@@ -0,0 +1,380 @@
+//! A solved Sudoku, proven against its public puzzle with a lookup and copy
+//! constraints together: the lookup bounds every cell, and the copies lay
+//! the same cells out in the orders of the rows, the columns and the boxes
+//! of the grid, which a gate then checks every group of 9 cells of.
+//!
+//! The cells are assigned in the order of the rows of the grid, each with
+//! its mask `2^(cell - 1)`, the pair looked up into a table of the digits
+//! and their masks. The masks of the 9 cells of a group sum to `2^9 - 1`
+//! exactly when the cells are different, every digit then being in its
+//! group once: 9 powers of two sum to a value of 9 bits set only if they
+//! are different, as adding two equal powers carries. The table also holds
+//! `(0, 0)`, the input of the lookup out of the grid, which a cell may then
+//! take, though 8 powers of two never set 9 bits.
+//!
+//! ```text
+//! | row | cell | mask_row | mask_column | mask_box | acc_* | q_first | q_last | puzzle |
+//! | --- | ---- | -------- | ----------- | -------- | ----- | ------- | ------ | ------ |
+//! | 0   | s_00 | m_00     | m_00        | m_00     | m     | 1       | 0      | p_00   |
+//! | 1   | s_01 | m_01     | m_10        | m_01     | ...   | 0       | 0      | p_01   |
+//! | ... |      |          |             |          |       |         |        |        |
+//! | 8   | s_08 | m_08     | m_80        | m_22     | 511   | 0       | 1      | p_08   |
+//! | 9   | s_10 | m_10     | m_01        | m_03     | m     | 1       | 0      | p_10   |
+//! | ... |      |          |             |          |       |         |        |        |
+//! ```
+//!
+//! Every `acc` is the running sum of the masks of a group, which starts
+//! over every 9 rows. A cell of the puzzle other than 0 is a given, which
+//! the cell of the solution must equal.
+
+use std::marker::PhantomData;
+
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{AssignedCell, Layouter, Value},
+    plonk::{Advice, Column, ConstraintSystem, Error, Expression, Instance, Selector, TableColumn},
+    poly::Rotation,
+};
+
+/// A grid of digits, 0 for a blank cell of a puzzle.
+pub type Grid = [[u8; 9]; 9];
+
+/// The index of the `i`-th cell of the row, the column and the box `g`,
+/// in the order of the rows of the grid.
+const ORDERS: [fn(usize, usize) -> usize; 3] = [
+    |g, i| 9 * g + i,
+    |g, i| 9 * i + g,
+    |g, i| 9 * (3 * (g / 3) + i / 3) + 3 * (g % 3) + i % 3,
+];
+
+#[derive(Clone, Copy, Debug)]
+pub struct SudokuConfig {
+    q_cell: Selector,  // enabled at every cell.
+    q_first: Selector, // enabled at the first cell of every group.
+    q_next: Selector,  // enabled at every other cell.
+    q_last: Selector,  // enabled at the last cell of every group.
+    cell: Column<Advice>,
+    masks: [Column<Advice>; 3], // by row, by column and by box.
+    accs: [Column<Advice>; 3],
+    pub puzzle: Column<Instance>, // the cells of the puzzle, see `puzzle_instance`.
+    digit: TableColumn,
+    mask: TableColumn,
+}
+
+pub struct SudokuChip<F> {
+    config: SudokuConfig,
+    _marker: PhantomData<F>,
+}
+
+/// Whether `solution` solves `puzzle`, out-of-circuit.
+pub fn solves(puzzle: &Grid, solution: &Grid) -> bool {
+    let agrees = puzzle
+        .iter()
+        .flatten()
+        .zip(solution.iter().flatten())
+        .all(|(given, cell)| *given == 0 || given == cell);
+    let cells = solution.iter().flatten().collect::<Vec<_>>();
+    let groups = ORDERS.iter().all(|order| {
+        (0..9).all(|g| {
+            let mut digits = (0..9).map(|i| *cells[order(g, i)]).collect::<Vec<_>>();
+            digits.sort_unstable();
+            digits == [1, 2, 3, 4, 5, 6, 7, 8, 9]
+        })
+    });
+    agrees && groups
+}
+
+/// The instance column of `puzzle`, in the order of the rows of the grid.
+pub fn puzzle_instance<F: FieldExt>(puzzle: &Grid) -> Vec<F> {
+    puzzle
+        .iter()
+        .flatten()
+        .map(|given| F::from(*given as u64))
+        .collect()
+}
+
+/// The mask of a digit, `2^(digit - 1)`, and 0 for a blank.
+fn mask(digit: u8) -> u64 {
+    if digit == 0 {
+        0
+    } else {
+        1 << (digit - 1)
+    }
+}
+
+impl SudokuConfig {
+    pub fn configure<F: FieldExt>(meta: &mut ConstraintSystem<F>) -> Self {
+        // The selector of the lookup is a complex selector, queried in its
+        // input expressions.
+        let q_cell = meta.complex_selector();
+        let q_first = meta.selector();
+        let q_next = meta.selector();
+        let q_last = meta.selector();
+        let cell = meta.advice_column();
+        let masks = [0; 3].map(|_| meta.advice_column());
+        let accs = [0; 3].map(|_| meta.advice_column());
+        let puzzle = meta.instance_column();
+        let digit = meta.lookup_table_column();
+        let mask = meta.lookup_table_column();
+        for column in masks {
+            meta.enable_equality(column);
+        }
+
+        meta.lookup("mask = 2^(cell - 1)", |meta| {
+            let q_cell = meta.query_selector(q_cell);
+            let cell = meta.query_advice(cell, Rotation::cur());
+            let mask_row = meta.query_advice(masks[0], Rotation::cur());
+            vec![(q_cell.clone() * cell, digit), (q_cell * mask_row, mask)]
+        });
+        meta.create_gate("cell = given", |meta| {
+            let q_cell = meta.query_selector(q_cell);
+            let cell = meta.query_advice(cell, Rotation::cur());
+            let given = meta.query_instance(puzzle, Rotation::cur());
+            vec![q_cell * given.clone() * (cell - given)]
+        });
+
+        meta.create_gate("acc = mask", |meta| {
+            let q_first = meta.query_selector(q_first);
+            (0..3)
+                .map(|order| {
+                    let mask = meta.query_advice(masks[order], Rotation::cur());
+                    let acc = meta.query_advice(accs[order], Rotation::cur());
+                    q_first.clone() * (acc - mask)
+                })
+                .collect::<Vec<_>>()
+        });
+        meta.create_gate("acc = acc_prev + mask", |meta| {
+            let q_next = meta.query_selector(q_next);
+            (0..3)
+                .map(|order| {
+                    let mask = meta.query_advice(masks[order], Rotation::cur());
+                    let acc_prev = meta.query_advice(accs[order], Rotation::prev());
+                    let acc = meta.query_advice(accs[order], Rotation::cur());
+                    q_next.clone() * (acc - (acc_prev + mask))
+                })
+                .collect::<Vec<_>>()
+        });
+        meta.create_gate("acc = 2^9 - 1", |meta| {
+            let q_last = meta.query_selector(q_last);
+            let all = Expression::Constant(F::from(511));
+            (0..3)
+                .map(|order| {
+                    let acc = meta.query_advice(accs[order], Rotation::cur());
+                    q_last.clone() * (acc - all.clone())
+                })
+                .collect::<Vec<_>>()
+        });
+
+        Self {
+            q_cell,
+            q_first,
+            q_next,
+            q_last,
+            cell,
+            masks,
+            accs,
+            puzzle,
+            digit,
+            mask,
+        }
+    }
+}
+
+impl<F: FieldExt> SudokuChip<F> {
+    pub fn new(config: SudokuConfig) -> Self {
+        Self {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Loads the table of the digits and of their masks, once per circuit.
+    pub fn load_table(&self, layouter: &mut impl Layouter<F>) -> Result<(), Error> {
+        let config = self.config;
+        layouter.assign_table(
+            || "digits",
+            |mut table| {
+                for digit in 0..=9 {
+                    for (column, value) in
+                        [(config.digit, digit as u64), (config.mask, mask(digit))]
+                    {
+                        table.assign_cell(
+                            || "digit",
+                            column,
+                            digit as usize,
+                            || Value::known(F::from(value)),
+                        )?;
+                    }
+                }
+                Ok(())
+            },
+        )
+    }
+
+    /// Assigns the cells of `solution`, checked against the puzzle of the
+    /// instance column, and returns their cells, in the order of the rows
+    /// of the grid.
+    pub fn assign(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        solution: &Grid,
+    ) -> Result<Vec<AssignedCell<F, F>>, Error> {
+        let config = self.config;
+        let cells = solution.iter().flatten().copied().collect::<Vec<_>>();
+        layouter.assign_region(
+            || "sudoku",
+            |mut region| {
+                let mut assigned = vec![];
+                let mut masks = vec![];
+                for (row, cell) in cells.iter().enumerate() {
+                    config.q_cell.enable(&mut region, row)?;
+                    let [cell, mask] =
+                        [*cell as u64, mask(*cell)].map(|v| Value::known(F::from(v)));
+                    assigned.push(region.assign_advice(|| "cell", config.cell, row, || cell)?);
+                    masks.push(region.assign_advice(|| "mask", config.masks[0], row, || mask)?);
+                }
+
+                // The masks of the groups of every order, copied from those
+                // of their cells but for the order of the rows.
+                for (k, order) in ORDERS.iter().enumerate() {
+                    let mut acc = Value::known(F::zero());
+                    for row in 0..81 {
+                        let (g, i) = (row / 9, row % 9);
+                        let mask = &masks[order(g, i)];
+                        if i == 0 {
+                            acc = Value::known(F::zero());
+                        }
+                        acc = acc + mask.value();
+                        if k > 0 {
+                            mask.copy_advice(|| "mask", &mut region, config.masks[k], row)?;
+                        }
+                        region.assign_advice(|| "acc", config.accs[k], row, || acc)?;
+                    }
+                }
+
+                for row in 0..81 {
+                    match row % 9 {
+                        0 => config.q_first.enable(&mut region, row)?,
+                        8 => {
+                            config.q_next.enable(&mut region, row)?;
+                            config.q_last.enable(&mut region, row)?
+                        }
+                        _ => config.q_next.enable(&mut region, row)?,
+                    }
+                }
+                Ok(assigned)
+            },
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use halo2_proofs::{
+        circuit::SimpleFloorPlanner, dev::MockProver, halo2curves::bn256::Fr, plonk::Circuit,
+    };
+
+    #[derive(Default)]
+    struct TestCircuit<F> {
+        solution: Grid,
+        _marker: PhantomData<F>,
+    }
+
+    impl<F: FieldExt> Circuit<F> for TestCircuit<F> {
+        type Config = SudokuConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            SudokuConfig::configure(meta)
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            let chip = SudokuChip::new(config);
+            chip.load_table(&mut layouter)?;
+            chip.assign(&mut layouter, &self.solution)?;
+            Ok(())
+        }
+    }
+
+    const PUZZLE: Grid = [
+        [5, 3, 0, 0, 7, 0, 0, 0, 0],
+        [6, 0, 0, 1, 9, 5, 0, 0, 0],
+        [0, 9, 8, 0, 0, 0, 0, 6, 0],
+        [8, 0, 0, 0, 6, 0, 0, 0, 3],
+        [4, 0, 0, 8, 0, 3, 0, 0, 1],
+        [7, 0, 0, 0, 2, 0, 0, 0, 6],
+        [0, 6, 0, 0, 0, 0, 2, 8, 0],
+        [0, 0, 0, 4, 1, 9, 0, 0, 5],
+        [0, 0, 0, 0, 8, 0, 0, 7, 9],
+    ];
+
+    const SOLUTION: Grid = [
+        [5, 3, 4, 6, 7, 8, 9, 1, 2],
+        [6, 7, 2, 1, 9, 5, 3, 4, 8],
+        [1, 9, 8, 3, 4, 2, 5, 6, 7],
+        [8, 5, 9, 7, 6, 1, 4, 2, 3],
+        [4, 2, 6, 8, 5, 3, 7, 9, 1],
+        [7, 1, 3, 9, 2, 4, 8, 5, 6],
+        [9, 6, 1, 5, 3, 7, 2, 8, 4],
+        [2, 8, 7, 4, 1, 9, 6, 3, 5],
+        [3, 4, 5, 2, 8, 6, 1, 7, 9],
+    ];
+
+    /// Whether the circuit accepts `solution` of `puzzle`, checking the
+    /// native check agrees.
+    fn verify(puzzle: &Grid, solution: Grid) -> bool {
+        let circuit = TestCircuit::<Fr> {
+            solution,
+            _marker: PhantomData,
+        };
+        let prover = MockProver::run(7, &circuit, vec![puzzle_instance(puzzle)]).unwrap();
+        let verified = prover.verify().is_ok();
+        assert_eq!(verified, solves(puzzle, &solution));
+        verified
+    }
+
+    #[test]
+    fn test_sudoku() {
+        assert!(verify(&PUZZLE, SOLUTION));
+
+        // a solution of another puzzle, the digits 1 and 2 swapped.
+        let swapped =
+            SOLUTION.map(|row| row.map(|cell| [0, 2, 1, 3, 4, 5, 6, 7, 8, 9][cell as usize]));
+        assert!(verify(&[[0; 9]; 9], swapped));
+        assert!(!verify(&PUZZLE, swapped));
+    }
+
+    #[test]
+    fn test_not_sudoku() {
+        // a latin square, every row and column holding every digit once,
+        // but not the boxes.
+        let mut latin = [[0; 9]; 9];
+        for (i, row) in latin.iter_mut().enumerate() {
+            for (j, cell) in row.iter_mut().enumerate() {
+                *cell = ((i + j) % 9 + 1) as u8;
+            }
+        }
+        assert!(!verify(&[[0; 9]; 9], latin));
+
+        // two cells of a row swapped, breaking their columns, a digit
+        // repeated, and a blank.
+        let mut swapped = SOLUTION;
+        swapped[0].swap(2, 3);
+        assert!(!verify(&[[0; 9]; 9], swapped));
+        let mut repeated = SOLUTION;
+        repeated[8][8] = 8;
+        assert!(!verify(&[[0; 9]; 9], repeated));
+        let mut blank = SOLUTION;
+        blank[4][4] = 0;
+        assert!(!verify(&[[0; 9]; 9], blank));
+    }
+}
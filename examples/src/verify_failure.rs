@@ -0,0 +1,228 @@
+//! Negative tests which check why a bad witness is rejected, not only that
+//! it is: `assert!(prover.verify().is_err())` also holds for a witness the
+//! test lays out wrong, e.g. a cell left unassigned, or rejected by another
+//! constraint than the one under test, so that a constraint may be dropped
+//! or weakened without any test failing. The `VerifyFailure`s returned by
+//! the `MockProver` name the constraint, the region and the offset of the
+//! row of every failure, which the tests below match.
+//!
+//! The chip is a running product, `c = a * b` on every row, with the `a` of
+//! every row but the first one copied from the `c` of the previous row, so
+//! that a bad witness breaks either a gate or a copy:
+//!
+//! ```text
+//! | row   | a       | b       | c       | q_mul |
+//! | ----- | ------- | ------- | ------- | ----- |
+//! | 0     | a       | b_0     | c_0     | 1     |
+//! | 1     | c_0     | b_1     | c_1     | 1     |
+//! | ...   |         |         |         |       |
+//! | n - 1 | c_{n-2} | b_{n-1} | c_{n-1} | 1     |
+//! ```
+
+use std::marker::PhantomData;
+
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{AssignedCell, Layouter, Value},
+    plonk::{Advice, Column, ConstraintSystem, Error, Selector},
+    poly::Rotation,
+};
+
+#[derive(Clone, Copy, Debug)]
+pub struct ProductConfig {
+    q_mul: Selector,
+    a: Column<Advice>,
+    b: Column<Advice>,
+    c: Column<Advice>,
+}
+
+pub struct ProductChip<F> {
+    config: ProductConfig,
+    _marker: PhantomData<F>,
+}
+
+impl ProductConfig {
+    pub fn configure<F: FieldExt>(meta: &mut ConstraintSystem<F>) -> Self {
+        let q_mul = meta.selector();
+        let [a, b, c] = [0; 3].map(|_| meta.advice_column());
+        meta.enable_equality(a);
+        meta.enable_equality(c);
+
+        // The constraint is named, to tell it from the other constraints of
+        // the gate in the failures.
+        meta.create_gate("mul", |meta| {
+            let q_mul = meta.query_selector(q_mul);
+            let a = meta.query_advice(a, Rotation::cur());
+            let b = meta.query_advice(b, Rotation::cur());
+            let c = meta.query_advice(c, Rotation::cur());
+            vec![("c = a * b", q_mul * (c - a * b))]
+        });
+
+        Self { q_mul, a, b, c }
+    }
+}
+
+impl<F: FieldExt> ProductChip<F> {
+    pub fn new(config: ProductConfig) -> Self {
+        Self {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Assigns the product of `a` and of the `bs`, a row per factor, and
+    /// returns its cell.
+    pub fn assign(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        a: Value<F>,
+        bs: &[Value<F>],
+    ) -> Result<AssignedCell<F, F>, Error> {
+        let mut rows = vec![];
+        let mut c = a;
+        for b in bs {
+            let a = c;
+            c = a * *b;
+            rows.push((a, *b, c));
+        }
+        self.assign_rows(layouter, &rows)
+    }
+
+    /// Assigns the `(a, b, c)` of every row.
+    fn assign_rows(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        rows: &[(Value<F>, Value<F>, Value<F>)],
+    ) -> Result<AssignedCell<F, F>, Error> {
+        assert!(!rows.is_empty(), "the product has a first factor");
+        let config = self.config;
+        layouter.assign_region(
+            || "product",
+            |mut region| {
+                let mut prev_c: Option<AssignedCell<F, F>> = None;
+                for (row, (a, b, c)) in rows.iter().enumerate() {
+                    config.q_mul.enable(&mut region, row)?;
+                    let a = region.assign_advice(|| "a", config.a, row, || *a)?;
+                    if let Some(prev_c) = prev_c {
+                        region.constrain_equal(prev_c.cell(), a.cell())?;
+                    }
+                    region.assign_advice(|| "b", config.b, row, || *b)?;
+                    prev_c = Some(region.assign_advice(|| "c", config.c, row, || *c)?);
+                }
+                Ok(prev_c.expect("the product has a first factor"))
+            },
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use halo2_proofs::{
+        circuit::SimpleFloorPlanner,
+        dev::{metadata, FailureLocation, MockProver, VerifyFailure},
+        halo2curves::bn256::Fr,
+        plonk::{Any, Circuit},
+    };
+
+    #[derive(Default)]
+    struct TestCircuit<F> {
+        rows: Vec<(F, F, F)>,
+    }
+
+    impl<F: FieldExt> Circuit<F> for TestCircuit<F> {
+        type Config = ProductConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            ProductConfig::configure(meta)
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            let rows = self
+                .rows
+                .iter()
+                .map(|(a, b, c)| (Value::known(*a), Value::known(*b), Value::known(*c)))
+                .collect::<Vec<_>>();
+            ProductChip::new(config).assign_rows(&mut layouter, &rows)?;
+            Ok(())
+        }
+    }
+
+    /// The rows of the product of `2 * 3 * 5 * 7`.
+    fn rows() -> Vec<(Fr, Fr, Fr)> {
+        [(2, 3, 6), (6, 5, 30), (30, 7, 210)]
+            .map(|(a, b, c)| (Fr::from(a), Fr::from(b), Fr::from(c)))
+            .to_vec()
+    }
+
+    fn verify(rows: Vec<(Fr, Fr, Fr)>) -> Result<(), Vec<VerifyFailure>> {
+        MockProver::run(4, &TestCircuit { rows }, vec![])
+            .unwrap()
+            .verify()
+    }
+
+    #[test]
+    fn test_product() {
+        assert_eq!(verify(rows()), Ok(()));
+    }
+
+    #[test]
+    fn test_gate_failure() {
+        // the product of the row 1 is wrong, and the row 2 is computed from
+        // it, so that only the row 1 fails.
+        let mut rows = rows();
+        rows[1].2 = Fr::from(31);
+        rows[2] = (Fr::from(31), Fr::from(7), Fr::from(217));
+        let failures = verify(rows).unwrap_err();
+
+        // The constraint is the 0th of the gate 0, the region the 0th one.
+        // The values of the cells of the constraint are left out, as they
+        // only help to debug.
+        let expected_constraint: metadata::Constraint = ((0, "mul").into(), 0, "c = a * b").into();
+        let expected_region: metadata::Region = (0, "product").into();
+        match &failures[..] {
+            [VerifyFailure::ConstraintNotSatisfied {
+                constraint,
+                location: FailureLocation::InRegion { region, offset },
+                ..
+            }] => {
+                assert_eq!(*constraint, expected_constraint);
+                assert_eq!(*region, expected_region);
+                assert_eq!(*offset, 1);
+            }
+            failures => panic!("unexpected failures: {failures:?}"),
+        }
+    }
+
+    #[test]
+    fn test_copy_failure() {
+        // the `a` of the row 2 is not the `c` of the row 1, though the gate
+        // of the row holds.
+        let mut rows = rows();
+        rows[2] = (Fr::from(31), Fr::from(7), Fr::from(217));
+        let failures = verify(rows).unwrap_err();
+
+        // A copy is reported at a cell of it, with its column, `c` being the
+        // advice column 2, and may be at the other cell as well.
+        let expected = VerifyFailure::Permutation {
+            column: (Any::advice(), 2).into(),
+            location: FailureLocation::InRegion {
+                region: (0, "product").into(),
+                offset: 1,
+            },
+        };
+        assert!(failures
+            .iter()
+            .all(|failure| matches!(failure, VerifyFailure::Permutation { .. })));
+        assert!(failures.contains(&expected), "{failures:?}");
+    }
+}
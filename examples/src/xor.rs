@@ -0,0 +1,304 @@
+//! The XOR of two words by a lookup into a table of the XOR of every pair
+//! of bytes, as the rounds of BLAKE2 and RIPEMD-160 XOR their words: a
+//! polynomial gate of the XOR exists for bits only, `a + b - 2ab`, which
+//! would take a row per bit.
+//!
+//! The words are decomposed into bytes, most significant first, in running
+//! sums as in `decompose`, one for each of `a`, `b` and `c = a ^ b`, and
+//! every row looks its three bytes up in the table, which range checks the
+//! bytes too:
+//!
+//! ```text
+//! | row   | a_limb  | b_limb  | c_limb  | a_acc | b_acc | c_acc | q_first | q_next |
+//! | ----- | ------- | ------- | ------- | ----- | ----- | ----- | ------- | ------ |
+//! | 0     | a_0     | b_0     | c_0     | a_0   | b_0   | c_0   | 1       | 0      |
+//! | ...   |         |         |         |       |       |       | 0       | 1      |
+//! | n - 1 | a_{n-1} | b_{n-1} | c_{n-1} | a     | b     | c     | 0       | 1      |
+//! ```
+//!
+//! A word of RIPEMD-160 takes 4 rows, and one of BLAKE2 8. The table has
+//! `2^16` rows, and so `k >= 17`: a circuit of fewer rows would use a table
+//! of nibbles, of `2^8` rows, at twice the rows per word.
+
+use std::marker::PhantomData;
+
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{AssignedCell, Layouter, Value},
+    plonk::{Advice, Column, ConstraintSystem, Error, Expression, Selector, TableColumn},
+    poly::Rotation,
+};
+
+use crate::decompose::limbs;
+
+#[derive(Clone, Copy, Debug)]
+pub struct XorConfig {
+    q_first: Selector, // enabled at the first byte.
+    q_next: Selector,  // enabled at every other byte.
+    limbs: [Column<Advice>; 3],
+    accs: [Column<Advice>; 3],
+    table: [TableColumn; 3], // `(lhs, rhs, lhs ^ rhs)` for every pair of bytes.
+}
+
+pub struct XorChip<F> {
+    config: XorConfig,
+    _marker: PhantomData<F>,
+}
+
+impl XorConfig {
+    pub fn configure<F: FieldExt>(meta: &mut ConstraintSystem<F>) -> Self {
+        // The selectors are queried by the lookup, which does not accept
+        // simple selectors.
+        let q_first = meta.complex_selector();
+        let q_next = meta.complex_selector();
+        let limbs = [0; 3].map(|_| meta.advice_column());
+        let accs = [0; 3].map(|_| {
+            let column = meta.advice_column();
+            meta.enable_equality(column);
+            column
+        });
+        let table = [0; 3].map(|_| meta.lookup_table_column());
+
+        meta.create_gate("acc = limb", |meta| {
+            let q_first = meta.query_selector(q_first);
+            limbs
+                .iter()
+                .zip(accs.iter())
+                .map(|(limb, acc)| {
+                    let limb = meta.query_advice(*limb, Rotation::cur());
+                    let acc = meta.query_advice(*acc, Rotation::cur());
+                    q_first.clone() * (acc - limb)
+                })
+                .collect::<Vec<_>>()
+        });
+        meta.create_gate("acc = acc_prev * 2^8 + limb", |meta| {
+            let q_next = meta.query_selector(q_next);
+            let radix = Expression::Constant(F::from(256));
+            limbs
+                .iter()
+                .zip(accs.iter())
+                .map(|(limb, acc)| {
+                    let limb = meta.query_advice(*limb, Rotation::cur());
+                    let acc_prev = meta.query_advice(*acc, Rotation::prev());
+                    let acc = meta.query_advice(*acc, Rotation::cur());
+                    q_next.clone() * (acc - (acc_prev * radix.clone() + limb))
+                })
+                .collect::<Vec<_>>()
+        });
+        meta.lookup("c_limb = a_limb ^ b_limb", |meta| {
+            let q_limb = meta.query_selector(q_first) + meta.query_selector(q_next);
+            limbs
+                .iter()
+                .zip(table)
+                .map(|(limb, column)| {
+                    let limb = meta.query_advice(*limb, Rotation::cur());
+                    (q_limb.clone() * limb, column)
+                })
+                .collect()
+        });
+
+        Self {
+            q_first,
+            q_next,
+            limbs,
+            accs,
+            table,
+        }
+    }
+}
+
+impl<F: FieldExt> XorChip<F> {
+    pub fn new(config: XorConfig) -> Self {
+        Self {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Loads the table of the XOR of the bytes, once per circuit.
+    pub fn load_table(&self, layouter: &mut impl Layouter<F>) -> Result<(), Error> {
+        let table = self.config.table;
+        layouter.assign_table(
+            || "xor",
+            |mut table_region| {
+                for lhs in 0..256u64 {
+                    for rhs in 0..256u64 {
+                        let row = (lhs * 256 + rhs) as usize;
+                        for (column, value) in table.iter().zip([lhs, rhs, lhs ^ rhs]) {
+                            table_region.assign_cell(
+                                || "xor",
+                                *column,
+                                row,
+                                || Value::known(F::from(value)),
+                            )?;
+                        }
+                    }
+                }
+                Ok(())
+            },
+        )
+    }
+
+    /// Assigns the XOR of the words `a` and `b` of `num_bytes` bytes, and
+    /// returns the cells of `a`, `b` and `a ^ b`.
+    pub fn assign(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        a: u64,
+        b: u64,
+        num_bytes: usize,
+    ) -> Result<[AssignedCell<F, F>; 3], Error> {
+        assert!(num_bytes <= 8);
+        let [a, b, c] = [a, b, a ^ b].map(|word| {
+            limbs(word, 8, num_bytes)
+                .into_iter()
+                .map(F::from)
+                .collect::<Vec<_>>()
+        });
+        self.assign_rows(layouter, [&a, &b, &c])
+    }
+
+    /// Assigns the bytes given, most significant first, and returns the
+    /// cells of the words they recompose.
+    fn assign_rows(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        words: [&[F]; 3],
+    ) -> Result<[AssignedCell<F, F>; 3], Error> {
+        assert!(!words[0].is_empty(), "the words have a first byte");
+        let config = self.config;
+        let radix = F::from(256);
+        layouter.assign_region(
+            || "xor",
+            |mut region| {
+                let mut accs = [F::zero(); 3];
+                let mut cells = vec![];
+                for row in 0..words[0].len() {
+                    if row == 0 {
+                        config.q_first.enable(&mut region, row)?;
+                    } else {
+                        config.q_next.enable(&mut region, row)?;
+                    }
+                    cells.clear();
+                    for (i, word) in words.iter().enumerate() {
+                        let limb = word[row];
+                        region.assign_advice(
+                            || "limb",
+                            config.limbs[i],
+                            row,
+                            || Value::known(limb),
+                        )?;
+                        accs[i] = accs[i] * radix + limb;
+                        cells.push(region.assign_advice(
+                            || "acc",
+                            config.accs[i],
+                            row,
+                            || Value::known(accs[i]),
+                        )?);
+                    }
+                }
+                Ok(cells.try_into().expect("a cell per word"))
+            },
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use halo2_proofs::{
+        arithmetic::Field,
+        circuit::SimpleFloorPlanner,
+        dev::{MockProver, VerifyFailure},
+        halo2curves::bn256::Fr,
+        plonk::{Circuit, Instance},
+    };
+
+    /// XORs the bytes given, and exposes the words `a`, `b` and `c` as the
+    /// instances.
+    #[derive(Default)]
+    struct TestCircuit<F> {
+        words: [Vec<F>; 3],
+    }
+
+    impl<F: FieldExt> Circuit<F> for TestCircuit<F> {
+        type Config = (XorConfig, Column<Instance>);
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            let config = XorConfig::configure(meta);
+            let instance = meta.instance_column();
+            meta.enable_equality(instance);
+            (config, instance)
+        }
+
+        fn synthesize(
+            &self,
+            (config, instance): Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            let chip = XorChip::new(config);
+            chip.load_table(&mut layouter)?;
+            let [a, b, c] = &self.words;
+            let cells = chip.assign_rows(&mut layouter, [a, b, c])?;
+            for (row, cell) in cells.iter().enumerate() {
+                layouter.constrain_instance(cell.cell(), instance, row)?;
+            }
+            Ok(())
+        }
+    }
+
+    fn circuit(a: u32, b: u32) -> TestCircuit<Fr> {
+        TestCircuit {
+            words: [a, b, a ^ b]
+                .map(|word| limbs(word as u64, 8, 4).into_iter().map(Fr::from).collect()),
+        }
+    }
+
+    fn verify(circuit: &TestCircuit<Fr>, c: u32) -> Result<(), Vec<VerifyFailure>> {
+        let recompose = |word: &[Fr]| {
+            word.iter()
+                .fold(Fr::zero(), |acc, byte| acc * Fr::from(256) + byte)
+        };
+        let [a, b, _] = &circuit.words;
+        let instance = vec![recompose(a), recompose(b), Fr::from(c as u64)];
+        MockProver::run(17, circuit, vec![instance])
+            .unwrap()
+            .verify()
+    }
+
+    #[test]
+    fn test_xor() {
+        for (a, b) in [(0, 0), (0x0123_4567, 0x89ab_cdef), (u32::MAX, 0x5a5a_5a5a)] {
+            assert_eq!(verify(&circuit(a, b), a ^ b), Ok(()));
+        }
+
+        // the XOR of the other bytes.
+        let mut wrong = circuit(0x0123_4567, 0x89ab_cdef);
+        wrong.words[2] = circuit(0x0123_4567, 0x89ab_cdee).words[2].clone();
+        assert!(verify(&wrong, 0x0123_4567 ^ 0x89ab_cdee).is_err());
+    }
+
+    #[test]
+    fn test_limb_out_of_range() {
+        // `c_2 - 1` and `c_3 + 256` recompose into the same `c`, so that
+        // only the lookups catch them, that of the last byte being out of
+        // the table.
+        let (a, b) = (0x0123_4567, 0x89ab_cdef);
+        let mut forged = circuit(a, b);
+        forged.words[2][2] -= Fr::one();
+        forged.words[2][3] += Fr::from(256);
+        let failures = verify(&forged, a ^ b).unwrap_err();
+        assert!(
+            failures.iter().all(|failure| matches!(
+                failure,
+                VerifyFailure::Lookup { name, .. } if name == "c_limb = a_limb ^ b_limb"
+            )),
+            "{failures:?}"
+        );
+    }
+}
@@ -0,0 +1,246 @@
+use std::marker::PhantomData;
+
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{Region, Value},
+    plonk::{Advice, Column, ConstraintSystem, Error, Expression, Selector},
+    poly::Rotation,
+};
+
+use crate::{
+    tables::RangeTable,
+    util::{mask, pow_of_two, AssignedValue},
+};
+
+/// Adds a fixed number of summands modulo `2^BITS` within a single row, i.e.
+/// constrains `sum(summands) = out + carry * 2^BITS`.
+///
+/// `out` is decomposed into bytes and `carry` is looked up as a byte as well,
+/// which keeps `out + carry * 2^BITS` far below the field modulus and hence
+/// makes the equation hold over the integers. The summands themselves are not
+/// range checked here: callers are expected to pass cells that are already
+/// constrained to `BITS` bits, e.g. the output of another gadget.
+#[derive(Clone, Debug)]
+pub struct ModAddConfig<const BITS: usize> {
+    q_add: Selector,
+    pub summands: Vec<Column<Advice>>,
+    pub out: Column<Advice>,
+    pub carry: Column<Advice>,
+    out_bytes: Vec<Column<Advice>>,
+}
+
+impl<const BITS: usize> ModAddConfig<BITS> {
+    pub fn configure<F: FieldExt>(
+        meta: &mut ConstraintSystem<F>,
+        num_summands: usize,
+        table: RangeTable<8>,
+    ) -> Self {
+        assert!(
+            BITS.is_multiple_of(8) && BITS <= 64,
+            "BITS must be a multiple of 8, at most 64"
+        );
+        assert!(
+            num_summands > 1 && num_summands <= 256,
+            "the carry must fit in a byte"
+        );
+
+        let q_add = meta.complex_selector();
+        let summands: Vec<_> = (0..num_summands).map(|_| meta.advice_column()).collect();
+        let out = meta.advice_column();
+        let carry = meta.advice_column();
+        let out_bytes: Vec<_> = (0..BITS / 8).map(|_| meta.advice_column()).collect();
+        for column in summands.iter().chain([&out]) {
+            meta.enable_equality(*column);
+        }
+
+        meta.create_gate("sum(summands) = out + carry * 2^BITS", |meta| {
+            let q_add = meta.query_selector(q_add);
+            let sum = summands
+                .iter()
+                .fold(Expression::Constant(F::zero()), |acc, summand| {
+                    acc + meta.query_advice(*summand, Rotation::cur())
+                });
+            let out = meta.query_advice(out, Rotation::cur());
+            let carry = meta.query_advice(carry, Rotation::cur());
+            vec![q_add * (sum - out - carry * pow_of_two::<F>(BITS))]
+        });
+
+        meta.create_gate("out = sum(byte_i * 256^i)", |meta| {
+            let q_add = meta.query_selector(q_add);
+            let out = meta.query_advice(out, Rotation::cur());
+            let recomposed = out_bytes.iter().enumerate().fold(
+                Expression::Constant(F::zero()),
+                |acc, (i, byte)| {
+                    acc + meta.query_advice(*byte, Rotation::cur()) * pow_of_two::<F>(8 * i)
+                },
+            );
+            vec![q_add * (out - recomposed)]
+        });
+
+        for column in out_bytes.iter().chain([&carry]) {
+            let column = *column;
+            meta.lookup("out bytes and carry are bytes", |meta| {
+                let q_add = meta.query_selector(q_add);
+                let value = meta.query_advice(column, Rotation::cur());
+                vec![(q_add * value, table.value)]
+            });
+        }
+
+        Self {
+            q_add,
+            summands,
+            out,
+            carry,
+            out_bytes,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct ModAddChip<F, const BITS: usize> {
+    config: ModAddConfig<BITS>,
+    _marker: PhantomData<F>,
+}
+
+impl<F: FieldExt, const BITS: usize> ModAddChip<F, BITS> {
+    pub fn construct(config: ModAddConfig<BITS>) -> Self {
+        Self {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Assigns the addition of `summands` at `offset`, returning the summand
+    /// cells and the `out` cell.
+    pub fn assign(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        summands: &[u64],
+    ) -> Result<(Vec<AssignedValue<F>>, AssignedValue<F>), Error> {
+        let config = &self.config;
+        assert_eq!(summands.len(), config.summands.len());
+        config.q_add.enable(region, offset)?;
+
+        let sum: u128 = summands.iter().map(|summand| *summand as u128).sum();
+        let out = (sum as u64) & mask(BITS);
+        let carry = (sum >> BITS) as u64;
+
+        let summand_cells = config
+            .summands
+            .iter()
+            .zip(summands)
+            .map(|(column, summand)| {
+                region.assign_advice(
+                    || "summand",
+                    *column,
+                    offset,
+                    || Value::known(F::from(*summand)),
+                )
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let out_cell =
+            region.assign_advice(|| "out", config.out, offset, || Value::known(F::from(out)))?;
+        region.assign_advice(
+            || "carry",
+            config.carry,
+            offset,
+            || Value::known(F::from(carry)),
+        )?;
+        for (i, column) in config.out_bytes.iter().enumerate() {
+            let byte = (out >> (8 * i)) & 0xff;
+            region.assign_advice(
+                || "out byte",
+                *column,
+                offset,
+                || Value::known(F::from(byte)),
+            )?;
+        }
+
+        Ok((summand_cells, out_cell))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use halo2_proofs::{
+        circuit::{Layouter, SimpleFloorPlanner},
+        dev::MockProver,
+        halo2curves::{bn256::Fr, pasta::pallas},
+        plonk::Circuit,
+    };
+    use mutation_coverage::mutation_coverage;
+
+    #[derive(Default)]
+    struct TestCircuit<F> {
+        rows: Vec<[u64; 3]>,
+        _marker: PhantomData<F>,
+    }
+
+    impl<F: FieldExt> Circuit<F> for TestCircuit<F> {
+        type Config = (RangeTable<8>, ModAddConfig<32>);
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            let table = RangeTable::construct(meta);
+            (table, ModAddConfig::configure(meta, 3, table))
+        }
+
+        fn synthesize(
+            &self,
+            (table, config): Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            table.load(&mut layouter)?;
+            let chip = ModAddChip::construct(config);
+            layouter.assign_region(
+                || "mod add",
+                |mut region| {
+                    for (offset, summands) in self.rows.iter().enumerate() {
+                        let (_, out) = chip.assign(&mut region, offset, summands)?;
+                        let expected = summands
+                            .iter()
+                            .fold(0u32, |acc, summand| acc.wrapping_add(*summand as u32));
+                        out.value()
+                            .assert_if_known(|out| **out == F::from(expected as u64));
+                    }
+                    Ok(())
+                },
+            )
+        }
+    }
+
+    fn test_mod_add_circuit_over<F: FieldExt>() {
+        let circuit = TestCircuit::<F> {
+            rows: vec![
+                [1, 2, 3],
+                [u32::MAX as u64, 1, 0],
+                [u32::MAX as u64, u32::MAX as u64, u32::MAX as u64],
+            ],
+            _marker: PhantomData,
+        };
+        let prover = MockProver::run(9, &circuit, vec![]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+    }
+
+    #[test]
+    fn test_mod_add_circuit() {
+        test_mod_add_circuit_over::<Fr>();
+        test_mod_add_circuit_over::<pallas::Base>();
+    }
+
+    #[test]
+    fn test_mod_add_circuit_coverage() {
+        let circuit = TestCircuit::<Fr> {
+            rows: vec![[1, 2, 3], [u32::MAX as u64, 1, 0]],
+            _marker: PhantomData,
+        };
+        let report = mutation_coverage(9, &circuit, vec![]).unwrap();
+        assert_eq!(report.unconstrained().count(), 0, "{}", report.report());
+    }
+}
@@ -0,0 +1,204 @@
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{Region, Value},
+    plonk::{Advice, Column, Error},
+};
+
+use crate::util::AssignedValue;
+
+/// Byte cells assigned by a chip, one byte per cell, together with the number
+/// of bytes they hold, for another chip to import into its own region, e.g.
+/// the digest of a hash call passed into the input of another one.
+///
+/// The cells may be followed by padding cells, e.g. the padded blocks of a
+/// hash input, of which only the first `len` are imported. Importing the bytes
+/// copies them and constrains each copy to be equal to the exported cell, so
+/// that the bytes are never witnessed twice.
+#[derive(Clone, Debug)]
+pub struct AssignedBytes<F: FieldExt> {
+    cells: Vec<AssignedValue<F>>,
+    len: usize,
+}
+
+impl<F: FieldExt> AssignedBytes<F> {
+    pub fn new(cells: Vec<AssignedValue<F>>) -> Self {
+        let len = cells.len();
+        Self { cells, len }
+    }
+
+    /// Bytes of which only the first `len` cells are exported, the others
+    /// being padding.
+    pub fn with_padding(cells: Vec<AssignedValue<F>>, len: usize) -> Self {
+        assert!(len <= cells.len(), "the padded cells hold the bytes");
+        Self { cells, len }
+    }
+
+    /// Number of exported bytes.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Cells of the exported bytes, without the padding.
+    pub fn cells(&self) -> &[AssignedValue<F>] {
+        &self.cells[..self.len]
+    }
+
+    /// Native exported bytes.
+    pub fn value(&self) -> Value<Vec<u8>> {
+        let bytes: Value<Vec<F>> = self
+            .cells()
+            .iter()
+            .map(|cell| cell.value().copied())
+            .collect();
+        bytes.map(|bytes| {
+            bytes
+                .iter()
+                .map(|byte| byte.get_lower_128() as u8)
+                .collect()
+        })
+    }
+
+    /// Copies the bytes into `column` from `offset`, one byte per row, and
+    /// returns the copies as the bytes of the importing chip.
+    pub fn import(
+        &self,
+        region: &mut Region<'_, F>,
+        annotation: &str,
+        column: Column<Advice>,
+        offset: usize,
+    ) -> Result<Self, Error> {
+        self.cells()
+            .iter()
+            .enumerate()
+            .map(|(i, cell)| cell.copy_advice(|| annotation, region, column, offset + i))
+            .collect::<Result<Vec<_>, _>>()
+            .map(Self::new)
+    }
+
+    /// Constrains cells already assigned by the importing chip to be equal to
+    /// the bytes, one cell per byte.
+    pub fn constrain_equal(
+        &self,
+        region: &mut Region<'_, F>,
+        cells: &[AssignedValue<F>],
+    ) -> Result<(), Error> {
+        assert_eq!(cells.len(), self.len, "one cell per imported byte");
+        for (cell, byte) in cells.iter().zip(self.cells()) {
+            region.constrain_equal(cell.cell(), byte.cell())?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use halo2_proofs::{
+        circuit::{Layouter, SimpleFloorPlanner},
+        dev::MockProver,
+        halo2curves::{bn256::Fr, pasta::pallas},
+        plonk::{Circuit, ConstraintSystem},
+    };
+    use std::marker::PhantomData;
+
+    /// Exports `bytes` followed by padding from a first region, imports them
+    /// into a second one, and constrains the copies to `expected` in a third.
+    #[derive(Default)]
+    struct TestCircuit<F> {
+        bytes: Vec<u8>,
+        padding: usize,
+        expected: Vec<u8>,
+        _marker: PhantomData<F>,
+    }
+
+    impl<F: FieldExt> Circuit<F> for TestCircuit<F> {
+        type Config = [Column<Advice>; 3];
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            [0; 3].map(|_| {
+                let column = meta.advice_column();
+                meta.enable_equality(column);
+                column
+            })
+        }
+
+        fn synthesize(
+            &self,
+            [exporter, importer, expected]: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            let assign = |region: &mut Region<'_, F>, column, bytes: &[u8]| {
+                bytes
+                    .iter()
+                    .enumerate()
+                    .map(|(offset, byte)| {
+                        region.assign_advice(
+                            || "byte",
+                            column,
+                            offset,
+                            || Value::known(F::from(*byte as u64)),
+                        )
+                    })
+                    .collect::<Result<Vec<_>, _>>()
+            };
+
+            let exported = layouter.assign_region(
+                || "exporter",
+                |mut region| {
+                    let mut bytes = self.bytes.clone();
+                    bytes.resize(self.bytes.len() + self.padding, 0x80);
+                    let cells = assign(&mut region, exporter, &bytes)?;
+                    Ok(AssignedBytes::with_padding(cells, self.bytes.len()))
+                },
+            )?;
+            exported
+                .value()
+                .assert_if_known(|bytes| *bytes == self.bytes);
+
+            let imported = layouter.assign_region(
+                || "importer",
+                |mut region| exported.import(&mut region, "byte", importer, 0),
+            )?;
+            assert_eq!(imported.len(), self.bytes.len());
+
+            layouter.assign_region(
+                || "expected",
+                |mut region| {
+                    let cells = assign(&mut region, expected, &self.expected)?;
+                    imported.constrain_equal(&mut region, &cells)
+                },
+            )
+        }
+    }
+
+    fn verify<F: FieldExt>(bytes: &[u8], padding: usize, expected: &[u8]) -> bool {
+        let circuit = TestCircuit::<F> {
+            bytes: bytes.to_vec(),
+            padding,
+            expected: expected.to_vec(),
+            _marker: PhantomData,
+        };
+        let prover = MockProver::run(6, &circuit, vec![]).unwrap();
+        prover.verify().is_ok()
+    }
+
+    #[test]
+    fn test_assigned_bytes() {
+        let bytes = b"abc";
+        assert!(verify::<Fr>(bytes, 0, bytes));
+        assert!(verify::<Fr>(bytes, 5, bytes));
+        assert!(verify::<Fr>(&[], 2, &[]));
+        assert!(!verify::<Fr>(bytes, 5, b"abd"));
+        assert!(verify::<pallas::Base>(bytes, 5, bytes));
+        assert!(!verify::<pallas::Base>(bytes, 5, b"abd"));
+    }
+}
@@ -0,0 +1,129 @@
+//! Proving the calls of many independent witness sets, e.g. the precompile
+//! calls of the transactions of a block, with a single proof of a single
+//! instance of a circuit, rather than a proof each.
+//!
+//! The witness sets are merged into the witness of one [`BatchCircuit`] by
+//! their [`BatchWitness`], in their order, checked against the capacity of
+//! the circuit by [`HashCircuitExt::check_witness`] before any proving, and
+//! proven by [`prove_batch`] with the [`HashCircuitExt::public_inputs`] of
+//! the merged witness as its instances.
+//!
+//! The keys are those of the default [`BatchCircuit`] of a chip of a bounded
+//! capacity, which pads its layout to it whatever its witness, see
+//! [`crate::capacity`]. The layout of a chip of an [`UNBOUNDED`] capacity is
+//! that of its calls, so that the keys of its default, of no calls, do not
+//! verify its proofs: the batch circuits of the chips are of a bounded
+//! capacity, see [`Bounded`].
+//!
+//! [`UNBOUNDED`]: crate::capacity::UNBOUNDED
+//! [`Bounded`]: crate::capacity::Bounded
+
+use std::marker::PhantomData;
+
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{Layouter, SimpleFloorPlanner},
+    plonk::{create_proof, Circuit, ConstraintSystem, Error as PlonkError, ProvingKey},
+    poly::commitment::{CommitmentScheme, Prover},
+    transcript::{EncodedChallenge, TranscriptWrite},
+};
+use rand_core::RngCore;
+use tracing::info_span;
+
+use crate::{Error, HashCircuitExt};
+
+/// The witness of a chip, as merged from many witness sets.
+pub trait BatchWitness: Sized {
+    /// The witness of all the calls of `witnesses`, in their order.
+    fn merge(witnesses: Vec<Self>) -> Self;
+}
+
+impl<T> BatchWitness for Vec<T> {
+    fn merge(witnesses: Vec<Self>) -> Self {
+        witnesses.into_iter().flatten().collect()
+    }
+}
+
+/// The circuit of the chip `H` alone, loading all the calls of its witness.
+pub struct BatchCircuit<F: FieldExt, H: HashCircuitExt<F>> {
+    pub witness: H::Witness,
+    _marker: PhantomData<F>,
+}
+
+impl<F: FieldExt, H: HashCircuitExt<F>> BatchCircuit<F, H> {
+    /// The circuit of the calls of all of `witnesses`.
+    pub fn new(witnesses: Vec<H::Witness>) -> Self
+    where
+        H::Witness: BatchWitness,
+    {
+        Self {
+            witness: BatchWitness::merge(witnesses),
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<F: FieldExt, H: HashCircuitExt<F>> Default for BatchCircuit<F, H>
+where
+    H::Witness: Default,
+{
+    fn default() -> Self {
+        Self {
+            witness: H::Witness::default(),
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<F: FieldExt, H: HashCircuitExt<F>> Circuit<F> for BatchCircuit<F, H>
+where
+    H::Witness: Default,
+{
+    type Config = H::Config;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        H::configure(meta)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), PlonkError> {
+        H::construct(config, self.witness.clone()).load(&mut layouter)
+    }
+}
+
+/// Proves the calls of all of `witnesses` with a single proof of a
+/// [`BatchCircuit`] of `H`, written to `transcript`, whose instances are
+/// the [`HashCircuitExt::public_inputs`] of the calls, or an
+/// [`Error::Capacity`] if they do not fit in the circuit.
+pub fn prove_batch<'params, Scheme, P, E, R, T, H>(
+    params: &'params Scheme::ParamsProver,
+    pk: &ProvingKey<Scheme::Curve>,
+    witnesses: Vec<H::Witness>,
+    rng: R,
+    transcript: &mut T,
+) -> Result<(), Error>
+where
+    Scheme: CommitmentScheme,
+    P: Prover<'params, Scheme>,
+    E: EncodedChallenge<Scheme::Curve>,
+    R: RngCore,
+    T: TranscriptWrite<Scheme::Curve, E>,
+    H: HashCircuitExt<Scheme::Scalar>,
+    H::Witness: BatchWitness + Default,
+{
+    let _span = info_span!("prove batch", witnesses = witnesses.len()).entered();
+    let circuit = BatchCircuit::<_, H>::new(witnesses);
+    H::check_witness(&circuit.witness)?;
+    let instances = H::public_inputs(&circuit.witness);
+    let instances: Vec<_> = instances.iter().map(Vec::as_slice).collect();
+    create_proof::<Scheme, P, E, R, T, _>(params, pk, &[circuit], &[&instances], rng, transcript)?;
+    Ok(())
+}
@@ -0,0 +1,258 @@
+use std::marker::PhantomData;
+
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{Region, Value},
+    plonk::{Advice, Column, ConstraintSystem, Error, Expression, Selector},
+    poly::Rotation,
+};
+
+use crate::{
+    tables::{BitwiseOp, BitwiseTable},
+    util::{mask, AssignedValue},
+};
+
+/// Applies XOR, AND, OR or NOT to `BITS`-bit limbs via a lookup into the
+/// [`BitwiseTable`]. Wider words are handled limb by limb, e.g. after a
+/// [`crate::DecomposeConfig`] split. Since every operand is looked up, the
+/// operands and the result are also range checked to `BITS` bits.
+///
+/// NOT is implemented as an XOR with the all-ones limb, which is enforced by a
+/// custom gate on the `rhs` column.
+#[derive(Clone, Copy, Debug)]
+pub struct BitwiseConfig<const BITS: usize> {
+    q_xor: Selector,
+    q_and: Selector,
+    q_or: Selector,
+    q_not: Selector,
+    pub lhs: Column<Advice>,
+    pub rhs: Column<Advice>,
+    pub out: Column<Advice>,
+}
+
+impl<const BITS: usize> BitwiseConfig<BITS> {
+    pub fn configure<F: FieldExt>(
+        meta: &mut ConstraintSystem<F>,
+        table: BitwiseTable<BITS>,
+    ) -> Self {
+        let [q_xor, q_and, q_or, q_not] = [0; 4].map(|_| meta.complex_selector());
+        let [lhs, rhs, out] = [0; 3].map(|_| meta.advice_column());
+        for column in [lhs, rhs, out] {
+            meta.enable_equality(column);
+        }
+
+        meta.create_gate("NOT uses an all-ones rhs", |meta| {
+            let q_not = meta.query_selector(q_not);
+            let rhs = meta.query_advice(rhs, Rotation::cur());
+            vec![q_not * (rhs - Expression::Constant(F::from(mask(BITS))))]
+        });
+
+        meta.lookup("op(lhs, rhs) == out", |meta| {
+            let q_xor = meta.query_selector(q_xor) + meta.query_selector(q_not);
+            let q_and = meta.query_selector(q_and);
+            let q_or = meta.query_selector(q_or);
+            let op = q_xor.clone() * Expression::Constant(F::from(BitwiseOp::Xor.tag()))
+                + q_and.clone() * Expression::Constant(F::from(BitwiseOp::And.tag()))
+                + q_or.clone() * Expression::Constant(F::from(BitwiseOp::Or.tag()));
+            let enabled = q_xor + q_and + q_or;
+
+            let lhs = meta.query_advice(lhs, Rotation::cur());
+            let rhs = meta.query_advice(rhs, Rotation::cur());
+            let out = meta.query_advice(out, Rotation::cur());
+            vec![
+                (op, table.op),
+                (enabled.clone() * lhs, table.lhs),
+                (enabled.clone() * rhs, table.rhs),
+                (enabled * out, table.out),
+            ]
+        });
+
+        Self {
+            q_xor,
+            q_and,
+            q_or,
+            q_not,
+            lhs,
+            rhs,
+            out,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct BitwiseChip<F, const BITS: usize> {
+    config: BitwiseConfig<BITS>,
+    _marker: PhantomData<F>,
+}
+
+impl<F: FieldExt, const BITS: usize> BitwiseChip<F, BITS> {
+    pub fn construct(config: BitwiseConfig<BITS>) -> Self {
+        Self {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Assigns `op(lhs, rhs)` at `offset`, returning the `[lhs, rhs, out]` cells.
+    pub fn assign(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        op: BitwiseOp,
+        lhs: u64,
+        rhs: u64,
+    ) -> Result<[AssignedValue<F>; 3], Error> {
+        match op {
+            BitwiseOp::Xor => self.config.q_xor,
+            BitwiseOp::And => self.config.q_and,
+            BitwiseOp::Or => self.config.q_or,
+        }
+        .enable(region, offset)?;
+        self.assign_row(region, offset, lhs, rhs, op.apply(lhs, rhs))
+    }
+
+    /// Assigns `!value` at `offset`, returning the `[value, out]` cells.
+    pub fn assign_not(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        value: u64,
+    ) -> Result<[AssignedValue<F>; 2], Error> {
+        self.config.q_not.enable(region, offset)?;
+        let [value, _, out] =
+            self.assign_row(region, offset, value, mask(BITS), !value & mask(BITS))?;
+        Ok([value, out])
+    }
+
+    fn assign_row(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        lhs: u64,
+        rhs: u64,
+        out: u64,
+    ) -> Result<[AssignedValue<F>; 3], Error> {
+        let lhs = region.assign_advice(
+            || "lhs",
+            self.config.lhs,
+            offset,
+            || Value::known(F::from(lhs)),
+        )?;
+        let rhs = region.assign_advice(
+            || "rhs",
+            self.config.rhs,
+            offset,
+            || Value::known(F::from(rhs)),
+        )?;
+        let out = region.assign_advice(
+            || "out",
+            self.config.out,
+            offset,
+            || Value::known(F::from(out)),
+        )?;
+        Ok([lhs, rhs, out])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use halo2_proofs::{
+        circuit::{Layouter, SimpleFloorPlanner},
+        dev::MockProver,
+        halo2curves::{bn256::Fr, pasta::pallas},
+        plonk::Circuit,
+    };
+    use mutation_coverage::mutation_coverage;
+
+    #[derive(Default)]
+    struct TestCircuit<F> {
+        // `None` stands for NOT applied to the lhs.
+        rows: Vec<(Option<BitwiseOp>, u64, u64)>,
+        _marker: PhantomData<F>,
+    }
+
+    impl<F: FieldExt> Circuit<F> for TestCircuit<F> {
+        type Config = (BitwiseTable<4>, BitwiseConfig<4>);
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            let table = BitwiseTable::construct(meta);
+            (table, BitwiseConfig::configure(meta, table))
+        }
+
+        fn synthesize(
+            &self,
+            (table, config): Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            table.load(&mut layouter)?;
+            let chip = BitwiseChip::construct(config);
+            layouter.assign_region(
+                || "bitwise",
+                |mut region| {
+                    for (offset, (op, lhs, rhs)) in self.rows.iter().enumerate() {
+                        match op {
+                            Some(op) => {
+                                chip.assign(&mut region, offset, *op, *lhs, *rhs)?;
+                            }
+                            None => {
+                                chip.assign_not(&mut region, offset, *lhs)?;
+                            }
+                        }
+                    }
+                    Ok(())
+                },
+            )
+        }
+    }
+
+    fn test_bitwise_circuit_over<F: FieldExt>() {
+        let circuit = TestCircuit::<F> {
+            rows: vec![
+                (Some(BitwiseOp::Xor), 0b1010, 0b0110),
+                (Some(BitwiseOp::And), 0b1010, 0b0110),
+                (Some(BitwiseOp::Or), 0b1010, 0b0110),
+                (None, 0b1010, 0),
+            ],
+            _marker: PhantomData,
+        };
+        let prover = MockProver::run(10, &circuit, vec![]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+
+        // operands wider than the table are rejected.
+        let circuit = TestCircuit::<F> {
+            rows: vec![(Some(BitwiseOp::Xor), 0b1_0000, 0)],
+            _marker: PhantomData,
+        };
+        let prover = MockProver::run(10, &circuit, vec![]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+
+    #[test]
+    fn test_bitwise_circuit() {
+        test_bitwise_circuit_over::<Fr>();
+        test_bitwise_circuit_over::<pallas::Base>();
+    }
+
+    #[test]
+    fn test_bitwise_circuit_coverage() {
+        // Unlike 0b1010 & 0b0110, which neither operand plus one changes,
+        // the operands of the AND are caught by the lookup.
+        let circuit = TestCircuit::<Fr> {
+            rows: vec![
+                (Some(BitwiseOp::Xor), 0b1010, 0b0110),
+                (Some(BitwiseOp::And), 0b1010, 0b0111),
+                (Some(BitwiseOp::Or), 0b1010, 0b0110),
+                (None, 0b1010, 0),
+            ],
+            _marker: PhantomData,
+        };
+        let report = mutation_coverage(10, &circuit, vec![]).unwrap();
+        assert_eq!(report.unconstrained().count(), 0, "{}", report.report());
+    }
+}
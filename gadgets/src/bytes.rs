@@ -0,0 +1,288 @@
+use std::marker::PhantomData;
+
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{Region, Value},
+    plonk::{Advice, Column, ConstraintSystem, Error, Expression, Selector},
+    poly::Rotation,
+};
+
+use crate::{tables::RangeTable, util::AssignedValue};
+
+/// Order of the bytes of a value, e.g. little-endian for the words of
+/// RIPEMD-160 and BLAKE2, and big-endian for those of SHA-2.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Endianness {
+    Little,
+    Big,
+}
+
+impl Endianness {
+    /// Bytes of a value given by its little-endian bytes, in this order.
+    pub fn order<const N_BYTES: usize>(&self, mut le_bytes: [u8; N_BYTES]) -> [u8; N_BYTES] {
+        if *self == Self::Big {
+            le_bytes.reverse();
+        }
+        le_bytes
+    }
+
+    /// Native bytes of `value` in this order, for a value that fits in
+    /// `N_BYTES` bytes.
+    pub fn to_bytes<F: FieldExt, const N_BYTES: usize>(&self, value: &F) -> [u8; N_BYTES] {
+        let repr = value.to_repr();
+        self.order(core::array::from_fn(|i| repr.as_ref()[i]))
+    }
+
+    /// Native value of `bytes`, given in this order.
+    pub fn from_bytes<F: FieldExt, const N_BYTES: usize>(&self, bytes: &[u8; N_BYTES]) -> F {
+        self.order(*bytes)
+            .iter()
+            .rev()
+            .fold(F::zero(), |acc, byte| {
+                acc * F::from(256) + F::from(*byte as u64)
+            })
+    }
+}
+
+/// Decomposes a value into `N_BYTES` bytes laid out within a single row, in
+/// the order given by its [`Endianness`], every byte being looked up in the
+/// byte table. The value itself is thus constrained to `N_BYTES` bytes.
+#[derive(Clone, Copy, Debug)]
+pub struct BytesConfig<const N_BYTES: usize> {
+    q_decompose: Selector,
+    pub value: Column<Advice>,
+    pub bytes: [Column<Advice>; N_BYTES],
+    pub endianness: Endianness,
+}
+
+impl<const N_BYTES: usize> BytesConfig<N_BYTES> {
+    pub fn configure<F: FieldExt>(
+        meta: &mut ConstraintSystem<F>,
+        table: RangeTable<8>,
+        endianness: Endianness,
+    ) -> Self {
+        assert!(
+            8 * N_BYTES < F::NUM_BITS as usize,
+            "decomposed values must fit in the field"
+        );
+
+        let q_decompose = meta.complex_selector();
+        let value = meta.advice_column();
+        let bytes = [(); N_BYTES].map(|_| meta.advice_column());
+        meta.enable_equality(value);
+        for byte in bytes {
+            meta.enable_equality(byte);
+        }
+
+        meta.create_gate("value = bytes packed in their endianness", |meta| {
+            let q_decompose = meta.query_selector(q_decompose);
+            let value = meta.query_advice(value, Rotation::cur());
+            // Horner's rule over the bytes from the most significant one.
+            let mut bytes = bytes.to_vec();
+            if endianness == Endianness::Little {
+                bytes.reverse();
+            }
+            let packed = bytes
+                .iter()
+                .fold(Expression::Constant(F::zero()), |acc, byte| {
+                    acc * Expression::Constant(F::from(256))
+                        + meta.query_advice(*byte, Rotation::cur())
+                });
+            vec![q_decompose * (value - packed)]
+        });
+
+        for byte in bytes {
+            meta.lookup("byte is in range", |meta| {
+                let q_decompose = meta.query_selector(q_decompose);
+                let byte = meta.query_advice(byte, Rotation::cur());
+                vec![(q_decompose * byte, table.value)]
+            });
+        }
+
+        Self {
+            q_decompose,
+            value,
+            bytes,
+            endianness,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct BytesChip<F, const N_BYTES: usize> {
+    config: BytesConfig<N_BYTES>,
+    _marker: PhantomData<F>,
+}
+
+impl<F: FieldExt, const N_BYTES: usize> BytesChip<F, N_BYTES> {
+    pub fn construct(config: BytesConfig<N_BYTES>) -> Self {
+        Self {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Assigns the value of `bytes` and the bytes at `offset`, returning the
+    /// value cell and the byte cells in the endianness of the chip.
+    pub fn assign(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        bytes: &[u8; N_BYTES],
+    ) -> Result<(AssignedValue<F>, Vec<AssignedValue<F>>), Error> {
+        let value = region.assign_advice(
+            || "value",
+            self.config.value,
+            offset,
+            || Value::known(self.config.endianness.from_bytes(bytes)),
+        )?;
+        let bytes = self.assign_bytes(region, offset, Value::known(*bytes))?;
+        Ok((value, bytes))
+    }
+
+    /// Copies an already assigned cell into the value column at `offset` and
+    /// assigns its bytes, returning the byte cells in the endianness of the
+    /// chip.
+    pub fn copy(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        cell: &AssignedValue<F>,
+    ) -> Result<Vec<AssignedValue<F>>, Error> {
+        let value = cell.copy_advice(|| "value", region, self.config.value, offset)?;
+        let bytes = value
+            .value()
+            .map(|value| self.config.endianness.to_bytes(value));
+        self.assign_bytes(region, offset, bytes)
+    }
+
+    fn assign_bytes(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        bytes: Value<[u8; N_BYTES]>,
+    ) -> Result<Vec<AssignedValue<F>>, Error> {
+        self.config.q_decompose.enable(region, offset)?;
+        self.config
+            .bytes
+            .iter()
+            .enumerate()
+            .map(|(i, column)| {
+                region.assign_advice(
+                    || "byte",
+                    *column,
+                    offset,
+                    || bytes.map(|bytes| F::from(bytes[i] as u64)),
+                )
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use halo2_proofs::{
+        circuit::{Layouter, SimpleFloorPlanner},
+        dev::MockProver,
+        halo2curves::{bn256::Fr, pasta::pallas},
+        plonk::Circuit,
+    };
+
+    /// Decomposes every value as little-endian and as big-endian bytes, and
+    /// checks the bytes against `expected`, given in little-endian order.
+    #[derive(Default)]
+    struct TestCircuit<F> {
+        values: Vec<(u64, [u8; 4])>,
+        _marker: PhantomData<F>,
+    }
+
+    impl<F: FieldExt> Circuit<F> for TestCircuit<F> {
+        type Config = (RangeTable<8>, BytesConfig<4>, BytesConfig<4>);
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            let table = RangeTable::construct(meta);
+            (
+                table,
+                BytesConfig::configure(meta, table, Endianness::Little),
+                BytesConfig::configure(meta, table, Endianness::Big),
+            )
+        }
+
+        fn synthesize(
+            &self,
+            (table, le, be): Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            table.load(&mut layouter)?;
+            let (le, be) = (BytesChip::construct(le), BytesChip::construct(be));
+            layouter.assign_region(
+                || "bytes",
+                |mut region| {
+                    for (offset, (value, expected)) in self.values.iter().enumerate() {
+                        let value = region.assign_advice(
+                            || "value",
+                            le.config.value,
+                            offset,
+                            || Value::known(F::from(*value)),
+                        )?;
+                        let le_bytes =
+                            le.assign_bytes(&mut region, offset, Value::known(*expected))?;
+                        let be_bytes = be.copy(&mut region, offset, &value)?;
+                        for (le_byte, be_byte) in le_bytes.iter().zip(be_bytes.iter().rev()) {
+                            region.constrain_equal(le_byte.cell(), be_byte.cell())?;
+                        }
+                    }
+                    Ok(())
+                },
+            )
+        }
+    }
+
+    fn test_bytes_native_over<F: FieldExt>() {
+        let value = F::from(0x01020304);
+        assert_eq!(Endianness::Little.to_bytes(&value), [4, 3, 2, 1]);
+        assert_eq!(Endianness::Big.to_bytes(&value), [1, 2, 3, 4]);
+        assert_eq!(Endianness::Little.from_bytes::<F, 4>(&[4, 3, 2, 1]), value);
+        assert_eq!(Endianness::Big.from_bytes::<F, 4>(&[1, 2, 3, 4]), value);
+    }
+
+    fn test_bytes_over<F: FieldExt>() {
+        let circuit = TestCircuit::<F> {
+            values: vec![
+                (0, [0; 4]),
+                (0x01020304, [4, 3, 2, 1]),
+                (0xffffffff, [0xff; 4]),
+            ],
+            _marker: PhantomData,
+        };
+        let prover = MockProver::run(9, &circuit, vec![]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+
+        for values in [vec![(0x01020304, [1, 2, 3, 4])], vec![(1 << 32, [0; 4])]] {
+            let circuit = TestCircuit::<F> {
+                values,
+                _marker: PhantomData,
+            };
+            let prover = MockProver::run(9, &circuit, vec![]).unwrap();
+            assert!(prover.verify().is_err());
+        }
+    }
+
+    #[test]
+    fn test_bytes_native() {
+        test_bytes_native_over::<Fr>();
+        test_bytes_native_over::<pallas::Base>();
+    }
+
+    #[test]
+    fn test_bytes() {
+        test_bytes_over::<Fr>();
+        test_bytes_over::<pallas::Base>();
+    }
+}
@@ -0,0 +1,904 @@
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{Layouter, Region, Value},
+    plonk::{
+        Advice, Column, ConstraintSystem, Error, Expression, Fixed, SecondPhase, Selector,
+        VirtualCells,
+    },
+    poly::Rotation,
+};
+
+use crate::{
+    bytes::Endianness, capacity::UNBOUNDED, hash_table::HashTableConfig,
+    public_inputs::CHUNK_BYTES, util::AssignedValue,
+};
+
+/// The Merkle–Damgård strengthening of the padding of a hash, e.g. of
+/// SHA2-256 or RIPEMD-160: the input is followed by a `0x80` byte, zeros,
+/// and its length in bits in the last `len_bytes` bytes of its last block,
+/// in `endianness`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MdPadding {
+    pub len_bytes: usize,
+    pub endianness: Endianness,
+}
+
+/// The padding of the inputs of the calls of a [`CallSlotsConfig`] up to
+/// the end of their last block.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SlotPadding {
+    /// A block per call, without padding, e.g. of BLAKE2F.
+    None,
+    /// The Merkle–Damgård strengthening of the input.
+    Md(MdPadding),
+    /// Zeros, a call taking a block at least, e.g. of BLAKE3, whose
+    /// compressions take the length of their block from the layout of the
+    /// circuit: the flags and lengths of the slots are constants of the
+    /// layout, rather than constrained by the padding.
+    Zeros,
+}
+
+/// The block a slot of a [`CallSlotsConfig`] region holds.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SlotPlan {
+    /// Index of the call of the block, or `None` for a disabled slot.
+    pub call: Option<usize>,
+    pub is_first: bool,
+    pub is_last: bool,
+    /// Offset of the block in the padded input of its call.
+    pub offset: usize,
+}
+
+/// The cells of a slot, as the chip computing its blocks assigned them.
+#[derive(Clone, Debug)]
+pub struct SlotCells<F: FieldExt> {
+    /// The flag of the first block of a call, from which the chip restarts
+    /// from the initial hash value.
+    pub is_first: AssignedValue<F>,
+    /// The bytes of the block.
+    pub block: Vec<AssignedValue<F>>,
+    /// The digest of the chaining value after the block.
+    pub digest: Vec<AssignedValue<F>>,
+}
+
+/// The calls of a hash circuit laid out in a fixed number of slots of a
+/// block each, whatever the calls, so that the layout of the circuit, and
+/// thus its keys, only depend on its number of slots.
+///
+/// A region holds a row per byte of every slot, between a sentinel row on
+/// either side, along with the flags of the block of the slot: whether it
+/// is enabled, the first and last block of its call and the call id. The
+/// enabled slots come first and hold the blocks of the calls in their
+/// order. The region accumulates the length and RLC of the input of a call
+/// over its bytes up to its padding, which the gates constrain to the
+/// padding of the length, or pin to the layout for a [`SlotPadding::Zeros`],
+/// and the RLC and chunks of the digest after every block. The last block
+/// of every call is looked up in the [`HashTable`] of the circuit, and every
+/// enabled row of the table in the last blocks, so that the table holds the
+/// calls of the slots and nothing else.
+///
+/// The input bytes of every slot, zeros in place of its padding, are
+/// accumulated in chunks of 16 bytes, which are constrained to the input
+/// instance column of a table that is exposed, see [`crate::public_inputs`].
+///
+/// [`HashTable`]: crate::hash_table::HashTable
+#[derive(Clone, Debug)]
+pub struct CallSlotsConfig {
+    block_bytes: usize,
+    digest_bytes: usize,
+    padding: SlotPadding,
+    byte: Column<Advice>,
+    is_pad: Column<Advice>,
+    marker: Column<Advice>,
+    len: Column<Advice>,
+    rlc: Column<Advice>,
+    is_enabled: Column<Advice>,
+    is_first: Column<Advice>,
+    is_last: Column<Advice>,
+    id: Column<Advice>,
+    is_end: Column<Advice>,
+    out_byte: Column<Advice>,
+    out_rlc: Column<Advice>,
+    out_chunk: Column<Advice>,
+    in_chunk: Column<Advice>,
+    /// 1 on the last row of every slot.
+    end: Column<Fixed>,
+    q_byte: Selector,
+    q_block: Selector,
+    q_cont: Selector,
+    q_seq: Selector,
+    q_pad_free: Selector,
+    q_len_field: Selector,
+    q_flag: Selector,
+    q_out_first: Selector,
+    q_out: Selector,
+    q_chunk_first: Selector,
+    q_chunk_next: Selector,
+    q_in_first: Selector,
+    q_in_next: Selector,
+}
+
+impl CallSlotsConfig {
+    /// Configures the slots of blocks of `block_bytes` bytes and digests of
+    /// `digest_bytes` bytes of the calls of `table`, padded with `padding`.
+    pub fn configure<F: FieldExt>(
+        meta: &mut ConstraintSystem<F>,
+        table: &HashTableConfig,
+        block_bytes: usize,
+        digest_bytes: usize,
+        padding: SlotPadding,
+    ) -> Self {
+        assert!(digest_bytes <= block_bytes, "the digest fits in a slot");
+        let mut advice = |second_phase: bool| {
+            let column = if second_phase {
+                meta.advice_column_in(SecondPhase)
+            } else {
+                meta.advice_column()
+            };
+            meta.enable_equality(column);
+            column
+        };
+        let [byte, is_pad, marker, len] = [(); 4].map(|_| advice(false));
+        let rlc = advice(true);
+        let [is_enabled, is_first, is_last, id, is_end, out_byte] = [(); 6].map(|_| advice(false));
+        let out_rlc = advice(true);
+        let [out_chunk, in_chunk] = [(); 2].map(|_| advice(false));
+        let end = meta.fixed_column();
+        let [q_byte, q_block, q_cont, q_seq, q_pad_free, q_len_field, q_flag] =
+            [(); 7].map(|_| meta.selector());
+        let [q_out_first, q_out, q_chunk_first, q_chunk_next] = [(); 4].map(|_| meta.selector());
+        let [q_in_first, q_in_next] = [(); 2].map(|_| meta.selector());
+
+        let one = || Expression::Constant(F::one());
+        let boolean = |value: Expression<F>| value.clone() * (one() - value);
+        let r = table.challenge;
+
+        meta.create_gate("call slot sequence", |meta| {
+            let q_seq = meta.query_selector(q_seq);
+            let cur =
+                |meta: &mut VirtualCells<'_, F>, column| meta.query_advice(column, Rotation::cur());
+            let prev = |meta: &mut VirtualCells<'_, F>, column| {
+                meta.query_advice(column, Rotation::prev())
+            };
+            let [en, first, last, id_cur] =
+                [is_enabled, is_first, is_last, id].map(|c| cur(meta, c));
+            let [en_prev, last_prev, id_prev] = [is_enabled, is_last, id].map(|c| prev(meta, c));
+            let mut constraints = vec![
+                boolean(en.clone()),
+                boolean(first.clone()),
+                boolean(last.clone()),
+                // the enabled slots come first.
+                en.clone() * (one() - en_prev.clone()),
+                (one() - en.clone()) * first.clone(),
+                (one() - en.clone()) * last.clone(),
+                // a call starts after the last block of the previous one,
+                // which ends before a disabled slot.
+                en.clone() * (first.clone() - last_prev.clone()),
+                en_prev * (one() - en.clone()) * (one() - last_prev),
+                en.clone() * (id_cur - id_prev - first.clone()),
+            ];
+            if padding == SlotPadding::None {
+                constraints.push(first - en.clone());
+                constraints.push(last - en);
+            }
+            constraints
+                .into_iter()
+                .map(|constraint| q_seq.clone() * constraint)
+                .collect::<Vec<_>>()
+        });
+
+        meta.create_gate("call slot flags", |meta| {
+            let q_cont = meta.query_selector(q_cont);
+            [is_enabled, is_first, is_last, id]
+                .map(|column| {
+                    q_cont.clone()
+                        * (meta.query_advice(column, Rotation::cur())
+                            - meta.query_advice(column, Rotation::prev()))
+                })
+                .to_vec()
+        });
+
+        meta.create_gate("call slot byte", |meta| {
+            let q_byte = meta.query_selector(q_byte);
+            let pad = meta.query_advice(is_pad, Rotation::cur());
+            let marker = meta.query_advice(marker, Rotation::cur());
+            let byte = meta.query_advice(byte, Rotation::cur());
+            let mut constraints = vec![boolean(pad.clone()), boolean(marker.clone())];
+            constraints.push(match padding {
+                SlotPadding::None => pad,
+                SlotPadding::Md(_) => marker * (byte - Expression::Constant(F::from(0x80))),
+                SlotPadding::Zeros => pad * byte,
+            });
+            constraints
+                .into_iter()
+                .map(|constraint| q_byte.clone() * constraint)
+                .collect::<Vec<_>>()
+        });
+
+        // The accumulators of a byte from those of the previous byte, reset
+        // on the first byte of a call.
+        for (name, q, resets) in [
+            ("call slot first byte", q_block, true),
+            ("call slot next byte", q_cont, false),
+        ] {
+            meta.create_gate(name, |meta| {
+                let q = meta.query_selector(q);
+                let first = meta.query_advice(is_first, Rotation::cur());
+                let mut prev = |column| {
+                    let prev = meta.query_advice(column, Rotation::prev());
+                    if resets {
+                        (one() - first.clone()) * prev
+                    } else {
+                        prev
+                    }
+                };
+                let [pad_prev, len_prev, rlc_prev] = [is_pad, len, rlc].map(&mut prev);
+                let pad = meta.query_advice(is_pad, Rotation::cur());
+                let marker = meta.query_advice(marker, Rotation::cur());
+                let byte = meta.query_advice(byte, Rotation::cur());
+                let len = meta.query_advice(len, Rotation::cur());
+                let rlc = meta.query_advice(rlc, Rotation::cur());
+                let r = meta.query_challenge(r);
+                let is_input = one() - pad.clone();
+                vec![
+                    q.clone() * (pad - pad_prev - marker),
+                    q.clone() * (len - len_prev - is_input.clone()),
+                    q * (rlc.clone()
+                        - rlc_prev.clone()
+                        - is_input * (rlc_prev * (r - one()) + byte)),
+                ]
+            });
+        }
+
+        if let SlotPadding::Md(MdPadding {
+            len_bytes,
+            endianness,
+        }) = padding
+        {
+            assert!(len_bytes < block_bytes, "the length fits in a block");
+            meta.create_gate("call slot padding", |meta| {
+                let q_pad_free = meta.query_selector(q_pad_free);
+                let q_len_field = meta.query_selector(q_len_field);
+                let q_flag = meta.query_selector(q_flag);
+                let pad = meta.query_advice(is_pad, Rotation::cur());
+                let marker = meta.query_advice(marker, Rotation::cur());
+                let byte = meta.query_advice(byte, Rotation::cur());
+                let en = meta.query_advice(is_enabled, Rotation::cur());
+                let last = meta.query_advice(is_last, Rotation::cur());
+                let zero_pad = (pad.clone() - marker) * byte;
+                vec![
+                    // the padding is zeros after its marker, but for the
+                    // length in the last block.
+                    q_pad_free * zero_pad.clone(),
+                    q_len_field * (one() - last.clone()) * zero_pad,
+                    // the length is in the last block, which has room for it.
+                    q_flag * en * (pad - last),
+                ]
+            });
+
+            meta.create_gate("call slot length", |meta| {
+                let end = meta.query_fixed(end, Rotation::cur());
+                let last = meta.query_advice(is_last, Rotation::cur());
+                let len = meta.query_advice(len, Rotation::cur());
+                let bits = (0..len_bytes).fold(Expression::Constant(F::zero()), |acc, k| {
+                    let rotation = match endianness {
+                        Endianness::Big => -(k as i32),
+                        Endianness::Little => k as i32 + 1 - len_bytes as i32,
+                    };
+                    acc + meta.query_advice(byte, Rotation(rotation))
+                        * Expression::Constant(F::from(256).pow_vartime([k as u64]))
+                });
+                vec![end * last * (bits - len * Expression::Constant(F::from(8)))]
+            });
+        }
+
+        meta.create_gate("call slot end", |meta| {
+            let end = meta.query_fixed(end, Rotation::cur());
+            let is_end = meta.query_advice(is_end, Rotation::cur());
+            let last = meta.query_advice(is_last, Rotation::cur());
+            vec![is_end - end * last]
+        });
+
+        meta.create_gate("call slot digest start", |meta| {
+            let [q_out_first, q_chunk_first] =
+                [q_out_first, q_chunk_first].map(|q| meta.query_selector(q));
+            let byte = meta.query_advice(out_byte, Rotation::cur());
+            let rlc = meta.query_advice(out_rlc, Rotation::cur());
+            let chunk = meta.query_advice(out_chunk, Rotation::cur());
+            vec![
+                q_out_first.clone() * (rlc - byte.clone()),
+                q_out_first * (chunk.clone() - byte.clone()),
+                q_chunk_first * (chunk - byte),
+            ]
+        });
+
+        meta.create_gate("call slot digest", |meta| {
+            let [q_out, q_chunk_next] = [q_out, q_chunk_next].map(|q| meta.query_selector(q));
+            let byte = meta.query_advice(out_byte, Rotation::cur());
+            let rlc = meta.query_advice(out_rlc, Rotation::cur());
+            let rlc_prev = meta.query_advice(out_rlc, Rotation::prev());
+            let chunk = meta.query_advice(out_chunk, Rotation::cur());
+            let chunk_prev = meta.query_advice(out_chunk, Rotation::prev());
+            let r = meta.query_challenge(r);
+            vec![
+                q_out * (rlc - rlc_prev * r - byte.clone()),
+                q_chunk_next * (chunk - chunk_prev * Expression::Constant(F::from(256)) - byte),
+            ]
+        });
+
+        meta.create_gate("call slot input chunk", |meta| {
+            let [q_in_first, q_in_next] = [q_in_first, q_in_next].map(|q| meta.query_selector(q));
+            let pad = meta.query_advice(is_pad, Rotation::cur());
+            let byte = meta.query_advice(byte, Rotation::cur());
+            let chunk = meta.query_advice(in_chunk, Rotation::cur());
+            let chunk_prev = meta.query_advice(in_chunk, Rotation::prev());
+            let input_byte = (one() - pad) * byte;
+            vec![
+                q_in_first * (chunk.clone() - input_byte.clone()),
+                q_in_next * (chunk - chunk_prev * Expression::Constant(F::from(256)) - input_byte),
+            ]
+        });
+
+        // The last row of a slot, and of the digest bytes of its chunks.
+        let last_row = block_bytes as i32 - 1;
+        let digest_rotation = |byte: usize| Rotation(byte as i32 - last_row);
+        let chunk_ends: Vec<_> = (0..digest_bytes.div_ceil(CHUNK_BYTES))
+            .map(|i| digest_bytes.min((i + 1) * CHUNK_BYTES) - 1)
+            .collect();
+        let slot_expressions = |meta: &mut VirtualCells<'_, F>| {
+            let is_end = meta.query_advice(is_end, Rotation::cur());
+            let mut values = vec![
+                one(),
+                meta.query_advice(id, Rotation::cur()),
+                meta.query_advice(rlc, Rotation::cur()),
+                meta.query_advice(len, Rotation::cur()),
+                meta.query_advice(out_rlc, digest_rotation(digest_bytes - 1)),
+            ];
+            for chunk_end in chunk_ends.iter() {
+                values.push(meta.query_advice(out_chunk, digest_rotation(*chunk_end)));
+            }
+            values
+                .into_iter()
+                .map(|value| is_end.clone() * value)
+                .collect::<Vec<_>>()
+        };
+        let table_columns = {
+            let table = &table.table;
+            let mut columns = vec![table.is_enabled, table.id, table.input_rlc, table.input_len];
+            columns.push(table.output_rlc);
+            columns.extend(table.output.iter().copied());
+            columns
+        };
+        meta.lookup_any("call slot in hash table", |meta| {
+            let slot = slot_expressions(meta);
+            slot.into_iter()
+                .zip(table_columns.iter())
+                .map(|(slot, column)| (slot, meta.query_advice(*column, Rotation::cur())))
+                .collect()
+        });
+        meta.lookup_any("hash table in call slots", |meta| {
+            let slot = slot_expressions(meta);
+            table_columns
+                .iter()
+                .map(|column| meta.query_advice(*column, Rotation::cur()))
+                .zip(slot)
+                .collect()
+        });
+
+        Self {
+            block_bytes,
+            digest_bytes,
+            padding,
+            byte,
+            is_pad,
+            marker,
+            len,
+            rlc,
+            is_enabled,
+            is_first,
+            is_last,
+            id,
+            is_end,
+            out_byte,
+            out_rlc,
+            out_chunk,
+            in_chunk,
+            end,
+            q_byte,
+            q_block,
+            q_cont,
+            q_seq,
+            q_pad_free,
+            q_len_field,
+            q_flag,
+            q_out_first,
+            q_out,
+            q_chunk_first,
+            q_chunk_next,
+            q_in_first,
+            q_in_next,
+        }
+    }
+
+    /// Number of blocks of an input of `len` bytes.
+    pub fn blocks(&self, len: usize) -> usize {
+        blocks(len, self.block_bytes, self.padding)
+    }
+
+    /// Most slots the blocks of `max_calls` calls of `max_len` input bytes
+    /// in total take, a call taking a block more than its share of the
+    /// bytes and its padding at most, or [`UNBOUNDED`] if either is, but
+    /// `max_len` for a block per call.
+    ///
+    /// [`UNBOUNDED`]: crate::capacity::UNBOUNDED
+    pub fn max_slots(&self, max_calls: usize, max_len: usize) -> usize {
+        max_slots(max_calls, max_len, self.block_bytes, self.padding)
+    }
+
+    /// The blocks of the inputs of `lens` bytes in `slots` slots, the
+    /// blocks of every call in their order, then the disabled slots.
+    pub fn plan(&self, lens: &[usize], slots: usize) -> Vec<SlotPlan> {
+        let mut plan = Vec::with_capacity(slots);
+        for (call, len) in lens.iter().enumerate() {
+            let blocks = self.blocks(*len);
+            plan.extend((0..blocks).map(|i| SlotPlan {
+                call: Some(call),
+                is_first: i == 0,
+                is_last: i + 1 == blocks,
+                offset: i * self.block_bytes,
+            }));
+        }
+        assert!(plan.len() <= slots, "the blocks fit in the slots");
+        plan.resize(
+            slots,
+            SlotPlan {
+                call: None,
+                is_first: false,
+                is_last: false,
+                offset: 0,
+            },
+        );
+        plan
+    }
+
+    /// Assigns the region `"{name} slots"` of the slots of `plan`, copying
+    /// the cells of every slot from `cells`, for the inputs of `lens` bytes.
+    /// The circuit enables a constants column.
+    ///
+    /// The input chunks of every slot, enabled or not, are constrained to
+    /// the input instance column of `table` if it is exposed, so that the
+    /// keys of the circuit only depend on the number of slots.
+    pub fn assign<F: FieldExt>(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        name: &str,
+        table: &HashTableConfig,
+        lens: &[usize],
+        plan: &[SlotPlan],
+        cells: &[SlotCells<F>],
+    ) -> Result<(), Error> {
+        assert_eq!(plan.len(), cells.len(), "the cells of every slot");
+        let challenge = layouter.get_challenge(table.challenge);
+        let block = self.block_bytes;
+        let md_padding = match self.padding {
+            SlotPadding::Md(padding) => Some(padding),
+            SlotPadding::None | SlotPadding::Zeros => None,
+        };
+        let is_padded = self.padding != SlotPadding::None;
+        let is_pinned = self.padding == SlotPadding::Zeros;
+        let in_chunks = layouter.assign_region(
+            || format!("{name} slots"),
+            |mut region| {
+                let region = &mut region;
+                let constant = |region: &mut Region<'_, F>, column, offset, value: u64| {
+                    region
+                        .assign_advice_from_constant(|| "sentinel", column, offset, F::from(value))
+                        .map(|_| ())
+                };
+                // The slot before the first, as the last of a call, which
+                // ends in its padding.
+                for (column, value) in [
+                    (self.is_enabled, 1),
+                    (self.is_last, 1),
+                    (self.id, 0),
+                    (self.is_pad, is_padded as u64),
+                    (self.len, 0),
+                    (self.rlc, 0),
+                ] {
+                    constant(region, column, 0, value)?;
+                }
+
+                let mut in_chunks = Vec::with_capacity(plan.len() * block.div_ceil(CHUNK_BYTES));
+                let mut id = 0;
+                let mut len = 0;
+                let mut rlc = Value::known(F::zero());
+                for (slot, (plan, cells)) in plan.iter().zip(cells).enumerate() {
+                    let start = 1 + slot * block;
+                    let call_len = plan.call.map(|call| lens[call]);
+                    if plan.is_first {
+                        id += 1;
+                        len = 0;
+                        rlc = Value::known(F::zero());
+                    }
+                    self.q_seq.enable(region, start)?;
+                    let mut out_rlc = Value::known(F::zero());
+                    let mut out_chunk = Value::known(F::zero());
+                    let mut in_chunk = Value::known(F::zero());
+                    for j in 0..block {
+                        let offset = start + j;
+                        self.q_byte.enable(region, offset)?;
+                        if j == 0 {
+                            self.q_block.enable(region, offset)?;
+                        } else {
+                            self.q_cont.enable(region, offset)?;
+                        }
+                        if let Some(MdPadding { len_bytes, .. }) = md_padding {
+                            if j < block - len_bytes {
+                                self.q_pad_free.enable(region, offset)?;
+                            } else {
+                                self.q_len_field.enable(region, offset)?;
+                            }
+                            if j == block - len_bytes - 1 {
+                                self.q_flag.enable(region, offset)?;
+                            }
+                        }
+                        let is_end = j == block - 1;
+                        region.assign_fixed(
+                            || "end",
+                            self.end,
+                            offset,
+                            || Value::known(F::from(is_end as u64)),
+                        )?;
+
+                        let index = plan.offset + j;
+                        let (is_pad, marker) = match (call_len, is_padded) {
+                            (_, false) => (false, false),
+                            (Some(call_len), true) => (index >= call_len, index == call_len),
+                            (None, true) => (true, false),
+                        };
+                        let byte =
+                            cells.block[j].copy_advice(|| "byte", region, self.byte, offset)?;
+                        let input_byte = if is_pad {
+                            Value::known(F::zero())
+                        } else {
+                            len += 1;
+                            rlc = rlc * challenge + byte.value().copied();
+                            byte.value().copied()
+                        };
+                        in_chunk = if j % CHUNK_BYTES == 0 {
+                            self.q_in_first.enable(region, offset)?;
+                            input_byte
+                        } else {
+                            self.q_in_next.enable(region, offset)?;
+                            in_chunk * Value::known(F::from(256)) + input_byte
+                        };
+                        let in_chunk_cell = region.assign_advice(
+                            || "in_chunk",
+                            self.in_chunk,
+                            offset,
+                            || in_chunk,
+                        )?;
+                        if j % CHUNK_BYTES == CHUNK_BYTES - 1 || is_end {
+                            in_chunks.push(in_chunk_cell);
+                        }
+                        for (column, value) in [
+                            (self.is_pad, is_pad),
+                            (self.marker, marker),
+                            (self.is_enabled, plan.call.is_some()),
+                            (self.is_last, plan.is_last),
+                            (self.is_end, plan.is_last && is_end),
+                        ] {
+                            let cell = region.assign_advice(
+                                || "flag",
+                                column,
+                                offset,
+                                || Value::known(F::from(value as u64)),
+                            )?;
+                            if is_pinned && j == 0 && column == self.is_enabled {
+                                region.constrain_constant(cell.cell(), F::from(value as u64))?;
+                            }
+                        }
+                        if j == 0 {
+                            let cell = cells.is_first.copy_advice(
+                                || "is_first",
+                                region,
+                                self.is_first,
+                                offset,
+                            )?;
+                            if is_pinned {
+                                region.constrain_constant(
+                                    cell.cell(),
+                                    F::from(plan.is_first as u64),
+                                )?;
+                            }
+                        } else {
+                            region.assign_advice(
+                                || "is_first",
+                                self.is_first,
+                                offset,
+                                || Value::known(F::from(plan.is_first as u64)),
+                            )?;
+                        }
+                        let slot_id = if plan.call.is_some() { id } else { 0 };
+                        region.assign_advice(
+                            || "id",
+                            self.id,
+                            offset,
+                            || Value::known(F::from(slot_id)),
+                        )?;
+                        let len_cell = region.assign_advice(
+                            || "len",
+                            self.len,
+                            offset,
+                            || Value::known(F::from(len)),
+                        )?;
+                        if is_pinned && is_end && plan.call.is_some() {
+                            region.constrain_constant(len_cell.cell(), F::from(len))?;
+                        }
+                        region.assign_advice(|| "rlc", self.rlc, offset, || rlc)?;
+
+                        if j < self.digest_bytes {
+                            if j == 0 {
+                                self.q_out_first.enable(region, offset)?;
+                            } else {
+                                self.q_out.enable(region, offset)?;
+                                if j % CHUNK_BYTES == 0 {
+                                    self.q_chunk_first.enable(region, offset)?;
+                                } else {
+                                    self.q_chunk_next.enable(region, offset)?;
+                                }
+                            }
+                            let byte = cells.digest[j].copy_advice(
+                                || "digest byte",
+                                region,
+                                self.out_byte,
+                                offset,
+                            )?;
+                            let byte = byte.value().copied();
+                            out_rlc = out_rlc * challenge + byte;
+                            out_chunk = if j % CHUNK_BYTES == 0 {
+                                byte
+                            } else {
+                                out_chunk * Value::known(F::from(256)) + byte
+                            };
+                            region.assign_advice(|| "out_rlc", self.out_rlc, offset, || out_rlc)?;
+                            region.assign_advice(
+                                || "out_chunk",
+                                self.out_chunk,
+                                offset,
+                                || out_chunk,
+                            )?;
+                        }
+                    }
+                }
+
+                // The slot after the last, as a disabled one.
+                let offset = 1 + plan.len() * block;
+                self.q_seq.enable(region, offset)?;
+                for column in [self.is_enabled, self.is_first, self.is_last, self.id] {
+                    constant(region, column, offset, 0)?;
+                }
+                Ok(in_chunks)
+            },
+        )?;
+
+        if let Some(instance) = table.inputs_instance() {
+            for (i, cell) in in_chunks.iter().enumerate() {
+                layouter.constrain_instance(cell.cell(), instance, i)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Most slots of blocks of `block_bytes` bytes padded with `padding` the
+/// calls of a capacity of `max_calls` calls of `max_len` take, see
+/// [`CallSlotsConfig::max_slots`].
+pub(crate) fn max_slots(
+    max_calls: usize,
+    max_len: usize,
+    block_bytes: usize,
+    padding: SlotPadding,
+) -> usize {
+    match padding {
+        SlotPadding::None => max_calls,
+        _ if max_calls == UNBOUNDED || max_len == UNBOUNDED => UNBOUNDED,
+        SlotPadding::Md(padding) => {
+            (max_len + max_calls * (padding.len_bytes + block_bytes)) / block_bytes
+        }
+        SlotPadding::Zeros => max_len / block_bytes + max_calls,
+    }
+}
+
+/// Number of blocks of `block_bytes` bytes of an input of `len` bytes
+/// padded with `padding`.
+pub(crate) fn blocks(len: usize, block_bytes: usize, padding: SlotPadding) -> usize {
+    match padding {
+        SlotPadding::None => 1,
+        SlotPadding::Md(padding) => (len + 1 + padding.len_bytes).div_ceil(block_bytes),
+        SlotPadding::Zeros => len.div_ceil(block_bytes).max(1),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{hash_table::HashTable, public_inputs::PublicInputs};
+    use halo2_proofs::{
+        circuit::SimpleFloorPlanner,
+        dev::MockProver,
+        halo2curves::bn256::Fr,
+        plonk::{Circuit, FirstPhase},
+    };
+
+    /// A toy hash over blocks of 8 bytes, padded with the 2-byte big-endian
+    /// length in bits, whose digest is the first 4 bytes of the last block.
+    const PADDING: MdPadding = MdPadding {
+        len_bytes: 2,
+        endianness: Endianness::Big,
+    };
+
+    fn pad(input: &[u8]) -> Vec<u8> {
+        let mut padded = input.to_vec();
+        padded.push(0x80);
+        padded.resize((padded.len() + 2).div_ceil(8) * 8 - 2, 0);
+        padded.extend_from_slice(&(input.len() as u16 * 8).to_be_bytes());
+        padded
+    }
+
+    fn digest(input: &[u8]) -> Vec<u8> {
+        let padded = pad(input);
+        padded[padded.len() - 8..][..4].to_vec()
+    }
+
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    enum Tamper {
+        None,
+        /// The table holds the call of another input.
+        Input,
+        /// The table holds another digest.
+        Digest,
+        /// A block holds the padding of another length.
+        Padding,
+        /// The instances hold another input byte.
+        Instance,
+    }
+
+    #[derive(Clone, Debug)]
+    struct TestCircuit {
+        inputs: Vec<Vec<u8>>,
+        slots: usize,
+        tamper: Tamper,
+    }
+
+    impl Circuit<Fr> for TestCircuit {
+        type Config = (HashTableConfig, CallSlotsConfig, Column<Advice>);
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            self.clone()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+            let constants = meta.fixed_column();
+            meta.enable_constant(constants);
+            let table = HashTable::construct(meta, 4);
+            let challenge = meta.challenge_usable_after(FirstPhase);
+            let mut table = HashTableConfig::configure(meta, table, challenge);
+            table.expose(meta);
+            let slots = CallSlotsConfig::configure(meta, &table, 8, 4, SlotPadding::Md(PADDING));
+            let bytes = meta.advice_column();
+            meta.enable_equality(bytes);
+            (table, slots, bytes)
+        }
+
+        fn synthesize(
+            &self,
+            (table, slots, bytes): Self::Config,
+            mut layouter: impl Layouter<Fr>,
+        ) -> Result<(), Error> {
+            let lens: Vec<_> = self.inputs.iter().map(Vec::len).collect();
+            let plan = slots.plan(&lens, self.slots);
+            let cells = layouter.assign_region(
+                || "blocks",
+                |mut region| {
+                    let mut offset = 0;
+                    let mut assign = |value: u64| {
+                        offset += 1;
+                        region.assign_advice(
+                            || "byte",
+                            bytes,
+                            offset - 1,
+                            || Value::known(Fr::from(value)),
+                        )
+                    };
+                    plan.iter()
+                        .map(|slot| {
+                            let mut block = match slot.call {
+                                Some(call) => pad(&self.inputs[call])[slot.offset..][..8].to_vec(),
+                                None => vec![0; 8],
+                            };
+                            if self.tamper == Tamper::Padding && slot.is_last {
+                                block[7] ^= 8;
+                            }
+                            let is_first = assign(slot.is_first as u64)?;
+                            let block = block
+                                .iter()
+                                .map(|byte| assign(*byte as u64))
+                                .collect::<Result<Vec<_>, _>>()?;
+                            Ok(SlotCells {
+                                is_first,
+                                digest: block[..4].to_vec(),
+                                block,
+                            })
+                        })
+                        .collect::<Result<Vec<_>, Error>>()
+                },
+            )?;
+            slots.assign(&mut layouter, "toy", &table, &lens, &plan, &cells)?;
+
+            let mut calls: Vec<_> = self
+                .inputs
+                .iter()
+                .map(|input| (input.clone(), digest(input)))
+                .collect();
+            match self.tamper {
+                Tamper::Input => calls[0].0.push(0),
+                Tamper::Digest => calls[0].1[3] ^= 1,
+                Tamper::None | Tamper::Padding | Tamper::Instance => {}
+            }
+            table.assign(&mut layouter, "toy", self.inputs.len(), &calls)?;
+            Ok(())
+        }
+    }
+
+    fn verify(inputs: Vec<Vec<u8>>, slots: usize, tamper: Tamper) -> bool {
+        let mut public_inputs = PublicInputs::new(4, 8, SlotPadding::Md(PADDING));
+        for input in &inputs {
+            public_inputs.call(input, &digest(input));
+        }
+        let mut instances = public_inputs.build();
+        if tamper == Tamper::Instance {
+            instances[1][0] += Fr::from(1);
+        }
+        let circuit = TestCircuit {
+            inputs,
+            slots,
+            tamper,
+        };
+        MockProver::run(10, &circuit, instances)
+            .unwrap()
+            .verify()
+            .is_ok()
+    }
+
+    #[test]
+    fn test_call_slots_plan() {
+        let mut meta = ConstraintSystem::<Fr>::default();
+        let (_, slots, _) = TestCircuit::configure(&mut meta);
+        assert_eq!([0, 5, 6, 13].map(|len| slots.blocks(len)), [1, 1, 2, 2]);
+        // two calls of 6 and 0 bytes, or of 5 and 1, take the most blocks.
+        assert_eq!(slots.max_slots(2, 6), 3);
+        let plan = slots.plan(&[6, 0], 4);
+        assert_eq!(
+            plan.iter().map(|slot| slot.call).collect::<Vec<_>>(),
+            [Some(0), Some(0), Some(1), None]
+        );
+        assert!(plan[0].is_first && !plan[0].is_last && plan[1].is_last);
+        assert_eq!(plan[1].offset, 8);
+    }
+
+    #[test]
+    fn test_call_slots() {
+        let inputs = || vec![vec![1, 2, 3], (0..13).collect(), vec![]];
+        assert!(verify(inputs(), 4, Tamper::None));
+        // the disabled slots after the calls.
+        assert!(verify(inputs(), 7, Tamper::None));
+        assert!(verify(vec![], 2, Tamper::None));
+
+        assert!(!verify(inputs(), 4, Tamper::Input));
+        assert!(!verify(inputs(), 4, Tamper::Digest));
+        assert!(!verify(inputs(), 4, Tamper::Padding));
+        assert!(!verify(inputs(), 4, Tamper::Instance));
+    }
+}
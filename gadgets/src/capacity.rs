@@ -0,0 +1,92 @@
+//! Compile-time sizing of the circuits of the precompiles.
+//!
+//! The config of a circuit takes the most calls its witness holds, and the
+//! most input bytes of them in total, or rounds of each for BLAKE2F, as
+//! const generics, e.g.
+//! `Sha2Config<F, 64, 8192>`, so that a deployment of a fixed block capacity
+//! rejects a witness exceeding it as its chip is loaded. A chip of a bounded
+//! capacity pads its layout to it with disabled slots and table rows, so
+//! that the layout of the circuit, and thus its keys, are those of any
+//! witness within it, e.g. of its default, see [`padded`]. They are
+//! [`UNBOUNDED`] by default, the circuit being sized at run time by the rows
+//! of its degree, e.g. by the `max_k` of the `CircuitInputBuilder` of the
+//! super-circuit, and its layout then depends on its witness.
+
+use tracing::error;
+
+use crate::Error;
+
+/// Capacity of a circuit sized at run time.
+pub const UNBOUNDED: usize = usize::MAX;
+
+/// Checks that the `calls` of a witness, of `len` input bytes in total or
+/// rounds of a call, fit in the capacity of `max_calls` calls of `max_len`, or an
+/// [`Error::Capacity`], which fails the synthesis of a chip loading them,
+/// and is logged as the `plonk::Error` it converts to is not telling.
+pub fn check_capacity(
+    calls: usize,
+    len: usize,
+    max_calls: usize,
+    max_len: usize,
+) -> Result<(), Error> {
+    if calls > max_calls || len > max_len {
+        error!(
+            calls,
+            len, max_calls, max_len, "the witness exceeds the capacity of the circuit"
+        );
+        return Err(Error::Capacity {
+            calls,
+            len,
+            max_calls,
+            max_len,
+        });
+    }
+    Ok(())
+}
+
+/// The size, e.g. in slots or table rows, of the layout of `used` of a
+/// capacity of `max`: `max` if it is bounded, and `used` if it is
+/// [`UNBOUNDED`].
+pub fn padded(used: usize, max: usize) -> usize {
+    assert!(used <= max, "the witness fits in the capacity");
+    if max == UNBOUNDED {
+        used
+    } else {
+        max
+    }
+}
+
+/// A capacity of `MAX_CALLS` calls of `MAX_LEN`, whose [`Self::CHECK`]
+/// fails the build if it is [`UNBOUNDED`], e.g. of a circuit whose keys are
+/// generated from its default, see [`crate::batch`].
+pub struct Bounded<const MAX_CALLS: usize, const MAX_LEN: usize>;
+
+impl<const MAX_CALLS: usize, const MAX_LEN: usize> Bounded<MAX_CALLS, MAX_LEN> {
+    pub const CHECK: () = assert!(
+        MAX_CALLS != UNBOUNDED && MAX_LEN != UNBOUNDED,
+        "a capacity that is bounded"
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_capacity() {
+        assert!(check_capacity(2, 128, 2, 128).is_ok());
+        assert!(matches!(
+            check_capacity(3, 128, 2, 128),
+            Err(Error::Capacity { calls: 3, .. })
+        ));
+        assert!(check_capacity(2, 129, 2, 128).is_err());
+        assert!(check_capacity(1 << 20, 1 << 30, UNBOUNDED, UNBOUNDED).is_ok());
+    }
+
+    #[test]
+    fn test_padded() {
+        assert_eq!(padded(3, 8), 8);
+        assert_eq!(padded(0, 8), 8);
+        assert_eq!(padded(3, UNBOUNDED), 3);
+    }
+}
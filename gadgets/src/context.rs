@@ -0,0 +1,197 @@
+//! A flat assignment API over the shared gadgets, in the style of the
+//! `Context` of halo2-lib, for circuits written against it that embed the
+//! gadgets of this workspace.
+//!
+//! A [`Context`] lays the gadgets out one after the other in a region, each
+//! on the next free row, so that a caller neither tracks the offsets nor
+//! opens the regions of the gadgets: it calls e.g.
+//! `small.range_check(ctx, &cell)` as it would call the `RangeChip` of
+//! halo2-lib. Every gadget call takes a row of its own, whatever the columns
+//! it uses, so a layout that shares rows between gadgets still assigns them
+//! through their `Region` API.
+//!
+//! ```ignore
+//! let sum = assign_context(&mut layouter, "sum", |ctx| {
+//!     let (_, sum) = add.add(ctx, &[a, b])?;
+//!     let low_byte = &bytes.decompose(ctx, &sum)?[0];
+//!     small.range_check(ctx, low_byte)?;
+//!     Ok(sum)
+//! })?;
+//! ```
+
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{Layouter, Region},
+    plonk::Error,
+};
+
+use crate::{util::AssignedValue, BytesChip, ModAddChip, RangeCheckChip};
+
+/// A region assigned row after row, from its first row.
+#[derive(Debug)]
+pub struct Context<'a, 'r, F: FieldExt> {
+    region: &'a mut Region<'r, F>,
+    offset: usize,
+}
+
+impl<'a, 'r, F: FieldExt> Context<'a, 'r, F> {
+    pub fn new(region: &'a mut Region<'r, F>) -> Self {
+        Self { region, offset: 0 }
+    }
+
+    /// The next free row of the region.
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    /// The region, to assign cells of the caller on the rows assigned so
+    /// far.
+    pub fn region(&mut self) -> &mut Region<'r, F> {
+        self.region
+    }
+
+    /// Runs `assign` on the next free row, and takes the row.
+    pub fn next_row<T>(
+        &mut self,
+        assign: impl FnOnce(&mut Region<'r, F>, usize) -> Result<T, Error>,
+    ) -> Result<T, Error> {
+        let result = assign(self.region, self.offset)?;
+        self.offset += 1;
+        Ok(result)
+    }
+}
+
+/// Assigns the region `name` through a [`Context`].
+pub fn assign_context<F: FieldExt, T>(
+    layouter: &mut impl Layouter<F>,
+    name: &str,
+    mut assign: impl FnMut(&mut Context<'_, '_, F>) -> Result<T, Error>,
+) -> Result<T, Error> {
+    layouter.assign_region(|| name, |mut region| assign(&mut Context::new(&mut region)))
+}
+
+impl<F: FieldExt, const BITS: usize> RangeCheckChip<F, BITS> {
+    /// Range checks a copy of `cell` on the next row of `ctx`.
+    pub fn range_check(
+        &self,
+        ctx: &mut Context<'_, '_, F>,
+        cell: &AssignedValue<F>,
+    ) -> Result<AssignedValue<F>, Error> {
+        ctx.next_row(|region, offset| self.copy(region, offset, cell))
+    }
+
+    /// Assigns `value`, range checked, on the next row of `ctx`.
+    pub fn load_range_checked(
+        &self,
+        ctx: &mut Context<'_, '_, F>,
+        value: u64,
+    ) -> Result<AssignedValue<F>, Error> {
+        ctx.next_row(|region, offset| self.assign(region, offset, value))
+    }
+}
+
+impl<F: FieldExt, const BITS: usize> ModAddChip<F, BITS> {
+    /// Adds `summands` modulo `2^BITS` on the next row of `ctx`, returning
+    /// the summand cells, for the caller to copy its own cells into, and the
+    /// `out` cell.
+    pub fn add(
+        &self,
+        ctx: &mut Context<'_, '_, F>,
+        summands: &[u64],
+    ) -> Result<(Vec<AssignedValue<F>>, AssignedValue<F>), Error> {
+        ctx.next_row(|region, offset| self.assign(region, offset, summands))
+    }
+}
+
+impl<F: FieldExt, const N_BYTES: usize> BytesChip<F, N_BYTES> {
+    /// Decomposes a copy of `cell` into its bytes on the next row of `ctx`,
+    /// returning them in the endianness of the chip.
+    pub fn decompose(
+        &self,
+        ctx: &mut Context<'_, '_, F>,
+        cell: &AssignedValue<F>,
+    ) -> Result<Vec<AssignedValue<F>>, Error> {
+        ctx.next_row(|region, offset| self.copy(region, offset, cell))
+    }
+
+    /// Assigns the value of `bytes` and the bytes on the next row of `ctx`.
+    pub fn load_bytes(
+        &self,
+        ctx: &mut Context<'_, '_, F>,
+        bytes: &[u8; N_BYTES],
+    ) -> Result<(AssignedValue<F>, Vec<AssignedValue<F>>), Error> {
+        ctx.next_row(|region, offset| self.assign(region, offset, bytes))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{tables::RangeTable, BytesConfig, Endianness, ModAddConfig, RangeCheckConfig};
+    use halo2_proofs::{
+        circuit::SimpleFloorPlanner,
+        dev::MockProver,
+        halo2curves::bn256::Fr,
+        plonk::{Circuit, ConstraintSystem},
+    };
+
+    /// Adds two words, range checks their sum and decomposes it, through a
+    /// context.
+    #[derive(Default)]
+    struct TestCircuit {
+        summands: [u64; 2],
+    }
+
+    impl Circuit<Fr> for TestCircuit {
+        type Config = (
+            RangeTable<8>,
+            ModAddConfig<32>,
+            RangeCheckConfig<8>,
+            BytesConfig<4>,
+        );
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+            let table = RangeTable::construct(meta);
+            (
+                table,
+                ModAddConfig::configure(meta, 2, table),
+                RangeCheckConfig::configure(meta, table),
+                BytesConfig::configure(meta, table, Endianness::Little),
+            )
+        }
+
+        fn synthesize(
+            &self,
+            (table, add, range, bytes): Self::Config,
+            mut layouter: impl Layouter<Fr>,
+        ) -> Result<(), Error> {
+            table.load(&mut layouter)?;
+            let (add, range, bytes) = (
+                ModAddChip::construct(add),
+                RangeCheckChip::construct(range),
+                BytesChip::<Fr, 4>::construct(bytes),
+            );
+            assign_context(&mut layouter, "context", |ctx| {
+                let (_, out) = add.add(ctx, &self.summands)?;
+                let low_byte = &bytes.decompose(ctx, &out)?[0];
+                range.range_check(ctx, low_byte)?;
+                assert_eq!(ctx.offset(), 3);
+                Ok(())
+            })
+        }
+    }
+
+    #[test]
+    fn test_context() {
+        let circuit = TestCircuit {
+            summands: [u32::MAX as u64, 0x0102],
+        };
+        let prover = MockProver::run(9, &circuit, vec![]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+    }
+}
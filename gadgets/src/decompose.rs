@@ -0,0 +1,241 @@
+use std::marker::PhantomData;
+
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{Region, Value},
+    plonk::{Advice, Column, ConstraintSystem, Error, Expression, Selector},
+    poly::Rotation,
+};
+
+use crate::{
+    tables::RangeTable,
+    util::{mask, pow_of_two, AssignedValue},
+};
+
+/// Decomposes a value into `NUM_LIMBS` little-endian limbs of `LIMB_BITS`
+/// bits each, laid out within a single row. Every limb is range checked, so
+/// the value itself is constrained to `LIMB_BITS * NUM_LIMBS` bits.
+#[derive(Clone, Copy, Debug)]
+pub struct DecomposeConfig<const LIMB_BITS: usize, const NUM_LIMBS: usize> {
+    q_decompose: Selector,
+    pub value: Column<Advice>,
+    pub limbs: [Column<Advice>; NUM_LIMBS],
+}
+
+impl<const LIMB_BITS: usize, const NUM_LIMBS: usize> DecomposeConfig<LIMB_BITS, NUM_LIMBS> {
+    pub fn configure<F: FieldExt>(
+        meta: &mut ConstraintSystem<F>,
+        table: RangeTable<LIMB_BITS>,
+    ) -> Self {
+        assert!(
+            LIMB_BITS * NUM_LIMBS <= 64,
+            "decomposed values must fit in a u64"
+        );
+
+        let q_decompose = meta.complex_selector();
+        let value = meta.advice_column();
+        let limbs = [(); NUM_LIMBS].map(|_| meta.advice_column());
+        meta.enable_equality(value);
+        for limb in limbs {
+            meta.enable_equality(limb);
+        }
+
+        meta.create_gate("value = sum(limb_i * 2^(i * LIMB_BITS))", |meta| {
+            let q_decompose = meta.query_selector(q_decompose);
+            let value = meta.query_advice(value, Rotation::cur());
+            let recomposed =
+                limbs
+                    .iter()
+                    .enumerate()
+                    .fold(Expression::Constant(F::zero()), |acc, (i, limb)| {
+                        acc + meta.query_advice(*limb, Rotation::cur())
+                            * pow_of_two::<F>(i * LIMB_BITS)
+                    });
+            vec![q_decompose * (value - recomposed)]
+        });
+
+        for limb in limbs {
+            meta.lookup("limb is in range", |meta| {
+                let q_decompose = meta.query_selector(q_decompose);
+                let limb = meta.query_advice(limb, Rotation::cur());
+                vec![(q_decompose * limb, table.value)]
+            });
+        }
+
+        Self {
+            q_decompose,
+            value,
+            limbs,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct DecomposeChip<F, const LIMB_BITS: usize, const NUM_LIMBS: usize> {
+    config: DecomposeConfig<LIMB_BITS, NUM_LIMBS>,
+    _marker: PhantomData<F>,
+}
+
+impl<F: FieldExt, const LIMB_BITS: usize, const NUM_LIMBS: usize>
+    DecomposeChip<F, LIMB_BITS, NUM_LIMBS>
+{
+    pub fn construct(config: DecomposeConfig<LIMB_BITS, NUM_LIMBS>) -> Self {
+        Self {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Native little-endian limbs of `value`.
+    pub fn limbs(value: u64) -> [u64; NUM_LIMBS] {
+        let mut i = 0;
+        [(); NUM_LIMBS].map(|_| {
+            let limb = value.checked_shr((i * LIMB_BITS) as u32).unwrap_or(0) & mask(LIMB_BITS);
+            i += 1;
+            limb
+        })
+    }
+
+    /// Assigns `value` and its limbs at `offset`, returning the value cell and
+    /// the limb cells in little-endian order.
+    pub fn assign(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        value: u64,
+    ) -> Result<(AssignedValue<F>, Vec<AssignedValue<F>>), Error> {
+        self.config.q_decompose.enable(region, offset)?;
+        let value_cell = region.assign_advice(
+            || "value",
+            self.config.value,
+            offset,
+            || Value::known(F::from(value)),
+        )?;
+        let limb_cells = self
+            .config
+            .limbs
+            .iter()
+            .zip(Self::limbs(value))
+            .map(|(column, limb)| {
+                region.assign_advice(|| "limb", *column, offset, || Value::known(F::from(limb)))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok((value_cell, limb_cells))
+    }
+
+    /// Copies an already assigned cell into the value column at `offset` and
+    /// assigns its limbs, returning the limb cells in little-endian order.
+    pub fn copy(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        cell: &AssignedValue<F>,
+    ) -> Result<Vec<AssignedValue<F>>, Error> {
+        self.config.q_decompose.enable(region, offset)?;
+        let value_cell = cell.copy_advice(|| "value", region, self.config.value, offset)?;
+        let limbs = value_cell
+            .value()
+            .map(|value| Self::limbs(value.get_lower_128() as u64));
+        self.config
+            .limbs
+            .iter()
+            .enumerate()
+            .map(|(i, column)| {
+                region.assign_advice(
+                    || "limb",
+                    *column,
+                    offset,
+                    || limbs.map(|limbs| F::from(limbs[i])),
+                )
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use halo2_proofs::{
+        circuit::{Layouter, SimpleFloorPlanner},
+        dev::MockProver,
+        halo2curves::{bn256::Fr, pasta::pallas},
+        plonk::Circuit,
+    };
+    use mutation_coverage::mutation_coverage;
+
+    #[derive(Default)]
+    struct TestCircuit<F> {
+        values: Vec<u64>,
+        _marker: PhantomData<F>,
+    }
+
+    impl<F: FieldExt> Circuit<F> for TestCircuit<F> {
+        type Config = (RangeTable<8>, DecomposeConfig<8, 4>);
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            let table = RangeTable::construct(meta);
+            (table, DecomposeConfig::configure(meta, table))
+        }
+
+        fn synthesize(
+            &self,
+            (table, config): Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            table.load(&mut layouter)?;
+            let chip = DecomposeChip::construct(config);
+            layouter.assign_region(
+                || "decompose",
+                |mut region| {
+                    for (offset, value) in self.values.iter().enumerate() {
+                        chip.assign(&mut region, offset, *value)?;
+                    }
+                    Ok(())
+                },
+            )
+        }
+    }
+
+    fn test_decompose_circuit_over<F: FieldExt>() {
+        assert_eq!(
+            DecomposeChip::<F, 8, 4>::limbs(0x0102_0304),
+            [0x04, 0x03, 0x02, 0x01]
+        );
+
+        let circuit = TestCircuit::<F> {
+            values: vec![0, 0x0102_0304, u32::MAX as u64],
+            _marker: PhantomData,
+        };
+        let prover = MockProver::run(9, &circuit, vec![]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+
+        // the top byte is truncated away, so recomposition fails.
+        let circuit = TestCircuit::<F> {
+            values: vec![1 << 32],
+            _marker: PhantomData,
+        };
+        let prover = MockProver::run(9, &circuit, vec![]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+
+    #[test]
+    fn test_decompose_circuit() {
+        test_decompose_circuit_over::<Fr>();
+        test_decompose_circuit_over::<pallas::Base>();
+    }
+
+    #[test]
+    fn test_decompose_circuit_coverage() {
+        let circuit = TestCircuit::<Fr> {
+            values: vec![0, 0x0102_0304],
+            _marker: PhantomData,
+        };
+        let report = mutation_coverage(9, &circuit, vec![]).unwrap();
+        assert_eq!(report.unconstrained().count(), 0, "{}", report.report());
+    }
+}
@@ -0,0 +1,153 @@
+//! The constraint graph of a configuration in the dot format of Graphviz,
+//! for auditors to see which columns feed which gates and lookups of a
+//! circuit, e.g. that every advice column of a chip is constrained:
+//!
+//! ```text
+//! dot -Tsvg sha2_256.dot -o sha2_256.svg
+//! ```
+//!
+//! The columns, selectors and challenges are the boxes of the graph, and
+//! the gates and lookups its ellipses, with an edge from every column a gate
+//! queries, at any rotation, to the gate. The input columns of a lookup are
+//! drawn into it, and its table columns out of it, dashed. The columns of
+//! the permutation, whose cells are copy-constrained, are drawn doubled.
+//!
+//! Unlike the `CircuitLayout` of halo2, which renders the regions a
+//! synthesis assigns, the graph only depends on the configuration, and so
+//! needs no witness.
+
+use std::{collections::BTreeSet, fmt::Write as _};
+
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    plonk::{Any, ConstraintSystem, Expression},
+};
+
+/// The columns, selectors and challenges queried by `expression`, as the
+/// names of their nodes.
+fn queried<F: FieldExt>(expression: &Expression<F>) -> BTreeSet<String> {
+    let node = |name: String| BTreeSet::from([name]);
+    expression.evaluate(
+        &|_| BTreeSet::new(),
+        &|selector| node(format!("selector_{}", selector.index())),
+        &|query| node(format!("fixed_{}", query.column_index())),
+        &|query| node(format!("advice_{}", query.column_index())),
+        &|query| node(format!("instance_{}", query.column_index())),
+        &|challenge| node(format!("challenge_{}", challenge.index())),
+        &|a| a,
+        &|mut a, b| {
+            a.extend(b);
+            a
+        },
+        &|mut a, b| {
+            a.extend(b);
+            a
+        },
+        &|a, _| a,
+    )
+}
+
+/// The constraint graph of the configuration built by `configure` over an
+/// empty constraint system, in the dot format, see the module docs.
+pub fn constraint_graph<F: FieldExt, C>(
+    name: &str,
+    configure: impl FnOnce(&mut ConstraintSystem<F>) -> C,
+) -> String {
+    let mut meta = ConstraintSystem::default();
+    configure(&mut meta);
+
+    let permuted: BTreeSet<String> = meta
+        .permutation()
+        .get_columns()
+        .iter()
+        .map(|column| match column.column_type() {
+            Any::Advice(_) => format!("advice_{}", column.index()),
+            Any::Fixed => format!("fixed_{}", column.index()),
+            Any::Instance => format!("instance_{}", column.index()),
+        })
+        .collect();
+    let columns = [
+        ("advice", meta.num_advice_columns()),
+        ("fixed", meta.num_fixed_columns()),
+        ("instance", meta.num_instance_columns()),
+        ("selector", meta.num_selectors()),
+        ("challenge", meta.challenge_phase().len()),
+    ];
+
+    let mut dot = format!("digraph \"{name}\" {{\n    rankdir=LR;\n    node [shape=box];\n");
+    for (kind, count) in columns {
+        for index in 0..count {
+            let node = format!("{kind}_{index}");
+            let peripheries = if permuted.contains(&node) { 2 } else { 1 };
+            writeln!(
+                dot,
+                "    {node} [label=\"{kind} {index}\", peripheries={peripheries}];"
+            )
+            .unwrap();
+        }
+    }
+    for (index, gate) in meta.gates().iter().enumerate() {
+        writeln!(
+            dot,
+            "    gate_{index} [label=\"{}\", shape=ellipse];",
+            gate.name().escape_default()
+        )
+        .unwrap();
+        let columns = gate.polynomials().iter().flat_map(queried::<F>);
+        for column in columns.collect::<BTreeSet<_>>() {
+            writeln!(dot, "    {column} -> gate_{index};").unwrap();
+        }
+    }
+    for (index, lookup) in meta.lookups().iter().enumerate() {
+        writeln!(
+            dot,
+            "    lookup_{index} [label=\"lookup {index}\", shape=ellipse];"
+        )
+        .unwrap();
+        let inputs = lookup.input_expressions().iter().flat_map(queried::<F>);
+        for column in inputs.collect::<BTreeSet<_>>() {
+            writeln!(dot, "    {column} -> lookup_{index};").unwrap();
+        }
+        let tables = lookup.table_expressions().iter().flat_map(queried::<F>);
+        for column in tables.collect::<BTreeSet<_>>() {
+            writeln!(dot, "    lookup_{index} -> {column} [style=dashed];").unwrap();
+        }
+    }
+    dot.push_str("}\n");
+    dot
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use halo2_proofs::{halo2curves::bn256::Fr, poly::Rotation};
+
+    #[test]
+    fn test_constraint_graph() {
+        let dot = constraint_graph::<Fr, _>("test", |meta| {
+            let q = meta.selector();
+            let [a, b, c] = [0; 3].map(|_| meta.advice_column());
+            let table = meta.lookup_table_column();
+            meta.enable_equality(c);
+            meta.create_gate("c = a + b", |meta| {
+                let q = meta.query_selector(q);
+                let [a, b, c] = [a, b, c].map(|column| meta.query_advice(column, Rotation::cur()));
+                vec![q * (a + b - c)]
+            });
+            meta.lookup("a in table", |meta| {
+                vec![(meta.query_advice(a, Rotation::next()), table)]
+            });
+        });
+        for line in [
+            "advice_2 [label=\"advice 2\", peripheries=2];",
+            "gate_0 [label=\"c = a + b\", shape=ellipse];",
+            "selector_0 -> gate_0;",
+            "advice_1 -> gate_0;",
+            "advice_0 -> lookup_0;",
+            "lookup_0 -> fixed_0 [style=dashed];",
+        ] {
+            assert!(dot.contains(line), "{line} not in {dot}");
+        }
+        assert!(!dot.contains("advice_1 -> lookup_0"));
+    }
+}
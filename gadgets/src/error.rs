@@ -0,0 +1,104 @@
+//! The error of the circuits of the workspace and of their helpers, so that
+//! the CLI and the integrators report why a witness, an input or a key is
+//! refused rather than the bare `plonk::Error::Synthesis` of halo2.
+//!
+//! `Circuit::synthesize` can only fail with a `plonk::Error`, which an
+//! [`Error`] converts to, so that the chips fail the synthesis with `?`; the
+//! reason is then only logged, and the helpers outside of the synthesis,
+//! e.g. [`check_capacity`](crate::capacity::check_capacity) or the decoding
+//! of the inputs of the precompiles, return it.
+
+use std::io;
+
+use halo2_proofs::plonk;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum Error {
+    /// The proof system failed, e.g. the rows of the degree do not fit the
+    /// witness.
+    #[error("the proof system failed: {0}")]
+    Plonk(#[from] plonk::Error),
+    /// The witness does not fit in the capacity of a circuit sized at
+    /// compile time, see [`crate::capacity`].
+    #[error(
+        "the witness of {calls} calls of {len} bytes or rounds exceeds the capacity of the \
+         circuit, {max_calls} calls of {max_len}"
+    )]
+    Capacity {
+        calls: usize,
+        len: usize,
+        max_calls: usize,
+        max_len: usize,
+    },
+    /// The input of a call is not an encoding of the input of the
+    /// precompile, e.g. a BLAKE2F input of another length than 213 bytes.
+    #[error("the input is not that of a {precompile} call: {reason}")]
+    Encoding {
+        precompile: &'static str,
+        reason: String,
+    },
+    /// The witness of a call is inconsistent, e.g. its digest is not that of
+    /// its input, see [`crate::validate`].
+    #[error("the witness of the {precompile} call {call} is invalid: {reason}")]
+    Witness {
+        precompile: &'static str,
+        call: usize,
+        reason: String,
+    },
+    /// The key was written by another version of its circuit, see
+    /// [`crate::keys`].
+    #[error(
+        "the key is of version {found:016x} of the circuit rather than {expected:016x}, \
+         generate the keys again"
+    )]
+    KeyVersion { found: u64, expected: u64 },
+    /// Reading or writing a file, a key or a proof failed.
+    #[error(transparent)]
+    Io(#[from] io::Error),
+}
+
+impl From<Error> for plonk::Error {
+    /// The error a chip fails the synthesis with, as halo2 only knows its
+    /// own errors.
+    fn from(err: Error) -> Self {
+        match err {
+            Error::Plonk(err) => err,
+            Error::Io(err) => plonk::Error::Transcript(err),
+            _ => plonk::Error::Synthesis,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_error_messages() {
+        let err = Error::Capacity {
+            calls: 3,
+            len: 64,
+            max_calls: 2,
+            max_len: 128,
+        };
+        assert_eq!(
+            err.to_string(),
+            "the witness of 3 calls of 64 bytes or rounds exceeds the capacity of the circuit, 2 \
+             calls of 128"
+        );
+        assert!(matches!(plonk::Error::from(err), plonk::Error::Synthesis));
+
+        let err = Error::from(plonk::Error::NotEnoughRowsAvailable { current_k: 4 });
+        assert!(err
+            .to_string()
+            .starts_with("the proof system failed: k = 4"));
+        assert!(matches!(
+            plonk::Error::from(err),
+            plonk::Error::NotEnoughRowsAvailable { current_k: 4 }
+        ));
+
+        let err = Error::from(io::Error::new(io::ErrorKind::NotFound, "no such file"));
+        assert_eq!(err.to_string(), "no such file");
+    }
+}
@@ -0,0 +1,192 @@
+use std::marker::PhantomData;
+
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{Layouter, SimpleFloorPlanner, Value},
+    plonk::{Advice, Any, Circuit, Column, ConstraintSystem, Error},
+};
+
+use crate::{
+    assigned_bytes::AssignedBytes,
+    rows::{self, ChipCircuit, RowUsage},
+    stats::CircuitStats,
+    tables::FixedTables,
+    util::k_for_rows,
+};
+
+/// The cells of a hash call, for a composing chip to constrain to its own:
+/// the bytes of the input and of the digest.
+#[derive(Clone, Debug)]
+pub struct AssignedCall<F: FieldExt> {
+    pub input: AssignedBytes<F>,
+    pub digest: AssignedBytes<F>,
+}
+
+/// Common interface of the precompile hash circuits in this workspace.
+///
+/// It is implemented by the chip of every hash circuit, so that composing
+/// circuits (e.g. a super-circuit or the benchmarks) can configure, size and
+/// load any of them generically.
+pub trait HashCircuitExt<F: FieldExt>: Sized {
+    /// Configuration of the circuit, including its lookup table.
+    type Config: Clone;
+    /// Witness data of all the hash calls handled by the chip.
+    type Witness: Clone;
+
+    /// Length of the digest in bytes.
+    const DIGEST_BYTES: usize;
+
+    /// Constructs the table of the circuit and configures its constraints,
+    /// for the circuit proven on its own, whose table is exposed in the
+    /// instance columns of [`Self::public_inputs`].
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config;
+
+    /// Configures the circuit over the fixed tables requested from `tables`,
+    /// which the caller loads once for all the circuits sharing them, e.g.
+    /// within a super-circuit, without instance columns, the calls being
+    /// those of the composing circuit. Circuits without fixed tables or
+    /// instance columns keep the default.
+    fn configure_with_tables(
+        meta: &mut ConstraintSystem<F>,
+        _tables: &mut FixedTables,
+    ) -> Self::Config {
+        Self::configure(meta)
+    }
+
+    /// Columns, gates, lookups and degree of [`Self::configure`].
+    fn stats() -> CircuitStats {
+        CircuitStats::of(Self::configure)
+    }
+
+    /// Constraint graph of [`Self::configure`] in the dot format, titled
+    /// `name`, see [`crate::dot`].
+    #[cfg(feature = "dev-graph")]
+    fn constraint_graph(name: &str) -> String {
+        crate::dot::constraint_graph(name, Self::configure)
+    }
+
+    /// Checks that `witness` fits in the capacity of the circuit, as
+    /// [`crate::batch::prove_batch`] does before proving it. Circuits sized
+    /// at run time keep the default.
+    fn check_witness(_witness: &Self::Witness) -> Result<(), crate::Error> {
+        Ok(())
+    }
+
+    /// The values of the instance columns of [`Self::configure`] for
+    /// `witness`, the table of the circuit as laid out by
+    /// [`crate::public_inputs`]. Circuits without instance columns keep the
+    /// default.
+    fn public_inputs(_witness: &Self::Witness) -> Vec<Vec<F>> {
+        vec![]
+    }
+
+    /// Rows of the regions the chip assigns as it loads `witness` over
+    /// `2^k` rows, see [`crate::rows`].
+    fn row_usage(k: u32, witness: &Self::Witness) -> Result<RowUsage, Error> {
+        let circuit = ChipCircuit::<F, Self> {
+            witness: witness.clone(),
+            _marker: PhantomData,
+        };
+        RowUsage::measure(k, &circuit, Self::public_inputs(witness))
+    }
+
+    /// Constructs the chip from its configuration and witness data.
+    fn construct(config: Self::Config, witness: Self::Witness) -> Self;
+
+    /// Assigns the hash calls and the table.
+    fn load(&self, layouter: &mut impl Layouter<F>) -> Result<(), Error>;
+
+    /// Columns of the table other circuits look up the hash calls from.
+    fn table_columns(config: &Self::Config) -> Vec<Column<Any>>;
+
+    /// Annotations of [`Self::table_columns`], in the same order.
+    fn annotations(config: &Self::Config) -> Vec<String>;
+
+    /// Rows the layout of `witness` takes, its tables included, before the
+    /// blinding rows, as the chip assigns them, see [`rows::layout_rows`].
+    fn layout_rows(witness: &Self::Witness) -> usize {
+        rows::layout_rows(&ChipCircuit::<F, Self> {
+            witness: witness.clone(),
+            _marker: PhantomData,
+        })
+    }
+
+    /// Smallest `k` that fits the layout of `witness` and the blinding rows
+    /// of [`Self::configure`], see [`k_for_rows`].
+    fn min_k(witness: &Self::Witness) -> u32 {
+        k_for_rows(Self::configure, Self::layout_rows(witness))
+    }
+
+    /// Assigns the hash calls and the table as [`Self::load`] does, and
+    /// returns the digest of every call, one byte per cell in the order the
+    /// bytes appear in the digest, for other chips to import. A chip is
+    /// loaded by either of them, not both.
+    fn assigned_digest(
+        &self,
+        layouter: &mut impl Layouter<F>,
+    ) -> Result<Vec<AssignedBytes<F>>, Error>;
+}
+
+/// The circuit of the chip `H` alone, loading `witness` through
+/// [`HashCircuitExt::assigned_digest`], whose digest cells are constrained
+/// to `digests`, to test that the digests a chip exports are those of its
+/// calls. Its instances are the [`HashCircuitExt::public_inputs`] of
+/// `witness`.
+pub struct DigestCircuit<F: FieldExt, H: HashCircuitExt<F>> {
+    pub witness: H::Witness,
+    pub digests: Vec<Vec<u8>>,
+    pub _marker: PhantomData<F>,
+}
+
+impl<F: FieldExt, H: HashCircuitExt<F>> Circuit<F> for DigestCircuit<F, H> {
+    type Config = (H::Config, Column<Advice>);
+    type FloorPlanner = SimpleFloorPlanner;
+
+    // The circuit is only synthesized by the `MockProver`, which keeps the
+    // witness.
+    fn without_witnesses(&self) -> Self {
+        Self {
+            witness: self.witness.clone(),
+            digests: self.digests.clone(),
+            _marker: PhantomData,
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let config = H::configure(meta);
+        let expected = meta.advice_column();
+        meta.enable_equality(expected);
+        (config, expected)
+    }
+
+    fn synthesize(
+        &self,
+        (config, expected): Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        let digests = H::construct(config, self.witness.clone()).assigned_digest(&mut layouter)?;
+        assert_eq!(digests.len(), self.digests.len(), "a digest per call");
+        layouter.assign_region(
+            || "expected digests",
+            |mut region| {
+                let mut offset = 0;
+                for (digest, bytes) in digests.iter().zip(&self.digests) {
+                    let cells = bytes
+                        .iter()
+                        .map(|byte| {
+                            offset += 1;
+                            region.assign_advice(
+                                || "expected digest byte",
+                                expected,
+                                offset - 1,
+                                || Value::known(F::from(*byte as u64)),
+                            )
+                        })
+                        .collect::<Result<Vec<_>, _>>()?;
+                    digest.constrain_equal(&mut region, &cells)?;
+                }
+                Ok(())
+            },
+        )
+    }
+}
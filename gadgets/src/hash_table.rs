@@ -0,0 +1,299 @@
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{Layouter, Value},
+    plonk::{
+        Advice, Any, Challenge, Column, ConstraintSystem, Error, Expression, FirstPhase, Instance,
+        SecondPhase, Selector,
+    },
+    poly::Rotation,
+};
+
+use crate::{
+    public_inputs::{digest_chunks, CHUNK_BYTES},
+    util::{rlc, AssignedValue},
+};
+
+/// The table of the calls of a hash circuit, one row per call, in the
+/// layout of the `KeccakTable` of the zkEVM:
+/// - `is_enabled`: whether the row is that of a call;
+/// - `id`: the call id, from 1, or 0 on a disabled row;
+/// - `input_rlc`: the RLC of the bytes of the input, the first byte
+///   multiplied by the highest power of the challenge;
+/// - `input_len`: the length of the input in bytes;
+/// - `output_rlc`: the RLC of the bytes of the digest;
+/// - `output`: the digest as chunks of 16 bytes, each the big-endian integer
+///   of its bytes, the high then low 128 bits of a 32-byte digest, see
+///   [`crate::public_inputs`].
+///
+/// The first row is disabled, and a disabled row is all zeros, so that a
+/// lookup gated off by the circuit looking it up finds a row.
+#[derive(Clone, Debug)]
+pub struct HashTable {
+    pub is_enabled: Column<Advice>,
+    pub id: Column<Advice>,
+    pub input_rlc: Column<Advice>,
+    pub input_len: Column<Advice>,
+    pub output_rlc: Column<Advice>,
+    pub output: Vec<Column<Advice>>,
+}
+
+impl HashTable {
+    /// Constructs the table of a hash of `digest_bytes` digests.
+    pub fn construct<F: FieldExt>(meta: &mut ConstraintSystem<F>, digest_bytes: usize) -> Self {
+        let mut column = |second_phase: bool| {
+            let column = if second_phase {
+                meta.advice_column_in(SecondPhase)
+            } else {
+                meta.advice_column_in(FirstPhase)
+            };
+            meta.enable_equality(column);
+            column
+        };
+        Self {
+            is_enabled: column(false),
+            id: column(false),
+            input_rlc: column(true),
+            input_len: column(false),
+            output_rlc: column(true),
+            output: (0..digest_bytes.div_ceil(CHUNK_BYTES))
+                .map(|_| column(false))
+                .collect(),
+        }
+    }
+
+    pub fn columns(&self) -> Vec<Column<Any>> {
+        [
+            self.is_enabled,
+            self.id,
+            self.input_rlc,
+            self.input_len,
+            self.output_rlc,
+        ]
+        .into_iter()
+        .chain(self.output.iter().copied())
+        .map(Column::into)
+        .collect()
+    }
+
+    pub fn annotations(&self) -> Vec<String> {
+        ["is_enabled", "id", "input_rlc", "input_len", "output_rlc"]
+            .into_iter()
+            .map(String::from)
+            .chain((0..self.output.len()).map(|i| format!("output_{i}")))
+            .collect()
+    }
+}
+
+/// The constraints of a [`HashTable`] on its own: the call ids count the
+/// enabled rows, which come first, and a disabled row is all zeros. The
+/// circuit filling it constrains its enabled rows to its calls.
+///
+/// A circuit proven on its own exposes the table and the input bytes of its
+/// calls in its instance columns, as [`crate::public_inputs`] lays them out,
+/// see [`Self::expose`].
+#[derive(Clone, Debug)]
+pub struct HashTableConfig {
+    pub table: HashTable,
+    /// The challenge of the RLCs.
+    pub challenge: Challenge,
+    /// Running number of the enabled rows.
+    calls: Column<Advice>,
+    q_row: Selector,
+    q_prefix: Selector,
+    instance: Option<Column<Instance>>,
+    inputs: Option<Column<Instance>>,
+}
+
+impl HashTableConfig {
+    pub fn configure<F: FieldExt>(
+        meta: &mut ConstraintSystem<F>,
+        table: HashTable,
+        challenge: Challenge,
+    ) -> Self {
+        let calls = meta.advice_column();
+        meta.enable_equality(calls);
+        let q_row = meta.selector();
+        let q_prefix = meta.selector();
+        let one = || Expression::Constant(F::one());
+
+        meta.create_gate("hash table row", |meta| {
+            let q_row = meta.query_selector(q_row);
+            let is_enabled = meta.query_advice(table.is_enabled, Rotation::cur());
+            let id = meta.query_advice(table.id, Rotation::cur());
+            let calls_cur = meta.query_advice(calls, Rotation::cur());
+            let calls_prev = meta.query_advice(calls, Rotation::prev());
+            let is_disabled = one() - is_enabled.clone();
+            let mut constraints = vec![
+                q_row.clone() * is_enabled.clone() * is_disabled.clone(),
+                q_row.clone() * (calls_cur.clone() - calls_prev - is_enabled.clone()),
+                q_row.clone() * (id - is_enabled * calls_cur),
+            ];
+            for column in [table.input_rlc, table.input_len, table.output_rlc]
+                .into_iter()
+                .chain(table.output.iter().copied())
+            {
+                let value = meta.query_advice(column, Rotation::cur());
+                constraints.push(q_row.clone() * is_disabled.clone() * value);
+            }
+            constraints
+        });
+
+        meta.create_gate("hash table calls first", |meta| {
+            let q_prefix = meta.query_selector(q_prefix);
+            let is_enabled = meta.query_advice(table.is_enabled, Rotation::cur());
+            let is_enabled_prev = meta.query_advice(table.is_enabled, Rotation::prev());
+            vec![q_prefix * is_enabled * (one() - is_enabled_prev)]
+        });
+
+        Self {
+            table,
+            challenge,
+            calls,
+            q_row,
+            q_prefix,
+            instance: None,
+            inputs: None,
+        }
+    }
+
+    /// Exposes the table in a new instance column, and the input bytes of
+    /// the calls in another, which the
+    /// [`CallSlotsConfig`](crate::CallSlotsConfig) of the table fills.
+    ///
+    /// The RLCs of the table are left out: their challenge is drawn after
+    /// the instances are committed to, so that they are bound by the input
+    /// bytes and digests instead.
+    pub fn expose<F: FieldExt>(&mut self, meta: &mut ConstraintSystem<F>) {
+        let [instance, inputs] = [(); 2].map(|_| {
+            let column = meta.instance_column();
+            meta.enable_equality(column);
+            column
+        });
+        self.instance = Some(instance);
+        self.inputs = Some(inputs);
+    }
+
+    /// The instance column of the table of [`Self::expose`], if any.
+    pub fn instance(&self) -> Option<Column<Instance>> {
+        self.instance
+    }
+
+    /// The instance column of the input bytes of [`Self::expose`], if any.
+    pub fn inputs_instance(&self) -> Option<Column<Instance>> {
+        self.inputs
+    }
+
+    /// Assigns the region `"{name} table"` of `rows` rows after its
+    /// disabled first row, the first of them the `calls` as pairs of
+    /// their input and digest, and the others disabled. The circuit enables
+    /// a constants column.
+    ///
+    /// Returns the cells of the enabled rows, in the order of
+    /// [`HashTable::columns`] without `is_enabled`. The rows are
+    /// constrained to their instances whatever the calls, so that the keys
+    /// of the circuit only depend on `rows`.
+    pub fn assign<F: FieldExt>(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        name: &str,
+        rows: usize,
+        calls: &[(Vec<u8>, Vec<u8>)],
+    ) -> Result<Vec<Vec<AssignedValue<F>>>, Error> {
+        assert!(calls.len() <= rows, "the calls fit in the table");
+        let table = &self.table;
+        let challenge = layouter.get_challenge(self.challenge);
+        let (mut cells, calls_cell) = layouter.assign_region(
+            || format!("{name} table"),
+            |mut region| {
+                let columns: Vec<_> = [table.is_enabled, table.id, table.input_rlc]
+                    .into_iter()
+                    .chain([table.input_len, table.output_rlc])
+                    .chain(table.output.iter().copied())
+                    .collect();
+                for column in columns {
+                    region.assign_advice_from_constant(|| "disabled", column, 0, F::zero())?;
+                }
+
+                let mut cells = Vec::with_capacity(calls.len());
+                let mut calls_cell =
+                    region.assign_advice_from_constant(|| "calls", self.calls, 0, F::zero())?;
+                for row in 1..=rows {
+                    self.q_row.enable(&mut region, row)?;
+                    if row > 1 {
+                        self.q_prefix.enable(&mut region, row)?;
+                    }
+                    let call = calls.get(row - 1);
+                    let value = |value: Option<Value<F>>| value.unwrap_or(Value::known(F::zero()));
+                    let is_enabled = Value::known(F::from(call.is_some() as u64));
+                    let id = call.map(|_| Value::known(F::from(row as u64)));
+                    let input_rlc = call.map(|(input, _)| rlc(input, challenge));
+                    let input_len =
+                        call.map(|(input, _)| Value::known(F::from(input.len() as u64)));
+                    let output_rlc = call.map(|(_, digest)| rlc(digest, challenge));
+                    let output = call.map(|(_, digest)| digest_chunks::<F>(digest));
+                    let output = (0..table.output.len())
+                        .map(|i| output.as_ref().map(|chunks| Value::known(chunks[i])));
+
+                    region.assign_advice(|| "is_enabled", table.is_enabled, row, || is_enabled)?;
+                    let mut row_cells = vec![
+                        region.assign_advice(|| "id", table.id, row, || value(id))?,
+                        region.assign_advice(
+                            || "input_rlc",
+                            table.input_rlc,
+                            row,
+                            || value(input_rlc),
+                        )?,
+                        region.assign_advice(
+                            || "input_len",
+                            table.input_len,
+                            row,
+                            || value(input_len),
+                        )?,
+                        region.assign_advice(
+                            || "output_rlc",
+                            table.output_rlc,
+                            row,
+                            || value(output_rlc),
+                        )?,
+                    ];
+                    for (column, chunk) in table.output.iter().zip(output) {
+                        row_cells.push(region.assign_advice(
+                            || "output",
+                            *column,
+                            row,
+                            || value(chunk),
+                        )?);
+                    }
+                    let calls = F::from(row.min(calls.len()) as u64);
+                    calls_cell = region.assign_advice(
+                        || "calls",
+                        self.calls,
+                        row,
+                        || Value::known(calls),
+                    )?;
+                    cells.push(row_cells);
+                }
+                Ok((cells, calls_cell))
+            },
+        )?;
+
+        if let Some(instance) = self.instance {
+            // The calls, then the call id, length and digest chunks of every
+            // row, those of the disabled rows being the zeros the instance
+            // column is padded with.
+            layouter.constrain_instance(calls_cell.cell(), instance, 0)?;
+            let values = 2 + table.output.len();
+            for (i, row) in cells.iter().enumerate() {
+                let offset = 1 + i * values;
+                let [id, _, input_len, _, output @ ..] = row.as_slice() else {
+                    unreachable!("the cells of a row");
+                };
+                for (j, cell) in [id, input_len].into_iter().chain(output).enumerate() {
+                    layouter.constrain_instance(cell.cell(), instance, offset + j)?;
+                }
+            }
+        }
+        cells.truncate(calls.len());
+        Ok(cells)
+    }
+}
@@ -0,0 +1,182 @@
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{Region, Value},
+    plonk::{Advice, Column, ConstraintSystem, Error, Expression, VirtualCells},
+};
+
+use crate::is_zero::{IsZeroChip, IsZeroConfig};
+
+/// Constrains whether two expressions are equal, as whether their difference
+/// is zero.
+#[derive(Clone, Debug)]
+pub struct IsEqualConfig<F> {
+    is_zero: IsZeroConfig<F>,
+}
+
+impl<F: FieldExt> IsEqualConfig<F> {
+    /// Configures the gadget over `lhs` and `rhs`, on the rows where
+    /// `q_enable` is non-zero.
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        q_enable: impl FnOnce(&mut VirtualCells<'_, F>) -> Expression<F>,
+        lhs: impl FnOnce(&mut VirtualCells<'_, F>) -> Expression<F>,
+        rhs: impl FnOnce(&mut VirtualCells<'_, F>) -> Expression<F>,
+        value_inv: Column<Advice>,
+    ) -> Self {
+        let is_zero =
+            IsZeroConfig::configure(meta, q_enable, |meta| lhs(meta) - rhs(meta), value_inv);
+        Self { is_zero }
+    }
+
+    /// Expression that is one if the values are equal, and zero otherwise.
+    pub fn expr(&self) -> Expression<F> {
+        self.is_zero.expr()
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct IsEqualChip<F> {
+    is_zero: IsZeroChip<F>,
+}
+
+impl<F: FieldExt> IsEqualChip<F> {
+    pub fn construct(config: IsEqualConfig<F>) -> Self {
+        Self {
+            is_zero: IsZeroChip::construct(config.is_zero),
+        }
+    }
+
+    pub fn assign(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        lhs: Value<F>,
+        rhs: Value<F>,
+    ) -> Result<(), Error> {
+        self.is_zero.assign(region, offset, lhs - rhs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use halo2_proofs::{
+        circuit::{Layouter, SimpleFloorPlanner},
+        dev::MockProver,
+        halo2curves::{bn256::Fr, pasta::pallas},
+        plonk::{Circuit, Selector},
+        poly::Rotation,
+    };
+    use mutation_coverage::{mutation_coverage, CellLocation};
+    use std::marker::PhantomData;
+
+    /// Checks `is_equal(lhs, 56) == expected` on every row.
+    #[derive(Default)]
+    struct TestCircuit<F> {
+        rows: Vec<(u64, bool)>,
+        _marker: PhantomData<F>,
+    }
+
+    impl<F: FieldExt> Circuit<F> for TestCircuit<F> {
+        type Config = (Selector, Column<Advice>, Column<Advice>, IsEqualConfig<F>);
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            let q_enable = meta.selector();
+            let lhs = meta.advice_column();
+            let expected = meta.advice_column();
+            let value_inv = meta.advice_column();
+            let is_equal = IsEqualConfig::configure(
+                meta,
+                |meta| meta.query_selector(q_enable),
+                |meta| meta.query_advice(lhs, Rotation::cur()),
+                |_| Expression::Constant(F::from(56)),
+                value_inv,
+            );
+
+            meta.create_gate("is_equal == expected", |meta| {
+                let q_enable = meta.query_selector(q_enable);
+                let expected = meta.query_advice(expected, Rotation::cur());
+                vec![q_enable * (is_equal.expr() - expected)]
+            });
+
+            (q_enable, lhs, expected, is_equal)
+        }
+
+        fn synthesize(
+            &self,
+            (q_enable, lhs, expected, is_equal): Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            let chip = IsEqualChip::construct(is_equal);
+            layouter.assign_region(
+                || "is equal",
+                |mut region| {
+                    for (offset, (value, is_equal)) in self.rows.iter().enumerate() {
+                        q_enable.enable(&mut region, offset)?;
+                        let value = Value::known(F::from(*value));
+                        region.assign_advice(|| "lhs", lhs, offset, || value)?;
+                        region.assign_advice(
+                            || "expected",
+                            expected,
+                            offset,
+                            || Value::known(F::from(*is_equal as u64)),
+                        )?;
+                        chip.assign(&mut region, offset, value, Value::known(F::from(56)))?;
+                    }
+                    Ok(())
+                },
+            )
+        }
+    }
+
+    fn test_is_equal_over<F: FieldExt>() {
+        let circuit = TestCircuit::<F> {
+            rows: vec![(56, true), (0, false), (55, false), (57, false)],
+            _marker: PhantomData,
+        };
+        let prover = MockProver::run(4, &circuit, vec![]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+
+        for row in [(56, false), (57, true)] {
+            let circuit = TestCircuit::<F> {
+                rows: vec![row],
+                _marker: PhantomData,
+            };
+            let prover = MockProver::run(4, &circuit, vec![]).unwrap();
+            assert!(prover.verify().is_err());
+        }
+    }
+
+    #[test]
+    fn test_is_equal() {
+        test_is_equal_over::<Fr>();
+        test_is_equal_over::<pallas::Base>();
+    }
+
+    #[test]
+    fn test_is_equal_coverage() {
+        let circuit = TestCircuit::<Fr> {
+            rows: vec![(56, true), (7, false)],
+            _marker: PhantomData,
+        };
+        let report = mutation_coverage(4, &circuit, vec![]).unwrap();
+        // Any inverse satisfies the gates where the operands are equal.
+        let value_inv = CellLocation {
+            region_index: 0,
+            region_name: String::from("is equal"),
+            column: 2,
+            offset: 0,
+        };
+        assert_eq!(
+            report.unconstrained().collect::<Vec<_>>(),
+            vec![&value_inv],
+            "{}",
+            report.report()
+        );
+    }
+}
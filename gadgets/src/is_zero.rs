@@ -0,0 +1,196 @@
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{Region, Value},
+    plonk::{Advice, Column, ConstraintSystem, Error, Expression, VirtualCells},
+    poly::Rotation,
+};
+
+/// Constrains whether an expression is zero, through the inverse of its value
+/// witnessed in `value_inv`: `is_zero = 1 - value * value_inv`, along with
+/// `value * is_zero = 0`.
+///
+/// The gadget does not allocate a column for its output; `expr` is used
+/// within the gates of the caller instead.
+#[derive(Clone, Debug)]
+pub struct IsZeroConfig<F> {
+    pub value_inv: Column<Advice>,
+    is_zero_expr: Expression<F>,
+}
+
+impl<F: FieldExt> IsZeroConfig<F> {
+    /// Configures the gadget over `value`, on the rows where `q_enable` is
+    /// non-zero.
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        q_enable: impl FnOnce(&mut VirtualCells<'_, F>) -> Expression<F>,
+        value: impl FnOnce(&mut VirtualCells<'_, F>) -> Expression<F>,
+        value_inv: Column<Advice>,
+    ) -> Self {
+        let mut is_zero_expr = Expression::Constant(F::zero());
+
+        meta.create_gate("value * (1 - value * value_inv) = 0", |meta| {
+            let q_enable = q_enable(meta);
+            let value = value(meta);
+            let value_inv = meta.query_advice(value_inv, Rotation::cur());
+
+            is_zero_expr = Expression::Constant(F::one()) - value.clone() * value_inv;
+            vec![q_enable * value * is_zero_expr.clone()]
+        });
+
+        Self {
+            value_inv,
+            is_zero_expr,
+        }
+    }
+
+    /// Expression that is one if the value is zero, and zero otherwise.
+    pub fn expr(&self) -> Expression<F> {
+        self.is_zero_expr.clone()
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct IsZeroChip<F> {
+    config: IsZeroConfig<F>,
+}
+
+impl<F: FieldExt> IsZeroChip<F> {
+    pub fn construct(config: IsZeroConfig<F>) -> Self {
+        Self { config }
+    }
+
+    /// Assigns the inverse of `value` at `offset`, or zero if `value` is
+    /// zero.
+    pub fn assign(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        value: Value<F>,
+    ) -> Result<(), Error> {
+        let value_inv = value.map(|value| value.invert().unwrap_or(F::zero()));
+        region.assign_advice(|| "value_inv", self.config.value_inv, offset, || value_inv)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use halo2_proofs::{
+        circuit::{Layouter, SimpleFloorPlanner},
+        dev::MockProver,
+        halo2curves::{bn256::Fr, pasta::pallas},
+        plonk::{Circuit, Selector},
+    };
+    use mutation_coverage::{mutation_coverage, CellLocation};
+    use std::marker::PhantomData;
+
+    /// Checks `is_zero(value) == expected` on every row.
+    #[derive(Default)]
+    struct TestCircuit<F> {
+        rows: Vec<(u64, bool)>,
+        _marker: PhantomData<F>,
+    }
+
+    impl<F: FieldExt> Circuit<F> for TestCircuit<F> {
+        type Config = (Selector, Column<Advice>, Column<Advice>, IsZeroConfig<F>);
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            let q_enable = meta.selector();
+            let value = meta.advice_column();
+            let expected = meta.advice_column();
+            let value_inv = meta.advice_column();
+            let is_zero = IsZeroConfig::configure(
+                meta,
+                |meta| meta.query_selector(q_enable),
+                |meta| meta.query_advice(value, Rotation::cur()),
+                value_inv,
+            );
+
+            meta.create_gate("is_zero == expected", |meta| {
+                let q_enable = meta.query_selector(q_enable);
+                let expected = meta.query_advice(expected, Rotation::cur());
+                vec![q_enable * (is_zero.expr() - expected)]
+            });
+
+            (q_enable, value, expected, is_zero)
+        }
+
+        fn synthesize(
+            &self,
+            (q_enable, value, expected, is_zero): Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            let chip = IsZeroChip::construct(is_zero);
+            layouter.assign_region(
+                || "is zero",
+                |mut region| {
+                    for (offset, (v, is_zero)) in self.rows.iter().enumerate() {
+                        q_enable.enable(&mut region, offset)?;
+                        let v = Value::known(F::from(*v));
+                        region.assign_advice(|| "value", value, offset, || v)?;
+                        region.assign_advice(
+                            || "expected",
+                            expected,
+                            offset,
+                            || Value::known(F::from(*is_zero as u64)),
+                        )?;
+                        chip.assign(&mut region, offset, v)?;
+                    }
+                    Ok(())
+                },
+            )
+        }
+    }
+
+    fn test_is_zero_over<F: FieldExt>() {
+        let circuit = TestCircuit::<F> {
+            rows: vec![(0, true), (1, false), (55, false), (u64::MAX, false)],
+            _marker: PhantomData,
+        };
+        let prover = MockProver::run(4, &circuit, vec![]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+
+        for row in [(0, false), (7, true)] {
+            let circuit = TestCircuit::<F> {
+                rows: vec![row],
+                _marker: PhantomData,
+            };
+            let prover = MockProver::run(4, &circuit, vec![]).unwrap();
+            assert!(prover.verify().is_err());
+        }
+    }
+
+    #[test]
+    fn test_is_zero() {
+        test_is_zero_over::<Fr>();
+        test_is_zero_over::<pallas::Base>();
+    }
+
+    #[test]
+    fn test_is_zero_coverage() {
+        let circuit = TestCircuit::<Fr> {
+            rows: vec![(0, true), (7, false)],
+            _marker: PhantomData,
+        };
+        let report = mutation_coverage(4, &circuit, vec![]).unwrap();
+        // Any inverse satisfies the gates where the value is zero.
+        let value_inv = CellLocation {
+            region_index: 0,
+            region_name: String::from("is zero"),
+            column: 2,
+            offset: 0,
+        };
+        assert_eq!(
+            report.unconstrained().collect::<Vec<_>>(),
+            vec![&value_inv],
+            "{}",
+            report.report()
+        );
+    }
+}
@@ -0,0 +1,238 @@
+//! Serialization of the proving and verifying keys of a circuit, guarded by
+//! the version of its constraint system.
+//!
+//! A key is only valid for the constraint system it was generated from, but
+//! halo2 reads the key of a circuit of other gates without complaint, and
+//! the proofs of the stale key then fail to verify, or verify against
+//! constraints the circuit no longer has. [`write_pk`] and [`write_vk`]
+//! thus prefix the key with the [`circuit_version`] of its circuit, and
+//! [`read_pk`] and [`read_vk`] refuse a key of another version, so that the
+//! keys are generated again after a change to the gates. The helpers fail
+//! with the `io::Error`s of the key serialization of halo2, that of a key of
+//! another version wrapping an [`Error::KeyVersion`].
+
+use std::{
+    fmt::Write as _,
+    io::{self, ErrorKind},
+};
+
+use halo2_proofs::{
+    arithmetic::{CurveAffine, FieldExt},
+    plonk::{Circuit, ConstraintSystem, ProvingKey, VerifyingKey},
+    SerdeFormat,
+};
+
+use crate::{util::Fnv1a, Error};
+
+/// Version of the constraint system of the circuit `C`: the FNV-1a hash of
+/// its columns, the expressions of its gates and lookups, its permutation
+/// and its degree, which changes whenever they do, and does not depend on
+/// the witness nor on the Rust toolchain.
+pub fn circuit_version<F: FieldExt, C: Circuit<F>>() -> u64 {
+    let mut meta = ConstraintSystem::default();
+    C::configure(&mut meta);
+
+    let mut repr = format!(
+        "advice {:?}, fixed {}, instance {}, selectors {}, challenges {:?}, degree {}",
+        meta.advice_column_phase(),
+        meta.num_fixed_columns(),
+        meta.num_instance_columns(),
+        meta.num_selectors(),
+        meta.challenge_phase(),
+        meta.degree(),
+    );
+    for gate in meta.gates() {
+        write!(repr, "; gate {}:", gate.name()).unwrap();
+        for polynomial in gate.polynomials() {
+            write!(repr, " {}", polynomial.identifier()).unwrap();
+        }
+    }
+    for lookup in meta.lookups() {
+        repr.push_str("; lookup:");
+        for (input, table) in lookup
+            .input_expressions()
+            .iter()
+            .zip(lookup.table_expressions())
+        {
+            write!(repr, " {} in {}", input.identifier(), table.identifier()).unwrap();
+        }
+    }
+    write!(
+        repr,
+        "; permutation {:?}; constants {:?}",
+        meta.permutation().get_columns(),
+        meta.constants()
+    )
+    .unwrap();
+
+    let mut hash = Fnv1a::default();
+    hash.update(repr.as_bytes());
+    hash.finish()
+}
+
+/// Reads the version prefixed to a key, and checks that it is that of `C`.
+fn read_version<F: FieldExt, C: Circuit<F>>(reader: &mut impl io::Read) -> io::Result<()> {
+    let mut version = [0; 8];
+    reader.read_exact(&mut version)?;
+    let (found, expected) = (u64::from_le_bytes(version), circuit_version::<F, C>());
+    if found != expected {
+        return Err(io::Error::new(
+            ErrorKind::InvalidData,
+            Error::KeyVersion { found, expected },
+        ));
+    }
+    Ok(())
+}
+
+/// Writes the proving key of the circuit `ConcreteCircuit` in `format`,
+/// prefixed with its [`circuit_version`].
+pub fn write_pk<C: CurveAffine, ConcreteCircuit: Circuit<C::ScalarExt>>(
+    writer: &mut impl io::Write,
+    pk: &ProvingKey<C>,
+    format: SerdeFormat,
+) -> io::Result<()>
+where
+    C::ScalarExt: FieldExt,
+{
+    writer.write_all(&circuit_version::<C::ScalarExt, ConcreteCircuit>().to_le_bytes())?;
+    pk.write(writer, format)
+}
+
+/// Reads a proving key written by [`write_pk`], failing with
+/// `ErrorKind::InvalidData` wrapping an [`Error::KeyVersion`] if it is of
+/// another version of the circuit.
+pub fn read_pk<C: CurveAffine, ConcreteCircuit: Circuit<C::ScalarExt>>(
+    reader: &mut impl io::Read,
+    format: SerdeFormat,
+) -> io::Result<ProvingKey<C>>
+where
+    C::ScalarExt: FieldExt,
+{
+    read_version::<C::ScalarExt, ConcreteCircuit>(reader)?;
+    ProvingKey::read::<_, ConcreteCircuit>(reader, format)
+}
+
+/// Writes the verifying key of the circuit `ConcreteCircuit` in `format`,
+/// prefixed with its [`circuit_version`].
+pub fn write_vk<C: CurveAffine, ConcreteCircuit: Circuit<C::ScalarExt>>(
+    writer: &mut impl io::Write,
+    vk: &VerifyingKey<C>,
+    format: SerdeFormat,
+) -> io::Result<()>
+where
+    C::ScalarExt: FieldExt,
+{
+    writer.write_all(&circuit_version::<C::ScalarExt, ConcreteCircuit>().to_le_bytes())?;
+    vk.write(writer, format)
+}
+
+/// Reads a verifying key written by [`write_vk`], failing with
+/// `ErrorKind::InvalidData` wrapping an [`Error::KeyVersion`] if it is of
+/// another version of the circuit.
+pub fn read_vk<C: CurveAffine, ConcreteCircuit: Circuit<C::ScalarExt>>(
+    reader: &mut impl io::Read,
+    format: SerdeFormat,
+) -> io::Result<VerifyingKey<C>>
+where
+    C::ScalarExt: FieldExt,
+{
+    read_version::<C::ScalarExt, ConcreteCircuit>(reader)?;
+    VerifyingKey::read::<_, ConcreteCircuit>(reader, format)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use halo2_proofs::{
+        circuit::{Layouter, SimpleFloorPlanner, Value},
+        halo2curves::bn256::{Bn256, Fr, G1Affine},
+        plonk::{keygen_pk, keygen_vk, Advice, Column, Error, Selector},
+        poly::{commitment::ParamsProver, kzg::commitment::ParamsKZG, Rotation},
+    };
+
+    /// Checks `a * b = c` on a row, or `a + b = c` if `ADD`, as a gate
+    /// changed between two versions of a circuit.
+    #[derive(Default)]
+    struct TestCircuit<const ADD: bool>;
+
+    impl<const ADD: bool> Circuit<Fr> for TestCircuit<ADD> {
+        type Config = (Selector, [Column<Advice>; 3]);
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+            let q = meta.selector();
+            let columns = [0; 3].map(|_| meta.advice_column());
+            meta.create_gate("c = a op b", |meta| {
+                let q = meta.query_selector(q);
+                let [a, b, c] = columns.map(|column| meta.query_advice(column, Rotation::cur()));
+                vec![q * (if ADD { a + b } else { a * b } - c)]
+            });
+            (q, columns)
+        }
+
+        fn synthesize(
+            &self,
+            (q, columns): Self::Config,
+            mut layouter: impl Layouter<Fr>,
+        ) -> Result<(), Error> {
+            layouter.assign_region(
+                || "c = a op b",
+                |mut region| {
+                    q.enable(&mut region, 0)?;
+                    for (column, value) in columns.into_iter().zip([2, 2, 4]) {
+                        region.assign_advice(
+                            || "cell",
+                            column,
+                            0,
+                            || Value::known(Fr::from(value)),
+                        )?;
+                    }
+                    Ok(())
+                },
+            )
+        }
+    }
+
+    #[test]
+    fn test_circuit_version() {
+        assert_eq!(
+            circuit_version::<Fr, TestCircuit<false>>(),
+            circuit_version::<Fr, TestCircuit<false>>()
+        );
+        assert_ne!(
+            circuit_version::<Fr, TestCircuit<false>>(),
+            circuit_version::<Fr, TestCircuit<true>>()
+        );
+    }
+
+    #[test]
+    fn test_read_keys() {
+        let params = ParamsKZG::<Bn256>::new(4);
+        let vk = keygen_vk(&params, &TestCircuit::<false>).unwrap();
+        let pk = keygen_pk(&params, vk, &TestCircuit::<false>).unwrap();
+        let format = SerdeFormat::RawBytes;
+
+        let mut pk_bytes = vec![];
+        write_pk::<_, TestCircuit<false>>(&mut pk_bytes, &pk, format).unwrap();
+        let mut vk_bytes = vec![];
+        write_vk::<_, TestCircuit<false>>(&mut vk_bytes, pk.get_vk(), format).unwrap();
+
+        let read = read_vk::<G1Affine, TestCircuit<false>>(&mut &vk_bytes[..], format).unwrap();
+        assert_eq!(read.transcript_repr(), pk.get_vk().transcript_repr());
+        assert!(read_pk::<G1Affine, TestCircuit<false>>(&mut &pk_bytes[..], format).is_ok());
+
+        // the keys of the circuit before its gate changed.
+        let err = read_vk::<G1Affine, TestCircuit<true>>(&mut &vk_bytes[..], format).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidData);
+        assert!(matches!(
+            err.get_ref().and_then(|err| err.downcast_ref()),
+            Some(crate::Error::KeyVersion { .. })
+        ));
+        let err = read_pk::<G1Affine, TestCircuit<true>>(&mut &pk_bytes[..], format).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidData);
+    }
+}
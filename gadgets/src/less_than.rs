@@ -0,0 +1,241 @@
+use std::marker::PhantomData;
+
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{Region, Value},
+    plonk::{Advice, Column, ConstraintSystem, Error, Expression, VirtualCells},
+    poly::Rotation,
+};
+
+use crate::{tables::RangeTable, util::pow_of_two};
+
+/// Constrains whether `lhs < rhs` for two values of `N_BYTES` bytes, i.e.
+/// `lhs - rhs = diff - lt * 2^(8 * N_BYTES)`, where `lt` is boolean and
+/// `diff` is decomposed into `N_BYTES` bytes, each looked up in the byte
+/// table.
+///
+/// The values themselves are not range checked here: callers are expected
+/// to pass expressions that are already constrained to `N_BYTES` bytes,
+/// e.g. a length or a constant.
+#[derive(Clone, Copy, Debug)]
+pub struct LtConfig<F, const N_BYTES: usize> {
+    pub lt: Column<Advice>,
+    pub diff: [Column<Advice>; N_BYTES],
+    _marker: PhantomData<F>,
+}
+
+impl<F: FieldExt, const N_BYTES: usize> LtConfig<F, N_BYTES> {
+    /// Configures the gadget over `lhs` and `rhs`, on the rows where
+    /// `q_enable` is non-zero. As `q_enable` is used in the lookups of the
+    /// bytes of `diff`, it must be built from complex selectors only.
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        q_enable: impl Fn(&mut VirtualCells<'_, F>) -> Expression<F>,
+        lhs: impl FnOnce(&mut VirtualCells<'_, F>) -> Expression<F>,
+        rhs: impl FnOnce(&mut VirtualCells<'_, F>) -> Expression<F>,
+        table: RangeTable<8>,
+    ) -> Self {
+        assert!(
+            N_BYTES > 0 && N_BYTES <= 15,
+            "the values must fit in 15 bytes"
+        );
+
+        let lt = meta.advice_column();
+        let diff = [(); N_BYTES].map(|_| meta.advice_column());
+
+        meta.create_gate("lhs - rhs = diff - lt * 2^(8 * N_BYTES)", |meta| {
+            let q_enable = q_enable(meta);
+            let lt = meta.query_advice(lt, Rotation::cur());
+            let diff =
+                diff.iter()
+                    .enumerate()
+                    .fold(Expression::Constant(F::zero()), |acc, (i, byte)| {
+                        acc + meta.query_advice(*byte, Rotation::cur()) * pow_of_two::<F>(8 * i)
+                    });
+            let range = pow_of_two::<F>(8 * N_BYTES);
+
+            vec![
+                q_enable.clone()
+                    * (lhs(meta) - rhs(meta) - diff + lt.clone() * Expression::Constant(range)),
+                q_enable * lt.clone() * (Expression::Constant(F::one()) - lt),
+            ]
+        });
+
+        for byte in diff {
+            meta.lookup("diff byte is in range", |meta| {
+                let q_enable = q_enable(meta);
+                let byte = meta.query_advice(byte, Rotation::cur());
+                vec![(q_enable * byte, table.value)]
+            });
+        }
+
+        Self {
+            lt,
+            diff,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Expression that is one if `lhs < rhs`, and zero otherwise.
+    pub fn is_lt(&self, meta: &mut VirtualCells<'_, F>) -> Expression<F> {
+        meta.query_advice(self.lt, Rotation::cur())
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct LtChip<F, const N_BYTES: usize> {
+    config: LtConfig<F, N_BYTES>,
+}
+
+impl<F: FieldExt, const N_BYTES: usize> LtChip<F, N_BYTES> {
+    pub fn construct(config: LtConfig<F, N_BYTES>) -> Self {
+        Self { config }
+    }
+
+    /// Assigns `lt` and the bytes of `diff` at `offset`, for values that fit
+    /// in `N_BYTES` bytes.
+    pub fn assign(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        lhs: Value<F>,
+        rhs: Value<F>,
+    ) -> Result<(), Error> {
+        let lt_diff = lhs.zip(rhs).map(|(lhs, rhs)| {
+            let (lhs, rhs) = (lhs.get_lower_128(), rhs.get_lower_128());
+            let lt = lhs < rhs;
+            let diff = lhs.wrapping_sub(rhs) & ((1u128 << (8 * N_BYTES)) - 1);
+            (lt, diff.to_le_bytes())
+        });
+
+        region.assign_advice(
+            || "lt",
+            self.config.lt,
+            offset,
+            || lt_diff.map(|(lt, _)| F::from(lt as u64)),
+        )?;
+        for (i, column) in self.config.diff.iter().enumerate() {
+            region.assign_advice(
+                || "diff byte",
+                *column,
+                offset,
+                || lt_diff.map(|(_, diff)| F::from(diff[i] as u64)),
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use halo2_proofs::{
+        circuit::{Layouter, SimpleFloorPlanner},
+        dev::MockProver,
+        halo2curves::{bn256::Fr, pasta::pallas},
+        plonk::{Circuit, Selector},
+    };
+
+    /// Checks `remaining_len < 56 == expected` on every row, as in the
+    /// padding of SHA2-256.
+    #[derive(Default)]
+    struct TestCircuit<F> {
+        rows: Vec<(u64, bool)>,
+        _marker: PhantomData<F>,
+    }
+
+    impl<F: FieldExt> Circuit<F> for TestCircuit<F> {
+        type Config = (
+            RangeTable<8>,
+            Selector,
+            Column<Advice>,
+            Column<Advice>,
+            LtConfig<F, 2>,
+        );
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            let table = RangeTable::construct(meta);
+            let q_enable = meta.complex_selector();
+            let remaining_len = meta.advice_column();
+            let expected = meta.advice_column();
+            let lt = LtConfig::configure(
+                meta,
+                |meta| meta.query_selector(q_enable),
+                |meta| meta.query_advice(remaining_len, Rotation::cur()),
+                |_| Expression::Constant(F::from(56)),
+                table,
+            );
+
+            meta.create_gate("lt == expected", |meta| {
+                let q_enable = meta.query_selector(q_enable);
+                let expected = meta.query_advice(expected, Rotation::cur());
+                vec![q_enable * (lt.is_lt(meta) - expected)]
+            });
+
+            (table, q_enable, remaining_len, expected, lt)
+        }
+
+        fn synthesize(
+            &self,
+            (table, q_enable, remaining_len, expected, lt): Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            table.load(&mut layouter)?;
+            let chip = LtChip::construct(lt);
+            layouter.assign_region(
+                || "less than",
+                |mut region| {
+                    for (offset, (len, is_lt)) in self.rows.iter().enumerate() {
+                        q_enable.enable(&mut region, offset)?;
+                        let len = Value::known(F::from(*len));
+                        region.assign_advice(|| "remaining_len", remaining_len, offset, || len)?;
+                        region.assign_advice(
+                            || "expected",
+                            expected,
+                            offset,
+                            || Value::known(F::from(*is_lt as u64)),
+                        )?;
+                        chip.assign(&mut region, offset, len, Value::known(F::from(56)))?;
+                    }
+                    Ok(())
+                },
+            )
+        }
+    }
+
+    fn test_less_than_over<F: FieldExt>() {
+        let circuit = TestCircuit::<F> {
+            rows: vec![
+                (0, true),
+                (55, true),
+                (56, false),
+                (57, false),
+                (64, false),
+                (65535, false),
+            ],
+            _marker: PhantomData,
+        };
+        let prover = MockProver::run(9, &circuit, vec![]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+
+        for row in [(55, false), (56, true)] {
+            let circuit = TestCircuit::<F> {
+                rows: vec![row],
+                _marker: PhantomData,
+            };
+            let prover = MockProver::run(9, &circuit, vec![]).unwrap();
+            assert!(prover.verify().is_err());
+        }
+    }
+
+    #[test]
+    fn test_less_than() {
+        test_less_than_over::<Fr>();
+        test_less_than_over::<pallas::Base>();
+    }
+}
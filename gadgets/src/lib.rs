@@ -0,0 +1,68 @@
+//! Bitwise building blocks shared by the hash circuits in this workspace.
+//!
+//! The hash circuits are specified over fixed-width words and are built from
+//! the same handful of operations: XOR/AND/NOT, rotations, shifts and
+//! additions modulo `2^32` or `2^64`. This crate provides a chip for each of
+//! those along with the fixed lookup tables they rely on, so that the
+//! individual circuits can share a single audited implementation, as well as
+//! the helpers the circuits share around them: their tables, public inputs,
+//! errors, keys and cost reports.
+//!
+//! All tables in [`tables`] are parameterised by their bit width and must be
+//! loaded exactly once per circuit via their `load` method. The chips only
+//! assign witness data within a region provided by the caller, which lets a
+//! hash circuit lay several gadgets out side by side.
+
+mod add;
+mod assigned_bytes;
+pub mod batch;
+mod bitwise;
+mod bytes;
+mod call_slots;
+pub mod capacity;
+pub mod context;
+mod decompose;
+#[cfg(feature = "dev-graph")]
+pub mod dot;
+mod error;
+mod hash;
+mod hash_table;
+mod is_equal;
+mod is_zero;
+pub mod keys;
+mod less_than;
+mod md;
+pub mod public_inputs;
+mod range_check;
+mod rotation;
+pub mod rows;
+mod spread;
+mod stats;
+pub mod tables;
+pub mod util;
+pub mod validate;
+pub mod witness;
+mod word;
+mod word_ops;
+#[cfg(feature = "zkevm")]
+pub mod zkevm;
+
+pub use add::{ModAddChip, ModAddConfig};
+pub use assigned_bytes::AssignedBytes;
+pub use bitwise::{BitwiseChip, BitwiseConfig};
+pub use bytes::{BytesChip, BytesConfig, Endianness};
+pub use call_slots::{CallSlotsConfig, MdPadding, SlotCells, SlotPadding, SlotPlan};
+pub use decompose::{DecomposeChip, DecomposeConfig};
+pub use error::Error;
+pub use hash::{AssignedCall, DigestCircuit, HashCircuitExt};
+pub use hash_table::{HashTable, HashTableConfig};
+pub use is_equal::{IsEqualChip, IsEqualConfig};
+pub use is_zero::{IsZeroChip, IsZeroConfig};
+pub use less_than::{LtChip, LtConfig};
+pub use md::{MdChip, MdCompression, MdConfig};
+pub use range_check::{RangeCheckChip, RangeCheckConfig};
+pub use rotation::{BitShift, RotateChip, RotateConfig};
+pub use spread::{spread, unspread, SpreadChip, SpreadConfig};
+pub use stats::{CircuitBudget, CircuitStats};
+pub use word::{AssignedWord, WordChip, WordConfig, WordLoHi, WORD_BYTES};
+pub use word_ops::{BitFn, WordCell, WordOpsChip, WordOpsConfig};
@@ -0,0 +1,497 @@
+use std::marker::PhantomData;
+
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    plonk::{ConstraintSystem, Error},
+};
+use tracing::debug_span;
+
+use crate::{
+    assigned_bytes::AssignedBytes,
+    bytes::Endianness,
+    call_slots::{SlotCells, SlotPlan},
+    context::Context,
+    hash::AssignedCall,
+    rotation::BitShift,
+    util::mask,
+    word_ops::{BitFn, WordCell, WordOpsChip, WordOpsConfig},
+};
+
+/// The cells of the calls and of the slots of [`MdChip::assign_slots`].
+type AssignedSlots<F> = (Vec<AssignedCall<F>>, Vec<SlotCells<F>>);
+
+/// The compression function of a Merkle–Damgård hash over words of
+/// `WORD_BITS` bits, e.g. SHA-1, MD5 or SHA-512, which [`MdChip`] chains over
+/// the padded blocks of an input.
+///
+/// A hash implements its padding and the rounds of its compression over a
+/// [`WordOpsChip`]: the chip assigns the words of every block, constrains the
+/// padding to the bytes [`Self::pad`] appends to the input, the chaining
+/// value of the first block to the initial hash value, and chains the later
+/// blocks to the output of the previous one.
+pub trait MdCompression<F: FieldExt, const WORD_BITS: usize> {
+    /// Number of words of the chaining value.
+    const STATE_WORDS: usize;
+    /// Number of bytes of a block.
+    const BLOCK_BYTES: usize;
+    /// Order of the bytes in the words of a block and of the digest.
+    const ENDIANNESS: Endianness;
+
+    /// Number of words of the chaining value the digest is made of, all of
+    /// them by default, and fewer for a truncated digest, e.g. SHA-384.
+    fn digest_words(&self) -> usize {
+        Self::STATE_WORDS
+    }
+
+    /// Sets of shifts the rounds XOR together, see [`WordOpsChip::shift`].
+    fn shifts() -> Vec<Vec<BitShift>>;
+
+    /// Functions the rounds evaluate, see [`WordOpsChip::apply`].
+    fn fns() -> Vec<BitFn>;
+
+    /// Initial hash value.
+    fn iv(&self) -> Vec<u64>;
+
+    /// Padded input, whose length is a multiple of [`Self::BLOCK_BYTES`].
+    fn pad(&self, input: &[u8]) -> Vec<u8>;
+
+    /// Working variables `v_i` of the compression of `block` from `state`,
+    /// before the feed-forward.
+    fn compress_rounds(&self, state: &[u64], block: &[u8]) -> Vec<u64>;
+
+    /// Chaining value after the compression of a block, from the chaining
+    /// value `state` before it and the working variables: `h_i + v_i` by
+    /// default.
+    fn feed_forward(&self, state: &[u64], working: &[u64]) -> Vec<u64> {
+        state
+            .iter()
+            .zip(working)
+            .map(|(h, v)| h.wrapping_add(*v) & mask(WORD_BITS))
+            .collect()
+    }
+
+    /// Number of rows [`Self::assign_rounds`] takes per block.
+    fn rounds_rows(&self) -> usize;
+
+    /// Assigns the rounds of the compression of the words `block` from the
+    /// chaining value `state`, and returns the working variables.
+    fn assign_rounds(
+        &self,
+        chip: &WordOpsChip<F, WORD_BITS>,
+        ctx: &mut Context<'_, '_, F>,
+        state: &[WordCell<F>],
+        block: &[WordCell<F>],
+    ) -> Result<Vec<WordCell<F>>, Error>;
+
+    /// Number of rows [`Self::assign_feed_forward`] takes per block.
+    fn feed_forward_rows(&self) -> usize {
+        Self::STATE_WORDS
+    }
+
+    /// Assigns [`Self::feed_forward`].
+    fn assign_feed_forward(
+        &self,
+        chip: &WordOpsChip<F, WORD_BITS>,
+        ctx: &mut Context<'_, '_, F>,
+        state: &[WordCell<F>],
+        working: &[WordCell<F>],
+    ) -> Result<Vec<WordCell<F>>, Error> {
+        state
+            .iter()
+            .zip(working)
+            .map(|(h, v)| chip.add(ctx, &[h, v], 0))
+            .collect()
+    }
+
+    /// Native chaining value after the last block of `input`.
+    fn hash_state(&self, input: &[u8]) -> Vec<u64> {
+        let mut state = self.iv();
+        for block in self.pad(input).chunks(Self::BLOCK_BYTES) {
+            let working = self.compress_rounds(&state, block);
+            state = self.feed_forward(&state, &working);
+        }
+        state
+    }
+}
+
+/// The word operations of the rounds of a Merkle–Damgård hash over words
+/// of `WORD_BITS` bits.
+#[derive(Clone, Debug)]
+pub struct MdConfig<const WORD_BITS: usize> {
+    pub word_ops: WordOpsConfig<WORD_BITS>,
+}
+
+impl<const WORD_BITS: usize> MdConfig<WORD_BITS> {
+    /// Configures the gates of the operations of the compression `C`.
+    pub fn configure<F: FieldExt, C: MdCompression<F, WORD_BITS>>(
+        meta: &mut ConstraintSystem<F>,
+    ) -> Self {
+        Self {
+            word_ops: WordOpsConfig::configure(meta, &C::shifts(), &C::fns()),
+        }
+    }
+}
+
+/// Chains the compression function of a Merkle–Damgård hash over the padded
+/// blocks of an input, so that a hash only implements its padding and
+/// compression rounds through [`MdCompression`].
+#[derive(Clone, Debug)]
+pub struct MdChip<F: FieldExt, C, const WORD_BITS: usize> {
+    word_ops: WordOpsChip<F, WORD_BITS>,
+    compression: C,
+    _marker: PhantomData<F>,
+}
+
+impl<F: FieldExt, C: MdCompression<F, WORD_BITS>, const WORD_BITS: usize> MdChip<F, C, WORD_BITS> {
+    pub fn construct(config: MdConfig<WORD_BITS>, compression: C) -> Self {
+        Self {
+            word_ops: WordOpsChip::construct(config.word_ops),
+            compression,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Number of rows a block takes: its words, the rounds and the
+    /// feed-forward.
+    pub fn block_rows(&self) -> usize {
+        C::BLOCK_BYTES / (WORD_BITS / 8)
+            + self.compression.rounds_rows()
+            + self.compression.feed_forward_rows()
+    }
+
+    /// Number of rows [`Self::assign`] takes for `input`: the initial hash
+    /// value and the blocks.
+    pub fn rows(&self, input: &[u8]) -> usize {
+        let blocks = self.compression.pad(input).len() / C::BLOCK_BYTES;
+        C::STATE_WORDS + blocks * self.block_rows()
+    }
+
+    /// Assigns the blocks of `input` on the next rows of `ctx`, returning the
+    /// cells of the bytes of the input and the chaining value after the last
+    /// block.
+    pub fn assign(
+        &self,
+        ctx: &mut Context<'_, '_, F>,
+        input: &[u8],
+    ) -> Result<(AssignedBytes<F>, Vec<WordCell<F>>), Error> {
+        let chip = &self.word_ops;
+        let iv = self.compression.iv();
+        assert_eq!(
+            iv.len(),
+            C::STATE_WORDS,
+            "the words of the initial hash value"
+        );
+        let mut state = iv
+            .iter()
+            .map(|word| chip.constant(ctx, *word))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let padded = self.compression.pad(input);
+        let blocks = padded.len() / C::BLOCK_BYTES;
+        let _span = debug_span!("md blocks", blocks, rows = blocks * self.block_rows()).entered();
+        let mut input_cells = Vec::with_capacity(input.len());
+        for (i, block) in padded.chunks(C::BLOCK_BYTES).enumerate() {
+            let mut words = Vec::with_capacity(C::BLOCK_BYTES / (WORD_BITS / 8));
+            for (j, bytes) in block.chunks(WORD_BITS / 8).enumerate() {
+                let (word, cells) = chip.load_bytes(ctx, bytes, C::ENDIANNESS)?;
+                for (k, cell) in cells.into_iter().enumerate() {
+                    let index = i * C::BLOCK_BYTES + j * (WORD_BITS / 8) + k;
+                    if index < input.len() {
+                        input_cells.push(cell);
+                    } else {
+                        // The padding only depends on the length of the input.
+                        ctx.region()
+                            .constrain_constant(cell.cell(), F::from(padded[index] as u64))?;
+                    }
+                }
+                words.push(word);
+            }
+
+            let working = self.compression.assign_rounds(chip, ctx, &state, &words)?;
+            state = self
+                .compression
+                .assign_feed_forward(chip, ctx, &state, &working)?;
+        }
+        Ok((AssignedBytes::new(input_cells), state))
+    }
+
+    /// Number of rows [`Self::assign_slots`] takes for `slots` slots: the
+    /// initial hash value, then the flag, the chaining value, the block and
+    /// the digest of every slot.
+    pub fn slots_rows(&self, slots: usize) -> usize {
+        C::STATE_WORDS
+            + slots * (1 + C::STATE_WORDS + self.block_rows() + self.compression.digest_words())
+    }
+
+    /// Assigns the blocks of the slots of `plan`, of the calls of `inputs`,
+    /// on the next rows of `ctx`, for a [`CallSlotsConfig`] region to
+    /// constrain their padding and calls.
+    ///
+    /// A slot restarts from the initial hash value on the first block of a
+    /// call and from the chaining value of the previous slot otherwise, and
+    /// decomposes its chaining value into a digest. A disabled slot
+    /// compresses a block of zeros. Returns the cells of the calls and those
+    /// of the slots.
+    ///
+    /// [`CallSlotsConfig`]: crate::CallSlotsConfig
+    pub fn assign_slots(
+        &self,
+        ctx: &mut Context<'_, '_, F>,
+        inputs: &[Vec<u8>],
+        plan: &[SlotPlan],
+    ) -> Result<AssignedSlots<F>, Error> {
+        let chip = &self.word_ops;
+        let iv = self
+            .compression
+            .iv()
+            .iter()
+            .map(|word| chip.constant(ctx, *word))
+            .collect::<Result<Vec<_>, _>>()?;
+        let _span = debug_span!(
+            "md slots",
+            slots = plan.len(),
+            rows = self.slots_rows(plan.len())
+        )
+        .entered();
+
+        let padded: Vec<_> = inputs
+            .iter()
+            .map(|input| self.compression.pad(input))
+            .collect();
+        let mut state = iv.clone();
+        let mut slots = Vec::with_capacity(plan.len());
+        for slot in plan {
+            let is_first = chip.assign(ctx, slot.is_first as u64)?;
+            let chained = iv
+                .iter()
+                .zip(&state)
+                .map(|(iv, prev)| chip.select(ctx, &is_first, iv, prev))
+                .collect::<Result<Vec<_>, _>>()?;
+            let block = match slot.call {
+                Some(call) => padded[call][slot.offset..slot.offset + C::BLOCK_BYTES].to_vec(),
+                None => vec![0; C::BLOCK_BYTES],
+            };
+            let mut words = Vec::with_capacity(C::BLOCK_BYTES / (WORD_BITS / 8));
+            let mut block_cells = Vec::with_capacity(C::BLOCK_BYTES);
+            for bytes in block.chunks(WORD_BITS / 8) {
+                let (word, cells) = chip.load_bytes(ctx, bytes, C::ENDIANNESS)?;
+                words.push(word);
+                block_cells.extend(cells);
+            }
+
+            let working = self
+                .compression
+                .assign_rounds(chip, ctx, &chained, &words)?;
+            state = self
+                .compression
+                .assign_feed_forward(chip, ctx, &chained, &working)?;
+            let digest = self.digest(ctx, &state[..self.compression.digest_words()])?;
+            slots.push(SlotCells {
+                is_first: is_first.cell,
+                block: block_cells,
+                digest: digest.cells().to_vec(),
+            });
+        }
+
+        let calls = inputs
+            .iter()
+            .enumerate()
+            .map(|(call, input)| {
+                let blocks = plan
+                    .iter()
+                    .zip(&slots)
+                    .filter(|(slot, _)| slot.call == Some(call));
+                let mut input_cells: Vec<_> = blocks
+                    .clone()
+                    .flat_map(|(_, cells)| cells.block.clone())
+                    .collect();
+                input_cells.truncate(input.len());
+                let (_, last) = blocks.last().expect("a call of a block at least");
+                AssignedCall {
+                    input: AssignedBytes::new(input_cells),
+                    digest: AssignedBytes::new(last.digest.clone()),
+                }
+            })
+            .collect();
+        Ok((calls, slots))
+    }
+
+    /// Decomposes the words `state` into the bytes of the digest, in
+    /// [`MdCompression::ENDIANNESS`], on a row per word of `ctx`.
+    pub fn digest(
+        &self,
+        ctx: &mut Context<'_, '_, F>,
+        state: &[WordCell<F>],
+    ) -> Result<AssignedBytes<F>, Error> {
+        let mut digest = Vec::with_capacity(state.len() * WORD_BITS / 8);
+        for word in state {
+            digest.extend(self.word_ops.bytes(ctx, word, C::ENDIANNESS)?);
+        }
+        Ok(AssignedBytes::new(digest))
+    }
+
+    /// Assigns the blocks of `input` and its digest, i.e. the first
+    /// [`MdCompression::digest_words`] words of the chaining value, on the
+    /// next rows of `ctx`, returning the cells of the call and the words of
+    /// the digest.
+    pub fn assign_call(
+        &self,
+        ctx: &mut Context<'_, '_, F>,
+        input: &[u8],
+    ) -> Result<(AssignedCall<F>, Vec<WordCell<F>>), Error> {
+        let (input, mut state) = self.assign(ctx, input)?;
+        state.truncate(self.compression.digest_words());
+        let digest = self.digest(ctx, &state)?;
+        Ok((AssignedCall { input, digest }, state))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::context::assign_context;
+    use halo2_proofs::{
+        circuit::{Layouter, SimpleFloorPlanner},
+        dev::MockProver,
+        halo2curves::{bn256::Fr, pasta::pallas},
+        plonk::Circuit,
+    };
+
+    /// A toy compression over two 32-bit words, whose working variables are
+    /// the words of the block rotated by a byte, padded with a one byte and
+    /// zeros.
+    #[derive(Clone, Copy, Debug, Default)]
+    struct Toy;
+
+    const ROTATE: BitShift = BitShift::RotateRight(8);
+
+    impl<F: FieldExt> MdCompression<F, 32> for Toy {
+        const STATE_WORDS: usize = 2;
+        const BLOCK_BYTES: usize = 8;
+        const ENDIANNESS: Endianness = Endianness::Little;
+
+        fn shifts() -> Vec<Vec<BitShift>> {
+            vec![vec![ROTATE]]
+        }
+
+        fn fns() -> Vec<BitFn> {
+            vec![]
+        }
+
+        fn iv(&self) -> Vec<u64> {
+            vec![0xffffffff, 1]
+        }
+
+        fn pad(&self, input: &[u8]) -> Vec<u8> {
+            let mut padded = input.to_vec();
+            padded.push(1);
+            padded.resize(padded.len().div_ceil(8) * 8, 0);
+            padded
+        }
+
+        fn compress_rounds(&self, _state: &[u64], block: &[u8]) -> Vec<u64> {
+            block
+                .chunks(4)
+                .map(|word| u32::from_le_bytes(word.try_into().unwrap()) as u64)
+                .map(|word| ROTATE.apply(word, 32))
+                .collect()
+        }
+
+        fn rounds_rows(&self) -> usize {
+            2
+        }
+
+        fn assign_rounds(
+            &self,
+            chip: &WordOpsChip<F, 32>,
+            ctx: &mut Context<'_, '_, F>,
+            _state: &[WordCell<F>],
+            block: &[WordCell<F>],
+        ) -> Result<Vec<WordCell<F>>, Error> {
+            block
+                .iter()
+                .map(|word| chip.shift(ctx, &[ROTATE], word))
+                .collect()
+        }
+    }
+
+    /// Hashes every input and checks the chaining value against `expected`.
+    #[derive(Default)]
+    struct TestCircuit<F> {
+        inputs: Vec<(Vec<u8>, [u64; 2])>,
+        _marker: PhantomData<F>,
+    }
+
+    impl<F: FieldExt> Circuit<F> for TestCircuit<F> {
+        type Config = MdConfig<32>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            MdConfig::configure::<F, Toy>(meta)
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            let chip = MdChip::<F, _, 32>::construct(config, Toy);
+            for (input, words) in self.inputs.iter() {
+                assign_context(&mut layouter, "md", |ctx| {
+                    let (bytes, state) = chip.assign(ctx, input)?;
+                    assert_eq!(bytes.len(), input.len());
+                    assert_eq!(ctx.offset(), chip.rows(input));
+                    let state = chip.digest(ctx, &state)?;
+                    let expected = words.iter().flat_map(|word| (*word as u32).to_le_bytes());
+                    for (cell, byte) in state.cells().iter().zip(expected) {
+                        ctx.region()
+                            .constrain_constant(cell.cell(), F::from(byte as u64))?;
+                    }
+                    Ok(())
+                })?;
+            }
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_md_native() {
+        let hash_state = |input: &[u8]| MdCompression::<Fr, 32>::hash_state(&Toy, input);
+        assert_eq!(hash_state(&[]), vec![0x00ffffff, 1]);
+        assert_eq!(hash_state(&[0; 8]), vec![0x00ffffff, 1]);
+        assert_eq!(MdCompression::<Fr, 32>::pad(&Toy, &[0; 8]).len(), 16);
+    }
+
+    fn test_md_circuit_over<F: FieldExt>() {
+        let inputs: Vec<Vec<u8>> = vec![vec![], vec![2; 7], (0..20).collect()];
+        let circuit = TestCircuit::<F> {
+            inputs: inputs
+                .iter()
+                .map(|input| {
+                    let state = MdCompression::<F, 32>::hash_state(&Toy, input);
+                    (input.clone(), [state[0], state[1]])
+                })
+                .collect(),
+            _marker: PhantomData,
+        };
+        let prover = MockProver::run(8, &circuit, vec![]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+
+        let circuit = TestCircuit::<F> {
+            inputs: vec![(vec![], [0, 2])],
+            _marker: PhantomData,
+        };
+        let prover = MockProver::run(8, &circuit, vec![]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+
+    #[test]
+    fn test_md_circuit() {
+        test_md_circuit_over::<Fr>();
+        test_md_circuit_over::<pallas::Base>();
+    }
+}
@@ -0,0 +1,200 @@
+//! Layout of the public inputs of the precompile circuits, in the two
+//! instance columns a circuit exposes its calls in, so that a verifier in
+//! another language reproduces the instances of a proof from the calls.
+//!
+//! The first instance column of a circuit whose digests are of
+//! `DIGEST_BYTES` bytes holds, from its first row:
+//!
+//! - the number of calls `n`,
+//! - then, for each call in the order of its call id, from 1 to `n`,
+//!   - its call id,
+//!   - the length of its input, in bytes, 213 for BLAKE2F,
+//!   - its digest, as `ceil(DIGEST_BYTES / 16)` chunks of 16 bytes in the
+//!     order of the digest, each the big-endian integer of its bytes, the
+//!     last of RIPEMD-160 being of 4 bytes.
+//!
+//! That is `1 + n * (2 + ceil(DIGEST_BYTES / 16))` values, a 32-byte digest
+//! being its high then low 128 bits. The column is that of the
+//! [`HashTable`](crate::HashTable) of a circuit proven on its own, see
+//! [`HashTableConfig::expose`](crate::HashTableConfig::expose), whose
+//! disabled rows are the zeros the rest of the column is padded with, and
+//! the values of a witness are its
+//! [`HashCircuitExt::public_inputs`](crate::HashCircuitExt::public_inputs).
+//!
+//! The second instance column holds the input bytes of the calls, block by
+//! block as the [`CallSlotsConfig`](crate::CallSlotsConfig) of the circuit
+//! lays them out: for each call in the order of its call id, the blocks of
+//! `BLOCK_BYTES` bytes of its padded input, 64 for SHA2-256, RIPEMD-160,
+//! SHA-1 and MD5, 128 for SHA-512 and SHA-384, 64 for BLAKE3, whose input
+//! is padded with zeros, a block at least, and a single block of 213 for
+//! BLAKE2F, each as `ceil(BLOCK_BYTES / 16)` chunks of 16 bytes as a digest
+//! is, the bytes of the padding being zeros. The disabled slots after the
+//! blocks of the calls are the zeros the rest of the column is padded with.
+//!
+//! The RLCs of the inputs and digests are not public inputs, as their
+//! challenge is drawn from the transcript after the instances.
+
+use halo2_proofs::arithmetic::FieldExt;
+
+use crate::{
+    call_slots::{blocks, max_slots, SlotPadding},
+    capacity::UNBOUNDED,
+};
+
+/// Bytes of a chunk of a digest.
+pub const CHUNK_BYTES: usize = 16;
+
+/// The chunks of `digest`, each the big-endian integer of its bytes, see
+/// the module docs.
+pub fn digest_chunks<F: FieldExt>(digest: &[u8]) -> Vec<F> {
+    digest
+        .chunks(CHUNK_BYTES)
+        .map(|chunk| {
+            let mut bytes = [0; CHUNK_BYTES];
+            bytes[CHUNK_BYTES - chunk.len()..].copy_from_slice(chunk);
+            F::from_u128(u128::from_be_bytes(bytes))
+        })
+        .collect()
+}
+
+/// Builder of the instance columns of a circuit, from its calls in the order
+/// of their call ids, see the module docs.
+#[derive(Clone, Debug)]
+pub struct PublicInputs<F> {
+    digest_bytes: usize,
+    block_bytes: usize,
+    padding: SlotPadding,
+    calls: u64,
+    values: Vec<F>,
+    inputs: Vec<F>,
+}
+
+impl<F: FieldExt> PublicInputs<F> {
+    /// The public inputs of no calls of a circuit of `digest_bytes` digests
+    /// and blocks of `block_bytes` bytes padded with `padding`, as its
+    /// [`CallSlotsConfig`](crate::CallSlotsConfig) is configured.
+    pub fn new(digest_bytes: usize, block_bytes: usize, padding: SlotPadding) -> Self {
+        Self {
+            digest_bytes,
+            block_bytes,
+            padding,
+            calls: 0,
+            values: vec![],
+            inputs: vec![],
+        }
+    }
+
+    /// Number of values of `calls` calls of a circuit of `digest_bytes`
+    /// digests.
+    pub fn num_values(digest_bytes: usize, calls: usize) -> usize {
+        1 + calls * (2 + digest_bytes.div_ceil(CHUNK_BYTES))
+    }
+
+    /// Pushes the call of `input` and of `digest`, and returns its call id.
+    /// The call ids start at 1, as 0 is that of the disabled rows of the
+    /// tables.
+    pub fn call(&mut self, input: &[u8], digest: &[u8]) -> u64 {
+        assert_eq!(
+            digest.len(),
+            self.digest_bytes,
+            "a digest of another circuit"
+        );
+        self.calls += 1;
+        self.values.push(F::from(self.calls));
+        self.values.push(F::from(input.len() as u64));
+        self.values.extend(digest_chunks::<F>(digest));
+        for i in 0..blocks(input.len(), self.block_bytes, self.padding) {
+            let mut block = vec![0; self.block_bytes];
+            let bytes = input
+                .iter()
+                .skip(i * self.block_bytes)
+                .take(self.block_bytes);
+            for (byte, input) in block.iter_mut().zip(bytes) {
+                *byte = *input;
+            }
+            self.inputs.extend(digest_chunks::<F>(&block));
+        }
+        self.calls
+    }
+
+    /// The instance columns of the calls pushed so far.
+    pub fn build(&self) -> Vec<Vec<F>> {
+        let mut column = Vec::with_capacity(1 + self.values.len());
+        column.push(F::from(self.calls));
+        column.extend_from_slice(&self.values);
+        vec![column, self.inputs.clone()]
+    }
+
+    /// The instance columns of the calls pushed so far, padded with the
+    /// zeros of the disabled table rows and slots of a circuit of a capacity
+    /// of `max_calls` calls of `max_len`, as its layout is, so that their
+    /// lengths only depend on the capacity, e.g. the instance counts of a
+    /// verifier contract. A capacity that is [`UNBOUNDED`] pads nothing.
+    pub fn build_padded(&self, max_calls: usize, max_len: usize) -> Vec<Vec<F>> {
+        let mut columns = self.build();
+        let slots = max_slots(max_calls, max_len, self.block_bytes, self.padding);
+        let lens = [
+            (max_calls != UNBOUNDED).then(|| Self::num_values(self.digest_bytes, max_calls)),
+            (slots != UNBOUNDED).then(|| slots * self.block_bytes.div_ceil(CHUNK_BYTES)),
+        ];
+        for (column, len) in columns.iter_mut().zip(lens) {
+            if let Some(len) = len {
+                assert!(column.len() <= len, "the calls fit in the capacity");
+                column.resize(len, F::zero());
+            }
+        }
+        columns
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use halo2_proofs::halo2curves::bn256::Fr;
+
+    #[test]
+    fn test_public_inputs() {
+        let padding = crate::MdPadding {
+            len_bytes: 8,
+            endianness: crate::bytes::Endianness::Little,
+        };
+        let mut public_inputs = PublicInputs::<Fr>::new(20, 64, SlotPadding::Md(padding));
+        assert_eq!(public_inputs.build(), vec![vec![Fr::from(0)], vec![]]);
+
+        let digest: Vec<u8> = (1..=20).collect();
+        assert_eq!(public_inputs.call(&[1, 2, 3], &digest), 1);
+        assert_eq!(public_inputs.call(&[0xff; 56], &[0xff; 20]), 2);
+        let [column, inputs]: [_; 2] = public_inputs.build().try_into().unwrap();
+        assert_eq!(column.len(), PublicInputs::<Fr>::num_values(20, 2));
+        assert_eq!(column[..3], [Fr::from(2), Fr::from(1), Fr::from(3)]);
+        let hi = u128::from_be_bytes(digest[..16].try_into().unwrap());
+        assert_eq!(column[3], Fr::from_u128(hi));
+        assert_eq!(column[4], Fr::from(0x11121314));
+        assert_eq!(
+            column[5..8],
+            [Fr::from(2), Fr::from(56), Fr::from_u128(u128::MAX)]
+        );
+        assert_eq!(column[8], Fr::from(0xffffffff));
+
+        // a block of the first input, and two of the second, without their
+        // padding.
+        assert_eq!(inputs.len(), 3 * 4);
+        assert_eq!(inputs[0], Fr::from_u128(0x010203 << 104));
+        assert_eq!(inputs[1..4], [Fr::from(0); 3]);
+        assert_eq!(inputs[4..7], [Fr::from_u128(u128::MAX); 3]);
+        assert_eq!(inputs[7], Fr::from_u128(u128::MAX << 64));
+        assert_eq!(inputs[8..], [Fr::from(0); 4]);
+
+        // the 4 table rows and 6 slots of 4 calls of 128 bytes.
+        let [padded_column, padded_inputs]: [_; 2] =
+            public_inputs.build_padded(4, 128).try_into().unwrap();
+        assert_eq!(padded_column.len(), PublicInputs::<Fr>::num_values(20, 4));
+        assert_eq!(padded_column[..column.len()], column);
+        assert_eq!(padded_inputs.len(), 6 * 4);
+        assert_eq!(padded_inputs[..inputs.len()], inputs);
+        assert_eq!(
+            public_inputs.build_padded(UNBOUNDED, UNBOUNDED),
+            public_inputs.build()
+        );
+    }
+}
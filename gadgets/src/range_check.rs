@@ -0,0 +1,145 @@
+use std::marker::PhantomData;
+
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{Region, Value},
+    plonk::{Advice, Column, ConstraintSystem, Error, Selector},
+    poly::Rotation,
+};
+
+use crate::{tables::RangeTable, util::AssignedValue};
+
+/// Constrains a single advice cell to lie in `[0, 2^BITS)`.
+#[derive(Clone, Copy, Debug)]
+pub struct RangeCheckConfig<const BITS: usize> {
+    q_lookup: Selector,
+    pub value: Column<Advice>,
+}
+
+impl<const BITS: usize> RangeCheckConfig<BITS> {
+    pub fn configure<F: FieldExt>(meta: &mut ConstraintSystem<F>, table: RangeTable<BITS>) -> Self {
+        let q_lookup = meta.complex_selector();
+        let value = meta.advice_column();
+        meta.enable_equality(value);
+
+        meta.lookup("value is in range", |meta| {
+            let q_lookup = meta.query_selector(q_lookup);
+            let value = meta.query_advice(value, Rotation::cur());
+            vec![(q_lookup * value, table.value)]
+        });
+
+        Self { q_lookup, value }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct RangeCheckChip<F, const BITS: usize> {
+    config: RangeCheckConfig<BITS>,
+    _marker: PhantomData<F>,
+}
+
+impl<F: FieldExt, const BITS: usize> RangeCheckChip<F, BITS> {
+    pub fn construct(config: RangeCheckConfig<BITS>) -> Self {
+        Self {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn assign(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        value: u64,
+    ) -> Result<AssignedValue<F>, Error> {
+        self.config.q_lookup.enable(region, offset)?;
+        region.assign_advice(
+            || "value",
+            self.config.value,
+            offset,
+            || Value::known(F::from(value)),
+        )
+    }
+
+    /// Copies an already assigned cell into the range-checked column.
+    pub fn copy(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        cell: &AssignedValue<F>,
+    ) -> Result<AssignedValue<F>, Error> {
+        self.config.q_lookup.enable(region, offset)?;
+        cell.copy_advice(|| "value", region, self.config.value, offset)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use halo2_proofs::{
+        circuit::{Layouter, SimpleFloorPlanner},
+        dev::MockProver,
+        halo2curves::{bn256::Fr, pasta::pallas},
+        plonk::Circuit,
+    };
+
+    #[derive(Default)]
+    struct TestCircuit<F> {
+        values: Vec<u64>,
+        _marker: PhantomData<F>,
+    }
+
+    impl<F: FieldExt> Circuit<F> for TestCircuit<F> {
+        type Config = (RangeTable<8>, RangeCheckConfig<8>);
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            let table = RangeTable::construct(meta);
+            (table, RangeCheckConfig::configure(meta, table))
+        }
+
+        fn synthesize(
+            &self,
+            (table, config): Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            table.load(&mut layouter)?;
+            let chip = RangeCheckChip::construct(config);
+            layouter.assign_region(
+                || "range check",
+                |mut region| {
+                    for (offset, value) in self.values.iter().enumerate() {
+                        chip.assign(&mut region, offset, *value)?;
+                    }
+                    Ok(())
+                },
+            )
+        }
+    }
+
+    fn test_range_check_over<F: FieldExt>() {
+        let circuit = TestCircuit::<F> {
+            values: vec![0, 1, 127, 255],
+            _marker: PhantomData,
+        };
+        let prover = MockProver::run(9, &circuit, vec![]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+
+        let circuit = TestCircuit::<F> {
+            values: vec![256],
+            _marker: PhantomData,
+        };
+        let prover = MockProver::run(9, &circuit, vec![]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+
+    #[test]
+    fn test_range_check() {
+        test_range_check_over::<Fr>();
+        test_range_check_over::<pallas::Base>();
+    }
+}
@@ -0,0 +1,296 @@
+use std::marker::PhantomData;
+
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{Region, Value},
+    plonk::{Advice, Column, ConstraintSystem, Error, Expression, Fixed, Selector},
+    poly::Rotation,
+};
+
+use crate::util::{mask, AssignedValue};
+
+/// Bit-level permutation of a word, as used by the hash functions.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BitShift {
+    RotateRight(usize),
+    RotateLeft(usize),
+    ShiftRight(usize),
+    ShiftLeft(usize),
+}
+
+impl BitShift {
+    /// Position of input bit `i` in the output word of `word_bits` bits, or
+    /// `None` if the bit is shifted out.
+    pub fn position(&self, i: usize, word_bits: usize) -> Option<usize> {
+        match *self {
+            Self::RotateRight(n) => Some((i + word_bits - n % word_bits) % word_bits),
+            Self::RotateLeft(n) => Some((i + n) % word_bits),
+            Self::ShiftRight(n) => i.checked_sub(n),
+            Self::ShiftLeft(n) => Some(i + n).filter(|position| *position < word_bits),
+        }
+    }
+
+    /// Native evaluation of the shift over a `word_bits`-bit word.
+    pub fn apply(&self, word: u64, word_bits: usize) -> u64 {
+        (0..word_bits)
+            .filter(|i| (word >> i) & 1 == 1)
+            .filter_map(|i| self.position(i, word_bits))
+            .fold(0, |acc, position| acc | (1 << position))
+            & mask(word_bits)
+    }
+}
+
+/// Rotates or shifts a word by decomposing it into bits over `word_bits`
+/// consecutive rows. Two running sums are accumulated over the bits: `acc_in`
+/// with the bit weights of the input word and `acc_out` with the weights of
+/// the permuted word, so the final row holds both the (range checked) input
+/// and its rotation.
+///
+/// | row | bit   | coeff_in | coeff_out        | acc_in  | acc_out  |
+/// |-----|-------|----------|------------------|---------|----------|
+/// | 0   | b_0   | 2^0      | 2^position(0)    | ..      | ..       |
+/// | i   | b_i   | 2^i      | 2^position(i)    | ..      | ..       |
+/// | w-1 | b_w-1 | 2^(w-1)  | 2^position(w-1)  | input   | output   |
+#[derive(Clone, Copy, Debug)]
+pub struct RotateConfig {
+    q_bit: Selector,   // enabled on every row of the decomposition.
+    q_first: Selector, // enabled on the first row of the decomposition.
+    q_step: Selector,  // enabled on all but the first row of the decomposition.
+    pub bit: Column<Advice>,
+    coeff_in: Column<Fixed>,
+    coeff_out: Column<Fixed>,
+    pub acc_in: Column<Advice>,
+    pub acc_out: Column<Advice>,
+}
+
+impl RotateConfig {
+    pub fn configure<F: FieldExt>(meta: &mut ConstraintSystem<F>) -> Self {
+        let [q_bit, q_first, q_step] = [0; 3].map(|_| meta.selector());
+        let [coeff_in, coeff_out] = [0; 2].map(|_| meta.fixed_column());
+        let [bit, acc_in, acc_out] = [0; 3].map(|_| meta.advice_column());
+        meta.enable_equality(acc_in);
+        meta.enable_equality(acc_out);
+
+        meta.create_gate("bit is boolean", |meta| {
+            let q_bit = meta.query_selector(q_bit);
+            let bit = meta.query_advice(bit, Rotation::cur());
+            vec![q_bit * bit.clone() * (Expression::Constant(F::one()) - bit)]
+        });
+
+        meta.create_gate("accumulators start from the first bit", |meta| {
+            let q_first = meta.query_selector(q_first);
+            let bit = meta.query_advice(bit, Rotation::cur());
+            let coeff_in = meta.query_fixed(coeff_in, Rotation::cur());
+            let coeff_out = meta.query_fixed(coeff_out, Rotation::cur());
+            let acc_in = meta.query_advice(acc_in, Rotation::cur());
+            let acc_out = meta.query_advice(acc_out, Rotation::cur());
+            vec![
+                q_first.clone() * (acc_in - bit.clone() * coeff_in),
+                q_first * (acc_out - bit * coeff_out),
+            ]
+        });
+
+        meta.create_gate("accumulators add the weighted bit", |meta| {
+            let q_step = meta.query_selector(q_step);
+            let bit = meta.query_advice(bit, Rotation::cur());
+            let coeff_in = meta.query_fixed(coeff_in, Rotation::cur());
+            let coeff_out = meta.query_fixed(coeff_out, Rotation::cur());
+            let acc_in_cur = meta.query_advice(acc_in, Rotation::cur());
+            let acc_in_prev = meta.query_advice(acc_in, Rotation::prev());
+            let acc_out_cur = meta.query_advice(acc_out, Rotation::cur());
+            let acc_out_prev = meta.query_advice(acc_out, Rotation::prev());
+            vec![
+                q_step.clone() * (acc_in_cur - acc_in_prev - bit.clone() * coeff_in),
+                q_step * (acc_out_cur - acc_out_prev - bit * coeff_out),
+            ]
+        });
+
+        Self {
+            q_bit,
+            q_first,
+            q_step,
+            bit,
+            coeff_in,
+            coeff_out,
+            acc_in,
+            acc_out,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct RotateChip<F> {
+    config: RotateConfig,
+    _marker: PhantomData<F>,
+}
+
+impl<F: FieldExt> RotateChip<F> {
+    pub fn construct(config: RotateConfig) -> Self {
+        Self {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Assigns the decomposition of `word` over rows `offset..offset + word_bits`
+    /// and returns the `(input, output)` cells in the last row.
+    pub fn assign(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        word: u64,
+        word_bits: usize,
+        shift: BitShift,
+    ) -> Result<(AssignedValue<F>, AssignedValue<F>), Error> {
+        assert!(word_bits > 0 && word_bits <= 64);
+        let config = self.config;
+
+        let mut acc_in = 0u128;
+        let mut acc_out = 0u128;
+        let mut cells = None;
+        for i in 0..word_bits {
+            let row = offset + i;
+            config.q_bit.enable(region, row)?;
+            if i == 0 {
+                config.q_first.enable(region, row)?;
+            } else {
+                config.q_step.enable(region, row)?;
+            }
+
+            let bit = (word >> i) & 1;
+            let coeff_in = 1u128 << i;
+            let coeff_out = shift
+                .position(i, word_bits)
+                .map_or(0, |position| 1u128 << position);
+            acc_in += bit as u128 * coeff_in;
+            acc_out += bit as u128 * coeff_out;
+
+            region.assign_advice(|| "bit", config.bit, row, || Value::known(F::from(bit)))?;
+            region.assign_fixed(
+                || "coeff_in",
+                config.coeff_in,
+                row,
+                || Value::known(F::from_u128(coeff_in)),
+            )?;
+            region.assign_fixed(
+                || "coeff_out",
+                config.coeff_out,
+                row,
+                || Value::known(F::from_u128(coeff_out)),
+            )?;
+            let acc_in_cell = region.assign_advice(
+                || "acc_in",
+                config.acc_in,
+                row,
+                || Value::known(F::from_u128(acc_in)),
+            )?;
+            let acc_out_cell = region.assign_advice(
+                || "acc_out",
+                config.acc_out,
+                row,
+                || Value::known(F::from_u128(acc_out)),
+            )?;
+            cells = Some((acc_in_cell, acc_out_cell));
+        }
+        Ok(cells.expect("word_bits is non-zero"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use halo2_proofs::{
+        circuit::{Layouter, SimpleFloorPlanner},
+        dev::MockProver,
+        halo2curves::{bn256::Fr, pasta::pallas},
+        plonk::Circuit,
+    };
+
+    #[derive(Default)]
+    struct TestCircuit<F> {
+        // (word, word_bits, shift, expected output)
+        rows: Vec<(u64, usize, BitShift, u64)>,
+        _marker: PhantomData<F>,
+    }
+
+    impl<F: FieldExt> Circuit<F> for TestCircuit<F> {
+        type Config = RotateConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            RotateConfig::configure(meta)
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            let chip = RotateChip::construct(config);
+            for (word, word_bits, shift, expected) in self.rows.iter() {
+                layouter.assign_region(
+                    || "rotate",
+                    |mut region| {
+                        let (_, output) = chip.assign(&mut region, 0, *word, *word_bits, *shift)?;
+                        output
+                            .value()
+                            .assert_if_known(|output| **output == F::from(*expected));
+                        Ok(())
+                    },
+                )?;
+            }
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_bit_shift() {
+        let word = 0x8000_0001u64;
+        assert_eq!(
+            BitShift::RotateRight(1).apply(word, 32),
+            (word as u32).rotate_right(1) as u64
+        );
+        assert_eq!(
+            BitShift::RotateLeft(7).apply(word, 32),
+            (word as u32).rotate_left(7) as u64
+        );
+        assert_eq!(BitShift::ShiftRight(3).apply(word, 32), word >> 3);
+        assert_eq!(
+            BitShift::ShiftLeft(3).apply(word, 32),
+            (word << 3) & 0xffff_ffff
+        );
+        assert_eq!(
+            BitShift::RotateRight(63).apply(u64::MAX - 1, 64),
+            (u64::MAX - 1).rotate_right(63)
+        );
+    }
+
+    fn test_rotate_circuit_over<F: FieldExt>() {
+        let rows = [
+            (0x8000_0001, 32, BitShift::RotateRight(7)),
+            (0xdead_beef, 32, BitShift::ShiftRight(3)),
+            (0x0123_4567_89ab_cdef, 64, BitShift::RotateRight(24)),
+            (0x0123_4567_89ab_cdef, 64, BitShift::RotateLeft(1)),
+        ]
+        .into_iter()
+        .map(|(word, word_bits, shift)| (word, word_bits, shift, shift.apply(word, word_bits)))
+        .collect();
+
+        let circuit = TestCircuit::<F> {
+            rows,
+            _marker: PhantomData,
+        };
+        let prover = MockProver::run(8, &circuit, vec![]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+    }
+
+    #[test]
+    fn test_rotate_circuit() {
+        test_rotate_circuit_over::<Fr>();
+        test_rotate_circuit_over::<pallas::Base>();
+    }
+}
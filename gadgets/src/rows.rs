@@ -0,0 +1,549 @@
+//! Rows the regions of a circuit use, measured as its chips assign them, so
+//! that the capacity of the super-circuit is planned from the rows of real
+//! witnesses rather than from estimates.
+//!
+//! The circuit is synthesized by the `MockProver` through a layouter
+//! wrapping the one of the floor planner, which records the regions in the
+//! order they are assigned, with their rows up to their last assigned cell
+//! or enabled selector, as laid out by the floor planner. The fixed tables, loaded once per
+//! circuit whatever its witness, are not recorded.
+//!
+//! [`layout_rows`] rather lays the circuit out without a prover, its fixed
+//! tables included, to size the `k` of a circuit before it is run, see
+//! [`min_k`].
+
+use std::{cell::RefCell, fmt, marker::PhantomData};
+
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{
+        floor_planner::FloorPlanner, layouter::RegionLayouter, Cell, Layouter, Region,
+        SimpleFloorPlanner, Table, Value,
+    },
+    dev::MockProver,
+    plonk::{
+        Advice, Any, Assigned, Assignment, Challenge, Circuit, Column, ConstraintSystem, Error,
+        Fixed, Instance, Selector,
+    },
+};
+
+use crate::{util::k_for_rows, HashCircuitExt};
+
+/// Rows the layout of `circuit` takes, its fixed tables and constants
+/// included, before the blinding rows. The circuit is laid out by its floor
+/// planner without its witness values, so that its chips must not depend on
+/// them, e.g. on the challenges, to place their cells.
+///
+/// If the synthesis fails, e.g. on a witness the chips refuse, the rows
+/// assigned so far are returned, the failure being that of the prover run on
+/// the circuit.
+pub fn layout_rows<F: FieldExt, C: Circuit<F>>(circuit: &C) -> usize {
+    let mut meta = ConstraintSystem::default();
+    let config = C::configure(&mut meta);
+    let mut recorder = RowRecorder::default();
+    // Ignoring the failure, see above.
+    let _ = C::FloorPlanner::synthesize(&mut recorder, circuit, config, meta.constants().clone());
+    recorder.rows
+}
+
+/// Smallest `k` that fits the layout of `circuit` and the blinding rows of
+/// its constraint system, see [`layout_rows`] and [`k_for_rows`].
+pub fn min_k<F: FieldExt, C: Circuit<F>>(circuit: &C) -> u32 {
+    k_for_rows(C::configure, layout_rows(circuit))
+}
+
+/// An `Assignment` recording the rows up to the last assigned cell or
+/// enabled selector, and nothing else.
+#[derive(Default)]
+struct RowRecorder {
+    rows: usize,
+}
+
+impl RowRecorder {
+    fn use_row(&mut self, row: usize) {
+        self.rows = self.rows.max(row + 1);
+    }
+}
+
+impl<F: FieldExt> Assignment<F> for RowRecorder {
+    fn enter_region<NR, N>(&mut self, _: N)
+    where
+        NR: Into<String>,
+        N: FnOnce() -> NR,
+    {
+    }
+
+    fn exit_region(&mut self) {}
+
+    fn enable_selector<A, AR>(&mut self, _: A, _: &Selector, row: usize) -> Result<(), Error>
+    where
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        self.use_row(row);
+        Ok(())
+    }
+
+    fn query_instance(&self, _: Column<Instance>, _: usize) -> Result<Value<F>, Error> {
+        Ok(Value::unknown())
+    }
+
+    fn assign_advice<V, VR, A, AR>(
+        &mut self,
+        _: A,
+        _: Column<Advice>,
+        row: usize,
+        _: V,
+    ) -> Result<(), Error>
+    where
+        V: FnOnce() -> Value<VR>,
+        VR: Into<Assigned<F>>,
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        self.use_row(row);
+        Ok(())
+    }
+
+    fn assign_fixed<V, VR, A, AR>(
+        &mut self,
+        _: A,
+        _: Column<Fixed>,
+        row: usize,
+        _: V,
+    ) -> Result<(), Error>
+    where
+        V: FnOnce() -> Value<VR>,
+        VR: Into<Assigned<F>>,
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        self.use_row(row);
+        Ok(())
+    }
+
+    fn copy(&mut self, _: Column<Any>, _: usize, _: Column<Any>, _: usize) -> Result<(), Error> {
+        Ok(())
+    }
+
+    // The rest of a table column is filled with its default, past the rows
+    // of the table.
+    fn fill_from_row(
+        &mut self,
+        _: Column<Fixed>,
+        _: usize,
+        _: Value<Assigned<F>>,
+    ) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn get_challenge(&self, _: Challenge) -> Value<F> {
+        Value::unknown()
+    }
+
+    fn push_namespace<NR, N>(&mut self, _: N)
+    where
+        NR: Into<String>,
+        N: FnOnce() -> NR,
+    {
+    }
+
+    fn pop_namespace(&mut self, _: Option<String>) {}
+}
+
+/// The rows a region uses, by its name.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RegionRows {
+    pub name: String,
+    pub rows: usize,
+}
+
+/// The rows of every region of a circuit, in the order they are assigned.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct RowUsage {
+    pub regions: Vec<RegionRows>,
+}
+
+impl RowUsage {
+    /// Rows of the regions of `circuit` synthesized over `2^k` rows.
+    pub fn measure<F: FieldExt, C: Circuit<F>>(
+        k: u32,
+        circuit: &C,
+        instances: Vec<Vec<F>>,
+    ) -> Result<Self, Error> {
+        let usage = RefCell::new(Self::default());
+        MockProver::run(
+            k,
+            &Measured {
+                circuit,
+                usage: &usage,
+            },
+            instances,
+        )?;
+        Ok(usage.into_inner())
+    }
+
+    /// Rows of all the regions, as laid out one after the other.
+    pub fn total(&self) -> usize {
+        self.regions.iter().map(|region| region.rows).sum()
+    }
+
+    /// The rows of every region, one per line, followed by their total.
+    pub fn report(&self) -> String {
+        let mut lines: Vec<_> = self
+            .regions
+            .iter()
+            .map(|region| format!("{}: {} rows", region.name, region.rows))
+            .collect();
+        lines.push(format!("total: {} rows", self.total()));
+        lines.join("\n")
+    }
+}
+
+/// The circuit of the chip `H` alone, loading `witness`, for
+/// [`HashCircuitExt::row_usage`].
+pub(crate) struct ChipCircuit<F, H: HashCircuitExt<F>>
+where
+    F: FieldExt,
+{
+    pub(crate) witness: H::Witness,
+    pub(crate) _marker: PhantomData<F>,
+}
+
+impl<F: FieldExt, H: HashCircuitExt<F>> Circuit<F> for ChipCircuit<F, H> {
+    type Config = H::Config;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    // The circuit is only synthesized by the `MockProver`, which keeps the
+    // witness.
+    fn without_witnesses(&self) -> Self {
+        Self {
+            witness: self.witness.clone(),
+            _marker: PhantomData,
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        H::configure(meta)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        H::construct(config, self.witness.clone()).load(&mut layouter)
+    }
+}
+
+/// `circuit` synthesized through a [`RowCountingLayouter`].
+struct Measured<'a, C> {
+    circuit: &'a C,
+    usage: &'a RefCell<RowUsage>,
+}
+
+impl<'a, F: FieldExt, C: Circuit<F>> Circuit<F> for Measured<'a, C> {
+    type Config = C::Config;
+    type FloorPlanner = C::FloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self {
+            circuit: self.circuit,
+            usage: self.usage,
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        C::configure(meta)
+    }
+
+    fn synthesize(&self, config: Self::Config, layouter: impl Layouter<F>) -> Result<(), Error> {
+        // A floor planner may synthesize the circuit more than once, e.g. to
+        // measure its regions first, so only the last pass is recorded.
+        self.usage.borrow_mut().regions.clear();
+        self.circuit.synthesize(
+            config,
+            RowCountingLayouter {
+                layouter,
+                usage: self.usage,
+            },
+        )
+    }
+}
+
+struct RowCountingLayouter<'a, L> {
+    layouter: L,
+    usage: &'a RefCell<RowUsage>,
+}
+
+impl<'a, F: FieldExt, L: Layouter<F>> Layouter<F> for RowCountingLayouter<'a, L> {
+    type Root = Self;
+
+    fn assign_region<A, AR, N, NR>(&mut self, name: N, mut assignment: A) -> Result<AR, Error>
+    where
+        A: FnMut(Region<'_, F>) -> Result<AR, Error>,
+        N: Fn() -> NR,
+        NR: Into<String>,
+    {
+        let name: String = name().into();
+        let mut rows = 0;
+        // The floor planner may run the assignment more than once, e.g. to
+        // find the shape of the region first.
+        let result = self.layouter.assign_region(
+            || name.clone(),
+            |region| {
+                let mut region = RowCountingRegion { region, rows: 0 };
+                let result = assignment(Region::from(&mut region as &mut dyn RegionLayouter<F>));
+                rows = rows.max(region.rows);
+                result
+            },
+        )?;
+        self.usage
+            .borrow_mut()
+            .regions
+            .push(RegionRows { name, rows });
+        Ok(result)
+    }
+
+    fn assign_table<A, N, NR>(&mut self, name: N, assignment: A) -> Result<(), Error>
+    where
+        A: FnMut(Table<'_, F>) -> Result<(), Error>,
+        N: Fn() -> NR,
+        NR: Into<String>,
+    {
+        self.layouter.assign_table(name, assignment)
+    }
+
+    fn constrain_instance(
+        &mut self,
+        cell: Cell,
+        column: Column<Instance>,
+        row: usize,
+    ) -> Result<(), Error> {
+        self.layouter.constrain_instance(cell, column, row)
+    }
+
+    fn get_challenge(&self, challenge: Challenge) -> Value<F> {
+        self.layouter.get_challenge(challenge)
+    }
+
+    fn get_root(&mut self) -> &mut Self::Root {
+        self
+    }
+
+    fn push_namespace<NR, N>(&mut self, name_fn: N)
+    where
+        NR: Into<String>,
+        N: FnOnce() -> NR,
+    {
+        self.layouter.get_root().push_namespace(name_fn)
+    }
+
+    fn pop_namespace(&mut self, gadget_name: Option<String>) {
+        self.layouter.get_root().pop_namespace(gadget_name)
+    }
+}
+
+/// A region of the floor planner, assigned through its public methods, with
+/// the rows up to the last offset it uses.
+struct RowCountingRegion<'r, F: FieldExt> {
+    region: Region<'r, F>,
+    rows: usize,
+}
+
+impl<'r, F: FieldExt> RowCountingRegion<'r, F> {
+    fn record(&mut self, offset: usize) {
+        self.rows = self.rows.max(offset + 1);
+    }
+}
+
+impl<'r, F: FieldExt> fmt::Debug for RowCountingRegion<'r, F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RowCountingRegion")
+            .field("rows", &self.rows)
+            .finish()
+    }
+}
+
+impl<'r, F: FieldExt> RegionLayouter<F> for RowCountingRegion<'r, F> {
+    fn enable_selector<'v>(
+        &'v mut self,
+        _annotation: &'v (dyn Fn() -> String + 'v),
+        selector: &Selector,
+        offset: usize,
+    ) -> Result<(), Error> {
+        self.record(offset);
+        selector.enable(&mut self.region, offset)
+    }
+
+    fn name_column<'v>(
+        &'v mut self,
+        annotation: &'v (dyn Fn() -> String + 'v),
+        column: Column<Any>,
+    ) {
+        self.region.name_column(annotation, column)
+    }
+
+    fn assign_advice<'v>(
+        &'v mut self,
+        annotation: &'v (dyn Fn() -> String + 'v),
+        column: Column<Advice>,
+        offset: usize,
+        to: &'v mut (dyn FnMut() -> Value<Assigned<F>> + 'v),
+    ) -> Result<Cell, Error> {
+        self.record(offset);
+        self.region
+            .assign_advice(annotation, column, offset, to)
+            .map(|cell| cell.cell())
+    }
+
+    fn assign_advice_from_constant<'v>(
+        &'v mut self,
+        annotation: &'v (dyn Fn() -> String + 'v),
+        column: Column<Advice>,
+        offset: usize,
+        constant: Assigned<F>,
+    ) -> Result<Cell, Error> {
+        self.record(offset);
+        self.region
+            .assign_advice_from_constant(annotation, column, offset, constant)
+            .map(|cell| cell.cell())
+    }
+
+    fn assign_advice_from_instance<'v>(
+        &mut self,
+        annotation: &'v (dyn Fn() -> String + 'v),
+        instance: Column<Instance>,
+        row: usize,
+        advice: Column<Advice>,
+        offset: usize,
+    ) -> Result<(Cell, Value<F>), Error> {
+        self.record(offset);
+        self.region
+            .assign_advice_from_instance(annotation, instance, row, advice, offset)
+            .map(|cell| (cell.cell(), cell.value().copied()))
+    }
+
+    fn instance_value(
+        &mut self,
+        instance: Column<Instance>,
+        row: usize,
+    ) -> Result<Value<F>, Error> {
+        self.region.instance_value(instance, row)
+    }
+
+    fn assign_fixed<'v>(
+        &'v mut self,
+        annotation: &'v (dyn Fn() -> String + 'v),
+        column: Column<Fixed>,
+        offset: usize,
+        to: &'v mut (dyn FnMut() -> Value<Assigned<F>> + 'v),
+    ) -> Result<Cell, Error> {
+        self.record(offset);
+        self.region
+            .assign_fixed(annotation, column, offset, to)
+            .map(|cell| cell.cell())
+    }
+
+    fn constrain_constant(&mut self, cell: Cell, constant: Assigned<F>) -> Result<(), Error> {
+        self.region.constrain_constant(cell, constant)
+    }
+
+    fn constrain_equal(&mut self, left: Cell, right: Cell) -> Result<(), Error> {
+        self.region.constrain_equal(left, right)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{tables::RangeTable, RangeCheckChip, RangeCheckConfig};
+    use halo2_proofs::halo2curves::bn256::Fr;
+
+    /// Range checks `values` in a region, and a value at the offset 2 of
+    /// another.
+    #[derive(Default)]
+    struct TestCircuit {
+        values: Vec<u64>,
+    }
+
+    impl Circuit<Fr> for TestCircuit {
+        type Config = (RangeTable<8>, RangeCheckConfig<8>);
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+            let table = RangeTable::construct(meta);
+            (table, RangeCheckConfig::configure(meta, table))
+        }
+
+        fn synthesize(
+            &self,
+            (table, config): Self::Config,
+            mut layouter: impl Layouter<Fr>,
+        ) -> Result<(), Error> {
+            table.load(&mut layouter)?;
+            let chip = RangeCheckChip::construct(config);
+            layouter.assign_region(
+                || "range check",
+                |mut region| {
+                    for (offset, value) in self.values.iter().enumerate() {
+                        chip.assign(&mut region, offset, *value)?;
+                    }
+                    Ok(())
+                },
+            )?;
+            layouter.assign_region(
+                || "offset",
+                |mut region| chip.assign(&mut region, 2, 7).map(|_| ()),
+            )
+        }
+    }
+
+    #[test]
+    fn test_row_usage() {
+        let circuit = TestCircuit {
+            values: vec![1, 2, 3, 255],
+        };
+        let usage = RowUsage::measure(9, &circuit, vec![]).unwrap();
+        // the table is not recorded.
+        assert_eq!(
+            usage.regions,
+            [
+                RegionRows {
+                    name: "range check".to_string(),
+                    rows: 4,
+                },
+                RegionRows {
+                    name: "offset".to_string(),
+                    rows: 3,
+                },
+            ]
+        );
+        assert_eq!(usage.total(), 7);
+        assert!(usage.report().ends_with("offset: 3 rows\ntotal: 7 rows"));
+    }
+
+    #[test]
+    fn test_layout_rows() {
+        // the rows of the table.
+        let circuit = TestCircuit {
+            values: vec![1, 2, 3, 255],
+        };
+        assert_eq!(layout_rows(&circuit), 256);
+
+        // the second region uses the columns of the first one, so it is laid
+        // out after it.
+        let circuit = TestCircuit {
+            values: vec![0; 300],
+        };
+        assert_eq!(layout_rows(&circuit), 303);
+        assert_eq!(min_k(&circuit), k_for_rows(TestCircuit::configure, 303));
+        MockProver::run(min_k(&circuit), &circuit, vec![])
+            .unwrap()
+            .assert_satisfied();
+    }
+}
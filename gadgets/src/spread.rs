@@ -0,0 +1,178 @@
+use std::marker::PhantomData;
+
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{Region, Value},
+    plonk::{Advice, Column, ConstraintSystem, Error, Selector},
+    poly::Rotation,
+};
+
+use crate::{tables::SpreadTable, util::AssignedValue};
+
+/// Interleaves a zero bit between each bit of `dense`. Adding up to three
+/// spread values never carries into a neighbouring bit, which lets XOR, AND and
+/// majority-style functions be read off the even and odd bits of the sum.
+pub fn spread(dense: u64) -> u64 {
+    assert!(dense <= u32::MAX as u64, "only 32-bit values can be spread");
+    (0..32).fold(0, |acc, i| acc | (((dense >> i) & 1) << (2 * i)))
+}
+
+/// Inverse of [`spread`], reading back the even bits of `spread`.
+pub fn unspread(spread: u64) -> u64 {
+    (0..32).fold(0, |acc, i| acc | (((spread >> (2 * i)) & 1) << i))
+}
+
+/// Constrains a `(dense, spread)` pair of cells in the same row to be a row of
+/// the [`SpreadTable`], which also range checks `dense` to `BITS` bits.
+#[derive(Clone, Copy, Debug)]
+pub struct SpreadConfig<const BITS: usize> {
+    q_lookup: Selector,
+    pub dense: Column<Advice>,
+    pub spread: Column<Advice>,
+}
+
+impl<const BITS: usize> SpreadConfig<BITS> {
+    pub fn configure<F: FieldExt>(
+        meta: &mut ConstraintSystem<F>,
+        table: SpreadTable<BITS>,
+    ) -> Self {
+        let q_lookup = meta.complex_selector();
+        let [dense, spread] = [0; 2].map(|_| meta.advice_column());
+        meta.enable_equality(dense);
+        meta.enable_equality(spread);
+
+        meta.lookup("spread(dense) == spread", |meta| {
+            let q_lookup = meta.query_selector(q_lookup);
+            let dense = meta.query_advice(dense, Rotation::cur());
+            let spread = meta.query_advice(spread, Rotation::cur());
+            vec![
+                (q_lookup.clone() * dense, table.dense),
+                (q_lookup * spread, table.spread),
+            ]
+        });
+
+        Self {
+            q_lookup,
+            dense,
+            spread,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct SpreadChip<F, const BITS: usize> {
+    config: SpreadConfig<BITS>,
+    _marker: PhantomData<F>,
+}
+
+impl<F: FieldExt, const BITS: usize> SpreadChip<F, BITS> {
+    pub fn construct(config: SpreadConfig<BITS>) -> Self {
+        Self {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Assigns `dense` and its spread form at `offset`, returning both cells.
+    pub fn assign(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        dense: u64,
+    ) -> Result<(AssignedValue<F>, AssignedValue<F>), Error> {
+        self.config.q_lookup.enable(region, offset)?;
+        let dense_cell = region.assign_advice(
+            || "dense",
+            self.config.dense,
+            offset,
+            || Value::known(F::from(dense)),
+        )?;
+        let spread_cell = region.assign_advice(
+            || "spread",
+            self.config.spread,
+            offset,
+            || Value::known(F::from(spread(dense))),
+        )?;
+        Ok((dense_cell, spread_cell))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use halo2_proofs::{
+        circuit::{Layouter, SimpleFloorPlanner},
+        dev::MockProver,
+        halo2curves::{bn256::Fr, pasta::pallas},
+        plonk::Circuit,
+    };
+
+    #[derive(Default)]
+    struct TestCircuit<F> {
+        values: Vec<u64>,
+        _marker: PhantomData<F>,
+    }
+
+    impl<F: FieldExt> Circuit<F> for TestCircuit<F> {
+        type Config = (SpreadTable<8>, SpreadConfig<8>);
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            let table = SpreadTable::construct(meta);
+            (table, SpreadConfig::configure(meta, table))
+        }
+
+        fn synthesize(
+            &self,
+            (table, config): Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            table.load(&mut layouter)?;
+            let chip = SpreadChip::construct(config);
+            layouter.assign_region(
+                || "spread",
+                |mut region| {
+                    for (offset, value) in self.values.iter().enumerate() {
+                        chip.assign(&mut region, offset, *value)?;
+                    }
+                    Ok(())
+                },
+            )
+        }
+    }
+
+    #[test]
+    fn test_spread_roundtrip() {
+        for value in [0, 1, 0b1011, 0xdead_beef, u32::MAX as u64] {
+            assert_eq!(unspread(spread(value)), value);
+        }
+        assert_eq!(spread(0b1011), 0b01_00_01_01);
+    }
+
+    fn test_spread_circuit_over<F: FieldExt>() {
+        let circuit = TestCircuit::<F> {
+            values: vec![0, 1, 0x5a, 0xff],
+            _marker: PhantomData,
+        };
+        let prover = MockProver::run(9, &circuit, vec![]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+
+        // values wider than the table are rejected.
+        let circuit = TestCircuit::<F> {
+            values: vec![0x100],
+            _marker: PhantomData,
+        };
+        let prover = MockProver::run(9, &circuit, vec![]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+
+    #[test]
+    fn test_spread_circuit() {
+        test_spread_circuit_over::<Fr>();
+        test_spread_circuit_over::<pallas::Base>();
+    }
+}
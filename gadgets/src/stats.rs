@@ -0,0 +1,179 @@
+use halo2_proofs::{arithmetic::FieldExt, plonk::ConstraintSystem};
+
+/// Cost of the configuration of a circuit: its columns, gates, lookups and
+/// the maximum degree of its constraints, so that cost regressions show up
+/// as the constraints are written.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CircuitStats {
+    pub advice_columns: usize,
+    pub fixed_columns: usize,
+    pub instance_columns: usize,
+    pub selectors: usize,
+    pub gates: usize,
+    /// Number of polynomial constraints of the gates.
+    pub constraints: usize,
+    pub lookups: usize,
+    pub degree: usize,
+}
+
+impl CircuitStats {
+    /// Column names of [`Self::report`].
+    const COLUMNS: [&'static str; 9] = [
+        "circuit",
+        "advice",
+        "fixed",
+        "instance",
+        "selectors",
+        "gates",
+        "constraints",
+        "lookups",
+        "degree",
+    ];
+
+    pub fn new<F: FieldExt>(meta: &ConstraintSystem<F>) -> Self {
+        Self {
+            advice_columns: meta.num_advice_columns(),
+            fixed_columns: meta.num_fixed_columns(),
+            instance_columns: meta.num_instance_columns(),
+            selectors: meta.num_selectors(),
+            gates: meta.gates().len(),
+            constraints: meta
+                .gates()
+                .iter()
+                .map(|gate| gate.polynomials().len())
+                .sum(),
+            lookups: meta.lookups().len(),
+            degree: meta.degree(),
+        }
+    }
+
+    /// Stats of the configuration built by `configure` over an empty
+    /// constraint system.
+    pub fn of<F: FieldExt, C>(configure: impl FnOnce(&mut ConstraintSystem<F>) -> C) -> Self {
+        let mut meta = ConstraintSystem::default();
+        configure(&mut meta);
+        Self::new(&meta)
+    }
+
+    /// Row of the markdown table of [`Self::report`] for the circuit `name`.
+    pub fn row(&self, name: &str) -> String {
+        let cells = [
+            self.advice_columns,
+            self.fixed_columns,
+            self.instance_columns,
+            self.selectors,
+            self.gates,
+            self.constraints,
+            self.lookups,
+            self.degree,
+        ]
+        .map(|cell| cell.to_string());
+        format!("| {} | {} |", name, cells.join(" | "))
+    }
+
+    /// Markdown table of the stats of every named circuit, one row each.
+    pub fn report(circuits: &[(&str, Self)]) -> String {
+        let mut lines = vec![
+            format!("| {} |", Self::COLUMNS.join(" | ")),
+            format!("|{}", " --- |".repeat(Self::COLUMNS.len())),
+        ];
+        lines.extend(circuits.iter().map(|(name, stats)| stats.row(name)));
+        lines.join("\n")
+    }
+}
+
+/// Most columns, lookups and degree a circuit is allowed, so that a change
+/// to its constraints blowing up its proving cost fails its budget test
+/// rather than going unnoticed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CircuitBudget {
+    pub advice_columns: usize,
+    pub fixed_columns: usize,
+    pub lookups: usize,
+    pub degree: usize,
+}
+
+impl CircuitStats {
+    /// The stats exceeding `budget`, each as `name: stat > budget`.
+    pub fn over_budget(&self, budget: &CircuitBudget) -> Vec<String> {
+        [
+            ("advice columns", self.advice_columns, budget.advice_columns),
+            ("fixed columns", self.fixed_columns, budget.fixed_columns),
+            ("lookups", self.lookups, budget.lookups),
+            ("degree", self.degree, budget.degree),
+        ]
+        .into_iter()
+        .filter(|(_, stat, budget)| stat > budget)
+        .map(|(name, stat, budget)| format!("{name}: {stat} > {budget}"))
+        .collect()
+    }
+
+    /// Panics, naming the circuit `name` and the stats in excess, if they
+    /// exceed `budget`.
+    #[track_caller]
+    pub fn assert_within(&self, name: &str, budget: &CircuitBudget) {
+        let over = self.over_budget(budget);
+        assert!(
+            over.is_empty(),
+            "the {name} circuit is over its budget, {}",
+            over.join(", ")
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{tables::RangeTable, RangeCheckConfig};
+    use halo2_proofs::halo2curves::bn256::Fr;
+
+    #[test]
+    fn test_circuit_stats() {
+        let stats = CircuitStats::of::<Fr, _>(|meta| {
+            let table = RangeTable::<8>::construct(meta);
+            RangeCheckConfig::<8>::configure(meta, table)
+        });
+        assert_eq!(stats.lookups, 1);
+        assert_eq!(stats.instance_columns, 0);
+        assert!(stats.fixed_columns >= 1);
+        assert!(stats.degree >= 3);
+
+        let report = CircuitStats::report(&[("range check", stats)]);
+        let lines: Vec<&str> = report.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert_eq!(
+            lines[1],
+            "| --- | --- | --- | --- | --- | --- | --- | --- | --- |"
+        );
+        assert_eq!(lines[2], stats.row("range check"));
+        assert!(lines[2].starts_with("| range check | "));
+    }
+
+    #[test]
+    fn test_circuit_budget() {
+        let stats = CircuitStats {
+            advice_columns: 4,
+            lookups: 2,
+            degree: 5,
+            ..Default::default()
+        };
+        let budget = CircuitBudget {
+            advice_columns: 4,
+            fixed_columns: 0,
+            lookups: 1,
+            degree: 4,
+        };
+        assert_eq!(
+            stats.over_budget(&budget),
+            ["lookups: 2 > 1", "degree: 5 > 4"]
+        );
+        stats.assert_within(
+            "test",
+            &CircuitBudget {
+                lookups: 2,
+                degree: 5,
+                ..budget
+            },
+        );
+    }
+}
@@ -0,0 +1,387 @@
+//! Fixed lookup tables used by the gadgets in this crate.
+//!
+//! Every table contains an all-zero row, so that lookups whose selector is
+//! disabled (and hence query all-zero inputs) are trivially satisfied.
+//!
+//! A circuit composed of several sub-circuits requests its tables from a
+//! single [`FixedTables`] registry instead of constructing them, so that each
+//! table is constructed and loaded once however many sub-circuits look it
+//! up. Of the hash circuits, only the Keccak-256, modexp and HASH160 circuits
+//! have a fixed table, the byte table, which their word and RLC gadgets
+//! share; the other circuits request none.
+
+use std::collections::BTreeMap;
+
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{Layouter, Value},
+    plonk::{ConstraintSystem, Error, TableColumn},
+};
+use tracing::info_span;
+
+use crate::spread::spread;
+
+/// Table holding every value in `[0, 2^BITS)`.
+#[derive(Clone, Copy, Debug)]
+pub struct RangeTable<const BITS: usize> {
+    pub value: TableColumn,
+}
+
+impl<const BITS: usize> RangeTable<BITS> {
+    pub fn construct<F: FieldExt>(meta: &mut ConstraintSystem<F>) -> Self {
+        Self {
+            value: meta.lookup_table_column(),
+        }
+    }
+
+    pub fn load<F: FieldExt>(&self, layouter: &mut impl Layouter<F>) -> Result<(), Error> {
+        load_range(layouter, self.value, BITS)
+    }
+}
+
+fn load_range<F: FieldExt>(
+    layouter: &mut impl Layouter<F>,
+    column: TableColumn,
+    bits: usize,
+) -> Result<(), Error> {
+    let _span = info_span!("load table", table = "range", bits, rows = 1usize << bits).entered();
+    layouter.assign_table(
+        || format!("{}-bit range table", bits),
+        |mut table| {
+            for value in 0..(1u64 << bits) {
+                table.assign_cell(
+                    || "value",
+                    column,
+                    value as usize,
+                    || Value::known(F::from(value)),
+                )?;
+            }
+            Ok(())
+        },
+    )
+}
+
+/// Table mapping every `BITS`-bit value to its spread form, i.e. the value
+/// with a zero bit interleaved between each of its bits.
+#[derive(Clone, Copy, Debug)]
+pub struct SpreadTable<const BITS: usize> {
+    pub dense: TableColumn,
+    pub spread: TableColumn,
+}
+
+impl<const BITS: usize> SpreadTable<BITS> {
+    pub fn construct<F: FieldExt>(meta: &mut ConstraintSystem<F>) -> Self {
+        assert!(
+            BITS <= 32,
+            "spread values of more than 32 bits do not fit in a u64"
+        );
+        Self {
+            dense: meta.lookup_table_column(),
+            spread: meta.lookup_table_column(),
+        }
+    }
+
+    pub fn load<F: FieldExt>(&self, layouter: &mut impl Layouter<F>) -> Result<(), Error> {
+        load_spread(layouter, [self.dense, self.spread], BITS)
+    }
+}
+
+fn load_spread<F: FieldExt>(
+    layouter: &mut impl Layouter<F>,
+    [dense_column, spread_column]: [TableColumn; 2],
+    bits: usize,
+) -> Result<(), Error> {
+    let _span = info_span!("load table", table = "spread", bits, rows = 1usize << bits).entered();
+    layouter.assign_table(
+        || format!("{}-bit spread table", bits),
+        |mut table| {
+            for dense in 0..(1u64 << bits) {
+                let offset = dense as usize;
+                table.assign_cell(
+                    || "dense",
+                    dense_column,
+                    offset,
+                    || Value::known(F::from(dense)),
+                )?;
+                table.assign_cell(
+                    || "spread",
+                    spread_column,
+                    offset,
+                    || Value::known(F::from(spread(dense))),
+                )?;
+            }
+            Ok(())
+        },
+    )
+}
+
+/// Binary bitwise operations supported by the [`BitwiseTable`]. The
+/// discriminant is the tag stored in the table's `op` column.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BitwiseOp {
+    Xor = 1,
+    And = 2,
+    Or = 3,
+}
+
+impl BitwiseOp {
+    pub const ALL: [Self; 3] = [Self::Xor, Self::And, Self::Or];
+
+    pub fn tag(&self) -> u64 {
+        *self as u64
+    }
+
+    pub fn apply(&self, lhs: u64, rhs: u64) -> u64 {
+        match self {
+            Self::Xor => lhs ^ rhs,
+            Self::And => lhs & rhs,
+            Self::Or => lhs | rhs,
+        }
+    }
+}
+
+/// Table of `(op, lhs, rhs, op(lhs, rhs))` for every pair of `BITS`-bit
+/// operands and every [`BitwiseOp`]. The table has `3 * 2^(2 * BITS) + 1`
+/// rows, so 8-bit operands require `k >= 18`.
+#[derive(Clone, Copy, Debug)]
+pub struct BitwiseTable<const BITS: usize> {
+    pub op: TableColumn,
+    pub lhs: TableColumn,
+    pub rhs: TableColumn,
+    pub out: TableColumn,
+}
+
+impl<const BITS: usize> BitwiseTable<BITS> {
+    pub fn construct<F: FieldExt>(meta: &mut ConstraintSystem<F>) -> Self {
+        Self {
+            op: meta.lookup_table_column(),
+            lhs: meta.lookup_table_column(),
+            rhs: meta.lookup_table_column(),
+            out: meta.lookup_table_column(),
+        }
+    }
+
+    pub fn load<F: FieldExt>(&self, layouter: &mut impl Layouter<F>) -> Result<(), Error> {
+        load_bitwise(layouter, [self.op, self.lhs, self.rhs, self.out], BITS)
+    }
+}
+
+fn load_bitwise<F: FieldExt>(
+    layouter: &mut impl Layouter<F>,
+    [op_column, lhs_column, rhs_column, out_column]: [TableColumn; 4],
+    bits: usize,
+) -> Result<(), Error> {
+    // an all-zero row, and a row per op and pair of operands.
+    let rows = 1 + (BitwiseOp::ALL.len() << (2 * bits));
+    let _span = info_span!("load table", table = "bitwise", bits, rows).entered();
+    layouter.assign_table(
+        || format!("{}-bit bitwise table", bits),
+        |mut table| {
+            let mut offset = 0;
+            let mut assign_row = |op: u64, lhs: u64, rhs: u64, out: u64| {
+                for (annotation, column, value) in [
+                    ("op", op_column, op),
+                    ("lhs", lhs_column, lhs),
+                    ("rhs", rhs_column, rhs),
+                    ("out", out_column, out),
+                ] {
+                    table.assign_cell(
+                        || annotation,
+                        column,
+                        offset,
+                        || Value::known(F::from(value)),
+                    )?;
+                }
+                offset += 1;
+                Ok::<_, Error>(())
+            };
+
+            // all-zero row for disabled lookups.
+            assign_row(0, 0, 0, 0)?;
+            for op in BitwiseOp::ALL {
+                for lhs in 0..(1u64 << bits) {
+                    for rhs in 0..(1u64 << bits) {
+                        assign_row(op.tag(), lhs, rhs, op.apply(lhs, rhs))?;
+                    }
+                }
+            }
+            Ok(())
+        },
+    )
+}
+
+/// Key of a table in [`FixedTables`]: its kind and its bit width.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum TableKey {
+    Range(usize),
+    Spread(usize),
+    Bitwise(usize),
+}
+
+/// Registry of the fixed tables of a circuit, which constructs every table
+/// the first time it is requested and returns the same columns for every
+/// later request of the same key.
+///
+/// The configs requesting their tables from a registry do not load them:
+/// the owner of the registry loads all the tables once with
+/// [`FixedTables::load`].
+#[derive(Clone, Debug, Default)]
+pub struct FixedTables {
+    columns: BTreeMap<TableKey, Vec<TableColumn>>,
+}
+
+impl FixedTables {
+    fn columns<F: FieldExt>(
+        &mut self,
+        meta: &mut ConstraintSystem<F>,
+        key: TableKey,
+        num_columns: usize,
+    ) -> &[TableColumn] {
+        self.columns.entry(key).or_insert_with(|| {
+            (0..num_columns)
+                .map(|_| meta.lookup_table_column())
+                .collect()
+        })
+    }
+
+    pub fn range<F: FieldExt, const BITS: usize>(
+        &mut self,
+        meta: &mut ConstraintSystem<F>,
+    ) -> RangeTable<BITS> {
+        let columns = self.columns(meta, TableKey::Range(BITS), 1);
+        RangeTable { value: columns[0] }
+    }
+
+    pub fn spread<F: FieldExt, const BITS: usize>(
+        &mut self,
+        meta: &mut ConstraintSystem<F>,
+    ) -> SpreadTable<BITS> {
+        assert!(
+            BITS <= 32,
+            "spread values of more than 32 bits do not fit in a u64"
+        );
+        let columns = self.columns(meta, TableKey::Spread(BITS), 2);
+        SpreadTable {
+            dense: columns[0],
+            spread: columns[1],
+        }
+    }
+
+    pub fn bitwise<F: FieldExt, const BITS: usize>(
+        &mut self,
+        meta: &mut ConstraintSystem<F>,
+    ) -> BitwiseTable<BITS> {
+        let columns = self.columns(meta, TableKey::Bitwise(BITS), 4);
+        BitwiseTable {
+            op: columns[0],
+            lhs: columns[1],
+            rhs: columns[2],
+            out: columns[3],
+        }
+    }
+
+    /// Keys of the tables constructed so far.
+    pub fn keys(&self) -> Vec<TableKey> {
+        self.columns.keys().copied().collect()
+    }
+
+    /// Number of fixed columns of the tables constructed so far.
+    pub fn num_columns(&self) -> usize {
+        self.columns.values().map(Vec::len).sum()
+    }
+
+    /// Loads every table constructed so far, once.
+    pub fn load<F: FieldExt>(&self, layouter: &mut impl Layouter<F>) -> Result<(), Error> {
+        for (key, columns) in self.columns.iter() {
+            match *key {
+                TableKey::Range(bits) => load_range(layouter, columns[0], bits)?,
+                TableKey::Spread(bits) => load_spread(layouter, [columns[0], columns[1]], bits)?,
+                TableKey::Bitwise(bits) => load_bitwise(
+                    layouter,
+                    [columns[0], columns[1], columns[2], columns[3]],
+                    bits,
+                )?,
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{WordChip, WordConfig};
+    use halo2_proofs::{
+        circuit::SimpleFloorPlanner,
+        dev::MockProver,
+        halo2curves::{bn256::Fr, pasta::pallas},
+        plonk::Circuit,
+    };
+    use std::marker::PhantomData;
+
+    /// Two word gadgets whose byte table is shared through the registry.
+    #[derive(Default)]
+    struct TestCircuit<F> {
+        _marker: PhantomData<F>,
+    }
+
+    impl<F: FieldExt> Circuit<F> for TestCircuit<F> {
+        type Config = (FixedTables, [WordConfig; 2]);
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            let mut tables = FixedTables::default();
+            let words = [(); 2].map(|_| {
+                let byte_table = tables.range(meta);
+                WordConfig::configure(meta, byte_table)
+            });
+            (tables, words)
+        }
+
+        fn synthesize(
+            &self,
+            (tables, words): Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            tables.load(&mut layouter)?;
+            layouter.assign_region(
+                || "words",
+                |mut region| {
+                    for (offset, word) in words.iter().enumerate() {
+                        WordChip::construct(*word).pack(&mut region, offset, &[0xab; 32])?;
+                    }
+                    Ok(())
+                },
+            )
+        }
+    }
+
+    fn test_fixed_tables_over<F: FieldExt>() {
+        let mut meta = ConstraintSystem::<F>::default();
+        let mut tables = FixedTables::default();
+        let lhs: RangeTable<8> = tables.range(&mut meta);
+        let rhs: RangeTable<8> = tables.range(&mut meta);
+        assert_eq!(lhs.value, rhs.value);
+        let _: BitwiseTable<4> = tables.bitwise(&mut meta);
+        let _: RangeTable<4> = tables.range(&mut meta);
+        assert_eq!(
+            tables.keys(),
+            vec![TableKey::Range(4), TableKey::Range(8), TableKey::Bitwise(4)]
+        );
+        assert_eq!(tables.num_columns(), 6);
+
+        let circuit = TestCircuit::<F>::default();
+        let prover = MockProver::run(9, &circuit, vec![]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+    }
+
+    #[test]
+    fn test_fixed_tables() {
+        test_fixed_tables_over::<Fr>();
+        test_fixed_tables_over::<pallas::Base>();
+    }
+}
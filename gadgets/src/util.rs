@@ -0,0 +1,100 @@
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{AssignedCell, Value},
+    plonk::ConstraintSystem,
+};
+
+/// A cell assigned with a field element, as returned by the chips.
+pub type AssignedValue<F> = AssignedCell<F, F>;
+
+/// Returns `2^exp` as a field element, for exponents that may exceed 64 bits.
+pub fn pow_of_two<F: FieldExt>(exp: usize) -> F {
+    F::from(2).pow_vartime([exp as u64])
+}
+
+/// Smallest `k` whose `2^k` rows fit `rows` rows of the layout of the
+/// circuit configured by `configure`, and the rows its constraint system
+/// reserves at the end for the blinding factors, see
+/// [`ConstraintSystem::minimum_rows`].
+pub fn k_for_rows<F: FieldExt, C>(
+    configure: impl FnOnce(&mut ConstraintSystem<F>) -> C,
+    rows: usize,
+) -> u32 {
+    let mut meta = ConstraintSystem::default();
+    configure(&mut meta);
+    (rows + meta.minimum_rows())
+        .next_power_of_two()
+        .trailing_zeros()
+}
+
+/// Mask with the lowest `bits` bits set.
+pub fn mask(bits: usize) -> u64 {
+    if bits >= 64 {
+        u64::MAX
+    } else {
+        (1u64 << bits) - 1
+    }
+}
+
+/// Computes the random linear combination of `bytes` with `randomness`, where
+/// the first byte is multiplied by the highest power.
+pub fn rlc<F: FieldExt>(bytes: &[u8], randomness: Value<F>) -> Value<F> {
+    bytes.iter().fold(Value::known(F::zero()), |acc, byte| {
+        acc * randomness + Value::known(F::from(*byte as u64))
+    })
+}
+
+/// The 64-bit FNV-1a hash, whose value, unlike that of the hashers of `std`,
+/// is the same on every platform and Rust version, for the hashes written
+/// to disk or compared across machines.
+#[derive(Clone, Copy, Debug)]
+pub struct Fnv1a(u64);
+
+impl Default for Fnv1a {
+    fn default() -> Self {
+        Self(0xcbf2_9ce4_8422_2325)
+    }
+}
+
+impl Fnv1a {
+    pub fn update(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 = (self.0 ^ *byte as u64).wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+
+    pub fn finish(&self) -> u64 {
+        self.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{tables::RangeTable, RangeCheckConfig};
+    use halo2_proofs::{halo2curves::bn256::Fr, poly::Rotation};
+
+    #[test]
+    fn test_k_for_rows() {
+        let range_check = |meta: &mut ConstraintSystem<Fr>| {
+            let table = RangeTable::<8>::construct(meta);
+            RangeCheckConfig::<8>::configure(meta, table)
+        };
+        let mut meta = ConstraintSystem::<Fr>::default();
+        range_check(&mut meta);
+        let blinding_rows = meta.minimum_rows();
+        assert_eq!(k_for_rows(range_check, 256 - blinding_rows), 8);
+        assert_eq!(k_for_rows(range_check, 256 - blinding_rows + 1), 9);
+
+        // more queries of a column take more blinding rows.
+        let rotations = |meta: &mut ConstraintSystem<Fr>| {
+            let column = meta.advice_column();
+            meta.create_gate("rotations", |meta| {
+                (0..8)
+                    .map(|rotation| meta.query_advice(column, Rotation(rotation)))
+                    .collect::<Vec<_>>()
+            });
+        };
+        assert_eq!(k_for_rows(rotations, 256 - blinding_rows), 9);
+    }
+}
@@ -0,0 +1,96 @@
+//! Validation of the witness of a circuit before it is assigned, so that a
+//! witness built outside of the workspace, e.g. by an integrator, fails fast
+//! with the call at fault and the reason, rather than with constraints
+//! unsatisfied in the `MockProver` or a proof that does not verify.
+//!
+//! Every chip checks that its witness fits its capacity as it is loaded, see
+//! [`HashCircuitExt::check_witness`](crate::HashCircuitExt::check_witness),
+//! and the dev circuits of the precompiles, which hold the digests of their
+//! calls, check them against the reference implementation with
+//! [`check_digests`] before synthesizing the chip.
+
+use tracing::error;
+
+use crate::Error;
+
+/// The [`Error::Witness`] of the call `call` of `precompile`, logged as the
+/// `plonk::Error` it converts to is not telling.
+pub fn invalid_call(precompile: &'static str, call: usize, reason: String) -> Error {
+    error!(precompile, call, %reason, "the witness of the call is invalid");
+    Error::Witness {
+        precompile,
+        call,
+        reason,
+    }
+}
+
+/// Checks that `digests` are those of `inputs` by `hash`, the reference
+/// implementation of `precompile`, one per call, or the [`Error::Witness`]
+/// of the first call whose digest is missing or wrong. No digests are
+/// checked of a circuit built without them, as they are not part of the
+/// witness of its chip.
+pub fn check_digests<I, D: AsRef<[u8]>, H: AsRef<[u8]>>(
+    precompile: &'static str,
+    inputs: &[I],
+    digests: &[D],
+    hash: impl Fn(&I) -> H,
+) -> Result<(), Error> {
+    if digests.is_empty() {
+        return Ok(());
+    }
+    if digests.len() != inputs.len() {
+        return Err(invalid_call(
+            precompile,
+            inputs.len().min(digests.len()),
+            format!(
+                "the witness has {} digests for {} inputs",
+                digests.len(),
+                inputs.len()
+            ),
+        ));
+    }
+    for (call, (input, digest)) in inputs.iter().zip(digests).enumerate() {
+        let expected = hash(input);
+        if digest.as_ref() != expected.as_ref() {
+            return Err(invalid_call(
+                precompile,
+                call,
+                format!(
+                    "its digest is {} rather than {}, that of its input",
+                    hex(digest.as_ref()),
+                    hex(expected.as_ref())
+                ),
+            ));
+        }
+    }
+    Ok(())
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_digests() {
+        let hash = |input: &Vec<u8>| [input.len() as u8; 2];
+        let inputs = vec![vec![], vec![7; 3]];
+        assert!(check_digests("test", &inputs, &[[0, 0], [3, 3]], hash).is_ok());
+        // a circuit built without its digests.
+        assert!(check_digests::<_, [u8; 2], _>("test", &inputs, &[], hash).is_ok());
+
+        let err = check_digests("test", &inputs, &[[0, 0], [3, 4]], hash).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "the witness of the test call 1 is invalid: its digest is 0304 rather than 0303, \
+             that of its input"
+        );
+        assert!(matches!(
+            check_digests("test", &inputs, &[[0, 0]], hash),
+            Err(Error::Witness { call: 1, .. })
+        ));
+    }
+}
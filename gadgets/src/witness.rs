@@ -0,0 +1,121 @@
+//! Canonical hash of the witness of a circuit, to compare its generation
+//! across machines and versions, e.g. when a proof fails on one machine
+//! only, or to check that a refactoring of a chip assigns the same cells.
+//!
+//! The witness is synthesized by the `MockProver`, whose challenges are
+//! fixed, so that the columns of the later phases are reproducible too. Only
+//! the assigned advice cells are hashed, column by column: the rows after
+//! the last assigned cell of a column, and the blinding rows, are the
+//! padding of the degree, so that the hash of a witness does not depend on
+//! the degree it is synthesized at.
+
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    dev::{CellValue, MockProver},
+    plonk::{Circuit, ConstraintSystem, Error},
+};
+
+use crate::util::Fnv1a;
+
+/// Hash of the advice cells `circuit` assigns at degree `k`, see the module
+/// docs, or the error of its synthesis.
+pub fn witness_hash<F: FieldExt, C: Circuit<F>>(
+    k: u32,
+    circuit: &C,
+    instances: Vec<Vec<F>>,
+) -> Result<u64, Error> {
+    let prover = MockProver::run(k, circuit, instances)?;
+    let usable_rows = prover.usable_rows().end;
+
+    // The advice columns of the circuit, in their order, as `MockProver`
+    // looks the values of a column up by its index only.
+    let mut meta = ConstraintSystem::<F>::default();
+    let columns = (0..prover.cs().num_advice_columns()).map(|_| meta.advice_column());
+
+    let mut hash = Fnv1a::default();
+    for (index, column) in columns.enumerate() {
+        let values = &prover.advice_values(column)[..usable_rows];
+        let assigned = values
+            .iter()
+            .rposition(|value| *value != CellValue::Unassigned)
+            .map_or(0, |last| last + 1);
+        hash.update(&(index as u64).to_le_bytes());
+        hash.update(&(assigned as u64).to_le_bytes());
+        for value in &values[..assigned] {
+            match value {
+                CellValue::Unassigned => hash.update(&[0]),
+                CellValue::Assigned(value) => {
+                    hash.update(&[1]);
+                    hash.update(value.to_repr().as_ref());
+                }
+                CellValue::Poison(_) => hash.update(&[2]),
+            }
+        }
+    }
+    Ok(hash.finish())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use halo2_proofs::{
+        circuit::{Layouter, SimpleFloorPlanner, Value},
+        halo2curves::bn256::Fr,
+        plonk::{Advice, Column},
+    };
+
+    /// Assigns `values` to the rows of a column.
+    #[derive(Default)]
+    struct TestCircuit {
+        values: Vec<u64>,
+    }
+
+    impl Circuit<Fr> for TestCircuit {
+        type Config = Column<Advice>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+            meta.advice_column()
+        }
+
+        fn synthesize(
+            &self,
+            column: Self::Config,
+            mut layouter: impl Layouter<Fr>,
+        ) -> Result<(), Error> {
+            layouter.assign_region(
+                || "values",
+                |mut region| {
+                    for (offset, value) in self.values.iter().enumerate() {
+                        region.assign_advice(
+                            || "value",
+                            column,
+                            offset,
+                            || Value::known(Fr::from(*value)),
+                        )?;
+                    }
+                    Ok(())
+                },
+            )
+        }
+    }
+
+    #[test]
+    fn test_witness_hash() {
+        let hash = |k, values: &[u64]| {
+            let circuit = TestCircuit {
+                values: values.to_vec(),
+            };
+            witness_hash(k, &circuit, vec![]).unwrap()
+        };
+        // the padding of the degree is not hashed.
+        assert_eq!(hash(4, &[1, 2, 3]), hash(8, &[1, 2, 3]));
+        assert_ne!(hash(4, &[1, 2, 3]), hash(4, &[1, 2, 4]));
+        // an assigned zero is not an unassigned cell.
+        assert_ne!(hash(4, &[1, 2, 3]), hash(4, &[1, 2, 3, 0]));
+    }
+}
@@ -0,0 +1,262 @@
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{Region, Value},
+    plonk::{Advice, Column, ConstraintSystem, Error, Expression, VirtualCells},
+    poly::Rotation,
+};
+
+use crate::{
+    bytes::{BytesChip, BytesConfig, Endianness},
+    tables::RangeTable,
+    util::AssignedValue,
+};
+
+/// Number of bytes of a word.
+pub const WORD_BYTES: usize = 32;
+
+/// Cells of an assigned word.
+pub type AssignedWord<F> = WordLoHi<AssignedValue<F>>;
+
+/// A 256-bit value as two 128-bit halves, each fitting in a field element,
+/// as in the tables of the zkevm-circuits. `T` is e.g. a field element, a
+/// column, an expression or an assigned cell.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct WordLoHi<T> {
+    lo: T,
+    hi: T,
+}
+
+impl<T> WordLoHi<T> {
+    pub fn new([lo, hi]: [T; 2]) -> Self {
+        Self { lo, hi }
+    }
+
+    pub fn lo(&self) -> &T {
+        &self.lo
+    }
+
+    pub fn hi(&self) -> &T {
+        &self.hi
+    }
+
+    pub fn to_lo_hi(self) -> [T; 2] {
+        [self.lo, self.hi]
+    }
+
+    pub fn map<U>(self, mut f: impl FnMut(T) -> U) -> WordLoHi<U> {
+        WordLoHi::new([f(self.lo), f(self.hi)])
+    }
+}
+
+impl<F: FieldExt> WordLoHi<F> {
+    /// The word of 32 big-endian bytes, e.g. a digest or an EVM word.
+    pub fn from_bytes_be(bytes: &[u8; WORD_BYTES]) -> Self {
+        let half = |bytes: &[u8]| {
+            F::from_u128(u128::from_be_bytes(
+                bytes.try_into().expect("halves are 16 bytes"),
+            ))
+        };
+        Self::new([half(&bytes[16..]), half(&bytes[..16])])
+    }
+
+    /// The 32 big-endian bytes of a word whose halves fit in 128 bits.
+    pub fn to_bytes_be(&self) -> [u8; WORD_BYTES] {
+        let mut bytes = [0u8; WORD_BYTES];
+        bytes[..16].copy_from_slice(&self.hi.get_lower_128().to_be_bytes());
+        bytes[16..].copy_from_slice(&self.lo.get_lower_128().to_be_bytes());
+        bytes
+    }
+}
+
+impl WordLoHi<Column<Advice>> {
+    /// Two advice columns holding the halves of words.
+    pub fn construct<F: FieldExt>(meta: &mut ConstraintSystem<F>) -> Self {
+        Self::new([meta.advice_column(), meta.advice_column()])
+    }
+
+    pub fn query<F: FieldExt>(
+        &self,
+        meta: &mut VirtualCells<'_, F>,
+        at: Rotation,
+    ) -> WordLoHi<Expression<F>> {
+        self.map(|column| meta.query_advice(column, at))
+    }
+}
+
+impl<F: FieldExt> WordLoHi<Value<F>> {
+    /// Assigns the halves of the word in `columns` at `offset`.
+    pub fn assign_advice(
+        &self,
+        region: &mut Region<'_, F>,
+        annotation: &str,
+        columns: WordLoHi<Column<Advice>>,
+        offset: usize,
+    ) -> Result<AssignedWord<F>, Error> {
+        Ok(WordLoHi::new([
+            region.assign_advice(
+                || format!("{} lo", annotation),
+                columns.lo,
+                offset,
+                || self.lo,
+            )?,
+            region.assign_advice(
+                || format!("{} hi", annotation),
+                columns.hi,
+                offset,
+                || self.hi,
+            )?,
+        ]))
+    }
+}
+
+/// Packs 32 big-endian bytes into a [`WordLoHi`] within a single row, i.e.
+/// decomposes each half of the word into 16 big-endian bytes, the first 16
+/// bytes being those of `hi`. The same layout unpacks a word into its bytes.
+#[derive(Clone, Copy, Debug)]
+pub struct WordConfig {
+    lo: BytesConfig<16>,
+    hi: BytesConfig<16>,
+    pub word: WordLoHi<Column<Advice>>,
+}
+
+impl WordConfig {
+    pub fn configure<F: FieldExt>(meta: &mut ConstraintSystem<F>, table: RangeTable<8>) -> Self {
+        let lo = BytesConfig::configure(meta, table, Endianness::Big);
+        let hi = BytesConfig::configure(meta, table, Endianness::Big);
+        Self {
+            lo,
+            hi,
+            word: WordLoHi::new([lo.value, hi.value]),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct WordChip {
+    config: WordConfig,
+}
+
+impl WordChip {
+    pub fn construct(config: WordConfig) -> Self {
+        Self { config }
+    }
+
+    /// Assigns `bytes` and the word they pack into at `offset`, returning the
+    /// byte cells and the word cells.
+    pub fn pack<F: FieldExt>(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        bytes: &[u8; WORD_BYTES],
+    ) -> Result<(Vec<AssignedValue<F>>, AssignedWord<F>), Error> {
+        let half = |bytes: &[u8]| -> [u8; 16] { bytes.try_into().expect("halves are 16 bytes") };
+        let (hi, mut hi_bytes) =
+            BytesChip::construct(self.config.hi).assign(region, offset, &half(&bytes[..16]))?;
+        let (lo, lo_bytes) =
+            BytesChip::construct(self.config.lo).assign(region, offset, &half(&bytes[16..]))?;
+        hi_bytes.extend(lo_bytes);
+        Ok((hi_bytes, WordLoHi::new([lo, hi])))
+    }
+
+    /// Copies the cells of an already assigned word at `offset` and assigns
+    /// its bytes, returning the byte cells in big-endian order.
+    pub fn unpack<F: FieldExt>(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        word: &AssignedWord<F>,
+    ) -> Result<Vec<AssignedValue<F>>, Error> {
+        let mut bytes = BytesChip::construct(self.config.hi).copy(region, offset, word.hi())?;
+        bytes.extend(BytesChip::construct(self.config.lo).copy(region, offset, word.lo())?);
+        Ok(bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use halo2_proofs::{
+        circuit::{Layouter, SimpleFloorPlanner},
+        dev::MockProver,
+        halo2curves::{bn256::Fr, pasta::pallas},
+        plonk::Circuit,
+    };
+    use std::marker::PhantomData;
+
+    /// Packs every word, then unpacks the packed word on the next row.
+    #[derive(Default)]
+    struct TestCircuit<F> {
+        words: Vec<[u8; WORD_BYTES]>,
+        _marker: PhantomData<F>,
+    }
+
+    impl<F: FieldExt> Circuit<F> for TestCircuit<F> {
+        type Config = (RangeTable<8>, WordConfig);
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            let table = RangeTable::construct(meta);
+            (table, WordConfig::configure(meta, table))
+        }
+
+        fn synthesize(
+            &self,
+            (table, config): Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            table.load(&mut layouter)?;
+            let chip = WordChip::construct(config);
+            layouter.assign_region(
+                || "word",
+                |mut region| {
+                    for (i, bytes) in self.words.iter().enumerate() {
+                        let (byte_cells, word) = chip.pack(&mut region, 2 * i, bytes)?;
+                        let unpacked = chip.unpack(&mut region, 2 * i + 1, &word)?;
+                        for (byte, unpacked) in byte_cells.iter().zip(unpacked) {
+                            region.constrain_equal(byte.cell(), unpacked.cell())?;
+                        }
+                    }
+                    Ok(())
+                },
+            )
+        }
+    }
+
+    fn test_word_lo_hi_over<F: FieldExt>() {
+        let mut bytes = [0u8; WORD_BYTES];
+        bytes[15] = 1;
+        bytes[31] = 2;
+        let word = WordLoHi::<F>::from_bytes_be(&bytes);
+        assert_eq!(word, WordLoHi::new([F::from(2), F::from(1)]));
+        assert_eq!(word.to_bytes_be(), bytes);
+    }
+
+    fn test_word_circuit_over<F: FieldExt>() {
+        let circuit = TestCircuit::<F> {
+            words: vec![
+                [0; WORD_BYTES],
+                [0xff; WORD_BYTES],
+                core::array::from_fn(|i| i as u8),
+            ],
+            _marker: PhantomData,
+        };
+        let prover = MockProver::run(9, &circuit, vec![]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+    }
+
+    #[test]
+    fn test_word_lo_hi() {
+        test_word_lo_hi_over::<Fr>();
+        test_word_lo_hi_over::<pallas::Base>();
+    }
+
+    #[test]
+    fn test_word_circuit() {
+        test_word_circuit_over::<Fr>();
+        test_word_circuit_over::<pallas::Base>();
+    }
+}
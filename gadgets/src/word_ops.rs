@@ -0,0 +1,758 @@
+use std::marker::PhantomData;
+
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{Region, Value},
+    plonk::{Advice, Column, ConstraintSystem, Error, Expression, Fixed, Selector, VirtualCells},
+    poly::Rotation,
+};
+
+use crate::{
+    bytes::Endianness,
+    context::Context,
+    rotation::BitShift,
+    util::{mask, pow_of_two, AssignedValue},
+};
+
+/// A boolean function of up to three bits, given by its truth table: bit
+/// `a + 2b + 4c` of `table` is `f(a, b, c)`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BitFn {
+    arity: usize,
+    table: u8,
+}
+
+impl BitFn {
+    /// `a ^ b`.
+    pub const XOR: Self = Self::new(2, 0b0110);
+    /// `a ^ b ^ c`.
+    pub const XOR3: Self = Self::new(3, 0b1001_0110);
+    /// `a & b`.
+    pub const AND: Self = Self::new(2, 0b1000);
+    /// `(a & b) | (!a & c)`, the choice of SHA-1 and SHA-2.
+    pub const CH: Self = Self::new(3, 0b1101_1000);
+    /// `(a & b) | (a & c) | (b & c)`, the majority of SHA-1 and SHA-2.
+    pub const MAJ: Self = Self::new(3, 0b1110_1000);
+
+    pub const fn new(arity: usize, table: u8) -> Self {
+        assert!(
+            arity >= 1 && arity <= 3,
+            "a function has one to three operands"
+        );
+        assert!(
+            arity == 3 || table >> (1 << arity) == 0,
+            "the table has a bit per combination of the operands"
+        );
+        Self { arity, table }
+    }
+
+    /// The function of `arity` operands evaluating to `f(a, b, c)`, with the
+    /// operands past `arity` false.
+    pub fn from_fn(arity: usize, f: impl Fn(bool, bool, bool) -> bool) -> Self {
+        let table = (0..1 << arity).fold(0, |table, i| {
+            table | (f(i & 1 == 1, i & 2 == 2, i & 4 == 4) as u8) << i
+        });
+        Self::new(arity, table)
+    }
+
+    pub fn arity(&self) -> usize {
+        self.arity
+    }
+
+    fn eval(&self, index: usize) -> bool {
+        (self.table >> index) & 1 == 1
+    }
+
+    /// Native evaluation of the function bit by bit over words of
+    /// `word_bits` bits.
+    pub fn apply(&self, words: &[u64], word_bits: usize) -> u64 {
+        assert_eq!(words.len(), self.arity, "one word per operand");
+        (0..word_bits)
+            .filter(|j| {
+                let index = words.iter().enumerate().fold(0, |index, (i, word)| {
+                    index | (((word >> j) & 1) as usize) << i
+                });
+                self.eval(index)
+            })
+            .fold(0, |acc, j| acc | 1 << j)
+            & mask(word_bits)
+    }
+
+    /// The multilinear polynomial equal to the function over boolean
+    /// `operands`, its coefficients being the Möbius transform of the truth
+    /// table.
+    fn expression<F: FieldExt>(&self, operands: &[Expression<F>]) -> Expression<F> {
+        let mut sum = Expression::Constant(F::zero());
+        for subset in 0..1usize << self.arity {
+            let coeff: i64 = (0..1usize << self.arity)
+                .filter(|inner| inner & !subset == 0)
+                .map(|inner| {
+                    let sign = if (subset ^ inner).count_ones() % 2 == 0 {
+                        1
+                    } else {
+                        -1
+                    };
+                    sign * self.eval(inner) as i64
+                })
+                .sum();
+            if coeff == 0 {
+                continue;
+            }
+            let coeff = if coeff > 0 {
+                F::from(coeff as u64)
+            } else {
+                -F::from(coeff.unsigned_abs())
+            };
+            let monomial = (0..self.arity)
+                .filter(|i| subset >> i & 1 == 1)
+                .fold(Expression::Constant(coeff), |acc, i| {
+                    acc * operands[i].clone()
+                });
+            sum = sum + monomial;
+        }
+        sum
+    }
+}
+
+/// A word of `WORD_BITS` bits in a cell, along with its value, so that the
+/// chips computing on it evaluate it natively as they assign.
+#[derive(Clone, Debug)]
+pub struct WordCell<F: FieldExt> {
+    pub cell: AssignedValue<F>,
+    pub value: u64,
+}
+
+/// Computes on words of `WORD_BITS` bits, an operation per row, by
+/// decomposing the operands of the row into bits in the same row. Every
+/// configured rotation, shift and boolean function is a gate of its own
+/// over the bits, so that the hash circuits built on the chip express their
+/// rounds as a sequence of operations rather than gates of their own.
+///
+/// | v0  | v1  | v2  | v3             | v4      | bits of v0, v1, v2 | bytes    | carry     | constant |
+/// |-----|-----|-----|----------------|---------|--------------------|----------|-----------|----------|
+/// | a   |     |     | shifts(a)      |         | a                  |          |           |          |
+/// | a   | b   | c   | f(a, b, c)     |         | a, b, c            |          |           |          |
+/// | sum | s_1 | s_2 | s_3            | s_4     | sum                |          | c_0..c_2  | k        |
+/// | a   |     |     |                |         | a                  | of a     |           |          |
+/// | s   | a   | b   | s ? a : b      |         |                    |          |           |          |
+///
+/// where `sum + carry * 2^WORD_BITS = s_1 + s_2 + s_3 + s_4 + k`, so that an
+/// addition modulo `2^WORD_BITS` takes up to four words and a constant, and
+/// the flag `s` of a selection is boolean.
+#[derive(Clone, Debug)]
+pub struct WordOpsConfig<const WORD_BITS: usize> {
+    values: [Column<Advice>; 5],
+    bits: [Vec<Column<Advice>>; 3],
+    bytes: Vec<Column<Advice>>,
+    carry: [Column<Advice>; 3],
+    constant: Column<Fixed>,
+    q_bits: [Selector; 3],
+    q_add: Selector,
+    q_bytes: Selector,
+    q_select: Selector,
+    q_shifts: Vec<(Vec<BitShift>, Selector)>,
+    q_fns: Vec<(BitFn, Selector)>,
+}
+
+impl<const WORD_BITS: usize> WordOpsConfig<WORD_BITS> {
+    /// Configures the chip with a gate for each set of `shifts`, whose
+    /// outputs are XORed together, and for each of the functions `fns`.
+    pub fn configure<F: FieldExt>(
+        meta: &mut ConstraintSystem<F>,
+        shifts: &[Vec<BitShift>],
+        fns: &[BitFn],
+    ) -> Self {
+        assert!(
+            WORD_BITS.is_multiple_of(8) && WORD_BITS <= 64,
+            "words are whole bytes of up to 64 bits"
+        );
+        let values = [(); 5].map(|_| meta.advice_column());
+        for value in values {
+            meta.enable_equality(value);
+        }
+        let bits = [(); 3].map(|_| (0..WORD_BITS).map(|_| meta.advice_column()).collect());
+        let bytes: Vec<_> = (0..WORD_BITS / 8).map(|_| meta.advice_column()).collect();
+        for byte in bytes.iter() {
+            meta.enable_equality(*byte);
+        }
+        let carry = [(); 3].map(|_| meta.advice_column());
+        let constant = meta.fixed_column();
+        let constants = meta.fixed_column();
+        meta.enable_constant(constants);
+
+        let q_bits = [(); 3].map(|_| meta.selector());
+        let q_add = meta.selector();
+        let q_bytes = meta.selector();
+        let q_select = meta.selector();
+        let boolean = |bit: Expression<F>| bit.clone() * (Expression::Constant(F::one()) - bit);
+        let query_bits = |meta: &mut VirtualCells<'_, F>, slot: usize| -> Vec<Expression<F>> {
+            let bits: &Vec<Column<Advice>> = &bits[slot];
+            bits.iter()
+                .map(|bit| meta.query_advice(*bit, Rotation::cur()))
+                .collect()
+        };
+        let pack = |bits: &[Expression<F>]| {
+            bits.iter()
+                .enumerate()
+                .fold(Expression::Constant(F::zero()), |acc, (i, bit)| {
+                    acc + Expression::Constant(pow_of_two(i)) * bit.clone()
+                })
+        };
+
+        for (slot, q_bits) in q_bits.into_iter().enumerate() {
+            meta.create_gate("value is its boolean bits", |meta| {
+                let q_bits = meta.query_selector(q_bits);
+                let value = meta.query_advice(values[slot], Rotation::cur());
+                let bits = query_bits(meta, slot);
+                let mut constraints = vec![q_bits.clone() * (value - pack(&bits))];
+                constraints.extend(bits.into_iter().map(|bit| q_bits.clone() * boolean(bit)));
+                constraints
+            });
+        }
+
+        meta.create_gate("sum + carry * 2^bits = summands + constant", |meta| {
+            let q_add = meta.query_selector(q_add);
+            let sum = meta.query_advice(values[0], Rotation::cur());
+            let summands = values[1..]
+                .iter()
+                .fold(meta.query_fixed(constant, Rotation::cur()), |acc, value| {
+                    acc + meta.query_advice(*value, Rotation::cur())
+                });
+            let carry = carry.map(|bit| meta.query_advice(bit, Rotation::cur()));
+            let mut constraints = vec![
+                q_add.clone()
+                    * (sum + pack(&carry) * Expression::Constant(pow_of_two(WORD_BITS)) - summands),
+            ];
+            constraints.extend(carry.into_iter().map(|bit| q_add.clone() * boolean(bit)));
+            constraints
+        });
+
+        meta.create_gate("bytes are the bits of v0", |meta| {
+            let q_bytes = meta.query_selector(q_bytes);
+            let bits = query_bits(meta, 0);
+            bytes
+                .iter()
+                .zip(bits.chunks(8))
+                .map(|(byte, bits)| {
+                    q_bytes.clone() * (meta.query_advice(*byte, Rotation::cur()) - pack(bits))
+                })
+                .collect::<Vec<_>>()
+        });
+
+        meta.create_gate("v3 = v0 ? v1 : v2", |meta| {
+            let q_select = meta.query_selector(q_select);
+            let [flag, a, b, selected] =
+                [0, 1, 2, 3].map(|slot| meta.query_advice(values[slot], Rotation::cur()));
+            vec![
+                q_select.clone() * boolean(flag.clone()),
+                q_select
+                    * (selected - flag.clone() * a - (Expression::Constant(F::one()) - flag) * b),
+            ]
+        });
+
+        let q_shifts = shifts
+            .iter()
+            .map(|shifts| {
+                let q_shift = meta.selector();
+                meta.create_gate("v3 = xor of the shifts of v0", |meta| {
+                    let q_shift = meta.query_selector(q_shift);
+                    let input = query_bits(meta, 0);
+                    let output = (0..WORD_BITS)
+                        .map(|j| {
+                            shifts
+                                .iter()
+                                .filter_map(|shift| {
+                                    (0..WORD_BITS)
+                                        .find(|i| shift.position(*i, WORD_BITS) == Some(j))
+                                })
+                                .fold(Expression::Constant(F::zero()), |acc, i| {
+                                    let bit = input[i].clone();
+                                    acc.clone() + bit.clone()
+                                        - Expression::Constant(F::from(2)) * acc * bit
+                                })
+                        })
+                        .collect::<Vec<_>>();
+                    let v3 = meta.query_advice(values[3], Rotation::cur());
+                    vec![q_shift * (v3 - pack(&output))]
+                });
+                (shifts.clone(), q_shift)
+            })
+            .collect();
+
+        let q_fns = fns
+            .iter()
+            .map(|f| {
+                let q_fn = meta.selector();
+                meta.create_gate("v3 = f(v0, v1, v2) bit by bit", |meta| {
+                    let q_fn = meta.query_selector(q_fn);
+                    let operands: Vec<_> =
+                        (0..f.arity()).map(|slot| query_bits(meta, slot)).collect();
+                    let output = (0..WORD_BITS)
+                        .map(|j| {
+                            let bits: Vec<_> =
+                                operands.iter().map(|bits| bits[j].clone()).collect();
+                            f.expression(&bits)
+                        })
+                        .collect::<Vec<_>>();
+                    let v3 = meta.query_advice(values[3], Rotation::cur());
+                    vec![q_fn * (v3 - pack(&output))]
+                });
+                (*f, q_fn)
+            })
+            .collect();
+
+        Self {
+            values,
+            bits,
+            bytes,
+            carry,
+            constant,
+            q_bits,
+            q_add,
+            q_bytes,
+            q_select,
+            q_shifts,
+            q_fns,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct WordOpsChip<F, const WORD_BITS: usize> {
+    config: WordOpsConfig<WORD_BITS>,
+    _marker: PhantomData<F>,
+}
+
+impl<F: FieldExt, const WORD_BITS: usize> WordOpsChip<F, WORD_BITS> {
+    pub fn construct(config: WordOpsConfig<WORD_BITS>) -> Self {
+        Self {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Assigns the bits of `value` for the value in `slot` of the row.
+    fn assign_bits(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        slot: usize,
+        value: u64,
+    ) -> Result<(), Error> {
+        self.config.q_bits[slot].enable(region, offset)?;
+        for (i, bit) in self.config.bits[slot].iter().enumerate() {
+            region.assign_advice(
+                || "bit",
+                *bit,
+                offset,
+                || Value::known(F::from((value >> i) & 1)),
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Copies `word` into `slot` of the row, along with its bits.
+    fn copy_operand(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        slot: usize,
+        word: &WordCell<F>,
+    ) -> Result<(), Error> {
+        word.cell
+            .copy_advice(|| "operand", region, self.config.values[slot], offset)?;
+        self.assign_bits(region, offset, slot, word.value)
+    }
+
+    fn assign_value(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        slot: usize,
+        value: u64,
+    ) -> Result<WordCell<F>, Error> {
+        let cell = region.assign_advice(
+            || "word",
+            self.config.values[slot],
+            offset,
+            || Value::known(F::from(value)),
+        )?;
+        Ok(WordCell { cell, value })
+    }
+
+    /// Assigns `value`, range checked by its bits, on the next row of `ctx`.
+    pub fn assign(&self, ctx: &mut Context<'_, '_, F>, value: u64) -> Result<WordCell<F>, Error> {
+        assert!(value <= mask(WORD_BITS), "the word fits in its bits");
+        ctx.next_row(|region, offset| {
+            self.assign_bits(region, offset, 0, value)?;
+            self.assign_value(region, offset, 0, value)
+        })
+    }
+
+    /// Assigns the constant `value` on the next row of `ctx`.
+    pub fn constant(&self, ctx: &mut Context<'_, '_, F>, value: u64) -> Result<WordCell<F>, Error> {
+        assert!(value <= mask(WORD_BITS), "the word fits in its bits");
+        ctx.next_row(|region, offset| {
+            let cell = region.assign_advice_from_constant(
+                || "constant word",
+                self.config.values[0],
+                offset,
+                F::from(value),
+            )?;
+            Ok(WordCell { cell, value })
+        })
+    }
+
+    /// XORs the `shifts` of `word` together on the next row of `ctx`, for a
+    /// set of shifts the chip is configured with.
+    pub fn shift(
+        &self,
+        ctx: &mut Context<'_, '_, F>,
+        shifts: &[BitShift],
+        word: &WordCell<F>,
+    ) -> Result<WordCell<F>, Error> {
+        let q_shift = self
+            .config
+            .q_shifts
+            .iter()
+            .find(|(configured, _)| configured == shifts)
+            .map(|(_, q_shift)| *q_shift)
+            .unwrap_or_else(|| panic!("the chip has no gate for the shifts {shifts:?}"));
+        let value = shifts
+            .iter()
+            .fold(0, |acc, shift| acc ^ shift.apply(word.value, WORD_BITS));
+        ctx.next_row(|region, offset| {
+            q_shift.enable(region, offset)?;
+            self.copy_operand(region, offset, 0, word)?;
+            self.assign_value(region, offset, 3, value)
+        })
+    }
+
+    /// Evaluates `f` over `words` bit by bit on the next row of `ctx`, for a
+    /// function the chip is configured with.
+    pub fn apply(
+        &self,
+        ctx: &mut Context<'_, '_, F>,
+        f: BitFn,
+        words: &[&WordCell<F>],
+    ) -> Result<WordCell<F>, Error> {
+        let q_fn = self
+            .config
+            .q_fns
+            .iter()
+            .find(|(configured, _)| *configured == f)
+            .map(|(_, q_fn)| *q_fn)
+            .unwrap_or_else(|| panic!("the chip has no gate for the function {f:?}"));
+        let values: Vec<_> = words.iter().map(|word| word.value).collect();
+        let value = f.apply(&values, WORD_BITS);
+        ctx.next_row(|region, offset| {
+            q_fn.enable(region, offset)?;
+            for (slot, word) in words.iter().enumerate() {
+                self.copy_operand(region, offset, slot, word)?;
+            }
+            self.assign_value(region, offset, 3, value)
+        })
+    }
+
+    /// Selects `a` if the boolean `flag` is 1 and `b` if it is 0, on the
+    /// next row of `ctx`. The words are copied without their bits, as the
+    /// selected word is one of them.
+    pub fn select(
+        &self,
+        ctx: &mut Context<'_, '_, F>,
+        flag: &WordCell<F>,
+        a: &WordCell<F>,
+        b: &WordCell<F>,
+    ) -> Result<WordCell<F>, Error> {
+        assert!(flag.value <= 1, "the flag is boolean");
+        let value = if flag.value == 1 { a.value } else { b.value };
+        ctx.next_row(|region, offset| {
+            self.config.q_select.enable(region, offset)?;
+            for (slot, word) in [flag, a, b].into_iter().enumerate() {
+                word.cell
+                    .copy_advice(|| "operand", region, self.config.values[slot], offset)?;
+            }
+            self.assign_value(region, offset, 3, value)
+        })
+    }
+
+    /// Adds up to four `summands` and `constant` modulo `2^WORD_BITS` on the
+    /// next row of `ctx`.
+    pub fn add(
+        &self,
+        ctx: &mut Context<'_, '_, F>,
+        summands: &[&WordCell<F>],
+        constant: u64,
+    ) -> Result<WordCell<F>, Error> {
+        assert!(summands.len() <= 4, "the chip adds up to four words");
+        let total = summands
+            .iter()
+            .fold(constant as u128, |acc, summand| acc + summand.value as u128);
+        let sum = (total & mask(WORD_BITS) as u128) as u64;
+        let carry = (total >> WORD_BITS) as u64;
+        ctx.next_row(|region, offset| {
+            self.config.q_add.enable(region, offset)?;
+            for slot in 1..5 {
+                match summands.get(slot - 1) {
+                    Some(summand) => {
+                        summand.cell.copy_advice(
+                            || "summand",
+                            region,
+                            self.config.values[slot],
+                            offset,
+                        )?;
+                    }
+                    None => {
+                        region.assign_advice_from_constant(
+                            || "no summand",
+                            self.config.values[slot],
+                            offset,
+                            F::zero(),
+                        )?;
+                    }
+                }
+            }
+            for (i, bit) in self.config.carry.iter().enumerate() {
+                region.assign_advice(
+                    || "carry",
+                    *bit,
+                    offset,
+                    || Value::known(F::from((carry >> i) & 1)),
+                )?;
+            }
+            region.assign_fixed(
+                || "constant",
+                self.config.constant,
+                offset,
+                || Value::known(F::from(constant)),
+            )?;
+            self.assign_bits(region, offset, 0, sum)?;
+            self.assign_value(region, offset, 0, sum)
+        })
+    }
+
+    fn assign_bytes(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        value: u64,
+        endianness: Endianness,
+    ) -> Result<Vec<AssignedValue<F>>, Error> {
+        self.config.q_bytes.enable(region, offset)?;
+        let mut bytes = self
+            .config
+            .bytes
+            .iter()
+            .enumerate()
+            .map(|(k, byte)| {
+                region.assign_advice(
+                    || "byte",
+                    *byte,
+                    offset,
+                    || Value::known(F::from((value >> (8 * k)) & 0xff)),
+                )
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        if endianness == Endianness::Big {
+            bytes.reverse();
+        }
+        Ok(bytes)
+    }
+
+    /// Decomposes `word` into its bytes, in `endianness`, on the next row
+    /// of `ctx`.
+    pub fn bytes(
+        &self,
+        ctx: &mut Context<'_, '_, F>,
+        word: &WordCell<F>,
+        endianness: Endianness,
+    ) -> Result<Vec<AssignedValue<F>>, Error> {
+        ctx.next_row(|region, offset| {
+            self.copy_operand(region, offset, 0, word)?;
+            self.assign_bytes(region, offset, word.value, endianness)
+        })
+    }
+
+    /// Assigns the word of `bytes`, in `endianness`, and the bytes on the
+    /// next row of `ctx`. A word of fewer bytes than the word has its
+    /// other bytes constrained to zero.
+    pub fn load_bytes(
+        &self,
+        ctx: &mut Context<'_, '_, F>,
+        bytes: &[u8],
+        endianness: Endianness,
+    ) -> Result<(WordCell<F>, Vec<AssignedValue<F>>), Error> {
+        let word_bytes = WORD_BITS / 8;
+        assert!(bytes.len() <= word_bytes, "the bytes fit in the word");
+        let value = match endianness {
+            Endianness::Little => bytes
+                .iter()
+                .rev()
+                .fold(0, |acc, byte| acc << 8 | *byte as u64),
+            Endianness::Big => bytes.iter().fold(0, |acc, byte| acc << 8 | *byte as u64),
+        };
+        ctx.next_row(|region, offset| {
+            self.assign_bits(region, offset, 0, value)?;
+            let word = self.assign_value(region, offset, 0, value)?;
+            let mut cells = self.assign_bytes(region, offset, value, endianness)?;
+            let zeros = match endianness {
+                Endianness::Little => cells.split_off(bytes.len()),
+                Endianness::Big => {
+                    let bytes = cells.split_off(word_bytes - bytes.len());
+                    std::mem::replace(&mut cells, bytes)
+                }
+            };
+            for zero in zeros {
+                region.constrain_constant(zero.cell(), F::zero())?;
+            }
+            Ok((word, cells))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::context::assign_context;
+    use halo2_proofs::{
+        circuit::{Layouter, SimpleFloorPlanner},
+        dev::MockProver,
+        halo2curves::{bn256::Fr, pasta::pallas},
+        plonk::Circuit,
+    };
+
+    const SIGMA: [BitShift; 3] = [
+        BitShift::RotateRight(7),
+        BitShift::RotateRight(18),
+        BitShift::ShiftRight(3),
+    ];
+
+    #[test]
+    fn test_bit_fns() {
+        assert_eq!(BitFn::XOR, BitFn::from_fn(2, |a, b, _| a ^ b));
+        assert_eq!(BitFn::XOR3, BitFn::from_fn(3, |a, b, c| a ^ b ^ c));
+        assert_eq!(BitFn::AND, BitFn::from_fn(2, |a, b, _| a & b));
+        assert_eq!(BitFn::CH, BitFn::from_fn(3, |a, b, c| (a & b) | (!a & c)));
+        assert_eq!(
+            BitFn::MAJ,
+            BitFn::from_fn(3, |a, b, c| (a & b) | (a & c) | (b & c))
+        );
+        assert_eq!(BitFn::CH.apply(&[0xf0, 0xcc, 0xaa], 8), 0xca);
+    }
+
+    /// Which cell of the operation a test assigns a wrong value to.
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    enum Tamper {
+        None,
+        Shift,
+        Fn,
+        Sum,
+        Byte,
+    }
+
+    struct TestCircuit<F> {
+        a: u64,
+        b: u64,
+        c: u64,
+        tamper: Tamper,
+        _marker: PhantomData<F>,
+    }
+
+    impl<F: FieldExt> Circuit<F> for TestCircuit<F> {
+        type Config = WordOpsConfig<32>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self {
+                a: 0,
+                b: 0,
+                c: 0,
+                tamper: Tamper::None,
+                _marker: PhantomData,
+            }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            WordOpsConfig::configure(meta, &[SIGMA.to_vec()], &[BitFn::CH, BitFn::XOR])
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            let chip = WordOpsChip::<F, 32>::construct(config.clone());
+            assign_context(&mut layouter, "word ops", |ctx| {
+                let a = chip.assign(ctx, self.a)?;
+                let b = chip.assign(ctx, self.b)?;
+                let c = chip.assign(ctx, self.c)?;
+                let sigma = chip.shift(ctx, &SIGMA, &a)?;
+                let ch = chip.apply(ctx, BitFn::CH, &[&a, &b, &c])?;
+                let xor = chip.apply(ctx, BitFn::XOR, &[&sigma, &ch])?;
+                let sum = chip.add(ctx, &[&a, &b, &c, &xor], 0x428a2f98)?;
+                let bytes = chip.bytes(ctx, &sum, Endianness::Big)?;
+                let (word, _) =
+                    chip.load_bytes(ctx, &sum.value.to_be_bytes()[4..], Endianness::Big)?;
+                ctx.region()
+                    .constrain_equal(word.cell.cell(), sum.cell.cell())?;
+
+                let expected = SIGMA
+                    .iter()
+                    .fold(0, |acc, shift| acc ^ shift.apply(self.a, 32))
+                    ^ ((self.a & self.b) | (!self.a & self.c));
+                let expected = (self.a + self.b + self.c + (expected & 0xffff_ffff) + 0x428a2f98)
+                    & 0xffff_ffff;
+                assert_eq!(sum.value, expected);
+                for (byte, expected) in bytes.iter().zip((expected as u32).to_be_bytes()) {
+                    byte.value()
+                        .assert_if_known(|byte| **byte == F::from(expected as u64));
+                }
+
+                // Overwrites a cell of an operation with a wrong value.
+                let wrong = || Value::known(F::from(1));
+                let region = ctx.region();
+                match self.tamper {
+                    Tamper::None => {}
+                    Tamper::Shift => {
+                        region.assign_advice(|| "wrong", config.values[3], 3, wrong)?;
+                    }
+                    Tamper::Fn => {
+                        region.assign_advice(|| "wrong", config.values[3], 4, wrong)?;
+                    }
+                    Tamper::Sum => {
+                        region.assign_advice(|| "wrong", config.values[0], 6, wrong)?;
+                    }
+                    Tamper::Byte => {
+                        region.assign_advice(|| "wrong", config.bytes[0], 7, wrong)?;
+                    }
+                }
+                Ok(())
+            })
+        }
+    }
+
+    fn test_word_ops_circuit_over<F: FieldExt>(tamper: Tamper) -> bool {
+        let circuit = TestCircuit::<F> {
+            a: 0x6a09_e667,
+            b: 0xbb67_ae85,
+            c: 0xffff_ffff,
+            tamper,
+            _marker: PhantomData,
+        };
+        let prover = MockProver::run(6, &circuit, vec![]).unwrap();
+        prover.verify().is_ok()
+    }
+
+    #[test]
+    fn test_word_ops_circuit() {
+        assert!(test_word_ops_circuit_over::<Fr>(Tamper::None));
+        assert!(test_word_ops_circuit_over::<pallas::Base>(Tamper::None));
+    }
+
+    #[test]
+    fn test_word_ops_circuit_wrong_output() {
+        for tamper in [Tamper::Shift, Tamper::Fn, Tamper::Sum, Tamper::Byte] {
+            assert!(!test_word_ops_circuit_over::<Fr>(tamper), "{tamper:?}");
+        }
+    }
+}
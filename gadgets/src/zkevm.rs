@@ -0,0 +1,49 @@
+//! Adapters of the tables of this workspace to the layouts of the tables of
+//! scroll's zkevm-circuits, enabled by the `zkevm` feature.
+//!
+//! [`LookupTable`] has the methods of the trait of the same name in the
+//! `table` module of zkevm-circuits, which its super-circuit and sub-circuits
+//! look up the tables through. A circuit crate implements it for an adapter
+//! of its table whose columns are in the order of the zkevm-circuits table,
+//! e.g. `ZkevmKeccakTable` in the Keccak-256 circuit, so that the precompile
+//! circuits are looked up the same way as those of zkevm-circuits.
+
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::Region,
+    plonk::{Any, Column, ConstraintSystem, Expression, VirtualCells},
+    poly::Rotation,
+};
+
+/// A table looked up by the circuits of zkevm-circuits, as the tuple of the
+/// expressions of its columns.
+pub trait LookupTable<F: FieldExt> {
+    /// Columns of the table, in the order of the zkevm-circuits table.
+    fn columns(&self) -> Vec<Column<Any>>;
+
+    /// Annotations of [`Self::columns`], in the same order.
+    fn annotations(&self) -> Vec<String>;
+
+    /// Expressions of the columns at the current row, i.e. the tuple the
+    /// lookups of zkevm-circuits are written against.
+    fn table_exprs(&self, meta: &mut VirtualCells<F>) -> Vec<Expression<F>> {
+        self.columns()
+            .into_iter()
+            .map(|column| meta.query_any(column, Rotation::cur()))
+            .collect()
+    }
+
+    /// Annotates the columns in the constraint system, for debugging.
+    fn annotate_columns(&self, meta: &mut ConstraintSystem<F>) {
+        for (column, annotation) in self.columns().into_iter().zip(self.annotations()) {
+            meta.annotate_lookup_any_column(column, || annotation.clone());
+        }
+    }
+
+    /// Annotates the columns in `region`, for debugging.
+    fn annotate_columns_in_region(&self, region: &mut Region<F>) {
+        for (column, annotation) in self.columns().into_iter().zip(self.annotations()) {
+            region.name_column(|| annotation.clone(), column);
+        }
+    }
+}
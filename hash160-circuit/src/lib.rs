@@ -0,0 +1,464 @@
+//! The HASH160 circuit verifies calls to `RIPEMD160(SHA256(input))`, the hash
+//! used by the addresses of Bitcoin, e.g. to prove that a public key hashes to
+//! a given P2PKH address.
+//!
+//! The circuit composes the SHA2-256 and RIPEMD-160 circuits rather than
+//! re-implementing them: the inputs are passed to a [`Sha2Chip`], and the
+//! cells of the 32-byte digests returned by its `assigned_digest` are the
+//! inputs of the calls of a [`Ripemd160Chip`] through its
+//! `load_assigned_inputs`, which constrains the input bytes of the calls to
+//! them, so that the intermediate digest is never witnessed twice.
+//!
+//! The calls are exposed through `Hash160Table`:
+//! - `is_enabled`: whether the row holds a HASH160 call;
+//! - `input_rlc`: the RLC of the input bytes;
+//! - `output_rlc`: the RLC of the 20 output bytes.
+//!
+//! `input_rlc` and `output_rlc` are copied from the RLCs an [`RlcChip`]
+//! accumulates over the input cells of the SHA2-256 calls and over the digest
+//! cells of the RIPEMD-160 calls.
+//!
+//! [`Sha2Chip`]: sha2_256_circuit::Sha2Chip
+//! [`Ripemd160Chip`]: ripemd160_circuit::Ripemd160Chip
+//! [`RlcChip`]: rlc_chip::RlcChip
+
+pub mod native;
+
+use std::marker::PhantomData;
+
+use constraint_builder::{BaseConstraintBuilder, Expr};
+use gadgets::{
+    tables::{FixedTables, RangeTable},
+    AssignedBytes, HashCircuitExt,
+};
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{Layouter, Value},
+    plonk::{Advice, Any, Column, ConstraintSystem, Error, FirstPhase, SecondPhase, Selector},
+    poly::Rotation,
+};
+use ripemd160_circuit::{Ripemd160Chip, Ripemd160Config, Ripemd160Table, Ripemd160Witness};
+use rlc_chip::{RlcChip, RlcConfig};
+use sha2_256_circuit::{Sha2Chip, Sha2Config, Sha2Table, Sha2Witness};
+
+#[derive(Clone, Debug)]
+pub struct Hash160Table {
+    is_enabled: Column<Advice>,
+    input_rlc: Column<Advice>,
+    output_rlc: Column<Advice>,
+}
+
+impl Hash160Table {
+    pub fn construct<F: FieldExt>(meta: &mut ConstraintSystem<F>) -> Self {
+        let is_enabled = meta.advice_column();
+        let [input_rlc, output_rlc] = [0; 2].map(|_| {
+            let column = meta.advice_column_in(SecondPhase);
+            meta.enable_equality(column);
+            column
+        });
+        Self {
+            is_enabled,
+            input_rlc,
+            output_rlc,
+        }
+    }
+
+    pub fn columns(&self) -> Vec<Column<Any>> {
+        vec![
+            self.is_enabled.into(),
+            self.input_rlc.into(),
+            self.output_rlc.into(),
+        ]
+    }
+
+    pub fn annotations(&self) -> Vec<String> {
+        vec![
+            String::from("is_enabled"),
+            String::from("input_rlc"),
+            String::from("output_rlc"),
+        ]
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Hash160Config<F> {
+    table: Hash160Table,
+    /// The fixed tables the chip loads itself, i.e. none if they are shared
+    /// with other circuits.
+    tables: FixedTables,
+    sha2: Sha2Config<F>,
+    ripemd160: Ripemd160Config<F>,
+    rlc: RlcConfig,
+    q_table: Selector,
+}
+
+impl<F: FieldExt> Hash160Config<F> {
+    pub fn configure(meta: &mut ConstraintSystem<F>, table: Hash160Table) -> Self {
+        let mut tables = FixedTables::default();
+        let config = Self::configure_with_tables(meta, table, &mut tables);
+        Self { tables, ..config }
+    }
+
+    /// Configures the circuit over the fixed tables requested from `tables`,
+    /// which the caller loads once for all the circuits sharing them.
+    pub fn configure_with_tables(
+        meta: &mut ConstraintSystem<F>,
+        table: Hash160Table,
+        tables: &mut FixedTables,
+    ) -> Self {
+        let byte_table: RangeTable<8> = tables.range(meta);
+        let sha2_table = Sha2Table::construct(meta, sha2_256_circuit::native::DIGEST_BYTES);
+        let sha2 = Sha2Config::configure(meta, sha2_table);
+        let ripemd160_table =
+            Ripemd160Table::construct(meta, ripemd160_circuit::native::DIGEST_BYTES);
+        let ripemd160 = Ripemd160Config::configure(meta, ripemd160_table);
+
+        let q_table = meta.selector();
+        let challenge = meta.challenge_usable_after(FirstPhase);
+        let rlc = RlcConfig::configure_with_challenge(meta, byte_table, challenge);
+
+        meta.create_gate("validity check over the table rows", |meta| {
+            let mut cb = BaseConstraintBuilder::default();
+            let is_enabled = meta.query_advice(table.is_enabled, Rotation::cur());
+
+            cb.require_boolean("is_enabled is boolean", is_enabled.clone());
+            cb.condition(1.expr() - is_enabled, |cb| {
+                for (name, column) in [
+                    ("input_rlc == 0 if !is_enabled", table.input_rlc),
+                    ("output_rlc == 0 if !is_enabled", table.output_rlc),
+                ] {
+                    cb.require_zero(name, meta.query_advice(column, Rotation::cur()));
+                }
+            });
+
+            cb.gate(meta.query_selector(q_table))
+        });
+
+        Self {
+            table,
+            tables: FixedTables::default(),
+            sha2,
+            ripemd160,
+            rlc,
+            q_table,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Hash160Witness<F> {
+    pub inputs: Vec<Vec<u8>>,
+    pub _marker: PhantomData<F>,
+}
+
+#[derive(Clone, Debug)]
+pub struct Hash160Chip<F> {
+    config: Hash160Config<F>,
+    data: Hash160Witness<F>,
+}
+
+impl<F: FieldExt> Hash160Chip<F> {
+    pub fn construct(config: Hash160Config<F>, data: Hash160Witness<F>) -> Self {
+        Self { config, data }
+    }
+
+    pub fn load(&self, layouter: &mut impl Layouter<F>) -> Result<(), Error> {
+        self.assign_calls(layouter).map(|_| ())
+    }
+
+    /// Loads the calls as [`Self::load`] does, and returns their digests.
+    fn assign_calls(
+        &self,
+        layouter: &mut impl Layouter<F>,
+    ) -> Result<Vec<AssignedBytes<F>>, Error> {
+        let config = &self.config;
+        config.tables.load(layouter)?;
+
+        let sha2_calls =
+            Sha2Chip::construct(config.sha2.clone(), self.sha2_witness()).assign_calls(layouter)?;
+        let sha2_digests: Vec<_> = sha2_calls.iter().map(|call| call.digest.clone()).collect();
+        let digests = Ripemd160Chip::construct(config.ripemd160.clone(), self.ripemd160_witness())
+            .load_assigned_inputs(layouter, &sha2_digests)?;
+
+        // The input of the SHA2-256 call and the digest of the RIPEMD-160 call
+        // of every call.
+        let messages: Vec<_> = sha2_calls
+            .iter()
+            .zip(&digests)
+            .flat_map(|(sha2_call, digest)| [sha2_call.input.clone(), digest.clone()])
+            .collect();
+        let rlc_cells = RlcChip::<F>::construct(config.rlc).assign_cells(layouter, &messages)?;
+
+        let table = &config.table;
+        layouter.assign_region(
+            || "hash160 table",
+            |mut region| {
+                for (annotation, column) in [
+                    ("is_enabled", table.is_enabled),
+                    ("input_rlc", table.input_rlc),
+                    ("output_rlc", table.output_rlc),
+                ] {
+                    region.assign_advice(|| annotation, column, 0, || Value::known(F::zero()))?;
+                }
+                config.q_table.enable(&mut region, 0)?;
+
+                for (i, rlcs) in rlc_cells.chunks(2).enumerate() {
+                    let offset = i + 1;
+                    config.q_table.enable(&mut region, offset)?;
+                    region.assign_advice(
+                        || "is_enabled",
+                        table.is_enabled,
+                        offset,
+                        || Value::known(F::one()),
+                    )?;
+                    rlcs[0].copy_advice(|| "input_rlc", &mut region, table.input_rlc, offset)?;
+                    rlcs[1].copy_advice(|| "output_rlc", &mut region, table.output_rlc, offset)?;
+                }
+                Ok(())
+            },
+        )?;
+        Ok(digests)
+    }
+
+    fn sha2_witness(&self) -> Sha2Witness<F> {
+        Sha2Witness {
+            inputs: self.data.inputs.clone(),
+            _marker: PhantomData,
+        }
+    }
+
+    /// The calls of RIPEMD-160, of the SHA2-256 digests of the inputs.
+    fn ripemd160_witness(&self) -> Ripemd160Witness<F> {
+        Ripemd160Witness {
+            inputs: self
+                .data
+                .inputs
+                .iter()
+                .map(|input| sha2_256_circuit::native::sha256(input).to_vec())
+                .collect(),
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<F: FieldExt> HashCircuitExt<F> for Hash160Chip<F> {
+    type Config = Hash160Config<F>;
+    type Witness = Hash160Witness<F>;
+
+    const DIGEST_BYTES: usize = 20;
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let table = Hash160Table::construct(meta);
+        Hash160Config::configure(meta, table)
+    }
+
+    fn configure_with_tables(
+        meta: &mut ConstraintSystem<F>,
+        tables: &mut FixedTables,
+    ) -> Self::Config {
+        let table = Hash160Table::construct(meta);
+        Hash160Config::configure_with_tables(meta, table, tables)
+    }
+
+    fn construct(config: Self::Config, witness: Self::Witness) -> Self {
+        Hash160Chip::construct(config, witness)
+    }
+
+    fn load(&self, layouter: &mut impl Layouter<F>) -> Result<(), Error> {
+        Hash160Chip::load(self, layouter)
+    }
+
+    fn table_columns(config: &Self::Config) -> Vec<Column<Any>> {
+        config.table.columns()
+    }
+
+    fn annotations(config: &Self::Config) -> Vec<String> {
+        config.table.annotations()
+    }
+
+    fn assigned_digest(
+        &self,
+        layouter: &mut impl Layouter<F>,
+    ) -> Result<Vec<AssignedBytes<F>>, Error> {
+        self.assign_calls(layouter)
+    }
+}
+
+#[cfg(any(feature = "test", test))]
+pub mod dev {
+    use super::*;
+
+    use ethers_core::{types::H160, utils::hex::FromHex};
+    use halo2_proofs::{circuit::SimpleFloorPlanner, plonk::Circuit};
+    use std::str::FromStr;
+
+    lazy_static::lazy_static! {
+        // The empty input, "abc", and the compressed public key of the
+        // technical background of version 1 Bitcoin addresses on the Bitcoin
+        // wiki.
+        pub static ref INPUTS_OUTPUTS: (Vec<Vec<u8>>, Vec<H160>) = {
+        [
+            ("", "b472a266d0bd89c13706a4132ccfb16f7c3b9fcb"),
+            ("616263", "bb1be98c142444d7a56aa3981c3942a978e4dc33"),
+            (
+                "0250863ad64a87ae8a2fe83c1af1a8403cb53f53e486d8511dad8a04887e5b2352",
+                "f54a5851e9372b87810a8e60cdd2e7cfd80b6e31",
+            ),
+        ]
+            .iter()
+            .map(|(input, output)| {
+                (
+                    Vec::from_hex(input).expect("input is hex-encoded"),
+                    H160::from_str(output).expect("HASH160 is 20-bytes"),
+                )
+            })
+            .unzip()
+        };
+    }
+
+    #[derive(Default)]
+    pub struct Hash160TestCircuit<F> {
+        pub inputs: Vec<Vec<u8>>,
+        pub outputs: Vec<H160>,
+        pub _marker: PhantomData<F>,
+    }
+
+    impl<F: FieldExt> Circuit<F> for Hash160TestCircuit<F> {
+        type Config = Hash160Config<F>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            let hash160_table = Hash160Table::construct(meta);
+            Hash160Config::configure(meta, hash160_table)
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            let chip = Hash160Chip::construct(
+                config,
+                Hash160Witness {
+                    inputs: self.inputs.clone(),
+                    _marker: PhantomData,
+                },
+            );
+            chip.load(&mut layouter)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use gadgets::{
+        tables::{FixedTables, TableKey},
+        DigestCircuit, HashCircuitExt,
+    };
+    use keccak256_circuit::KeccakChip;
+    use halo2_proofs::{
+        arithmetic::FieldExt,
+        dev::MockProver,
+        halo2curves::{bn256::Fr, pasta::pallas},
+        plonk::ConstraintSystem,
+    };
+    use std::marker::PhantomData;
+
+    use crate::{
+        dev::{Hash160TestCircuit, INPUTS_OUTPUTS},
+        native, Hash160Chip, Hash160Witness,
+    };
+
+    #[test]
+    fn test_hash160_native() {
+        let (inputs, outputs) = INPUTS_OUTPUTS.clone();
+        for (input, output) in inputs.iter().zip(outputs) {
+            assert_eq!(native::hash160(input), output.0);
+        }
+    }
+
+    fn test_hash160_circuit_over<F: FieldExt>() {
+        let (inputs, outputs) = INPUTS_OUTPUTS.clone();
+        let k = Hash160Chip::<F>::min_k(&Hash160Witness {
+            inputs: inputs.clone(),
+            _marker: PhantomData,
+        });
+
+        let circuit: Hash160TestCircuit<F> = Hash160TestCircuit {
+            inputs,
+            outputs,
+            _marker: PhantomData,
+        };
+
+        let prover = MockProver::run(k, &circuit, vec![]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+    }
+
+    #[test]
+    fn test_hash160_assigned_digest() {
+        let (inputs, outputs) = INPUTS_OUTPUTS.clone();
+        let witness = Hash160Witness::<Fr> {
+            inputs,
+            _marker: PhantomData,
+        };
+        let verify = |digests: Vec<Vec<u8>>| {
+            let circuit = DigestCircuit::<Fr, Hash160Chip<Fr>> {
+                witness: witness.clone(),
+                digests,
+                _marker: PhantomData,
+            };
+            let k = Hash160Chip::<Fr>::min_k(&witness);
+            MockProver::run(k, &circuit, vec![]).unwrap().verify()
+        };
+        let mut digests: Vec<_> = outputs.iter().map(|output| output.0.to_vec()).collect();
+        assert_eq!(verify(digests.clone()), Ok(()));
+
+        // a byte of a digest is not the one of the call.
+        digests[2][19] ^= 1;
+        assert!(verify(digests).is_err());
+    }
+
+    #[test]
+    fn test_hash160_hash_circuit_ext() {
+        let mut meta = ConstraintSystem::<Fr>::default();
+        let config = Hash160Chip::<Fr>::configure(&mut meta);
+        assert_eq!(
+            Hash160Chip::<Fr>::table_columns(&config).len(),
+            Hash160Chip::<Fr>::annotations(&config).len()
+        );
+        assert_eq!(Hash160Chip::<Fr>::DIGEST_BYTES, 20);
+    }
+
+    #[test]
+    fn test_hash160_shared_byte_table() {
+        let fixed_columns = |configure: fn(&mut ConstraintSystem<Fr>)| {
+            let mut meta = ConstraintSystem::<Fr>::default();
+            configure(&mut meta);
+            meta.num_fixed_columns()
+        };
+        let hash160 = fixed_columns(|meta| {
+            Hash160Chip::<Fr>::configure(meta);
+        });
+        let keccak = fixed_columns(|meta| {
+            KeccakChip::<Fr>::configure(meta);
+        });
+        let shared = fixed_columns(|meta| {
+            let mut tables = FixedTables::default();
+            Hash160Chip::<Fr>::configure_with_tables(meta, &mut tables);
+            KeccakChip::<Fr>::configure_with_tables(meta, &mut tables);
+            assert_eq!(tables.keys(), vec![TableKey::Range(8)]);
+        });
+        // a single byte table of 2^8 rows for both circuits.
+        assert_eq!(shared, hash160 + keccak - 1);
+    }
+
+    #[test]
+    fn test_hash160_circuit() {
+        test_hash160_circuit_over::<Fr>();
+        test_hash160_circuit_over::<pallas::Base>();
+    }
+}
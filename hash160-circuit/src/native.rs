@@ -0,0 +1,8 @@
+//! Native implementation of HASH160, used to compute the witness of the
+//! circuit and as the reference it is tested against.
+
+/// Computes `RIPEMD160(SHA256(input))`, the hash of a public key or a script
+/// in the addresses of Bitcoin.
+pub fn hash160(input: &[u8]) -> [u8; 20] {
+    ripemd160_circuit::native::ripemd160(&sha2_256_circuit::native::sha256(input))
+}
@@ -0,0 +1,680 @@
+//! The Keccak-256 circuit verifies the calls to the [`Keccak-256`] hash
+//! function made by the other sub-circuits of the [`zkEVM`], most notably by
+//! the EVM circuit for the `SHA3` opcode and by the MPT and transaction
+//! circuits.
+//!
+//! Like the other hash circuits in this workspace, it exposes the
+//! input-output relationship of every hash call through a table,
+//! `KeccakTable`, whose layout follows the keccak table of the
+//! [`zkevm-circuits`]:
+//! - `is_enabled`: whether the row holds a hash call;
+//! - `input_rlc`: the random linear combination of the input bytes;
+//! - `input_len`: the length of the input in bytes;
+//! - `output_rlc`: the random linear combination of the 32 digest bytes;
+//! - `output`: the digest as a `WordLoHi`, i.e. its two big-endian 128-bit
+//!   halves, packed from the digest bytes by a `WordConfig`.
+//!
+//! The sponge absorbs a block of 17 little-endian lanes per permutation,
+//! which [`MdChip`] chains from the all-zero state on a [`WordOpsChip`] of
+//! 64-bit lanes, and constrains the padding of the input to the bytes of
+//! [`native::pad`]. A round of the permutation is a row per operation on a
+//! lane: `θ` XORs the columns of the state and the rotation of their
+//! neighbours into every lane, `ρ` and `π` rotate the lanes as they move
+//! them, `χ` evaluates `a ^ (!b & c)` bit by bit, and `ι` XORs the round
+//! constant into the first lane. The digest is the first four lanes of the
+//! state after the last block.
+//!
+//! The random linear combinations are computed with the challenge of the
+//! circuit, so `input_rlc` and `output_rlc` are second phase columns, which
+//! an [`RlcChip`] accumulates from the cells of the input and digest bytes.
+//! The first row of the table is disabled, and holds zeros only, so that a
+//! lookup from a disabled row of another circuit always finds a match.
+//! `is_enabled` and `input_len` are fixed from the constants of the circuit,
+//! and the other columns of the rows of the calls copied from the cells of
+//! the call.
+//!
+//! With the `zkevm` feature, `ZkevmKeccakTable` adapts the table to the
+//! `LookupTable` layout of the keccak table of zkevm-circuits, so that the
+//! circuit can be dropped into its super-circuit.
+//!
+//! [`Keccak-256`]: https://keccak.team/keccak_specs_summary.html
+//! [`zkEVM`]: https://privacy-scaling-explorations.github.io/zkevm-docs/introduction.html
+//! [`zkevm-circuits`]: https://github.com/scroll-tech/zkevm-circuits/blob/scroll-stable/zkevm-circuits/src/table.rs
+//! [`MdChip`]: gadgets::MdChip
+//! [`WordOpsChip`]: gadgets::WordOpsChip
+//! [`RlcChip`]: rlc_chip::RlcChip
+
+pub mod native;
+#[cfg(feature = "zkevm")]
+pub mod zkevm;
+
+use std::marker::PhantomData;
+
+use constraint_builder::{BaseConstraintBuilder, Expr};
+use gadgets::{
+    context::{assign_context, Context},
+    tables::{FixedTables, RangeTable},
+    AssignedBytes, AssignedCall, BitFn, BitShift, Endianness, HashCircuitExt, MdChip,
+    MdCompression, MdConfig, WordCell, WordChip, WordConfig, WordLoHi, WordOpsChip,
+};
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{Layouter, Value},
+    plonk::{
+        Advice, Any, Challenge, Column, ConstraintSystem, Error, FirstPhase, SecondPhase, Selector,
+    },
+    poly::Rotation,
+};
+use rlc_chip::{RlcChip, RlcConfig};
+
+#[derive(Clone, Debug)]
+pub struct KeccakTable {
+    pub is_enabled: Column<Advice>,
+    pub input_rlc: Column<Advice>,
+    pub input_len: Column<Advice>,
+    pub output_rlc: Column<Advice>,
+    pub output: WordLoHi<Column<Advice>>,
+}
+
+impl KeccakTable {
+    pub fn construct<F: FieldExt>(meta: &mut ConstraintSystem<F>) -> Self {
+        let table = Self {
+            is_enabled: meta.advice_column(),
+            input_rlc: meta.advice_column_in(SecondPhase),
+            input_len: meta.advice_column(),
+            output_rlc: meta.advice_column_in(SecondPhase),
+            output: WordLoHi::construct(meta),
+        };
+        for column in [
+            table.is_enabled,
+            table.input_rlc,
+            table.input_len,
+            table.output_rlc,
+        ]
+        .into_iter()
+        .chain(table.output.to_lo_hi())
+        {
+            meta.enable_equality(column);
+        }
+        table
+    }
+
+    pub fn columns(&self) -> Vec<Column<Any>> {
+        vec![
+            self.is_enabled.into(),
+            self.input_rlc.into(),
+            self.input_len.into(),
+            self.output_rlc.into(),
+            (*self.output.lo()).into(),
+            (*self.output.hi()).into(),
+        ]
+    }
+
+    pub fn annotations(&self) -> Vec<String> {
+        vec![
+            String::from("is_enabled"),
+            String::from("input_rlc"),
+            String::from("input_len"),
+            String::from("output_rlc"),
+            String::from("output_lo"),
+            String::from("output_hi"),
+        ]
+    }
+}
+
+/// The Keccak-f[1600] permutation of the Keccak-256 sponge, absorbing a
+/// block per permutation, whose blocks are chained by [`MdChip`].
+#[derive(Clone, Copy, Debug, Default)]
+pub struct KeccakCompression;
+
+impl KeccakCompression {
+    /// Lanes of a block.
+    const BLOCK_LANES: usize = native::RATE / 8;
+    /// Rows of a round: 10 for the column parities and 10 for their
+    /// combinations in `θ`, 25 for their XOR into the lanes, 24 rotations of
+    /// `ρ`, 25 rows of `χ`, and 2 of `ι`.
+    const ROUND_ROWS: usize = 10 + 10 + 25 + 24 + 25 + 2;
+
+    /// `χ` of a lane and of the next two in its row.
+    fn chi() -> BitFn {
+        BitFn::from_fn(3, |a, b, c| a ^ (!b & c))
+    }
+
+    fn rotate_left(offset: u32) -> [BitShift; 1] {
+        [BitShift::RotateLeft(offset as usize)]
+    }
+}
+
+impl<F: FieldExt> MdCompression<F, 64> for KeccakCompression {
+    const STATE_WORDS: usize = 25;
+    const BLOCK_BYTES: usize = native::RATE;
+    const ENDIANNESS: Endianness = Endianness::Little;
+
+    fn digest_words(&self) -> usize {
+        4
+    }
+
+    /// A rotation per lane of `ρ`, the one by one bit shared with `θ`.
+    fn shifts() -> Vec<Vec<BitShift>> {
+        native::ROTATION_OFFSETS
+            .iter()
+            .flatten()
+            .filter(|offset| **offset != 0)
+            .map(|offset| Self::rotate_left(*offset).to_vec())
+            .collect()
+    }
+
+    fn fns() -> Vec<BitFn> {
+        vec![BitFn::XOR, BitFn::XOR3, Self::chi()]
+    }
+
+    fn iv(&self) -> Vec<u64> {
+        vec![0; 25]
+    }
+
+    fn pad(&self, input: &[u8]) -> Vec<u8> {
+        native::pad(input)
+    }
+
+    fn compress_rounds(&self, state: &[u64], block: &[u8]) -> Vec<u64> {
+        let mut state: [u64; 25] = state.try_into().expect("the state has 25 lanes");
+        for (lane, bytes) in state.iter_mut().zip(block.chunks(8)) {
+            *lane ^= u64::from_le_bytes(bytes.try_into().expect("lanes are 8 bytes"));
+        }
+        native::keccak_f(&mut state);
+        state.to_vec()
+    }
+
+    /// The permuted state, which is not added to the previous one.
+    fn feed_forward(&self, _state: &[u64], working: &[u64]) -> Vec<u64> {
+        working.to_vec()
+    }
+
+    /// A row per lane of the block XORed into the state, and the rows of
+    /// every round.
+    fn rounds_rows(&self) -> usize {
+        Self::BLOCK_LANES + native::NUM_ROUNDS * Self::ROUND_ROWS
+    }
+
+    fn assign_rounds(
+        &self,
+        chip: &WordOpsChip<F, 64>,
+        ctx: &mut Context<'_, '_, F>,
+        state: &[WordCell<F>],
+        block: &[WordCell<F>],
+    ) -> Result<Vec<WordCell<F>>, Error> {
+        let mut a = state
+            .iter()
+            .enumerate()
+            .map(|(i, lane)| match block.get(i) {
+                Some(word) => chip.apply(ctx, BitFn::XOR, &[lane, word]),
+                None => Ok(lane.clone()),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        for round_constant in native::ROUND_CONSTANTS {
+            // theta
+            let c = (0..5)
+                .map(|x| {
+                    let parity = chip.apply(ctx, BitFn::XOR3, &[&a[x], &a[x + 5], &a[x + 10]])?;
+                    chip.apply(ctx, BitFn::XOR3, &[&parity, &a[x + 15], &a[x + 20]])
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            let d = (0..5)
+                .map(|x| {
+                    let rotated = chip.shift(ctx, &Self::rotate_left(1), &c[(x + 1) % 5])?;
+                    chip.apply(ctx, BitFn::XOR, &[&c[(x + 4) % 5], &rotated])
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            let a_theta = a
+                .iter()
+                .enumerate()
+                .map(|(i, lane)| chip.apply(ctx, BitFn::XOR, &[lane, &d[i % 5]]))
+                .collect::<Result<Vec<_>, _>>()?;
+
+            // rho and pi
+            let mut b = vec![None; 25];
+            for x in 0..5 {
+                for y in 0..5 {
+                    let lane = &a_theta[x + 5 * y];
+                    let rotated = match native::ROTATION_OFFSETS[x][y] {
+                        0 => lane.clone(),
+                        offset => chip.shift(ctx, &Self::rotate_left(offset), lane)?,
+                    };
+                    b[y + 5 * ((2 * x + 3 * y) % 5)] = Some(rotated);
+                }
+            }
+            let b: Vec<_> = b
+                .into_iter()
+                .map(|lane| lane.expect("pi permutes the lanes"))
+                .collect();
+
+            // chi
+            a = (0..25)
+                .map(|i| {
+                    let (x, y) = (i % 5, i / 5);
+                    chip.apply(
+                        ctx,
+                        Self::chi(),
+                        &[&b[i], &b[(x + 1) % 5 + 5 * y], &b[(x + 2) % 5 + 5 * y]],
+                    )
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+
+            // iota
+            let round_constant = chip.constant(ctx, round_constant)?;
+            a[0] = chip.apply(ctx, BitFn::XOR, &[&a[0], &round_constant])?;
+        }
+        Ok(a)
+    }
+
+    fn feed_forward_rows(&self) -> usize {
+        0
+    }
+
+    fn assign_feed_forward(
+        &self,
+        _chip: &WordOpsChip<F, 64>,
+        _ctx: &mut Context<'_, '_, F>,
+        _state: &[WordCell<F>],
+        working: &[WordCell<F>],
+    ) -> Result<Vec<WordCell<F>>, Error> {
+        Ok(working.to_vec())
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct KeccakConfig<F> {
+    q_enable: Selector,
+    table: KeccakTable,
+    /// The fixed tables the chip loads itself, i.e. none if they are shared
+    /// with other circuits.
+    tables: FixedTables,
+    md: MdConfig<64>,
+    word: WordConfig,
+    rlc: RlcConfig,
+    challenge: Challenge,
+    _marker: PhantomData<F>,
+}
+
+impl<F: FieldExt> KeccakConfig<F> {
+    pub fn configure(meta: &mut ConstraintSystem<F>, table: KeccakTable) -> Self {
+        let mut tables = FixedTables::default();
+        let config = Self::configure_with_tables(meta, table, &mut tables);
+        Self { tables, ..config }
+    }
+
+    /// Configures the circuit over the fixed tables requested from `tables`,
+    /// which the caller loads once for all the circuits sharing them.
+    pub fn configure_with_tables(
+        meta: &mut ConstraintSystem<F>,
+        table: KeccakTable,
+        tables: &mut FixedTables,
+    ) -> Self {
+        let q_enable = meta.selector();
+        let challenge = meta.challenge_usable_after(FirstPhase);
+        let byte_table: RangeTable<8> = tables.range(meta);
+        let md = MdConfig::configure::<F, KeccakCompression>(meta);
+        let word = WordConfig::configure(meta, byte_table);
+        let rlc = RlcConfig::configure_with_challenge(meta, byte_table, challenge);
+
+        meta.create_gate("validity check over the table rows", |meta| {
+            let mut cb = BaseConstraintBuilder::default();
+            let is_enabled = meta.query_advice(table.is_enabled, Rotation::cur());
+
+            cb.require_boolean("is_enabled is boolean", is_enabled.clone());
+            cb.condition(1.expr() - is_enabled, |cb| {
+                for (name, column) in [
+                    ("input_rlc == 0 if !is_enabled", table.input_rlc),
+                    ("input_len == 0 if !is_enabled", table.input_len),
+                    ("output_rlc == 0 if !is_enabled", table.output_rlc),
+                    ("output.lo == 0 if !is_enabled", *table.output.lo()),
+                    ("output.hi == 0 if !is_enabled", *table.output.hi()),
+                ] {
+                    cb.require_zero(name, meta.query_advice(column, Rotation::cur()));
+                }
+            });
+
+            cb.gate(meta.query_selector(q_enable))
+        });
+
+        Self {
+            q_enable,
+            table,
+            tables: FixedTables::default(),
+            md,
+            word,
+            rlc,
+            challenge,
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn table(&self) -> &KeccakTable {
+        &self.table
+    }
+
+    /// Challenge of the RLCs of the table, which circuits looking up the
+    /// table must use for their own RLCs.
+    pub fn challenge(&self) -> Challenge {
+        self.challenge
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct KeccakWitness<F> {
+    pub inputs: Vec<Vec<u8>>,
+    pub _marker: PhantomData<F>,
+}
+
+#[derive(Clone, Debug)]
+pub struct KeccakChip<F> {
+    config: KeccakConfig<F>,
+    data: KeccakWitness<F>,
+}
+
+impl<F: FieldExt> KeccakChip<F> {
+    pub fn construct(config: KeccakConfig<F>, data: KeccakWitness<F>) -> Self {
+        Self { config, data }
+    }
+
+    pub fn load(&self, layouter: &mut impl Layouter<F>) -> Result<(), Error> {
+        self.assign_calls(layouter).map(|_| ())
+    }
+
+    /// Loads the calls of the witness as [`Self::load`] does, and returns the
+    /// cells of their inputs and digests.
+    ///
+    /// Every call is assigned in a region `"keccak call {id}"`, the calls
+    /// numbered from 1, followed by the RLCs of their inputs and digests, and
+    /// the table: a disabled first row, and a row per call, whose `output` is
+    /// packed from the digest bytes by the word gadget on the same row.
+    pub fn assign_calls(
+        &self,
+        layouter: &mut impl Layouter<F>,
+    ) -> Result<Vec<AssignedCall<F>>, Error> {
+        self.config.tables.load(layouter)?;
+        let md_chip = self.md_chip();
+        let calls = self
+            .data
+            .inputs
+            .iter()
+            .enumerate()
+            .map(|(i, input)| {
+                assign_context(layouter, &format!("keccak call {}", i + 1), |ctx| {
+                    md_chip.assign_call(ctx, input).map(|(call, _)| call)
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let messages: Vec<_> = calls
+            .iter()
+            .flat_map(|call| [call.input.clone(), call.digest.clone()])
+            .collect();
+        let rlcs = RlcChip::construct(self.config.rlc).assign_cells(layouter, &messages)?;
+
+        let table = &self.config.table;
+        let word_chip = WordChip::construct(self.config.word);
+        layouter.assign_region(
+            || "keccak table",
+            |mut region| {
+                self.config.q_enable.enable(&mut region, 0)?;
+                for column in [
+                    table.is_enabled,
+                    table.input_rlc,
+                    table.input_len,
+                    table.output_rlc,
+                ] {
+                    region.assign_advice_from_constant(|| "disabled row", column, 0, F::zero())?;
+                }
+                WordLoHi::new([Value::known(F::zero()); 2]).assign_advice(
+                    &mut region,
+                    "output",
+                    table.output,
+                    0,
+                )?;
+
+                for (i, (call, rlcs)) in calls.iter().zip(rlcs.chunks(2)).enumerate() {
+                    let offset = i + 1;
+                    self.config.q_enable.enable(&mut region, offset)?;
+                    region.assign_advice_from_constant(
+                        || "is_enabled",
+                        table.is_enabled,
+                        offset,
+                        F::one(),
+                    )?;
+                    region.assign_advice_from_constant(
+                        || "input_len",
+                        table.input_len,
+                        offset,
+                        F::from(call.input.len() as u64),
+                    )?;
+                    rlcs[0].copy_advice(|| "input_rlc", &mut region, table.input_rlc, offset)?;
+                    rlcs[1].copy_advice(|| "output_rlc", &mut region, table.output_rlc, offset)?;
+
+                    let digest = native::keccak256(&self.data.inputs[i]);
+                    let (bytes, word) = word_chip.pack(&mut region, offset, &digest)?;
+                    call.digest.constrain_equal(&mut region, &bytes)?;
+                    for (cell, column) in word.to_lo_hi().iter().zip(table.output.to_lo_hi()) {
+                        cell.copy_advice(|| "output", &mut region, column, offset)?;
+                    }
+                }
+                Ok(())
+            },
+        )?;
+        Ok(calls)
+    }
+
+    fn md_chip(&self) -> MdChip<F, KeccakCompression, 64> {
+        MdChip::construct(self.config.md.clone(), KeccakCompression)
+    }
+}
+
+impl<F: FieldExt> HashCircuitExt<F> for KeccakChip<F> {
+    type Config = KeccakConfig<F>;
+    type Witness = KeccakWitness<F>;
+
+    const DIGEST_BYTES: usize = 32;
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let table = KeccakTable::construct(meta);
+        KeccakConfig::configure(meta, table)
+    }
+
+    fn configure_with_tables(
+        meta: &mut ConstraintSystem<F>,
+        tables: &mut FixedTables,
+    ) -> Self::Config {
+        let table = KeccakTable::construct(meta);
+        KeccakConfig::configure_with_tables(meta, table, tables)
+    }
+
+    fn construct(config: Self::Config, witness: Self::Witness) -> Self {
+        KeccakChip::construct(config, witness)
+    }
+
+    fn load(&self, layouter: &mut impl Layouter<F>) -> Result<(), Error> {
+        KeccakChip::load(self, layouter)
+    }
+
+    fn table_columns(config: &Self::Config) -> Vec<Column<Any>> {
+        config.table.columns()
+    }
+
+    fn annotations(config: &Self::Config) -> Vec<String> {
+        config.table.annotations()
+    }
+
+    fn assigned_digest(
+        &self,
+        layouter: &mut impl Layouter<F>,
+    ) -> Result<Vec<AssignedBytes<F>>, Error> {
+        let calls = self.assign_calls(layouter)?;
+        Ok(calls.into_iter().map(|call| call.digest).collect())
+    }
+}
+
+#[cfg(any(feature = "test", test))]
+pub mod dev {
+    use super::*;
+
+    use ethers_core::types::H256;
+    use halo2_proofs::{circuit::SimpleFloorPlanner, plonk::Circuit};
+    use std::str::FromStr;
+
+    lazy_static::lazy_static! {
+        pub static ref INPUTS_OUTPUTS: (Vec<Vec<u8>>, Vec<H256>) = {
+        [
+            (
+                "",
+                "c5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470",
+            ),
+            (
+                "abc",
+                "4e03657aea45a94fc7d47ba826c8d667c0d1e6e33a64a036ec44f58fa12d6c45",
+            ),
+            (
+                "abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq",
+                "45d3b367a6904e6e8d502ee04999a7c27647f91fa845d456525fd352ae3d7371",
+            ),
+            (
+                "abcdefghbcdefghicdefghijdefghijkefghijklfghijklmghijklmnhijklmnoijklmnopjklmnopqklmnopqrlmnopqrsmnopqrstnopqrstu",
+                "f519747ed599024f3882238e5ab43960132572b7345fbeb9a90769dafd21ad67",
+            ),
+        ]
+            .iter()
+            .map(|(input, output)| {
+                (
+                    input.as_bytes().to_vec(),
+                    H256::from_str(output).expect("Keccak-256 hash is 32-bytes"),
+                )
+            })
+            .unzip()
+        };
+    }
+
+    #[derive(Default)]
+    pub struct KeccakTestCircuit<F> {
+        pub inputs: Vec<Vec<u8>>,
+        pub outputs: Vec<H256>,
+        pub _marker: PhantomData<F>,
+    }
+
+    impl<F: FieldExt> KeccakTestCircuit<F> {
+        /// Smallest `k` that fits the calls of the circuit, see
+        /// [`HashCircuitExt::min_k`].
+        pub fn min_k(&self) -> u32 {
+            KeccakChip::<F>::min_k(&KeccakWitness {
+                inputs: self.inputs.clone(),
+                _marker: PhantomData,
+            })
+        }
+    }
+
+    impl<F: FieldExt> Circuit<F> for KeccakTestCircuit<F> {
+        type Config = KeccakConfig<F>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            let keccak_table = KeccakTable::construct(meta);
+            KeccakConfig::configure(meta, keccak_table)
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            let chip = KeccakChip::construct(
+                config,
+                KeccakWitness {
+                    inputs: self.inputs.clone(),
+                    _marker: PhantomData,
+                },
+            );
+            chip.load(&mut layouter)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use gadgets::{DigestCircuit, HashCircuitExt};
+    use halo2_proofs::{
+        arithmetic::FieldExt,
+        dev::MockProver,
+        halo2curves::{bn256::Fr, pasta::pallas},
+        plonk::ConstraintSystem,
+    };
+    use std::marker::PhantomData;
+
+    use crate::{
+        dev::{KeccakTestCircuit, INPUTS_OUTPUTS},
+        native, KeccakChip, KeccakWitness,
+    };
+
+    #[test]
+    fn test_keccak256_native() {
+        let (inputs, outputs) = INPUTS_OUTPUTS.clone();
+        for (input, output) in inputs.iter().zip(outputs) {
+            assert_eq!(native::keccak256(input), output.0);
+        }
+    }
+
+    fn test_keccak256_circuit_over<F: FieldExt>() {
+        let (inputs, outputs) = INPUTS_OUTPUTS.clone();
+
+        let circuit: KeccakTestCircuit<F> = KeccakTestCircuit {
+            inputs,
+            outputs,
+            _marker: PhantomData,
+        };
+
+        let prover = MockProver::run(circuit.min_k(), &circuit, vec![]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+    }
+
+    #[test]
+    fn test_keccak256_assigned_digest() {
+        let (inputs, outputs) = INPUTS_OUTPUTS.clone();
+        let witness = KeccakWitness::<Fr> {
+            inputs,
+            _marker: PhantomData,
+        };
+        let verify = |digests: Vec<Vec<u8>>| {
+            let circuit = DigestCircuit::<Fr, KeccakChip<Fr>> {
+                witness: witness.clone(),
+                digests,
+                _marker: PhantomData,
+            };
+            let k = KeccakChip::<Fr>::min_k(&witness);
+            MockProver::run(k, &circuit, vec![]).unwrap().verify()
+        };
+        let mut digests: Vec<_> = outputs.iter().map(|output| output.0.to_vec()).collect();
+        assert_eq!(verify(digests.clone()), Ok(()));
+
+        // a byte of a digest is not the one of the call.
+        digests[2][9] ^= 1;
+        assert!(verify(digests).is_err());
+    }
+
+    #[test]
+    fn test_keccak256_hash_circuit_ext() {
+        let mut meta = ConstraintSystem::<Fr>::default();
+        let config = KeccakChip::<Fr>::configure(&mut meta);
+        assert_eq!(
+            KeccakChip::<Fr>::table_columns(&config).len(),
+            KeccakChip::<Fr>::annotations(&config).len()
+        );
+        assert_eq!(KeccakChip::<Fr>::DIGEST_BYTES, 32);
+    }
+
+    #[test]
+    fn test_keccak256_circuit() {
+        test_keccak256_circuit_over::<Fr>();
+        test_keccak256_circuit_over::<pallas::Base>();
+    }
+}
@@ -0,0 +1,120 @@
+//! Native implementation of Keccak-256, used to compute the witness of the
+//! circuit and as the reference it is tested against.
+
+/// Number of rounds of the Keccak-f[1600] permutation.
+pub const NUM_ROUNDS: usize = 24;
+
+/// Rate of the Keccak-256 sponge in bytes, i.e. the size of an absorbed block.
+pub const RATE: usize = 136;
+
+/// Round constants of the iota step.
+pub const ROUND_CONSTANTS: [u64; NUM_ROUNDS] = [
+    0x0000000000000001,
+    0x0000000000008082,
+    0x800000000000808a,
+    0x8000000080008000,
+    0x000000000000808b,
+    0x0000000080000001,
+    0x8000000080008081,
+    0x8000000000008009,
+    0x000000000000008a,
+    0x0000000000000088,
+    0x0000000080008009,
+    0x000000008000000a,
+    0x000000008000808b,
+    0x800000000000008b,
+    0x8000000000008089,
+    0x8000000000008003,
+    0x8000000000008002,
+    0x8000000000000080,
+    0x000000000000800a,
+    0x800000008000000a,
+    0x8000000080008081,
+    0x8000000000008080,
+    0x0000000080000001,
+    0x8000000080008008,
+];
+
+/// Rotation offsets of the rho step, indexed by `[x][y]`.
+pub const ROTATION_OFFSETS: [[u32; 5]; 5] = [
+    [0, 36, 3, 41, 18],
+    [1, 44, 10, 45, 2],
+    [62, 6, 43, 15, 61],
+    [28, 55, 25, 21, 56],
+    [27, 20, 39, 8, 14],
+];
+
+/// Applies the Keccak-f[1600] permutation to `state`, whose lane `(x, y)` is
+/// stored at index `x + 5 * y`.
+pub fn keccak_f(state: &mut [u64; 25]) {
+    for round_constant in ROUND_CONSTANTS {
+        // theta
+        let c: [u64; 5] = std::array::from_fn(|x| (0..5).fold(0, |acc, y| acc ^ state[x + 5 * y]));
+        for x in 0..5 {
+            let d = c[(x + 4) % 5] ^ c[(x + 1) % 5].rotate_left(1);
+            for y in 0..5 {
+                state[x + 5 * y] ^= d;
+            }
+        }
+
+        // rho and pi
+        let mut b = [0u64; 25];
+        for x in 0..5 {
+            for y in 0..5 {
+                b[y + 5 * ((2 * x + 3 * y) % 5)] =
+                    state[x + 5 * y].rotate_left(ROTATION_OFFSETS[x][y]);
+            }
+        }
+
+        // chi
+        for x in 0..5 {
+            for y in 0..5 {
+                state[x + 5 * y] =
+                    b[x + 5 * y] ^ (!b[(x + 1) % 5 + 5 * y] & b[(x + 2) % 5 + 5 * y]);
+            }
+        }
+
+        // iota
+        state[0] ^= round_constant;
+    }
+}
+
+/// Pads `input` to a multiple of [`RATE`] bytes with the Keccak `pad10*1`
+/// rule, using the original (pre-SHA-3) domain byte `0x01`.
+pub fn pad(input: &[u8]) -> Vec<u8> {
+    let mut padded = input.to_vec();
+    padded.push(0x01);
+    padded.resize(padded.len().div_ceil(RATE) * RATE, 0);
+    *padded.last_mut().expect("padded input is not empty") |= 0x80;
+    padded
+}
+
+/// Computes the Keccak-256 digest of `input`.
+pub fn keccak256(input: &[u8]) -> [u8; 32] {
+    let mut state = [0u64; 25];
+    for block in pad(input).chunks(RATE) {
+        for (lane, bytes) in state.iter_mut().zip(block.chunks(8)) {
+            *lane ^= u64::from_le_bytes(bytes.try_into().expect("lanes are 8 bytes"));
+        }
+        keccak_f(&mut state);
+    }
+
+    let mut digest = [0u8; 32];
+    for (bytes, lane) in digest.chunks_mut(8).zip(state) {
+        bytes.copy_from_slice(&lane.to_le_bytes());
+    }
+    digest
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pad() {
+        assert_eq!(pad(&[]).len(), RATE);
+        assert_eq!(pad(&[0; RATE - 1]).len(), RATE);
+        assert_eq!(pad(&[0; RATE]).len(), 2 * RATE);
+        assert_eq!(pad(&[0; RATE - 1])[RATE - 1], 0x81);
+    }
+}
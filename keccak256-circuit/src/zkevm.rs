@@ -0,0 +1,59 @@
+//! The `KeccakTable` of the circuit in the layout of the keccak table of
+//! scroll's zkevm-circuits, whose lookups are written against the tuple
+//! `(q_enable, is_final, input_rlc, input_len, output_rlc)`.
+//!
+//! Every call of the circuit takes a single row of its table, so that the
+//! `is_enabled` column takes the place of both `q_enable` and `is_final`: a
+//! lookup of zkevm-circuits, which sets both to one, only matches the rows of
+//! the calls. The `output` word of the table is not part of the layout.
+
+use gadgets::zkevm::LookupTable;
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    plonk::{Any, Column},
+};
+
+use crate::KeccakTable;
+
+#[derive(Clone, Debug)]
+pub struct ZkevmKeccakTable(pub KeccakTable);
+
+impl<F: FieldExt> LookupTable<F> for ZkevmKeccakTable {
+    fn columns(&self) -> Vec<Column<Any>> {
+        vec![
+            self.0.is_enabled.into(),
+            self.0.is_enabled.into(),
+            self.0.input_rlc.into(),
+            self.0.input_len.into(),
+            self.0.output_rlc.into(),
+        ]
+    }
+
+    fn annotations(&self) -> Vec<String> {
+        vec![
+            String::from("q_enable"),
+            String::from("is_final"),
+            String::from("input_rlc"),
+            String::from("input_len"),
+            String::from("output_rlc"),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use halo2_proofs::{halo2curves::bn256::Fr, plonk::ConstraintSystem};
+
+    #[test]
+    fn test_zkevm_keccak_table() {
+        let mut meta = ConstraintSystem::<Fr>::default();
+        let table = ZkevmKeccakTable(KeccakTable::construct(&mut meta));
+        let columns = LookupTable::<Fr>::columns(&table);
+        assert_eq!(columns.len(), LookupTable::<Fr>::annotations(&table).len());
+        assert_eq!(columns[0], table.0.is_enabled.into());
+        assert_eq!(columns[1], table.0.is_enabled.into());
+        assert_eq!(columns[4], table.0.output_rlc.into());
+        LookupTable::<Fr>::annotate_columns(&table, &mut meta);
+    }
+}
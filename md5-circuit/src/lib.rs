@@ -0,0 +1,473 @@
+//! The MD5 circuit verifies calls to the [`MD5`] hash function. MD5 is broken
+//! for collision resistance and is not part of the EVM, but it is still needed
+//! to prove statements about legacy artifacts, e.g. old file checksums or the
+//! digests signed by legacy certificates.
+//!
+//! The compression function runs 64 rounds over 32-bit little-endian words,
+//! which [`MdChip`] assigns on a [`WordOpsChip`] along with the chaining of
+//! the blocks: a round evaluates its boolean function, adds the message word
+//! and the round constant, rotates the sum left and adds it to `b`, a row
+//! each. The chip decomposes the words of every block into their
+//! little-endian bytes, and constrains the padding of the input to the bytes
+//! of [`native::pad`].
+//!
+//! The blocks of the calls are laid out in the slots of a
+//! [`CallSlotsConfig`], which constrains their padding, and the circuit
+//! exposes the calls through `Md5Table`, a [`HashTable`] with one row per
+//! call of the RLC and length of its input and of its digest, whose digest
+//! is the little-endian bytes of the chaining value.
+//!
+//! [`MD5`]: https://www.rfc-editor.org/rfc/rfc1321
+//! [`MdChip`]: gadgets::MdChip
+//! [`WordOpsChip`]: gadgets::WordOpsChip
+//! [`CallSlotsConfig`]: gadgets::CallSlotsConfig
+//! [`HashTable`]: gadgets::HashTable
+
+pub mod native;
+
+use std::marker::PhantomData;
+
+use gadgets::{
+    context::{assign_context, Context},
+    public_inputs::PublicInputs,
+    tables::FixedTables,
+    AssignedBytes, AssignedCall, BitFn, BitShift, CallSlotsConfig, Endianness, HashCircuitExt,
+    HashTable, HashTableConfig, MdChip, MdCompression, MdConfig, MdPadding, SlotPadding, WordCell,
+    WordOpsChip,
+};
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::Layouter,
+    plonk::{Any, Challenge, Column, ConstraintSystem, Error, FirstPhase},
+};
+
+pub type Md5Table = HashTable;
+
+/// The MD5 compression function, whose blocks are chained by [`MdChip`].
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Md5Compression;
+
+impl Md5Compression {
+    /// The function of the last 16 rounds, `c ^ (b | !d)`.
+    fn i() -> BitFn {
+        BitFn::from_fn(3, |b, c, d| c ^ (b | !d))
+    }
+}
+
+impl<F: FieldExt> MdCompression<F, 32> for Md5Compression {
+    const STATE_WORDS: usize = 4;
+    const BLOCK_BYTES: usize = native::BLOCK_BYTES;
+    const ENDIANNESS: Endianness = Endianness::Little;
+
+    fn shifts() -> Vec<Vec<BitShift>> {
+        native::ROTATIONS
+            .iter()
+            .flatten()
+            .map(|n| vec![BitShift::RotateLeft(*n as usize)])
+            .collect()
+    }
+
+    fn fns() -> Vec<BitFn> {
+        vec![BitFn::CH, BitFn::XOR3, Self::i()]
+    }
+
+    fn iv(&self) -> Vec<u64> {
+        native::IV.iter().map(|word| *word as u64).collect()
+    }
+
+    fn pad(&self, input: &[u8]) -> Vec<u8> {
+        native::pad(input)
+    }
+
+    fn compress_rounds(&self, state: &[u64], block: &[u8]) -> Vec<u64> {
+        let state = core::array::from_fn(|i| state[i] as u32);
+        native::compress_rounds(&state, block)
+            .iter()
+            .map(|word| *word as u64)
+            .collect()
+    }
+
+    /// Four rows per round.
+    fn rounds_rows(&self) -> usize {
+        4 * native::NUM_ROUNDS
+    }
+
+    fn assign_rounds(
+        &self,
+        chip: &WordOpsChip<F, 32>,
+        ctx: &mut Context<'_, '_, F>,
+        state: &[WordCell<F>],
+        block: &[WordCell<F>],
+    ) -> Result<Vec<WordCell<F>>, Error> {
+        let [mut a, mut b, mut c, mut d]: [WordCell<F>; 4] =
+            state.to_vec().try_into().expect("the state has 4 words");
+        for i in 0..native::NUM_ROUNDS {
+            let (f, g) = match i / 16 {
+                0 => (chip.apply(ctx, BitFn::CH, &[&b, &c, &d])?, i),
+                1 => (chip.apply(ctx, BitFn::CH, &[&d, &b, &c])?, (5 * i + 1) % 16),
+                2 => (
+                    chip.apply(ctx, BitFn::XOR3, &[&b, &c, &d])?,
+                    (3 * i + 5) % 16,
+                ),
+                _ => (chip.apply(ctx, Self::i(), &[&b, &c, &d])?, (7 * i) % 16),
+            };
+            let temp = chip.add(ctx, &[&a, &f, &block[g]], native::ROUND_CONSTANTS[i] as u64)?;
+            let rotation = native::ROTATIONS[i / 16][i % 4] as usize;
+            let rotated = chip.shift(ctx, &[BitShift::RotateLeft(rotation)], &temp)?;
+            a = d;
+            d = c;
+            c = b.clone();
+            b = chip.add(ctx, &[&b, &rotated], 0)?;
+        }
+        Ok(vec![a, b, c, d])
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Md5Config<F> {
+    table: HashTableConfig,
+    md: MdConfig<32>,
+    slots: CallSlotsConfig,
+    _marker: PhantomData<F>,
+}
+
+impl<F: FieldExt> Md5Config<F> {
+    /// The padding of the blocks: a `0x80` byte, zeros and the little-endian
+    /// length of the input in bits, in 8 bytes.
+    const PADDING: MdPadding = MdPadding {
+        len_bytes: 8,
+        endianness: Endianness::Little,
+    };
+
+    /// Configures the circuit over `table`, constructed with
+    /// `Md5Table::construct(meta, native::DIGEST_BYTES)`, and a challenge of
+    /// its own for the RLCs of the table.
+    pub fn configure(meta: &mut ConstraintSystem<F>, table: Md5Table) -> Self {
+        let challenge = meta.challenge_usable_after(FirstPhase);
+        let table = HashTableConfig::configure(meta, table, challenge);
+        let md = MdConfig::configure::<F, Md5Compression>(meta);
+        let slots = CallSlotsConfig::configure(
+            meta,
+            &table,
+            native::BLOCK_BYTES,
+            native::DIGEST_BYTES,
+            SlotPadding::Md(Self::PADDING),
+        );
+        Self {
+            table,
+            md,
+            slots,
+            _marker: PhantomData,
+        }
+    }
+
+    /// The table of the calls.
+    pub fn table(&self) -> &Md5Table {
+        &self.table.table
+    }
+
+    /// The challenge of the RLCs of [`Self::table`].
+    pub fn challenge(&self) -> Challenge {
+        self.table.challenge
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Md5Witness<F> {
+    pub inputs: Vec<Vec<u8>>,
+    pub _marker: PhantomData<F>,
+}
+
+#[derive(Clone, Debug)]
+pub struct Md5Chip<F> {
+    config: Md5Config<F>,
+    data: Md5Witness<F>,
+}
+
+impl<F: FieldExt> Md5Chip<F> {
+    pub fn construct(config: Md5Config<F>, data: Md5Witness<F>) -> Self {
+        Self { config, data }
+    }
+
+    pub fn load(&self, layouter: &mut impl Layouter<F>) -> Result<(), Error> {
+        self.assign_calls(layouter).map(|_| ())
+    }
+
+    /// Loads the calls of the witness as [`Self::load`] does, and returns the
+    /// cells of their inputs and digests.
+    pub fn assign_calls(
+        &self,
+        layouter: &mut impl Layouter<F>,
+    ) -> Result<Vec<AssignedCall<F>>, Error> {
+        let inputs = &self.data.inputs;
+        let lens: Vec<_> = inputs.iter().map(Vec::len).collect();
+        let slots = &self.config.slots;
+        let blocks = lens.iter().map(|len| slots.blocks(*len)).sum();
+        let plan = slots.plan(&lens, blocks);
+        let (calls, cells) = assign_context(layouter, "md5 blocks", |ctx| {
+            self.md_chip().assign_slots(ctx, inputs, &plan)
+        })?;
+        slots.assign(layouter, "md5", &self.config.table, &lens, &plan, &cells)?;
+        let table_calls: Vec<_> = inputs
+            .iter()
+            .map(|input| (input.clone(), native::md5(input).to_vec()))
+            .collect();
+        self.config
+            .table
+            .assign(layouter, "md5", inputs.len(), &table_calls)?;
+        Ok(calls)
+    }
+
+    fn md_chip(&self) -> MdChip<F, Md5Compression, 32> {
+        MdChip::construct(self.config.md.clone(), Md5Compression)
+    }
+}
+
+impl<F: FieldExt> HashCircuitExt<F> for Md5Chip<F> {
+    type Config = Md5Config<F>;
+    type Witness = Md5Witness<F>;
+
+    const DIGEST_BYTES: usize = native::DIGEST_BYTES;
+
+    /// Configures the circuit over its table, exposed in its instance
+    /// columns.
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let mut config = Self::configure_with_tables(meta, &mut FixedTables::default());
+        config.table.expose(meta);
+        config
+    }
+
+    /// Configures the circuit over its table, not exposed, the circuit
+    /// having no fixed tables.
+    fn configure_with_tables(
+        meta: &mut ConstraintSystem<F>,
+        _tables: &mut FixedTables,
+    ) -> Self::Config {
+        let table = Md5Table::construct(meta, native::DIGEST_BYTES);
+        Md5Config::configure(meta, table)
+    }
+
+    /// The calls of the inputs of `witness`, in their order.
+    fn public_inputs(witness: &Self::Witness) -> Vec<Vec<F>> {
+        let mut public_inputs = PublicInputs::new(
+            native::DIGEST_BYTES,
+            native::BLOCK_BYTES,
+            SlotPadding::Md(Md5Config::<F>::PADDING),
+        );
+        for input in &witness.inputs {
+            public_inputs.call(input, &native::md5(input));
+        }
+        public_inputs.build()
+    }
+
+    fn construct(config: Self::Config, witness: Self::Witness) -> Self {
+        Md5Chip::construct(config, witness)
+    }
+
+    fn load(&self, layouter: &mut impl Layouter<F>) -> Result<(), Error> {
+        Md5Chip::load(self, layouter)
+    }
+
+    fn table_columns(config: &Self::Config) -> Vec<Column<Any>> {
+        config.table().columns()
+    }
+
+    fn annotations(config: &Self::Config) -> Vec<String> {
+        config.table().annotations()
+    }
+
+    fn assigned_digest(
+        &self,
+        layouter: &mut impl Layouter<F>,
+    ) -> Result<Vec<AssignedBytes<F>>, Error> {
+        let calls = self.assign_calls(layouter)?;
+        Ok(calls.into_iter().map(|call| call.digest).collect())
+    }
+}
+
+#[cfg(any(feature = "test", test))]
+pub mod dev {
+    use super::*;
+
+    use ethers_core::{types::H128, utils::hex::FromHex};
+    use halo2_proofs::{circuit::SimpleFloorPlanner, plonk::Circuit};
+    use std::str::FromStr;
+
+    lazy_static::lazy_static! {
+        // The test suite of RFC 1321.
+        pub static ref INPUTS_OUTPUTS: (Vec<Vec<u8>>, Vec<H128>) = {
+        [
+            ("", "d41d8cd98f00b204e9800998ecf8427e"),
+            ("61", "0cc175b9c0f1b6a831c399e269772661"),
+            ("616263", "900150983cd24fb0d6963f7d28e17f72"),
+            ("6d65737361676520646967657374", "f96b697d7cb7938d525a2f31aaf161d0"),
+            (
+                "6162636465666768696a6b6c6d6e6f707172737475767778797a",
+                "c3fcd3d76192e4007dfb496cca67e13b",
+            ),
+            (
+                "4142434445464748494a4b4c4d4e4f505152535455565758595a6162636465666768696a6b6c6d6e6f707172737475767778797a30313233343536373839",
+                "d174ab98d277d9f5a5611c2c9f419d9f",
+            ),
+            (
+                "3132333435363738393031323334353637383930313233343536373839303132333435363738393031323334353637383930313233343536373839303132333435363738393031323334353637383930",
+                "57edf4a22be3c955ac49da2e2107b67a",
+            ),
+        ]
+            .iter()
+            .map(|(input, output)| {
+                (
+                    Vec::from_hex(input).expect("input is hex-encoded"),
+                    H128::from_str(output).expect("MD5 hash is 16-bytes"),
+                )
+            })
+            .unzip()
+        };
+    }
+
+    #[derive(Default)]
+    pub struct Md5TestCircuit<F> {
+        pub inputs: Vec<Vec<u8>>,
+        pub outputs: Vec<H128>,
+        pub _marker: PhantomData<F>,
+    }
+
+    impl<F: FieldExt> Circuit<F> for Md5TestCircuit<F> {
+        type Config = Md5Config<F>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            let md5_table = Md5Table::construct(meta, native::DIGEST_BYTES);
+            Md5Config::configure(meta, md5_table)
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            let chip = Md5Chip::construct(
+                config,
+                Md5Witness {
+                    inputs: self.inputs.clone(),
+                    _marker: PhantomData,
+                },
+            );
+            chip.load(&mut layouter)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use gadgets::{DigestCircuit, HashCircuitExt};
+    use halo2_proofs::{
+        arithmetic::FieldExt,
+        dev::MockProver,
+        halo2curves::{bn256::Fr, pasta::pallas},
+        plonk::ConstraintSystem,
+    };
+    use std::marker::PhantomData;
+
+    use crate::{
+        dev::{Md5TestCircuit, INPUTS_OUTPUTS},
+        native, Md5Chip, Md5Witness,
+    };
+
+    #[test]
+    fn test_md5_native() {
+        let (inputs, outputs) = INPUTS_OUTPUTS.clone();
+        for (input, output) in inputs.iter().zip(outputs) {
+            assert_eq!(native::md5(input), output.0);
+        }
+    }
+
+    fn test_md5_circuit_over<F: FieldExt>() {
+        let (inputs, outputs) = INPUTS_OUTPUTS.clone();
+        let k = Md5Chip::<F>::min_k(&Md5Witness {
+            inputs: inputs.clone(),
+            _marker: PhantomData,
+        });
+
+        let circuit: Md5TestCircuit<F> = Md5TestCircuit {
+            inputs,
+            outputs,
+            _marker: PhantomData,
+        };
+
+        let prover = MockProver::run(k, &circuit, vec![]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+    }
+
+    #[test]
+    fn test_md5_assigned_digest() {
+        let (inputs, outputs) = INPUTS_OUTPUTS.clone();
+        let witness = Md5Witness::<Fr> {
+            inputs,
+            _marker: PhantomData,
+        };
+        let verify = |digests: Vec<Vec<u8>>| {
+            let circuit = DigestCircuit::<Fr, Md5Chip<Fr>> {
+                witness: witness.clone(),
+                digests,
+                _marker: PhantomData,
+            };
+            let k = Md5Chip::<Fr>::min_k(&witness);
+            let instances = Md5Chip::<Fr>::public_inputs(&witness);
+            MockProver::run(k, &circuit, instances).unwrap().verify()
+        };
+        let mut digests: Vec<_> = outputs.iter().map(|output| output.0.to_vec()).collect();
+        assert_eq!(verify(digests.clone()), Ok(()));
+
+        // a byte of a digest is not the one of the call.
+        digests[3][0] ^= 1;
+        assert!(verify(digests).is_err());
+    }
+
+    #[test]
+    fn test_md5_hash_circuit_ext() {
+        let mut meta = ConstraintSystem::<Fr>::default();
+        let config = Md5Chip::<Fr>::configure(&mut meta);
+        assert_eq!(
+            Md5Chip::<Fr>::table_columns(&config).len(),
+            Md5Chip::<Fr>::annotations(&config).len()
+        );
+        assert_eq!(Md5Chip::<Fr>::DIGEST_BYTES, 16);
+    }
+
+    #[test]
+    fn test_md5_row_usage() {
+        let witness = Md5Witness::<Fr> {
+            inputs: vec![b"abc".to_vec(), vec![0; 64]],
+            _marker: PhantomData,
+        };
+        let usage = Md5Chip::<Fr>::row_usage(Md5Chip::<Fr>::min_k(&witness), &witness).unwrap();
+        // the initial hash value, then a slot per block of its flag, chaining
+        // value, 16 words, 256 rounds and 4 feed-forward rows and of the
+        // bytes of its digest, then a row per byte of every slot and a row
+        // per call in the table, between their sentinel rows.
+        let regions: Vec<_> = usage
+            .regions
+            .iter()
+            .map(|region| (region.name.as_str(), region.rows))
+            .collect();
+        assert_eq!(
+            regions,
+            [
+                ("md5 blocks", 4 + 3 * (1 + 4 + 276 + 4)),
+                ("md5 slots", 1 + 3 * 64 + 1),
+                ("md5 table", 1 + 2)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_md5_circuit() {
+        test_md5_circuit_over::<Fr>();
+        test_md5_circuit_over::<pallas::Base>();
+    }
+}
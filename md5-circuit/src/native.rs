@@ -0,0 +1,116 @@
+//! Native implementation of MD5, used to compute the witness of the circuit
+//! and as the reference it is tested against.
+
+/// Size of a message block in bytes.
+pub const BLOCK_BYTES: usize = 64;
+
+/// Size of a digest in bytes.
+pub const DIGEST_BYTES: usize = 16;
+
+/// Number of rounds of the compression function.
+pub const NUM_ROUNDS: usize = 64;
+
+/// Initial hash value of MD5.
+pub const IV: [u32; 4] = [0x67452301, 0xefcdab89, 0x98badcfe, 0x10325476];
+
+/// Left rotation amounts, each row being used for 16 consecutive rounds.
+pub const ROTATIONS: [[u32; 4]; 4] = [
+    [7, 12, 17, 22],
+    [5, 9, 14, 20],
+    [4, 11, 16, 23],
+    [6, 10, 15, 21],
+];
+
+/// Round constants, the integer parts of `2^32 * |sin(i + 1)|`.
+pub const ROUND_CONSTANTS: [u32; NUM_ROUNDS] = [
+    0xd76aa478, 0xe8c7b756, 0x242070db, 0xc1bdceee, 0xf57c0faf, 0x4787c62a, 0xa8304613, 0xfd469501,
+    0x698098d8, 0x8b44f7af, 0xffff5bb1, 0x895cd7be, 0x6b901122, 0xfd987193, 0xa679438e, 0x49b40821,
+    0xf61e2562, 0xc040b340, 0x265e5a51, 0xe9b6c7aa, 0xd62f105d, 0x02441453, 0xd8a1e681, 0xe7d3fbc8,
+    0x21e1cde6, 0xc33707d6, 0xf4d50d87, 0x455a14ed, 0xa9e3e905, 0xfcefa3f8, 0x676f02d9, 0x8d2a4c8a,
+    0xfffa3942, 0x8771f681, 0x6d9d6122, 0xfde5380c, 0xa4beea44, 0x4bdecfa9, 0xf6bb4b60, 0xbebfbc70,
+    0x289b7ec6, 0xeaa127fa, 0xd4ef3085, 0x04881d05, 0xd9d4d039, 0xe6db99e5, 0x1fa27cf8, 0xc4ac5665,
+    0xf4292244, 0x432aff97, 0xab9423a7, 0xfc93a039, 0x655b59c3, 0x8f0ccc92, 0xffeff47d, 0x85845dd1,
+    0x6fa87e4f, 0xfe2ce6e0, 0xa3014314, 0x4e0811a1, 0xf7537e82, 0xbd3af235, 0x2ad7d2bb, 0xeb86d391,
+];
+
+/// Pads `input` to a multiple of [`BLOCK_BYTES`] bytes: a `1` bit, zeros, and
+/// the length of the input in bits as a 64-bit little-endian integer.
+pub fn pad(input: &[u8]) -> Vec<u8> {
+    let mut padded = input.to_vec();
+    padded.push(0x80);
+    padded.resize(
+        (padded.len() + 8).div_ceil(BLOCK_BYTES) * BLOCK_BYTES - 8,
+        0,
+    );
+    padded.extend_from_slice(&((input.len() as u64) * 8).to_le_bytes());
+    padded
+}
+
+/// Reads a block as 16 little-endian words.
+pub fn message_words(block: &[u8]) -> [u32; 16] {
+    assert_eq!(block.len(), BLOCK_BYTES);
+    let mut m = [0u32; 16];
+    for (word, bytes) in m.iter_mut().zip(block.chunks(4)) {
+        *word = u32::from_le_bytes(bytes.try_into().expect("words are 4 bytes"));
+    }
+    m
+}
+
+/// Applies the 64 rounds of the compression function to `state`, returning
+/// the working variables before they are added back to `state`.
+pub fn compress_rounds(state: &[u32; 4], block: &[u8]) -> [u32; 4] {
+    let m = message_words(block);
+    let [mut a, mut b, mut c, mut d] = *state;
+    for i in 0..NUM_ROUNDS {
+        let (f, g) = match i / 16 {
+            0 => ((b & c) | (!b & d), i),
+            1 => ((d & b) | (!d & c), (5 * i + 1) % 16),
+            2 => (b ^ c ^ d, (3 * i + 5) % 16),
+            _ => (c ^ (b | !d), (7 * i) % 16),
+        };
+        let temp = a
+            .wrapping_add(f)
+            .wrapping_add(ROUND_CONSTANTS[i])
+            .wrapping_add(m[g]);
+        a = d;
+        d = c;
+        c = b;
+        b = b.wrapping_add(temp.rotate_left(ROTATIONS[i / 16][i % 4]));
+    }
+    [a, b, c, d]
+}
+
+/// Compresses `block` into `state`.
+pub fn compress(state: &mut [u32; 4], block: &[u8]) {
+    let working = compress_rounds(state, block);
+    for (word, working) in state.iter_mut().zip(working) {
+        *word = word.wrapping_add(working);
+    }
+}
+
+/// Computes the MD5 digest of `input`.
+pub fn md5(input: &[u8]) -> [u8; 16] {
+    let mut state = IV;
+    for block in pad(input).chunks(BLOCK_BYTES) {
+        compress(&mut state, block);
+    }
+
+    let mut digest = [0u8; 16];
+    for (bytes, word) in digest.chunks_mut(4).zip(state) {
+        bytes.copy_from_slice(&word.to_le_bytes());
+    }
+    digest
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pad() {
+        assert_eq!(pad(&[]).len(), BLOCK_BYTES);
+        assert_eq!(pad(&[0; BLOCK_BYTES - 9]).len(), BLOCK_BYTES);
+        assert_eq!(pad(&[0; BLOCK_BYTES - 8]).len(), 2 * BLOCK_BYTES);
+        assert_eq!(pad(b"abc")[BLOCK_BYTES - 8], 24);
+    }
+}
@@ -0,0 +1,213 @@
+//! The hash circuits a Merkle path can be verified with.
+
+use std::{fmt::Debug, marker::PhantomData};
+
+use gadgets::{
+    tables::RangeTable,
+    util::{rlc, AssignedValue},
+    AssignedBytes, HashCircuitExt,
+};
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{Layouter, Value},
+    plonk::{Advice, Challenge, Column, ConstraintSystem, Error, Expression, Selector},
+    poly::Rotation,
+};
+use poseidon_circuit::{
+    native::{self as poseidon, PoseidonParams},
+    partial_rounds, PoseidonChip, PoseidonWitness, FULL_ROUNDS,
+};
+use rlc_chip::{RlcChip, RlcConfig};
+use sha2_256_circuit::{native::sha256, Sha2Chip, Sha2Witness};
+
+/// A hash circuit that compresses two nodes of a Merkle tree into their
+/// parent.
+pub trait MerkleHasher<F: FieldExt>: HashCircuitExt<F> + Clone {
+    /// A node of the tree, i.e. a digest of the hash.
+    type Node: Clone + Debug + Default + PartialEq;
+
+    /// The config constraining the pairs of children to the calls of the
+    /// hash, along with the config of the circuit.
+    type PairConfig: Clone;
+
+    /// Native hash of the children `left` and `right` into their parent.
+    fn hash_pair(left: &Self::Node, right: &Self::Node) -> Self::Node;
+
+    /// Witness of the hash calls over the given pairs of children.
+    fn witness(pairs: &[(Self::Node, Self::Node)]) -> Self::Witness;
+
+    /// The field element a node is laid out as in the path, e.g. the RLC of
+    /// the bytes of a digest.
+    fn node_value(node: &Self::Node, challenge: Value<F>) -> Value<F>;
+
+    /// Constrains the hash of the node values in `left` and `right` into the
+    /// one in `parent`, on the rows where `q_enable` is enabled, where a node
+    /// value of the RLC of bytes is over `challenge`: either by a lookup into
+    /// the table of the circuit, or by the cells [`Self::assign_pairs`]
+    /// returns.
+    fn configure_pair(
+        config: &Self::Config,
+        meta: &mut ConstraintSystem<F>,
+        q_enable: Selector,
+        nodes: [Column<Advice>; 3],
+        challenge: Challenge,
+    ) -> Self::PairConfig;
+
+    /// Loads the hash calls of `witness`, and returns the cells of the
+    /// `[left, right, parent]` node values of every call, which the levels of
+    /// the paths are constrained to, or none if they are looked up.
+    fn assign_pairs(
+        config: &Self::Config,
+        pair_config: &Self::PairConfig,
+        layouter: &mut impl Layouter<F>,
+        witness: Self::Witness,
+    ) -> Result<Vec<[AssignedValue<F>; 3]>, Error>;
+}
+
+/// The RLCs of the children and the parent of every SHA2-256 call, over the
+/// challenge of the node values.
+#[derive(Clone, Copy, Debug)]
+pub struct Sha2PairConfig {
+    byte_table: RangeTable<8>,
+    rlc: RlcConfig,
+}
+
+/// A binary tree of SHA2-256, whose parents are the digests of the 64-byte
+/// concatenation of their children.
+impl<F: FieldExt> MerkleHasher<F> for Sha2Chip<F> {
+    type Node = [u8; 32];
+    type PairConfig = Sha2PairConfig;
+
+    fn hash_pair(left: &Self::Node, right: &Self::Node) -> Self::Node {
+        sha256(&[left.as_slice(), right.as_slice()].concat())
+    }
+
+    fn witness(pairs: &[(Self::Node, Self::Node)]) -> Self::Witness {
+        Sha2Witness {
+            inputs: pairs
+                .iter()
+                .map(|(left, right)| [left.as_slice(), right.as_slice()].concat())
+                .collect(),
+            _marker: PhantomData,
+        }
+    }
+
+    fn node_value(node: &Self::Node, challenge: Value<F>) -> Value<F> {
+        rlc(node, challenge)
+    }
+
+    /// The table of SHA2-256 only holds the digests, so the node values are
+    /// the RLCs of the halves of the input and of the digest of the calls.
+    fn configure_pair(
+        _config: &Self::Config,
+        meta: &mut ConstraintSystem<F>,
+        _q_enable: Selector,
+        _nodes: [Column<Advice>; 3],
+        challenge: Challenge,
+    ) -> Self::PairConfig {
+        let byte_table = RangeTable::construct(meta);
+        let rlc = RlcConfig::configure_with_challenge(meta, byte_table, challenge);
+        Sha2PairConfig { byte_table, rlc }
+    }
+
+    fn assign_pairs(
+        config: &Self::Config,
+        pair_config: &Self::PairConfig,
+        layouter: &mut impl Layouter<F>,
+        witness: Self::Witness,
+    ) -> Result<Vec<[AssignedValue<F>; 3]>, Error> {
+        pair_config.byte_table.load(layouter)?;
+        let calls = Sha2Chip::construct(config.clone(), witness).assign_calls(layouter)?;
+        let messages: Vec<_> = calls
+            .iter()
+            .flat_map(|call| {
+                let (left, right) = call.input.cells().split_at(32);
+                [
+                    AssignedBytes::new(left.to_vec()),
+                    AssignedBytes::new(right.to_vec()),
+                    call.digest.clone(),
+                ]
+            })
+            .collect();
+        let rlcs = RlcChip::construct(pair_config.rlc).assign_cells(layouter, &messages)?;
+        Ok(rlcs
+            .chunks(3)
+            .map(|rlcs| [rlcs[0].clone(), rlcs[1].clone(), rlcs[2].clone()])
+            .collect())
+    }
+}
+
+/// A binary tree of Poseidon, whose parents are the digests of the sponge
+/// over their two children.
+impl<F: FieldExt> MerkleHasher<F> for PoseidonChip<F, 3> {
+    type Node = F;
+    type PairConfig = ();
+
+    fn hash_pair(left: &Self::Node, right: &Self::Node) -> Self::Node {
+        let params = PoseidonParams::new(3, FULL_ROUNDS, partial_rounds(3));
+        poseidon::hash(&params, &[*left, *right])
+    }
+
+    fn witness(pairs: &[(Self::Node, Self::Node)]) -> Self::Witness {
+        PoseidonWitness {
+            inputs: pairs
+                .iter()
+                .map(|(left, right)| vec![*left, *right])
+                .collect(),
+            _marker: PhantomData,
+        }
+    }
+
+    fn node_value(node: &Self::Node, _challenge: Value<F>) -> Value<F> {
+        Value::known(*node)
+    }
+
+    /// Looks the pairs up from the table of Poseidon, whose input RLC is over
+    /// its own challenge.
+    fn configure_pair(
+        config: &Self::Config,
+        meta: &mut ConstraintSystem<F>,
+        q_enable: Selector,
+        [left, right, parent]: [Column<Advice>; 3],
+        _challenge: Challenge,
+    ) -> Self::PairConfig {
+        let table = config.table().clone();
+        let challenge = config.challenge();
+        meta.lookup_any("poseidon(left, right) == parent", |meta| {
+            let q_enable = meta.query_selector(q_enable);
+            let left = meta.query_advice(left, Rotation::cur());
+            let right = meta.query_advice(right, Rotation::cur());
+            let parent = meta.query_advice(parent, Rotation::cur());
+            let input_rlc = left * meta.query_challenge(challenge) + right;
+
+            vec![
+                (
+                    q_enable.clone(),
+                    meta.query_advice(table.is_enabled, Rotation::cur()),
+                ),
+                (
+                    q_enable.clone() * input_rlc,
+                    meta.query_advice(table.input_rlc, Rotation::cur()),
+                ),
+                (
+                    q_enable.clone() * Expression::Constant(F::from(2)),
+                    meta.query_advice(table.input_len, Rotation::cur()),
+                ),
+                (
+                    q_enable * parent,
+                    meta.query_advice(table.output, Rotation::cur()),
+                ),
+            ]
+        });
+    }
+
+    fn assign_pairs(
+        config: &Self::Config,
+        _pair_config: &Self::PairConfig,
+        layouter: &mut impl Layouter<F>,
+        witness: Self::Witness,
+    ) -> Result<Vec<[AssignedValue<F>; 3]>, Error> {
+        PoseidonChip::construct(config.clone(), witness).load(layouter)?;
+        Ok(vec![])
+    }
+}
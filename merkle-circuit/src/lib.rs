@@ -0,0 +1,581 @@
+//! The Merkle circuit verifies the membership of leaves in Merkle trees, i.e.
+//! that a leaf at a given index hashes up to a given root along the path of
+//! its siblings. It is generic over the hash circuits of the workspace
+//! through the [`MerkleHasher`] trait, which is implemented for the SHA2-256
+//! and the Poseidon circuits.
+//!
+//! A path of depth `d` is laid out over `d` rows, one per level from the leaf
+//! up, with the node `cur` at the level, its `sibling`, the `left` and
+//! `right` children ordered by the bit `is_right` of the index, and their
+//! `parent`, which is the node of the next level. The index of the leaf is
+//! accumulated from its bits along the path. The hash of the children into
+//! their parent is constrained by the [`MerkleHasher`], which configures and
+//! loads the hash circuit with every pair of children of the paths: the
+//! Poseidon pairs are looked up from its table, and the SHA2-256 ones are
+//! copied from the RLCs of the inputs and the digests of its calls.
+//!
+//! The circuit exposes the paths through `MerkleTable`:
+//! - `is_enabled`: whether the row holds a path;
+//! - `leaf`: the leaf, as laid out by [`MerkleHasher::node_value`];
+//! - `index`: the index of the leaf;
+//! - `root`: the root of the tree.
+//!
+//! The leaf, the index and the root are copied from the layout of the path,
+//! and `is_enabled` and the disabled first row are assigned from constants.
+
+pub mod hasher;
+pub mod native;
+
+use std::marker::PhantomData;
+
+use constraint_builder::{BaseConstraintBuilder, Expr};
+use gadgets::{tables::FixedTables, util::AssignedValue};
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{Layouter, Value},
+    plonk::{
+        Advice, Any, Challenge, Column, ConstraintSystem, Error, FirstPhase, SecondPhase, Selector,
+    },
+    poly::Rotation,
+};
+
+pub use hasher::MerkleHasher;
+pub use native::{MerklePath, MerkleTree, PathNodes};
+
+#[derive(Clone, Debug)]
+pub struct MerkleTable {
+    is_enabled: Column<Advice>,
+    leaf: Column<Advice>,
+    index: Column<Advice>,
+    root: Column<Advice>,
+}
+
+impl MerkleTable {
+    pub fn construct<F: FieldExt>(meta: &mut ConstraintSystem<F>) -> Self {
+        let is_enabled = meta.advice_column();
+        let index = meta.advice_column();
+        let leaf = meta.advice_column_in(SecondPhase);
+        let root = meta.advice_column_in(SecondPhase);
+        for column in [is_enabled, leaf, index, root] {
+            meta.enable_equality(column);
+        }
+
+        Self {
+            is_enabled,
+            leaf,
+            index,
+            root,
+        }
+    }
+
+    pub fn columns(&self) -> Vec<Column<Any>> {
+        vec![
+            self.is_enabled.into(),
+            self.leaf.into(),
+            self.index.into(),
+            self.root.into(),
+        ]
+    }
+
+    pub fn annotations(&self) -> Vec<String> {
+        vec![
+            String::from("is_enabled"),
+            String::from("leaf"),
+            String::from("index"),
+            String::from("root"),
+        ]
+    }
+}
+
+#[derive(Clone)]
+pub struct MerklePathConfig<F: FieldExt, H: MerkleHasher<F>> {
+    table: MerkleTable,
+    /// The fixed tables of the hash circuit, whose table is not exposed.
+    tables: FixedTables,
+    hash: H::Config,
+    pair: H::PairConfig,
+    q_table: Selector,
+    q_level: Selector, // enabled on every level of a path.
+    q_first: Selector, // enabled on the level of the leaf.
+    q_step: Selector,  // enabled on all but the level of the leaf.
+    is_right: Column<Advice>,
+    cur: Column<Advice>,
+    sibling: Column<Advice>,
+    left: Column<Advice>,
+    right: Column<Advice>,
+    parent: Column<Advice>,
+    pow: Column<Advice>,
+    index: Column<Advice>,
+    challenge: Challenge,
+}
+
+impl<F: FieldExt, H: MerkleHasher<F>> MerklePathConfig<F, H> {
+    pub fn configure(meta: &mut ConstraintSystem<F>, table: MerkleTable) -> Self {
+        let mut tables = FixedTables::default();
+        let hash = H::configure_with_tables(meta, &mut tables);
+
+        let q_table = meta.selector();
+        let q_level = meta.complex_selector();
+        let [q_first, q_step] = [0; 2].map(|_| meta.selector());
+        let [is_right, pow, index] = [0; 3].map(|_| meta.advice_column());
+        let [cur, sibling, left, right, parent] =
+            [0; 5].map(|_| meta.advice_column_in(SecondPhase));
+        for column in [cur, left, right, parent, index] {
+            meta.enable_equality(column);
+        }
+        let challenge = meta.challenge_usable_after(FirstPhase);
+
+        meta.create_gate("children are ordered by the index bit", |meta| {
+            let mut cb = BaseConstraintBuilder::default();
+            let is_right = meta.query_advice(is_right, Rotation::cur());
+            let cur = meta.query_advice(cur, Rotation::cur());
+            let sibling = meta.query_advice(sibling, Rotation::cur());
+
+            cb.require_boolean("is_right is boolean", is_right.clone());
+            cb.require_equal(
+                "left == is_right ? sibling : cur",
+                meta.query_advice(left, Rotation::cur()),
+                cur.clone() + is_right.clone() * (sibling.clone() - cur.clone()),
+            );
+            cb.require_equal(
+                "right == is_right ? cur : sibling",
+                meta.query_advice(right, Rotation::cur()),
+                sibling.clone() + is_right * (cur - sibling),
+            );
+            cb.gate(meta.query_selector(q_level))
+        });
+
+        meta.create_gate("the index starts from the bit of the leaf", |meta| {
+            let mut cb = BaseConstraintBuilder::default();
+            cb.require_equal(
+                "pow == 1",
+                meta.query_advice(pow, Rotation::cur()),
+                1.expr(),
+            );
+            cb.require_equal(
+                "index == is_right",
+                meta.query_advice(index, Rotation::cur()),
+                meta.query_advice(is_right, Rotation::cur()),
+            );
+            cb.gate(meta.query_selector(q_first))
+        });
+
+        meta.create_gate("the levels are chained", |meta| {
+            let mut cb = BaseConstraintBuilder::default();
+            let pow_cur = meta.query_advice(pow, Rotation::cur());
+            cb.require_equal(
+                "cur == parent::prev",
+                meta.query_advice(cur, Rotation::cur()),
+                meta.query_advice(parent, Rotation::prev()),
+            );
+            cb.require_equal(
+                "pow == pow::prev * 2",
+                pow_cur.clone(),
+                meta.query_advice(pow, Rotation::prev()) * 2.expr(),
+            );
+            cb.require_equal(
+                "index == index::prev + is_right * pow",
+                meta.query_advice(index, Rotation::cur()),
+                meta.query_advice(index, Rotation::prev())
+                    + meta.query_advice(is_right, Rotation::cur()) * pow_cur,
+            );
+            cb.gate(meta.query_selector(q_step))
+        });
+
+        let pair = H::configure_pair(&hash, meta, q_level, [left, right, parent], challenge);
+
+        meta.create_gate("validity check over the table rows", |meta| {
+            let mut cb = BaseConstraintBuilder::default();
+            let is_enabled = meta.query_advice(table.is_enabled, Rotation::cur());
+
+            cb.require_boolean("is_enabled is boolean", is_enabled.clone());
+            cb.condition(1.expr() - is_enabled, |cb| {
+                for (name, column) in [
+                    ("leaf == 0 if !is_enabled", table.leaf),
+                    ("index == 0 if !is_enabled", table.index),
+                    ("root == 0 if !is_enabled", table.root),
+                ] {
+                    cb.require_zero(name, meta.query_advice(column, Rotation::cur()));
+                }
+            });
+
+            cb.gate(meta.query_selector(q_table))
+        });
+
+        Self {
+            table,
+            tables,
+            hash,
+            pair,
+            q_table,
+            q_level,
+            q_first,
+            q_step,
+            is_right,
+            cur,
+            sibling,
+            left,
+            right,
+            parent,
+            pow,
+            index,
+            challenge,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct MerkleWitness<N> {
+    pub paths: Vec<MerklePath<N>>,
+}
+
+#[derive(Clone)]
+pub struct MerklePathChip<F: FieldExt, H: MerkleHasher<F>> {
+    config: MerklePathConfig<F, H>,
+    data: MerkleWitness<H::Node>,
+}
+
+impl<F: FieldExt, H: MerkleHasher<F>> MerklePathChip<F, H> {
+    pub fn construct(config: MerklePathConfig<F, H>, data: MerkleWitness<H::Node>) -> Self {
+        Self { config, data }
+    }
+
+    /// Witness of the hash circuit, with every pair of children of the paths.
+    fn hash_witness(witness: &MerkleWitness<H::Node>) -> H::Witness {
+        let pairs: Vec<_> = witness
+            .paths
+            .iter()
+            .flat_map(|path| path.nodes::<F, H>().1)
+            .collect();
+        H::witness(&pairs)
+    }
+
+    pub fn load(&self, layouter: &mut impl Layouter<F>) -> Result<(), Error> {
+        let config = &self.config;
+        config.tables.load(layouter)?;
+        let pair_cells = H::assign_pairs(
+            &config.hash,
+            &config.pair,
+            layouter,
+            Self::hash_witness(&self.data),
+        )?;
+
+        let challenge = layouter.get_challenge(config.challenge);
+        let mut levels = 0;
+        let cells = self
+            .data
+            .paths
+            .iter()
+            .enumerate()
+            .map(|(id, path)| {
+                let pairs = pair_cells.get(levels..levels + path.depth()).unwrap_or(&[]);
+                levels += path.depth();
+                self.assign_path(layouter, id, path, path.nodes::<F, H>(), pairs, challenge)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let table = &config.table;
+        layouter.assign_region(
+            || "merkle table",
+            |mut region| {
+                for (annotation, column) in [
+                    ("is_enabled", table.is_enabled),
+                    ("leaf", table.leaf),
+                    ("index", table.index),
+                    ("root", table.root),
+                ] {
+                    region.assign_advice_from_constant(|| annotation, column, 0, F::zero())?;
+                }
+                config.q_table.enable(&mut region, 0)?;
+
+                for (i, [leaf, index, root]) in cells.iter().enumerate() {
+                    let offset = i + 1;
+                    config.q_table.enable(&mut region, offset)?;
+                    region.assign_advice_from_constant(
+                        || "is_enabled",
+                        table.is_enabled,
+                        offset,
+                        F::one(),
+                    )?;
+                    leaf.copy_advice(|| "leaf", &mut region, table.leaf, offset)?;
+                    index.copy_advice(|| "index", &mut region, table.index, offset)?;
+                    root.copy_advice(|| "root", &mut region, table.root, offset)?;
+                }
+                Ok(())
+            },
+        )
+    }
+
+    /// Assigns the levels of the `id`-th path with its `nodes` and children,
+    /// as computed by [`MerklePath::nodes`], constraining their children and
+    /// parent to the cells of `pairs` if any, and returns the cells of its
+    /// leaf, index and root.
+    fn assign_path(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        id: usize,
+        path: &MerklePath<H::Node>,
+        (nodes, children): PathNodes<H::Node>,
+        pairs: &[[AssignedValue<F>; 3]],
+        challenge: Value<F>,
+    ) -> Result<[AssignedValue<F>; 3], Error> {
+        assert!(path.depth() > 0, "a path has at least one level");
+        let config = &self.config;
+
+        layouter.assign_region(
+            || format!("merkle path {}", id),
+            |mut region| {
+                let mut index = 0;
+                let mut cells: Option<[AssignedValue<F>; 3]> = None;
+                for (level, (sibling, (left, right))) in
+                    path.siblings.iter().zip(&children).enumerate()
+                {
+                    config.q_level.enable(&mut region, level)?;
+                    if level == 0 {
+                        config.q_first.enable(&mut region, level)?;
+                    } else {
+                        config.q_step.enable(&mut region, level)?;
+                    }
+
+                    let is_right = path.is_right(level);
+                    index += (is_right as u64) << level;
+                    for (annotation, column, value) in [
+                        ("is_right", config.is_right, F::from(is_right as u64)),
+                        ("pow", config.pow, F::from(1 << level)),
+                    ] {
+                        region.assign_advice(
+                            || annotation,
+                            column,
+                            level,
+                            || Value::known(value),
+                        )?;
+                    }
+                    let index_cell = region.assign_advice(
+                        || "index",
+                        config.index,
+                        level,
+                        || Value::known(F::from(index)),
+                    )?;
+
+                    let mut node_cells = [
+                        ("cur", config.cur, &nodes[level]),
+                        ("sibling", config.sibling, sibling),
+                        ("left", config.left, left),
+                        ("right", config.right, right),
+                        ("parent", config.parent, &nodes[level + 1]),
+                    ]
+                    .into_iter()
+                    .map(|(annotation, column, node)| {
+                        region.assign_advice(
+                            || annotation,
+                            column,
+                            level,
+                            || H::node_value(node, challenge),
+                        )
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+                    if let Some(pair) = pairs.get(level) {
+                        for (node, pair) in node_cells[2..].iter().zip(pair) {
+                            region.constrain_equal(node.cell(), pair.cell())?;
+                        }
+                    }
+
+                    let parent_cell = node_cells.pop().expect("the parent is assigned");
+                    let leaf_cell = match cells {
+                        Some([leaf, _, _]) => leaf,
+                        None => node_cells.swap_remove(0),
+                    };
+                    cells = Some([leaf_cell, index_cell, parent_cell]);
+                }
+                Ok(cells.expect("a path has at least one level"))
+            },
+        )
+    }
+}
+
+#[cfg(any(feature = "test", test))]
+pub mod dev {
+    use super::*;
+
+    use halo2_proofs::{circuit::SimpleFloorPlanner, plonk::Circuit};
+
+    pub struct MerkleTestCircuit<F: FieldExt, H: MerkleHasher<F>> {
+        pub paths: Vec<MerklePath<H::Node>>,
+        pub _marker: PhantomData<F>,
+    }
+
+    impl<F: FieldExt, H: MerkleHasher<F>> Circuit<F> for MerkleTestCircuit<F, H> {
+        type Config = MerklePathConfig<F, H>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self {
+                paths: self.paths.clone(),
+                _marker: PhantomData,
+            }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            let table = MerkleTable::construct(meta);
+            MerklePathConfig::configure(meta, table)
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            let chip = MerklePathChip::construct(
+                config,
+                MerkleWitness {
+                    paths: self.paths.clone(),
+                },
+            );
+            chip.load(&mut layouter)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use gadgets::rows;
+    use halo2_proofs::{
+        circuit::SimpleFloorPlanner,
+        dev::MockProver,
+        halo2curves::{bn256::Fr, pasta::pallas},
+        plonk::Circuit,
+    };
+    use poseidon_circuit::PoseidonChip;
+    use sha2_256_circuit::Sha2Chip;
+    use std::marker::PhantomData;
+
+    use super::dev::MerkleTestCircuit;
+    use super::*;
+    use mutation_coverage::mutation_coverage;
+
+    fn run<F: FieldExt, H: MerkleHasher<F>>(
+        paths: Vec<MerklePath<H::Node>>,
+    ) -> Result<(), Vec<String>> {
+        let circuit = MerkleTestCircuit::<F, H> {
+            paths,
+            _marker: PhantomData,
+        };
+        let prover = MockProver::run(rows::min_k(&circuit), &circuit, vec![]).unwrap();
+        prover
+            .verify()
+            .map_err(|errors| errors.iter().map(|e| e.to_string()).collect())
+    }
+
+    fn test_merkle_path_poseidon_over<F: FieldExt>() {
+        let leaves = (1..=8).map(F::from).collect();
+        let tree = MerkleTree::new::<F, PoseidonChip<F, 3>>(leaves);
+        let paths = [0, 5, 7].map(|index| tree.path(index)).to_vec();
+        assert_eq!(run::<F, PoseidonChip<F, 3>>(paths), Ok(()));
+    }
+
+    /// A path of one level whose parent is the digest of another pair than
+    /// its children, with the Poseidon witness hashing that other pair.
+    struct ForgedPairCircuit<F: FieldExt> {
+        path: MerklePath<F>,
+        preimage: (F, F),
+    }
+
+    impl<F: FieldExt> Circuit<F> for ForgedPairCircuit<F> {
+        type Config = MerklePathConfig<F, PoseidonChip<F, 3>>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self {
+                path: self.path.clone(),
+                preimage: self.preimage,
+            }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            let table = MerkleTable::construct(meta);
+            MerklePathConfig::configure(meta, table)
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            type H<F> = PoseidonChip<F, 3>;
+            H::assign_pairs(
+                &config.hash,
+                &config.pair,
+                &mut layouter,
+                H::witness(&[self.preimage]),
+            )?;
+            let challenge = layouter.get_challenge(config.challenge);
+            let (left, right) = (self.path.leaf, self.path.siblings[0]);
+            let parent = H::hash_pair(&self.preimage.0, &self.preimage.1);
+            let chip = MerklePathChip::construct(config, MerkleWitness { paths: vec![] });
+            chip.assign_path(
+                &mut layouter,
+                0,
+                &self.path,
+                (vec![left, parent], vec![(left, right)]),
+                &[],
+                challenge,
+            )?;
+            Ok(())
+        }
+    }
+
+    fn test_merkle_path_forged_pair_over<F: FieldExt>() {
+        let circuit = ForgedPairCircuit {
+            path: MerklePath {
+                leaf: F::from(1),
+                index: 0,
+                siblings: vec![F::from(2)],
+            },
+            preimage: (F::from(3), F::from(4)),
+        };
+        let prover = MockProver::run(rows::min_k(&circuit), &circuit, vec![]).unwrap();
+        let errors = prover.verify().expect_err("the pair is not the preimage");
+        assert!(errors
+            .iter()
+            .all(|e| e.to_string().contains("poseidon(left, right) == parent")));
+    }
+
+    fn test_merkle_path_sha2_over<F: FieldExt>() {
+        let leaves = (0..4).map(|i| [i; 32]).collect();
+        let tree = MerkleTree::new::<F, Sha2Chip<F>>(leaves);
+        let paths = [0, 3].map(|index| tree.path(index)).to_vec();
+        assert_eq!(run::<F, Sha2Chip<F>>(paths), Ok(()));
+    }
+
+    #[test]
+    fn test_merkle_path_poseidon() {
+        test_merkle_path_poseidon_over::<Fr>();
+        test_merkle_path_poseidon_over::<pallas::Base>();
+    }
+
+    #[test]
+    fn test_merkle_path_forged_pair() {
+        test_merkle_path_forged_pair_over::<Fr>();
+        test_merkle_path_forged_pair_over::<pallas::Base>();
+    }
+
+    #[test]
+    fn test_merkle_path_sha2() {
+        test_merkle_path_sha2_over::<Fr>();
+        test_merkle_path_sha2_over::<pallas::Base>();
+    }
+
+    #[test]
+    fn test_merkle_path_coverage() {
+        // Every cell of the path, of the table and of the Poseidon calls of
+        // its pairs is constrained.
+        let leaves = (1..=2).map(Fr::from).collect();
+        let tree = MerkleTree::new::<Fr, PoseidonChip<Fr, 3>>(leaves);
+        let circuit = MerkleTestCircuit::<Fr, PoseidonChip<Fr, 3>> {
+            paths: vec![tree.path(1)],
+            _marker: PhantomData,
+        };
+        let report = mutation_coverage(rows::min_k(&circuit), &circuit, vec![]).unwrap();
+        assert_eq!(report.unconstrained().count(), 0, "{}", report.report());
+    }
+}
@@ -0,0 +1,124 @@
+//! Native Merkle trees and paths, used to compute the witness of the circuit
+//! and as the reference it is tested against.
+
+use halo2_proofs::arithmetic::FieldExt;
+
+use crate::hasher::MerkleHasher;
+
+/// The nodes of a path from the leaf to the root, and the `(left, right)`
+/// children hashed into each of them but the leaf.
+pub type PathNodes<N> = (Vec<N>, Vec<(N, N)>);
+
+/// The path from a leaf to the root of a tree: the siblings of the leaf and
+/// of its ancestors, from the bottom of the tree up, and the index of the
+/// leaf, whose `i`-th bit is set if the node at the `i`-th level is a right
+/// child.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct MerklePath<N> {
+    pub leaf: N,
+    pub index: u64,
+    pub siblings: Vec<N>,
+}
+
+impl<N: Clone> MerklePath<N> {
+    pub fn depth(&self) -> usize {
+        self.siblings.len()
+    }
+
+    /// Whether the node at level `level` is a right child.
+    pub fn is_right(&self, level: usize) -> bool {
+        (self.index >> level) & 1 == 1
+    }
+
+    /// The node at every level of the path, from the leaf to the root, and
+    /// the `(left, right)` children hashed into each of them but the leaf.
+    pub fn nodes<F: FieldExt, H: MerkleHasher<F, Node = N>>(&self) -> PathNodes<N> {
+        let mut nodes = vec![self.leaf.clone()];
+        let mut pairs = Vec::with_capacity(self.depth());
+        for (level, sibling) in self.siblings.iter().enumerate() {
+            let node = nodes.last().expect("the path starts from the leaf").clone();
+            let pair = if self.is_right(level) {
+                (sibling.clone(), node)
+            } else {
+                (node, sibling.clone())
+            };
+            nodes.push(H::hash_pair(&pair.0, &pair.1));
+            pairs.push(pair);
+        }
+        (nodes, pairs)
+    }
+
+    pub fn root<F: FieldExt, H: MerkleHasher<F, Node = N>>(&self) -> N {
+        let (mut nodes, _) = self.nodes::<F, H>();
+        nodes.pop().expect("the path starts from the leaf")
+    }
+}
+
+/// A complete binary tree over `2^depth` leaves, stored level by level from
+/// the leaves up.
+#[derive(Clone, Debug)]
+pub struct MerkleTree<N> {
+    levels: Vec<Vec<N>>,
+}
+
+impl<N: Clone> MerkleTree<N> {
+    pub fn new<F: FieldExt, H: MerkleHasher<F, Node = N>>(leaves: Vec<N>) -> Self {
+        assert!(
+            leaves.len().is_power_of_two(),
+            "the tree is complete over a power of two leaves"
+        );
+        let mut levels = vec![leaves];
+        while levels.last().expect("there are leaves").len() > 1 {
+            let parents = levels
+                .last()
+                .expect("there are leaves")
+                .chunks(2)
+                .map(|pair| H::hash_pair(&pair[0], &pair[1]))
+                .collect();
+            levels.push(parents);
+        }
+        Self { levels }
+    }
+
+    pub fn depth(&self) -> usize {
+        self.levels.len() - 1
+    }
+
+    pub fn root(&self) -> N {
+        self.levels[self.depth()][0].clone()
+    }
+
+    /// The path from the `index`-th leaf to the root.
+    pub fn path(&self, index: u64) -> MerklePath<N> {
+        let siblings = (0..self.depth())
+            .map(|level| self.levels[level][((index >> level) ^ 1) as usize].clone())
+            .collect();
+        MerklePath {
+            leaf: self.levels[0][index as usize].clone(),
+            index,
+            siblings,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use halo2_proofs::halo2curves::bn256::Fr;
+    use sha2_256_circuit::Sha2Chip;
+
+    #[test]
+    fn test_merkle_tree() {
+        let leaves: Vec<[u8; 32]> = (0..8).map(|i| [i; 32]).collect();
+        let tree = MerkleTree::new::<Fr, Sha2Chip<Fr>>(leaves);
+        assert_eq!(tree.depth(), 3);
+        for index in 0..8 {
+            let path = tree.path(index);
+            assert_eq!(path.root::<Fr, Sha2Chip<Fr>>(), tree.root());
+        }
+
+        let mut path = tree.path(5);
+        path.index = 4;
+        assert_ne!(path.root::<Fr, Sha2Chip<Fr>>(), tree.root());
+    }
+}
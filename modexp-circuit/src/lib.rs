@@ -0,0 +1,983 @@
+//! The modexp circuit verifies calls to the [`MODEXP`] precompiled contract at
+//! address `0x05`, which computes `base^exponent mod modulus` for big-endian
+//! integers. Like the zkEVM, the circuit supports operands of at most 32 bytes.
+//!
+//! Every call is computed with the left-to-right square-and-multiply
+//! algorithm. For every bit of the exponent, the accumulator is squared and
+//! then multiplied by either the base or one, depending on the bit. Each of
+//! those modular multiplications occupies one row, which constrains
+//! `a * b = q * m + r` over 8 limbs of 32 bits:
+//! - the product is checked limb by limb with signed carries, which are range
+//!   checked to 40 bits after adding an offset of `2^39`;
+//! - the limbs of `q` and `r` are range checked to 32 bits;
+//! - `r < m` is checked by range checking the limbs of `d = m - r - 1`.
+//!
+//! The rows are chained so that the accumulator starts at one, the operand
+//! of every multiplication is the result of the previous one, and the modulus
+//! and the base are the same for all the rows of a call. Every byte of the
+//! exponent is laid out on the row of the squaring of its first bit, and is
+//! constrained to the bits of its 8 multiplications.
+//!
+//! The base, the modulus and the result are laid out as 32 big-endian bytes
+//! each, which are composed into the limbs of the first row and of the result
+//! of the last row of the call. The RLCs of the bytes of the operands are
+//! accumulated by an [`RlcChip`], which also range checks the bytes, and are
+//! copied into the table. The leading zero bytes of a word do not change its
+//! RLC, so that it is the one of the bytes of the call.
+//!
+//! A call with a zero modulus takes no rows but its row of the table, which
+//! is constrained to have a zero `modulus_rlc` and `result_rlc`: the output
+//! of such a call is zero whatever its base and exponent.
+//!
+//! The circuit exposes the calls through `ModExpTable`:
+//! - `is_enabled`: whether the row holds a call;
+//! - `base_rlc`, `exponent_rlc` and `modulus_rlc`: the RLCs of the input bytes;
+//! - `result_rlc`: the RLC of the output bytes, which are as many as the bytes
+//!   of the modulus.
+//!
+//! [`MODEXP`]: https://eips.ethereum.org/EIPS/eip-198
+
+pub mod native;
+
+use std::marker::PhantomData;
+
+use constraint_builder::{BaseConstraintBuilder, Expr};
+use gadgets::{
+    tables::{FixedTables, RangeTable},
+    util::{pow_of_two, rlc, AssignedValue},
+    AssignedBytes, DecomposeChip, DecomposeConfig,
+};
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{Layouter, Region, Value},
+    plonk::{
+        Advice, Any, Challenge, Column, ConstraintSystem, Error, Expression, FirstPhase,
+        SecondPhase, Selector, VirtualCells,
+    },
+    poly::Rotation,
+};
+use rlc_chip::{RlcChip, RlcConfig};
+
+use native::{Limbs, MulModStep, LIMB_BITS, NUM_LIMBS, WORD_BYTES};
+
+/// Number of carries of the limb-wise product `a * b - q * m - r`.
+const NUM_CARRIES: usize = 2 * NUM_LIMBS - 2;
+
+/// Bits of a range-checked carry. The carries are signed, so they are offset
+/// by `2^(CARRY_BITS - 1)` before being range checked.
+const CARRY_BITS: usize = 40;
+
+/// Bytes of a limb.
+const LIMB_BYTES: usize = LIMB_BITS / 8;
+
+#[derive(Clone, Debug)]
+pub struct ModExpTable {
+    is_enabled: Column<Advice>,
+    base_rlc: Column<Advice>,
+    exponent_rlc: Column<Advice>,
+    modulus_rlc: Column<Advice>,
+    result_rlc: Column<Advice>,
+}
+
+impl ModExpTable {
+    pub fn construct<F: FieldExt>(meta: &mut ConstraintSystem<F>) -> Self {
+        let is_enabled = meta.advice_column();
+        let [base_rlc, exponent_rlc, modulus_rlc, result_rlc] = [0; 4].map(|_| {
+            let column = meta.advice_column_in(SecondPhase);
+            meta.enable_equality(column);
+            column
+        });
+        Self {
+            is_enabled,
+            base_rlc,
+            exponent_rlc,
+            modulus_rlc,
+            result_rlc,
+        }
+    }
+
+    pub fn columns(&self) -> Vec<Column<Any>> {
+        vec![
+            self.is_enabled.into(),
+            self.base_rlc.into(),
+            self.exponent_rlc.into(),
+            self.modulus_rlc.into(),
+            self.result_rlc.into(),
+        ]
+    }
+
+    pub fn annotations(&self) -> Vec<String> {
+        vec![
+            String::from("is_enabled"),
+            String::from("base_rlc"),
+            String::from("exponent_rlc"),
+            String::from("modulus_rlc"),
+            String::from("result_rlc"),
+        ]
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct ModExpConfig<F> {
+    table: ModExpTable,
+    /// The fixed tables the chip loads itself, i.e. none if they are shared
+    /// with other circuits.
+    tables: FixedTables,
+    limb_range: DecomposeConfig<8, 4>,
+    carry_range: DecomposeConfig<8, 5>,
+    rlc: RlcConfig,
+    q_table: Selector,
+    q_zero_modulus: Selector, // enabled on the table rows of a zero modulus.
+    q_mul: Selector,          // enabled on every modular multiplication.
+    q_square: Selector,       // enabled on the squarings.
+    q_multiply: Selector,     // enabled on the multiplications by the base or one.
+    q_first: Selector,        // enabled on the first row of a call.
+    q_chain: Selector,        // enabled on all but the last row of a call.
+    q_exponent_byte: Selector, // enabled on the squaring of the first bit of a byte.
+    q_limb: Selector,         // enabled on the first byte of a limb of an operand.
+    a: [Column<Advice>; NUM_LIMBS],
+    b: [Column<Advice>; NUM_LIMBS],
+    m: [Column<Advice>; NUM_LIMBS],
+    q: [Column<Advice>; NUM_LIMBS],
+    r: [Column<Advice>; NUM_LIMBS],
+    d: [Column<Advice>; NUM_LIMBS],
+    base: [Column<Advice>; NUM_LIMBS],
+    carries: [Column<Advice>; NUM_CARRIES],
+    lt_carries: [Column<Advice>; NUM_LIMBS],
+    bit: Column<Advice>,
+    exponent_byte: Column<Advice>,
+    operand_byte: Column<Advice>,
+    operand_limb: Column<Advice>,
+    challenge: Challenge,
+    _marker: PhantomData<F>,
+}
+
+fn query_limbs<F: FieldExt>(
+    meta: &mut VirtualCells<'_, F>,
+    columns: &[Column<Advice>; NUM_LIMBS],
+    rotation: Rotation,
+) -> [Expression<F>; NUM_LIMBS] {
+    columns.map(|column| meta.query_advice(column, rotation))
+}
+
+impl<F: FieldExt> ModExpConfig<F> {
+    pub fn configure(meta: &mut ConstraintSystem<F>, table: ModExpTable) -> Self {
+        let mut tables = FixedTables::default();
+        let config = Self::configure_with_tables(meta, table, &mut tables);
+        Self { tables, ..config }
+    }
+
+    /// Configures the circuit over the fixed tables requested from `tables`,
+    /// which the caller loads once for all the circuits sharing them.
+    pub fn configure_with_tables(
+        meta: &mut ConstraintSystem<F>,
+        table: ModExpTable,
+        tables: &mut FixedTables,
+    ) -> Self {
+        let byte_table: RangeTable<8> = tables.range(meta);
+        let limb_range = DecomposeConfig::configure(meta, byte_table);
+        let carry_range = DecomposeConfig::configure(meta, byte_table);
+
+        let [q_table, q_zero_modulus] = [0; 2].map(|_| meta.selector());
+        let [q_mul, q_square, q_multiply, q_first, q_chain, q_exponent_byte, q_limb] =
+            [0; 7].map(|_| meta.selector());
+        let [a, b, m, q, r, d, base] = [0; 7].map(|_| [0; NUM_LIMBS].map(|_| meta.advice_column()));
+        let carries = [0; NUM_CARRIES].map(|_| meta.advice_column());
+        let lt_carries = [0; NUM_LIMBS].map(|_| meta.advice_column());
+        let [bit, exponent_byte, operand_byte, operand_limb] = [0; 4].map(|_| meta.advice_column());
+        for column in [m, q, r, d, base]
+            .iter()
+            .flatten()
+            .chain(carries.iter())
+            .chain([exponent_byte, operand_byte, operand_limb].iter())
+        {
+            meta.enable_equality(*column);
+        }
+        // The RLC chip assigns its flags from constants.
+        let constants = meta.fixed_column();
+        meta.enable_constant(constants);
+        let challenge = meta.challenge_usable_after(FirstPhase);
+        let rlc = RlcConfig::configure_with_challenge(meta, byte_table, challenge);
+
+        meta.create_gate("a * b == q * m + r", |meta| {
+            let mut cb = BaseConstraintBuilder::default();
+            let [a, b, m, q, r] =
+                [a, b, m, q, r].map(|limbs| query_limbs(meta, &limbs, Rotation::cur()));
+            let carries = carries.map(|carry| {
+                meta.query_advice(carry, Rotation::cur())
+                    - Expression::Constant(pow_of_two::<F>(CARRY_BITS - 1))
+            });
+
+            for k in 0..=NUM_CARRIES {
+                let mut t = (0..NUM_LIMBS)
+                    .filter_map(|i| k.checked_sub(i).filter(|j| *j < NUM_LIMBS).map(|j| (i, j)))
+                    .fold(0.expr(), |acc, (i, j)| {
+                        acc + a[i].clone() * b[j].clone() - q[i].clone() * m[j].clone()
+                    });
+                if k < NUM_LIMBS {
+                    t = t - r[k].clone();
+                }
+                if k > 0 {
+                    t = t + carries[k - 1].clone();
+                }
+                if k < NUM_CARRIES {
+                    t = t - carries[k].clone() * Expression::Constant(pow_of_two::<F>(LIMB_BITS));
+                }
+                cb.require_zero("limb k of a * b - q * m - r is carried over", t);
+            }
+
+            cb.gate(meta.query_selector(q_mul))
+        });
+
+        meta.create_gate("r + d + 1 == m, i.e. r < m", |meta| {
+            let mut cb = BaseConstraintBuilder::default();
+            let [m, r, d, lt_carries] =
+                [m, r, d, lt_carries].map(|limbs| query_limbs(meta, &limbs, Rotation::cur()));
+
+            for i in 0..NUM_LIMBS {
+                let carry_in = if i == 0 {
+                    1.expr()
+                } else {
+                    lt_carries[i - 1].clone()
+                };
+                cb.require_boolean("lt_carry is boolean", lt_carries[i].clone());
+                cb.require_equal(
+                    "r_i + d_i + carry_in == m_i + lt_carry_i * 2^32",
+                    r[i].clone() + d[i].clone() + carry_in,
+                    m[i].clone()
+                        + lt_carries[i].clone() * Expression::Constant(pow_of_two::<F>(LIMB_BITS)),
+                );
+            }
+            cb.require_zero(
+                "r + d + 1 does not overflow",
+                lt_carries[NUM_LIMBS - 1].clone(),
+            );
+
+            cb.gate(meta.query_selector(q_mul))
+        });
+
+        meta.create_gate("squaring multiplies a by itself", |meta| {
+            let mut cb = BaseConstraintBuilder::default();
+            let [a, b] = [a, b].map(|limbs| query_limbs(meta, &limbs, Rotation::cur()));
+            for (a, b) in a.into_iter().zip(b) {
+                cb.require_equal("b == a", b, a);
+            }
+            cb.gate(meta.query_selector(q_square))
+        });
+
+        meta.create_gate("multiplication by the base if the bit is set", |meta| {
+            let mut cb = BaseConstraintBuilder::default();
+            let [b, base] = [b, base].map(|limbs| query_limbs(meta, &limbs, Rotation::cur()));
+            let bit = meta.query_advice(bit, Rotation::cur());
+
+            cb.require_boolean("bit is boolean", bit.clone());
+            for (i, (b, base)) in b.into_iter().zip(base).enumerate() {
+                cb.require_equal(
+                    "b == bit ? base : 1",
+                    b,
+                    bit.clone() * base + (1.expr() - bit.clone()) * native::ONE[i].expr(),
+                );
+            }
+            cb.gate(meta.query_selector(q_multiply))
+        });
+
+        meta.create_gate("accumulator starts at one", |meta| {
+            let mut cb = BaseConstraintBuilder::default();
+            for (i, a) in query_limbs(meta, &a, Rotation::cur())
+                .into_iter()
+                .enumerate()
+            {
+                cb.require_equal("a == 1", a, native::ONE[i].expr());
+            }
+            cb.gate(meta.query_selector(q_first))
+        });
+
+        meta.create_gate("rows of a call are chained", |meta| {
+            let mut cb = BaseConstraintBuilder::default();
+            let [a_next, m_next, base_next] =
+                [a, m, base].map(|limbs| query_limbs(meta, &limbs, Rotation::next()));
+            let [r, m, base] = [r, m, base].map(|limbs| query_limbs(meta, &limbs, Rotation::cur()));
+            for i in 0..NUM_LIMBS {
+                cb.require_equal("a::next == r::cur", a_next[i].clone(), r[i].clone());
+                cb.require_equal("m::next == m::cur", m_next[i].clone(), m[i].clone());
+                cb.require_equal(
+                    "base::next == base::cur",
+                    base_next[i].clone(),
+                    base[i].clone(),
+                );
+            }
+            cb.gate(meta.query_selector(q_chain))
+        });
+
+        meta.create_gate("exponent byte is the bits of its multiplications", |meta| {
+            let mut cb = BaseConstraintBuilder::default();
+            // The multiplication of the j-th bit of the byte is on the row
+            // after its squaring.
+            let bits = (0..8).fold(0.expr(), |acc, j| {
+                acc * 2.expr() + meta.query_advice(bit, Rotation(2 * j + 1))
+            });
+            cb.require_equal(
+                "exponent_byte == bits",
+                meta.query_advice(exponent_byte, Rotation::cur()),
+                bits,
+            );
+            cb.gate(meta.query_selector(q_exponent_byte))
+        });
+
+        meta.create_gate("limb is its big-endian bytes", |meta| {
+            let mut cb = BaseConstraintBuilder::default();
+            let bytes = (0..LIMB_BYTES as i32).fold(0.expr(), |acc, i| {
+                acc * 256.expr() + meta.query_advice(operand_byte, Rotation(i))
+            });
+            cb.require_equal(
+                "operand_limb == bytes",
+                meta.query_advice(operand_limb, Rotation::cur()),
+                bytes,
+            );
+            cb.gate(meta.query_selector(q_limb))
+        });
+
+        meta.create_gate("a zero modulus has a zero result", |meta| {
+            let mut cb = BaseConstraintBuilder::default();
+            for (name, column) in [
+                ("modulus_rlc == 0", table.modulus_rlc),
+                ("result_rlc == 0", table.result_rlc),
+            ] {
+                cb.require_zero(name, meta.query_advice(column, Rotation::cur()));
+            }
+            cb.gate(meta.query_selector(q_zero_modulus))
+        });
+
+        meta.create_gate("validity check over the table rows", |meta| {
+            let mut cb = BaseConstraintBuilder::default();
+            let is_enabled = meta.query_advice(table.is_enabled, Rotation::cur());
+
+            cb.require_boolean("is_enabled is boolean", is_enabled.clone());
+            cb.condition(1.expr() - is_enabled, |cb| {
+                for (name, column) in [
+                    ("base_rlc == 0 if !is_enabled", table.base_rlc),
+                    ("exponent_rlc == 0 if !is_enabled", table.exponent_rlc),
+                    ("modulus_rlc == 0 if !is_enabled", table.modulus_rlc),
+                    ("result_rlc == 0 if !is_enabled", table.result_rlc),
+                ] {
+                    cb.require_zero(name, meta.query_advice(column, Rotation::cur()));
+                }
+            });
+
+            cb.gate(meta.query_selector(q_table))
+        });
+
+        Self {
+            table,
+            tables: FixedTables::default(),
+            limb_range,
+            carry_range,
+            rlc,
+            q_table,
+            q_zero_modulus,
+            q_mul,
+            q_square,
+            q_multiply,
+            q_first,
+            q_chain,
+            q_exponent_byte,
+            q_limb,
+            a,
+            b,
+            m,
+            q,
+            r,
+            d,
+            base,
+            carries,
+            lt_carries,
+            bit,
+            exponent_byte,
+            operand_byte,
+            operand_limb,
+            challenge,
+            _marker: PhantomData,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct ModExpWitness<F> {
+    /// The big-endian `(base, exponent, modulus)` of every call.
+    pub inputs: Vec<(Vec<u8>, Vec<u8>, Vec<u8>)>,
+    pub _marker: PhantomData<F>,
+}
+
+/// The witness of a call, from which the chip assigns it.
+#[derive(Clone, Debug)]
+struct CallWitness {
+    base: Vec<u8>,
+    exponent: Vec<u8>,
+    modulus: Vec<u8>,
+    /// The square-and-multiply steps, or none if the modulus is zero.
+    steps: Option<Vec<(bool, MulModStep, MulModStep)>>,
+    /// The big-endian word of the result.
+    result: [u8; WORD_BYTES],
+}
+
+impl CallWitness {
+    fn new(base: &[u8], exponent: &[u8], modulus: &[u8]) -> Self {
+        let m = native::from_be_bytes(modulus);
+        Self {
+            base: base.to_vec(),
+            exponent: exponent.to_vec(),
+            modulus: modulus.to_vec(),
+            steps: (m != [0; NUM_LIMBS])
+                .then(|| native::mod_exp_steps(&native::from_be_bytes(base), exponent, &m)),
+            result: native::mod_exp(base, exponent, modulus),
+        }
+    }
+
+    /// The bytes of the exponent the steps are of, see
+    /// [`native::exponent_bits`].
+    fn exponent_bytes(&self) -> Vec<u8> {
+        if self.exponent.is_empty() {
+            vec![0]
+        } else {
+            self.exponent.clone()
+        }
+    }
+}
+
+/// Cells of a modular multiplication that are range checked, or bound to
+/// the bytes of the operands.
+struct MulModCells<F: FieldExt> {
+    m: Vec<AssignedValue<F>>,
+    base: Vec<AssignedValue<F>>,
+    q: Vec<AssignedValue<F>>,
+    r: Vec<AssignedValue<F>>,
+    d: Vec<AssignedValue<F>>,
+    carries: Vec<AssignedValue<F>>,
+}
+
+#[derive(Clone, Debug)]
+pub struct ModExpChip<F> {
+    config: ModExpConfig<F>,
+    data: ModExpWitness<F>,
+}
+
+impl<F: FieldExt> ModExpChip<F> {
+    pub fn construct(config: ModExpConfig<F>, data: ModExpWitness<F>) -> Self {
+        Self { config, data }
+    }
+
+    pub fn load(&self, layouter: &mut impl Layouter<F>) -> Result<(), Error> {
+        let calls: Vec<_> = self
+            .data
+            .inputs
+            .iter()
+            .map(|(base, exponent, modulus)| CallWitness::new(base, exponent, modulus))
+            .collect();
+        self.assign_calls(layouter, &calls)
+    }
+
+    fn assign_calls(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        calls: &[CallWitness],
+    ) -> Result<(), Error> {
+        let config = &self.config;
+        config.tables.load(layouter)?;
+
+        // The cells of the RLCs of the base, exponent, modulus and result of
+        // every call with a nonzero modulus.
+        let rlc_chip = RlcChip::construct(config.rlc);
+        let rlc_cells = calls
+            .iter()
+            .enumerate()
+            .map(|(id, call)| {
+                call.steps
+                    .as_ref()
+                    .map(|steps| {
+                        let operands = self.assign_call(layouter, id, call, steps)?;
+                        rlc_chip.assign_cells(layouter, &operands)
+                    })
+                    .transpose()
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let challenge = layouter.get_challenge(config.challenge);
+        let table = &config.table;
+        let columns = [
+            table.is_enabled,
+            table.base_rlc,
+            table.exponent_rlc,
+            table.modulus_rlc,
+            table.result_rlc,
+        ];
+        layouter.assign_region(
+            || "modexp table",
+            |mut region| {
+                config.q_table.enable(&mut region, 0)?;
+                for (annotation, column) in table.annotations().iter().zip(columns) {
+                    region.assign_advice(|| annotation, column, 0, || Value::known(F::zero()))?;
+                }
+
+                for (i, (call, rlc_cells)) in calls.iter().zip(rlc_cells.iter()).enumerate() {
+                    let offset = i + 1;
+                    config.q_table.enable(&mut region, offset)?;
+                    region.assign_advice(
+                        || "is_enabled",
+                        table.is_enabled,
+                        offset,
+                        || Value::known(F::one()),
+                    )?;
+                    match rlc_cells {
+                        Some(rlc_cells) => {
+                            for ((annotation, column), cell) in table
+                                .annotations()
+                                .iter()
+                                .zip(columns)
+                                .skip(1)
+                                .zip(rlc_cells)
+                            {
+                                cell.copy_advice(|| annotation, &mut region, column, offset)?;
+                            }
+                        }
+                        None => {
+                            config.q_zero_modulus.enable(&mut region, offset)?;
+                            let result = &call.result[WORD_BYTES - call.modulus.len()..];
+                            for ((annotation, column), bytes) in table
+                                .annotations()
+                                .iter()
+                                .zip(columns)
+                                .skip(1)
+                                .zip([&call.base[..], &call.exponent, &call.modulus, result])
+                            {
+                                region.assign_advice(
+                                    || annotation,
+                                    column,
+                                    offset,
+                                    || rlc(bytes, challenge),
+                                )?;
+                            }
+                        }
+                    }
+                }
+                Ok(())
+            },
+        )
+    }
+
+    /// Assigns the square-and-multiply `steps` of the `id`-th call and the
+    /// bytes of its operands, and range checks them in a separate region.
+    /// Returns the cells of the bytes of the base, the exponent, the modulus
+    /// and the result.
+    fn assign_call(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        id: usize,
+        call: &CallWitness,
+        steps: &[(bool, MulModStep, MulModStep)],
+    ) -> Result<[AssignedBytes<F>; 4], Error> {
+        let config = &self.config;
+        let base = native::from_be_bytes(&call.base);
+        let m = native::from_be_bytes(&call.modulus);
+
+        let (exponent, cells) = layouter.assign_region(
+            || format!("modexp call {}", id),
+            |mut region| {
+                let mut cells = vec![];
+                for (i, (bit, square, multiply)) in steps.iter().enumerate() {
+                    for (j, step) in [square, multiply].into_iter().enumerate() {
+                        let offset = 2 * i + j;
+                        config.q_mul.enable(&mut region, offset)?;
+                        if j == 0 {
+                            config.q_square.enable(&mut region, offset)?;
+                        } else {
+                            config.q_multiply.enable(&mut region, offset)?;
+                        }
+                        if offset == 0 {
+                            config.q_first.enable(&mut region, offset)?;
+                        }
+                        if offset + 1 < 2 * steps.len() {
+                            config.q_chain.enable(&mut region, offset)?;
+                        }
+
+                        region.assign_advice(
+                            || "bit",
+                            config.bit,
+                            offset,
+                            || Value::known(F::from(*bit as u64)),
+                        )?;
+                        cells.push(self.assign_mul_mod(&mut region, offset, step, &base, &m)?);
+                    }
+                }
+
+                let exponent = call
+                    .exponent_bytes()
+                    .iter()
+                    .enumerate()
+                    .map(|(k, byte)| {
+                        let offset = 16 * k;
+                        config.q_exponent_byte.enable(&mut region, offset)?;
+                        region.assign_advice(
+                            || "exponent_byte",
+                            config.exponent_byte,
+                            offset,
+                            || Value::known(F::from(*byte as u64)),
+                        )
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+                Ok((exponent, cells))
+            },
+        )?;
+
+        let (first, last) = (&cells[0], &cells[cells.len() - 1]);
+        let [base, modulus, result] = layouter.assign_region(
+            || format!("modexp call {} operands", id),
+            |mut region| {
+                let words = [
+                    (native::to_be_bytes(&base), &first.base),
+                    (native::to_be_bytes(&m), &first.m),
+                    (call.result, &last.r),
+                ];
+                let mut offset = 0;
+                let mut operands = vec![];
+                for (word, limbs) in words {
+                    let bytes = word
+                        .iter()
+                        .enumerate()
+                        .map(|(i, byte)| {
+                            region.assign_advice(
+                                || "operand_byte",
+                                config.operand_byte,
+                                offset + i,
+                                || Value::known(F::from(*byte as u64)),
+                            )
+                        })
+                        .collect::<Result<Vec<_>, _>>()?;
+                    // The limbs are little-endian, the bytes big-endian.
+                    for (i, (bytes, limb)) in
+                        word.chunks(LIMB_BYTES).zip(limbs.iter().rev()).enumerate()
+                    {
+                        let row = offset + LIMB_BYTES * i;
+                        config.q_limb.enable(&mut region, row)?;
+                        let value = bytes.iter().fold(0, |acc, byte| (acc << 8) | *byte as u64);
+                        let cell = region.assign_advice(
+                            || "operand_limb",
+                            config.operand_limb,
+                            row,
+                            || Value::known(F::from(value)),
+                        )?;
+                        region.constrain_equal(cell.cell(), limb.cell())?;
+                    }
+                    operands.push(AssignedBytes::new(bytes));
+                    offset += WORD_BYTES;
+                }
+                Ok(<[_; 3]>::try_from(operands).expect("three operands"))
+            },
+        )?;
+
+        let limb_range = DecomposeChip::construct(config.limb_range);
+        let carry_range = DecomposeChip::construct(config.carry_range);
+        layouter.assign_region(
+            || format!("modexp call {} range checks", id),
+            |mut region| {
+                for (step, cells) in cells.iter().enumerate() {
+                    let offset = 3 * NUM_LIMBS * step;
+                    let limbs = cells.q.iter().chain(&cells.r).chain(&cells.d);
+                    for (i, cell) in limbs.enumerate() {
+                        limb_range.copy(&mut region, offset + i, cell)?;
+                    }
+                    for (i, cell) in cells.carries.iter().enumerate() {
+                        carry_range.copy(&mut region, offset + i, cell)?;
+                    }
+                }
+                Ok(())
+            },
+        )?;
+
+        Ok([base, AssignedBytes::new(exponent), modulus, result])
+    }
+
+    /// Assigns `step` at `offset`, returning the cells to range check.
+    fn assign_mul_mod(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        step: &MulModStep,
+        base: &Limbs,
+        m: &Limbs,
+    ) -> Result<MulModCells<F>, Error> {
+        let config = &self.config;
+        let mut assign_limbs = |annotation: &'static str,
+                                columns: &[Column<Advice>],
+                                values: &[u64]|
+         -> Result<Vec<AssignedValue<F>>, Error> {
+            columns
+                .iter()
+                .zip(values)
+                .map(|(column, value)| {
+                    region.assign_advice(
+                        || annotation,
+                        *column,
+                        offset,
+                        || Value::known(F::from(*value)),
+                    )
+                })
+                .collect()
+        };
+
+        // r + d + 1 == m, along with the carries of the limb-wise addition.
+        let mut d = [0; NUM_LIMBS];
+        let mut lt_carries = [0; NUM_LIMBS];
+        let mut carry_in = 1;
+        for i in 0..NUM_LIMBS {
+            let value = m[i] as i64 - step.r[i] as i64 - carry_in as i64;
+            d[i] = value.rem_euclid(1 << LIMB_BITS) as u64;
+            lt_carries[i] = (value < 0) as u64;
+            carry_in = lt_carries[i];
+        }
+
+        // Signed carries of a * b - q * m - r, offset by 2^(CARRY_BITS - 1).
+        let mut carries = [0; NUM_CARRIES];
+        let mut carry = 0i128;
+        for (k, offset_carry) in carries.iter_mut().enumerate() {
+            let mut t = carry;
+            for i in 0..NUM_LIMBS {
+                if let Some(j) = k.checked_sub(i).filter(|j| *j < NUM_LIMBS) {
+                    t += (step.a[i] * step.b[j]) as i128 - (step.q[i] * m[j]) as i128;
+                }
+            }
+            if k < NUM_LIMBS {
+                t -= step.r[k] as i128;
+            }
+            debug_assert_eq!(t % (1 << LIMB_BITS), 0);
+            carry = t >> LIMB_BITS;
+            *offset_carry = (carry + (1 << (CARRY_BITS - 1))) as u64;
+        }
+
+        assign_limbs("a", &config.a, &step.a)?;
+        assign_limbs("b", &config.b, &step.b)?;
+        assign_limbs("lt_carry", &config.lt_carries, &lt_carries)?;
+        Ok(MulModCells {
+            m: assign_limbs("m", &config.m, m)?,
+            base: assign_limbs("base", &config.base, base)?,
+            q: assign_limbs("q", &config.q, &step.q)?,
+            r: assign_limbs("r", &config.r, &step.r)?,
+            d: assign_limbs("d", &config.d, &d)?,
+            carries: assign_limbs("carry", &config.carries, &carries)?,
+        })
+    }
+}
+
+#[cfg(any(feature = "test", test))]
+pub mod dev {
+    use super::*;
+
+    use ethers_core::utils::hex::FromHex;
+    use halo2_proofs::{circuit::SimpleFloorPlanner, plonk::Circuit};
+
+    lazy_static::lazy_static! {
+        pub static ref INPUTS_OUTPUTS: (Vec<(Vec<u8>, Vec<u8>, Vec<u8>)>, Vec<Vec<u8>>) = {
+        [
+            // 2^10 mod 1000
+            ("02", "0a", "03e8", "0018"),
+            // the base is larger than the modulus.
+            ("1234567890", "03", "0101", "0014"),
+            // a modulus of one.
+            ("05", "07", "01", "00"),
+            // a zero modulus has an empty output.
+            ("05", "07", "", ""),
+            // an output of zeros otherwise.
+            ("05", "07", "00", "00"),
+            // an empty exponent.
+            ("05", "", "0d", "01"),
+            (
+                "deadbeef",
+                "010001",
+                "fffffffffffffffffffffffffffffffffffffffffffffffffffffffefffffc2f",
+                "f3ce9132a068e23a8ae9901e3c53f4545d29a11cbb25f6f28a0021f19bf51e04",
+            ),
+        ]
+            .iter()
+            .map(|(base, exponent, modulus, result)| {
+                let decode = |hex: &str| Vec::from_hex(hex).expect("operands are hex-encoded");
+                ((decode(base), decode(exponent), decode(modulus)), decode(result))
+            })
+            .unzip()
+        };
+    }
+
+    #[derive(Default)]
+    pub struct ModExpTestCircuit<F> {
+        pub inputs: Vec<(Vec<u8>, Vec<u8>, Vec<u8>)>,
+        pub outputs: Vec<Vec<u8>>,
+        pub _marker: PhantomData<F>,
+    }
+
+    impl<F: FieldExt> Circuit<F> for ModExpTestCircuit<F> {
+        type Config = ModExpConfig<F>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            let modexp_table = ModExpTable::construct(meta);
+            ModExpConfig::configure(meta, modexp_table)
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            let chip = ModExpChip::construct(
+                config,
+                ModExpWitness {
+                    inputs: self.inputs.clone(),
+                    _marker: PhantomData,
+                },
+            );
+            chip.load(&mut layouter)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use gadgets::rows;
+    use halo2_proofs::{
+        arithmetic::FieldExt,
+        circuit::{Layouter, SimpleFloorPlanner},
+        dev::MockProver,
+        halo2curves::{bn256::Fr, pasta::pallas},
+        plonk::{Circuit, ConstraintSystem, Error},
+    };
+    use std::marker::PhantomData;
+
+    use crate::{
+        dev::{ModExpTestCircuit, INPUTS_OUTPUTS},
+        native, CallWitness, ModExpChip, ModExpConfig, ModExpTable, ModExpWitness,
+    };
+
+    /// Assigns the calls of `calls` as they are, rather than from their
+    /// operands.
+    #[derive(Default)]
+    struct CallsCircuit {
+        calls: Vec<CallWitness>,
+    }
+
+    impl Circuit<Fr> for CallsCircuit {
+        type Config = ModExpConfig<Fr>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+            let modexp_table = ModExpTable::construct(meta);
+            ModExpConfig::configure(meta, modexp_table)
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<Fr>,
+        ) -> Result<(), Error> {
+            let chip = ModExpChip::construct(
+                config,
+                ModExpWitness {
+                    inputs: vec![],
+                    _marker: PhantomData,
+                },
+            );
+            chip.assign_calls(&mut layouter, &self.calls)
+        }
+    }
+
+    fn verify(calls: Vec<CallWitness>) -> Result<(), Vec<halo2_proofs::dev::VerifyFailure>> {
+        let circuit = CallsCircuit { calls };
+        MockProver::run(rows::min_k(&circuit), &circuit, vec![])
+            .unwrap()
+            .verify()
+    }
+
+    fn call(base: &str, exponent: &str, modulus: &str) -> CallWitness {
+        let decode = |hex: &str| ethers_core::utils::hex::decode(hex).unwrap();
+        CallWitness::new(&decode(base), &decode(exponent), &decode(modulus))
+    }
+
+    #[test]
+    fn test_modexp_native() {
+        let (inputs, outputs) = INPUTS_OUTPUTS.clone();
+        for ((base, exponent, modulus), output) in inputs.iter().zip(outputs) {
+            let result = native::mod_exp(base, exponent, modulus);
+            assert_eq!(result[native::WORD_BYTES - modulus.len()..], output);
+        }
+    }
+
+    fn test_modexp_circuit_over<F: FieldExt>() {
+        let (inputs, outputs) = INPUTS_OUTPUTS.clone();
+
+        let circuit: ModExpTestCircuit<F> = ModExpTestCircuit {
+            inputs,
+            outputs,
+            _marker: PhantomData,
+        };
+
+        let prover = MockProver::run(rows::min_k(&circuit), &circuit, vec![]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+    }
+
+    #[test]
+    fn test_modexp_circuit() {
+        test_modexp_circuit_over::<Fr>();
+        test_modexp_circuit_over::<pallas::Base>();
+    }
+
+    #[test]
+    fn test_modexp_wrong_exponent_bits() {
+        let mut wrong = call("02", "0a", "03e8");
+        assert_eq!(verify(vec![wrong.clone()]), Ok(()));
+
+        // the steps, and the result, are those of 2^11 mod 1000.
+        let steps = call("02", "0b", "03e8").steps.unwrap();
+        wrong.result = native::to_be_bytes(&steps[steps.len() - 1].2.r);
+        wrong.steps = Some(steps);
+        assert!(verify(vec![wrong]).is_err());
+    }
+
+    #[test]
+    fn test_modexp_wrong_operand_bytes() {
+        let right = call("1234567890", "03", "0101");
+        assert_eq!(verify(vec![right.clone()]), Ok(()));
+
+        // the bytes of the result, then of the base, are not those of the
+        // limbs of the steps.
+        let mut wrong = right.clone();
+        wrong.result[native::WORD_BYTES - 1] ^= 1;
+        assert!(verify(vec![wrong]).is_err());
+
+        let mut wrong = right;
+        wrong.base[0] ^= 1;
+        assert!(verify(vec![wrong]).is_err());
+    }
+
+    #[test]
+    fn test_modexp_zero_modulus() {
+        let right = call("05", "07", "00");
+        assert!(right.steps.is_none());
+        assert_eq!(verify(vec![right.clone()]), Ok(()));
+
+        // the result of a zero modulus is not zero.
+        let mut wrong = right;
+        wrong.result[native::WORD_BYTES - 1] = 1;
+        assert!(verify(vec![wrong]).is_err());
+
+        // a nonzero modulus is taken as a zero one.
+        let mut wrong = call("05", "07", "0d");
+        wrong.steps = None;
+        wrong.result = [0; native::WORD_BYTES];
+        assert!(verify(vec![wrong]).is_err());
+    }
+}
@@ -0,0 +1,210 @@
+//! Native modular exponentiation over 256-bit integers, used to compute the
+//! witness of the circuit and as the reference it is tested against.
+//!
+//! Integers are represented by [`NUM_LIMBS`] little-endian limbs of
+//! [`LIMB_BITS`] bits, stored in `u64`s so that they can be passed to the
+//! gadgets directly.
+
+/// Number of bits of a limb.
+pub const LIMB_BITS: usize = 32;
+
+/// Number of limbs of a 256-bit integer.
+pub const NUM_LIMBS: usize = 8;
+
+/// Size of a 256-bit integer in bytes.
+pub const WORD_BYTES: usize = 32;
+
+/// Little-endian limbs of a 256-bit integer.
+pub type Limbs = [u64; NUM_LIMBS];
+
+/// The integer `1`.
+pub const ONE: Limbs = [1, 0, 0, 0, 0, 0, 0, 0];
+
+/// Parses a big-endian integer of at most [`WORD_BYTES`] bytes.
+pub fn from_be_bytes(bytes: &[u8]) -> Limbs {
+    assert!(bytes.len() <= WORD_BYTES, "integers are at most 32 bytes");
+    let mut word = [0u8; WORD_BYTES];
+    word[WORD_BYTES - bytes.len()..].copy_from_slice(bytes);
+
+    let mut limbs = [0; NUM_LIMBS];
+    for (limb, bytes) in limbs.iter_mut().zip(word.rchunks(LIMB_BITS / 8)) {
+        *limb = u32::from_be_bytes(bytes.try_into().expect("limbs are 4 bytes")) as u64;
+    }
+    limbs
+}
+
+/// Big-endian bytes of `limbs`.
+pub fn to_be_bytes(limbs: &Limbs) -> [u8; WORD_BYTES] {
+    let mut word = [0u8; WORD_BYTES];
+    for (bytes, limb) in word.rchunks_mut(LIMB_BITS / 8).zip(limbs) {
+        bytes.copy_from_slice(&(*limb as u32).to_be_bytes());
+    }
+    word
+}
+
+fn bit(limbs: &[u64], i: usize) -> bool {
+    (limbs[i / LIMB_BITS] >> (i % LIMB_BITS)) & 1 == 1
+}
+
+fn is_less(lhs: &[u64], rhs: &[u64]) -> bool {
+    for i in (0..lhs.len().max(rhs.len())).rev() {
+        let (lhs, rhs) = (
+            lhs.get(i).copied().unwrap_or(0),
+            rhs.get(i).copied().unwrap_or(0),
+        );
+        if lhs != rhs {
+            return lhs < rhs;
+        }
+    }
+    false
+}
+
+/// Full product of `a` and `b`, in `2 * NUM_LIMBS` limbs.
+pub fn mul_wide(a: &Limbs, b: &Limbs) -> [u64; 2 * NUM_LIMBS] {
+    let mut product = [0u128; 2 * NUM_LIMBS];
+    for (i, a) in a.iter().enumerate() {
+        for (j, b) in b.iter().enumerate() {
+            product[i + j] += (*a as u128) * (*b as u128);
+        }
+    }
+
+    let mut limbs = [0u64; 2 * NUM_LIMBS];
+    let mut carry = 0u128;
+    for (limb, value) in limbs.iter_mut().zip(product) {
+        let value = value + carry;
+        *limb = (value as u64) & u32::MAX as u64;
+        carry = value >> LIMB_BITS;
+    }
+    limbs
+}
+
+/// Computes `(q, r)` such that `a * b = q * m + r` and `r < m`. The quotient
+/// must fit in 256 bits, which holds whenever `a < m`.
+pub fn mul_mod(a: &Limbs, b: &Limbs, m: &Limbs) -> (Limbs, Limbs) {
+    assert!(*m != [0; NUM_LIMBS], "modulus is zero");
+    let product = mul_wide(a, b);
+
+    // Schoolbook long division, one bit at a time.
+    let mut q = [0u64; 2 * NUM_LIMBS];
+    let mut r = [0u64; NUM_LIMBS + 1];
+    for i in (0..2 * NUM_LIMBS * LIMB_BITS).rev() {
+        let mut carry = bit(&product, i) as u64;
+        for limb in r.iter_mut() {
+            let value = (*limb << 1) | carry;
+            *limb = value & u32::MAX as u64;
+            carry = value >> LIMB_BITS;
+        }
+        if !is_less(&r, m) {
+            let mut borrow = 0i64;
+            for (j, limb) in r.iter_mut().enumerate() {
+                let value = *limb as i64 - m.get(j).copied().unwrap_or(0) as i64 - borrow;
+                borrow = (value < 0) as i64;
+                *limb = (value + (borrow << LIMB_BITS)) as u64;
+            }
+            q[i / LIMB_BITS] |= 1 << (i % LIMB_BITS);
+        }
+    }
+
+    assert!(
+        q[NUM_LIMBS..].iter().all(|limb| *limb == 0),
+        "quotient does not fit in 256 bits"
+    );
+    (
+        q[..NUM_LIMBS]
+            .try_into()
+            .expect("quotient has NUM_LIMBS limbs"),
+        r[..NUM_LIMBS]
+            .try_into()
+            .expect("remainder has NUM_LIMBS limbs"),
+    )
+}
+
+/// Bits of `exponent`, most significant first. An empty exponent is read as
+/// a single zero byte.
+pub fn exponent_bits(exponent: &[u8]) -> Vec<bool> {
+    let exponent = if exponent.is_empty() {
+        &[0][..]
+    } else {
+        exponent
+    };
+    exponent
+        .iter()
+        .flat_map(|byte| (0..8).rev().map(move |i| (byte >> i) & 1 == 1))
+        .collect()
+}
+
+/// A modular multiplication `a * b = q * m + r` of the square-and-multiply
+/// algorithm.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MulModStep {
+    pub a: Limbs,
+    pub b: Limbs,
+    pub q: Limbs,
+    pub r: Limbs,
+}
+
+impl MulModStep {
+    pub fn new(a: Limbs, b: Limbs, m: &Limbs) -> Self {
+        let (q, r) = mul_mod(&a, &b, m);
+        Self { a, b, q, r }
+    }
+}
+
+/// Steps of the left-to-right square-and-multiply algorithm: for every bit of
+/// the exponent, the accumulator is squared and then multiplied by the base if
+/// the bit is set, or by one otherwise.
+pub fn mod_exp_steps(
+    base: &Limbs,
+    exponent: &[u8],
+    m: &Limbs,
+) -> Vec<(bool, MulModStep, MulModStep)> {
+    let mut acc = ONE;
+    exponent_bits(exponent)
+        .into_iter()
+        .map(|bit| {
+            let square = MulModStep::new(acc, acc, m);
+            let multiply = MulModStep::new(square.r, if bit { *base } else { ONE }, m);
+            acc = multiply.r;
+            (bit, square, multiply)
+        })
+        .collect()
+}
+
+/// Computes `base^exponent mod modulus` for big-endian inputs of at most 32
+/// bytes, returning the result as a 32-byte big-endian word. Following
+/// EIP-198, the result is zero if the modulus is zero.
+pub fn mod_exp(base: &[u8], exponent: &[u8], modulus: &[u8]) -> [u8; WORD_BYTES] {
+    let m = from_be_bytes(modulus);
+    if m == [0; NUM_LIMBS] {
+        return [0; WORD_BYTES];
+    }
+    let result = mod_exp_steps(&from_be_bytes(base), exponent, &m)
+        .last()
+        .map_or(ONE, |(_, _, multiply)| multiply.r);
+    to_be_bytes(&result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_be_bytes() {
+        let bytes: Vec<u8> = (1..=32).collect();
+        assert_eq!(to_be_bytes(&from_be_bytes(&bytes)).to_vec(), bytes);
+        assert_eq!(from_be_bytes(&[1, 0, 0, 0, 2]), [2, 1, 0, 0, 0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn test_mul_mod() {
+        let word = |last: u8| {
+            let mut word = [0xff; 32];
+            word[31] = last;
+            from_be_bytes(&word)
+        };
+        // (m - 1)^2 = (m - 2) * m + 1
+        let (q, r) = mul_mod(&word(0xfe), &word(0xfe), &word(0xff));
+        assert_eq!(q, word(0xfd));
+        assert_eq!(r, ONE);
+    }
+}
@@ -0,0 +1,529 @@
+//! A negative-test harness auditing the soundness of the circuits of this
+//! workspace: given a circuit and a witness satisfying it, it perturbs every
+//! assigned advice cell in turn and records which constraints catch the
+//! change, so that the cells no constraint depends on show up in a coverage
+//! report.
+//!
+//! The circuit is synthesized through a layouter wrapping the one of the
+//! floor planner, which numbers the regions in the order they are assigned
+//! and adds one to the value of the targeted cell when it is assigned. Each
+//! mutant is verified by the `MockProver`, whose failures name the gates,
+//! lookups and copy constraints that caught the mutation.
+//!
+//! A cell that no failure catches is either unconstrained, or constrained in
+//! a way that its value plus one still satisfies, e.g. a bit whose negation
+//! is also valid. Cells assigned from constants or instances are not
+//! mutated, as their copy constraints pin them already.
+//!
+//! Each mutant runs the whole `MockProver`, so the harness is meant for the
+//! test circuits of the workspace rather than for full-size witnesses: the
+//! crates run it on the small witnesses of their `*_coverage` tests.
+
+use std::{cell::RefCell, collections::BTreeSet, fmt};
+
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{layouter::RegionLayouter, Cell, Layouter, Region, Table, Value},
+    dev::{MockProver, VerifyFailure},
+    plonk::{
+        Advice, Any, Assigned, Challenge, Circuit, Column, ConstraintSystem, Error, Fixed,
+        Instance, Selector,
+    },
+};
+
+/// An assigned advice cell, by the index of its region in the order the
+/// regions are assigned, and its offset within the region.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct CellLocation {
+    pub region_index: usize,
+    pub region_name: String,
+    pub column: usize,
+    pub offset: usize,
+}
+
+impl fmt::Display for CellLocation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "region {} ('{}'), advice column {}, offset {}",
+            self.region_index, self.region_name, self.column, self.offset
+        )
+    }
+}
+
+/// The constraints that caught the mutation of a cell, by the name of the
+/// gate constraint or lookup, or `permutation` for a copy constraint.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CellCoverage {
+    pub cell: CellLocation,
+    pub caught_by: BTreeSet<String>,
+}
+
+impl CellCoverage {
+    pub fn is_constrained(&self) -> bool {
+        !self.caught_by.is_empty()
+    }
+}
+
+/// Coverage of every assigned advice cell of a circuit, in the order of
+/// their locations.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct CoverageReport {
+    pub cells: Vec<CellCoverage>,
+}
+
+impl CoverageReport {
+    /// The cells whose mutation no constraint caught.
+    pub fn unconstrained(&self) -> impl Iterator<Item = &CellLocation> {
+        self.cells
+            .iter()
+            .filter(|coverage| !coverage.is_constrained())
+            .map(|coverage| &coverage.cell)
+    }
+
+    /// Number of mutated cells that at least one constraint caught.
+    pub fn num_constrained(&self) -> usize {
+        self.cells
+            .iter()
+            .filter(|coverage| coverage.is_constrained())
+            .count()
+    }
+
+    /// Summary of the coverage followed by the unconstrained cells, one per
+    /// line.
+    pub fn report(&self) -> String {
+        let mut lines = vec![format!(
+            "{} of {} advice cells constrained",
+            self.num_constrained(),
+            self.cells.len()
+        )];
+        lines.extend(
+            self.unconstrained()
+                .map(|cell| format!("unconstrained: {}", cell)),
+        );
+        lines.join("\n")
+    }
+}
+
+/// Mutates every advice cell `circuit` assigns and verifies each mutant with
+/// the `MockProver` over `2^k` rows. Panics if `circuit` is not satisfied
+/// without mutations.
+pub fn mutation_coverage<F: FieldExt, C: Circuit<F>>(
+    k: u32,
+    circuit: &C,
+    instance: Vec<Vec<F>>,
+) -> Result<CoverageReport, Error> {
+    let cells = RefCell::new(BTreeSet::new());
+    let mutant = Mutant {
+        circuit,
+        target: None,
+        cells: &cells,
+    };
+    let prover = MockProver::run(k, &mutant, instance.clone())?;
+    if let Err(failures) = prover.verify() {
+        panic!("the witness does not satisfy the circuit: {:?}", failures);
+    }
+
+    let cells = cells.into_inner();
+    let mut report = CoverageReport::default();
+    for cell in cells {
+        let mutant = Mutant {
+            circuit,
+            target: Some(&cell),
+            cells: &RefCell::new(BTreeSet::new()),
+        };
+        let prover = MockProver::run(k, &mutant, instance.clone())?;
+        let caught_by = match prover.verify() {
+            Ok(()) => BTreeSet::new(),
+            Err(failures) => failures.iter().map(constraint_name).collect(),
+        };
+        report.cells.push(CellCoverage { cell, caught_by });
+    }
+    Ok(report)
+}
+
+/// Name of the constraint of a failure, without its location.
+fn constraint_name(failure: &VerifyFailure) -> String {
+    match failure {
+        VerifyFailure::ConstraintNotSatisfied { constraint, .. } => constraint.to_string(),
+        VerifyFailure::Lookup { name, .. } => format!("lookup '{}'", name),
+        VerifyFailure::Permutation { .. } => String::from("permutation"),
+        failure => failure.to_string(),
+    }
+}
+
+/// `circuit` synthesized through a [`MutatingLayouter`], which records the
+/// assigned advice cells and mutates the `target` one.
+struct Mutant<'a, C> {
+    circuit: &'a C,
+    target: Option<&'a CellLocation>,
+    cells: &'a RefCell<BTreeSet<CellLocation>>,
+}
+
+impl<'a, F: FieldExt, C: Circuit<F>> Circuit<F> for Mutant<'a, C> {
+    type Config = C::Config;
+    type FloorPlanner = C::FloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self {
+            circuit: self.circuit,
+            target: None,
+            cells: self.cells,
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        C::configure(meta)
+    }
+
+    fn synthesize(&self, config: Self::Config, layouter: impl Layouter<F>) -> Result<(), Error> {
+        // The regions are numbered from zero in every phase.
+        self.circuit.synthesize(
+            config,
+            MutatingLayouter {
+                layouter,
+                num_regions: 0,
+                target: self.target,
+                cells: self.cells,
+            },
+        )
+    }
+}
+
+struct MutatingLayouter<'a, L> {
+    layouter: L,
+    num_regions: usize,
+    target: Option<&'a CellLocation>,
+    cells: &'a RefCell<BTreeSet<CellLocation>>,
+}
+
+impl<'a, F: FieldExt, L: Layouter<F>> Layouter<F> for MutatingLayouter<'a, L> {
+    type Root = Self;
+
+    fn assign_region<A, AR, N, NR>(&mut self, name: N, mut assignment: A) -> Result<AR, Error>
+    where
+        A: FnMut(Region<'_, F>) -> Result<AR, Error>,
+        N: Fn() -> NR,
+        NR: Into<String>,
+    {
+        let region_index = self.num_regions;
+        self.num_regions += 1;
+        let region_name: String = name().into();
+        let (target, cells) = (self.target, self.cells);
+        self.layouter.assign_region(name, |region| {
+            let mut region = MutatingRegion {
+                region,
+                region_index,
+                region_name: region_name.clone(),
+                target,
+                cells,
+            };
+            assignment(Region::from(&mut region as &mut dyn RegionLayouter<F>))
+        })
+    }
+
+    fn assign_table<A, N, NR>(&mut self, name: N, assignment: A) -> Result<(), Error>
+    where
+        A: FnMut(Table<'_, F>) -> Result<(), Error>,
+        N: Fn() -> NR,
+        NR: Into<String>,
+    {
+        self.layouter.assign_table(name, assignment)
+    }
+
+    fn constrain_instance(
+        &mut self,
+        cell: Cell,
+        column: Column<Instance>,
+        row: usize,
+    ) -> Result<(), Error> {
+        self.layouter.constrain_instance(cell, column, row)
+    }
+
+    fn get_challenge(&self, challenge: Challenge) -> Value<F> {
+        self.layouter.get_challenge(challenge)
+    }
+
+    fn get_root(&mut self) -> &mut Self::Root {
+        self
+    }
+
+    fn push_namespace<NR, N>(&mut self, name_fn: N)
+    where
+        NR: Into<String>,
+        N: FnOnce() -> NR,
+    {
+        self.layouter.get_root().push_namespace(name_fn)
+    }
+
+    fn pop_namespace(&mut self, gadget_name: Option<String>) {
+        self.layouter.get_root().pop_namespace(gadget_name)
+    }
+}
+
+/// A region of the floor planner, assigned through its public methods.
+struct MutatingRegion<'r, 'a, F: FieldExt> {
+    region: Region<'r, F>,
+    region_index: usize,
+    region_name: String,
+    target: Option<&'a CellLocation>,
+    cells: &'a RefCell<BTreeSet<CellLocation>>,
+}
+
+impl<'r, 'a, F: FieldExt> fmt::Debug for MutatingRegion<'r, 'a, F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MutatingRegion")
+            .field("region_index", &self.region_index)
+            .field("region_name", &self.region_name)
+            .finish()
+    }
+}
+
+impl<'r, 'a, F: FieldExt> RegionLayouter<F> for MutatingRegion<'r, 'a, F> {
+    fn enable_selector<'v>(
+        &'v mut self,
+        _annotation: &'v (dyn Fn() -> String + 'v),
+        selector: &Selector,
+        offset: usize,
+    ) -> Result<(), Error> {
+        selector.enable(&mut self.region, offset)
+    }
+
+    fn name_column<'v>(
+        &'v mut self,
+        annotation: &'v (dyn Fn() -> String + 'v),
+        column: Column<Any>,
+    ) {
+        self.region.name_column(annotation, column)
+    }
+
+    fn assign_advice<'v>(
+        &'v mut self,
+        annotation: &'v (dyn Fn() -> String + 'v),
+        column: Column<Advice>,
+        offset: usize,
+        to: &'v mut (dyn FnMut() -> Value<Assigned<F>> + 'v),
+    ) -> Result<Cell, Error> {
+        let location = CellLocation {
+            region_index: self.region_index,
+            region_name: self.region_name.clone(),
+            column: column.index(),
+            offset,
+        };
+        let is_target = self.target == Some(&location);
+        self.cells.borrow_mut().insert(location);
+        self.region
+            .assign_advice(annotation, column, offset, || {
+                let value = to();
+                if is_target {
+                    value.map(|value| Assigned::from(value.evaluate() + F::one()))
+                } else {
+                    value
+                }
+            })
+            .map(|cell| cell.cell())
+    }
+
+    fn assign_advice_from_constant<'v>(
+        &'v mut self,
+        annotation: &'v (dyn Fn() -> String + 'v),
+        column: Column<Advice>,
+        offset: usize,
+        constant: Assigned<F>,
+    ) -> Result<Cell, Error> {
+        self.region
+            .assign_advice_from_constant(annotation, column, offset, constant)
+            .map(|cell| cell.cell())
+    }
+
+    fn assign_advice_from_instance<'v>(
+        &mut self,
+        annotation: &'v (dyn Fn() -> String + 'v),
+        instance: Column<Instance>,
+        row: usize,
+        advice: Column<Advice>,
+        offset: usize,
+    ) -> Result<(Cell, Value<F>), Error> {
+        self.region
+            .assign_advice_from_instance(annotation, instance, row, advice, offset)
+            .map(|cell| (cell.cell(), cell.value().copied()))
+    }
+
+    fn instance_value(
+        &mut self,
+        instance: Column<Instance>,
+        row: usize,
+    ) -> Result<Value<F>, Error> {
+        self.region.instance_value(instance, row)
+    }
+
+    fn assign_fixed<'v>(
+        &'v mut self,
+        annotation: &'v (dyn Fn() -> String + 'v),
+        column: Column<Fixed>,
+        offset: usize,
+        to: &'v mut (dyn FnMut() -> Value<Assigned<F>> + 'v),
+    ) -> Result<Cell, Error> {
+        self.region
+            .assign_fixed(annotation, column, offset, to)
+            .map(|cell| cell.cell())
+    }
+
+    fn constrain_constant(&mut self, cell: Cell, constant: Assigned<F>) -> Result<(), Error> {
+        self.region.constrain_constant(cell, constant)
+    }
+
+    fn constrain_equal(&mut self, left: Cell, right: Cell) -> Result<(), Error> {
+        self.region.constrain_equal(left, right)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gadgets::{tables::RangeTable, RangeCheckChip, RangeCheckConfig};
+    use halo2_proofs::{circuit::SimpleFloorPlanner, halo2curves::bn256::Fr, poly::Rotation};
+    use std::marker::PhantomData;
+
+    /// Constrains `a * b == c` on the rows of `values`, and assigns `d`
+    /// without constraining it.
+    #[derive(Default)]
+    struct TestCircuit<F> {
+        values: Vec<[u64; 4]>,
+        _marker: PhantomData<F>,
+    }
+
+    impl<F: FieldExt> Circuit<F> for TestCircuit<F> {
+        type Config = (Selector, [Column<Advice>; 4]);
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            let q_mul = meta.selector();
+            let columns = [(); 4].map(|_| meta.advice_column());
+            meta.create_gate("a * b == c", |meta| {
+                let [a, b, c, _] = columns.map(|column| meta.query_advice(column, Rotation::cur()));
+                vec![meta.query_selector(q_mul) * (a * b - c)]
+            });
+            (q_mul, columns)
+        }
+
+        fn synthesize(
+            &self,
+            (q_mul, columns): Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            layouter.assign_region(
+                || "mul",
+                |mut region| {
+                    for (offset, values) in self.values.iter().enumerate() {
+                        q_mul.enable(&mut region, offset)?;
+                        for (column, value) in columns.iter().zip(values) {
+                            region.assign_advice(
+                                || "value",
+                                *column,
+                                offset,
+                                || Value::known(F::from(*value)),
+                            )?;
+                        }
+                    }
+                    Ok(())
+                },
+            )
+        }
+    }
+
+    /// Range checks every value to a byte.
+    #[derive(Default)]
+    struct RangeCheckCircuit<F> {
+        values: Vec<u64>,
+        _marker: PhantomData<F>,
+    }
+
+    impl<F: FieldExt> Circuit<F> for RangeCheckCircuit<F> {
+        type Config = (RangeTable<8>, RangeCheckConfig<8>);
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            let table = RangeTable::construct(meta);
+            (table, RangeCheckConfig::configure(meta, table))
+        }
+
+        fn synthesize(
+            &self,
+            (table, config): Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            table.load(&mut layouter)?;
+            let chip = RangeCheckChip::<F, 8>::construct(config);
+            layouter.assign_region(
+                || "range check",
+                |mut region| {
+                    for (offset, value) in self.values.iter().enumerate() {
+                        chip.assign(&mut region, offset, *value)?;
+                    }
+                    Ok(())
+                },
+            )
+        }
+    }
+
+    #[test]
+    fn test_mutation_coverage() {
+        let circuit = TestCircuit::<Fr> {
+            values: vec![[2, 3, 6, 7], [0, 5, 0, 1]],
+            _marker: PhantomData,
+        };
+        let report = mutation_coverage(4, &circuit, vec![]).unwrap();
+        assert_eq!(report.cells.len(), 8);
+        assert_eq!(report.num_constrained(), 5);
+        // d is unconstrained, and so is b on the second row, where a is zero.
+        let location = |column, offset| CellLocation {
+            region_index: 0,
+            region_name: String::from("mul"),
+            column,
+            offset,
+        };
+        assert_eq!(
+            report.unconstrained().cloned().collect::<Vec<_>>(),
+            vec![location(1, 1), location(3, 0), location(3, 1)]
+        );
+        for coverage in report
+            .cells
+            .iter()
+            .filter(|coverage| coverage.is_constrained())
+        {
+            assert_eq!(coverage.caught_by.len(), 1);
+            assert!(coverage
+                .caught_by
+                .iter()
+                .all(|name| name.contains("a * b == c")));
+        }
+        assert!(report
+            .report()
+            .starts_with("5 of 8 advice cells constrained\nunconstrained: region 0 ('mul')"));
+    }
+
+    #[test]
+    fn test_mutation_coverage_lookup() {
+        // 0xff + 1 is out of range, while 7 + 1 is still a byte.
+        let circuit = RangeCheckCircuit::<Fr> {
+            values: vec![0xff, 7],
+            _marker: PhantomData,
+        };
+        let report = mutation_coverage(9, &circuit, vec![]).unwrap();
+        assert_eq!(report.cells.len(), 2);
+        assert_eq!(report.num_constrained(), 1);
+        assert!(report.cells[0]
+            .caught_by
+            .iter()
+            .all(|name| name.starts_with("lookup")));
+    }
+}
@@ -0,0 +1,748 @@
+//! A chip for the arithmetic modulo a prime of at most 256 bits that is not
+//! the native field of the circuit, e.g. the base and scalar fields of
+//! secp256k1 and secp256r1 over BN254 or Pallas. It is the shared
+//! infrastructure of the ecrecover circuit and of a future P-256 verification
+//! precompile circuit.
+//!
+//! Integers are laid out as 8 little-endian limbs of 32 bits. Every operation
+//! occupies one row, which constrains `a * b + c == q * p + r`: `b = 1` for an
+//! addition, `c = 0` for a multiplication, and `c = 0` and `r = 1` for
+//! checking the inverse `b` of `a`. A subtraction `a - b` is checked as the
+//! addition of its result and `b`, and the reduction of an integer of 256
+//! bits as its multiplication by one. Rather than carrying the product over all
+//! of its limbs as the modexp circuit does, the row is checked CRT-style:
+//! - modulo the native modulus, over the native values of the limbs;
+//! - modulo `2^288`, over the lowest 9 limbs of the product with signed
+//!   carries, which are range checked to 40 bits after adding an offset of
+//!   `2^39`.
+//!
+//! As the operands are reduced and the limbs of `q` and `r` are range checked
+//! to 32 bits, `|a * b + c - q * p - r| < 2^513`, which is less than the
+//! product of both moduli, so that the equality holds over the integers.
+//!
+//! Every `AssignedInteger` is reduced: `r < p` is checked by range checking
+//! the limbs of `d = p - r - 1`. The range checks of an operation are laid out
+//! along the `NonNativeChip::ROWS` rows from its offset.
+//!
+//! The following tasks are expected to be done:
+//! - Add unreduced additions and subtractions, whose results only need to be
+//!   reduced before a multiplication.
+
+pub mod native;
+
+use std::marker::PhantomData;
+
+use constraint_builder::{BaseConstraintBuilder, Expr};
+use gadgets::{
+    tables::RangeTable,
+    util::{pow_of_two, AssignedValue},
+    DecomposeChip, DecomposeConfig,
+};
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{Region, Value},
+    plonk::{Advice, Column, ConstraintSystem, Error, Expression, Selector, VirtualCells},
+    poly::Rotation,
+};
+
+use native::{Limbs, Modulus, LIMB_BITS, NUM_LIMBS, ONE, ZERO};
+
+/// Number of carries of `a * b + c - q * p - r` modulo `2^(NUM_CARRIES *
+/// LIMB_BITS)`, the binary modulus of the CRT check.
+const NUM_CARRIES: usize = NUM_LIMBS + 1;
+
+/// Bits of a range-checked carry. The carries are signed, so they are offset
+/// by `2^(CARRY_BITS - 1)` before being range checked.
+const CARRY_BITS: usize = 40;
+
+/// Cells of the limbs of a reduced integer, along with its native limbs.
+#[derive(Clone, Debug)]
+pub struct AssignedInteger<F: FieldExt> {
+    pub limbs: Vec<AssignedValue<F>>,
+    pub value: Limbs,
+}
+
+#[derive(Clone, Debug)]
+pub struct NonNativeConfig {
+    modulus: Modulus,
+    limb_range: DecomposeConfig<8, 4>,
+    carry_range: DecomposeConfig<8, 5>,
+    q_reduce: Selector, // enabled on every row, whose `r` is reduced.
+    q_crt: Selector,    // enabled on every operation.
+    q_add: Selector,    // enabled on the additions.
+    q_mul: Selector,    // enabled on the multiplications and the inverses.
+    q_inv: Selector,    // enabled on the inverses.
+    a: [Column<Advice>; NUM_LIMBS],
+    b: [Column<Advice>; NUM_LIMBS],
+    c: [Column<Advice>; NUM_LIMBS],
+    q: [Column<Advice>; NUM_LIMBS],
+    r: [Column<Advice>; NUM_LIMBS],
+    d: [Column<Advice>; NUM_LIMBS],
+    carries: [Column<Advice>; NUM_CARRIES],
+    lt_carries: [Column<Advice>; NUM_LIMBS],
+}
+
+fn query_limbs<F: FieldExt>(
+    meta: &mut VirtualCells<'_, F>,
+    columns: &[Column<Advice>; NUM_LIMBS],
+) -> [Expression<F>; NUM_LIMBS] {
+    columns.map(|column| meta.query_advice(column, Rotation::cur()))
+}
+
+/// Native value of `limbs`, i.e. their value modulo the native modulus.
+fn native_value<F: FieldExt>(limbs: &[Expression<F>]) -> Expression<F> {
+    limbs.iter().enumerate().fold(0.expr(), |acc, (i, limb)| {
+        acc + limb.clone() * Expression::Constant(pow_of_two::<F>(i * LIMB_BITS))
+    })
+}
+
+impl NonNativeConfig {
+    /// Configures the arithmetic modulo the prime `modulus`, e.g.
+    /// [`native::SECP256K1_P`].
+    pub fn configure<F: FieldExt>(
+        meta: &mut ConstraintSystem<F>,
+        table: RangeTable<8>,
+        modulus: Limbs,
+    ) -> Self {
+        let limb_range = DecomposeConfig::configure(meta, table);
+        let carry_range = DecomposeConfig::configure(meta, table);
+
+        let [q_reduce, q_crt, q_add, q_mul, q_inv] = [0; 5].map(|_| meta.selector());
+        let [a, b, c, q, r, d] = [0; 6].map(|_| [0; NUM_LIMBS].map(|_| meta.advice_column()));
+        let carries = [0; NUM_CARRIES].map(|_| meta.advice_column());
+        let lt_carries = [0; NUM_LIMBS].map(|_| meta.advice_column());
+        for column in [a, b, c, q, r, d].iter().flatten().chain(carries.iter()) {
+            meta.enable_equality(*column);
+        }
+        let p = modulus.map(|limb| limb.expr());
+
+        meta.create_gate("a * b + c == q * p + r", |meta| {
+            let mut cb = BaseConstraintBuilder::default();
+            let [a, b, c, q, r] = [a, b, c, q, r].map(|limbs| query_limbs(meta, &limbs));
+            let carries = carries.map(|carry| {
+                meta.query_advice(carry, Rotation::cur())
+                    - Expression::Constant(pow_of_two::<F>(CARRY_BITS - 1))
+            });
+
+            cb.require_equal(
+                "a * b + c == q * p + r modulo the native modulus",
+                native_value(&a) * native_value(&b) + native_value(&c),
+                native_value(&q) * native_value(&p) + native_value(&r),
+            );
+            for k in 0..NUM_CARRIES {
+                let mut t = (0..NUM_LIMBS)
+                    .filter_map(|i| k.checked_sub(i).filter(|j| *j < NUM_LIMBS).map(|j| (i, j)))
+                    .fold(0.expr(), |acc, (i, j)| {
+                        acc + a[i].clone() * b[j].clone() - q[i].clone() * p[j].clone()
+                    });
+                if k < NUM_LIMBS {
+                    t = t + c[k].clone() - r[k].clone();
+                }
+                if k > 0 {
+                    t = t + carries[k - 1].clone();
+                }
+                t = t - carries[k].clone() * Expression::Constant(pow_of_two::<F>(LIMB_BITS));
+                cb.require_zero("limb k of a * b + c - q * p - r is carried over", t);
+            }
+
+            cb.gate(meta.query_selector(q_crt))
+        });
+
+        meta.create_gate("r + d + 1 == p, i.e. r < p", |meta| {
+            let mut cb = BaseConstraintBuilder::default();
+            let [r, d, lt_carries] = [r, d, lt_carries].map(|limbs| query_limbs(meta, &limbs));
+
+            for i in 0..NUM_LIMBS {
+                let carry_in = if i == 0 {
+                    1.expr()
+                } else {
+                    lt_carries[i - 1].clone()
+                };
+                cb.require_boolean("lt_carry is boolean", lt_carries[i].clone());
+                cb.require_equal(
+                    "r_i + d_i + carry_in == p_i + lt_carry_i * 2^32",
+                    r[i].clone() + d[i].clone() + carry_in,
+                    p[i].clone()
+                        + lt_carries[i].clone() * Expression::Constant(pow_of_two::<F>(LIMB_BITS)),
+                );
+            }
+            cb.require_zero(
+                "r + d + 1 does not overflow",
+                lt_carries[NUM_LIMBS - 1].clone(),
+            );
+
+            cb.gate(meta.query_selector(q_reduce))
+        });
+
+        meta.create_gate("addition multiplies by one", |meta| {
+            let mut cb = BaseConstraintBuilder::default();
+            for (i, b) in query_limbs(meta, &b).into_iter().enumerate() {
+                cb.require_equal("b == 1", b, ONE[i].expr());
+            }
+            cb.gate(meta.query_selector(q_add))
+        });
+
+        meta.create_gate("multiplication adds zero", |meta| {
+            let mut cb = BaseConstraintBuilder::default();
+            for c in query_limbs(meta, &c) {
+                cb.require_zero("c == 0", c);
+            }
+            cb.gate(meta.query_selector(q_mul))
+        });
+
+        meta.create_gate("inverse multiplies to one", |meta| {
+            let mut cb = BaseConstraintBuilder::default();
+            for (i, r) in query_limbs(meta, &r).into_iter().enumerate() {
+                cb.require_equal("r == 1", r, ONE[i].expr());
+            }
+            cb.gate(meta.query_selector(q_inv))
+        });
+
+        Self {
+            modulus: Modulus(modulus),
+            limb_range,
+            carry_range,
+            q_reduce,
+            q_crt,
+            q_add,
+            q_mul,
+            q_inv,
+            a,
+            b,
+            c,
+            q,
+            r,
+            d,
+            carries,
+            lt_carries,
+        }
+    }
+}
+
+/// The native operands of a row `a * b + c == q * p + r`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Step {
+    a: Limbs,
+    b: Limbs,
+    c: Limbs,
+    q: Limbs,
+    r: Limbs,
+}
+
+/// Cells of the operands and of the result of a row.
+struct StepCells<F: FieldExt> {
+    a: Vec<AssignedValue<F>>,
+    b: Vec<AssignedValue<F>>,
+    c: Vec<AssignedValue<F>>,
+    r: Vec<AssignedValue<F>>,
+}
+
+#[derive(Clone, Debug)]
+pub struct NonNativeChip<F> {
+    config: NonNativeConfig,
+    _marker: PhantomData<F>,
+}
+
+impl<F: FieldExt> NonNativeChip<F> {
+    /// Number of rows of an operation, along which the limbs of `q`, `r` and
+    /// `d` are range checked. An inverse and a subtraction take twice as many.
+    pub const ROWS: usize = 3 * NUM_LIMBS;
+
+    pub fn construct(config: NonNativeConfig) -> Self {
+        Self {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn modulus(&self) -> Modulus {
+        self.config.modulus
+    }
+
+    /// Assigns the integer `value` at `offset`, which must be reduced for the
+    /// constraints to hold.
+    pub fn assign(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        value: &Limbs,
+    ) -> Result<AssignedInteger<F>, Error> {
+        let step = Step {
+            a: *value,
+            b: ONE,
+            c: ZERO,
+            q: ZERO,
+            r: *value,
+        };
+        let cells = self.assign_step(region, offset, &[], &step)?;
+        Ok(AssignedInteger {
+            limbs: cells.r,
+            value: *value,
+        })
+    }
+
+    /// Assigns the constant `value` at `offset`, which must be reduced. The
+    /// circuit enables a constants column.
+    pub fn assign_constant(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        value: &Limbs,
+    ) -> Result<AssignedInteger<F>, Error> {
+        let integer = self.assign(region, offset, value)?;
+        for (cell, limb) in integer.limbs.iter().zip(value) {
+            region.constrain_constant(cell.cell(), F::from(*limb))?;
+        }
+        Ok(integer)
+    }
+
+    /// Assigns `a mod p` at `offset` for an integer `a` of 256 bits given by
+    /// the cells of its limbs, which the caller range checks to
+    /// [`LIMB_BITS`] bits, e.g. a message hash modulo the order of a curve.
+    pub fn reduce(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        limbs: &[AssignedValue<F>],
+        value: &Limbs,
+    ) -> Result<AssignedInteger<F>, Error> {
+        let (q, r) = self.config.modulus.mul_add(value, &ONE, &ZERO);
+        let step = Step {
+            a: *value,
+            b: ONE,
+            c: ZERO,
+            q,
+            r,
+        };
+        let cells = self.assign_step(
+            region,
+            offset,
+            &[self.config.q_add, self.config.q_mul],
+            &step,
+        )?;
+        for (cell, limb) in cells.a.iter().zip(limbs) {
+            region.constrain_equal(cell.cell(), limb.cell())?;
+        }
+        Ok(AssignedInteger {
+            limbs: cells.r,
+            value: r,
+        })
+    }
+
+    /// Assigns `a + b mod p` at `offset`.
+    pub fn add(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        a: &AssignedInteger<F>,
+        b: &AssignedInteger<F>,
+    ) -> Result<AssignedInteger<F>, Error> {
+        let (q, r) = self.config.modulus.mul_add(&a.value, &ONE, &b.value);
+        let step = Step {
+            a: a.value,
+            b: ONE,
+            c: b.value,
+            q,
+            r,
+        };
+        let cells = self.assign_step(region, offset, &[self.config.q_add], &step)?;
+        Self::constrain_limbs(region, &cells.a, a)?;
+        Self::constrain_limbs(region, &cells.c, b)?;
+        Ok(AssignedInteger {
+            limbs: cells.r,
+            value: r,
+        })
+    }
+
+    /// Assigns `a * b mod p` at `offset`.
+    pub fn mul(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        a: &AssignedInteger<F>,
+        b: &AssignedInteger<F>,
+    ) -> Result<AssignedInteger<F>, Error> {
+        let (q, r) = self.config.modulus.mul_add(&a.value, &b.value, &ZERO);
+        let step = Step {
+            a: a.value,
+            b: b.value,
+            c: ZERO,
+            q,
+            r,
+        };
+        let cells = self.assign_step(region, offset, &[self.config.q_mul], &step)?;
+        Self::constrain_limbs(region, &cells.a, a)?;
+        Self::constrain_limbs(region, &cells.b, b)?;
+        Ok(AssignedInteger {
+            limbs: cells.r,
+            value: r,
+        })
+    }
+
+    /// Assigns `a - b mod p` at `offset`, and checks that its sum with `b` is
+    /// `a` over the next [`Self::ROWS`] rows.
+    pub fn sub(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        a: &AssignedInteger<F>,
+        b: &AssignedInteger<F>,
+    ) -> Result<AssignedInteger<F>, Error> {
+        let difference =
+            self.assign(region, offset, &self.config.modulus.sub(&a.value, &b.value))?;
+        let sum = self.add(region, offset + Self::ROWS, &difference, b)?;
+        Self::constrain_limbs(region, &sum.limbs, a)?;
+        Ok(difference)
+    }
+
+    /// Assigns the inverse of `a` at `offset`, and checks it over the next
+    /// [`Self::ROWS`] rows. The constraints do not hold if `a` is zero.
+    pub fn inv(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        a: &AssignedInteger<F>,
+    ) -> Result<AssignedInteger<F>, Error> {
+        let inv = self.assign(
+            region,
+            offset,
+            &self.config.modulus.inv(&a.value).unwrap_or(ZERO),
+        )?;
+        let (q, _) = self.config.modulus.mul_add(&a.value, &inv.value, &ZERO);
+        let step = Step {
+            a: a.value,
+            b: inv.value,
+            c: ZERO,
+            q,
+            r: ONE,
+        };
+        let cells = self.assign_step(
+            region,
+            offset + Self::ROWS,
+            &[self.config.q_mul, self.config.q_inv],
+            &step,
+        )?;
+        Self::constrain_limbs(region, &cells.a, a)?;
+        Self::constrain_limbs(region, &cells.b, &inv)?;
+        Ok(inv)
+    }
+
+    /// Constrains `a` and `b` to be the same integer.
+    pub fn constrain_equal(
+        &self,
+        region: &mut Region<'_, F>,
+        a: &AssignedInteger<F>,
+        b: &AssignedInteger<F>,
+    ) -> Result<(), Error> {
+        Self::constrain_limbs(region, &a.limbs, b)
+    }
+
+    fn constrain_limbs(
+        region: &mut Region<'_, F>,
+        cells: &[AssignedValue<F>],
+        integer: &AssignedInteger<F>,
+    ) -> Result<(), Error> {
+        for (cell, limb) in cells.iter().zip(integer.limbs.iter()) {
+            region.constrain_equal(cell.cell(), limb.cell())?;
+        }
+        Ok(())
+    }
+
+    /// Assigns `step` at `offset` along with its range checks, enabling the
+    /// CRT check of the operation if `selectors` is not empty.
+    fn assign_step(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        selectors: &[Selector],
+        step: &Step,
+    ) -> Result<StepCells<F>, Error> {
+        let config = &self.config;
+        let p = &config.modulus.0;
+        config.q_reduce.enable(region, offset)?;
+        if !selectors.is_empty() {
+            config.q_crt.enable(region, offset)?;
+        }
+        for selector in selectors {
+            selector.enable(region, offset)?;
+        }
+
+        let mut assign_limbs = |annotation: &'static str,
+                                columns: &[Column<Advice>],
+                                values: &[u64]|
+         -> Result<Vec<AssignedValue<F>>, Error> {
+            columns
+                .iter()
+                .zip(values)
+                .map(|(column, value)| {
+                    region.assign_advice(
+                        || annotation,
+                        *column,
+                        offset,
+                        || Value::known(F::from(*value)),
+                    )
+                })
+                .collect()
+        };
+
+        // r + d + 1 == p, along with the carries of the limb-wise addition.
+        let mut d = [0; NUM_LIMBS];
+        let mut lt_carries = [0; NUM_LIMBS];
+        let mut carry_in = 1;
+        for i in 0..NUM_LIMBS {
+            let value = p[i] as i64 - step.r[i] as i64 - carry_in as i64;
+            d[i] = value.rem_euclid(1 << LIMB_BITS) as u64;
+            lt_carries[i] = (value < 0) as u64;
+            carry_in = lt_carries[i];
+        }
+
+        // Signed carries of a * b + c - q * p - r, offset by 2^(CARRY_BITS - 1).
+        let mut carries = [0; NUM_CARRIES];
+        let mut carry = 0i128;
+        for (k, offset_carry) in carries.iter_mut().enumerate() {
+            let mut t = carry;
+            for i in 0..NUM_LIMBS {
+                if let Some(j) = k.checked_sub(i).filter(|j| *j < NUM_LIMBS) {
+                    t += (step.a[i] * step.b[j]) as i128 - (step.q[i] * p[j]) as i128;
+                }
+            }
+            if k < NUM_LIMBS {
+                t += step.c[k] as i128 - step.r[k] as i128;
+            }
+            carry = t >> LIMB_BITS;
+            *offset_carry = (carry + (1 << (CARRY_BITS - 1))) as u64;
+        }
+
+        let a = assign_limbs("a", &config.a, &step.a)?;
+        let b = assign_limbs("b", &config.b, &step.b)?;
+        let c = assign_limbs("c", &config.c, &step.c)?;
+        let q = assign_limbs("q", &config.q, &step.q)?;
+        let r = assign_limbs("r", &config.r, &step.r)?;
+        let d = assign_limbs("d", &config.d, &d)?;
+        let carries = assign_limbs("carry", &config.carries, &carries)?;
+        assign_limbs("lt_carry", &config.lt_carries, &lt_carries)?;
+
+        let limb_range = DecomposeChip::construct(config.limb_range);
+        let carry_range = DecomposeChip::construct(config.carry_range);
+        for (i, cell) in q.iter().chain(&r).chain(&d).enumerate() {
+            limb_range.copy(region, offset + i, cell)?;
+        }
+        for (i, cell) in carries.iter().enumerate() {
+            carry_range.copy(region, offset + i, cell)?;
+        }
+        Ok(StepCells { a, b, c, r })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gadgets::rows;
+    use halo2_proofs::{
+        circuit::{Layouter, SimpleFloorPlanner},
+        dev::MockProver,
+        halo2curves::{bn256::Fr, pasta::pallas},
+        plonk::Circuit,
+    };
+    use native::{SECP256K1_N, SECP256K1_P, SECP256R1_N, SECP256R1_P};
+
+    const MODULI: [Limbs; 4] = [SECP256K1_P, SECP256K1_N, SECP256R1_P, SECP256R1_N];
+
+    #[derive(Clone, Copy, Debug)]
+    enum Op {
+        Add(Limbs, Limbs),
+        Mul(Limbs, Limbs),
+        Sub(Limbs, Limbs),
+        Inv(Limbs),
+        /// A multiplication whose quotient and result are given.
+        Step(Limbs, Limbs, Limbs, Limbs),
+    }
+
+    /// Runs every operation modulo `MODULI[M]`, and checks its result against
+    /// the native one, or against the given result of a [`Op::Step`].
+    #[derive(Default)]
+    struct TestCircuit<F, const M: usize> {
+        ops: Vec<Op>,
+        _marker: PhantomData<F>,
+    }
+
+    impl<F: FieldExt, const M: usize> Circuit<F> for TestCircuit<F, M> {
+        type Config = (RangeTable<8>, NonNativeConfig);
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            let table = RangeTable::construct(meta);
+            (table, NonNativeConfig::configure(meta, table, MODULI[M]))
+        }
+
+        fn synthesize(
+            &self,
+            (table, config): Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            table.load(&mut layouter)?;
+            let chip = NonNativeChip::<F>::construct(config);
+            let p = chip.modulus();
+            let rows = NonNativeChip::<F>::ROWS;
+            layouter.assign_region(
+                || "non-native field",
+                |mut region| {
+                    let mut offset = 0;
+                    for op in self.ops.iter() {
+                        let (result, expected) = match *op {
+                            Op::Add(a, b) | Op::Mul(a, b) | Op::Sub(a, b) => {
+                                let a = chip.assign(&mut region, offset, &a)?;
+                                let b = chip.assign(&mut region, offset + rows, &b)?;
+                                let at = offset + 2 * rows;
+                                offset += 4 * rows;
+                                match op {
+                                    Op::Add(..) => (
+                                        chip.add(&mut region, at, &a, &b)?,
+                                        p.add(&a.value, &b.value),
+                                    ),
+                                    Op::Mul(..) => (
+                                        chip.mul(&mut region, at, &a, &b)?,
+                                        p.mul(&a.value, &b.value),
+                                    ),
+                                    _ => (
+                                        chip.sub(&mut region, at, &a, &b)?,
+                                        p.sub(&a.value, &b.value),
+                                    ),
+                                }
+                            }
+                            Op::Inv(a) => {
+                                let a = chip.assign(&mut region, offset, &a)?;
+                                offset += 3 * rows;
+                                let expected = p.inv(&a.value).unwrap_or(ZERO);
+                                (chip.inv(&mut region, offset - 2 * rows, &a)?, expected)
+                            }
+                            Op::Step(a, b, q, r) => {
+                                let step = Step {
+                                    a,
+                                    b,
+                                    c: ZERO,
+                                    q,
+                                    r,
+                                };
+                                let cells = chip.assign_step(
+                                    &mut region,
+                                    offset,
+                                    &[chip.config.q_mul],
+                                    &step,
+                                )?;
+                                offset += rows;
+                                (
+                                    AssignedInteger {
+                                        limbs: cells.r,
+                                        value: r,
+                                    },
+                                    r,
+                                )
+                            }
+                        };
+                        let expected = chip.assign(&mut region, offset, &expected)?;
+                        offset += rows;
+                        NonNativeChip::constrain_limbs(&mut region, &result.limbs, &expected)?;
+                    }
+                    Ok(())
+                },
+            )
+        }
+    }
+
+    fn verify<F: FieldExt, const M: usize>(ops: Vec<Op>) -> bool {
+        let circuit = TestCircuit::<F, M> {
+            ops,
+            _marker: PhantomData,
+        };
+        let prover = MockProver::run(rows::min_k(&circuit), &circuit, vec![]).unwrap();
+        prover.verify().is_ok()
+    }
+
+    /// The edge cases of the operands modulo `MODULI[M]`: zero, one, `p - 1`,
+    /// `p - 2` and an integer all of whose limbs are at the top of their range
+    /// but the most significant one.
+    fn edge_cases<const M: usize>() -> Vec<Limbs> {
+        let p = Modulus(MODULI[M]);
+        let mut top = [0xFFFF_FFFF; NUM_LIMBS];
+        top[NUM_LIMBS - 1] = 0;
+        vec![ZERO, ONE, p.max(), p.add(&p.max(), &p.max()), top]
+    }
+
+    fn test_edge_cases<F: FieldExt, const M: usize>() {
+        let values = edge_cases::<M>();
+        let mut ops = vec![];
+        for (i, a) in values.iter().enumerate() {
+            for b in values[i..].iter() {
+                ops.push(Op::Add(*a, *b));
+                ops.push(Op::Mul(*a, *b));
+                ops.push(Op::Sub(*a, *b));
+                ops.push(Op::Sub(*b, *a));
+            }
+            if *a != ZERO {
+                ops.push(Op::Inv(*a));
+            }
+        }
+        assert!(verify::<F, M>(ops));
+    }
+
+    #[test]
+    fn test_non_native_edge_cases() {
+        test_edge_cases::<Fr, 0>();
+        test_edge_cases::<Fr, 1>();
+        test_edge_cases::<Fr, 2>();
+        test_edge_cases::<Fr, 3>();
+        test_edge_cases::<pallas::Base, 0>();
+    }
+
+    #[test]
+    fn test_non_native_unsound_witnesses() {
+        let p = Modulus(SECP256K1_P);
+        let max = p.max();
+        // (p - 1)^2 = (p - 2) * p + 1
+        let q = p.add(&max, &max);
+        assert!(verify::<Fr, 0>(vec![Op::Step(max, max, q, ONE)]));
+
+        // the result is not reduced: (p - 1)^2 = (p - 3) * p + p + 1.
+        let not_reduced = native::mul_add_wide(&ONE, &ONE, &p.0)[..NUM_LIMBS]
+            .try_into()
+            .unwrap();
+        let q_minus_one = p.add(&q, &max);
+        assert!(!verify::<Fr, 0>(vec![Op::Step(
+            max,
+            max,
+            q_minus_one,
+            not_reduced
+        )]));
+
+        // the result is the same integer, but its lowest limb overflows.
+        let overflow = [1 << LIMB_BITS, 0, 0, 0, 0, 0, 0, 0];
+        assert!(!verify::<Fr, 0>(vec![Op::Step(
+            ONE,
+            [0, 1, 0, 0, 0, 0, 0, 0],
+            ZERO,
+            overflow
+        )]));
+        assert!(verify::<Fr, 0>(vec![Op::Step(
+            ONE,
+            [0, 1, 0, 0, 0, 0, 0, 0],
+            ZERO,
+            [0, 1, 0, 0, 0, 0, 0, 0]
+        )]));
+
+        // the result is off by one.
+        assert!(!verify::<Fr, 0>(vec![Op::Step(
+            max,
+            max,
+            q,
+            [2, 0, 0, 0, 0, 0, 0, 0]
+        )]));
+
+        // zero has no inverse.
+        assert!(!verify::<Fr, 0>(vec![Op::Inv(ZERO)]));
+    }
+}
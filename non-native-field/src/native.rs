@@ -0,0 +1,254 @@
+//! Native arithmetic modulo a 256-bit prime, used to compute the witness of
+//! the chip and as the reference it is tested against.
+//!
+//! Integers are represented by [`NUM_LIMBS`] little-endian limbs of
+//! [`LIMB_BITS`] bits, stored in `u64`s so that they can be passed to the
+//! gadgets directly.
+
+/// Number of bits of a limb.
+pub const LIMB_BITS: usize = 32;
+
+/// Number of limbs of a 256-bit integer.
+pub const NUM_LIMBS: usize = 8;
+
+/// Little-endian limbs of a 256-bit integer.
+pub type Limbs = [u64; NUM_LIMBS];
+
+/// The integer `0`.
+pub const ZERO: Limbs = [0; NUM_LIMBS];
+
+/// The integer `1`.
+pub const ONE: Limbs = [1, 0, 0, 0, 0, 0, 0, 0];
+
+/// The base field modulus of secp256k1, `2^256 - 2^32 - 977`.
+pub const SECP256K1_P: Limbs = [
+    0xFFFF_FC2F,
+    0xFFFF_FFFE,
+    0xFFFF_FFFF,
+    0xFFFF_FFFF,
+    0xFFFF_FFFF,
+    0xFFFF_FFFF,
+    0xFFFF_FFFF,
+    0xFFFF_FFFF,
+];
+
+/// The order of the group of secp256k1, i.e. the modulus of its scalars.
+pub const SECP256K1_N: Limbs = [
+    0xD036_4141,
+    0xBFD2_5E8C,
+    0xAF48_A03B,
+    0xBAAE_DCE6,
+    0xFFFF_FFFE,
+    0xFFFF_FFFF,
+    0xFFFF_FFFF,
+    0xFFFF_FFFF,
+];
+
+/// The base field modulus of secp256r1 (P-256),
+/// `2^256 - 2^224 + 2^192 + 2^96 - 1`.
+pub const SECP256R1_P: Limbs = [
+    0xFFFF_FFFF,
+    0xFFFF_FFFF,
+    0xFFFF_FFFF,
+    0x0000_0000,
+    0x0000_0000,
+    0x0000_0000,
+    0x0000_0001,
+    0xFFFF_FFFF,
+];
+
+/// The order of the group of secp256r1, i.e. the modulus of its scalars.
+pub const SECP256R1_N: Limbs = [
+    0xFC63_2551,
+    0xF3B9_CAC2,
+    0xA717_9E84,
+    0xBCE6_FAAD,
+    0xFFFF_FFFF,
+    0xFFFF_FFFF,
+    0x0000_0000,
+    0xFFFF_FFFF,
+];
+
+fn bit(limbs: &[u64], i: usize) -> bool {
+    (limbs[i / LIMB_BITS] >> (i % LIMB_BITS)) & 1 == 1
+}
+
+fn is_less(lhs: &[u64], rhs: &[u64]) -> bool {
+    for i in (0..lhs.len().max(rhs.len())).rev() {
+        let (lhs, rhs) = (
+            lhs.get(i).copied().unwrap_or(0),
+            rhs.get(i).copied().unwrap_or(0),
+        );
+        if lhs != rhs {
+            return lhs < rhs;
+        }
+    }
+    false
+}
+
+/// `a - b` for `b <= a`, over limbs of [`LIMB_BITS`] bits.
+fn sub(a: &Limbs, b: &Limbs) -> Limbs {
+    let mut difference = ZERO;
+    let mut borrow = 0i64;
+    for i in 0..NUM_LIMBS {
+        let value = a[i] as i64 - b[i] as i64 - borrow;
+        borrow = (value < 0) as i64;
+        difference[i] = (value + (borrow << LIMB_BITS)) as u64;
+    }
+    difference
+}
+
+/// Full value of `a * b + c`, in `2 * NUM_LIMBS` limbs.
+pub fn mul_add_wide(a: &Limbs, b: &Limbs, c: &Limbs) -> [u64; 2 * NUM_LIMBS] {
+    let mut product = [0u128; 2 * NUM_LIMBS];
+    for (i, a) in a.iter().enumerate() {
+        for (j, b) in b.iter().enumerate() {
+            product[i + j] += (*a as u128) * (*b as u128);
+        }
+    }
+    for (limb, c) in product.iter_mut().zip(c) {
+        *limb += *c as u128;
+    }
+
+    let mut limbs = [0u64; 2 * NUM_LIMBS];
+    let mut carry = 0u128;
+    for (limb, value) in limbs.iter_mut().zip(product) {
+        let value = value + carry;
+        *limb = (value as u64) & u32::MAX as u64;
+        carry = value >> LIMB_BITS;
+    }
+    limbs
+}
+
+/// A prime modulus of at most 256 bits, e.g. [`SECP256K1_P`]. The operands
+/// of its methods must be reduced.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Modulus(pub Limbs);
+
+impl Modulus {
+    /// Computes `(q, r)` such that `a * b + c = q * p + r` and `r < p`.
+    pub fn mul_add(&self, a: &Limbs, b: &Limbs, c: &Limbs) -> (Limbs, Limbs) {
+        assert!(self.0 != ZERO, "modulus is zero");
+        let wide = mul_add_wide(a, b, c);
+
+        // Schoolbook long division, one bit at a time.
+        let mut q = [0u64; 2 * NUM_LIMBS];
+        let mut r = [0u64; NUM_LIMBS + 1];
+        for i in (0..2 * NUM_LIMBS * LIMB_BITS).rev() {
+            let mut carry = bit(&wide, i) as u64;
+            for limb in r.iter_mut() {
+                let value = (*limb << 1) | carry;
+                *limb = value & u32::MAX as u64;
+                carry = value >> LIMB_BITS;
+            }
+            if !is_less(&r, &self.0) {
+                let mut borrow = 0i64;
+                for (j, limb) in r.iter_mut().enumerate() {
+                    let value = *limb as i64 - self.0.get(j).copied().unwrap_or(0) as i64 - borrow;
+                    borrow = (value < 0) as i64;
+                    *limb = (value + (borrow << LIMB_BITS)) as u64;
+                }
+                q[i / LIMB_BITS] |= 1 << (i % LIMB_BITS);
+            }
+        }
+
+        assert!(
+            q[NUM_LIMBS..].iter().all(|limb| *limb == 0),
+            "quotient does not fit in 256 bits"
+        );
+        (
+            q[..NUM_LIMBS]
+                .try_into()
+                .expect("quotient has NUM_LIMBS limbs"),
+            r[..NUM_LIMBS]
+                .try_into()
+                .expect("remainder has NUM_LIMBS limbs"),
+        )
+    }
+
+    pub fn add(&self, a: &Limbs, b: &Limbs) -> Limbs {
+        self.mul_add(a, &ONE, b).1
+    }
+
+    /// `-a`, i.e. `p - a` but for a zero `a`.
+    pub fn neg(&self, a: &Limbs) -> Limbs {
+        if *a == ZERO {
+            ZERO
+        } else {
+            sub(&self.0, a)
+        }
+    }
+
+    pub fn sub(&self, a: &Limbs, b: &Limbs) -> Limbs {
+        self.add(a, &self.neg(b))
+    }
+
+    pub fn mul(&self, a: &Limbs, b: &Limbs) -> Limbs {
+        self.mul_add(a, b, &ZERO).1
+    }
+
+    pub fn pow(&self, a: &Limbs, exponent: &Limbs) -> Limbs {
+        let mut acc = ONE;
+        for i in (0..NUM_LIMBS * LIMB_BITS).rev() {
+            acc = self.mul(&acc, &acc);
+            if bit(exponent, i) {
+                acc = self.mul(&acc, a);
+            }
+        }
+        acc
+    }
+
+    /// Inverse of `a` by Fermat's little theorem, or `None` if `a` is zero.
+    pub fn inv(&self, a: &Limbs) -> Option<Limbs> {
+        if *a == ZERO {
+            return None;
+        }
+        Some(self.pow(a, &sub(&self.0, &[2, 0, 0, 0, 0, 0, 0, 0])))
+    }
+
+    /// `p - 1`, the largest reduced integer.
+    pub fn max(&self) -> Limbs {
+        sub(&self.0, &ONE)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mul_add() {
+        for p in [SECP256K1_P, SECP256K1_N, SECP256R1_P, SECP256R1_N] {
+            let p = Modulus(p);
+            let max = p.max();
+            // (p - 1)^2 + (p - 1) = (p - 1) * p
+            assert_eq!(p.mul_add(&max, &max, &max), (max, ZERO));
+            assert_eq!(p.add(&max, &ONE), ZERO);
+            assert_eq!(p.mul(&max, &max), ONE);
+            assert_eq!(p.mul(&max, &ZERO), ZERO);
+            assert_eq!(p.sub(&ZERO, &ONE), max);
+            assert_eq!(p.sub(&max, &max), ZERO);
+            assert_eq!(p.neg(&ZERO), ZERO);
+            assert_eq!(p.add(&p.neg(&max), &max), ZERO);
+        }
+    }
+
+    #[test]
+    fn test_inv() {
+        for p in [SECP256K1_P, SECP256K1_N, SECP256R1_P, SECP256R1_N] {
+            let p = Modulus(p);
+            for a in [
+                ONE,
+                [2, 0, 0, 0, 0, 0, 0, 0],
+                [0xFFFF_FFFF; NUM_LIMBS],
+                p.max(),
+            ] {
+                let a = p.add(&a, &ZERO);
+                let inv = p.inv(&a).expect("a is not zero");
+                assert_eq!(p.mul(&a, &inv), ONE);
+            }
+            assert_eq!(p.inv(&ZERO), None);
+            assert_eq!(p.inv(&p.max()), Some(p.max()));
+        }
+    }
+}
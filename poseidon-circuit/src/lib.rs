@@ -0,0 +1,898 @@
+//! The Poseidon circuit verifies calls to the [`Poseidon`] hash over the
+//! scalar field the circuits are defined over, e.g. the scalar field of BN254
+//! or the base field of Pallas. The round constants and the MDS matrix are
+//! generated for the field by the Grain LFSR of the reference implementation,
+//! with the round numbers of circomlib for BN254, see the `native` module.
+//!
+//! Unlike the hash functions of the EVM precompiles, Poseidon is defined over
+//! field elements and its permutation takes a few constraints per round,
+//! which makes it the hash of choice within circuits, e.g. for Merkle trees
+//! or the transcript of a verifier in-circuit.
+//!
+//! The permutation operates over a state of `T` field elements, with
+//! `FULL_ROUNDS` full rounds and `partial_rounds(T)` partial rounds by
+//! default. The sponge absorbs `T - 1` inputs per permutation, starting from
+//! a capacity that holds the number of inputs, as described in the `native`
+//! module. It is not the sponge of circomlib, which starts from a zero
+//! capacity, so the digests differ from those of circomlib even over BN254:
+//! the digests of the `dev` fixtures are those of [`native::hash`].
+//!
+//! The circuit lays out a permutation over `num_rounds + 1` rows, one row per
+//! state between rounds, with the round constants in fixed columns. The
+//! gates of the full and partial rounds constrain the next state against the
+//! current one, while the gates of the absorption constrain the initial state
+//! of each permutation against the chunk of inputs and the previous state.
+//!
+//! Each element of a chunk is flagged as an input or as padding: the inputs
+//! come first, the padding is zero, and every chunk but the last is full of
+//! inputs, the last one holding at least one unless the call has none, so
+//! that a call of `n` inputs takes exactly `max(1, ceil(n / (T - 1)))`
+//! permutations. The number of inputs and their RLC are accumulated along the
+//! inputs of the chunks, the accumulators of a chunk continuing from those of
+//! the chunk of the previous permutation, `num_rounds + 1` rows above.
+//!
+//! Like the hash circuits of the workspace, the circuit exposes the calls
+//! through the `PoseidonTable`, with an `is_enabled` column, the RLC of the
+//! inputs, their number and the digest. The number of inputs, their RLC and
+//! the digest are copied from the layout of the permutations, the number of
+//! inputs being both the one the capacity is initialised with and the one
+//! accumulated over the chunks.
+//!
+//! [`Poseidon`]: https://eprint.iacr.org/2019/458.pdf
+
+pub mod native;
+
+use std::marker::PhantomData;
+
+use constraint_builder::{BaseConstraintBuilder, Expr};
+use gadgets::{
+    tables::RangeTable,
+    util::{pow_of_two, AssignedValue},
+    AssignedBytes, Endianness, HashCircuitExt, WordChip, WordConfig, WordLoHi,
+};
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{Layouter, Value},
+    plonk::{
+        Advice, Any, Challenge, Column, ConstraintSystem, Error, Expression, FirstPhase, Fixed,
+        SecondPhase, Selector, VirtualCells,
+    },
+    poly::Rotation,
+};
+
+use native::PoseidonParams;
+
+pub use native::{partial_rounds, FULL_ROUNDS};
+
+#[derive(Clone, Debug)]
+pub struct PoseidonTable {
+    pub is_enabled: Column<Advice>,
+    pub input_rlc: Column<Advice>,
+    pub input_len: Column<Advice>,
+    pub output: Column<Advice>,
+}
+
+impl PoseidonTable {
+    pub fn construct<F: FieldExt>(meta: &mut ConstraintSystem<F>) -> Self {
+        let is_enabled = meta.advice_column();
+        let input_rlc = meta.advice_column_in(SecondPhase);
+        let input_len = meta.advice_column();
+        let output = meta.advice_column();
+        for column in [is_enabled, input_rlc, input_len, output] {
+            meta.enable_equality(column);
+        }
+
+        Self {
+            is_enabled,
+            input_rlc,
+            input_len,
+            output,
+        }
+    }
+
+    pub fn columns(&self) -> Vec<Column<Any>> {
+        vec![
+            self.is_enabled.into(),
+            self.input_rlc.into(),
+            self.input_len.into(),
+            self.output.into(),
+        ]
+    }
+
+    pub fn annotations(&self) -> Vec<String> {
+        vec![
+            String::from("is_enabled"),
+            String::from("input_rlc"),
+            String::from("input_len"),
+            String::from("output"),
+        ]
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct PoseidonConfig<F, const T: usize> {
+    q_full: Selector,
+    q_partial: Selector,
+    q_init: Selector,
+    q_absorb: Selector,
+    q_table: Selector,
+    q_digest: Selector,
+    state: [Column<Advice>; T],
+    chunk: Vec<Column<Advice>>,
+    /// Whether each element of the chunk is an input rather than padding.
+    is_input: Vec<Column<Advice>>,
+    /// The RLC of the inputs of the call up to each element of the chunk.
+    rlc: Vec<Column<Advice>>,
+    /// The number of inputs of the call up to the end of the chunk.
+    count: Column<Advice>,
+    input_len: Column<Advice>,
+    round_constants: [Column<Fixed>; T],
+    digest: Column<Advice>,
+    carry: Column<Advice>,
+    digest_word: WordConfig,
+    byte_table: RangeTable<8>,
+    table: PoseidonTable,
+    challenge: Challenge,
+    params: PoseidonParams<F>,
+}
+
+impl<F: FieldExt, const T: usize> PoseidonConfig<F, T> {
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        table: PoseidonTable,
+        params: PoseidonParams<F>,
+    ) -> Self {
+        assert_eq!(params.t, T, "the parameters are for a width of T");
+
+        let q_full = meta.selector();
+        let q_partial = meta.selector();
+        let q_init = meta.selector();
+        let q_absorb = meta.selector();
+        let q_table = meta.selector();
+        let q_digest = meta.selector();
+        let state = [(); T].map(|_| meta.advice_column());
+        let chunk = (1..T).map(|_| meta.advice_column()).collect::<Vec<_>>();
+        let is_input = (1..T).map(|_| meta.advice_column()).collect::<Vec<_>>();
+        let input_len = meta.advice_column();
+        let count_column = meta.advice_column();
+        let round_constants = [(); T].map(|_| meta.fixed_column());
+        let digest = meta.advice_column();
+        let carry = meta.advice_column();
+        let byte_table = RangeTable::construct(meta);
+        let digest_word = WordConfig::configure(meta, byte_table);
+        let challenge = meta.challenge_usable_after(FirstPhase);
+        let rlc_columns = (1..T)
+            .map(|_| meta.advice_column_in(SecondPhase))
+            .collect::<Vec<_>>();
+        // The constants of the table share the column of the first round
+        // constant, on rows of their own.
+        meta.enable_constant(round_constants[0]);
+        meta.enable_equality(state[0]);
+        meta.enable_equality(input_len);
+        meta.enable_equality(digest);
+        meta.enable_equality(count_column);
+        meta.enable_equality(rlc_columns[T - 2]);
+
+        // next_i = sum_j mds_ij * sbox_j, for the S-box over the state and the
+        // round constants of the current row.
+        let round_gate = |meta: &mut VirtualCells<'_, F>, full: bool| {
+            let sbox = state
+                .iter()
+                .zip(round_constants)
+                .enumerate()
+                .map(|(i, (x, c))| {
+                    let x = meta.query_advice(*x, Rotation::cur())
+                        + meta.query_fixed(c, Rotation::cur());
+                    if full || i == 0 {
+                        x.clone() * x.clone() * x.clone() * x.clone() * x
+                    } else {
+                        x
+                    }
+                })
+                .collect::<Vec<_>>();
+
+            let mut cb = BaseConstraintBuilder::default();
+            for (x, row) in state.iter().zip(&params.mds) {
+                let mixed = row.iter().zip(&sbox).fold(0.expr(), |acc, (m, x)| {
+                    acc + Expression::Constant(*m) * x.clone()
+                });
+                cb.require_equal(
+                    "next state is the output of the round",
+                    meta.query_advice(*x, Rotation::next()),
+                    mixed,
+                );
+            }
+            cb
+        };
+        meta.create_gate("full round", |meta| {
+            let cb = round_gate(meta, true);
+            cb.gate(meta.query_selector(q_full))
+        });
+        meta.create_gate("partial round", |meta| {
+            let cb = round_gate(meta, false);
+            cb.gate(meta.query_selector(q_partial))
+        });
+
+        meta.create_gate("initial state of the sponge", |meta| {
+            let mut cb = BaseConstraintBuilder::default();
+            cb.require_equal(
+                "capacity == input_len * 2^64",
+                meta.query_advice(state[0], Rotation::cur()),
+                meta.query_advice(input_len, Rotation::cur())
+                    * Expression::Constant(native::initial_capacity(1)),
+            );
+            for (x, input) in state.iter().skip(1).zip(&chunk) {
+                cb.require_equal(
+                    "state == chunk",
+                    meta.query_advice(*x, Rotation::cur()),
+                    meta.query_advice(*input, Rotation::cur()),
+                );
+            }
+            cb.gate(meta.query_selector(q_init))
+        });
+
+        meta.create_gate("absorption of a chunk", |meta| {
+            let mut cb = BaseConstraintBuilder::default();
+            cb.require_equal(
+                "capacity == capacity::prev",
+                meta.query_advice(state[0], Rotation::cur()),
+                meta.query_advice(state[0], Rotation::prev()),
+            );
+            for (x, input) in state.iter().skip(1).zip(&chunk) {
+                cb.require_equal(
+                    "state == state::prev + chunk",
+                    meta.query_advice(*x, Rotation::cur()),
+                    meta.query_advice(*x, Rotation::prev())
+                        + meta.query_advice(*input, Rotation::cur()),
+                );
+            }
+            cb.gate(meta.query_selector(q_absorb))
+        });
+
+        // The flags, the padding and the accumulators of a chunk, which
+        // continue from `count` and `rlc` of the previous chunk.
+        let chunk_gate =
+            |meta: &mut VirtualCells<'_, F>, count: Expression<F>, rlc: Expression<F>| {
+                let mut cb = BaseConstraintBuilder::default();
+                let r = meta.query_challenge(challenge);
+                let (mut count, mut rlc, mut prev_is_input) = (count, rlc, 1.expr());
+                for ((x, flag), acc) in chunk.iter().zip(&is_input).zip(&rlc_columns) {
+                    let x = meta.query_advice(*x, Rotation::cur());
+                    let flag = meta.query_advice(*flag, Rotation::cur());
+                    let acc = meta.query_advice(*acc, Rotation::cur());
+                    cb.require_boolean("is_input is boolean", flag.clone());
+                    cb.require_zero(
+                        "the inputs precede the padding",
+                        flag.clone() * (1.expr() - prev_is_input),
+                    );
+                    cb.require_zero("the padding is zero", (1.expr() - flag.clone()) * x.clone());
+                    cb.require_equal(
+                        "rlc == rlc::prev * r + x if is_input, rlc::prev otherwise",
+                        acc.clone(),
+                        rlc.clone() + flag.clone() * (rlc * (r.clone() - 1.expr()) + x),
+                    );
+                    count = count + flag.clone();
+                    (rlc, prev_is_input) = (acc, flag);
+                }
+                cb.require_equal(
+                    "count == count::prev + inputs in the chunk",
+                    meta.query_advice(count_column, Rotation::cur()),
+                    count,
+                );
+                cb
+            };
+        meta.create_gate("inputs of the first chunk", |meta| {
+            let cb = chunk_gate(meta, 0.expr(), 0.expr());
+            cb.gate(meta.query_selector(q_init))
+        });
+        // The chunk of the previous permutation, at the row of its first state.
+        let prev_chunk = Rotation(-(params.num_rounds() as i32 + 1));
+        meta.create_gate("inputs of the next chunks", |meta| {
+            let count = meta.query_advice(count_column, prev_chunk);
+            let rlc = meta.query_advice(rlc_columns[T - 2], prev_chunk);
+            let mut cb = chunk_gate(meta, count, rlc);
+            cb.require_equal(
+                "the previous chunk is full",
+                meta.query_advice(is_input[T - 2], prev_chunk),
+                1.expr(),
+            );
+            cb.require_equal(
+                "the chunk holds an input",
+                meta.query_advice(is_input[0], Rotation::cur()),
+                1.expr(),
+            );
+            cb.gate(meta.query_selector(q_absorb))
+        });
+
+        meta.create_gate("validity check over the table rows", |meta| {
+            let mut cb = BaseConstraintBuilder::default();
+            let is_enabled = meta.query_advice(table.is_enabled, Rotation::cur());
+
+            cb.require_boolean("is_enabled is boolean", is_enabled.clone());
+            cb.condition(1.expr() - is_enabled, |cb| {
+                for column in [table.input_rlc, table.input_len, table.output] {
+                    cb.require_zero(
+                        "value == 0 if !is_enabled",
+                        meta.query_advice(column, Rotation::cur()),
+                    );
+                }
+            });
+            cb.gate(meta.query_selector(q_table))
+        });
+
+        // The bytes of the digest are those of the word on the current row,
+        // and the word on the next row is `p - 1 - digest`, added limb by
+        // limb with a carry: as the limbs are 128 bits, the sums do not wrap
+        // around the modulus, so that the word is the canonical encoding of
+        // the digest.
+        meta.create_gate("digest bytes are canonical", |meta| {
+            let mut cb = BaseConstraintBuilder::default();
+            let word = digest_word.word.query(meta, Rotation::cur());
+            let complement = digest_word.word.query(meta, Rotation::next());
+            let carry = meta.query_advice(carry, Rotation::cur());
+            let [max_lo, max_hi] = max_word::<F>().to_lo_hi();
+            let range = Expression::Constant(pow_of_two::<F>(128));
+
+            cb.require_equal(
+                "digest == hi * 2^128 + lo",
+                meta.query_advice(digest, Rotation::cur()),
+                word.hi().clone() * range.clone() + word.lo().clone(),
+            );
+            cb.require_boolean("carry is boolean", carry.clone());
+            cb.require_equal(
+                "lo + complement.lo == (p - 1).lo + carry * 2^128",
+                word.lo().clone() + complement.lo().clone(),
+                Expression::Constant(max_lo) + carry.clone() * range,
+            );
+            cb.require_equal(
+                "hi + complement.hi + carry == (p - 1).hi",
+                word.hi().clone() + complement.hi().clone() + carry,
+                Expression::Constant(max_hi),
+            );
+            cb.gate(meta.query_selector(q_digest))
+        });
+
+        Self {
+            q_full,
+            q_partial,
+            q_init,
+            q_absorb,
+            q_table,
+            q_digest,
+            state,
+            chunk,
+            is_input,
+            rlc: rlc_columns,
+            count: count_column,
+            input_len,
+            round_constants,
+            digest,
+            carry,
+            digest_word,
+            byte_table,
+            table,
+            challenge,
+            params,
+        }
+    }
+
+    /// Configures the circuit with the default parameters for a width of `T`.
+    pub fn configure_default(meta: &mut ConstraintSystem<F>, table: PoseidonTable) -> Self {
+        let params = PoseidonParams::new(T, FULL_ROUNDS, partial_rounds(T));
+        Self::configure(meta, table, params)
+    }
+
+    pub fn params(&self) -> &PoseidonParams<F> {
+        &self.params
+    }
+
+    pub fn table(&self) -> &PoseidonTable {
+        &self.table
+    }
+
+    /// Challenge of the RLC of the inputs in the table, which circuits
+    /// looking up the table must use for their own RLCs.
+    pub fn challenge(&self) -> Challenge {
+        self.challenge
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct PoseidonWitness<F> {
+    pub inputs: Vec<Vec<F>>,
+    pub _marker: PhantomData<F>,
+}
+
+/// The cells of a call in the layout of its permutations, which the table
+/// copies.
+struct AssignedCall<F: FieldExt> {
+    len: AssignedValue<F>,
+    rlc: AssignedValue<F>,
+    digest: AssignedValue<F>,
+}
+
+#[derive(Clone, Debug)]
+pub struct PoseidonChip<F, const T: usize> {
+    config: PoseidonConfig<F, T>,
+    data: PoseidonWitness<F>,
+}
+
+impl<F: FieldExt, const T: usize> PoseidonChip<F, T> {
+    pub fn construct(config: PoseidonConfig<F, T>, data: PoseidonWitness<F>) -> Self {
+        Self { config, data }
+    }
+
+    /// Assigns the permutations of a call, returning the cells of the number
+    /// of inputs, of their RLC and of the digest.
+    fn assign_hash(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        inputs: &[F],
+        challenge: Value<F>,
+    ) -> Result<AssignedCall<F>, Error> {
+        let config = &self.config;
+        let params = &config.params;
+
+        layouter.assign_region(
+            || "poseidon hash",
+            |mut region| {
+                let mut state = vec![F::zero(); T];
+                state[0] = native::initial_capacity(inputs.len());
+                let mut offset = 0;
+                let mut len_cell = None;
+                let mut digest_cell = None;
+                let (mut count, mut rlc) = (0, Value::known(F::zero()));
+                let (mut count_cell, mut rlc_cell) = (None, None);
+
+                for (i, chunk) in native::chunks(params, inputs).iter().enumerate() {
+                    if i == 0 {
+                        config.q_init.enable(&mut region, offset)?;
+                        len_cell = Some(region.assign_advice(
+                            || "input_len",
+                            config.input_len,
+                            offset,
+                            || Value::known(F::from(inputs.len() as u64)),
+                        )?);
+                    } else {
+                        config.q_absorb.enable(&mut region, offset)?;
+                    }
+                    for (j, (column, input)) in config.chunk.iter().zip(chunk).enumerate() {
+                        region.assign_advice(
+                            || "chunk",
+                            *column,
+                            offset,
+                            || Value::known(*input),
+                        )?;
+                        let is_input = count < inputs.len();
+                        region.assign_advice(
+                            || "is_input",
+                            config.is_input[j],
+                            offset,
+                            || Value::known(F::from(is_input as u64)),
+                        )?;
+                        if is_input {
+                            count += 1;
+                            rlc = rlc * challenge + Value::known(*input);
+                        }
+                        rlc_cell =
+                            Some(region.assign_advice(|| "rlc", config.rlc[j], offset, || rlc)?);
+                    }
+                    count_cell = Some(region.assign_advice(
+                        || "count",
+                        config.count,
+                        offset,
+                        || Value::known(F::from(count as u64)),
+                    )?);
+                    for (x, input) in state[1..].iter_mut().zip(chunk) {
+                        *x += input;
+                    }
+
+                    let states = std::iter::once(state.clone())
+                        .chain(native::permute_rounds(params, &state))
+                        .collect::<Vec<_>>();
+                    for (round, row) in states.iter().enumerate() {
+                        if round < params.num_rounds() {
+                            let q_round = if params.is_full_round(round) {
+                                config.q_full
+                            } else {
+                                config.q_partial
+                            };
+                            q_round.enable(&mut region, offset)?;
+                            for (column, c) in config
+                                .round_constants
+                                .iter()
+                                .zip(&params.round_constants[round])
+                            {
+                                region.assign_fixed(
+                                    || "round constant",
+                                    *column,
+                                    offset,
+                                    || Value::known(*c),
+                                )?;
+                            }
+                        }
+                        for (j, (column, x)) in config.state.iter().zip(row).enumerate() {
+                            let cell = region.assign_advice(
+                                || "state",
+                                *column,
+                                offset,
+                                || Value::known(*x),
+                            )?;
+                            if j == 0 {
+                                digest_cell = Some(cell);
+                            }
+                        }
+                        offset += 1;
+                    }
+                    state = states.last().expect("there are rounds").clone();
+                }
+
+                let len_cell = len_cell.expect("there is at least one chunk");
+                region.constrain_equal(
+                    len_cell.cell(),
+                    count_cell.expect("there is at least one chunk").cell(),
+                )?;
+                Ok(AssignedCall {
+                    len: len_cell,
+                    rlc: rlc_cell.expect("the chunks are not empty"),
+                    digest: digest_cell.expect("there is at least one chunk"),
+                })
+            },
+        )
+    }
+
+    /// Assigns the permutations of every call and the table: a disabled
+    /// first row, followed by one row per call.
+    pub fn load(&self, layouter: &mut impl Layouter<F>) -> Result<(), Error> {
+        self.assign_calls(layouter).map(|_| ())
+    }
+
+    /// Assigns the byte table, the permutations of every call and the table,
+    /// returning the cells of the digests.
+    fn assign_calls(
+        &self,
+        layouter: &mut impl Layouter<F>,
+    ) -> Result<Vec<AssignedValue<F>>, Error> {
+        self.config.byte_table.load(layouter)?;
+        let challenge = layouter.get_challenge(self.config.challenge);
+        let cells = self
+            .data
+            .inputs
+            .iter()
+            .map(|inputs| self.assign_hash(layouter, inputs, challenge))
+            .collect::<Result<Vec<_>, Error>>()?;
+
+        let table = &self.config.table;
+        layouter.assign_region(
+            || "poseidon table",
+            |mut region| {
+                self.config.q_table.enable(&mut region, 0)?;
+                for column in [
+                    table.is_enabled,
+                    table.input_rlc,
+                    table.input_len,
+                    table.output,
+                ] {
+                    region.assign_advice_from_constant(|| "disabled row", column, 0, F::zero())?;
+                }
+
+                for (i, call) in cells.iter().enumerate() {
+                    let offset = i + 1;
+                    self.config.q_table.enable(&mut region, offset)?;
+                    region.assign_advice_from_constant(
+                        || "is_enabled",
+                        table.is_enabled,
+                        offset,
+                        F::one(),
+                    )?;
+                    call.rlc
+                        .copy_advice(|| "input_rlc", &mut region, table.input_rlc, offset)?;
+                    call.len
+                        .copy_advice(|| "input_len", &mut region, table.input_len, offset)?;
+                    call.digest
+                        .copy_advice(|| "output", &mut region, table.output, offset)?;
+                }
+                Ok(())
+            },
+        )?;
+        Ok(cells.into_iter().map(|call| call.digest).collect())
+    }
+
+    /// Assigns the canonical big-endian bytes of `digest` over two rows,
+    /// copying it from `cell`: the word of the bytes, then the word of
+    /// `p - 1 - digest`.
+    fn assign_digest_bytes(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        cell: &AssignedValue<F>,
+        digest: F,
+    ) -> Result<AssignedBytes<F>, Error> {
+        let config = &self.config;
+        let chip = WordChip::construct(config.digest_word);
+        let bytes = to_bytes_be(&digest);
+        let [lo, hi] = WordLoHi::<F>::from_bytes_be(&bytes)
+            .to_lo_hi()
+            .map(|half| half.get_lower_128());
+        let [max_lo, max_hi] = max_word::<F>().to_lo_hi().map(|half| half.get_lower_128());
+        let carry = lo > max_lo;
+        let complement_lo = max_lo.wrapping_sub(lo);
+        let complement_hi = max_hi - hi - carry as u128;
+        let mut complement = [0u8; 32];
+        complement[..16].copy_from_slice(&complement_hi.to_be_bytes());
+        complement[16..].copy_from_slice(&complement_lo.to_be_bytes());
+
+        layouter.assign_region(
+            || "poseidon digest bytes",
+            |mut region| {
+                config.q_digest.enable(&mut region, 0)?;
+                cell.copy_advice(|| "digest", &mut region, config.digest, 0)?;
+                region.assign_advice(
+                    || "carry",
+                    config.carry,
+                    0,
+                    || Value::known(F::from(carry as u64)),
+                )?;
+                let (cells, _) = chip.pack(&mut region, 0, &bytes)?;
+                chip.pack(&mut region, 1, &complement)?;
+                Ok(AssignedBytes::new(cells))
+            },
+        )
+    }
+}
+
+/// The word of `p - 1`, the largest field element.
+fn max_word<F: FieldExt>() -> WordLoHi<F> {
+    WordLoHi::from_bytes_be(&to_bytes_be(&-F::one()))
+}
+
+/// The canonical big-endian bytes of `value`.
+fn to_bytes_be<F: FieldExt>(value: &F) -> [u8; 32] {
+    Endianness::Big.to_bytes(value)
+}
+
+impl<F: FieldExt, const T: usize> HashCircuitExt<F> for PoseidonChip<F, T> {
+    type Config = PoseidonConfig<F, T>;
+    type Witness = PoseidonWitness<F>;
+
+    /// The digest is a field element, encoded over 32 bytes.
+    const DIGEST_BYTES: usize = 32;
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let table = PoseidonTable::construct(meta);
+        PoseidonConfig::configure_default(meta, table)
+    }
+
+    fn construct(config: Self::Config, witness: Self::Witness) -> Self {
+        PoseidonChip::construct(config, witness)
+    }
+
+    fn load(&self, layouter: &mut impl Layouter<F>) -> Result<(), Error> {
+        PoseidonChip::load(self, layouter)
+    }
+
+    fn table_columns(config: &Self::Config) -> Vec<Column<Any>> {
+        config.table.columns()
+    }
+
+    fn annotations(config: &Self::Config) -> Vec<String> {
+        config.table.annotations()
+    }
+
+    fn assigned_digest(
+        &self,
+        layouter: &mut impl Layouter<F>,
+    ) -> Result<Vec<AssignedBytes<F>>, Error> {
+        let params = &self.config.params;
+        self.assign_calls(layouter)?
+            .iter()
+            .zip(&self.data.inputs)
+            .map(|(cell, inputs)| {
+                self.assign_digest_bytes(layouter, cell, native::hash(params, inputs))
+            })
+            .collect()
+    }
+}
+
+#[cfg(any(feature = "test", test))]
+pub mod dev {
+    use super::*;
+
+    use halo2_proofs::{
+        circuit::SimpleFloorPlanner,
+        halo2curves::{bn256::Fr, group::ff::PrimeField},
+        plonk::Circuit,
+    };
+
+    fn from_hex(hex: &str) -> Fr {
+        let mut repr = [0u8; 32];
+        for (i, byte) in repr.iter_mut().rev().enumerate() {
+            *byte =
+                u8::from_str_radix(&hex[2 * i..2 * i + 2], 16).expect("digests are hex-encoded");
+        }
+        Fr::from_repr(repr).expect("digests are field elements")
+    }
+
+    lazy_static::lazy_static! {
+        // Digests of the sponge with a width of 3.
+        pub static ref INPUTS_OUTPUTS: (Vec<Vec<Fr>>, Vec<Fr>) = {
+        [
+            (vec![], "2098f5fb9e239eab3ceac3f27b81e481dc3124d55ffed523a839ee8446b64864"),
+            (vec![1], "27174c6a901b2d124d26a1bbb81eb4c54a57b72315bebd396a4e9096dbe000b0"),
+            (vec![1, 2], "2b18212fbe4e0732f20c516f8783037e743af4b6a4252fff72d82600f2a7f3a7"),
+            (vec![1, 2, 3], "29267c7859bd8ffc519fe83e5da9fd5c297d5d095dfd602bc98b6e1eb3150af2"),
+            (
+                (0..10).collect(),
+                "2333a356dd9a7a9e9e5309078334af71b7111cb62e702f8b678ccfa4215b81e6",
+            ),
+        ]
+            .into_iter()
+            .map(|(inputs, digest): (Vec<u64>, &str)| {
+                (inputs.into_iter().map(Fr::from).collect(), from_hex(digest))
+            })
+            .unzip()
+        };
+    }
+
+    #[derive(Default)]
+    pub struct PoseidonTestCircuit<F, const T: usize> {
+        pub inputs: Vec<Vec<F>>,
+        pub outputs: Vec<F>,
+        pub _marker: PhantomData<F>,
+    }
+
+    impl<F: FieldExt, const T: usize> Circuit<F> for PoseidonTestCircuit<F, T> {
+        type Config = PoseidonConfig<F, T>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self {
+                inputs: self.inputs.clone(),
+                outputs: vec![],
+                _marker: PhantomData,
+            }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            let table = PoseidonTable::construct(meta);
+            PoseidonConfig::configure_default(meta, table)
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            let chip = PoseidonChip::construct(
+                config,
+                PoseidonWitness {
+                    inputs: self.inputs.clone(),
+                    _marker: PhantomData,
+                },
+            );
+            chip.load(&mut layouter)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{
+        dev::MockProver,
+        halo2curves::{bn256::Fr, pasta::pallas},
+    };
+    use std::marker::PhantomData;
+
+    use super::dev::{PoseidonTestCircuit, INPUTS_OUTPUTS};
+    use super::*;
+    use gadgets::{rows, DigestCircuit};
+    use mutation_coverage::mutation_coverage;
+
+    #[test]
+    fn test_poseidon_native() {
+        let params = PoseidonParams::<Fr>::new(3, FULL_ROUNDS, partial_rounds(3));
+        let (inputs, outputs) = INPUTS_OUTPUTS.clone();
+        for (inputs, output) in inputs.iter().zip(outputs) {
+            assert_eq!(native::hash(&params, inputs), output);
+        }
+    }
+
+    #[test]
+    fn test_poseidon_circuit() {
+        let (inputs, outputs) = INPUTS_OUTPUTS.clone();
+        let circuit: PoseidonTestCircuit<Fr, 3> = PoseidonTestCircuit {
+            inputs,
+            outputs,
+            _marker: PhantomData,
+        };
+        let prover = MockProver::run(rows::min_k(&circuit), &circuit, vec![]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+    }
+
+    #[test]
+    fn test_poseidon_circuit_width() {
+        // A width of 5 absorbs 4 inputs per permutation.
+        let circuit: PoseidonTestCircuit<Fr, 5> = PoseidonTestCircuit {
+            inputs: vec![
+                (1..=4).map(Fr::from).collect(),
+                (1..=9).map(Fr::from).collect(),
+            ],
+            outputs: vec![],
+            _marker: PhantomData,
+        };
+        let prover = MockProver::run(rows::min_k(&circuit), &circuit, vec![]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+    }
+
+    #[test]
+    fn test_poseidon_circuit_pallas() {
+        // The fixtures are digests over BN254, so the digests over Pallas are
+        // those of the native sponge.
+        let params = PoseidonParams::<pallas::Base>::new(3, FULL_ROUNDS, partial_rounds(3));
+        let inputs: Vec<Vec<pallas::Base>> = [0, 1, 2, 10]
+            .into_iter()
+            .map(|len| (0..len).map(pallas::Base::from).collect())
+            .collect();
+        let outputs = inputs
+            .iter()
+            .map(|inputs| native::hash(&params, inputs))
+            .collect();
+        let circuit: PoseidonTestCircuit<pallas::Base, 3> = PoseidonTestCircuit {
+            inputs,
+            outputs,
+            _marker: PhantomData,
+        };
+        let prover = MockProver::run(rows::min_k(&circuit), &circuit, vec![]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+    }
+
+    #[test]
+    fn test_poseidon_assigned_digest() {
+        let (inputs, outputs) = INPUTS_OUTPUTS.clone();
+        let witness = PoseidonWitness {
+            inputs,
+            _marker: PhantomData,
+        };
+        let verify = |digests: Vec<Vec<u8>>| {
+            let k = PoseidonChip::<Fr, 3>::min_k(&witness);
+            let circuit = DigestCircuit::<Fr, PoseidonChip<Fr, 3>> {
+                witness: witness.clone(),
+                digests,
+                _marker: PhantomData,
+            };
+            MockProver::run(k, &circuit, vec![]).unwrap().verify()
+        };
+        let mut digests: Vec<_> = outputs
+            .iter()
+            .map(|output| to_bytes_be(output).to_vec())
+            .collect();
+        assert_eq!(verify(digests.clone()), Ok(()));
+
+        // a byte of a digest is not the one of the call.
+        digests[2][31] ^= 1;
+        assert!(verify(digests).is_err());
+    }
+
+    #[test]
+    fn test_poseidon_circuit_coverage() {
+        // Every cell is constrained, among which the flags of the inputs and
+        // the accumulators of their number and RLC, and the RLC in the table.
+        let circuit: PoseidonTestCircuit<Fr, 3> = PoseidonTestCircuit {
+            inputs: vec![vec![], (1..=3).map(Fr::from).collect()],
+            outputs: vec![],
+            _marker: PhantomData,
+        };
+        let report = mutation_coverage(rows::min_k(&circuit), &circuit, vec![]).unwrap();
+        assert_eq!(report.unconstrained().count(), 0, "{}", report.report());
+    }
+
+    #[test]
+    fn test_poseidon_hash_circuit_ext() {
+        let mut meta = ConstraintSystem::<Fr>::default();
+        let config = <PoseidonChip<Fr, 3> as HashCircuitExt<Fr>>::configure(&mut meta);
+        assert_eq!(
+            PoseidonChip::<Fr, 3>::table_columns(&config).len(),
+            PoseidonChip::<Fr, 3>::annotations(&config).len()
+        );
+        assert_eq!(config.params().t, 3);
+    }
+}
@@ -0,0 +1,315 @@
+//! Native Poseidon permutation and sponge, used to compute the witness of the
+//! circuit and as the reference it is tested against.
+//!
+//! The round constants and the MDS matrix are generated for the field by the
+//! Grain LFSR of the [`reference implementation`]. Over BN254, the
+//! permutation is the one of circomlib for the same width and number of
+//! rounds, e.g. of the `Poseidon(2)` template for a width of 3, but the
+//! sponge of [`hash`] is not: its initial capacity is [`initial_capacity`]
+//! rather than zero.
+//!
+//! [`reference implementation`]: https://extgit.iaik.tugraz.at/krypto/hadeshash
+
+use std::collections::VecDeque;
+
+use halo2_proofs::arithmetic::FieldExt;
+
+/// Number of full rounds, half of which are before the partial rounds.
+pub const FULL_ROUNDS: usize = 8;
+
+/// Number of partial rounds for the widths `2..=17`, as in circomlib.
+pub const PARTIAL_ROUNDS: [usize; 16] = [
+    56, 57, 56, 60, 60, 63, 64, 63, 60, 66, 60, 65, 70, 60, 64, 68,
+];
+
+/// Number of partial rounds for a width of `t`.
+pub fn partial_rounds(t: usize) -> usize {
+    assert!((2..=17).contains(&t), "the width is in 2..=17");
+    PARTIAL_ROUNDS[t - 2]
+}
+
+/// The Grain LFSR generating the parameters of the permutation.
+struct Grain {
+    bits: VecDeque<bool>,
+}
+
+impl Grain {
+    fn new(field_bits: usize, t: usize, r_f: usize, r_p: usize) -> Self {
+        // The field is prime (0b01) and the S-box is x^alpha (0b0000).
+        let mut bits = VecDeque::with_capacity(80);
+        let mut push = |value: usize, num_bits: usize| {
+            for i in (0..num_bits).rev() {
+                bits.push_back((value >> i) & 1 == 1);
+            }
+        };
+        push(1, 2);
+        push(0, 4);
+        push(field_bits, 12);
+        push(t, 12);
+        push(r_f, 10);
+        push(r_p, 10);
+        push((1 << 30) - 1, 30);
+
+        let mut grain = Self { bits };
+        for _ in 0..160 {
+            grain.clock();
+        }
+        grain
+    }
+
+    fn clock(&mut self) -> bool {
+        let bit = [62, 51, 38, 23, 13, 0]
+            .iter()
+            .fold(false, |acc, i| acc ^ self.bits[*i]);
+        self.bits.pop_front();
+        self.bits.push_back(bit);
+        bit
+    }
+
+    /// Next output bit: of each pair of bits, the second one is output if
+    /// the first one is set, and discarded otherwise.
+    fn next_bit(&mut self) -> bool {
+        loop {
+            let (first, second) = (self.clock(), self.clock());
+            if first {
+                return second;
+            }
+        }
+    }
+
+    /// Next `F::NUM_BITS` bits, as the little-endian bytes of a big-endian
+    /// integer.
+    fn next_bytes<F: FieldExt>(&mut self) -> [u8; 64] {
+        let num_bits = F::NUM_BITS as usize;
+        let mut bytes = [0u8; 64];
+        for i in (0..num_bits).rev() {
+            if self.next_bit() {
+                bytes[i / 8] |= 1 << (i % 8);
+            }
+        }
+        bytes
+    }
+
+    /// Next field element, sampled by rejection.
+    fn next_field_element<F: FieldExt>(&mut self) -> F {
+        loop {
+            let bytes = self.next_bytes::<F>();
+            let mut repr = F::Repr::default();
+            let len = repr.as_ref().len();
+            repr.as_mut().copy_from_slice(&bytes[..len]);
+            if let Some(element) = Option::from(F::from_repr(repr)) {
+                return element;
+            }
+        }
+    }
+
+    /// Next field element, reduced modulo the order of the field.
+    fn next_field_element_without_rejection<F: FieldExt>(&mut self) -> F {
+        F::from_bytes_wide(&self.next_bytes::<F>())
+    }
+}
+
+/// Parameters of the permutation over a state of `t` field elements.
+#[derive(Clone, Debug)]
+pub struct PoseidonParams<F> {
+    pub t: usize,
+    pub r_f: usize,
+    pub r_p: usize,
+    /// `t` constants per round.
+    pub round_constants: Vec<Vec<F>>,
+    pub mds: Vec<Vec<F>>,
+}
+
+impl<F: FieldExt> PoseidonParams<F> {
+    /// Generates the parameters for a width of `t`, with `r_f` full rounds
+    /// and `r_p` partial rounds.
+    pub fn new(t: usize, r_f: usize, r_p: usize) -> Self {
+        assert!(t >= 2, "the state holds at least one input");
+        assert!(r_f.is_multiple_of(2), "the full rounds are split in halves");
+
+        let mut grain = Grain::new(F::NUM_BITS as usize, t, r_f, r_p);
+        let round_constants = (0..r_f + r_p)
+            .map(|_| (0..t).map(|_| grain.next_field_element()).collect())
+            .collect();
+
+        // The MDS matrix is the Cauchy matrix `1 / (x_i + y_j)` over distinct
+        // sampled elements.
+        let mds = loop {
+            let elements: Vec<F> = (0..2 * t)
+                .map(|_| grain.next_field_element_without_rejection())
+                .collect();
+            let (xs, ys) = elements.split_at(t);
+            let distinct = elements
+                .iter()
+                .enumerate()
+                .all(|(i, a)| elements[..i].iter().all(|b| a != b));
+            let inverses: Option<Vec<Vec<F>>> = xs
+                .iter()
+                .map(|x| ys.iter().map(|y| Option::from((*x + y).invert())).collect())
+                .collect();
+            match inverses {
+                Some(mds) if distinct => break mds,
+                _ => continue,
+            }
+        };
+
+        Self {
+            t,
+            r_f,
+            r_p,
+            round_constants,
+            mds,
+        }
+    }
+
+    /// Width of the state absorbing the inputs, the first element of the
+    /// state being its capacity.
+    pub fn rate(&self) -> usize {
+        self.t - 1
+    }
+
+    pub fn num_rounds(&self) -> usize {
+        self.r_f + self.r_p
+    }
+
+    pub fn is_full_round(&self, round: usize) -> bool {
+        round < self.r_f / 2 || round >= self.r_f / 2 + self.r_p
+    }
+}
+
+fn pow5<F: FieldExt>(x: F) -> F {
+    x.square().square() * x
+}
+
+/// Applies the round `round` of the permutation to `state`: the addition of
+/// the round constants, the S-box `x^5` over the whole state for a full round
+/// or over its first element for a partial round, and the MDS matrix.
+pub fn round<F: FieldExt>(params: &PoseidonParams<F>, round: usize, state: &[F]) -> Vec<F> {
+    let full = params.is_full_round(round);
+    let sbox: Vec<F> = state
+        .iter()
+        .zip(&params.round_constants[round])
+        .enumerate()
+        .map(
+            |(i, (x, c))| {
+                if full || i == 0 {
+                    pow5(*x + c)
+                } else {
+                    *x + c
+                }
+            },
+        )
+        .collect();
+    params
+        .mds
+        .iter()
+        .map(|row| {
+            row.iter()
+                .zip(&sbox)
+                .fold(F::zero(), |acc, (m, x)| acc + *m * x)
+        })
+        .collect()
+}
+
+/// The states of the permutation of `state`, after each of its rounds.
+pub fn permute_rounds<F: FieldExt>(params: &PoseidonParams<F>, state: &[F]) -> Vec<Vec<F>> {
+    assert_eq!(state.len(), params.t, "the state has t elements");
+    let mut states = Vec::with_capacity(params.num_rounds());
+    let mut state = state.to_vec();
+    for r in 0..params.num_rounds() {
+        state = round(params, r, &state);
+        states.push(state.clone());
+    }
+    states
+}
+
+pub fn permute<F: FieldExt>(params: &PoseidonParams<F>, state: &[F]) -> Vec<F> {
+    permute_rounds(params, state)
+        .pop()
+        .unwrap_or_else(|| state.to_vec())
+}
+
+/// Capacity of the initial state of the sponge over `len` inputs, which
+/// separates the inputs of different lengths.
+pub fn initial_capacity<F: FieldExt>(len: usize) -> F {
+    F::from_u128((len as u128) << 64)
+}
+
+/// The inputs absorbed by each permutation of the sponge: `rate` inputs per
+/// permutation, the last ones padded with zeros, and at least one
+/// permutation.
+pub fn chunks<F: FieldExt>(params: &PoseidonParams<F>, inputs: &[F]) -> Vec<Vec<F>> {
+    let rate = params.rate();
+    let num_chunks = inputs.len().div_ceil(rate).max(1);
+    (0..num_chunks)
+        .map(|i| {
+            (0..rate)
+                .map(|j| inputs.get(i * rate + j).copied().unwrap_or(F::zero()))
+                .collect()
+        })
+        .collect()
+}
+
+/// Hashes `inputs` with the sponge: the capacity is initialised with the
+/// number of inputs, the chunks of inputs are added to the rest of the state
+/// before each permutation, and the digest is the capacity of the final
+/// state.
+pub fn hash<F: FieldExt>(params: &PoseidonParams<F>, inputs: &[F]) -> F {
+    let mut state = vec![F::zero(); params.t];
+    state[0] = initial_capacity(inputs.len());
+    for chunk in chunks(params, inputs) {
+        for (x, input) in state[1..].iter_mut().zip(chunk) {
+            *x += input;
+        }
+        state = permute(params, &state);
+    }
+    state[0]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use halo2_proofs::halo2curves::{bn256::Fr, group::ff::PrimeField};
+
+    pub(crate) fn from_hex(hex: &str) -> Fr {
+        let mut repr = [0u8; 32];
+        for (i, byte) in repr.iter_mut().rev().enumerate() {
+            *byte = u8::from_str_radix(&hex[2 * i..2 * i + 2], 16).unwrap();
+        }
+        Fr::from_repr(repr).unwrap()
+    }
+
+    #[test]
+    fn test_params() {
+        let params = PoseidonParams::<Fr>::new(3, FULL_ROUNDS, partial_rounds(3));
+        assert_eq!(
+            params.round_constants[0][0],
+            from_hex("0ee9a592ba9a9518d05986d656f40c2114c4993c11bb29938d21d47304cd8e6e")
+        );
+        assert_eq!(
+            params.mds[0][0],
+            from_hex("109b7f411ba0e4c9b2b70caf5c36a7b194be7c11ad24378bfedb68592ba8118b")
+        );
+    }
+
+    #[test]
+    fn test_permutation() {
+        // The vectors of circomlib, whose digest is the first element of the
+        // permutation of `[0, inputs...]`.
+        for (inputs, digest) in [
+            (
+                vec![1, 2],
+                "115cc0f5e7d690413df64c6b9662e9cf2a3617f2743245519e19607a4417189a",
+            ),
+            (
+                vec![1, 2, 3, 4],
+                "299c867db6c1fdd79dcefa40e4510b9837e60ebb1ce0663dbaa525df65250465",
+            ),
+        ] {
+            let t = inputs.len() + 1;
+            let params = PoseidonParams::<Fr>::new(t, FULL_ROUNDS, partial_rounds(t));
+            let state: Vec<Fr> = std::iter::once(0).chain(inputs).map(Fr::from).collect();
+            assert_eq!(permute(&params, &state)[0], from_hex(digest));
+        }
+    }
+}
@@ -0,0 +1,187 @@
+//! The witness of the super-circuit built from the precompile calls of a
+//! trace, the way the `CircuitInputBuilder` of scroll's bus-mapping builds the
+//! witnesses of the zkEVM sub-circuits: the calls are pushed in the order of
+//! the trace, each is given the next call id, and the inputs of every
+//! precompile are laid out in the order of their call ids. The caller looks
+//! up every call from the table of its precompile.
+
+use std::marker::PhantomData;
+
+use blake2f_circuit::Blake2fWitness;
+use halo2_proofs::arithmetic::FieldExt;
+use thiserror::Error;
+
+use crate::{Precompile, PrecompileCall, SuperCircuit};
+
+/// Why a call or the witness is rejected by the builder.
+#[derive(Clone, Debug, PartialEq, Eq, Error)]
+pub enum BuilderError {
+    /// The address is not that of a precompile of the super-circuit.
+    #[error("the address {0:#x} is not that of a precompile of the super-circuit")]
+    UnsupportedPrecompile(u64),
+    /// The input of the BLAKE2F call is malformed, see
+    /// `Blake2fWitness::from_input`.
+    #[error("the input of the BLAKE2F call {call_id} is malformed")]
+    InvalidBlake2fInput { call_id: u64 },
+    /// The witness needs `2^k` rows, more than the `2^max_k` of the circuit.
+    #[error("the witness needs 2^{k} rows, more than the 2^{max_k} of the circuit")]
+    CapacityExceeded { k: u32, max_k: u32 },
+}
+
+/// A precompile call of the trace, with the call id it was given.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PrecompileInvocation {
+    pub call_id: u64,
+    pub precompile: Precompile,
+    pub input: Vec<u8>,
+}
+
+#[derive(Clone, Debug)]
+pub struct CircuitInputBuilder {
+    max_k: u32,
+    invocations: Vec<PrecompileInvocation>,
+}
+
+impl CircuitInputBuilder {
+    /// A builder of a super-circuit of at most `2^max_k` rows.
+    pub fn new(max_k: u32) -> Self {
+        Self {
+            max_k,
+            invocations: vec![],
+        }
+    }
+
+    /// Pushes a call to the precompile at `address`, and returns its call
+    /// id. The call ids start at 1, as 0 is that of the disabled rows of the
+    /// tables.
+    pub fn call(&mut self, address: u64, input: &[u8]) -> Result<u64, BuilderError> {
+        let precompile = Precompile::from_address(address)
+            .ok_or(BuilderError::UnsupportedPrecompile(address))?;
+        let call_id = self.invocations.len() as u64 + 1;
+        if precompile == Precompile::Blake2f && Blake2fWitness::from_input(input).is_err() {
+            return Err(BuilderError::InvalidBlake2fInput { call_id });
+        }
+        self.invocations.push(PrecompileInvocation {
+            call_id,
+            precompile,
+            input: input.to_vec(),
+        });
+        Ok(call_id)
+    }
+
+    /// The calls pushed so far, in the order of their call ids.
+    pub fn invocations(&self) -> &[PrecompileInvocation] {
+        &self.invocations
+    }
+
+    /// The witness of the super-circuit, if it fits in `2^max_k` rows.
+    pub fn build<F: FieldExt>(&self) -> Result<SuperCircuit<F>, BuilderError> {
+        let inputs = |precompile| {
+            self.invocations
+                .iter()
+                .filter(move |invocation| invocation.precompile == precompile)
+                .map(|invocation| invocation.input.clone())
+        };
+        let circuit = SuperCircuit {
+            sha2_inputs: inputs(Precompile::Sha2).collect(),
+            blake2f_inputs: inputs(Precompile::Blake2f)
+                .map(|input| Blake2fWitness::from_input(&input).expect("checked by call"))
+                .collect(),
+            ripemd160_inputs: inputs(Precompile::Ripemd160).collect(),
+            calls: self
+                .invocations
+                .iter()
+                .enumerate()
+                .map(|(i, invocation)| {
+                    // The calls of a sub-circuit are numbered from 1 in the
+                    // order of their inputs.
+                    let id = 1 + self.invocations[..i]
+                        .iter()
+                        .filter(|other| other.precompile == invocation.precompile)
+                        .count();
+                    PrecompileCall::new(invocation.precompile, id, &invocation.input)
+                })
+                .collect(),
+            _marker: PhantomData,
+        };
+
+        let k = circuit.min_k();
+        if k > self.max_k {
+            return Err(BuilderError::CapacityExceeded {
+                k,
+                max_k: self.max_k,
+            });
+        }
+        Ok(circuit)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{dev::MockProver, halo2curves::bn256::Fr};
+
+    use super::*;
+
+    #[test]
+    fn test_circuit_input_builder() {
+        let blake2f = &blake2f_circuit::dev::INPUTS_OUTPUTS.0[0];
+        let mut builder = CircuitInputBuilder::new(11);
+        assert_eq!(builder.call(0x02, b"abc"), Ok(1));
+        assert_eq!(builder.call(0x09, &blake2f.to_input()), Ok(2));
+        assert_eq!(builder.call(0x03, b""), Ok(3));
+        assert_eq!(builder.call(0x02, b"def"), Ok(4));
+        assert_eq!(
+            builder.call(0x01, b""),
+            Err(BuilderError::UnsupportedPrecompile(0x01))
+        );
+        assert_eq!(
+            builder.call(0x09, &[0; 212]),
+            Err(BuilderError::InvalidBlake2fInput { call_id: 5 })
+        );
+        assert_eq!(builder.invocations().len(), 4);
+        for invocation in builder.invocations() {
+            let address = invocation.precompile.address();
+            assert_eq!(
+                Precompile::from_address(address),
+                Some(invocation.precompile)
+            );
+        }
+
+        let circuit = builder.build::<Fr>().unwrap();
+        assert_eq!(circuit.sha2_inputs, vec![b"abc".to_vec(), b"def".to_vec()]);
+        assert_eq!(circuit.ripemd160_inputs, vec![Vec::<u8>::new()]);
+        assert_eq!(circuit.blake2f_inputs.len(), 1);
+        assert_eq!(circuit.blake2f_inputs[0].h, blake2f.h);
+        assert_eq!(circuit.blake2f_inputs[0].f, blake2f.f);
+        let precompiles: Vec<_> = circuit.calls.iter().map(|call| call.precompile).collect();
+        assert_eq!(
+            precompiles,
+            vec![
+                Precompile::Sha2,
+                Precompile::Blake2f,
+                Precompile::Ripemd160,
+                Precompile::Sha2
+            ]
+        );
+        // the second SHA2-256 call is the second one of its table.
+        assert_eq!(
+            circuit.calls[3],
+            PrecompileCall::new(Precompile::Sha2, 2, b"def")
+        );
+        let prover = MockProver::run(circuit.min_k(), &circuit, circuit.public_inputs()).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+
+        let mut builder = CircuitInputBuilder::new(10);
+        for _ in 0..2 {
+            builder.call(0x02, b"abc").unwrap();
+        }
+        assert_eq!(
+            builder.build::<Fr>().unwrap_err(),
+            BuilderError::CapacityExceeded { k: 11, max_k: 10 }
+        );
+        assert_eq!(
+            BuilderError::UnsupportedPrecompile(0x01).to_string(),
+            "the address 0x1 is not that of a precompile of the super-circuit"
+        );
+    }
+}
@@ -0,0 +1,454 @@
+//! The precompile super-circuit places the SHA2-256, BLAKE2F and RIPEMD-160
+//! circuits within a single circuit, so that all of them are verified by a
+//! single proof, the way the [`zkEVM`] super-circuit places its
+//! sub-circuits.
+//!
+//! As described in the documentation of the sha2-256-circuit crate, a circuit
+//! that encounters a call to a precompiled contract does not verify it
+//! itself, but looks up its input-output relationship from the table of the
+//! circuit of the precompile. The super-circuit demonstrates this pattern
+//! with a caller, standing in for the EVM circuit: every call of the caller
+//! is a row of the table of one of the precompiles, which is looked up from
+//! that table. A call to a row that is not in the table fails the
+//! verification of the whole proof.
+//!
+//! The sub-circuits are configured and loaded through [`HashCircuitExt`],
+//! so adding a precompile to the super-circuit amounts to adding its config
+//! to `SuperCircuitConfig` and its witness to `SuperCircuit`. Their tables are
+//! exposed jointly by `SuperCircuitConfig::table_columns`, and their calls
+//! in the two instance columns of every precompile, in their order, see
+//! [`SuperCircuit::public_inputs`].
+//!
+//! The sub-circuits are configured with `configure_with_tables` over a
+//! single `FixedTables` registry, which the super-circuit loads. None of the
+//! SHA2-256, BLAKE2F and RIPEMD-160 circuits has a fixed table, so the
+//! registry stays empty until a precompile with one, e.g. the byte table of
+//! the Keccak-256 circuit, is added.
+//!
+//! A prover builds the witness with [`CircuitInputBuilder`] from the
+//! precompile calls of a trace, given by their address and input bytes. It
+//! gives the calls their call ids, lays out the inputs of every sub-circuit
+//! in their order, and rejects a witness that does not fit the circuit.
+//!
+//! [`zkEVM`]: https://github.com/scroll-tech/zkevm-circuits/blob/scroll-stable/zkevm-circuits/src/super_circuit.rs
+
+use std::marker::PhantomData;
+
+use blake2f_circuit::{Blake2fChip, Blake2fConfig, Blake2fWitness};
+use gadgets::{public_inputs::digest_chunks, rows, tables::FixedTables, util::rlc, HashCircuitExt};
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{Layouter, SimpleFloorPlanner, Value},
+    plonk::{
+        Advice, Any, Challenge, Circuit, Column, ConstraintSystem, Error, SecondPhase, Selector,
+    },
+    poly::Rotation,
+};
+use ripemd160_circuit::{Ripemd160Chip, Ripemd160Config, Ripemd160Witness};
+use sha2_256_circuit::{Sha2Chip, Sha2Config, Sha2Witness};
+
+mod builder;
+
+pub use builder::{BuilderError, CircuitInputBuilder, PrecompileInvocation};
+
+/// The precompiles placed within the super-circuit.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Precompile {
+    Sha2,
+    Blake2f,
+    Ripemd160,
+}
+
+impl Precompile {
+    /// The precompile of the super-circuit at `address`, if any.
+    pub fn from_address(address: u64) -> Option<Self> {
+        match address {
+            0x02 => Some(Self::Sha2),
+            0x03 => Some(Self::Ripemd160),
+            0x09 => Some(Self::Blake2f),
+            _ => None,
+        }
+    }
+
+    /// Address of the precompile, the inverse of [`Self::from_address`].
+    pub fn address(&self) -> u64 {
+        match self {
+            Self::Sha2 => 0x02,
+            Self::Ripemd160 => 0x03,
+            Self::Blake2f => 0x09,
+        }
+    }
+
+    /// The output of a call with `input`, the compressed state for
+    /// BLAKE2F, whose EIP-152 input is `input`.
+    pub fn output(&self, input: &[u8]) -> Vec<u8> {
+        match self {
+            Self::Sha2 => sha2_256_circuit::native::sha256(input).to_vec(),
+            Self::Ripemd160 => ripemd160_circuit::native::ripemd160(input).to_vec(),
+            Self::Blake2f => {
+                let witness = Blake2fWitness::from_input(input).expect("a BLAKE2F input");
+                blake2f_circuit::native::blake2f(&witness).to_vec()
+            }
+        }
+    }
+}
+
+/// A call of the caller: the `id`-th call of the table of `precompile`, of
+/// `input` and `output`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PrecompileCall {
+    pub precompile: Precompile,
+    pub id: u64,
+    pub input: Vec<u8>,
+    pub output: Vec<u8>,
+}
+
+impl PrecompileCall {
+    /// The `id`-th call to `precompile`, with `input` and its output.
+    pub fn new(precompile: Precompile, id: usize, input: &[u8]) -> Self {
+        Self {
+            precompile,
+            id: id as u64,
+            input: input.to_vec(),
+            output: precompile.output(input),
+        }
+    }
+
+    /// The row of the call in the [`HashTable`](gadgets::HashTable) of its
+    /// precompile, in the order of its columns, the RLCs over `challenge`.
+    fn row<F: FieldExt>(&self, challenge: Value<F>) -> Vec<Value<F>> {
+        [
+            Value::known(F::one()),
+            Value::known(F::from(self.id)),
+            rlc(&self.input, challenge),
+            Value::known(F::from(self.input.len() as u64)),
+            rlc(&self.output, challenge),
+        ]
+        .into_iter()
+        .chain(digest_chunks(&self.output).into_iter().map(Value::known))
+        .collect()
+    }
+}
+
+/// The caller, whose rows are looked up from the table of a precompile.
+#[derive(Clone, Debug)]
+pub struct CallerConfig {
+    q_sha2: Selector,
+    q_blake2f: Selector,
+    q_ripemd160: Selector,
+    /// The challenges of the RLCs of the tables, in the order of the
+    /// selectors.
+    challenges: [Challenge; 3],
+    values: Vec<Column<Advice>>,
+}
+
+impl CallerConfig {
+    fn configure<F: FieldExt>(
+        meta: &mut ConstraintSystem<F>,
+        tables: [(&'static str, Vec<Column<Any>>, Challenge); 3],
+    ) -> Self {
+        let width = tables
+            .iter()
+            .map(|(_, columns, _)| columns.len())
+            .max()
+            .unwrap_or(0);
+        // The RLCs of a row are only known in the second phase.
+        let values = (0..width)
+            .map(|_| meta.advice_column_in(SecondPhase))
+            .collect::<Vec<_>>();
+        let selectors = [(); 3].map(|_| meta.complex_selector());
+        let challenges = tables.each_ref().map(|(_, _, challenge)| *challenge);
+
+        for (q_call, (name, columns, _)) in selectors.iter().zip(tables) {
+            let values = values.clone();
+            meta.lookup_any(name, |meta| {
+                let q_call = meta.query_selector(*q_call);
+                values
+                    .iter()
+                    .zip(columns)
+                    .map(|(value, column)| {
+                        (
+                            q_call.clone() * meta.query_advice(*value, Rotation::cur()),
+                            meta.query_any(column, Rotation::cur()),
+                        )
+                    })
+                    .collect()
+            });
+        }
+
+        let [q_sha2, q_blake2f, q_ripemd160] = selectors;
+        Self {
+            q_sha2,
+            q_blake2f,
+            q_ripemd160,
+            challenges,
+            values,
+        }
+    }
+
+    fn selector(&self, precompile: Precompile) -> Selector {
+        match precompile {
+            Precompile::Sha2 => self.q_sha2,
+            Precompile::Blake2f => self.q_blake2f,
+            Precompile::Ripemd160 => self.q_ripemd160,
+        }
+    }
+
+    fn load<F: FieldExt>(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        calls: &[PrecompileCall],
+    ) -> Result<(), Error> {
+        let [sha2, blake2f, ripemd160] = self
+            .challenges
+            .map(|challenge| layouter.get_challenge(challenge));
+        let challenge = |precompile| match precompile {
+            Precompile::Sha2 => sha2,
+            Precompile::Blake2f => blake2f,
+            Precompile::Ripemd160 => ripemd160,
+        };
+        layouter.assign_region(
+            || "precompile calls",
+            |mut region| {
+                for (offset, call) in calls.iter().enumerate() {
+                    let row = call.row(challenge(call.precompile));
+                    assert!(
+                        row.len() <= self.values.len(),
+                        "the call is wider than the tables"
+                    );
+                    self.selector(call.precompile).enable(&mut region, offset)?;
+                    for (i, column) in self.values.iter().enumerate() {
+                        let value = row.get(i).copied().unwrap_or(Value::known(F::zero()));
+                        region.assign_advice(|| "call value", *column, offset, || value)?;
+                    }
+                }
+                Ok(())
+            },
+        )
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct SuperCircuitConfig<F> {
+    tables: FixedTables,
+    sha2: Sha2Config<F>,
+    blake2f: Blake2fConfig<F>,
+    ripemd160: Ripemd160Config<F>,
+    caller: CallerConfig,
+}
+
+impl<F: FieldExt> SuperCircuitConfig<F> {
+    pub fn configure(meta: &mut ConstraintSystem<F>) -> Self {
+        let mut tables = FixedTables::default();
+        let mut sha2 = Sha2Chip::configure_with_tables(meta, &mut tables);
+        let mut blake2f = Blake2fChip::configure_with_tables(meta, &mut tables);
+        let mut ripemd160 = Ripemd160Chip::configure_with_tables(meta, &mut tables);
+        sha2.expose(meta);
+        blake2f.expose(meta);
+        ripemd160.expose(meta);
+        let caller = CallerConfig::configure(
+            meta,
+            [
+                (
+                    "sha2 call in sha2 table",
+                    Sha2Chip::table_columns(&sha2),
+                    sha2.challenge(),
+                ),
+                (
+                    "blake2f call in blake2f table",
+                    Blake2fChip::table_columns(&blake2f),
+                    blake2f.challenge(),
+                ),
+                (
+                    "ripemd160 call in ripemd160 table",
+                    Ripemd160Chip::table_columns(&ripemd160),
+                    ripemd160.challenge(),
+                ),
+            ],
+        );
+
+        Self {
+            tables,
+            sha2,
+            blake2f,
+            ripemd160,
+            caller,
+        }
+    }
+
+    /// Columns of the tables of all the precompiles.
+    pub fn table_columns(&self) -> Vec<Column<Any>> {
+        [
+            Sha2Chip::table_columns(&self.sha2),
+            Blake2fChip::table_columns(&self.blake2f),
+            Ripemd160Chip::table_columns(&self.ripemd160),
+        ]
+        .concat()
+    }
+
+    /// Annotations of [`Self::table_columns`], prefixed with the name of the
+    /// precompile.
+    pub fn annotations(&self) -> Vec<String> {
+        let prefixed = |prefix: &str, annotations: Vec<String>| {
+            annotations
+                .into_iter()
+                .map(|annotation| format!("{prefix}.{annotation}"))
+                .collect::<Vec<_>>()
+        };
+        [
+            prefixed("sha2", Sha2Chip::annotations(&self.sha2)),
+            prefixed("blake2f", Blake2fChip::annotations(&self.blake2f)),
+            prefixed("ripemd160", Ripemd160Chip::annotations(&self.ripemd160)),
+        ]
+        .concat()
+    }
+}
+
+/// Witness of the super-circuit: the hash calls of every precompile, and the
+/// calls of the caller.
+#[derive(Clone, Debug, Default)]
+pub struct SuperCircuit<F> {
+    pub sha2_inputs: Vec<Vec<u8>>,
+    pub blake2f_inputs: Vec<Blake2fWitness>,
+    pub ripemd160_inputs: Vec<Vec<u8>>,
+    pub calls: Vec<PrecompileCall>,
+    pub _marker: PhantomData<F>,
+}
+
+impl<F: FieldExt> SuperCircuit<F> {
+    fn sha2_witness(&self) -> Sha2Witness<F> {
+        Sha2Witness {
+            inputs: self.sha2_inputs.clone(),
+            _marker: PhantomData,
+        }
+    }
+
+    fn ripemd160_witness(&self) -> Ripemd160Witness<F> {
+        Ripemd160Witness {
+            inputs: self.ripemd160_inputs.clone(),
+            _marker: PhantomData,
+        }
+    }
+
+    /// Smallest `k` that fits the layout of all the sub-circuits and the
+    /// calls, see [`rows::min_k`].
+    pub fn min_k(&self) -> u32 {
+        rows::min_k(self)
+    }
+
+    /// The instances of the circuit: the public inputs of the calls of the
+    /// SHA2-256, BLAKE2F and RIPEMD-160 circuits, two columns each, see
+    /// [`HashCircuitExt::public_inputs`].
+    pub fn public_inputs(&self) -> Vec<Vec<F>> {
+        [
+            Sha2Chip::<F>::public_inputs(&self.sha2_witness()),
+            Blake2fChip::<F>::public_inputs(&self.blake2f_inputs),
+            Ripemd160Chip::<F>::public_inputs(&self.ripemd160_witness()),
+        ]
+        .concat()
+    }
+}
+
+impl<F: FieldExt> Circuit<F> for SuperCircuit<F> {
+    type Config = SuperCircuitConfig<F>;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        SuperCircuitConfig::configure(meta)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        config.tables.load(&mut layouter)?;
+        Sha2Chip::construct(config.sha2, self.sha2_witness()).load(&mut layouter)?;
+        Blake2fChip::construct(config.blake2f, self.blake2f_inputs.clone()).load(&mut layouter)?;
+        Ripemd160Chip::construct(config.ripemd160, self.ripemd160_witness()).load(&mut layouter)?;
+        config.caller.load(&mut layouter, &self.calls)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{
+        dev::MockProver,
+        halo2curves::{bn256::Fr, pasta::pallas},
+        plonk::ConstraintSystem,
+    };
+    use std::marker::PhantomData;
+
+    use super::*;
+
+    fn circuit<F: FieldExt>(calls: Vec<PrecompileCall>) -> SuperCircuit<F> {
+        SuperCircuit {
+            sha2_inputs: sha2_256_circuit::dev::INPUTS_OUTPUTS.0.clone(),
+            blake2f_inputs: blake2f_circuit::dev::INPUTS_OUTPUTS.0.clone(),
+            ripemd160_inputs: ripemd160_circuit::dev::INPUTS_OUTPUTS.0.clone(),
+            calls,
+            _marker: PhantomData,
+        }
+    }
+
+    /// The first call of every precompile.
+    fn calls() -> Vec<PrecompileCall> {
+        let blake2f = &blake2f_circuit::dev::INPUTS_OUTPUTS.0[0];
+        [
+            (
+                Precompile::Sha2,
+                sha2_256_circuit::dev::INPUTS_OUTPUTS.0[0].clone(),
+            ),
+            (Precompile::Blake2f, blake2f.to_input()),
+            (
+                Precompile::Ripemd160,
+                ripemd160_circuit::dev::INPUTS_OUTPUTS.0[0].clone(),
+            ),
+        ]
+        .iter()
+        .map(|(precompile, input)| PrecompileCall::new(*precompile, 1, input))
+        .collect()
+    }
+
+    fn test_super_circuit_over<F: FieldExt>() {
+        let circuit = circuit::<F>(calls());
+        let instances = circuit.public_inputs();
+        assert_eq!(instances.len(), 6);
+        let prover = MockProver::run(circuit.min_k(), &circuit, instances.clone()).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+
+        // the first digest chunk of the BLAKE2F table is not that of its
+        // call.
+        let mut other = instances;
+        other[2][3] += F::one();
+        let prover = MockProver::run(circuit.min_k(), &circuit, other).unwrap();
+        assert!(prover.verify().is_err());
+
+        // a word that is not the one of the digest in the table.
+        let mut calls = calls();
+        let last = calls.len() - 1;
+        calls[last].output[2] ^= 1;
+        let circuit = self::circuit::<F>(calls);
+        let instances = circuit.public_inputs();
+        let prover = MockProver::run(circuit.min_k(), &circuit, instances).unwrap();
+        assert!(prover.verify().is_err());
+    }
+
+    #[test]
+    fn test_super_circuit_tables() {
+        let mut meta = ConstraintSystem::<Fr>::default();
+        let config = SuperCircuitConfig::configure(&mut meta);
+        assert_eq!(config.table_columns().len(), config.annotations().len());
+        assert_eq!(config.annotations()[1], "sha2.id");
+    }
+
+    #[test]
+    fn test_super_circuit() {
+        test_super_circuit_over::<Fr>();
+        test_super_circuit_over::<pallas::Base>();
+    }
+}
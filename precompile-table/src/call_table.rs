@@ -0,0 +1,193 @@
+//! The table of the calls of the circuit of a precompile, in the layout of a
+//! [`PrecompileSource`], for the hash circuits whose own tables hold the RLCs
+//! of a challenge of their own.
+//!
+//! `CallTable` holds a row per call of the circuit:
+//! - `is_enabled`: whether the row holds a call;
+//! - `input_rlc`: the RLC of the input bytes;
+//! - `input_len`: the length of the input in bytes;
+//! - `output_rlc`: the RLC of the digest bytes.
+//!
+//! The RLCs are copied from those an [`RlcChip`] accumulates over the cells
+//! of the input and the digest of every call, as assigned by the chip of the
+//! circuit, and the lengths are assigned from constants, so that a row holds
+//! a call of the circuit and no other.
+
+use constraint_builder::{BaseConstraintBuilder, Expr};
+use gadgets::{tables::RangeTable, AssignedBytes, AssignedCall};
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{Layouter, Value},
+    plonk::{Advice, Any, Challenge, Column, ConstraintSystem, Error, SecondPhase, Selector},
+    poly::Rotation,
+};
+use rlc_chip::{RlcChip, RlcConfig};
+
+use crate::PrecompileSource;
+
+#[derive(Clone, Copy, Debug)]
+pub struct CallTable {
+    pub is_enabled: Column<Advice>,
+    pub input_rlc: Column<Advice>,
+    pub input_len: Column<Advice>,
+    pub output_rlc: Column<Advice>,
+}
+
+impl CallTable {
+    pub fn construct<F: FieldExt>(meta: &mut ConstraintSystem<F>) -> Self {
+        let [is_enabled, input_len] = [0; 2].map(|_| meta.advice_column());
+        let [input_rlc, output_rlc] = [0; 2].map(|_| meta.advice_column_in(SecondPhase));
+        for column in [input_len, input_rlc, output_rlc] {
+            meta.enable_equality(column);
+        }
+        Self {
+            is_enabled,
+            input_rlc,
+            input_len,
+            output_rlc,
+        }
+    }
+
+    /// The source of a precompile of a [`GasCost::Linear`](crate::GasCost)
+    /// cost, constrained within the precompile table.
+    pub fn source(&self) -> PrecompileSource {
+        PrecompileSource {
+            is_enabled: self.is_enabled.into(),
+            input_rlc: self.input_rlc.into(),
+            input_len: self.input_len.into(),
+            output_rlc: self.output_rlc.into(),
+            gas: None,
+        }
+    }
+
+    pub fn columns(&self) -> Vec<Column<Any>> {
+        vec![
+            self.is_enabled.into(),
+            self.input_rlc.into(),
+            self.input_len.into(),
+            self.output_rlc.into(),
+        ]
+    }
+
+    pub fn annotations(&self) -> Vec<String> {
+        vec![
+            String::from("is_enabled"),
+            String::from("input_rlc"),
+            String::from("input_len"),
+            String::from("output_rlc"),
+        ]
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct CallTableConfig {
+    table: CallTable,
+    rlc: RlcConfig,
+    q_table: Selector,
+}
+
+impl CallTableConfig {
+    /// Configures the table with the `challenge` of the precompile table,
+    /// over the byte table the caller loads.
+    pub fn configure<F: FieldExt>(
+        meta: &mut ConstraintSystem<F>,
+        table: CallTable,
+        byte_table: RangeTable<8>,
+        challenge: Challenge,
+    ) -> Self {
+        let rlc = RlcConfig::configure_with_challenge(meta, byte_table, challenge);
+        let q_table = meta.selector();
+
+        meta.create_gate("validity check over the call table rows", |meta| {
+            let mut cb = BaseConstraintBuilder::default();
+            let is_enabled = meta.query_advice(table.is_enabled, Rotation::cur());
+
+            cb.require_boolean("is_enabled is boolean", is_enabled.clone());
+            cb.condition(1.expr() - is_enabled, |cb| {
+                for (name, column) in [
+                    ("input_rlc == 0 if !is_enabled", table.input_rlc),
+                    ("input_len == 0 if !is_enabled", table.input_len),
+                    ("output_rlc == 0 if !is_enabled", table.output_rlc),
+                ] {
+                    cb.require_zero(name, meta.query_advice(column, Rotation::cur()));
+                }
+            });
+
+            cb.gate(meta.query_selector(q_table))
+        });
+
+        Self {
+            table,
+            rlc,
+            q_table,
+        }
+    }
+
+    pub fn table(&self) -> &CallTable {
+        &self.table
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct CallTableChip {
+    config: CallTableConfig,
+}
+
+impl CallTableChip {
+    pub fn construct(config: CallTableConfig) -> Self {
+        Self { config }
+    }
+
+    /// Assigns the table of the `calls` of the circuit `name`: a disabled
+    /// first row, followed by one row per call. The circuit enables a
+    /// constants column.
+    pub fn assign<F: FieldExt>(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        name: &str,
+        calls: &[AssignedCall<F>],
+    ) -> Result<(), Error> {
+        let config = &self.config;
+        let messages: Vec<AssignedBytes<F>> = calls
+            .iter()
+            .flat_map(|call| [call.input.clone(), call.digest.clone()])
+            .collect();
+        let rlcs = RlcChip::construct(config.rlc).assign_cells(layouter, &messages)?;
+
+        let table = &config.table;
+        layouter.assign_region(
+            || format!("{name} call table"),
+            |mut region| {
+                for (annotation, column) in [
+                    ("is_enabled", table.is_enabled),
+                    ("input_rlc", table.input_rlc),
+                    ("input_len", table.input_len),
+                    ("output_rlc", table.output_rlc),
+                ] {
+                    region.assign_advice(|| annotation, column, 0, || Value::known(F::zero()))?;
+                }
+                config.q_table.enable(&mut region, 0)?;
+
+                for (i, (call, rlcs)) in calls.iter().zip(rlcs.chunks(2)).enumerate() {
+                    let offset = i + 1;
+                    config.q_table.enable(&mut region, offset)?;
+                    region.assign_advice(
+                        || "is_enabled",
+                        table.is_enabled,
+                        offset,
+                        || Value::known(F::one()),
+                    )?;
+                    rlcs[0].copy_advice(|| "input_rlc", &mut region, table.input_rlc, offset)?;
+                    region.assign_advice_from_constant(
+                        || "input_len",
+                        table.input_len,
+                        offset,
+                        F::from(call.input.len() as u64),
+                    )?;
+                    rlcs[1].copy_advice(|| "output_rlc", &mut region, table.output_rlc, offset)?;
+                }
+                Ok(())
+            },
+        )
+    }
+}
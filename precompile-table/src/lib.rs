@@ -0,0 +1,769 @@
+//! The precompile table gathers the calls to all the precompiled contracts in
+//! a single table, `PrecompileTable`, so that the EVM circuit verifies a call
+//! to any precompile with a single lookup argument, rather than with one
+//! lookup per circuit of a precompile as in the precompile super-circuit:
+//! - `is_enabled`: whether the row holds a call;
+//! - `precompile_address`: the address of the precompiled contract;
+//! - `call_id`: the identifier of the call, e.g. the RW counter of the EVM
+//!   circuit at the call;
+//! - `input_rlc`: the RLC of the input bytes;
+//! - `input_len`: the length of the input in bytes;
+//! - `output_rlc`: the RLC of the output bytes;
+//! - `gas`: the gas cost of the call.
+//!
+//! Every circuit of a precompile is registered in `PrecompileConfig` with the
+//! columns of its own table, as a `PrecompileSource`. The rows of the
+//! precompile table whose address is that of the precompile are looked up
+//! from the table of its circuit, so that each circuit populates its rows
+//! through its own constraints, and an enabled row must have the address of
+//! one of the registered precompiles. The gas of a precompile whose cost is
+//! linear in the number of 32-byte words of its input is constrained within
+//! the table, while the other ones look it up from the table of their
+//! circuit along with the call.
+//!
+//! The RLCs must use the same challenge as the tables of the precompiles,
+//! so the challenge is passed to `PrecompileConfig::configure`.
+//!
+//! The hash circuits, whose own tables hold the RLCs of a challenge of their
+//! own, are registered through a [`CallTable`], whose rows are the RLCs of the cells
+//! of the inputs and the digests their chips assign.
+
+mod call_table;
+
+use std::marker::PhantomData;
+
+use constraint_builder::{BaseConstraintBuilder, Expr};
+use gadgets::{tables::RangeTable, util::rlc, IsEqualChip, IsEqualConfig};
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{Layouter, Value},
+    plonk::{
+        Advice, Any, Challenge, Column, ConstraintSystem, Error, SecondPhase, Selector,
+        VirtualCells,
+    },
+    poly::Rotation,
+};
+
+pub use call_table::{CallTable, CallTableChip, CallTableConfig};
+
+/// The precompiled contracts of the EVM.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Precompile {
+    Ecrecover,
+    Sha256,
+    Ripemd160,
+    Identity,
+    Modexp,
+    Bn128Add,
+    Bn128Mul,
+    Bn128Pairing,
+    Blake2f,
+}
+
+/// Gas cost of a precompile, as of the Berlin hard fork.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GasCost {
+    /// `base + per_word * ceil(input_len / 32)`.
+    Linear { base: u64, per_word: u64 },
+    /// A cost that depends on the content of the input, e.g. the number of
+    /// rounds of BLAKE2F.
+    Dynamic,
+}
+
+impl Precompile {
+    pub fn address(&self) -> u64 {
+        match self {
+            Self::Ecrecover => 0x01,
+            Self::Sha256 => 0x02,
+            Self::Ripemd160 => 0x03,
+            Self::Identity => 0x04,
+            Self::Modexp => 0x05,
+            Self::Bn128Add => 0x06,
+            Self::Bn128Mul => 0x07,
+            Self::Bn128Pairing => 0x08,
+            Self::Blake2f => 0x09,
+        }
+    }
+
+    pub fn gas_cost(&self) -> GasCost {
+        let linear = |base, per_word| GasCost::Linear { base, per_word };
+        match self {
+            Self::Ecrecover => linear(3000, 0),
+            Self::Sha256 => linear(60, 12),
+            Self::Ripemd160 => linear(600, 120),
+            Self::Identity => linear(15, 3),
+            Self::Bn128Add => linear(150, 0),
+            Self::Bn128Mul => linear(6000, 0),
+            Self::Modexp | Self::Bn128Pairing | Self::Blake2f => GasCost::Dynamic,
+        }
+    }
+
+    /// Gas of a call with an input of `input_len` bytes, if the cost does not
+    /// depend on the content of the input.
+    pub fn gas(&self, input_len: usize) -> Option<u64> {
+        match self.gas_cost() {
+            GasCost::Linear { base, per_word } => {
+                Some(base + per_word * input_len.div_ceil(32) as u64)
+            }
+            GasCost::Dynamic => None,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct PrecompileTable {
+    pub is_enabled: Column<Advice>,
+    pub precompile_address: Column<Advice>,
+    pub call_id: Column<Advice>,
+    pub input_rlc: Column<Advice>,
+    pub input_len: Column<Advice>,
+    pub output_rlc: Column<Advice>,
+    pub gas: Column<Advice>,
+}
+
+impl PrecompileTable {
+    pub fn construct<F: FieldExt>(meta: &mut ConstraintSystem<F>) -> Self {
+        Self {
+            is_enabled: meta.advice_column(),
+            precompile_address: meta.advice_column(),
+            call_id: meta.advice_column(),
+            input_rlc: meta.advice_column_in(SecondPhase),
+            input_len: meta.advice_column(),
+            output_rlc: meta.advice_column_in(SecondPhase),
+            gas: meta.advice_column(),
+        }
+    }
+
+    pub fn columns(&self) -> Vec<Column<Any>> {
+        vec![
+            self.is_enabled.into(),
+            self.precompile_address.into(),
+            self.call_id.into(),
+            self.input_rlc.into(),
+            self.input_len.into(),
+            self.output_rlc.into(),
+            self.gas.into(),
+        ]
+    }
+
+    pub fn annotations(&self) -> Vec<String> {
+        vec![
+            String::from("is_enabled"),
+            String::from("precompile_address"),
+            String::from("call_id"),
+            String::from("input_rlc"),
+            String::from("input_len"),
+            String::from("output_rlc"),
+            String::from("gas"),
+        ]
+    }
+}
+
+/// Columns of the table of the circuit of a precompile, which the rows of
+/// the precompile table with its address are looked up from. `gas` is only
+/// looked up for a precompile with a [`GasCost::Dynamic`] cost.
+#[derive(Clone, Debug)]
+pub struct PrecompileSource {
+    pub is_enabled: Column<Any>,
+    pub input_rlc: Column<Any>,
+    pub input_len: Column<Any>,
+    pub output_rlc: Column<Any>,
+    pub gas: Option<Column<Any>>,
+}
+
+#[derive(Clone, Debug)]
+pub struct PrecompileConfig<F> {
+    q_enable: Selector,
+    table: PrecompileTable,
+    /// Number of words of the input, as two bytes.
+    words: [Column<Advice>; 2],
+    /// Whether the address of the row is that of each registered precompile.
+    is_precompile: Vec<(Precompile, IsEqualConfig<F>)>,
+    challenge: Challenge,
+    _marker: PhantomData<F>,
+}
+
+impl<F: FieldExt> PrecompileConfig<F> {
+    /// Configures the table over the byte table the caller loads.
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        table: PrecompileTable,
+        byte_table: RangeTable<8>,
+        challenge: Challenge,
+        sources: Vec<(Precompile, PrecompileSource)>,
+    ) -> Self {
+        let q_enable = meta.complex_selector();
+        let words = [(); 2].map(|_| meta.advice_column());
+
+        let is_precompile: Vec<_> = sources
+            .iter()
+            .map(|(precompile, _)| {
+                let address = precompile.address();
+                let value_inv = meta.advice_column();
+                let is_equal = IsEqualConfig::configure(
+                    meta,
+                    |meta| meta.query_selector(q_enable),
+                    |meta| meta.query_advice(table.precompile_address, Rotation::cur()),
+                    |_| address.expr(),
+                    value_inv,
+                );
+                (*precompile, is_equal)
+            })
+            .collect();
+
+        let num_words = |meta: &mut VirtualCells<'_, F>| {
+            meta.query_advice(words[0], Rotation::cur())
+                + meta.query_advice(words[1], Rotation::cur()) * 256.expr()
+        };
+
+        meta.create_gate("validity check over the table rows", |meta| {
+            let mut cb = BaseConstraintBuilder::default();
+            let is_enabled = meta.query_advice(table.is_enabled, Rotation::cur());
+            let gas = meta.query_advice(table.gas, Rotation::cur());
+            let num_words = num_words(meta);
+
+            cb.require_boolean("is_enabled is boolean", is_enabled.clone());
+            cb.condition(1.expr() - is_enabled.clone(), |cb| {
+                for (name, column) in [
+                    (
+                        "precompile_address == 0 if !is_enabled",
+                        table.precompile_address,
+                    ),
+                    ("call_id == 0 if !is_enabled", table.call_id),
+                    ("input_rlc == 0 if !is_enabled", table.input_rlc),
+                    ("input_len == 0 if !is_enabled", table.input_len),
+                    ("output_rlc == 0 if !is_enabled", table.output_rlc),
+                    ("gas == 0 if !is_enabled", table.gas),
+                ] {
+                    cb.require_zero(name, meta.query_advice(column, Rotation::cur()));
+                }
+            });
+            cb.condition(is_enabled, |cb| {
+                cb.require_equal(
+                    "precompile_address is registered if is_enabled",
+                    is_precompile
+                        .iter()
+                        .fold(0.expr(), |acc, (_, is_equal)| acc + is_equal.expr()),
+                    1.expr(),
+                );
+                for (precompile, is_equal) in is_precompile.iter() {
+                    if let GasCost::Linear { base, per_word } = precompile.gas_cost() {
+                        cb.require_zero(
+                            "gas == base + per_word * words",
+                            is_equal.expr()
+                                * (gas.clone() - base.expr() - num_words.clone() * per_word.expr()),
+                        );
+                    }
+                }
+            });
+
+            cb.gate(meta.query_selector(q_enable))
+        });
+
+        // words = ceil(input_len / 32), i.e. 32 * words - input_len is in
+        // [0, 32), with words of two bytes.
+        for (name, column) in [
+            ("words lo is a byte", words[0]),
+            ("words hi is a byte", words[1]),
+        ] {
+            meta.lookup(name, |meta| {
+                let q_enable = meta.query_selector(q_enable);
+                vec![(
+                    q_enable * meta.query_advice(column, Rotation::cur()),
+                    byte_table.value,
+                )]
+            });
+        }
+        for (name, offset) in [
+            ("32 * words - input_len >= 0", 0u64),
+            ("32 * words - input_len < 32", 256 - 32),
+        ] {
+            meta.lookup(name, |meta| {
+                let q_enable = meta.query_selector(q_enable);
+                let input_len = meta.query_advice(table.input_len, Rotation::cur());
+                vec![(
+                    q_enable * (num_words(meta) * 32.expr() - input_len + offset.expr()),
+                    byte_table.value,
+                )]
+            });
+        }
+
+        for ((precompile, source), (_, is_equal)) in sources.iter().zip(is_precompile.iter()) {
+            meta.lookup_any("precompile call in the table of its circuit", |meta| {
+                let q_enable = meta.query_selector(q_enable);
+                let is_enabled = meta.query_advice(table.is_enabled, Rotation::cur());
+                let condition = q_enable * is_enabled * is_equal.expr();
+
+                let mut pairs = vec![
+                    (table.is_enabled, source.is_enabled),
+                    (table.input_rlc, source.input_rlc),
+                    (table.input_len, source.input_len),
+                    (table.output_rlc, source.output_rlc),
+                ];
+                if precompile.gas_cost() == GasCost::Dynamic {
+                    let gas = source
+                        .gas
+                        .expect("a precompile of dynamic cost looks up its gas");
+                    pairs.push((table.gas, gas));
+                }
+                pairs
+                    .into_iter()
+                    .map(|(column, source)| {
+                        (
+                            condition.clone() * meta.query_advice(column, Rotation::cur()),
+                            meta.query_any(source, Rotation::cur()),
+                        )
+                    })
+                    .collect()
+            });
+        }
+
+        Self {
+            q_enable,
+            table,
+            words,
+            is_precompile,
+            challenge,
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn table(&self) -> &PrecompileTable {
+        &self.table
+    }
+
+    pub fn challenge(&self) -> Challenge {
+        self.challenge
+    }
+}
+
+/// A call to a precompile, as seen by the EVM circuit.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PrecompileCall {
+    pub precompile: Precompile,
+    pub call_id: u64,
+    pub input: Vec<u8>,
+    pub output: Vec<u8>,
+    pub gas: u64,
+}
+
+impl PrecompileCall {
+    /// A call to a precompile whose gas only depends on the length of its
+    /// input.
+    pub fn new(precompile: Precompile, call_id: u64, input: Vec<u8>, output: Vec<u8>) -> Self {
+        let gas = precompile
+            .gas(input.len())
+            .expect("the gas of the precompile only depends on the input length");
+        Self {
+            precompile,
+            call_id,
+            input,
+            output,
+            gas,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct PrecompileWitness<F> {
+    pub calls: Vec<PrecompileCall>,
+    pub _marker: PhantomData<F>,
+}
+
+#[derive(Clone, Debug)]
+pub struct PrecompileChip<F> {
+    config: PrecompileConfig<F>,
+    data: PrecompileWitness<F>,
+}
+
+impl<F: FieldExt> PrecompileChip<F> {
+    pub fn construct(config: PrecompileConfig<F>, data: PrecompileWitness<F>) -> Self {
+        Self { config, data }
+    }
+
+    /// Assigns the table: a disabled first row, followed by one row per call.
+    /// The byte table passed to the config must be loaded by the caller.
+    pub fn load(&self, layouter: &mut impl Layouter<F>) -> Result<(), Error> {
+        let challenge = layouter.get_challenge(self.config.challenge);
+        let table = &self.config.table;
+        let is_precompile: Vec<_> = self
+            .config
+            .is_precompile
+            .iter()
+            .map(|(precompile, config)| (*precompile, IsEqualChip::construct(config.clone())))
+            .collect();
+
+        layouter.assign_region(
+            || "precompile table",
+            |mut region| {
+                let disabled_row = (
+                    false,
+                    0,
+                    0,
+                    Value::known(F::zero()),
+                    0,
+                    Value::known(F::zero()),
+                    0,
+                );
+                let rows = self.data.calls.iter().map(|call| {
+                    (
+                        true,
+                        call.precompile.address(),
+                        call.call_id,
+                        rlc(&call.input, challenge),
+                        call.input.len(),
+                        rlc(&call.output, challenge),
+                        call.gas,
+                    )
+                });
+
+                for (
+                    offset,
+                    (is_enabled, address, call_id, input_rlc, input_len, output_rlc, gas),
+                ) in std::iter::once(disabled_row).chain(rows).enumerate()
+                {
+                    self.config.q_enable.enable(&mut region, offset)?;
+                    for (name, column, value) in [
+                        ("is_enabled", table.is_enabled, is_enabled as u64),
+                        ("precompile_address", table.precompile_address, address),
+                        ("call_id", table.call_id, call_id),
+                        ("input_len", table.input_len, input_len as u64),
+                        ("gas", table.gas, gas),
+                    ] {
+                        region.assign_advice(
+                            || name,
+                            column,
+                            offset,
+                            || Value::known(F::from(value)),
+                        )?;
+                    }
+                    region.assign_advice(|| "input_rlc", table.input_rlc, offset, || input_rlc)?;
+                    region.assign_advice(
+                        || "output_rlc",
+                        table.output_rlc,
+                        offset,
+                        || output_rlc,
+                    )?;
+
+                    let words = input_len.div_ceil(32) as u64;
+                    for (i, column) in self.config.words.iter().enumerate() {
+                        region.assign_advice(
+                            || "words",
+                            *column,
+                            offset,
+                            || Value::known(F::from((words >> (8 * i)) & 0xff)),
+                        )?;
+                    }
+                    for (precompile, chip) in is_precompile.iter() {
+                        chip.assign(
+                            &mut region,
+                            offset,
+                            Value::known(F::from(address)),
+                            Value::known(F::from(precompile.address())),
+                        )?;
+                    }
+                }
+                Ok(())
+            },
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gadgets::{rows, tables::FixedTables, HashCircuitExt};
+    use halo2_proofs::{
+        circuit::SimpleFloorPlanner,
+        dev::MockProver,
+        halo2curves::{bn256::Fr, pasta::pallas},
+        plonk::Circuit,
+        plonk::FirstPhase,
+    };
+    use ripemd160_circuit::{native::ripemd160, Ripemd160Chip, Ripemd160Config, Ripemd160Witness};
+    use sha2_256_circuit::{native::sha256, Sha2Chip, Sha2Config, Sha2Witness};
+
+    /// The table of the circuit of a precompile, holding its calls as is.
+    #[derive(Clone, Debug)]
+    struct SourceTable {
+        is_enabled: Column<Advice>,
+        input_rlc: Column<Advice>,
+        input_len: Column<Advice>,
+        output_rlc: Column<Advice>,
+        gas: Column<Advice>,
+    }
+
+    impl SourceTable {
+        fn construct<F: FieldExt>(meta: &mut ConstraintSystem<F>) -> Self {
+            Self {
+                is_enabled: meta.advice_column(),
+                input_rlc: meta.advice_column_in(SecondPhase),
+                input_len: meta.advice_column(),
+                output_rlc: meta.advice_column_in(SecondPhase),
+                gas: meta.advice_column(),
+            }
+        }
+
+        fn source(&self) -> PrecompileSource {
+            PrecompileSource {
+                is_enabled: self.is_enabled.into(),
+                input_rlc: self.input_rlc.into(),
+                input_len: self.input_len.into(),
+                output_rlc: self.output_rlc.into(),
+                gas: Some(self.gas.into()),
+            }
+        }
+    }
+
+    /// The precompiles of the tables of the test, whose calls are held as is.
+    const PRECOMPILES: [Precompile; 2] = [Precompile::Identity, Precompile::Blake2f];
+
+    #[derive(Clone, Debug)]
+    struct TestConfig<F> {
+        precompile: PrecompileConfig<F>,
+        byte_table: RangeTable<8>,
+        sha2: Sha2Config<F>,
+        sha2_calls: CallTableConfig,
+        ripemd160: Ripemd160Config<F>,
+        ripemd160_calls: CallTableConfig,
+        sources: Vec<SourceTable>,
+    }
+
+    /// The precompile table over the SHA2-256 and RIPEMD-160 circuits and the
+    /// tables of identity and BLAKE2F, which hold the calls of `sources`, and
+    /// the calls of the table respectively.
+    #[derive(Default)]
+    struct TestCircuit<F> {
+        sources: Vec<PrecompileCall>,
+        calls: Vec<PrecompileCall>,
+        _marker: PhantomData<F>,
+    }
+
+    impl<F: FieldExt> TestCircuit<F> {
+        /// The inputs of the calls of `sources` to `precompile`.
+        fn inputs(&self, precompile: Precompile) -> Vec<Vec<u8>> {
+            self.sources
+                .iter()
+                .filter(|call| call.precompile == precompile)
+                .map(|call| call.input.clone())
+                .collect()
+        }
+    }
+
+    impl<F: FieldExt> Circuit<F> for TestCircuit<F> {
+        type Config = TestConfig<F>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            let table = PrecompileTable::construct(meta);
+            let byte_table = RangeTable::construct(meta);
+            let challenge = meta.challenge_usable_after(FirstPhase);
+            // the calls are those of the circuit, not exposed.
+            let sha2 = Sha2Chip::configure_with_tables(meta, &mut FixedTables::default());
+            let ripemd160 = Ripemd160Chip::configure_with_tables(meta, &mut FixedTables::default());
+            let [sha2_calls, ripemd160_calls] = [0; 2].map(|_| {
+                let table = CallTable::construct(meta);
+                CallTableConfig::configure(meta, table, byte_table, challenge)
+            });
+            let sources: Vec<_> = PRECOMPILES
+                .iter()
+                .map(|_| SourceTable::construct(meta))
+                .collect();
+            let precompile = PrecompileConfig::configure(
+                meta,
+                table,
+                byte_table,
+                challenge,
+                [
+                    (Precompile::Sha256, sha2_calls.table().source()),
+                    (Precompile::Ripemd160, ripemd160_calls.table().source()),
+                ]
+                .into_iter()
+                .chain(
+                    PRECOMPILES
+                        .iter()
+                        .zip(sources.iter())
+                        .map(|(precompile, table)| (*precompile, table.source())),
+                )
+                .collect(),
+            );
+            TestConfig {
+                precompile,
+                byte_table,
+                sha2,
+                sha2_calls,
+                ripemd160,
+                ripemd160_calls,
+                sources,
+            }
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            let challenge = layouter.get_challenge(config.precompile.challenge());
+            config.byte_table.load(&mut layouter)?;
+
+            let sha2_calls = Sha2Chip::construct(
+                config.sha2,
+                Sha2Witness {
+                    inputs: self.inputs(Precompile::Sha256),
+                    _marker: PhantomData,
+                },
+            )
+            .assign_calls(&mut layouter)?;
+            CallTableChip::construct(config.sha2_calls).assign(
+                &mut layouter,
+                "sha2",
+                &sha2_calls,
+            )?;
+            let ripemd160_calls = Ripemd160Chip::construct(
+                config.ripemd160,
+                Ripemd160Witness {
+                    inputs: self.inputs(Precompile::Ripemd160),
+                    _marker: PhantomData,
+                },
+            )
+            .assign_calls(&mut layouter)?;
+            CallTableChip::construct(config.ripemd160_calls).assign(
+                &mut layouter,
+                "ripemd160",
+                &ripemd160_calls,
+            )?;
+
+            for (precompile, table) in PRECOMPILES.iter().zip(config.sources.iter()) {
+                layouter.assign_region(
+                    || "source table",
+                    |mut region| {
+                        let calls = self
+                            .sources
+                            .iter()
+                            .filter(|call| call.precompile == *precompile);
+                        for (offset, call) in calls.enumerate() {
+                            for (column, value) in [
+                                (table.is_enabled, Value::known(F::one())),
+                                (table.input_rlc, rlc(&call.input, challenge)),
+                                (
+                                    table.input_len,
+                                    Value::known(F::from(call.input.len() as u64)),
+                                ),
+                                (table.output_rlc, rlc(&call.output, challenge)),
+                                (table.gas, Value::known(F::from(call.gas))),
+                            ] {
+                                region.assign_advice(|| "source", column, offset, || value)?;
+                            }
+                        }
+                        Ok(())
+                    },
+                )?;
+            }
+
+            let chip = PrecompileChip::construct(
+                config.precompile,
+                PrecompileWitness {
+                    calls: self.calls.clone(),
+                    _marker: PhantomData,
+                },
+            );
+            chip.load(&mut layouter)
+        }
+    }
+
+    fn calls() -> Vec<PrecompileCall> {
+        // The rounds of BLAKE2F are the first 4 bytes of its input, and its
+        // gas is one per round.
+        let mut blake2f_input = vec![0u8; 213];
+        blake2f_input[3] = 12;
+        vec![
+            PrecompileCall::new(
+                Precompile::Sha256,
+                1,
+                b"abc".to_vec(),
+                sha256(b"abc").to_vec(),
+            ),
+            PrecompileCall::new(Precompile::Identity, 2, vec![7; 33], vec![7; 33]),
+            PrecompileCall {
+                precompile: Precompile::Blake2f,
+                call_id: 3,
+                input: blake2f_input,
+                output: vec![0; 64],
+                gas: 12,
+            },
+            PrecompileCall::new(Precompile::Identity, 4, vec![], vec![]),
+            PrecompileCall::new(
+                Precompile::Ripemd160,
+                5,
+                vec![1; 65],
+                ripemd160(&[1; 65]).to_vec(),
+            ),
+        ]
+    }
+
+    fn verify<F: FieldExt>(
+        calls: Vec<PrecompileCall>,
+    ) -> Result<(), Vec<halo2_proofs::dev::VerifyFailure>> {
+        let circuit = TestCircuit::<F> {
+            sources: self::calls(),
+            calls,
+            _marker: PhantomData,
+        };
+        let prover = MockProver::run(rows::min_k(&circuit), &circuit, vec![]).unwrap();
+        prover.verify()
+    }
+
+    #[test]
+    fn test_precompile_gas() {
+        assert_eq!(Precompile::Sha256.gas(0), Some(60));
+        assert_eq!(Precompile::Sha256.gas(33), Some(84));
+        assert_eq!(Precompile::Ecrecover.gas(128), Some(3000));
+        assert_eq!(Precompile::Blake2f.gas(213), None);
+    }
+
+    fn test_precompile_table_over<F: FieldExt>() {
+        assert_eq!(verify::<F>(calls()), Ok(()));
+
+        let mut wrong_gas = calls();
+        wrong_gas[1].gas += 1;
+        let mut wrong_output = calls();
+        wrong_output[0].output[0] ^= 1;
+        // a leading zero byte leaves the RLC and the gas of the input as is.
+        let mut wrong_input_len = calls();
+        wrong_input_len[4].input.insert(0, 0);
+        let mut dynamic_gas = calls();
+        dynamic_gas[2].gas = 13;
+        let unregistered = vec![PrecompileCall::new(
+            Precompile::Ecrecover,
+            6,
+            vec![0; 128],
+            vec![0; 32],
+        )];
+        for calls in [
+            wrong_gas,
+            wrong_output,
+            wrong_input_len,
+            dynamic_gas,
+            unregistered,
+        ] {
+            assert!(verify::<F>(calls).is_err());
+        }
+    }
+
+    #[test]
+    fn test_precompile_table_columns() {
+        let mut meta = ConstraintSystem::<Fr>::default();
+        let table = PrecompileTable::construct(&mut meta);
+        assert_eq!(table.columns().len(), table.annotations().len());
+    }
+
+    #[test]
+    fn test_precompile_table() {
+        test_precompile_table_over::<Fr>();
+        test_precompile_table_over::<pallas::Base>();
+    }
+}
@@ -0,0 +1,106 @@
+//! The verifier contract of a circuit on the EVM, and the calldata of its
+//! proofs, generated with the EVM loader of `snark-verifier`. The contract
+//! verifies the SHPLONK proofs of [`crate::prove`] over the Keccak-256
+//! transcript of `snark-verifier`, which the `evm` feature proves with, and
+//! reverts on a proof that does not verify.
+//!
+//! The contract is generated in Solidity, its verifier being Yul assembly,
+//! and compiled by the `solc` of the `PATH`, which [`compile`] needs; it is
+//! deployed and called in `revm` by [`call_verifier`].
+
+use std::rc::Rc;
+
+use halo2_proofs::{
+    halo2curves::bn256::{Bn256, Fq, Fr, G1Affine},
+    plonk::VerifyingKey,
+    poly::{commitment::ParamsProver, kzg::commitment::ParamsKZG},
+};
+use snark_verifier::{
+    loader::evm::{self, EvmLoader},
+    pcs::kzg::{Bdfg21, KzgAs, KzgDecidingKey},
+    system::halo2::{compile as compile_protocol, transcript::evm::EvmTranscript, Config},
+    verifier::{self, SnarkVerifier},
+};
+use tracing::info_span;
+
+/// The verifier of the proofs of [`crate::prove`], whose SHPLONK multi-open
+/// scheme is that of BDFG21.
+type PlonkVerifier = verifier::plonk::PlonkVerifier<KzgAs<Bn256, Bdfg21>>;
+
+/// The Solidity source of the verifier contract of the proofs of the
+/// circuit of `vk`, whose instance columns have `num_instance` values each.
+pub fn verifier_contract(
+    params: &ParamsKZG<Bn256>,
+    vk: &VerifyingKey<G1Affine>,
+    num_instance: Vec<usize>,
+) -> String {
+    let _span = info_span!("verifier contract").entered();
+    let protocol = compile_protocol(
+        params,
+        vk,
+        Config::kzg().with_num_instance(num_instance.clone()),
+    );
+    let dk: KzgDecidingKey<Bn256> = (params.get_g()[0], params.g2(), params.s_g2()).into();
+
+    let loader = EvmLoader::new::<Fq, Fr>();
+    let protocol = protocol.loaded(&loader);
+    let mut transcript = EvmTranscript::<_, Rc<EvmLoader>, _, _>::new(&loader);
+    let instances = transcript.load_instances(num_instance);
+    // the loader records the reads and checks of the verifier as code, so
+    // that they cannot fail before the contract is called.
+    let proof = PlonkVerifier::read_proof(&dk, &protocol, &instances, &mut transcript)
+        .expect("the EVM loader reads any proof");
+    PlonkVerifier::verify(&dk, &protocol, &instances, &proof)
+        .expect("the EVM loader defers the checks to the contract");
+    loader.solidity_code()
+}
+
+/// The deployment code of `contract`, compiled by `solc`, which panics if
+/// `solc` is not in the `PATH` or refuses the contract.
+pub fn compile(contract: &str) -> Vec<u8> {
+    let _span = info_span!("solc").entered();
+    evm::compile_solidity(contract)
+}
+
+/// The calldata of a call of a verifier contract for `proof`, with
+/// `instances` the values of the instance columns of its circuit.
+pub fn calldata(instances: &[Vec<Fr>], proof: &[u8]) -> Vec<u8> {
+    evm::encode_calldata(instances, proof)
+}
+
+/// Deploys the verifier contract of `deployment_code` in `revm` and calls it
+/// with `calldata`: the gas of the call if it accepts the proof, or `None`
+/// if it reverts.
+pub fn call_verifier(deployment_code: Vec<u8>, calldata: Vec<u8>) -> Option<u64> {
+    let _span = info_span!("revm").entered();
+    evm::deploy_and_call(deployment_code, calldata).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::circuit::Value;
+    use rand::rngs::OsRng;
+
+    use super::*;
+    use crate::{keygen, prove, tests::InstanceCircuit, verify};
+
+    #[test]
+    fn test_verifier_contract() {
+        let params = ParamsKZG::<Bn256>::setup(4, OsRng);
+        let pk = keygen(&params, &InstanceCircuit::default()).unwrap();
+        let circuit = InstanceCircuit {
+            value: Value::known(Fr::from(7)),
+        };
+        let instances = vec![vec![Fr::from(7)]];
+        let proof = prove(&params, &pk, circuit, &instances).unwrap();
+        assert!(verify(&params, pk.get_vk(), &proof, &instances));
+
+        let code = compile(&verifier_contract(&params, pk.get_vk(), vec![1]));
+        assert!(call_verifier(code.clone(), calldata(&instances, &proof)).is_some());
+        // the contract reverts on the instances of another statement, and
+        // on a truncated proof.
+        assert!(call_verifier(code.clone(), calldata(&[vec![Fr::from(8)]], &proof)).is_none());
+        let truncated = &proof[..proof.len() / 2];
+        assert!(call_verifier(code, calldata(&instances, truncated)).is_none());
+    }
+}
@@ -0,0 +1,194 @@
+//! Keys, proofs and verification of the circuits outside of the benchmarks,
+//! for the `precompile-prover` binary and the integrators: SHPLONK proofs
+//! over the KZG commitment scheme on BN254, with the BLAKE2b transcript, or
+//! the Keccak-256 one of the verifiers on the EVM with the `evm` feature,
+//! whose verifier contracts are generated by [`evm`].
+//!
+//! Unlike the harness of the benchmarks, whose proofs are seeded to be
+//! reproducible, [`prove`] blinds the proofs with the randomness of the
+//! operating system, and the helpers fail with a [`gadgets::Error`] rather
+//! than panic, so that the caller reports why a key or a proof is refused.
+//! The parameters of a production setup are read with [`srs::read_srs`].
+
+use halo2_proofs::{
+    halo2curves::bn256::{Bn256, Fr, G1Affine},
+    plonk::{create_proof, keygen_pk, keygen_vk, verify_proof, Circuit, ProvingKey, VerifyingKey},
+    poly::{
+        commitment::{Params, ParamsProver},
+        kzg::{
+            commitment::{KZGCommitmentScheme, ParamsKZG},
+            multiopen::{ProverSHPLONK, VerifierSHPLONK},
+            strategy::SingleStrategy,
+        },
+    },
+    transcript::{TranscriptReadBuffer, TranscriptWriterBuffer},
+    SerdeFormat,
+};
+use rand::rngs::OsRng;
+use tracing::{info, info_span};
+use tracing_subscriber::{fmt::format::FmtSpan, EnvFilter};
+
+use gadgets::Error;
+
+#[cfg(feature = "evm")]
+pub mod evm;
+pub mod srs;
+
+/// Format of the keys, whose points are checked as they are read.
+pub const SERDE_FORMAT: SerdeFormat = SerdeFormat::RawBytes;
+
+#[cfg(not(feature = "evm"))]
+type TranscriptWrite = halo2_proofs::transcript::Blake2bWrite<
+    Vec<u8>,
+    G1Affine,
+    halo2_proofs::transcript::Challenge255<G1Affine>,
+>;
+#[cfg(not(feature = "evm"))]
+type TranscriptRead<'proof> = halo2_proofs::transcript::Blake2bRead<
+    &'proof [u8],
+    G1Affine,
+    halo2_proofs::transcript::Challenge255<G1Affine>,
+>;
+// the Keccak-256 transcript of the verifier contracts of `snark-verifier`,
+// whose points and challenges are encoded as the contracts read them.
+#[cfg(feature = "evm")]
+type TranscriptWrite = snark_verifier::system::halo2::transcript::evm::EvmTranscript<
+    G1Affine,
+    snark_verifier::loader::native::NativeLoader,
+    Vec<u8>,
+    Vec<u8>,
+>;
+#[cfg(feature = "evm")]
+type TranscriptRead<'proof> = snark_verifier::system::halo2::transcript::evm::EvmTranscript<
+    G1Affine,
+    snark_verifier::loader::native::NativeLoader,
+    &'proof [u8],
+    Vec<u8>,
+>;
+
+/// Installs a subscriber printing the `tracing` spans of the circuits and
+/// the phases of their proofs to stderr, with their duration when they
+/// close, at the levels of the `RUST_LOG` env var, e.g. `RUST_LOG=info` or
+/// `RUST_LOG=gadgets=debug`; none are printed if it is unset. Does nothing
+/// if a subscriber is already installed.
+pub fn init_tracing() {
+    let _ = tracing_subscriber::fmt()
+        .with_env_filter(EnvFilter::from_default_env())
+        .with_span_events(FmtSpan::CLOSE)
+        .with_writer(std::io::stderr)
+        .try_init();
+}
+
+/// The proving key of `circuit`, whose fixed columns, selectors and
+/// permutation are those of its layout.
+pub fn keygen<C: Circuit<Fr>>(
+    params: &ParamsKZG<Bn256>,
+    circuit: &C,
+) -> Result<ProvingKey<G1Affine>, Error> {
+    let rows = 1usize << params.k();
+    let vk = info_span!("keygen_vk", rows).in_scope(|| keygen_vk(params, circuit))?;
+    Ok(info_span!("keygen_pk", rows).in_scope(|| keygen_pk(params, vk, circuit))?)
+}
+
+/// The proof of `circuit` with the proving key `pk`, with `instances` the
+/// values of its instance columns, or the error of the prover, e.g. an
+/// [`Error::Plonk`] if the witness does not satisfy the constraints.
+pub fn prove<C: Circuit<Fr>>(
+    params: &ParamsKZG<Bn256>,
+    pk: &ProvingKey<G1Affine>,
+    circuit: C,
+    instances: &[Vec<Fr>],
+) -> Result<Vec<u8>, Error> {
+    let _span = info_span!("prove", rows = 1usize << params.k()).entered();
+    let instances = instances.iter().map(Vec::as_slice).collect::<Vec<_>>();
+    let mut transcript = TranscriptWrite::init(vec![]);
+    create_proof::<KZGCommitmentScheme<Bn256>, ProverSHPLONK<'_, Bn256>, _, _, _, _>(
+        params,
+        pk,
+        &[circuit],
+        &[&instances],
+        OsRng,
+        &mut transcript,
+    )?;
+    let proof = transcript.finalize();
+    info!(bytes = proof.len(), "proof generated");
+    Ok(proof)
+}
+
+/// Whether `proof`, of [`prove`], is valid with the verifying key `vk` and
+/// `instances` the values of the instance columns of its circuit.
+pub fn verify(
+    params: &ParamsKZG<Bn256>,
+    vk: &VerifyingKey<G1Affine>,
+    proof: &[u8],
+    instances: &[Vec<Fr>],
+) -> bool {
+    let _span = info_span!("verify", bytes = proof.len()).entered();
+    let instances = instances.iter().map(Vec::as_slice).collect::<Vec<_>>();
+    let mut transcript = TranscriptRead::init(proof);
+    verify_proof::<_, VerifierSHPLONK<'_, Bn256>, _, _, _>(
+        params.verifier_params(),
+        vk,
+        SingleStrategy::new(params),
+        &[&instances],
+        &mut transcript,
+    )
+    .is_ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{
+        circuit::{Layouter, SimpleFloorPlanner, Value},
+        plonk::{Advice, Column, ConstraintSystem, Error as PlonkError, Instance},
+    };
+
+    use super::*;
+
+    /// A circuit exposing its value as its single instance value.
+    #[derive(Clone, Default)]
+    pub(crate) struct InstanceCircuit {
+        pub(crate) value: Value<Fr>,
+    }
+
+    impl Circuit<Fr> for InstanceCircuit {
+        type Config = (Column<Advice>, Column<Instance>);
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+            let (advice, instance) = (meta.advice_column(), meta.instance_column());
+            meta.enable_equality(advice);
+            meta.enable_equality(instance);
+            (advice, instance)
+        }
+
+        fn synthesize(
+            &self,
+            (advice, instance): Self::Config,
+            mut layouter: impl Layouter<Fr>,
+        ) -> Result<(), PlonkError> {
+            let cell = layouter.assign_region(
+                || "value",
+                |mut region| region.assign_advice(|| "value", advice, 0, || self.value),
+            )?;
+            layouter.constrain_instance(cell.cell(), instance, 0)
+        }
+    }
+
+    #[test]
+    fn test_prove_and_verify() {
+        let params = ParamsKZG::<Bn256>::setup(4, OsRng);
+        let pk = keygen(&params, &InstanceCircuit::default()).unwrap();
+        let circuit = InstanceCircuit {
+            value: Value::known(Fr::from(7)),
+        };
+        let proof = prove(&params, &pk, circuit.clone(), &[vec![Fr::from(7)]]).unwrap();
+        assert!(verify(&params, pk.get_vk(), &proof, &[vec![Fr::from(7)]]));
+        assert!(!verify(&params, pk.get_vk(), &proof, &[vec![Fr::from(8)]]));
+        assert!(prove(&params, &pk, circuit, &[vec![Fr::from(8)]]).is_err());
+    }
+}
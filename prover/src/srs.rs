@@ -0,0 +1,208 @@
+//! Parameters of the KZG commitment scheme of a production setup, e.g. the
+//! perpetual powers of tau, read from a file rather than generated from a
+//! local secret, so that the proofs are those real provers make.
+//!
+//! The file is either a `.ptau` file of the powers of tau, in the format of
+//! snarkjs, or halo2 parameters in the format of `ParamsKZG::write`, such as
+//! those cached by the benchmarks or written by their `convert_srs` binary.
+//! It may be of a larger degree than the circuits: it is memory-mapped, and
+//! only the powers of tau a degree needs are read from it. The Lagrange
+//! basis of the parameters of a smaller degree is then computed from them,
+//! which takes a while at large degrees, so `convert_srs` converts a file to
+//! the parameters of a degree ahead of time:
+//!
+//! ```text
+//! cargo run --release --bin convert_srs -- powersOfTau28_hez_final.ptau 22 kzg_bn256_22.srs
+//! SRS_PATH=kzg_bn256_22.srs DEGREE=18..=22 cargo bench
+//! ```
+
+use std::{fs::File, io, path::Path};
+
+use halo2_proofs::{
+    halo2curves::{
+        bn256::{Bn256, G1Affine, G2Affine},
+        serde::SerdeObject,
+    },
+    poly::kzg::commitment::ParamsKZG,
+};
+use memmap2::Mmap;
+use rand::rngs::OsRng;
+
+/// Bytes of a point of G1 in both formats, its affine coordinates in
+/// Montgomery form, in little-endian.
+const G1_BYTES: usize = 64;
+
+/// Bytes of a point of G2 in both formats, as those of G1.
+const G2_BYTES: usize = 128;
+
+/// Powers of tau of a degree, `[tau^i] G1` for every `i` below `2^degree`,
+/// along with `G2` and `[tau] G2`.
+struct Powers {
+    g: Vec<G1Affine>,
+    /// The Lagrange basis of `g`, if the file holds it at the degree.
+    g_lagrange: Option<Vec<G1Affine>>,
+    g2: G2Affine,
+    s_g2: G2Affine,
+}
+
+/// Reads the parameters of `degree` from the SRS at `path`, a `.ptau` file
+/// or halo2 parameters of any other extension, of at least `degree`.
+pub fn read_srs(path: &Path, degree: u32) -> io::Result<ParamsKZG<Bn256>> {
+    let file = File::open(path)?;
+    // SAFETY: the file is only read, and is not expected to be modified
+    // while it is read.
+    let bytes = unsafe { Mmap::map(&file)? };
+    let powers = if path.extension() == Some("ptau".as_ref()) {
+        read_ptau(&bytes, degree)?
+    } else {
+        read_halo2(&bytes, degree)?
+    };
+    // `from_parts` builds the parameters from its arguments only, so it is
+    // called on those of the smallest degree.
+    Ok(ParamsKZG::<Bn256>::setup(0, OsRng).from_parts(
+        degree,
+        powers.g,
+        powers.g_lagrange,
+        powers.g2,
+        powers.s_g2,
+    ))
+}
+
+/// Reads the powers of tau of `degree` from halo2 parameters: their degree
+/// `k` as a `u32`, `g` and `g_lagrange`, of `2^k` points each, then `g2`
+/// and `s_g2`.
+fn read_halo2(bytes: &[u8], degree: u32) -> io::Result<Powers> {
+    let k = u32_at(bytes, 0)?;
+    if k < degree {
+        return Err(invalid(format!(
+            "the params of degree {k} are smaller than {degree}"
+        )));
+    }
+    let n = 1usize << degree;
+    let g_lagrange = 4 + (1usize << k) * G1_BYTES;
+    let g2 = g_lagrange + (1usize << k) * G1_BYTES;
+    Ok(Powers {
+        g: g1_points(slice(bytes, 4, n * G1_BYTES)?)?,
+        g_lagrange: (k == degree)
+            .then(|| g1_points(slice(bytes, g_lagrange, n * G1_BYTES)?))
+            .transpose()?,
+        g2: point(slice(bytes, g2, G2_BYTES)?)?,
+        s_g2: point(slice(bytes, g2 + G2_BYTES, G2_BYTES)?)?,
+    })
+}
+
+/// Reads the powers of tau of `degree` from a `.ptau` file: its magic
+/// `ptau`, its version and its number of sections, then the sections, each
+/// with its id, its size as a `u64` and its content. Those read are the
+/// header (1), with the size of the field elements and the degree of the
+/// powers, and the powers of tau in G1 (2) and in G2 (3).
+fn read_ptau(bytes: &[u8], degree: u32) -> io::Result<Powers> {
+    if slice(bytes, 0, 4)? != b"ptau" {
+        return Err(invalid("not a ptau file".to_string()));
+    }
+    let mut sections: [Option<&[u8]>; 4] = [None; 4];
+    let mut offset = 12;
+    for _ in 0..u32_at(bytes, 8)? {
+        let id = u32_at(bytes, offset)? as usize;
+        let size = u64_at(bytes, offset + 4)? as usize;
+        if let Some(section) = sections.get_mut(id) {
+            *section = Some(slice(bytes, offset + 12, size)?);
+        }
+        offset += 12 + size;
+    }
+    let section = |id: usize| {
+        sections[id].ok_or_else(|| invalid(format!("no section {id} in the ptau file")))
+    };
+
+    let header = section(1)?;
+    if u32_at(header, 0)? != 32 {
+        return Err(invalid("the ptau file is not over BN254".to_string()));
+    }
+    let power = u32_at(header, 4 + 32)?;
+    if power < degree {
+        return Err(invalid(format!(
+            "the powers of tau of degree {power} are smaller than {degree}"
+        )));
+    }
+    let tau_g2 = section(3)?;
+    Ok(Powers {
+        g: g1_points(slice(section(2)?, 0, (1usize << degree) * G1_BYTES)?)?,
+        g_lagrange: None,
+        g2: point(slice(tau_g2, 0, G2_BYTES)?)?,
+        s_g2: point(slice(tau_g2, G2_BYTES, G2_BYTES)?)?,
+    })
+}
+
+fn invalid(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+/// The `len` bytes of `bytes` from `start`, if the file is not truncated.
+fn slice(bytes: &[u8], start: usize, len: usize) -> io::Result<&[u8]> {
+    bytes
+        .get(start..start + len)
+        .ok_or_else(|| invalid("truncated SRS file".to_string()))
+}
+
+fn u32_at(bytes: &[u8], offset: usize) -> io::Result<u32> {
+    let bytes = slice(bytes, offset, 4)?;
+    Ok(u32::from_le_bytes(bytes.try_into().expect("4 bytes")))
+}
+
+fn u64_at(bytes: &[u8], offset: usize) -> io::Result<u64> {
+    let bytes = slice(bytes, offset, 8)?;
+    Ok(u64::from_le_bytes(bytes.try_into().expect("8 bytes")))
+}
+
+/// The point encoded by `bytes`, checked to be on its curve.
+fn point<T: SerdeObject>(bytes: &[u8]) -> io::Result<T> {
+    T::from_raw_bytes(bytes).ok_or_else(|| invalid("invalid point in the SRS file".to_string()))
+}
+
+fn g1_points(bytes: &[u8]) -> io::Result<Vec<G1Affine>> {
+    bytes.chunks(G1_BYTES).map(point).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use halo2_proofs::poly::commitment::Params;
+
+    use super::*;
+
+    #[test]
+    fn test_read_srs() {
+        let mut srs = vec![];
+        ParamsKZG::<Bn256>::setup(4, OsRng).write(&mut srs).unwrap();
+
+        // a ptau file of the powers of the params, with only those read.
+        let g = &srs[4..4 + 16 * G1_BYTES];
+        let g2 = &srs[srs.len() - 2 * G2_BYTES..];
+        let mut header = 32u32.to_le_bytes().to_vec();
+        header.extend([0; 32]);
+        header.extend(4u32.to_le_bytes());
+        header.extend(4u32.to_le_bytes());
+        let mut ptau = b"ptau".to_vec();
+        ptau.extend(1u32.to_le_bytes());
+        ptau.extend(3u32.to_le_bytes());
+        for (id, section) in [header.as_slice(), g, g2].into_iter().enumerate() {
+            ptau.extend((id as u32 + 1).to_le_bytes());
+            ptau.extend((section.len() as u64).to_le_bytes());
+            ptau.extend(section);
+        }
+
+        let dir = std::env::temp_dir().join(format!("prover_srs_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        for (name, bytes) in [("kzg_bn256_4.srs", &srs), ("kzg_bn256_4.ptau", &ptau)] {
+            let path = dir.join(name);
+            fs::write(&path, bytes).unwrap();
+            let mut read = vec![];
+            read_srs(&path, 4).unwrap().write(&mut read).unwrap();
+            assert_eq!(read, srs, "{name}");
+            assert_eq!(read_srs(&path, 3).unwrap().k(), 3);
+            assert!(read_srs(&path, 5).is_err());
+        }
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
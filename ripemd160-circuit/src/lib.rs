@@ -1,162 +1,809 @@
-#![allow(dead_code)]
-#![allow(unused_variables)]
-#![allow(unreachable_code)]
+//! The RIPEMD-160 circuit verifies calls to the [`RIPEMD-160`] hash function,
+//! the precompiled contract at address `0x03`.
+//!
+//! The compression function runs two lines of 80 rounds each over 32-bit
+//! little-endian words, which [`MdChip`] assigns on a [`WordOpsChip`] along
+//! with the chaining of the blocks: a round of a line evaluates its boolean
+//! function, adds the message word and the round constant, rotates the sum
+//! left, adds it to `e` and rotates `c` left by 10 bits, a row each. The
+//! feed-forward combines the working variables of both lines with the
+//! chaining value, as in [`native::feed_forward`]. The chip decomposes the
+//! words of every block into their little-endian bytes, which the slots of a
+//! [`CallSlotsConfig`] constrain to the padding of [`native::pad`].
+//!
+//! The circuit exposes the calls through `Ripemd160Table`, a [`HashTable`]
+//! with one row per call of the RLC and length of its input and of its
+//! digest, in the layout of the `KeccakTable`.
+//!
+//! [`RIPEMD-160`]: https://homes.esat.kuleuven.be/~bosselae/ripemd160.html
+//! [`MdChip`]: gadgets::MdChip
+//! [`WordOpsChip`]: gadgets::WordOpsChip
+//! [`CallSlotsConfig`]: gadgets::CallSlotsConfig
+//! [`HashTable`]: gadgets::HashTable
 
-use std::marker::PhantomData;
+pub mod native;
 
+use std::{collections::BTreeSet, marker::PhantomData};
+
+use ethers_core::types::H160;
+use gadgets::{
+    batch::{self, BatchCircuit, BatchWitness},
+    capacity::{check_capacity, padded, Bounded, UNBOUNDED},
+    context::{assign_context, Context},
+    public_inputs::PublicInputs,
+    tables::FixedTables,
+    util::mask,
+    AssignedBytes, AssignedCall, BitFn, BitShift, CallSlotsConfig, Endianness, HashCircuitExt,
+    HashTable, HashTableConfig, MdChip, MdCompression, MdConfig, MdPadding, SlotPadding, WordCell,
+    WordOpsChip,
+};
 use halo2_proofs::{
     arithmetic::FieldExt,
-    circuit::Layouter,
-    plonk::{Advice, Any, Column, ConstraintSystem, Error},
+    circuit::{Layouter, SimpleFloorPlanner},
+    plonk::{Any, Challenge, Circuit, Column, ConstraintSystem, Error, FirstPhase, ProvingKey},
+    poly::commitment::{CommitmentScheme, Prover},
+    transcript::{EncodedChallenge, TranscriptWrite},
 };
+use rand_core::RngCore;
+use tracing::{debug_span, info_span};
 
-#[derive(Clone, Debug)]
-pub struct Ripemd160Table {
-    id: Column<Advice>,
-}
+pub type Ripemd160Table = HashTable;
 
-impl Ripemd160Table {
-    pub fn construct<F: FieldExt>(meta: &mut ConstraintSystem<F>) -> Self {
-        Self {
-            id: meta.advice_column(),
+/// The RIPEMD-160 compression function, whose blocks are chained by
+/// [`MdChip`].
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Ripemd160Compression;
+
+impl Ripemd160Compression {
+    /// The boolean function of the `j`-th group of 16 rounds, as
+    /// [`native::f`].
+    fn f(j: usize) -> BitFn {
+        match j {
+            0 => BitFn::XOR3,
+            1 => BitFn::CH,
+            2 => BitFn::from_fn(3, |x, y, z| (x | !y) ^ z),
+            // `(x & z) | (y & !z)`, that is `Ch(z, x, y)`.
+            3 => BitFn::CH,
+            _ => BitFn::from_fn(3, |x, y, z| x ^ (y | !z)),
         }
     }
 
-    pub fn columns(&self) -> Vec<Column<Any>> {
-        vec![self.id.into()]
+    /// Assigns a round of a line, in five rows, updating its working
+    /// variables.
+    fn assign_round<F: FieldExt>(
+        chip: &WordOpsChip<F, 32>,
+        ctx: &mut Context<'_, '_, F>,
+        line: &mut [WordCell<F>; 5],
+        j: usize,
+        word: &WordCell<F>,
+        constant: u32,
+        rotation: u32,
+    ) -> Result<(), Error> {
+        let [a, b, c, d, e] = line.clone();
+        let f = match j {
+            3 => chip.apply(ctx, Self::f(j), &[&d, &b, &c])?,
+            _ => chip.apply(ctx, Self::f(j), &[&b, &c, &d])?,
+        };
+        let sum = chip.add(ctx, &[&a, &f, word], constant as u64)?;
+        let rotated = chip.shift(ctx, &[BitShift::RotateLeft(rotation as usize)], &sum)?;
+        let t = chip.add(ctx, &[&rotated, &e], 0)?;
+        let c = chip.shift(ctx, &[BitShift::RotateLeft(10)], &c)?;
+        *line = [e, t, b, c, d];
+        Ok(())
     }
+}
+
+impl<F: FieldExt> MdCompression<F, 32> for Ripemd160Compression {
+    const STATE_WORDS: usize = 5;
+    const BLOCK_BYTES: usize = native::BLOCK_BYTES;
+    const ENDIANNESS: Endianness = Endianness::Little;
 
-    pub fn annotations(&self) -> Vec<String> {
-        vec![String::from("id")]
+    fn shifts() -> Vec<Vec<BitShift>> {
+        native::LEFT_ROTATIONS
+            .iter()
+            .chain(native::RIGHT_ROTATIONS.iter())
+            .chain([10].iter())
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .map(|n| vec![BitShift::RotateLeft(*n as usize)])
+            .collect()
+    }
+
+    fn fns() -> Vec<BitFn> {
+        // The function of the fourth group is `Ch` of other operands.
+        vec![Self::f(0), Self::f(1), Self::f(2), Self::f(4)]
+    }
+
+    fn iv(&self) -> Vec<u64> {
+        native::IV.iter().map(|word| *word as u64).collect()
+    }
+
+    fn pad(&self, input: &[u8]) -> Vec<u8> {
+        native::pad(input)
+    }
+
+    fn compress_rounds(&self, state: &[u64], block: &[u8]) -> Vec<u64> {
+        let state = core::array::from_fn(|i| state[i] as u32);
+        native::compress_rounds(&state, block)
+            .iter()
+            .map(|word| *word as u64)
+            .collect()
+    }
+
+    fn feed_forward(&self, state: &[u64], working: &[u64]) -> Vec<u64> {
+        native::FEED_FORWARD
+            .iter()
+            .map(|[h, l, r]| (state[*h] + working[*l] + working[*r]) & mask(32))
+            .collect()
+    }
+
+    /// Five rows per round of each line.
+    fn rounds_rows(&self) -> usize {
+        2 * 5 * native::NUM_ROUNDS
+    }
+
+    fn assign_rounds(
+        &self,
+        chip: &WordOpsChip<F, 32>,
+        ctx: &mut Context<'_, '_, F>,
+        state: &[WordCell<F>],
+        block: &[WordCell<F>],
+    ) -> Result<Vec<WordCell<F>>, Error> {
+        let state: [WordCell<F>; 5] = state.to_vec().try_into().expect("the state has 5 words");
+        let (mut left, mut right) = (state.clone(), state);
+        for i in 0..native::NUM_ROUNDS {
+            let j = i / 16;
+            Self::assign_round(
+                chip,
+                ctx,
+                &mut left,
+                j,
+                &block[native::LEFT_WORDS[i]],
+                native::LEFT_CONSTANTS[j],
+                native::LEFT_ROTATIONS[i],
+            )?;
+            Self::assign_round(
+                chip,
+                ctx,
+                &mut right,
+                4 - j,
+                &block[native::RIGHT_WORDS[i]],
+                native::RIGHT_CONSTANTS[j],
+                native::RIGHT_ROTATIONS[i],
+            )?;
+        }
+        Ok(left.into_iter().chain(right).collect())
+    }
+
+    fn assign_feed_forward(
+        &self,
+        chip: &WordOpsChip<F, 32>,
+        ctx: &mut Context<'_, '_, F>,
+        state: &[WordCell<F>],
+        working: &[WordCell<F>],
+    ) -> Result<Vec<WordCell<F>>, Error> {
+        native::FEED_FORWARD
+            .iter()
+            .map(|[h, l, r]| chip.add(ctx, &[&state[*h], &working[*l], &working[*r]], 0))
+            .collect()
     }
 }
 
+/// The config of the circuit, of at most `MAX_CALLS` calls of
+/// `MAX_INPUT_BYTES` input bytes in total, or sized at run time by default,
+/// see [`gadgets::capacity`].
 #[derive(Clone, Debug)]
-pub struct Ripemd160Config<F> {
-    table: Ripemd160Table,
+pub struct Ripemd160Config<
+    F,
+    const MAX_CALLS: usize = UNBOUNDED,
+    const MAX_INPUT_BYTES: usize = UNBOUNDED,
+> {
+    table: HashTableConfig,
+    md: MdConfig<32>,
+    slots: CallSlotsConfig,
     _marker: PhantomData<F>,
 }
 
-impl<F: FieldExt> Ripemd160Config<F> {
+impl<F: FieldExt, const MAX_CALLS: usize, const MAX_INPUT_BYTES: usize>
+    Ripemd160Config<F, MAX_CALLS, MAX_INPUT_BYTES>
+{
+    /// Fails the build of a config of no calls.
+    const HAS_CAPACITY: () = assert!(MAX_CALLS > 0, "a circuit of no calls");
+
+    /// The padding of the blocks: a `0x80` byte, zeros and the
+    /// little-endian length of the input in bits, in 8 bytes.
+    const PADDING: MdPadding = MdPadding {
+        len_bytes: 8,
+        endianness: Endianness::Little,
+    };
+
+    /// Configures the circuit over `table`, constructed with
+    /// `Ripemd160Table::construct(meta, native::DIGEST_BYTES)`, and a
+    /// challenge of its own for the RLCs of the table.
     pub fn configure(meta: &mut ConstraintSystem<F>, table: Ripemd160Table) -> Self {
+        let () = Self::HAS_CAPACITY;
+        let _span = info_span!("configure", circuit = "ripemd160").entered();
+        let challenge = meta.challenge_usable_after(FirstPhase);
+        let table = HashTableConfig::configure(meta, table, challenge);
+        let md = MdConfig::configure::<F, Ripemd160Compression>(meta);
+        let slots = CallSlotsConfig::configure(
+            meta,
+            &table,
+            native::BLOCK_BYTES,
+            native::DIGEST_BYTES,
+            SlotPadding::Md(Self::PADDING),
+        );
         Self {
             table,
+            md,
+            slots,
             _marker: PhantomData,
         }
     }
+
+    /// The table of the calls.
+    pub fn table(&self) -> &Ripemd160Table {
+        &self.table.table
+    }
+
+    /// The challenge of the RLCs of [`Self::table`].
+    pub fn challenge(&self) -> Challenge {
+        self.table.challenge
+    }
+
+    /// Exposes [`Self::table`] and the input bytes of its calls in two
+    /// instance columns, see [`HashTableConfig::expose`].
+    pub fn expose(&mut self, meta: &mut ConstraintSystem<F>) {
+        self.table.expose(meta);
+    }
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Default)]
 pub struct Ripemd160Witness<F> {
     pub inputs: Vec<Vec<u8>>,
     pub _marker: PhantomData<F>,
 }
 
 #[derive(Clone, Debug)]
-pub struct Ripemd160Chip<F> {
-    config: Ripemd160Config<F>,
+pub struct Ripemd160Chip<
+    F,
+    const MAX_CALLS: usize = UNBOUNDED,
+    const MAX_INPUT_BYTES: usize = UNBOUNDED,
+> {
+    config: Ripemd160Config<F, MAX_CALLS, MAX_INPUT_BYTES>,
     data: Ripemd160Witness<F>,
 }
 
-impl<F: FieldExt> Ripemd160Chip<F> {
-    pub fn construct(config: Ripemd160Config<F>, data: Ripemd160Witness<F>) -> Self {
+impl<F: FieldExt, const MAX_CALLS: usize, const MAX_INPUT_BYTES: usize>
+    Ripemd160Chip<F, MAX_CALLS, MAX_INPUT_BYTES>
+{
+    pub fn construct(
+        config: Ripemd160Config<F, MAX_CALLS, MAX_INPUT_BYTES>,
+        data: Ripemd160Witness<F>,
+    ) -> Self {
         Self { config, data }
     }
 
+    /// Loads the calls of the witness, failing if they exceed the capacity
+    /// of the config.
     pub fn load(&self, layouter: &mut impl Layouter<F>) -> Result<(), Error> {
-        Ok(())
+        self.assign_calls(layouter).map(|_| ())
+    }
+
+    /// Loads the calls of the witness as [`Self::load`] does, and returns the
+    /// cells of their inputs and digests, for a composing chip to constrain
+    /// to its own.
+    pub fn assign_calls(
+        &self,
+        layouter: &mut impl Layouter<F>,
+    ) -> Result<Vec<AssignedCall<F>>, Error> {
+        let inputs = &self.data.inputs;
+        let bytes: usize = inputs.iter().map(Vec::len).sum();
+        let _span =
+            info_span!("load", circuit = "ripemd160", calls = inputs.len(), bytes).entered();
+        Self::check_witness(&self.data)?;
+        let lens: Vec<_> = inputs.iter().map(Vec::len).collect();
+        let slots = &self.config.slots;
+        // A bounded capacity pads the layout to it, whatever the calls.
+        let blocks = lens.iter().map(|len| slots.blocks(*len)).sum();
+        let max_slots = slots.max_slots(MAX_CALLS, MAX_INPUT_BYTES);
+        let plan = slots.plan(&lens, padded(blocks, max_slots));
+        let (calls, cells) = assign_context(layouter, "ripemd160 blocks", |ctx| {
+            self.md_chip().assign_slots(ctx, inputs, &plan)
+        })?;
+        slots.assign(
+            layouter,
+            "ripemd160",
+            &self.config.table,
+            &lens,
+            &plan,
+            &cells,
+        )?;
+        let table_calls: Vec<_> = inputs
+            .iter()
+            .map(|input| (input.clone(), native::ripemd160(input).to_vec()))
+            .collect();
+        self.config.table.assign(
+            layouter,
+            "ripemd160",
+            padded(inputs.len(), MAX_CALLS),
+            &table_calls,
+        )?;
+        Ok(calls)
+    }
+
+    /// Loads the calls of the witness, the inputs of the first ones being
+    /// bytes assigned by another chip, e.g. the SHA2-256 digests of HASH160,
+    /// which the inputs of the calls are constrained to rather than
+    /// witnessed again. Returns the digests of the calls.
+    pub fn load_assigned_inputs(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        inputs: &[AssignedBytes<F>],
+    ) -> Result<Vec<AssignedBytes<F>>, Error> {
+        let _span = debug_span!("ripemd160 assigned inputs", calls = inputs.len()).entered();
+        assert!(
+            inputs.len() <= self.data.inputs.len(),
+            "the assigned inputs are calls of the witness"
+        );
+        let calls = self.assign_calls(layouter)?;
+        layouter.assign_region(
+            || "ripemd160 assigned inputs",
+            |mut region| {
+                for (input, call) in inputs.iter().zip(&calls) {
+                    input.constrain_equal(&mut region, call.input.cells())?;
+                }
+                Ok(())
+            },
+        )?;
+        Ok(calls.into_iter().map(|call| call.digest).collect())
+    }
+
+    fn md_chip(&self) -> MdChip<F, Ripemd160Compression, 32> {
+        MdChip::construct(self.config.md.clone(), Ripemd160Compression)
     }
 }
 
-#[cfg(any(feature = "test", test))]
-pub mod dev {
-    use super::*;
+impl<F: FieldExt, const MAX_CALLS: usize, const MAX_INPUT_BYTES: usize> HashCircuitExt<F>
+    for Ripemd160Chip<F, MAX_CALLS, MAX_INPUT_BYTES>
+{
+    type Config = Ripemd160Config<F, MAX_CALLS, MAX_INPUT_BYTES>;
+    type Witness = Ripemd160Witness<F>;
 
-    use ethers_core::types::H160;
-    use halo2_proofs::{circuit::SimpleFloorPlanner, plonk::Circuit};
-    use std::str::FromStr;
+    const DIGEST_BYTES: usize = native::DIGEST_BYTES;
 
-    lazy_static::lazy_static! {
-        pub static ref INPUTS_OUTPUTS: (Vec<Vec<u8>>, Vec<H160>) = {
-            [
-                ("", "9c1185a5c5e9fc54612808977ee8f548b2258d31"),
-                ("abc", "8eb208f7e05d987a9b044a8e98c6b087f15a0bfc"),
-                (
-                    "abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq",
-                    "12a053384a9c0c88e405a06c27dcf49ada62eb2b",
-                ),
-                (
-                    "abcdefghijklmnopqrstuvwxyz",
-                    "f71c27109c692c1b56bbdceb5b9d2865b3708dbc",
-                ),
-            ]
-            .iter()
-            .map(|(input, output)| {
-                (
-                    input.as_bytes().to_vec(),
-                    H160::from_str(output).expect("ripemd-160 hash is 20-bytes"),
-                )
-            })
-            .unzip()
-        };
+    /// Configures the circuit over its table, exposed in its instance
+    /// column.
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let mut config = Self::configure_with_tables(meta, &mut FixedTables::default());
+        config.expose(meta);
+        config
     }
 
-    #[derive(Default)]
-    pub struct Ripemd160TestCircuit<F> {
-        pub inputs: Vec<Vec<u8>>,
-        pub outputs: Vec<H160>,
-        pub _marker: PhantomData<F>,
+    /// Configures the circuit over its table, not exposed, the circuit
+    /// having no fixed tables.
+    fn configure_with_tables(
+        meta: &mut ConstraintSystem<F>,
+        _tables: &mut FixedTables,
+    ) -> Self::Config {
+        let table = Ripemd160Table::construct(meta, native::DIGEST_BYTES);
+        Ripemd160Config::configure(meta, table)
     }
 
-    impl<F: FieldExt> Circuit<F> for Ripemd160TestCircuit<F> {
-        type Config = Ripemd160Config<F>;
-        type FloorPlanner = SimpleFloorPlanner;
+    /// At most `MAX_CALLS` calls of `MAX_INPUT_BYTES` bytes in total.
+    fn check_witness(witness: &Self::Witness) -> Result<(), gadgets::Error> {
+        let bytes = witness.inputs.iter().map(Vec::len).sum();
+        check_capacity(witness.inputs.len(), bytes, MAX_CALLS, MAX_INPUT_BYTES)
+    }
 
-        fn without_witnesses(&self) -> Self {
-            Self::default()
+    /// The calls of the inputs of `witness`, in their order, padded to the
+    /// capacity as the layout is.
+    fn public_inputs(witness: &Self::Witness) -> Vec<Vec<F>> {
+        let mut public_inputs = PublicInputs::new(
+            native::DIGEST_BYTES,
+            native::BLOCK_BYTES,
+            SlotPadding::Md(Ripemd160Config::<F, MAX_CALLS, MAX_INPUT_BYTES>::PADDING),
+        );
+        for input in &witness.inputs {
+            public_inputs.call(input, &native::ripemd160(input));
         }
+        public_inputs.build_padded(MAX_CALLS, MAX_INPUT_BYTES)
+    }
 
-        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
-            let ripemd160_table = Ripemd160Table::construct(meta);
-            Ripemd160Config::configure(meta, ripemd160_table)
-        }
+    fn construct(config: Self::Config, witness: Self::Witness) -> Self {
+        Ripemd160Chip::construct(config, witness)
+    }
+
+    fn load(&self, layouter: &mut impl Layouter<F>) -> Result<(), Error> {
+        Ripemd160Chip::load(self, layouter)
+    }
+
+    fn table_columns(config: &Self::Config) -> Vec<Column<Any>> {
+        config.table().columns()
+    }
+
+    fn annotations(config: &Self::Config) -> Vec<String> {
+        config.table().annotations()
+    }
 
-        fn synthesize(
-            &self,
-            config: Self::Config,
-            mut layouter: impl Layouter<F>,
-        ) -> Result<(), Error> {
-            let chip = Ripemd160Chip::construct(
-                config,
-                Ripemd160Witness {
-                    inputs: self.inputs.clone(),
-                    _marker: PhantomData,
-                },
-            );
-            chip.load(&mut layouter)
+    fn assigned_digest(
+        &self,
+        layouter: &mut impl Layouter<F>,
+    ) -> Result<Vec<AssignedBytes<F>>, Error> {
+        let calls = self.assign_calls(layouter)?;
+        Ok(calls.into_iter().map(|call| call.digest).collect())
+    }
+}
+
+impl<F> BatchWitness for Ripemd160Witness<F> {
+    fn merge(witnesses: Vec<Self>) -> Self {
+        Self {
+            inputs: witnesses
+                .into_iter()
+                .flat_map(|witness| witness.inputs)
+                .collect(),
+            _marker: PhantomData,
         }
     }
 }
 
+/// The circuit of the calls of many witness sets, see [`gadgets::batch`],
+/// whose keys are generated from its default, as its layout is that of its
+/// capacity, which is bounded.
+pub type Ripemd160BatchCircuit<F, const MAX_CALLS: usize, const MAX_INPUT_BYTES: usize> =
+    BatchCircuit<F, Ripemd160Chip<F, MAX_CALLS, MAX_INPUT_BYTES>>;
+
+/// Proves the calls of all of `witnesses` with a single proof of a
+/// [`Ripemd160BatchCircuit`], or fails with an [`gadgets::Error::Capacity`] if they
+/// exceed `MAX_CALLS` calls of `MAX_INPUT_BYTES` bytes, see
+/// [`batch::prove_batch`]. The build fails if the capacity is unbounded.
+pub fn prove_batch<
+    'params,
+    Scheme,
+    P,
+    E,
+    R,
+    T,
+    const MAX_CALLS: usize,
+    const MAX_INPUT_BYTES: usize,
+>(
+    params: &'params Scheme::ParamsProver,
+    pk: &ProvingKey<Scheme::Curve>,
+    witnesses: Vec<Ripemd160Witness<Scheme::Scalar>>,
+    rng: R,
+    transcript: &mut T,
+) -> Result<(), gadgets::Error>
+where
+    Scheme: CommitmentScheme,
+    P: Prover<'params, Scheme>,
+    E: EncodedChallenge<Scheme::Curve>,
+    R: RngCore,
+    T: TranscriptWrite<Scheme::Curve, E>,
+{
+    let () = Bounded::<MAX_CALLS, MAX_INPUT_BYTES>::CHECK;
+    batch::prove_batch::<
+        Scheme,
+        P,
+        E,
+        R,
+        T,
+        Ripemd160Chip<Scheme::Scalar, MAX_CALLS, MAX_INPUT_BYTES>,
+    >(params, pk, witnesses, rng, transcript)
+}
+
+/// The circuit of the calls of RIPEMD-160 proven on their own, whose digests
+/// are checked against `outputs`, without exposing its table, e.g. by the
+/// benchmarks.
+#[derive(Default)]
+pub struct Ripemd160TestCircuit<F> {
+    pub inputs: Vec<Vec<u8>>,
+    pub outputs: Vec<H160>,
+    pub _marker: PhantomData<F>,
+}
+
+impl<F: FieldExt> Ripemd160TestCircuit<F> {
+    /// Smallest `k` that fits the calls of the circuit, see
+    /// [`HashCircuitExt::min_k`].
+    pub fn min_k(&self) -> u32 {
+        Ripemd160Chip::<F>::min_k(&Ripemd160Witness {
+            inputs: self.inputs.clone(),
+            _marker: PhantomData,
+        })
+    }
+
+    /// Checks the witness of the circuit before it is assigned: that it
+    /// fits the capacity of the chip, and that the digests of the calls
+    /// are those of [`native::ripemd160`], see [`gadgets::validate`].
+    pub fn check_witness(&self) -> Result<(), gadgets::Error> {
+        Ripemd160Chip::<F>::check_witness(&Ripemd160Witness {
+            inputs: self.inputs.clone(),
+            _marker: PhantomData,
+        })?;
+        gadgets::validate::check_digests("RIPEMD-160", &self.inputs, &self.outputs, |input| {
+            native::ripemd160(input)
+        })
+    }
+}
+
+impl<F: FieldExt> Circuit<F> for Ripemd160TestCircuit<F> {
+    type Config = Ripemd160Config<F>;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let ripemd160_table = Ripemd160Table::construct(meta, native::DIGEST_BYTES);
+        Ripemd160Config::configure(meta, ripemd160_table)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        self.check_witness()?;
+        let chip = Ripemd160Chip::construct(
+            config,
+            Ripemd160Witness {
+                inputs: self.inputs.clone(),
+                _marker: PhantomData,
+            },
+        );
+        chip.load(&mut layouter)
+    }
+}
+
+/// The keys of [`Ripemd160BatchCircuit`], prefixed with the version of its
+/// constraint system, see [`gadgets::keys`], which is that of any capacity.
+pub mod keys {
+    use super::*;
+
+    use halo2_proofs::{
+        arithmetic::CurveAffine,
+        plonk::{ProvingKey, VerifyingKey},
+        SerdeFormat,
+    };
+    use std::io;
+
+    /// Writes the proving key of [`Ripemd160BatchCircuit`] prefixed with the version of its
+    /// constraint system, see [`gadgets::keys`].
+    pub fn write_pk<C: CurveAffine>(
+        writer: &mut impl io::Write,
+        pk: &ProvingKey<C>,
+        format: SerdeFormat,
+    ) -> io::Result<()>
+    where
+        C::ScalarExt: FieldExt,
+    {
+        gadgets::keys::write_pk::<C, BatchCircuit<C::ScalarExt, Ripemd160Chip<C::ScalarExt>>>(
+            writer, pk, format,
+        )
+    }
+
+    /// Reads a proving key written by [`write_pk`], refusing that of another
+    /// version of [`Ripemd160BatchCircuit`].
+    pub fn read_pk<C: CurveAffine>(
+        reader: &mut impl io::Read,
+        format: SerdeFormat,
+    ) -> io::Result<ProvingKey<C>>
+    where
+        C::ScalarExt: FieldExt,
+    {
+        gadgets::keys::read_pk::<C, BatchCircuit<C::ScalarExt, Ripemd160Chip<C::ScalarExt>>>(
+            reader, format,
+        )
+    }
+
+    /// Writes the verifying key of [`Ripemd160BatchCircuit`] prefixed with the version of
+    /// its constraint system, see [`gadgets::keys`].
+    pub fn write_vk<C: CurveAffine>(
+        writer: &mut impl io::Write,
+        vk: &VerifyingKey<C>,
+        format: SerdeFormat,
+    ) -> io::Result<()>
+    where
+        C::ScalarExt: FieldExt,
+    {
+        gadgets::keys::write_vk::<C, BatchCircuit<C::ScalarExt, Ripemd160Chip<C::ScalarExt>>>(
+            writer, vk, format,
+        )
+    }
+
+    /// Reads a verifying key written by [`write_vk`], refusing that of
+    /// another version of [`Ripemd160BatchCircuit`].
+    pub fn read_vk<C: CurveAffine>(
+        reader: &mut impl io::Read,
+        format: SerdeFormat,
+    ) -> io::Result<VerifyingKey<C>>
+    where
+        C::ScalarExt: FieldExt,
+    {
+        gadgets::keys::read_vk::<C, BatchCircuit<C::ScalarExt, Ripemd160Chip<C::ScalarExt>>>(
+            reader, format,
+        )
+    }
+}
+
+#[cfg(any(feature = "test", test))]
+pub mod dev {
+    use super::*;
+
+    lazy_static::lazy_static! {
+        /// The calls of the fixtures of RIPEMD-160, see [`test_utils`].
+        pub static ref INPUTS_OUTPUTS: (Vec<Vec<u8>>, Vec<H160>) = {
+            let (inputs, outputs) = test_utils::inputs_outputs("ripemd160");
+            (inputs, outputs.iter().map(|output| H160::from_slice(output)).collect())
+        };
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use halo2_proofs::{dev::MockProver, halo2curves::bn256::Fr};
+    use gadgets::{DigestCircuit, HashCircuitExt};
+    use halo2_proofs::{
+        arithmetic::FieldExt,
+        dev::MockProver,
+        halo2curves::{
+            bn256::{Bn256, Fr, G1Affine},
+            pasta::pallas,
+        },
+        plonk::{keygen_pk, keygen_vk, verify_proof, ConstraintSystem},
+        poly::{
+            commitment::ParamsProver,
+            kzg::{
+                commitment::{KZGCommitmentScheme, ParamsKZG},
+                multiopen::{ProverSHPLONK, VerifierSHPLONK},
+                strategy::SingleStrategy,
+            },
+        },
+        transcript::{
+            Blake2bRead, Blake2bWrite, Challenge255, TranscriptReadBuffer, TranscriptWriterBuffer,
+        },
+    };
+    use rand_xorshift::XorShiftRng;
     use std::marker::PhantomData;
 
-    use crate::dev::{Ripemd160TestCircuit, INPUTS_OUTPUTS};
+    use crate::{
+        dev::INPUTS_OUTPUTS, native, prove_batch, Ripemd160BatchCircuit, Ripemd160Chip,
+        Ripemd160TestCircuit, Ripemd160Witness,
+    };
 
     #[test]
-    fn test_ripemd160_circuit() {
+    fn test_ripemd160_native() {
+        let (inputs, outputs) = INPUTS_OUTPUTS.clone();
+        for (input, output) in inputs.iter().zip(outputs) {
+            assert_eq!(native::ripemd160(input), output.0);
+        }
+    }
+
+    fn test_ripemd160_circuit_over<F: FieldExt>() {
         let (inputs, outputs) = INPUTS_OUTPUTS.clone();
 
-        let circuit: Ripemd160TestCircuit<Fr> = Ripemd160TestCircuit {
+        let circuit: Ripemd160TestCircuit<F> = Ripemd160TestCircuit {
             inputs,
             outputs,
             _marker: PhantomData,
         };
 
-        let k = 8;
-        let prover = MockProver::run(k, &circuit, vec![]).unwrap();
+        let prover = MockProver::run(circuit.min_k(), &circuit, vec![]).unwrap();
         assert_eq!(prover.verify(), Ok(()));
     }
+
+    #[test]
+    fn test_ripemd160_hash_circuit_ext() {
+        let mut meta = ConstraintSystem::<Fr>::default();
+        let config = Ripemd160Chip::<Fr>::configure(&mut meta);
+        assert_eq!(
+            Ripemd160Chip::<Fr>::table_columns(&config).len(),
+            Ripemd160Chip::<Fr>::annotations(&config).len()
+        );
+        assert_eq!(Ripemd160Chip::<Fr>::DIGEST_BYTES, 20);
+    }
+
+    #[test]
+    fn test_ripemd160_assigned_digest() {
+        let (inputs, outputs) = INPUTS_OUTPUTS.clone();
+        let witness = Ripemd160Witness::<Fr> {
+            inputs,
+            _marker: PhantomData,
+        };
+        let verify = |digests: Vec<Vec<u8>>| {
+            let circuit = DigestCircuit::<Fr, Ripemd160Chip<Fr>> {
+                witness: witness.clone(),
+                digests,
+                _marker: PhantomData,
+            };
+            let k = Ripemd160Chip::<Fr>::min_k(&witness);
+            let instances = Ripemd160Chip::<Fr>::public_inputs(&witness);
+            MockProver::run(k, &circuit, instances).unwrap().verify()
+        };
+        let mut digests: Vec<_> = outputs.iter().map(|output| output.0.to_vec()).collect();
+        assert_eq!(verify(digests.clone()), Ok(()));
+
+        // a byte of a digest is not the one of the call.
+        digests[0][0] ^= 1;
+        assert!(verify(digests).is_err());
+    }
+
+    #[test]
+    fn test_ripemd160_circuit() {
+        test_ripemd160_circuit_over::<Fr>();
+        test_ripemd160_circuit_over::<pallas::Base>();
+    }
+
+    /// The capacity of the soak test, of a hundred calls of 16 KiB, whose
+    /// layout fits in [`test_utils::SOAK_K`].
+    const SOAK_CALLS: usize = 128;
+    const SOAK_BYTES: usize = 1 << 14;
+
+    #[test]
+    #[ignore = "proves a hundred calls at k = 19, run with --ignored"]
+    fn test_ripemd160_soak() {
+        use rand_core::SeedableRng;
+        use test_utils::{random_inputs, SOAK_K};
+
+        let mut rng = XorShiftRng::from_seed([0x42; 16]);
+        let inputs = random_inputs(&mut rng, SOAK_CALLS, SOAK_BYTES);
+        // the calls of the transactions of a block, a few each.
+        let witnesses: Vec<_> = inputs
+            .chunks(7)
+            .map(|inputs| Ripemd160Witness {
+                inputs: inputs.to_vec(),
+                _marker: PhantomData,
+            })
+            .collect();
+        let circuit = Ripemd160BatchCircuit::<Fr, SOAK_CALLS, SOAK_BYTES>::new(witnesses.clone());
+        // the layout of the capacity, that of the calls and of the keys.
+        assert!(
+            Ripemd160Chip::<Fr, SOAK_CALLS, SOAK_BYTES>::min_k(&Ripemd160Witness::default())
+                <= SOAK_K
+        );
+        let instances =
+            Ripemd160Chip::<Fr, SOAK_CALLS, SOAK_BYTES>::public_inputs(&circuit.witness);
+        MockProver::run(SOAK_K, &circuit, instances.clone())
+            .unwrap()
+            .assert_satisfied();
+
+        let params = ParamsKZG::<Bn256>::setup(SOAK_K, rng.clone());
+        let circuit = Ripemd160BatchCircuit::<Fr, SOAK_CALLS, SOAK_BYTES>::default();
+        let vk = keygen_vk(&params, &circuit).unwrap();
+        let pk = keygen_pk(&params, vk, &circuit).unwrap();
+        let prove = |witnesses| {
+            let mut transcript = Blake2bWrite::<_, G1Affine, Challenge255<_>>::init(vec![]);
+            prove_batch::<
+                KZGCommitmentScheme<Bn256>,
+                ProverSHPLONK<_>,
+                _,
+                _,
+                _,
+                SOAK_CALLS,
+                SOAK_BYTES,
+            >(&params, &pk, witnesses, rng.clone(), &mut transcript)
+            .map(|()| transcript.finalize())
+        };
+
+        let proof = prove(witnesses.clone()).unwrap();
+        let mut transcript = Blake2bRead::<_, G1Affine, Challenge255<_>>::init(&proof[..]);
+        assert!(verify_proof::<_, VerifierSHPLONK<_>, _, _, _>(
+            params.verifier_params(),
+            pk.get_vk(),
+            SingleStrategy::new(&params),
+            &[&[&instances[0], &instances[1]]],
+            &mut transcript,
+        )
+        .is_ok());
+
+        // one call more than the capacity, even of no bytes.
+        let mut witnesses = witnesses;
+        witnesses.push(Ripemd160Witness {
+            inputs: vec![vec![]],
+            _marker: PhantomData,
+        });
+        assert!(matches!(
+            prove(witnesses),
+            Err(gadgets::Error::Capacity { calls, .. }) if calls == SOAK_CALLS + 1
+        ));
+    }
 }
@@ -0,0 +1,166 @@
+//! Native implementation of RIPEMD-160, used to compute the witness of the
+//! circuit and as the reference it is tested against.
+
+/// Size of a message block in bytes.
+pub const BLOCK_BYTES: usize = 64;
+
+/// Size of a digest in bytes.
+pub const DIGEST_BYTES: usize = 20;
+
+/// Number of rounds of each of the two lines of the compression function.
+pub const NUM_ROUNDS: usize = 80;
+
+/// Initial hash value of RIPEMD-160.
+pub const IV: [u32; 5] = [0x67452301, 0xefcdab89, 0x98badcfe, 0x10325476, 0xc3d2e1f0];
+
+/// Round constants of the left line, each used for 16 consecutive rounds.
+pub const LEFT_CONSTANTS: [u32; 5] = [0x00000000, 0x5a827999, 0x6ed9eba1, 0x8f1bbcdc, 0xa953fd4e];
+
+/// Round constants of the right line, each used for 16 consecutive rounds.
+pub const RIGHT_CONSTANTS: [u32; 5] = [0x50a28be6, 0x5c4dd124, 0x6d703ef3, 0x7a6d76e9, 0x00000000];
+
+/// Message word selected by each round of the left line.
+pub const LEFT_WORDS: [usize; NUM_ROUNDS] = [
+    0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, //
+    7, 4, 13, 1, 10, 6, 15, 3, 12, 0, 9, 5, 2, 14, 11, 8, //
+    3, 10, 14, 4, 9, 15, 8, 1, 2, 7, 0, 6, 13, 11, 5, 12, //
+    1, 9, 11, 10, 0, 8, 12, 4, 13, 3, 7, 15, 14, 5, 6, 2, //
+    4, 0, 5, 9, 7, 12, 2, 10, 14, 1, 3, 8, 11, 6, 15, 13,
+];
+
+/// Message word selected by each round of the right line.
+pub const RIGHT_WORDS: [usize; NUM_ROUNDS] = [
+    5, 14, 7, 0, 9, 2, 11, 4, 13, 6, 15, 8, 1, 10, 3, 12, //
+    6, 11, 3, 7, 0, 13, 5, 10, 14, 15, 8, 12, 4, 9, 1, 2, //
+    15, 5, 1, 3, 7, 14, 6, 9, 11, 8, 12, 2, 10, 0, 4, 13, //
+    8, 6, 4, 1, 3, 11, 15, 0, 5, 12, 2, 13, 9, 7, 10, 14, //
+    12, 15, 10, 4, 1, 5, 8, 7, 6, 2, 13, 14, 0, 3, 9, 11,
+];
+
+/// Left rotation of each round of the left line.
+pub const LEFT_ROTATIONS: [u32; NUM_ROUNDS] = [
+    11, 14, 15, 12, 5, 8, 7, 9, 11, 13, 14, 15, 6, 7, 9, 8, //
+    7, 6, 8, 13, 11, 9, 7, 15, 7, 12, 15, 9, 11, 7, 13, 12, //
+    11, 13, 6, 7, 14, 9, 13, 15, 14, 8, 13, 6, 5, 12, 7, 5, //
+    11, 12, 14, 15, 14, 15, 9, 8, 9, 14, 5, 6, 8, 6, 5, 12, //
+    9, 15, 5, 11, 6, 8, 13, 12, 5, 12, 13, 14, 11, 8, 5, 6,
+];
+
+/// Left rotation of each round of the right line.
+pub const RIGHT_ROTATIONS: [u32; NUM_ROUNDS] = [
+    8, 9, 9, 11, 13, 15, 15, 5, 7, 7, 8, 11, 14, 14, 12, 6, //
+    9, 13, 15, 7, 12, 8, 9, 11, 7, 7, 12, 7, 6, 15, 13, 11, //
+    9, 7, 15, 11, 8, 6, 6, 14, 12, 13, 5, 14, 13, 13, 7, 5, //
+    15, 5, 8, 11, 14, 14, 6, 14, 6, 9, 12, 9, 12, 5, 15, 8, //
+    8, 5, 12, 9, 12, 5, 14, 6, 8, 13, 6, 5, 15, 13, 11, 11,
+];
+
+/// Pads `input` to a multiple of [`BLOCK_BYTES`] bytes: a `1` bit, zeros, and
+/// the length of the input in bits as a 64-bit little-endian integer.
+pub fn pad(input: &[u8]) -> Vec<u8> {
+    let mut padded = input.to_vec();
+    padded.push(0x80);
+    padded.resize(
+        (padded.len() + 8).div_ceil(BLOCK_BYTES) * BLOCK_BYTES - 8,
+        0,
+    );
+    padded.extend_from_slice(&((input.len() as u64) * 8).to_le_bytes());
+    padded
+}
+
+/// The boolean function of the `j`-th group of 16 rounds, the right line
+/// using the groups in reverse order.
+pub fn f(j: usize, x: u32, y: u32, z: u32) -> u32 {
+    match j {
+        0 => x ^ y ^ z,
+        1 => (x & y) | (!x & z),
+        2 => (x | !y) ^ z,
+        3 => (x & z) | (y & !z),
+        _ => x ^ (y | !z),
+    }
+}
+
+/// Applies the 80 rounds of both lines of the compression function to
+/// `state`, returning the working variables `a..e` of the left line followed
+/// by those of the right line, before they are combined with `state`.
+pub fn compress_rounds(state: &[u32; 5], block: &[u8]) -> [u32; 10] {
+    assert_eq!(block.len(), BLOCK_BYTES);
+    let mut m = [0u32; 16];
+    for (word, bytes) in m.iter_mut().zip(block.chunks(4)) {
+        *word = u32::from_le_bytes(bytes.try_into().expect("words are 4 bytes"));
+    }
+
+    let [mut al, mut bl, mut cl, mut dl, mut el] = *state;
+    let [mut ar, mut br, mut cr, mut dr, mut er] = *state;
+    for i in 0..NUM_ROUNDS {
+        let j = i / 16;
+        let t = al
+            .wrapping_add(f(j, bl, cl, dl))
+            .wrapping_add(m[LEFT_WORDS[i]])
+            .wrapping_add(LEFT_CONSTANTS[j])
+            .rotate_left(LEFT_ROTATIONS[i])
+            .wrapping_add(el);
+        al = el;
+        el = dl;
+        dl = cl.rotate_left(10);
+        cl = bl;
+        bl = t;
+
+        let t = ar
+            .wrapping_add(f(4 - j, br, cr, dr))
+            .wrapping_add(m[RIGHT_WORDS[i]])
+            .wrapping_add(RIGHT_CONSTANTS[j])
+            .rotate_left(RIGHT_ROTATIONS[i])
+            .wrapping_add(er);
+        ar = er;
+        er = dr;
+        dr = cr.rotate_left(10);
+        cr = br;
+        br = t;
+    }
+    [al, bl, cl, dl, el, ar, br, cr, dr, er]
+}
+
+/// The words of `state` and of the working variables of both lines summed
+/// by the feed-forward, three per word of the next state, in the order of
+/// [`compress_rounds`].
+pub const FEED_FORWARD: [[usize; 3]; 5] = [[1, 2, 8], [2, 3, 9], [3, 4, 5], [4, 0, 6], [0, 1, 7]];
+
+/// Combines the working variables of both lines with `state` into the next
+/// state.
+pub fn feed_forward(state: &[u32; 5], working: &[u32; 10]) -> [u32; 5] {
+    FEED_FORWARD.map(|[h, l, r]| state[h].wrapping_add(working[l]).wrapping_add(working[r]))
+}
+
+/// Compresses `block` into `state`.
+pub fn compress(state: &mut [u32; 5], block: &[u8]) {
+    let working = compress_rounds(state, block);
+    *state = feed_forward(state, &working);
+}
+
+/// Computes the RIPEMD-160 digest of `input`.
+pub fn ripemd160(input: &[u8]) -> [u8; DIGEST_BYTES] {
+    let mut state = IV;
+    for block in pad(input).chunks(BLOCK_BYTES) {
+        compress(&mut state, block);
+    }
+
+    let mut digest = [0u8; DIGEST_BYTES];
+    for (bytes, word) in digest.chunks_mut(4).zip(state) {
+        bytes.copy_from_slice(&word.to_le_bytes());
+    }
+    digest
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pad() {
+        assert_eq!(pad(&[]).len(), BLOCK_BYTES);
+        assert_eq!(pad(&[0; BLOCK_BYTES - 9]).len(), BLOCK_BYTES);
+        assert_eq!(pad(&[0; BLOCK_BYTES - 8]).len(), 2 * BLOCK_BYTES);
+        assert_eq!(pad(b"abc")[BLOCK_BYTES - 8], 24);
+    }
+}
@@ -0,0 +1,461 @@
+//! A chip computing the random linear combinations (RLCs) of byte strings,
+//! shared by the tables of the circuits in this workspace.
+//!
+//! The tables of the circuits encode their variable-length inputs and
+//! outputs, e.g. the message of a hash call, as the RLC of their bytes with a
+//! challenge derived from the witness of the first phase:
+//! `rlc(b_0, ..., b_{n-1}) = b_0 * r^(n-1) + ... + b_{n-2} * r + b_{n-1}`,
+//! the same as `gadgets::util::rlc` computes out-of-circuit.
+//!
+//! `RlcChip` lays the bytes of a sequence of messages out one per row, and
+//! accumulates the RLC along the rows: on the first row of a message
+//! `rlc = byte`, and on the following ones `rlc = rlc_prev * r + byte`. The
+//! RLC of a message is found on its last row, and is returned as a cell to
+//! copy into a table. Since the RLC depends on the challenge, the `rlc` column
+//! is a second-phase column, while the bytes are assigned in the first phase.
+//! `RlcConfig::configure` creates the challenge after the first-phase
+//! columns of the chip, while a circuit sharing a single challenge across
+//! all its RLC columns passes it to `RlcConfig::configure_with_challenge`.
+//!
+//! An empty message is laid out as a single zero byte, whose RLC is zero as
+//! well.
+//!
+//! `RlcChip::assign_cells` accumulates the RLCs of messages already assigned
+//! by another chip, e.g. the input and the digest of a hash call, by copying
+//! their byte cells, and fixes `is_first` from the constants of the circuit
+//! so that the RLC of a message is accumulated from its first byte.
+
+use std::marker::PhantomData;
+
+use constraint_builder::{BaseConstraintBuilder, Expr};
+use gadgets::{tables::RangeTable, util::AssignedValue, AssignedBytes};
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{Layouter, Value},
+    plonk::{
+        Advice, Challenge, Column, ConstraintSystem, Error, FirstPhase, SecondPhase, Selector,
+    },
+    poly::Rotation,
+};
+
+#[derive(Clone, Copy, Debug)]
+pub struct RlcConfig {
+    q_enable: Selector,
+    q_first: Selector,
+    q_next: Selector,
+    /// Whether the row is the first one of a message.
+    pub is_first: Column<Advice>,
+    pub byte: Column<Advice>,
+    pub rlc: Column<Advice>,
+    challenge: Challenge,
+}
+
+impl RlcConfig {
+    pub fn configure<F: FieldExt>(meta: &mut ConstraintSystem<F>, table: RangeTable<8>) -> Self {
+        let is_first = meta.advice_column();
+        let byte = meta.advice_column();
+        let challenge = meta.challenge_usable_after(FirstPhase);
+        Self::configure_columns(meta, table, challenge, is_first, byte)
+    }
+
+    /// Configures the chip with a `challenge` usable after the first phase,
+    /// shared with the other RLC columns of the circuit.
+    pub fn configure_with_challenge<F: FieldExt>(
+        meta: &mut ConstraintSystem<F>,
+        table: RangeTable<8>,
+        challenge: Challenge,
+    ) -> Self {
+        let is_first = meta.advice_column();
+        let byte = meta.advice_column();
+        Self::configure_columns(meta, table, challenge, is_first, byte)
+    }
+
+    fn configure_columns<F: FieldExt>(
+        meta: &mut ConstraintSystem<F>,
+        table: RangeTable<8>,
+        challenge: Challenge,
+        is_first: Column<Advice>,
+        byte: Column<Advice>,
+    ) -> Self {
+        let q_enable = meta.complex_selector();
+        let q_first = meta.selector();
+        let q_next = meta.selector();
+        let rlc = meta.advice_column_in(SecondPhase);
+        for column in [is_first, byte, rlc] {
+            meta.enable_equality(column);
+        }
+
+        meta.create_gate("is_first is boolean", |meta| {
+            let mut cb = BaseConstraintBuilder::default();
+            cb.require_boolean(
+                "is_first is boolean",
+                meta.query_advice(is_first, Rotation::cur()),
+            );
+            cb.gate(meta.query_selector(q_enable))
+        });
+
+        meta.create_gate("the first row starts a message", |meta| {
+            let mut cb = BaseConstraintBuilder::default();
+            cb.require_equal(
+                "is_first == 1",
+                meta.query_advice(is_first, Rotation::cur()),
+                1.expr(),
+            );
+            cb.require_equal(
+                "rlc == byte",
+                meta.query_advice(rlc, Rotation::cur()),
+                meta.query_advice(byte, Rotation::cur()),
+            );
+            cb.gate(meta.query_selector(q_first))
+        });
+
+        meta.create_gate("rlc = is_first ? byte : rlc_prev * r + byte", |meta| {
+            let mut cb = BaseConstraintBuilder::default();
+            let is_first = meta.query_advice(is_first, Rotation::cur());
+            let byte = meta.query_advice(byte, Rotation::cur());
+            let rlc_prev = meta.query_advice(rlc, Rotation::prev());
+            let rlc = meta.query_advice(rlc, Rotation::cur());
+            let r = meta.query_challenge(challenge);
+
+            cb.require_equal(
+                "rlc accumulates the bytes of the message",
+                rlc,
+                (1.expr() - is_first) * rlc_prev * r + byte,
+            );
+            cb.gate(meta.query_selector(q_next))
+        });
+
+        meta.lookup("byte is in range", |meta| {
+            let q_enable = meta.query_selector(q_enable);
+            let byte = meta.query_advice(byte, Rotation::cur());
+            vec![(q_enable * byte, table.value)]
+        });
+
+        Self {
+            q_enable,
+            q_first,
+            q_next,
+            is_first,
+            byte,
+            rlc,
+            challenge,
+        }
+    }
+
+    pub fn challenge(&self) -> Challenge {
+        self.challenge
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct RlcChip<F> {
+    config: RlcConfig,
+    _marker: PhantomData<F>,
+}
+
+impl<F: FieldExt> RlcChip<F> {
+    pub fn construct(config: RlcConfig) -> Self {
+        Self {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Number of rows taken by `messages`.
+    pub fn num_rows(messages: &[Vec<u8>]) -> usize {
+        messages.iter().map(|message| message.len().max(1)).sum()
+    }
+
+    /// Assigns the bytes of `messages` one per row, returning the cells of
+    /// the RLCs of the messages.
+    pub fn assign(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        messages: &[Vec<u8>],
+    ) -> Result<Vec<AssignedValue<F>>, Error> {
+        let challenge = layouter.get_challenge(self.config.challenge);
+
+        layouter.assign_region(
+            || "rlc",
+            |mut region| {
+                let mut offset = 0;
+                let mut cells = Vec::with_capacity(messages.len());
+                for message in messages {
+                    let bytes = if message.is_empty() {
+                        &[0][..]
+                    } else {
+                        message
+                    };
+                    let mut rlc = Value::known(F::zero());
+                    let mut cell = None;
+                    for (i, byte) in bytes.iter().enumerate() {
+                        self.config.q_enable.enable(&mut region, offset)?;
+                        // The first row of the region has no previous row to
+                        // accumulate from.
+                        if offset == 0 {
+                            self.config.q_first.enable(&mut region, offset)?;
+                        } else {
+                            self.config.q_next.enable(&mut region, offset)?;
+                        }
+                        rlc = rlc * challenge + Value::known(F::from(*byte as u64));
+
+                        region.assign_advice(
+                            || "is_first",
+                            self.config.is_first,
+                            offset,
+                            || Value::known(F::from((i == 0) as u64)),
+                        )?;
+                        region.assign_advice(
+                            || "byte",
+                            self.config.byte,
+                            offset,
+                            || Value::known(F::from(*byte as u64)),
+                        )?;
+                        cell = Some(region.assign_advice(
+                            || "rlc",
+                            self.config.rlc,
+                            offset,
+                            || rlc,
+                        )?);
+                        offset += 1;
+                    }
+                    cells.push(cell.expect("a message takes at least one row"));
+                }
+                Ok(cells)
+            },
+        )
+    }
+
+    /// Assigns the RLCs of `messages` as [`Self::assign`] does, copying the
+    /// bytes from their cells, and returns the cells of the RLCs. The
+    /// circuit enables a constants column.
+    pub fn assign_cells(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        messages: &[AssignedBytes<F>],
+    ) -> Result<Vec<AssignedValue<F>>, Error> {
+        let challenge = layouter.get_challenge(self.config.challenge);
+
+        layouter.assign_region(
+            || "rlc of cells",
+            |mut region| {
+                let mut offset = 0;
+                let mut cells = Vec::with_capacity(messages.len());
+                for message in messages {
+                    let mut rlc = Value::known(F::zero());
+                    let mut cell = None;
+                    for i in 0..message.len().max(1) {
+                        self.config.q_enable.enable(&mut region, offset)?;
+                        if offset == 0 {
+                            self.config.q_first.enable(&mut region, offset)?;
+                        } else {
+                            self.config.q_next.enable(&mut region, offset)?;
+                        }
+                        region.assign_advice_from_constant(
+                            || "is_first",
+                            self.config.is_first,
+                            offset,
+                            F::from((i == 0) as u64),
+                        )?;
+                        let byte = match message.cells().get(i) {
+                            Some(cell) => {
+                                cell.copy_advice(|| "byte", &mut region, self.config.byte, offset)?
+                            }
+                            None => region.assign_advice_from_constant(
+                                || "byte",
+                                self.config.byte,
+                                offset,
+                                F::zero(),
+                            )?,
+                        };
+                        rlc = rlc * challenge + byte.value().copied();
+                        cell = Some(region.assign_advice(
+                            || "rlc",
+                            self.config.rlc,
+                            offset,
+                            || rlc,
+                        )?);
+                        offset += 1;
+                    }
+                    cells.push(cell.expect("a message takes at least one row"));
+                }
+                Ok(cells)
+            },
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gadgets::{rows, util::rlc};
+    use halo2_proofs::{
+        circuit::SimpleFloorPlanner,
+        dev::MockProver,
+        halo2curves::{bn256::Fr, pasta::pallas},
+        plonk::Circuit,
+    };
+    use mutation_coverage::mutation_coverage;
+    use proptest::prelude::*;
+
+    /// Checks the RLCs of `messages` against the out-of-circuit RLCs of
+    /// `expected`, with the bytes of the messages assigned by the chip, or
+    /// copied from the cells of another column if `copied`.
+    #[derive(Default)]
+    struct TestCircuit<F> {
+        messages: Vec<Vec<u8>>,
+        expected: Vec<Vec<u8>>,
+        copied: bool,
+        _marker: PhantomData<F>,
+    }
+
+    impl<F: FieldExt> Circuit<F> for TestCircuit<F> {
+        type Config = (RangeTable<8>, RlcConfig, Column<Advice>, Column<Advice>);
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            let table = RangeTable::construct(meta);
+            let config = RlcConfig::configure(meta, table);
+            let bytes = meta.advice_column();
+            let expected = meta.advice_column_in(SecondPhase);
+            meta.enable_equality(bytes);
+            meta.enable_equality(expected);
+            let constants = meta.fixed_column();
+            meta.enable_constant(constants);
+            (table, config, bytes, expected)
+        }
+
+        fn synthesize(
+            &self,
+            (table, config, bytes, expected): Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            table.load(&mut layouter)?;
+            let challenge = layouter.get_challenge(config.challenge());
+            let chip = RlcChip::construct(config);
+            let cells = if self.copied {
+                let messages = layouter.assign_region(
+                    || "message bytes",
+                    |mut region| {
+                        let mut offset = 0;
+                        self.messages
+                            .iter()
+                            .map(|message| {
+                                let cells = message
+                                    .iter()
+                                    .map(|byte| {
+                                        offset += 1;
+                                        region.assign_advice(
+                                            || "byte",
+                                            bytes,
+                                            offset - 1,
+                                            || Value::known(F::from(*byte as u64)),
+                                        )
+                                    })
+                                    .collect::<Result<Vec<_>, _>>()?;
+                                Ok(AssignedBytes::new(cells))
+                            })
+                            .collect::<Result<Vec<_>, Error>>()
+                    },
+                )?;
+                chip.assign_cells(&mut layouter, &messages)?
+            } else {
+                chip.assign(&mut layouter, &self.messages)?
+            };
+
+            layouter.assign_region(
+                || "expected rlc",
+                |mut region| {
+                    for (offset, (message, cell)) in self.expected.iter().zip(&cells).enumerate() {
+                        let expected = region.assign_advice(
+                            || "expected rlc",
+                            expected,
+                            offset,
+                            || rlc(message, challenge),
+                        )?;
+                        region.constrain_equal(expected.cell(), cell.cell())?;
+                    }
+                    Ok(())
+                },
+            )
+        }
+    }
+
+    /// Whether the RLCs of `messages` are those of `expected`, the same with
+    /// the bytes assigned by the chip or copied.
+    fn verify<F: FieldExt>(messages: Vec<Vec<u8>>, expected: Vec<Vec<u8>>) -> bool {
+        let [assigned, copied] = [false, true].map(|copied| {
+            let circuit: TestCircuit<F> = TestCircuit {
+                messages: messages.clone(),
+                expected: expected.clone(),
+                copied,
+                _marker: PhantomData,
+            };
+            let prover = MockProver::run(rows::min_k(&circuit), &circuit, vec![]).unwrap();
+            prover.verify().is_ok()
+        });
+        assert_eq!(assigned, copied);
+        assigned
+    }
+
+    fn test_rlc_circuit_over<F: FieldExt>() {
+        let messages = vec![
+            b"abc".to_vec(),
+            vec![],
+            vec![0xff; 32],
+            b"the quick brown fox".to_vec(),
+        ];
+        assert_eq!(RlcChip::<F>::num_rows(&messages), 55);
+        assert!(verify::<F>(messages.clone(), messages.clone()));
+
+        // the bytes of a message are swapped.
+        let mut expected = messages.clone();
+        expected[0] = b"acb".to_vec();
+        assert!(!verify::<F>(messages.clone(), expected));
+
+        // the messages are concatenated.
+        let expected = vec![[messages[0].clone(), messages[2].clone()].concat()];
+        assert!(!verify::<F>(
+            vec![messages[0].clone(), messages[2].clone()],
+            expected
+        ));
+    }
+
+    #[test]
+    fn test_rlc_circuit() {
+        test_rlc_circuit_over::<Fr>();
+        test_rlc_circuit_over::<pallas::Base>();
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(32))]
+
+        // The RLCs match the out-of-circuit computation on random messages.
+        #[test]
+        fn test_rlc_circuit_random(
+            messages in prop::collection::vec(prop::collection::vec(any::<u8>(), 0..48), 1..6)
+        ) {
+            prop_assert!(verify::<Fr>(messages.clone(), messages.clone()));
+            prop_assert!(verify::<pallas::Base>(messages.clone(), messages));
+        }
+    }
+
+    #[test]
+    fn test_rlc_circuit_coverage() {
+        for copied in [false, true] {
+            let messages = vec![b"abc".to_vec(), vec![]];
+            let circuit: TestCircuit<Fr> = TestCircuit {
+                messages: messages.clone(),
+                expected: messages,
+                copied,
+                _marker: PhantomData,
+            };
+            let report = mutation_coverage(rows::min_k(&circuit), &circuit, vec![]).unwrap();
+            assert_eq!(report.unconstrained().count(), 0, "{}", report.report());
+        }
+    }
+}
@@ -0,0 +1,440 @@
+//! The SHA-1 circuit verifies calls to the [`SHA-1`] hash function. SHA-1 is
+//! broken for collision resistance and is not part of the EVM, but it is still
+//! needed to prove statements about legacy protocols, e.g. DKIM signatures or
+//! the hashes of git objects.
+//!
+//! The compression function runs 80 rounds over 32-bit words, which
+//! [`MdChip`] assigns on a [`WordOpsChip`] along with the chaining of the
+//! blocks: every word of the message schedule past the block is the XOR of
+//! four earlier words rotated left, and a round adds `a` rotated left, the
+//! boolean function of the round, `e`, the round constant and the word of
+//! the schedule, a row each. The chip decomposes the words of every block
+//! into their big-endian bytes, and constrains the padding of the input to
+//! the bytes of [`native::pad`].
+//!
+//! The blocks of the calls are laid out in the slots of a
+//! [`CallSlotsConfig`], which constrains their padding, and the circuit
+//! exposes the calls through `Sha1Table`, a [`HashTable`] with one row per
+//! call of the RLC and length of its input and of its digest.
+//!
+//! [`SHA-1`]: https://en.wikipedia.org/wiki/SHA-1#SHA-1_pseudocode
+//! [`MdChip`]: gadgets::MdChip
+//! [`WordOpsChip`]: gadgets::WordOpsChip
+//! [`CallSlotsConfig`]: gadgets::CallSlotsConfig
+//! [`HashTable`]: gadgets::HashTable
+
+pub mod native;
+
+use std::marker::PhantomData;
+
+use gadgets::{
+    context::{assign_context, Context},
+    public_inputs::PublicInputs,
+    tables::FixedTables,
+    AssignedBytes, AssignedCall, BitFn, BitShift, CallSlotsConfig, Endianness, HashCircuitExt,
+    HashTable, HashTableConfig, MdChip, MdCompression, MdConfig, MdPadding, SlotPadding, WordCell,
+    WordOpsChip,
+};
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::Layouter,
+    plonk::{Any, Challenge, Column, ConstraintSystem, Error, FirstPhase},
+};
+
+pub type Sha1Table = HashTable;
+
+/// The SHA-1 compression function, whose blocks are chained by [`MdChip`].
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Sha1Compression;
+
+impl<F: FieldExt> MdCompression<F, 32> for Sha1Compression {
+    const STATE_WORDS: usize = 5;
+    const BLOCK_BYTES: usize = native::BLOCK_BYTES;
+    const ENDIANNESS: Endianness = Endianness::Big;
+
+    fn shifts() -> Vec<Vec<BitShift>> {
+        [1, 5, 30].map(|n| vec![BitShift::RotateLeft(n)]).to_vec()
+    }
+
+    fn fns() -> Vec<BitFn> {
+        vec![BitFn::CH, BitFn::XOR3, BitFn::MAJ, BitFn::XOR]
+    }
+
+    fn iv(&self) -> Vec<u64> {
+        native::IV.iter().map(|word| *word as u64).collect()
+    }
+
+    fn pad(&self, input: &[u8]) -> Vec<u8> {
+        native::pad(input)
+    }
+
+    fn compress_rounds(&self, state: &[u64], block: &[u8]) -> Vec<u64> {
+        let state = core::array::from_fn(|i| state[i] as u32);
+        native::compress_rounds(&state, block)
+            .iter()
+            .map(|word| *word as u64)
+            .collect()
+    }
+
+    /// Three rows per word of the message schedule past the block, and four
+    /// per round.
+    fn rounds_rows(&self) -> usize {
+        3 * (native::NUM_ROUNDS - 16) + 4 * native::NUM_ROUNDS
+    }
+
+    fn assign_rounds(
+        &self,
+        chip: &WordOpsChip<F, 32>,
+        ctx: &mut Context<'_, '_, F>,
+        state: &[WordCell<F>],
+        block: &[WordCell<F>],
+    ) -> Result<Vec<WordCell<F>>, Error> {
+        let mut w = block.to_vec();
+        for i in 16..native::NUM_ROUNDS {
+            let xor = chip.apply(ctx, BitFn::XOR3, &[&w[i - 3], &w[i - 8], &w[i - 14]])?;
+            let xor = chip.apply(ctx, BitFn::XOR, &[&xor, &w[i - 16]])?;
+            w.push(chip.shift(ctx, &[BitShift::RotateLeft(1)], &xor)?);
+        }
+
+        let [mut a, mut b, mut c, mut d, mut e]: [WordCell<F>; 5] =
+            state.to_vec().try_into().expect("the state has 5 words");
+        for (i, w) in w.iter().enumerate() {
+            let f = match i / 20 {
+                0 => BitFn::CH,
+                2 => BitFn::MAJ,
+                _ => BitFn::XOR3,
+            };
+            let rotated = chip.shift(ctx, &[BitShift::RotateLeft(5)], &a)?;
+            let f = chip.apply(ctx, f, &[&b, &c, &d])?;
+            let temp = chip.add(
+                ctx,
+                &[&rotated, &f, &e, w],
+                native::ROUND_CONSTANTS[i / 20] as u64,
+            )?;
+            e = d;
+            d = c;
+            c = chip.shift(ctx, &[BitShift::RotateLeft(30)], &b)?;
+            b = a;
+            a = temp;
+        }
+        Ok(vec![a, b, c, d, e])
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Sha1Config<F> {
+    table: HashTableConfig,
+    md: MdConfig<32>,
+    slots: CallSlotsConfig,
+    _marker: PhantomData<F>,
+}
+
+impl<F: FieldExt> Sha1Config<F> {
+    /// The padding of the blocks: a `0x80` byte, zeros and the big-endian
+    /// length of the input in bits, in 8 bytes.
+    const PADDING: MdPadding = MdPadding {
+        len_bytes: 8,
+        endianness: Endianness::Big,
+    };
+
+    /// Configures the circuit over `table`, constructed with
+    /// `Sha1Table::construct(meta, native::DIGEST_BYTES)`, and a challenge
+    /// of its own for the RLCs of the table.
+    pub fn configure(meta: &mut ConstraintSystem<F>, table: Sha1Table) -> Self {
+        let challenge = meta.challenge_usable_after(FirstPhase);
+        let table = HashTableConfig::configure(meta, table, challenge);
+        let md = MdConfig::configure::<F, Sha1Compression>(meta);
+        let slots = CallSlotsConfig::configure(
+            meta,
+            &table,
+            native::BLOCK_BYTES,
+            native::DIGEST_BYTES,
+            SlotPadding::Md(Self::PADDING),
+        );
+        Self {
+            table,
+            md,
+            slots,
+            _marker: PhantomData,
+        }
+    }
+
+    /// The table of the calls.
+    pub fn table(&self) -> &Sha1Table {
+        &self.table.table
+    }
+
+    /// The challenge of the RLCs of [`Self::table`].
+    pub fn challenge(&self) -> Challenge {
+        self.table.challenge
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Sha1Witness<F> {
+    pub inputs: Vec<Vec<u8>>,
+    pub _marker: PhantomData<F>,
+}
+
+#[derive(Clone, Debug)]
+pub struct Sha1Chip<F> {
+    config: Sha1Config<F>,
+    data: Sha1Witness<F>,
+}
+
+impl<F: FieldExt> Sha1Chip<F> {
+    pub fn construct(config: Sha1Config<F>, data: Sha1Witness<F>) -> Self {
+        Self { config, data }
+    }
+
+    pub fn load(&self, layouter: &mut impl Layouter<F>) -> Result<(), Error> {
+        self.assign_calls(layouter).map(|_| ())
+    }
+
+    /// Loads the calls of the witness as [`Self::load`] does, and returns the
+    /// cells of their inputs and digests.
+    pub fn assign_calls(
+        &self,
+        layouter: &mut impl Layouter<F>,
+    ) -> Result<Vec<AssignedCall<F>>, Error> {
+        let inputs = &self.data.inputs;
+        let lens: Vec<_> = inputs.iter().map(Vec::len).collect();
+        let slots = &self.config.slots;
+        let blocks = lens.iter().map(|len| slots.blocks(*len)).sum();
+        let plan = slots.plan(&lens, blocks);
+        let (calls, cells) = assign_context(layouter, "sha1 blocks", |ctx| {
+            self.md_chip().assign_slots(ctx, inputs, &plan)
+        })?;
+        slots.assign(layouter, "sha1", &self.config.table, &lens, &plan, &cells)?;
+        let table_calls: Vec<_> = inputs
+            .iter()
+            .map(|input| (input.clone(), native::sha1(input).to_vec()))
+            .collect();
+        self.config
+            .table
+            .assign(layouter, "sha1", inputs.len(), &table_calls)?;
+        Ok(calls)
+    }
+
+    fn md_chip(&self) -> MdChip<F, Sha1Compression, 32> {
+        MdChip::construct(self.config.md.clone(), Sha1Compression)
+    }
+}
+
+impl<F: FieldExt> HashCircuitExt<F> for Sha1Chip<F> {
+    type Config = Sha1Config<F>;
+    type Witness = Sha1Witness<F>;
+
+    const DIGEST_BYTES: usize = native::DIGEST_BYTES;
+
+    /// Configures the circuit over its table, exposed in its instance
+    /// columns.
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let mut config = Self::configure_with_tables(meta, &mut FixedTables::default());
+        config.table.expose(meta);
+        config
+    }
+
+    /// Configures the circuit over its table, not exposed, the circuit
+    /// having no fixed tables.
+    fn configure_with_tables(
+        meta: &mut ConstraintSystem<F>,
+        _tables: &mut FixedTables,
+    ) -> Self::Config {
+        let table = Sha1Table::construct(meta, native::DIGEST_BYTES);
+        Sha1Config::configure(meta, table)
+    }
+
+    /// The calls of the inputs of `witness`, in their order.
+    fn public_inputs(witness: &Self::Witness) -> Vec<Vec<F>> {
+        let mut public_inputs = PublicInputs::new(
+            native::DIGEST_BYTES,
+            native::BLOCK_BYTES,
+            SlotPadding::Md(Sha1Config::<F>::PADDING),
+        );
+        for input in &witness.inputs {
+            public_inputs.call(input, &native::sha1(input));
+        }
+        public_inputs.build()
+    }
+
+    fn construct(config: Self::Config, witness: Self::Witness) -> Self {
+        Sha1Chip::construct(config, witness)
+    }
+
+    fn load(&self, layouter: &mut impl Layouter<F>) -> Result<(), Error> {
+        Sha1Chip::load(self, layouter)
+    }
+
+    fn table_columns(config: &Self::Config) -> Vec<Column<Any>> {
+        config.table().columns()
+    }
+
+    fn annotations(config: &Self::Config) -> Vec<String> {
+        config.table().annotations()
+    }
+
+    fn assigned_digest(
+        &self,
+        layouter: &mut impl Layouter<F>,
+    ) -> Result<Vec<AssignedBytes<F>>, Error> {
+        let calls = self.assign_calls(layouter)?;
+        Ok(calls.into_iter().map(|call| call.digest).collect())
+    }
+}
+
+#[cfg(any(feature = "test", test))]
+pub mod dev {
+    use super::*;
+
+    use ethers_core::{types::H160, utils::hex::FromHex};
+    use halo2_proofs::{circuit::SimpleFloorPlanner, plonk::Circuit};
+    use std::str::FromStr;
+
+    lazy_static::lazy_static! {
+        // The examples of FIPS 180-4, followed by the first vectors of
+        // SHA1ShortMsg.rsp from the NIST CAVP byte-oriented test vectors.
+        pub static ref INPUTS_OUTPUTS: (Vec<Vec<u8>>, Vec<H160>) = {
+        [
+            ("", "da39a3ee5e6b4b0d3255bfef95601890afd80709"),
+            ("616263", "a9993e364706816aba3e25717850c26c9cd0d89d"),
+            (
+                "6162636462636465636465666465666765666768666768696768696a68696a6b696a6b6c6a6b6c6d6b6c6d6e6c6d6e6f6d6e6f706e6f7071",
+                "84983e441c3bd26ebaae4aa1f95129e5e54670f1",
+            ),
+            ("36", "c1dfd96eea8cc2b62785275bca38ac261256e278"),
+            ("195a", "0a1c2d555bbe431ad6288af5a54f93e0449c9232"),
+            ("df4bd2", "bf36ed5d74727dfd5d7854ec6b1d49468d8ee8aa"),
+            ("549e959e", "b78bae6d14338ffccfd5d5b5674a275f6ef9c717"),
+        ]
+            .iter()
+            .map(|(input, output)| {
+                (
+                    Vec::from_hex(input).expect("input is hex-encoded"),
+                    H160::from_str(output).expect("SHA-1 hash is 20-bytes"),
+                )
+            })
+            .unzip()
+        };
+    }
+
+    #[derive(Default)]
+    pub struct Sha1TestCircuit<F> {
+        pub inputs: Vec<Vec<u8>>,
+        pub outputs: Vec<H160>,
+        pub _marker: PhantomData<F>,
+    }
+
+    impl<F: FieldExt> Circuit<F> for Sha1TestCircuit<F> {
+        type Config = Sha1Config<F>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            let sha1_table = Sha1Table::construct(meta, native::DIGEST_BYTES);
+            Sha1Config::configure(meta, sha1_table)
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            let chip = Sha1Chip::construct(
+                config,
+                Sha1Witness {
+                    inputs: self.inputs.clone(),
+                    _marker: PhantomData,
+                },
+            );
+            chip.load(&mut layouter)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use gadgets::{DigestCircuit, HashCircuitExt};
+    use halo2_proofs::{
+        arithmetic::FieldExt,
+        dev::MockProver,
+        halo2curves::{bn256::Fr, pasta::pallas},
+        plonk::ConstraintSystem,
+    };
+    use std::marker::PhantomData;
+
+    use crate::{
+        dev::{Sha1TestCircuit, INPUTS_OUTPUTS},
+        native, Sha1Chip, Sha1Witness,
+    };
+
+    #[test]
+    fn test_sha1_native() {
+        let (inputs, outputs) = INPUTS_OUTPUTS.clone();
+        for (input, output) in inputs.iter().zip(outputs) {
+            assert_eq!(native::sha1(input), output.0);
+        }
+    }
+
+    fn test_sha1_circuit_over<F: FieldExt>() {
+        let (inputs, outputs) = INPUTS_OUTPUTS.clone();
+        let k = Sha1Chip::<F>::min_k(&Sha1Witness {
+            inputs: inputs.clone(),
+            _marker: PhantomData,
+        });
+
+        let circuit: Sha1TestCircuit<F> = Sha1TestCircuit {
+            inputs,
+            outputs,
+            _marker: PhantomData,
+        };
+
+        let prover = MockProver::run(k, &circuit, vec![]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+    }
+
+    #[test]
+    fn test_sha1_assigned_digest() {
+        let (inputs, outputs) = INPUTS_OUTPUTS.clone();
+        let witness = Sha1Witness::<Fr> {
+            inputs,
+            _marker: PhantomData,
+        };
+        let verify = |digests: Vec<Vec<u8>>| {
+            let circuit = DigestCircuit::<Fr, Sha1Chip<Fr>> {
+                witness: witness.clone(),
+                digests,
+                _marker: PhantomData,
+            };
+            let k = Sha1Chip::<Fr>::min_k(&witness);
+            let instances = Sha1Chip::<Fr>::public_inputs(&witness);
+            MockProver::run(k, &circuit, instances).unwrap().verify()
+        };
+        let mut digests: Vec<_> = outputs.iter().map(|output| output.0.to_vec()).collect();
+        assert_eq!(verify(digests.clone()), Ok(()));
+
+        // a byte of a digest is not the one of the call.
+        digests[1][19] ^= 1;
+        assert!(verify(digests).is_err());
+    }
+
+    #[test]
+    fn test_sha1_hash_circuit_ext() {
+        let mut meta = ConstraintSystem::<Fr>::default();
+        let config = Sha1Chip::<Fr>::configure(&mut meta);
+        assert_eq!(
+            Sha1Chip::<Fr>::table_columns(&config).len(),
+            Sha1Chip::<Fr>::annotations(&config).len()
+        );
+        assert_eq!(Sha1Chip::<Fr>::DIGEST_BYTES, 20);
+    }
+
+    #[test]
+    fn test_sha1_circuit() {
+        test_sha1_circuit_over::<Fr>();
+        test_sha1_circuit_over::<pallas::Base>();
+    }
+}
@@ -0,0 +1,104 @@
+//! Native implementation of SHA-1, used to compute the witness of the circuit
+//! and as the reference it is tested against.
+
+/// Size of a message block in bytes.
+pub const BLOCK_BYTES: usize = 64;
+
+/// Size of a digest in bytes.
+pub const DIGEST_BYTES: usize = 20;
+
+/// Number of rounds of the compression function.
+pub const NUM_ROUNDS: usize = 80;
+
+/// Initial hash value of SHA-1.
+pub const IV: [u32; 5] = [0x67452301, 0xefcdab89, 0x98badcfe, 0x10325476, 0xc3d2e1f0];
+
+/// Round constants, each used for 20 consecutive rounds.
+pub const ROUND_CONSTANTS: [u32; 4] = [0x5a827999, 0x6ed9eba1, 0x8f1bbcdc, 0xca62c1d6];
+
+/// Pads `input` to a multiple of [`BLOCK_BYTES`] bytes: a `1` bit, zeros, and
+/// the length of the input in bits as a 64-bit big-endian integer.
+pub fn pad(input: &[u8]) -> Vec<u8> {
+    let mut padded = input.to_vec();
+    padded.push(0x80);
+    padded.resize(
+        (padded.len() + 8).div_ceil(BLOCK_BYTES) * BLOCK_BYTES - 8,
+        0,
+    );
+    padded.extend_from_slice(&((input.len() as u64) * 8).to_be_bytes());
+    padded
+}
+
+/// Expands a block into the 80 words of the message schedule.
+pub fn message_schedule(block: &[u8]) -> [u32; NUM_ROUNDS] {
+    assert_eq!(block.len(), BLOCK_BYTES);
+    let mut w = [0u32; NUM_ROUNDS];
+    for (word, bytes) in w.iter_mut().zip(block.chunks(4)) {
+        *word = u32::from_be_bytes(bytes.try_into().expect("words are 4 bytes"));
+    }
+    for i in 16..NUM_ROUNDS {
+        w[i] = (w[i - 3] ^ w[i - 8] ^ w[i - 14] ^ w[i - 16]).rotate_left(1);
+    }
+    w
+}
+
+/// Applies the 80 rounds of the compression function to `state`, returning
+/// the working variables before they are added back to `state`.
+pub fn compress_rounds(state: &[u32; 5], block: &[u8]) -> [u32; 5] {
+    let w = message_schedule(block);
+    let [mut a, mut b, mut c, mut d, mut e] = *state;
+    for (i, w) in w.iter().enumerate() {
+        let f = match i / 20 {
+            0 => (b & c) | (!b & d),
+            2 => (b & c) | (b & d) | (c & d),
+            _ => b ^ c ^ d,
+        };
+        let temp = a
+            .rotate_left(5)
+            .wrapping_add(f)
+            .wrapping_add(e)
+            .wrapping_add(ROUND_CONSTANTS[i / 20])
+            .wrapping_add(*w);
+        e = d;
+        d = c;
+        c = b.rotate_left(30);
+        b = a;
+        a = temp;
+    }
+    [a, b, c, d, e]
+}
+
+/// Compresses `block` into `state`.
+pub fn compress(state: &mut [u32; 5], block: &[u8]) {
+    let working = compress_rounds(state, block);
+    for (word, working) in state.iter_mut().zip(working) {
+        *word = word.wrapping_add(working);
+    }
+}
+
+/// Computes the SHA-1 digest of `input`.
+pub fn sha1(input: &[u8]) -> [u8; 20] {
+    let mut state = IV;
+    for block in pad(input).chunks(BLOCK_BYTES) {
+        compress(&mut state, block);
+    }
+
+    let mut digest = [0u8; 20];
+    for (bytes, word) in digest.chunks_mut(4).zip(state) {
+        bytes.copy_from_slice(&word.to_be_bytes());
+    }
+    digest
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pad() {
+        assert_eq!(pad(&[]).len(), BLOCK_BYTES);
+        assert_eq!(pad(&[0; BLOCK_BYTES - 9]).len(), BLOCK_BYTES);
+        assert_eq!(pad(&[0; BLOCK_BYTES - 8]).len(), 2 * BLOCK_BYTES);
+        assert_eq!(pad(b"abc")[BLOCK_BYTES - 1], 24);
+    }
+}
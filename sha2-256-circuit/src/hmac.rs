@@ -0,0 +1,887 @@
+//! HMAC-SHA256, composed from calls to the SHA2-256 circuit.
+//!
+//! For a key block `k` (see [`native::hmac_key_block`]), the MAC of a message
+//! `m` is `SHA256((k ^ opad) || SHA256((k ^ ipad) || m))`. The chip lays out
+//! the bytes of every key, flagged by `is_key` so that the key of at most a
+//! block is followed by zeros up to the block, and accumulates its length and
+//! RLC. The key block is the key itself if it is not longer than a block, or
+//! otherwise the digest of a SHA2-256 call whose input is the key, followed
+//! by zeros. The 64 bytes of the key block are laid out along with their XOR
+//! with `ipad` and `opad`, which are looked up from `HmacPadTable`. The inner
+//! and outer SHA2-256 calls are assigned by a [`Sha2Chip`], whose input cells
+//! are constrained to the XORs of the key block, and the input of the outer
+//! call to the digest of the inner one after them.
+//!
+//! The calls are exposed through `HmacTable`:
+//! - `is_enabled`: whether the row holds an HMAC call;
+//! - `key_rlc`: the RLC of the key bytes;
+//! - `key_len`: the number of key bytes;
+//! - `message_rlc`: the RLC of the message bytes;
+//! - `mac_rlc`: the RLC of the 32 MAC bytes.
+//!
+//! `message_rlc` and `mac_rlc` are copied from the RLCs an [`RlcChip`]
+//! accumulates over the message bytes of the inner input and over the digest
+//! of the outer call.
+
+use std::marker::PhantomData;
+
+use constraint_builder::{BaseConstraintBuilder, Expr};
+use gadgets::{
+    tables::RangeTable, util::AssignedValue, AssignedBytes, AssignedCall, HashCircuitExt,
+};
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{Layouter, Region, Value},
+    plonk::{
+        Advice, Any, Challenge, Column, ConstraintSystem, Error, FirstPhase, SecondPhase, Selector,
+        TableColumn,
+    },
+    poly::Rotation,
+};
+use rlc_chip::{RlcChip, RlcConfig};
+use tracing::{debug_span, info_span};
+
+use crate::{native, Sha2Chip, Sha2Config, Sha2Table, Sha2Witness};
+
+/// Table of `(1, byte, byte ^ ipad, byte ^ opad)` for every byte, along with
+/// an all-zero row for disabled lookups, which the tag tells apart.
+#[derive(Clone, Copy, Debug)]
+pub struct HmacPadTable {
+    tag: TableColumn,
+    byte: TableColumn,
+    ipad_byte: TableColumn,
+    opad_byte: TableColumn,
+}
+
+impl HmacPadTable {
+    pub fn construct<F: FieldExt>(meta: &mut ConstraintSystem<F>) -> Self {
+        Self {
+            tag: meta.lookup_table_column(),
+            byte: meta.lookup_table_column(),
+            ipad_byte: meta.lookup_table_column(),
+            opad_byte: meta.lookup_table_column(),
+        }
+    }
+
+    pub fn load<F: FieldExt>(&self, layouter: &mut impl Layouter<F>) -> Result<(), Error> {
+        let _span = info_span!("load table", table = "hmac pad", rows = 257).entered();
+        layouter.assign_table(
+            || "hmac pad table",
+            |mut table| {
+                let rows = std::iter::once((0, 0, 0, 0)).chain(
+                    (0..=u8::MAX).map(|byte| (1, byte, byte ^ native::IPAD, byte ^ native::OPAD)),
+                );
+                for (offset, (tag, byte, ipad_byte, opad_byte)) in rows.enumerate() {
+                    for (annotation, column, value) in [
+                        ("tag", self.tag, tag),
+                        ("byte", self.byte, byte),
+                        ("ipad_byte", self.ipad_byte, ipad_byte),
+                        ("opad_byte", self.opad_byte, opad_byte),
+                    ] {
+                        table.assign_cell(
+                            || annotation,
+                            column,
+                            offset,
+                            || Value::known(F::from(value as u64)),
+                        )?;
+                    }
+                }
+                Ok(())
+            },
+        )
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct HmacTable {
+    is_enabled: Column<Advice>,
+    key_rlc: Column<Advice>,
+    key_len: Column<Advice>,
+    message_rlc: Column<Advice>,
+    mac_rlc: Column<Advice>,
+}
+
+impl HmacTable {
+    pub fn construct<F: FieldExt>(meta: &mut ConstraintSystem<F>) -> Self {
+        let [is_enabled, key_len] = [0; 2].map(|_| meta.advice_column());
+        let [key_rlc, message_rlc, mac_rlc] = [0; 3].map(|_| meta.advice_column_in(SecondPhase));
+        for column in [is_enabled, key_rlc, key_len, message_rlc, mac_rlc] {
+            meta.enable_equality(column);
+        }
+        Self {
+            is_enabled,
+            key_rlc,
+            key_len,
+            message_rlc,
+            mac_rlc,
+        }
+    }
+
+    pub fn columns(&self) -> Vec<Column<Any>> {
+        vec![
+            self.is_enabled.into(),
+            self.key_rlc.into(),
+            self.key_len.into(),
+            self.message_rlc.into(),
+            self.mac_rlc.into(),
+        ]
+    }
+
+    pub fn annotations(&self) -> Vec<String> {
+        vec![
+            String::from("is_enabled"),
+            String::from("key_rlc"),
+            String::from("key_len"),
+            String::from("message_rlc"),
+            String::from("mac_rlc"),
+        ]
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct HmacSha256Config<F> {
+    table: HmacTable,
+    pad_table: HmacPadTable,
+    byte_table: RangeTable<8>,
+    sha2: Sha2Config<F>,
+    rlc: RlcConfig,
+    q_table: Selector,
+    q_key: Selector,   // enabled on every byte of a key.
+    q_first: Selector, // enabled on the first byte of a key.
+    q_step: Selector,  // enabled on all but the first byte of a key.
+    q_long: Selector,  // enabled on every byte of a key longer than a block.
+    q_block: Selector, // enabled on every byte of a key block.
+    q_plain: Selector, // enabled on the key block of a key of at most a block.
+    q_pad: Selector,   // enabled on the bytes of a key block after a key digest.
+    key_byte: Column<Advice>,
+    is_key: Column<Advice>,
+    key_len: Column<Advice>,
+    key_rlc: Column<Advice>,
+    block_byte: Column<Advice>,
+    ipad_byte: Column<Advice>,
+    opad_byte: Column<Advice>,
+    challenge: Challenge,
+}
+
+impl<F: FieldExt> HmacSha256Config<F> {
+    pub fn configure(meta: &mut ConstraintSystem<F>, table: HmacTable) -> Self {
+        let _span = info_span!("configure", circuit = "hmac-sha256").entered();
+        let pad_table = HmacPadTable::construct(meta);
+        let byte_table = RangeTable::construct(meta);
+        let sha2_table = Sha2Table::construct(meta, native::DIGEST_BYTES);
+        let sha2 = Sha2Config::configure(meta, sha2_table);
+
+        let q_table = meta.selector();
+        let q_block = meta.complex_selector();
+        let [q_key, q_first, q_step, q_long, q_plain, q_pad] = [0; 6].map(|_| meta.selector());
+        let is_key = meta.advice_column();
+        let [key_byte, key_len, block_byte, ipad_byte, opad_byte] = [0; 5].map(|_| {
+            let column = meta.advice_column();
+            meta.enable_equality(column);
+            column
+        });
+        let key_rlc = meta.advice_column_in(SecondPhase);
+        meta.enable_equality(key_rlc);
+        let challenge = meta.challenge_usable_after(FirstPhase);
+        let rlc = RlcConfig::configure_with_challenge(meta, byte_table, challenge);
+
+        meta.create_gate("the bytes of the key are flagged", |meta| {
+            let mut cb = BaseConstraintBuilder::default();
+            let is_key = meta.query_advice(is_key, Rotation::cur());
+            cb.require_boolean("is_key is boolean", is_key.clone());
+            cb.condition(1.expr() - is_key, |cb| {
+                cb.require_zero(
+                    "key_byte == 0 if !is_key",
+                    meta.query_advice(key_byte, Rotation::cur()),
+                );
+            });
+            cb.gate(meta.query_selector(q_key))
+        });
+
+        meta.create_gate("key_len and key_rlc start from the first byte", |meta| {
+            let mut cb = BaseConstraintBuilder::default();
+            cb.require_equal(
+                "key_len == is_key",
+                meta.query_advice(key_len, Rotation::cur()),
+                meta.query_advice(is_key, Rotation::cur()),
+            );
+            cb.require_equal(
+                "key_rlc == key_byte",
+                meta.query_advice(key_rlc, Rotation::cur()),
+                meta.query_advice(key_byte, Rotation::cur()),
+            );
+            cb.gate(meta.query_selector(q_first))
+        });
+
+        meta.create_gate("key_len and key_rlc accumulate the key bytes", |meta| {
+            let mut cb = BaseConstraintBuilder::default();
+            let is_key_prev = meta.query_advice(is_key, Rotation::prev());
+            let is_key = meta.query_advice(is_key, Rotation::cur());
+            let key_rlc_prev = meta.query_advice(key_rlc, Rotation::prev());
+            cb.require_zero(
+                "the key bytes precede the padding",
+                is_key.clone() * (1.expr() - is_key_prev),
+            );
+            cb.require_equal(
+                "key_len::cur == key_len::prev + is_key",
+                meta.query_advice(key_len, Rotation::cur()),
+                meta.query_advice(key_len, Rotation::prev()) + is_key.clone(),
+            );
+            cb.require_equal(
+                "key_rlc::cur == is_key ? key_rlc::prev * r + key_byte : key_rlc::prev",
+                meta.query_advice(key_rlc, Rotation::cur()),
+                key_rlc_prev.clone()
+                    + is_key
+                        * (key_rlc_prev.clone() * meta.query_challenge(challenge)
+                            + meta.query_advice(key_byte, Rotation::cur())
+                            - key_rlc_prev),
+            );
+            cb.gate(meta.query_selector(q_step))
+        });
+
+        meta.create_gate("every byte of a long key is hashed", |meta| {
+            let mut cb = BaseConstraintBuilder::default();
+            cb.require_equal(
+                "is_key == 1",
+                meta.query_advice(is_key, Rotation::cur()),
+                1.expr(),
+            );
+            cb.gate(meta.query_selector(q_long))
+        });
+
+        meta.create_gate("a short key is its key block", |meta| {
+            let mut cb = BaseConstraintBuilder::default();
+            cb.require_equal(
+                "block_byte == key_byte",
+                meta.query_advice(block_byte, Rotation::cur()),
+                meta.query_advice(key_byte, Rotation::cur()),
+            );
+            cb.gate(meta.query_selector(q_plain))
+        });
+
+        meta.create_gate("the digest of a long key is zero-padded", |meta| {
+            let mut cb = BaseConstraintBuilder::default();
+            cb.require_zero(
+                "block_byte == 0",
+                meta.query_advice(block_byte, Rotation::cur()),
+            );
+            cb.gate(meta.query_selector(q_pad))
+        });
+
+        meta.lookup(
+            "ipad and opad bytes are the XOR of the block byte",
+            |meta| {
+                let q_block = meta.query_selector(q_block);
+                let [block_byte, ipad_byte, opad_byte] = [block_byte, ipad_byte, opad_byte]
+                    .map(|column| q_block.clone() * meta.query_advice(column, Rotation::cur()));
+                vec![
+                    (q_block, pad_table.tag),
+                    (block_byte, pad_table.byte),
+                    (ipad_byte, pad_table.ipad_byte),
+                    (opad_byte, pad_table.opad_byte),
+                ]
+            },
+        );
+
+        meta.create_gate("validity check over the table rows", |meta| {
+            let mut cb = BaseConstraintBuilder::default();
+            let is_enabled = meta.query_advice(table.is_enabled, Rotation::cur());
+
+            cb.require_boolean("is_enabled is boolean", is_enabled.clone());
+            cb.condition(1.expr() - is_enabled, |cb| {
+                for (name, column) in [
+                    ("key_rlc == 0 if !is_enabled", table.key_rlc),
+                    ("key_len == 0 if !is_enabled", table.key_len),
+                    ("message_rlc == 0 if !is_enabled", table.message_rlc),
+                    ("mac_rlc == 0 if !is_enabled", table.mac_rlc),
+                ] {
+                    cb.require_zero(name, meta.query_advice(column, Rotation::cur()));
+                }
+            });
+
+            cb.gate(meta.query_selector(q_table))
+        });
+
+        Self {
+            table,
+            pad_table,
+            byte_table,
+            sha2,
+            rlc,
+            q_table,
+            q_key,
+            q_first,
+            q_step,
+            q_long,
+            q_block,
+            q_plain,
+            q_pad,
+            key_byte,
+            is_key,
+            key_len,
+            key_rlc,
+            block_byte,
+            ipad_byte,
+            opad_byte,
+            challenge,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct HmacSha256Witness<F> {
+    /// The `(key, message)` pair of every HMAC call.
+    pub inputs: Vec<(Vec<u8>, Vec<u8>)>,
+    pub _marker: PhantomData<F>,
+}
+
+#[derive(Clone, Debug)]
+pub struct HmacSha256Chip<F> {
+    config: HmacSha256Config<F>,
+    data: HmacSha256Witness<F>,
+}
+
+impl<F: FieldExt> HmacSha256Chip<F> {
+    pub fn construct(config: HmacSha256Config<F>, data: HmacSha256Witness<F>) -> Self {
+        Self { config, data }
+    }
+
+    pub fn load(&self, layouter: &mut impl Layouter<F>) -> Result<(), Error> {
+        self.assign_calls(layouter).map(|_| ())
+    }
+
+    /// Loads the calls of the witness as [`Self::load`] does, and returns the
+    /// cells of their MACs.
+    pub fn assign_calls(
+        &self,
+        layouter: &mut impl Layouter<F>,
+    ) -> Result<Vec<AssignedBytes<F>>, Error> {
+        let sha2_inputs = self
+            .data
+            .inputs
+            .iter()
+            .flat_map(|(key, message)| native::hmac_sha256_calls(key, message))
+            .collect();
+        self.assign_sha2_calls(layouter, sha2_inputs)
+    }
+
+    /// Assigns the calls of the witness over the SHA2-256 calls of
+    /// `sha2_inputs`, those of every HMAC call in turn: the one hashing its
+    /// key if longer than a block, then the inner and the outer one.
+    fn assign_sha2_calls(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        sha2_inputs: Vec<Vec<u8>>,
+    ) -> Result<Vec<AssignedBytes<F>>, Error> {
+        let calls = self.data.inputs.len();
+        let _span = info_span!("load", circuit = "hmac-sha256", calls).entered();
+        let config = &self.config;
+        config.pad_table.load(layouter)?;
+        config.byte_table.load(layouter)?;
+
+        let sha2_calls = Sha2Chip::construct(
+            config.sha2.clone(),
+            Sha2Witness {
+                inputs: sha2_inputs,
+                _marker: PhantomData,
+            },
+        )
+        .assign_calls(layouter)?;
+
+        // The SHA2-256 calls of every HMAC call.
+        let mut next = 0;
+        let hmac_calls: Vec<_> = self
+            .data
+            .inputs
+            .iter()
+            .map(|(key, _)| {
+                let num_calls = if key.len() > native::BLOCK_BYTES {
+                    3
+                } else {
+                    2
+                };
+                next += num_calls;
+                &sha2_calls[next - num_calls..next]
+            })
+            .collect();
+
+        let challenge = layouter.get_challenge(config.challenge);
+        let key_cells = self
+            .data
+            .inputs
+            .iter()
+            .zip(&hmac_calls)
+            .enumerate()
+            .map(|(id, ((key, _), sha2_calls))| {
+                self.assign_key(layouter, id, key, sha2_calls, challenge)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        // The message after the key block of the inner input, and the digest
+        // of the outer call.
+        let messages: Vec<_> = hmac_calls
+            .iter()
+            .flat_map(|sha2_calls| {
+                let [inner, outer] = [
+                    &sha2_calls[sha2_calls.len() - 2],
+                    &sha2_calls[sha2_calls.len() - 1],
+                ];
+                let message = &inner.input.cells()[native::BLOCK_BYTES..];
+                [AssignedBytes::new(message.to_vec()), outer.digest.clone()]
+            })
+            .collect();
+        let rlc_cells = RlcChip::<F>::construct(config.rlc).assign_cells(layouter, &messages)?;
+
+        let table = &config.table;
+        // an all-zero row, and a row per call.
+        let _span = debug_span!("hmac table", rows = 1 + calls).entered();
+        layouter.assign_region(
+            || "hmac table",
+            |mut region| {
+                for (annotation, column) in [
+                    ("is_enabled", table.is_enabled),
+                    ("key_rlc", table.key_rlc),
+                    ("key_len", table.key_len),
+                    ("message_rlc", table.message_rlc),
+                    ("mac_rlc", table.mac_rlc),
+                ] {
+                    region.assign_advice_from_constant(|| annotation, column, 0, F::zero())?;
+                }
+                config.q_table.enable(&mut region, 0)?;
+
+                for (i, ([key_rlc, key_len], rlcs)) in
+                    key_cells.iter().zip(rlc_cells.chunks(2)).enumerate()
+                {
+                    let offset = i + 1;
+                    config.q_table.enable(&mut region, offset)?;
+                    region.assign_advice_from_constant(
+                        || "is_enabled",
+                        table.is_enabled,
+                        offset,
+                        F::one(),
+                    )?;
+                    key_rlc.copy_advice(|| "key_rlc", &mut region, table.key_rlc, offset)?;
+                    key_len.copy_advice(|| "key_len", &mut region, table.key_len, offset)?;
+                    rlcs[0].copy_advice(
+                        || "message_rlc",
+                        &mut region,
+                        table.message_rlc,
+                        offset,
+                    )?;
+                    rlcs[1].copy_advice(|| "mac_rlc", &mut region, table.mac_rlc, offset)?;
+                }
+                Ok(())
+            },
+        )?;
+
+        Ok(hmac_calls
+            .iter()
+            .map(|sha2_calls| sha2_calls[sha2_calls.len() - 1].digest.clone())
+            .collect())
+    }
+
+    /// Assigns the key of the `id`-th call and its key block, with the XOR of
+    /// the block by `ipad` and `opad`, over the `sha2_calls` of the HMAC
+    /// call. The key bytes are constrained to the input of the call hashing
+    /// the key if longer than a block, whose digest starts the key block, and
+    /// the XORs to the key blocks of the inputs of the inner and outer calls,
+    /// as the digest of the inner call is to the input of the outer one
+    /// after them. Returns the cells of the RLC and the length of the key.
+    fn assign_key(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        id: usize,
+        key: &[u8],
+        sha2_calls: &[AssignedCall<F>],
+        challenge: Value<F>,
+    ) -> Result<[AssignedValue<F>; 2], Error> {
+        let config = &self.config;
+        let (key_call, inner, outer) = match sha2_calls {
+            [key_call, inner, outer] => (Some(key_call), inner, outer),
+            [inner, outer] => (None, inner, outer),
+            _ => panic!("an HMAC call has two or three SHA2-256 calls"),
+        };
+        let _span = debug_span!("hmac key", id, len = key.len()).entered();
+        layouter.assign_region(
+            || format!("hmac key {}", id),
+            |mut region| {
+                let block = native::hmac_key_block(key);
+                let mut key_rlc = Value::known(F::zero());
+                let mut cells = None;
+                for offset in 0..key.len().max(native::BLOCK_BYTES) {
+                    config.q_key.enable(&mut region, offset)?;
+                    if offset == 0 {
+                        config.q_first.enable(&mut region, offset)?;
+                    } else {
+                        config.q_step.enable(&mut region, offset)?;
+                    }
+                    if key_call.is_some() {
+                        config.q_long.enable(&mut region, offset)?;
+                    }
+
+                    let is_key = offset < key.len();
+                    let byte = key.get(offset).copied().unwrap_or_default();
+                    let key_byte = region.assign_advice(
+                        || "key_byte",
+                        config.key_byte,
+                        offset,
+                        || Value::known(F::from(byte as u64)),
+                    )?;
+                    if let Some(key_call) = key_call {
+                        region.constrain_equal(
+                            key_byte.cell(),
+                            key_call.input.cells()[offset].cell(),
+                        )?;
+                    }
+                    region.assign_advice(
+                        || "is_key",
+                        config.is_key,
+                        offset,
+                        || Value::known(F::from(is_key as u64)),
+                    )?;
+                    let key_len = region.assign_advice(
+                        || "key_len",
+                        config.key_len,
+                        offset,
+                        || Value::known(F::from(key.len().min(offset + 1) as u64)),
+                    )?;
+                    if is_key {
+                        key_rlc = key_rlc * challenge + Value::known(F::from(byte as u64));
+                    }
+                    let key_rlc =
+                        region.assign_advice(|| "key_rlc", config.key_rlc, offset, || key_rlc)?;
+                    cells = Some([key_rlc, key_len]);
+
+                    if let Some(&block_byte) = block.get(offset) {
+                        self.assign_block_byte(
+                            &mut region,
+                            offset,
+                            block_byte,
+                            key_call,
+                            [inner, outer],
+                        )?;
+                    }
+                }
+                inner
+                    .digest
+                    .constrain_equal(&mut region, &outer.input.cells()[native::BLOCK_BYTES..])?;
+                Ok(cells.expect("a key block is not empty"))
+            },
+        )
+    }
+
+    /// Assigns the byte of a key block at `offset` with its XOR by `ipad` and
+    /// `opad`, copied from the digest of the `key_call` if any, and to the
+    /// inputs of the `inner` and `outer` calls.
+    fn assign_block_byte(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        byte: u8,
+        key_call: Option<&AssignedCall<F>>,
+        [inner, outer]: [&AssignedCall<F>; 2],
+    ) -> Result<(), Error> {
+        let config = &self.config;
+        config.q_block.enable(region, offset)?;
+        let block_byte = region.assign_advice(
+            || "block_byte",
+            config.block_byte,
+            offset,
+            || Value::known(F::from(byte as u64)),
+        )?;
+        match key_call.map(|key_call| key_call.digest.cells().get(offset)) {
+            None => config.q_plain.enable(region, offset)?,
+            Some(Some(digest_byte)) => {
+                region.constrain_equal(block_byte.cell(), digest_byte.cell())?
+            }
+            Some(None) => config.q_pad.enable(region, offset)?,
+        }
+        for (annotation, column, value, call) in [
+            ("ipad_byte", config.ipad_byte, byte ^ native::IPAD, inner),
+            ("opad_byte", config.opad_byte, byte ^ native::OPAD, outer),
+        ] {
+            let cell = region.assign_advice(
+                || annotation,
+                column,
+                offset,
+                || Value::known(F::from(value as u64)),
+            )?;
+            region.constrain_equal(cell.cell(), call.input.cells()[offset].cell())?;
+        }
+        Ok(())
+    }
+}
+
+impl<F: FieldExt> HashCircuitExt<F> for HmacSha256Chip<F> {
+    type Config = HmacSha256Config<F>;
+    type Witness = HmacSha256Witness<F>;
+
+    const DIGEST_BYTES: usize = 32;
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let table = HmacTable::construct(meta);
+        HmacSha256Config::configure(meta, table)
+    }
+
+    fn construct(config: Self::Config, witness: Self::Witness) -> Self {
+        HmacSha256Chip::construct(config, witness)
+    }
+
+    fn load(&self, layouter: &mut impl Layouter<F>) -> Result<(), Error> {
+        HmacSha256Chip::load(self, layouter)
+    }
+
+    fn table_columns(config: &Self::Config) -> Vec<Column<Any>> {
+        config.table.columns()
+    }
+
+    fn annotations(config: &Self::Config) -> Vec<String> {
+        config.table.annotations()
+    }
+
+    /// The MACs of the calls.
+    fn assigned_digest(
+        &self,
+        layouter: &mut impl Layouter<F>,
+    ) -> Result<Vec<AssignedBytes<F>>, Error> {
+        self.assign_calls(layouter)
+    }
+}
+
+#[cfg(any(feature = "test", test))]
+pub mod dev {
+    use super::*;
+
+    use ethers_core::{types::H256, utils::hex::FromHex};
+    use halo2_proofs::{circuit::SimpleFloorPlanner, plonk::Circuit};
+    use std::str::FromStr;
+
+    lazy_static::lazy_static! {
+        // Test cases 1, 2 and 6 of RFC 4231.
+        pub static ref INPUTS_OUTPUTS: (Vec<(Vec<u8>, Vec<u8>)>, Vec<H256>) = {
+        [
+            (
+                "0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b".to_string(),
+                "Hi There",
+                "b0344c61d8db38535ca8afceaf0bf12b881dc200c9833da726e9376c2e32cff7",
+            ),
+            (
+                "4a656665".to_string(),
+                "what do ya want for nothing?",
+                "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843",
+            ),
+            (
+                "aa".repeat(131),
+                "Test Using Larger Than Block-Size Key - Hash Key First",
+                "60e431591ee0b67f0d8a26aacbf5b77f8e0bc6213728c5140546040f0ee37f54",
+            ),
+        ]
+            .iter()
+            .map(|(key, message, mac)| {
+                (
+                    (
+                        Vec::from_hex(key).expect("key is hex-encoded"),
+                        message.as_bytes().to_vec(),
+                    ),
+                    H256::from_str(mac).expect("HMAC-SHA256 is 32-bytes"),
+                )
+            })
+            .unzip()
+        };
+    }
+
+    #[derive(Default)]
+    pub struct HmacSha256TestCircuit<F> {
+        pub inputs: Vec<(Vec<u8>, Vec<u8>)>,
+        pub outputs: Vec<H256>,
+        pub _marker: PhantomData<F>,
+    }
+
+    impl<F: FieldExt> Circuit<F> for HmacSha256TestCircuit<F> {
+        type Config = HmacSha256Config<F>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            let hmac_table = HmacTable::construct(meta);
+            HmacSha256Config::configure(meta, hmac_table)
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            let chip = HmacSha256Chip::construct(
+                config,
+                HmacSha256Witness {
+                    inputs: self.inputs.clone(),
+                    _marker: PhantomData,
+                },
+            );
+            chip.load(&mut layouter)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use gadgets::{rows, DigestCircuit, HashCircuitExt};
+    use halo2_proofs::{
+        arithmetic::FieldExt,
+        circuit::{Layouter, SimpleFloorPlanner},
+        dev::{CellValue, MockProver},
+        halo2curves::{bn256::Fr, pasta::pallas},
+        plonk::{Circuit, ConstraintSystem, Error},
+    };
+    use std::marker::PhantomData;
+
+    use super::dev::{HmacSha256TestCircuit, INPUTS_OUTPUTS};
+    use super::{HmacSha256Chip, HmacSha256Config, HmacSha256Witness, HmacTable};
+    use crate::native;
+
+    /// Loads the HMAC calls of `inputs` over the SHA2-256 calls of
+    /// `sha2_inputs`, rather than over those of the calls.
+    #[derive(Default)]
+    struct Sha2InputsCircuit {
+        inputs: Vec<(Vec<u8>, Vec<u8>)>,
+        sha2_inputs: Vec<Vec<u8>>,
+    }
+
+    impl Circuit<Fr> for Sha2InputsCircuit {
+        type Config = HmacSha256Config<Fr>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+            let hmac_table = HmacTable::construct(meta);
+            HmacSha256Config::configure(meta, hmac_table)
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<Fr>,
+        ) -> Result<(), Error> {
+            let chip = HmacSha256Chip::construct(
+                config,
+                HmacSha256Witness {
+                    inputs: self.inputs.clone(),
+                    _marker: PhantomData,
+                },
+            );
+            chip.assign_sha2_calls(&mut layouter, self.sha2_inputs.clone())
+                .map(|_| ())
+        }
+    }
+
+    #[test]
+    fn test_hmac_sha256_native() {
+        let (inputs, outputs) = INPUTS_OUTPUTS.clone();
+        for ((key, message), output) in inputs.iter().zip(outputs) {
+            assert_eq!(native::hmac_sha256(key, message), output.0);
+        }
+    }
+
+    fn test_hmac_sha256_circuit_over<F: FieldExt>() {
+        let (inputs, outputs) = INPUTS_OUTPUTS.clone();
+
+        let circuit: HmacSha256TestCircuit<F> = HmacSha256TestCircuit {
+            inputs,
+            outputs,
+            _marker: PhantomData,
+        };
+
+        let prover = MockProver::run(rows::min_k(&circuit), &circuit, vec![]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+    }
+
+    #[test]
+    fn test_hmac_sha256_circuit() {
+        test_hmac_sha256_circuit_over::<Fr>();
+        test_hmac_sha256_circuit_over::<pallas::Base>();
+    }
+
+    #[test]
+    fn test_hmac_sha256_key_len() {
+        let (inputs, outputs) = INPUTS_OUTPUTS.clone();
+        let circuit: HmacSha256TestCircuit<Fr> = HmacSha256TestCircuit {
+            inputs,
+            outputs,
+            _marker: PhantomData,
+        };
+        let prover = MockProver::run(rows::min_k(&circuit), &circuit, vec![]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+
+        // The lengths of the keys, not of their key blocks, after the
+        // disabled row of the table.
+        let config = HmacSha256TestCircuit::<Fr>::configure(&mut ConstraintSystem::default());
+        let key_lens: Vec<_> = prover
+            .advice_values(config.table.key_len)
+            .iter()
+            .filter_map(|value| match value {
+                CellValue::Assigned(value) => Some(*value),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(key_lens, [0, 20, 4, 131].map(Fr::from));
+    }
+
+    #[test]
+    fn test_hmac_sha256_wrong_mac() {
+        let (inputs, outputs) = INPUTS_OUTPUTS.clone();
+        let witness = HmacSha256Witness::<Fr> {
+            inputs,
+            _marker: PhantomData,
+        };
+        let verify = |macs: Vec<Vec<u8>>| {
+            let circuit = DigestCircuit::<Fr, HmacSha256Chip<Fr>> {
+                witness: witness.clone(),
+                digests: macs,
+                _marker: PhantomData,
+            };
+            let k = HmacSha256Chip::<Fr>::min_k(&witness);
+            MockProver::run(k, &circuit, vec![]).unwrap().verify()
+        };
+        let mut macs: Vec<_> = outputs.iter().map(|output| output.0.to_vec()).collect();
+        assert_eq!(verify(macs.clone()), Ok(()));
+
+        // a byte of a MAC is not the one of the call.
+        macs[1][0] ^= 1;
+        assert!(verify(macs).is_err());
+    }
+
+    #[test]
+    fn test_hmac_sha256_wrong_pad_byte() {
+        let (inputs, _) = INPUTS_OUTPUTS.clone();
+        let sha2_inputs: Vec<_> = inputs
+            .iter()
+            .flat_map(|(key, message)| native::hmac_sha256_calls(key, message))
+            .collect();
+        let verify = |sha2_inputs: Vec<Vec<u8>>| {
+            let circuit = Sha2InputsCircuit {
+                inputs: inputs.clone(),
+                sha2_inputs,
+            };
+            MockProver::run(rows::min_k(&circuit), &circuit, vec![])
+                .unwrap()
+                .verify()
+        };
+        assert_eq!(verify(sha2_inputs.clone()), Ok(()));
+
+        // a byte of the key block XORed with ipad, then with opad, is not the
+        // one of the key, and a byte of the long key of the third call is not
+        // the one hashed into its key block.
+        for (call, byte) in [(0, 5), (3, 63), (4, 100)] {
+            let mut sha2_inputs = sha2_inputs.clone();
+            sha2_inputs[call][byte] ^= 1;
+            assert!(verify(sha2_inputs).is_err());
+        }
+    }
+}
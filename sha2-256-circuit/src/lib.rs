@@ -1,7 +1,3 @@
-#![allow(dead_code)]
-#![allow(unused_variables)]
-#![allow(unreachable_code)]
-
 //! A circuit is a layout of columns over multiple rows, capable of building or
 //! defining their own custom constraints. In the [`zkEVM`] architecture, many
 //! such circuits (individually termed as sub-circuits) are placed within a
@@ -17,114 +13,564 @@
 //! relationship, outsourcing the effort of verifying the relationship itself
 //! to the SHA2-256 circuit.
 //!
-//! In the sha2-256-circuit crate, we export the SHA2-256 circuit's config `Sha2Config`,
-//! and the table within it (that other circuits can use as a lookup argument)
-//! `Sha2Table`. The config type defines the layout of the circuit, the various
-//! named columns in the circuit's layout, and the `configure` method is meant
-//! to define the relationship between those columns over its neighbouring rows.
-//!
-//! For instance, for the `id` field to be an incremental field, one may specify
-//! the following relationship:
-//! ```
-//! # impl<F: FieldExt> Sha2Config<F> {
-//!     pub fn configure(meta: &mut ConstraintSystem<F>, table: Sha2Table) -> Self {
-//!         meta.create_gate("validity check over all rows", |meta| {
-//!             let mut cb = BaseConstraintBuilder::default();
-//!             cb.require_equal(
-//!                 "id field is incremental, i.e. id::cur + 1 == id::next",
-//!                 meta.query_advice(table.id, Rotation::cur()) + 1.expr(),
-//!                 meta.query_advice(table.id, Rotation::next()),
-//!             );
-//!             cb.gate(1.expr()) // enable this gate over all rows.
-//!         });
-//!
-//!         Self {
-//!             table,
-//!             _marker: PhantomData,
-//!         }
-//!     }
-//! # }
-//! ```
+//! In the sha2-256-circuit crate, we export the SHA2-256 circuit's config
+//! `Sha2Config`, and the table within it (that other circuits can use as a
+//! lookup argument) `Sha2Table`. [`MdChip`] assigns the message schedule and
+//! the 64 rounds of every block on a [`WordOpsChip`] of 32-bit words, whose
+//! gates XOR the rotations and shifts of `σ0`, `σ1`, `Σ0` and `Σ1` together
+//! from the bits of a word in a single row, and evaluate `Ch` and `Maj` bit by
+//! bit. The chip decomposes the words of every block into their big-endian
+//! bytes, and constrains the padding of the input to the bytes of
+//! [`native::pad`].
 //!
-//! We also describe how the EVM circuit would lookup to the SHA2 circuit via lookup
-//! arguments [`here`]. Currently, the table is a dummy column named `id`.
+//! The blocks of the calls are laid out in the slots of a
+//! [`CallSlotsConfig`], which constrains their padding, and the circuit
+//! exposes the calls through `Sha2Table`, a [`HashTable`] with one row per
+//! call of the RLC and length of its input and of its digest, in the layout
+//! of the `KeccakTable`. We also describe how the EVM circuit would lookup to
+//! the SHA2 circuit via lookup arguments [`here`].
 //!
-//! The following tasks are expected to be done:
-//! - Define the layout of the SHA2-256 circuit through columns in `Sha2Config`.
-//! - Define the lookup argument exposed by SHA2-256 circuit via `Sha2Table`.
-//! - Define verification logic over rows of the circuit by constraining the relationship
-//!   between the columns.
-//! - Assign witness data to the circuit via the `load` method.
-//! - Test the verification logic in the circuit.
+//! The crate also provides the `hmac` module, an HMAC-SHA256 chip composed
+//! from calls to the SHA2-256 circuit, and the `native` module, which
+//! computes the witness of both.
 //!
 //! [`zkEVM`]: https://privacy-scaling-explorations.github.io/zkevm-docs/introduction.html
 //! [`EVM-circuit`]: https://github.com/scroll-tech/zkevm-circuits/blob/scroll-stable/zkevm-circuits/src/evm_circuit.rs
 //! [`SHA2-256`]: https://en.wikipedia.org/wiki/SHA-2#Pseudocode
 //! [`here`]: https://github.com/scroll-tech/zkevm-circuits/pull/398
+//! [`MdChip`]: gadgets::MdChip
+//! [`WordOpsChip`]: gadgets::WordOpsChip
+//! [`CallSlotsConfig`]: gadgets::CallSlotsConfig
+//! [`HashTable`]: gadgets::HashTable
+
+pub mod hmac;
+pub mod native;
 
 use std::marker::PhantomData;
 
+use ethers_core::types::H256;
+use gadgets::{
+    batch::{self, BatchCircuit, BatchWitness},
+    capacity::{check_capacity, padded, Bounded, UNBOUNDED},
+    context::{assign_context, Context},
+    public_inputs::PublicInputs,
+    tables::FixedTables,
+    AssignedBytes, AssignedCall, BitFn, BitShift, CallSlotsConfig, Endianness, HashCircuitExt,
+    HashTable, HashTableConfig, MdChip, MdCompression, MdConfig, MdPadding, SlotPadding, WordCell,
+    WordOpsChip,
+};
 use halo2_proofs::{
     arithmetic::FieldExt,
-    circuit::Layouter,
-    plonk::{Advice, Any, Column, ConstraintSystem, Error},
+    circuit::{Layouter, SimpleFloorPlanner},
+    plonk::{Any, Challenge, Circuit, Column, ConstraintSystem, Error, FirstPhase, ProvingKey},
+    poly::commitment::{CommitmentScheme, Prover},
+    transcript::{EncodedChallenge, TranscriptWrite},
 };
+use rand_core::RngCore;
+use tracing::info_span;
 
-#[derive(Clone, Debug)]
-pub struct Sha2Table {
-    id: Column<Advice>,
+pub type Sha2Table = HashTable;
+
+/// The SHA2-256 compression function, whose blocks are chained by
+/// [`MdChip`].
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Sha2Compression;
+
+impl Sha2Compression {
+    /// `σ0` and `σ1` of the message schedule.
+    const SIGMA: [[BitShift; 3]; 2] = [
+        [
+            BitShift::RotateRight(7),
+            BitShift::RotateRight(18),
+            BitShift::ShiftRight(3),
+        ],
+        [
+            BitShift::RotateRight(17),
+            BitShift::RotateRight(19),
+            BitShift::ShiftRight(10),
+        ],
+    ];
+    /// `Σ0` and `Σ1` of the rounds.
+    const BIG_SIGMA: [[BitShift; 3]; 2] = [
+        [
+            BitShift::RotateRight(2),
+            BitShift::RotateRight(13),
+            BitShift::RotateRight(22),
+        ],
+        [
+            BitShift::RotateRight(6),
+            BitShift::RotateRight(11),
+            BitShift::RotateRight(25),
+        ],
+    ];
 }
 
-impl Sha2Table {
-    pub fn construct<F: FieldExt>(meta: &mut ConstraintSystem<F>) -> Self {
-        Self {
-            id: meta.advice_column(),
-        }
+impl<F: FieldExt> MdCompression<F, 32> for Sha2Compression {
+    const STATE_WORDS: usize = 8;
+    const BLOCK_BYTES: usize = native::BLOCK_BYTES;
+    const ENDIANNESS: Endianness = Endianness::Big;
+
+    fn shifts() -> Vec<Vec<BitShift>> {
+        Self::SIGMA
+            .iter()
+            .chain(Self::BIG_SIGMA.iter())
+            .map(|shifts| shifts.to_vec())
+            .collect()
+    }
+
+    fn fns() -> Vec<BitFn> {
+        vec![BitFn::CH, BitFn::MAJ]
+    }
+
+    fn iv(&self) -> Vec<u64> {
+        native::IV.iter().map(|word| *word as u64).collect()
+    }
+
+    fn pad(&self, input: &[u8]) -> Vec<u8> {
+        native::pad(input)
+    }
+
+    fn compress_rounds(&self, state: &[u64], block: &[u8]) -> Vec<u64> {
+        let state = core::array::from_fn(|i| state[i] as u32);
+        native::compress_rounds(&state, block)
+            .iter()
+            .map(|word| *word as u64)
+            .collect()
     }
 
-    pub fn columns(&self) -> Vec<Column<Any>> {
-        vec![self.id.into()]
+    /// Three rows per word of the message schedule past the block, and seven
+    /// per round.
+    fn rounds_rows(&self) -> usize {
+        3 * (native::NUM_ROUNDS - 16) + 7 * native::NUM_ROUNDS
     }
 
-    pub fn annotations(&self) -> Vec<String> {
-        vec![String::from("id")]
+    fn assign_rounds(
+        &self,
+        chip: &WordOpsChip<F, 32>,
+        ctx: &mut Context<'_, '_, F>,
+        state: &[WordCell<F>],
+        block: &[WordCell<F>],
+    ) -> Result<Vec<WordCell<F>>, Error> {
+        let mut w = block.to_vec();
+        for i in 16..native::NUM_ROUNDS {
+            let s0 = chip.shift(ctx, &Self::SIGMA[0], &w[i - 15])?;
+            let s1 = chip.shift(ctx, &Self::SIGMA[1], &w[i - 2])?;
+            w.push(chip.add(ctx, &[&w[i - 16], &s0, &w[i - 7], &s1], 0)?);
+        }
+
+        let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut h]: [WordCell<F>; 8] =
+            state.to_vec().try_into().expect("the state has 8 words");
+        for (k, w) in native::ROUND_CONSTANTS.iter().zip(w.iter()) {
+            let s1 = chip.shift(ctx, &Self::BIG_SIGMA[1], &e)?;
+            let ch = chip.apply(ctx, BitFn::CH, &[&e, &f, &g])?;
+            let temp1 = chip.add(ctx, &[&h, &s1, &ch, w], *k as u64)?;
+            let s0 = chip.shift(ctx, &Self::BIG_SIGMA[0], &a)?;
+            let maj = chip.apply(ctx, BitFn::MAJ, &[&a, &b, &c])?;
+
+            h = g;
+            g = f;
+            f = e;
+            e = chip.add(ctx, &[&d, &temp1], 0)?;
+            d = c;
+            c = b;
+            b = a;
+            a = chip.add(ctx, &[&temp1, &s0, &maj], 0)?;
+        }
+        Ok(vec![a, b, c, d, e, f, g, h])
     }
 }
 
+/// The config of the circuit, of at most `MAX_CALLS` calls of
+/// `MAX_INPUT_BYTES` input bytes in total, or sized at run time by default,
+/// see [`gadgets::capacity`].
 #[derive(Clone, Debug)]
-pub struct Sha2Config<F> {
-    table: Sha2Table,
+pub struct Sha2Config<
+    F,
+    const MAX_CALLS: usize = UNBOUNDED,
+    const MAX_INPUT_BYTES: usize = UNBOUNDED,
+> {
+    table: HashTableConfig,
+    md: MdConfig<32>,
+    slots: CallSlotsConfig,
     _marker: PhantomData<F>,
 }
 
-impl<F: FieldExt> Sha2Config<F> {
+impl<F: FieldExt, const MAX_CALLS: usize, const MAX_INPUT_BYTES: usize>
+    Sha2Config<F, MAX_CALLS, MAX_INPUT_BYTES>
+{
+    /// Fails the build of a config of no calls.
+    const HAS_CAPACITY: () = assert!(MAX_CALLS > 0, "a circuit of no calls");
+
+    /// The padding of the blocks: a `0x80` byte, zeros and the big-endian
+    /// length of the input in bits, in 8 bytes.
+    const PADDING: MdPadding = MdPadding {
+        len_bytes: 8,
+        endianness: Endianness::Big,
+    };
+
+    /// Configures the circuit over `table`, constructed with
+    /// `Sha2Table::construct(meta, native::DIGEST_BYTES)`, and a challenge
+    /// of its own for the RLCs of the table.
     pub fn configure(meta: &mut ConstraintSystem<F>, table: Sha2Table) -> Self {
+        let () = Self::HAS_CAPACITY;
+        let _span = info_span!("configure", circuit = "sha2").entered();
+        let challenge = meta.challenge_usable_after(FirstPhase);
+        let table = HashTableConfig::configure(meta, table, challenge);
+        let md = MdConfig::configure::<F, Sha2Compression>(meta);
+        let slots = CallSlotsConfig::configure(
+            meta,
+            &table,
+            native::BLOCK_BYTES,
+            native::DIGEST_BYTES,
+            SlotPadding::Md(Self::PADDING),
+        );
         Self {
             table,
+            md,
+            slots,
             _marker: PhantomData,
         }
     }
+
+    /// The table of the calls.
+    pub fn table(&self) -> &Sha2Table {
+        &self.table.table
+    }
+
+    /// The challenge of the RLCs of [`Self::table`].
+    pub fn challenge(&self) -> Challenge {
+        self.table.challenge
+    }
+
+    /// Exposes [`Self::table`] and the input bytes of its calls in two
+    /// instance columns, see [`HashTableConfig::expose`].
+    pub fn expose(&mut self, meta: &mut ConstraintSystem<F>) {
+        self.table.expose(meta);
+    }
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Default)]
 pub struct Sha2Witness<F> {
     pub inputs: Vec<Vec<u8>>,
     pub _marker: PhantomData<F>,
 }
 
 #[derive(Clone, Debug)]
-pub struct Sha2Chip<F> {
-    config: Sha2Config<F>,
+pub struct Sha2Chip<F, const MAX_CALLS: usize = UNBOUNDED, const MAX_INPUT_BYTES: usize = UNBOUNDED>
+{
+    config: Sha2Config<F, MAX_CALLS, MAX_INPUT_BYTES>,
     data: Sha2Witness<F>,
 }
 
-impl<F: FieldExt> Sha2Chip<F> {
-    pub fn construct(config: Sha2Config<F>, data: Sha2Witness<F>) -> Self {
+impl<F: FieldExt, const MAX_CALLS: usize, const MAX_INPUT_BYTES: usize>
+    Sha2Chip<F, MAX_CALLS, MAX_INPUT_BYTES>
+{
+    pub fn construct(
+        config: Sha2Config<F, MAX_CALLS, MAX_INPUT_BYTES>,
+        data: Sha2Witness<F>,
+    ) -> Self {
         Self { data, config }
     }
 
+    /// Loads the calls of the witness, failing if they exceed the capacity
+    /// of the config.
     pub fn load(&self, layouter: &mut impl Layouter<F>) -> Result<(), Error> {
-        Ok(())
+        self.assign_calls(layouter).map(|_| ())
+    }
+
+    /// Loads the calls of the witness as [`Self::load`] does, and returns the
+    /// cells of their inputs and digests, for a composing chip to constrain
+    /// to its own, e.g. the key blocks of HMAC-SHA256.
+    pub fn assign_calls(
+        &self,
+        layouter: &mut impl Layouter<F>,
+    ) -> Result<Vec<AssignedCall<F>>, Error> {
+        let inputs = &self.data.inputs;
+        let bytes: usize = inputs.iter().map(Vec::len).sum();
+        let _span = info_span!("load", circuit = "sha2", calls = inputs.len(), bytes).entered();
+        Self::check_witness(&self.data)?;
+        let lens: Vec<_> = inputs.iter().map(Vec::len).collect();
+        let slots = &self.config.slots;
+        // A bounded capacity pads the layout to it, whatever the calls.
+        let blocks = lens.iter().map(|len| slots.blocks(*len)).sum();
+        let max_slots = slots.max_slots(MAX_CALLS, MAX_INPUT_BYTES);
+        let plan = slots.plan(&lens, padded(blocks, max_slots));
+        let (calls, cells) = assign_context(layouter, "sha2 blocks", |ctx| {
+            self.md_chip().assign_slots(ctx, inputs, &plan)
+        })?;
+        slots.assign(layouter, "sha2", &self.config.table, &lens, &plan, &cells)?;
+        let table_calls: Vec<_> = inputs
+            .iter()
+            .map(|input| (input.clone(), native::sha256(input).to_vec()))
+            .collect();
+        self.config.table.assign(
+            layouter,
+            "sha2",
+            padded(inputs.len(), MAX_CALLS),
+            &table_calls,
+        )?;
+        Ok(calls)
+    }
+
+    fn md_chip(&self) -> MdChip<F, Sha2Compression, 32> {
+        MdChip::construct(self.config.md.clone(), Sha2Compression)
+    }
+}
+
+impl<F: FieldExt, const MAX_CALLS: usize, const MAX_INPUT_BYTES: usize> HashCircuitExt<F>
+    for Sha2Chip<F, MAX_CALLS, MAX_INPUT_BYTES>
+{
+    type Config = Sha2Config<F, MAX_CALLS, MAX_INPUT_BYTES>;
+    type Witness = Sha2Witness<F>;
+
+    const DIGEST_BYTES: usize = native::DIGEST_BYTES;
+
+    /// Configures the circuit over its table, exposed in its instance
+    /// column.
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let mut config = Self::configure_with_tables(meta, &mut FixedTables::default());
+        config.expose(meta);
+        config
+    }
+
+    /// Configures the circuit over its table, not exposed, the circuit
+    /// having no fixed tables.
+    fn configure_with_tables(
+        meta: &mut ConstraintSystem<F>,
+        _tables: &mut FixedTables,
+    ) -> Self::Config {
+        let table = Sha2Table::construct(meta, native::DIGEST_BYTES);
+        Sha2Config::configure(meta, table)
+    }
+
+    /// At most `MAX_CALLS` calls of `MAX_INPUT_BYTES` bytes in total.
+    fn check_witness(witness: &Self::Witness) -> Result<(), gadgets::Error> {
+        let bytes = witness.inputs.iter().map(Vec::len).sum();
+        check_capacity(witness.inputs.len(), bytes, MAX_CALLS, MAX_INPUT_BYTES)
+    }
+
+    /// The calls of the inputs of `witness`, in their order, padded to the
+    /// capacity as the layout is.
+    fn public_inputs(witness: &Self::Witness) -> Vec<Vec<F>> {
+        let mut public_inputs = PublicInputs::new(
+            native::DIGEST_BYTES,
+            native::BLOCK_BYTES,
+            SlotPadding::Md(Sha2Config::<F, MAX_CALLS, MAX_INPUT_BYTES>::PADDING),
+        );
+        for input in &witness.inputs {
+            public_inputs.call(input, &native::sha256(input));
+        }
+        public_inputs.build_padded(MAX_CALLS, MAX_INPUT_BYTES)
+    }
+
+    fn construct(config: Self::Config, witness: Self::Witness) -> Self {
+        Sha2Chip::construct(config, witness)
+    }
+
+    fn load(&self, layouter: &mut impl Layouter<F>) -> Result<(), Error> {
+        Sha2Chip::load(self, layouter)
+    }
+
+    fn table_columns(config: &Self::Config) -> Vec<Column<Any>> {
+        config.table().columns()
+    }
+
+    fn annotations(config: &Self::Config) -> Vec<String> {
+        config.table().annotations()
+    }
+
+    fn assigned_digest(
+        &self,
+        layouter: &mut impl Layouter<F>,
+    ) -> Result<Vec<AssignedBytes<F>>, Error> {
+        let calls = self.assign_calls(layouter)?;
+        Ok(calls.into_iter().map(|call| call.digest).collect())
+    }
+}
+
+impl<F> BatchWitness for Sha2Witness<F> {
+    fn merge(witnesses: Vec<Self>) -> Self {
+        Self {
+            inputs: witnesses
+                .into_iter()
+                .flat_map(|witness| witness.inputs)
+                .collect(),
+            _marker: PhantomData,
+        }
+    }
+}
+
+/// The circuit of the calls of many witness sets, see [`gadgets::batch`],
+/// whose keys are generated from its default, as its layout is that of its
+/// capacity, which is bounded.
+pub type Sha2BatchCircuit<F, const MAX_CALLS: usize, const MAX_INPUT_BYTES: usize> =
+    BatchCircuit<F, Sha2Chip<F, MAX_CALLS, MAX_INPUT_BYTES>>;
+
+/// Proves the calls of all of `witnesses` with a single proof of a
+/// [`Sha2BatchCircuit`], or fails with an [`gadgets::Error::Capacity`] if they
+/// exceed `MAX_CALLS` calls of `MAX_INPUT_BYTES` bytes, see
+/// [`batch::prove_batch`]. The build fails if the capacity is unbounded.
+pub fn prove_batch<
+    'params,
+    Scheme,
+    P,
+    E,
+    R,
+    T,
+    const MAX_CALLS: usize,
+    const MAX_INPUT_BYTES: usize,
+>(
+    params: &'params Scheme::ParamsProver,
+    pk: &ProvingKey<Scheme::Curve>,
+    witnesses: Vec<Sha2Witness<Scheme::Scalar>>,
+    rng: R,
+    transcript: &mut T,
+) -> Result<(), gadgets::Error>
+where
+    Scheme: CommitmentScheme,
+    P: Prover<'params, Scheme>,
+    E: EncodedChallenge<Scheme::Curve>,
+    R: RngCore,
+    T: TranscriptWrite<Scheme::Curve, E>,
+{
+    let () = Bounded::<MAX_CALLS, MAX_INPUT_BYTES>::CHECK;
+    batch::prove_batch::<Scheme, P, E, R, T, Sha2Chip<Scheme::Scalar, MAX_CALLS, MAX_INPUT_BYTES>>(
+        params, pk, witnesses, rng, transcript,
+    )
+}
+
+/// The circuit of the calls of SHA2-256 proven on their own, whose digests
+/// are checked against `outputs`, without exposing its table, e.g. by the
+/// benchmarks.
+#[derive(Default)]
+pub struct Sha2TestCircuit<F> {
+    pub inputs: Vec<Vec<u8>>,
+    pub outputs: Vec<H256>,
+    pub _marker: PhantomData<F>,
+}
+
+impl<F: FieldExt> Sha2TestCircuit<F> {
+    /// Smallest `k` that fits the calls of the circuit, see
+    /// [`HashCircuitExt::min_k`].
+    pub fn min_k(&self) -> u32 {
+        Sha2Chip::<F>::min_k(&Sha2Witness {
+            inputs: self.inputs.clone(),
+            _marker: PhantomData,
+        })
+    }
+
+    /// Checks the witness of the circuit before it is assigned: that it
+    /// fits the capacity of the chip, and that the digests of the calls
+    /// are those of [`native::sha256`], see [`gadgets::validate`].
+    pub fn check_witness(&self) -> Result<(), gadgets::Error> {
+        Sha2Chip::<F>::check_witness(&Sha2Witness {
+            inputs: self.inputs.clone(),
+            _marker: PhantomData,
+        })?;
+        gadgets::validate::check_digests("SHA2-256", &self.inputs, &self.outputs, |input| {
+            native::sha256(input)
+        })
+    }
+}
+
+impl<F: FieldExt> Circuit<F> for Sha2TestCircuit<F> {
+    type Config = Sha2Config<F>;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let sha2_table = Sha2Table::construct(meta, native::DIGEST_BYTES);
+        Sha2Config::configure(meta, sha2_table)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        self.check_witness()?;
+        let chip = Sha2Chip::construct(
+            config,
+            Sha2Witness {
+                inputs: self.inputs.clone(),
+                _marker: PhantomData,
+            },
+        );
+        chip.load(&mut layouter)
+    }
+}
+
+/// The keys of [`Sha2BatchCircuit`], prefixed with the version of its
+/// constraint system, see [`gadgets::keys`], which is that of any capacity.
+pub mod keys {
+    use super::*;
+
+    use halo2_proofs::{
+        arithmetic::CurveAffine,
+        plonk::{ProvingKey, VerifyingKey},
+        SerdeFormat,
+    };
+    use std::io;
+
+    /// Writes the proving key of [`Sha2BatchCircuit`] prefixed with the version of its
+    /// constraint system, see [`gadgets::keys`].
+    pub fn write_pk<C: CurveAffine>(
+        writer: &mut impl io::Write,
+        pk: &ProvingKey<C>,
+        format: SerdeFormat,
+    ) -> io::Result<()>
+    where
+        C::ScalarExt: FieldExt,
+    {
+        gadgets::keys::write_pk::<C, BatchCircuit<C::ScalarExt, Sha2Chip<C::ScalarExt>>>(
+            writer, pk, format,
+        )
+    }
+
+    /// Reads a proving key written by [`write_pk`], refusing that of another
+    /// version of [`Sha2BatchCircuit`].
+    pub fn read_pk<C: CurveAffine>(
+        reader: &mut impl io::Read,
+        format: SerdeFormat,
+    ) -> io::Result<ProvingKey<C>>
+    where
+        C::ScalarExt: FieldExt,
+    {
+        gadgets::keys::read_pk::<C, BatchCircuit<C::ScalarExt, Sha2Chip<C::ScalarExt>>>(
+            reader, format,
+        )
+    }
+
+    /// Writes the verifying key of [`Sha2BatchCircuit`] prefixed with the version of
+    /// its constraint system, see [`gadgets::keys`].
+    pub fn write_vk<C: CurveAffine>(
+        writer: &mut impl io::Write,
+        vk: &VerifyingKey<C>,
+        format: SerdeFormat,
+    ) -> io::Result<()>
+    where
+        C::ScalarExt: FieldExt,
+    {
+        gadgets::keys::write_vk::<C, BatchCircuit<C::ScalarExt, Sha2Chip<C::ScalarExt>>>(
+            writer, vk, format,
+        )
+    }
+
+    /// Reads a verifying key written by [`write_vk`], refusing that of
+    /// another version of [`Sha2BatchCircuit`].
+    pub fn read_vk<C: CurveAffine>(
+        reader: &mut impl io::Read,
+        format: SerdeFormat,
+    ) -> io::Result<VerifyingKey<C>>
+    where
+        C::ScalarExt: FieldExt,
+    {
+        gadgets::keys::read_vk::<C, BatchCircuit<C::ScalarExt, Sha2Chip<C::ScalarExt>>>(
+            reader, format,
+        )
     }
 }
 
@@ -132,97 +578,392 @@ impl<F: FieldExt> Sha2Chip<F> {
 pub mod dev {
     use super::*;
 
-    use ethers_core::types::H256;
-    use halo2_proofs::{circuit::SimpleFloorPlanner, plonk::Circuit};
-    use std::str::FromStr;
-
     lazy_static::lazy_static! {
+        /// The calls of the fixtures of SHA2-256, see [`test_utils`].
         pub static ref INPUTS_OUTPUTS: (Vec<Vec<u8>>, Vec<H256>) = {
-        [
-            (
-                "",
-                "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855",
-            ),
-            (
-                "abc",
-                "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad",
-            ),
-            (
-                "abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq",
-                "248d6a61d20638b8e5c026930c3e6039a33ce45964ff2167f6ecedd419db06c1",
-            ),
-            (
-                "abcdefghbcdefghicdefghijdefghijkefghijklfghijklmghijklmnhijklmnoijklmnopjklmnopqklmnopqrlmnopqrsmnopqrstnopqrstu",
-                "cf5b16a778af8380036ce59e7b0492370b249b11e8f07a51afac45037afee9d1",
-            ),
-        ]
-            .iter()
-            .map(|(input, output)| {
-                (
-                    input.as_bytes().to_vec(),
-                    H256::from_str(output).expect("SHA-256 hash is 32-bytes"),
-                )
-            })
-            .unzip()
+            let (inputs, outputs) = test_utils::inputs_outputs("sha2");
+            (inputs, outputs.iter().map(|output| H256::from_slice(output)).collect())
         };
     }
+}
+
+#[cfg(test)]
+mod tests {
+    use gadgets::{
+        batch::BatchWitness, public_inputs::PublicInputs, DigestCircuit, HashCircuitExt, WordLoHi,
+    };
+    use halo2_proofs::{
+        arithmetic::FieldExt,
+        circuit::{Layouter, SimpleFloorPlanner},
+        dev::MockProver,
+        halo2curves::{
+            bn256::{Bn256, Fr, G1Affine},
+            pasta::pallas,
+        },
+        plonk::{keygen_pk, keygen_vk, verify_proof, Circuit, ConstraintSystem, Error},
+        poly::{
+            commitment::ParamsProver,
+            kzg::{
+                commitment::{KZGCommitmentScheme, ParamsKZG},
+                multiopen::{ProverSHPLONK, VerifierSHPLONK},
+                strategy::SingleStrategy,
+            },
+        },
+        transcript::{
+            Blake2bRead, Blake2bWrite, Challenge255, TranscriptReadBuffer, TranscriptWriterBuffer,
+        },
+    };
+    use rand_xorshift::XorShiftRng;
+    use std::marker::PhantomData;
 
+    use crate::{
+        dev::INPUTS_OUTPUTS, native, prove_batch, Sha2BatchCircuit, Sha2Chip, Sha2Config,
+        Sha2TestCircuit, Sha2Witness,
+    };
+
+    /// Loads `inputs` in a circuit of at most 2 calls of 64 bytes.
     #[derive(Default)]
-    pub struct Sha2TestCircuit<F> {
-        pub inputs: Vec<Vec<u8>>,
-        pub outputs: Vec<H256>,
-        pub _marker: PhantomData<F>,
+    struct SizedCircuit {
+        inputs: Vec<Vec<u8>>,
     }
 
-    impl<F: FieldExt> Circuit<F> for Sha2TestCircuit<F> {
-        type Config = Sha2Config<F>;
+    impl Circuit<Fr> for SizedCircuit {
+        type Config = Sha2Config<Fr, 2, 64>;
         type FloorPlanner = SimpleFloorPlanner;
 
         fn without_witnesses(&self) -> Self {
             Self::default()
         }
 
-        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
-            let sha2_table = Sha2Table::construct(meta);
-            Sha2Config::configure(meta, sha2_table)
+        fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+            Sha2Chip::<Fr, 2, 64>::configure(meta)
         }
 
         fn synthesize(
             &self,
             config: Self::Config,
-            mut layouter: impl Layouter<F>,
+            mut layouter: impl Layouter<Fr>,
         ) -> Result<(), Error> {
-            let chip = Sha2Chip::construct(
-                config,
-                Sha2Witness {
-                    inputs: self.inputs.clone(),
-                    _marker: PhantomData,
-                },
-            );
-            chip.load(&mut layouter)
+            let witness = Sha2Witness {
+                inputs: self.inputs.clone(),
+                _marker: PhantomData,
+            };
+            Sha2Chip::construct(config, witness).load(&mut layouter)
         }
     }
-}
-
-#[cfg(test)]
-mod tests {
-    use halo2_proofs::{dev::MockProver, halo2curves::bn256::Fr};
-    use std::marker::PhantomData;
 
-    use crate::dev::{Sha2TestCircuit, INPUTS_OUTPUTS};
+    #[test]
+    fn test_sha2_native() {
+        let (inputs, outputs) = INPUTS_OUTPUTS.clone();
+        for (input, output) in inputs.iter().zip(outputs) {
+            assert_eq!(native::sha256(input), output.0);
+        }
+    }
 
     #[test]
-    fn test_sha2_circuit() {
+    fn test_sha2_public_inputs() {
+        let (inputs, outputs) = INPUTS_OUTPUTS.clone();
+        let witness = Sha2Witness::<Fr> {
+            inputs: inputs.clone(),
+            _marker: PhantomData,
+        };
+        let [column, blocks]: [_; 2] = Sha2Chip::<Fr>::public_inputs(&witness).try_into().unwrap();
+        // the four chunks of every block of the inputs.
+        let slots: usize = inputs
+            .iter()
+            .map(|input| (input.len() + 9).div_ceil(64))
+            .sum();
+        assert_eq!(blocks.len(), 4 * slots);
+        assert_eq!(
+            column.len(),
+            PublicInputs::<Fr>::num_values(32, inputs.len())
+        );
+        assert_eq!(column[0], Fr::from(inputs.len() as u64));
+        // the call id, the length and the high and low halves of the digest.
+        for (i, (values, (input, output))) in column[1..]
+            .chunks(4)
+            .zip(inputs.iter().zip(outputs))
+            .enumerate()
+        {
+            let digest = WordLoHi::<Fr>::from_bytes_be(&output.0);
+            let expected = [
+                Fr::from(i as u64 + 1),
+                Fr::from(input.len() as u64),
+                *digest.hi(),
+                *digest.lo(),
+            ];
+            assert_eq!(values, expected);
+        }
+    }
+
+    fn test_sha2_circuit_over<F: FieldExt>() {
         let (inputs, outputs) = INPUTS_OUTPUTS.clone();
 
-        let circuit: Sha2TestCircuit<Fr> = Sha2TestCircuit {
+        let circuit: Sha2TestCircuit<F> = Sha2TestCircuit {
             inputs,
             outputs,
             _marker: PhantomData,
         };
 
-        let k = 8;
-        let prover = MockProver::run(k, &circuit, vec![]).unwrap();
+        let prover = MockProver::run(circuit.min_k(), &circuit, vec![]).unwrap();
         assert_eq!(prover.verify(), Ok(()));
     }
+
+    #[test]
+    fn test_sha2_hash_circuit_ext() {
+        let mut meta = ConstraintSystem::<Fr>::default();
+        let config = Sha2Chip::<Fr>::configure(&mut meta);
+        assert_eq!(
+            Sha2Chip::<Fr>::table_columns(&config).len(),
+            Sha2Chip::<Fr>::annotations(&config).len()
+        );
+        assert_eq!(Sha2Chip::<Fr>::DIGEST_BYTES, 32);
+    }
+
+    #[test]
+    fn test_sha2_assigned_digest() {
+        let (inputs, outputs) = INPUTS_OUTPUTS.clone();
+        let witness = Sha2Witness::<Fr> {
+            inputs,
+            _marker: PhantomData,
+        };
+        let verify = |digests: Vec<Vec<u8>>| {
+            let circuit = DigestCircuit::<Fr, Sha2Chip<Fr>> {
+                witness: witness.clone(),
+                digests,
+                _marker: PhantomData,
+            };
+            let k = Sha2Chip::<Fr>::min_k(&witness);
+            let instances = Sha2Chip::<Fr>::public_inputs(&witness);
+            MockProver::run(k, &circuit, instances).unwrap().verify()
+        };
+        let mut digests: Vec<_> = outputs.iter().map(|output| output.0.to_vec()).collect();
+        assert_eq!(verify(digests.clone()), Ok(()));
+
+        // a byte of a digest is not the one of the call.
+        digests[1][31] ^= 1;
+        assert!(verify(digests).is_err());
+    }
+
+    #[test]
+    fn test_sha2_instance() {
+        let witness = Sha2Witness::<Fr> {
+            inputs: vec![b"abc".to_vec()],
+            _marker: PhantomData,
+        };
+        let k = Sha2Chip::<Fr, 2, 64>::min_k(&witness);
+        let verify = |instances| {
+            let circuit = SizedCircuit {
+                inputs: witness.inputs.clone(),
+            };
+            MockProver::run(k, &circuit, instances).unwrap().verify()
+        };
+        let instances = Sha2Chip::<Fr, 2, 64>::public_inputs(&witness);
+        assert_eq!(verify(instances.clone()), Ok(()));
+
+        // the calls, a length, the low half of a digest, and a disabled row
+        // are not those of the table, and a chunk of an input, its padding
+        // and a disabled slot are not those of the slots.
+        for (column, i) in [(0, 0), (0, 2), (0, 4), (0, 5), (1, 0), (1, 1), (1, 4)] {
+            let mut instances = instances.clone();
+            if instances[column].len() <= i {
+                instances[column].resize(i + 1, Fr::from(0));
+            }
+            instances[column][i] += Fr::from(1);
+            assert!(verify(instances).is_err());
+        }
+        // the instances of no calls.
+        assert!(verify(Sha2Chip::<Fr, 2, 64>::public_inputs(&Sha2Witness::default())).is_err());
+    }
+
+    #[test]
+    fn test_sha2_capacity() {
+        let run = |inputs: Vec<Vec<u8>>| {
+            let witness = Sha2Witness {
+                inputs: inputs.clone(),
+                _marker: PhantomData,
+            };
+            let k = Sha2Chip::<Fr, 2, 64>::min_k(&witness);
+            let instances = Sha2Chip::<Fr, 2, 64>::public_inputs(&witness);
+            MockProver::run(k, &SizedCircuit { inputs }, instances)
+        };
+        assert!(run(vec![vec![0; 32], vec![0; 32]]).is_ok());
+        // the layout is that of the capacity, whatever the calls.
+        assert_eq!(
+            Sha2Chip::<Fr, 2, 64>::layout_rows(&Sha2Witness::default()),
+            Sha2Chip::<Fr, 2, 64>::layout_rows(&Sha2Witness {
+                inputs: vec![vec![0; 64]],
+                _marker: PhantomData,
+            })
+        );
+        // so are the lengths of the instances.
+        let lens = |inputs: Vec<Vec<u8>>| {
+            Sha2Chip::<Fr, 2, 64>::public_inputs(&Sha2Witness {
+                inputs,
+                _marker: PhantomData,
+            })
+            .iter()
+            .map(Vec::len)
+            .collect::<Vec<_>>()
+        };
+        assert_eq!(lens(vec![]), lens(vec![vec![0; 32], vec![0; 32]]));
+        assert!(run(vec![vec![0; 16]; 3]).is_err());
+        assert!(run(vec![vec![0; 65]]).is_err());
+    }
+
+    #[test]
+    fn test_sha2_check_witness() {
+        let (inputs, mut outputs) = INPUTS_OUTPUTS.clone();
+        outputs[1].0[0] ^= 1;
+        let circuit = Sha2TestCircuit::<Fr> {
+            inputs,
+            outputs,
+            _marker: PhantomData,
+        };
+        assert!(matches!(
+            circuit.check_witness(),
+            Err(gadgets::Error::Witness { call: 1, .. })
+        ));
+        assert!(matches!(
+            MockProver::run(circuit.min_k(), &circuit, vec![]),
+            Err(Error::Synthesis)
+        ));
+    }
+
+    #[test]
+    fn test_sha2_prove_batch() {
+        use rand_core::SeedableRng;
+        let rng = || XorShiftRng::from_seed([0x42; 16]);
+        let witness = |input: &[u8]| Sha2Witness {
+            inputs: vec![input.to_vec()],
+            _marker: PhantomData,
+        };
+        // the layout of the capacity of 2 calls of 64 bytes.
+        let k = Sha2Chip::<Fr, 2, 64>::min_k(&Sha2Witness::default());
+        let params = ParamsKZG::<Bn256>::setup(k, rng());
+        let circuit = Sha2BatchCircuit::<Fr, 2, 64>::default();
+        let vk = keygen_vk(&params, &circuit).unwrap();
+        let pk = keygen_pk(&params, vk, &circuit).unwrap();
+
+        let prove = |witnesses| {
+            let mut transcript = Blake2bWrite::<_, G1Affine, Challenge255<_>>::init(vec![]);
+            prove_batch::<KZGCommitmentScheme<Bn256>, ProverSHPLONK<_>, _, _, _, 2, 64>(
+                &params,
+                &pk,
+                witnesses,
+                rng(),
+                &mut transcript,
+            )
+            .map(|()| transcript.finalize())
+        };
+
+        let witnesses = vec![witness(b"abc"), witness(b"def")];
+        let [column, blocks]: [_; 2] =
+            Sha2Chip::<Fr, 2, 64>::public_inputs(&Sha2Witness::merge(witnesses.clone()))
+                .try_into()
+                .unwrap();
+        let proof = prove(witnesses).unwrap();
+        let verify = |column: &[Fr], blocks: &[Fr]| {
+            let mut transcript = Blake2bRead::<_, G1Affine, Challenge255<_>>::init(&proof[..]);
+            verify_proof::<_, VerifierSHPLONK<_>, _, _, _>(
+                params.verifier_params(),
+                pk.get_vk(),
+                SingleStrategy::new(&params),
+                &[&[column, blocks]],
+                &mut transcript,
+            )
+        };
+        assert!(verify(&column, &blocks).is_ok());
+        // the proof is not one of a digest of another call.
+        let mut wrong = column.clone();
+        *wrong.last_mut().unwrap() += Fr::from(1);
+        assert!(verify(&wrong, &blocks).is_err());
+        // nor of another input.
+        let mut wrong = blocks.clone();
+        wrong[0] += Fr::from(1);
+        assert!(verify(&column, &wrong).is_err());
+
+        // the witness sets exceed the 2 calls of the circuit together.
+        assert!(matches!(
+            prove(vec![witness(b"abc"); 3]),
+            Err(gadgets::Error::Capacity { calls: 3, .. })
+        ));
+    }
+
+    #[test]
+    fn test_sha2_circuit() {
+        test_sha2_circuit_over::<Fr>();
+        test_sha2_circuit_over::<pallas::Base>();
+    }
+
+    /// The capacity of the soak test, of a hundred calls of 32 KiB, whose
+    /// layout fits in [`test_utils::SOAK_K`].
+    const SOAK_CALLS: usize = 128;
+    const SOAK_BYTES: usize = 1 << 15;
+
+    #[test]
+    #[ignore = "proves a hundred calls at k = 19, run with --ignored"]
+    fn test_sha2_soak() {
+        use rand_core::SeedableRng;
+        use test_utils::{random_inputs, SOAK_K};
+
+        let mut rng = XorShiftRng::from_seed([0x42; 16]);
+        let inputs = random_inputs(&mut rng, SOAK_CALLS, SOAK_BYTES);
+        // the calls of the transactions of a block, a few each.
+        let witnesses: Vec<_> = inputs
+            .chunks(7)
+            .map(|inputs| Sha2Witness {
+                inputs: inputs.to_vec(),
+                _marker: PhantomData,
+            })
+            .collect();
+        let circuit = Sha2BatchCircuit::<Fr, SOAK_CALLS, SOAK_BYTES>::new(witnesses.clone());
+        assert_eq!(circuit.witness.inputs, inputs);
+        // the layout of the capacity, that of the calls and of the keys.
+        assert!(Sha2Chip::<Fr, SOAK_CALLS, SOAK_BYTES>::min_k(&Sha2Witness::default()) <= SOAK_K);
+        let instances = Sha2Chip::<Fr, SOAK_CALLS, SOAK_BYTES>::public_inputs(&circuit.witness);
+        MockProver::run(SOAK_K, &circuit, instances.clone())
+            .unwrap()
+            .assert_satisfied();
+
+        let params = ParamsKZG::<Bn256>::setup(SOAK_K, rng.clone());
+        let circuit = Sha2BatchCircuit::<Fr, SOAK_CALLS, SOAK_BYTES>::default();
+        let vk = keygen_vk(&params, &circuit).unwrap();
+        let pk = keygen_pk(&params, vk, &circuit).unwrap();
+        let prove = |witnesses| {
+            let mut transcript = Blake2bWrite::<_, G1Affine, Challenge255<_>>::init(vec![]);
+            prove_batch::<
+                KZGCommitmentScheme<Bn256>,
+                ProverSHPLONK<_>,
+                _,
+                _,
+                _,
+                SOAK_CALLS,
+                SOAK_BYTES,
+            >(&params, &pk, witnesses, rng.clone(), &mut transcript)
+            .map(|()| transcript.finalize())
+        };
+
+        let proof = prove(witnesses.clone()).unwrap();
+        let mut transcript = Blake2bRead::<_, G1Affine, Challenge255<_>>::init(&proof[..]);
+        assert!(verify_proof::<_, VerifierSHPLONK<_>, _, _, _>(
+            params.verifier_params(),
+            pk.get_vk(),
+            SingleStrategy::new(&params),
+            &[&[&instances[0], &instances[1]]],
+            &mut transcript,
+        )
+        .is_ok());
+
+        // one call more than the capacity, even of no bytes.
+        let mut witnesses = witnesses;
+        witnesses.push(Sha2Witness {
+            inputs: vec![vec![]],
+            _marker: PhantomData,
+        });
+        assert!(matches!(
+            prove(witnesses),
+            Err(gadgets::Error::Capacity { calls, .. }) if calls == SOAK_CALLS + 1
+        ));
+    }
 }
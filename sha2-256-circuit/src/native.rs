@@ -0,0 +1,171 @@
+//! Native implementation of SHA2-256 and HMAC-SHA256, used to compute the
+//! witness of the circuits and as the reference they are tested against.
+
+/// Size of a message block in bytes.
+pub const BLOCK_BYTES: usize = 64;
+
+/// Size of a digest in bytes.
+pub const DIGEST_BYTES: usize = 32;
+
+/// Number of rounds of the compression function.
+pub const NUM_ROUNDS: usize = 64;
+
+/// Round constants, i.e. the first 32 bits of the fractional parts of the cube
+/// roots of the first 64 primes.
+pub const ROUND_CONSTANTS: [u32; NUM_ROUNDS] = [
+    0x428a2f98, 0x71374491, 0xb5c0fbcf, 0xe9b5dba5, 0x3956c25b, 0x59f111f1, 0x923f82a4, 0xab1c5ed5,
+    0xd807aa98, 0x12835b01, 0x243185be, 0x550c7dc3, 0x72be5d74, 0x80deb1fe, 0x9bdc06a7, 0xc19bf174,
+    0xe49b69c1, 0xefbe4786, 0x0fc19dc6, 0x240ca1cc, 0x2de92c6f, 0x4a7484aa, 0x5cb0a9dc, 0x76f988da,
+    0x983e5152, 0xa831c66d, 0xb00327c8, 0xbf597fc7, 0xc6e00bf3, 0xd5a79147, 0x06ca6351, 0x14292967,
+    0x27b70a85, 0x2e1b2138, 0x4d2c6dfc, 0x53380d13, 0x650a7354, 0x766a0abb, 0x81c2c92e, 0x92722c85,
+    0xa2bfe8a1, 0xa81a664b, 0xc24b8b70, 0xc76c51a3, 0xd192e819, 0xd6990624, 0xf40e3585, 0x106aa070,
+    0x19a4c116, 0x1e376c08, 0x2748774c, 0x34b0bcb5, 0x391c0cb3, 0x4ed8aa4a, 0x5b9cca4f, 0x682e6ff3,
+    0x748f82ee, 0x78a5636f, 0x84c87814, 0x8cc70208, 0x90befffa, 0xa4506ceb, 0xbef9a3f7, 0xc67178f2,
+];
+
+/// Initial hash value of SHA2-256.
+pub const IV: [u32; 8] = [
+    0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f, 0x9b05688c, 0x1f83d9ab, 0x5be0cd19,
+];
+
+/// Byte XORed into the key block for the inner hash of HMAC.
+pub const IPAD: u8 = 0x36;
+
+/// Byte XORed into the key block for the outer hash of HMAC.
+pub const OPAD: u8 = 0x5c;
+
+/// Pads `input` to a multiple of [`BLOCK_BYTES`] bytes: a `1` bit, zeros, and
+/// the length of the input in bits as a 64-bit big-endian integer.
+pub fn pad(input: &[u8]) -> Vec<u8> {
+    let mut padded = input.to_vec();
+    padded.push(0x80);
+    padded.resize(
+        (padded.len() + 8).div_ceil(BLOCK_BYTES) * BLOCK_BYTES - 8,
+        0,
+    );
+    padded.extend_from_slice(&((input.len() as u64) * 8).to_be_bytes());
+    padded
+}
+
+/// Expands a block into the 64 words of the message schedule.
+pub fn message_schedule(block: &[u8]) -> [u32; NUM_ROUNDS] {
+    assert_eq!(block.len(), BLOCK_BYTES);
+    let mut w = [0u32; NUM_ROUNDS];
+    for (word, bytes) in w.iter_mut().zip(block.chunks(4)) {
+        *word = u32::from_be_bytes(bytes.try_into().expect("words are 4 bytes"));
+    }
+    for i in 16..NUM_ROUNDS {
+        let s0 = w[i - 15].rotate_right(7) ^ w[i - 15].rotate_right(18) ^ (w[i - 15] >> 3);
+        let s1 = w[i - 2].rotate_right(17) ^ w[i - 2].rotate_right(19) ^ (w[i - 2] >> 10);
+        w[i] = w[i - 16]
+            .wrapping_add(s0)
+            .wrapping_add(w[i - 7])
+            .wrapping_add(s1);
+    }
+    w
+}
+
+/// Applies the 64 rounds of the compression function to `state`, returning
+/// the working variables before they are added back to `state`.
+pub fn compress_rounds(state: &[u32; 8], block: &[u8]) -> [u32; 8] {
+    let w = message_schedule(block);
+    let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut h] = *state;
+    for (k, w) in ROUND_CONSTANTS.iter().zip(w) {
+        let s1 = e.rotate_right(6) ^ e.rotate_right(11) ^ e.rotate_right(25);
+        let ch = (e & f) ^ (!e & g);
+        let temp1 = h
+            .wrapping_add(s1)
+            .wrapping_add(ch)
+            .wrapping_add(*k)
+            .wrapping_add(w);
+        let s0 = a.rotate_right(2) ^ a.rotate_right(13) ^ a.rotate_right(22);
+        let maj = (a & b) ^ (a & c) ^ (b & c);
+        let temp2 = s0.wrapping_add(maj);
+
+        h = g;
+        g = f;
+        f = e;
+        e = d.wrapping_add(temp1);
+        d = c;
+        c = b;
+        b = a;
+        a = temp1.wrapping_add(temp2);
+    }
+    [a, b, c, d, e, f, g, h]
+}
+
+/// Compresses `block` into `state`.
+pub fn compress(state: &mut [u32; 8], block: &[u8]) {
+    let working = compress_rounds(state, block);
+    for (word, working) in state.iter_mut().zip(working) {
+        *word = word.wrapping_add(working);
+    }
+}
+
+/// Computes the SHA2-256 digest of `input`.
+pub fn sha256(input: &[u8]) -> [u8; DIGEST_BYTES] {
+    let mut state = IV;
+    for block in pad(input).chunks(BLOCK_BYTES) {
+        compress(&mut state, block);
+    }
+
+    let mut digest = [0u8; DIGEST_BYTES];
+    for (bytes, word) in digest.chunks_mut(4).zip(state) {
+        bytes.copy_from_slice(&word.to_be_bytes());
+    }
+    digest
+}
+
+/// Key block of HMAC-SHA256: the key zero-padded to [`BLOCK_BYTES`] bytes, or
+/// the digest of the key, zero-padded, if the key is longer than a block.
+pub fn hmac_key_block(key: &[u8]) -> [u8; BLOCK_BYTES] {
+    let mut block = [0u8; BLOCK_BYTES];
+    if key.len() > BLOCK_BYTES {
+        block[..32].copy_from_slice(&sha256(key));
+    } else {
+        block[..key.len()].copy_from_slice(key);
+    }
+    block
+}
+
+/// Inputs of the inner and outer SHA2-256 calls of HMAC-SHA256.
+pub fn hmac_sha256_inputs(key: &[u8], message: &[u8]) -> (Vec<u8>, Vec<u8>) {
+    let key_block = hmac_key_block(key);
+    let inner: Vec<u8> = key_block
+        .iter()
+        .map(|byte| byte ^ IPAD)
+        .chain(message.iter().copied())
+        .collect();
+    let outer: Vec<u8> = key_block
+        .iter()
+        .map(|byte| byte ^ OPAD)
+        .chain(sha256(&inner))
+        .collect();
+    (inner, outer)
+}
+
+/// Inputs of the SHA2-256 calls of HMAC-SHA256: the key if it is longer
+/// than a block, then the inner and outer inputs.
+pub fn hmac_sha256_calls(key: &[u8], message: &[u8]) -> Vec<Vec<u8>> {
+    let (inner, outer) = hmac_sha256_inputs(key, message);
+    let key = (key.len() > BLOCK_BYTES).then(|| key.to_vec());
+    key.into_iter().chain([inner, outer]).collect()
+}
+
+/// Computes the HMAC-SHA256 of `message` under `key`.
+pub fn hmac_sha256(key: &[u8], message: &[u8]) -> [u8; 32] {
+    let (_, outer) = hmac_sha256_inputs(key, message);
+    sha256(&outer)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hmac_key_block() {
+        assert_eq!(hmac_key_block(b"key")[..4], *b"key\0");
+        assert_eq!(hmac_key_block(&[1; 65])[..32], sha256(&[1; 65]));
+        assert_eq!(hmac_key_block(&[1; 65])[32..], [0; 32]);
+    }
+}
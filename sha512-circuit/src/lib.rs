@@ -0,0 +1,546 @@
+//! The SHA-512 circuit verifies calls to the [`SHA-512`] hash function, and to
+//! SHA-384, which only differs by its initial hash value and by truncating the
+//! digest to its first 6 words.
+//!
+//! SHA-512 has the same structure as SHA2-256 over 64-bit words: [`MdChip`]
+//! assigns the message schedule and the 80 rounds of every block on a
+//! [`WordOpsChip`] of 64-bit words, whose gates XOR the rotations and shifts
+//! of `σ0`, `σ1`, `Σ0` and `Σ1` together from the bits of a word in a single
+//! row, and evaluate `Ch` and `Maj` bit by bit. The chip decomposes the words
+//! of every block into their big-endian bytes, and constrains the padding of
+//! the input to the bytes of [`native::pad`].
+//!
+//! The blocks of the calls are laid out in the slots of a
+//! [`CallSlotsConfig`], which constrains their padding, and the circuit
+//! exposes the calls through `Sha512Table`, a [`HashTable`] with one row per
+//! call of the RLC and length of its input and of its digest, of 64 bytes or
+//! of 48 for SHA-384.
+//!
+//! [`SHA-512`]: https://en.wikipedia.org/wiki/SHA-2#Pseudocode
+//! [`MdChip`]: gadgets::MdChip
+//! [`WordOpsChip`]: gadgets::WordOpsChip
+//! [`CallSlotsConfig`]: gadgets::CallSlotsConfig
+//! [`HashTable`]: gadgets::HashTable
+
+pub mod native;
+
+use std::marker::PhantomData;
+
+use gadgets::{
+    context::{assign_context, Context},
+    public_inputs::PublicInputs,
+    tables::FixedTables,
+    AssignedBytes, AssignedCall, BitFn, BitShift, CallSlotsConfig, Endianness, HashCircuitExt,
+    HashTable, HashTableConfig, MdChip, MdCompression, MdConfig, MdPadding, SlotPadding, WordCell,
+    WordOpsChip,
+};
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::Layouter,
+    plonk::{Any, Challenge, Column, ConstraintSystem, Error, FirstPhase},
+};
+
+pub type Sha512Table = HashTable;
+
+/// The SHA-512 compression function from the initial hash value `iv`, i.e.
+/// that of SHA-512 or of SHA-384, whose blocks are chained by [`MdChip`],
+/// and whose digest is the first `digest_words` words of the chaining value.
+#[derive(Clone, Copy, Debug)]
+pub struct Sha512Compression {
+    pub iv: [u64; 8],
+    pub digest_words: usize,
+}
+
+impl Sha512Compression {
+    /// `σ0` and `σ1` of the message schedule.
+    const SIGMA: [[BitShift; 3]; 2] = [
+        [
+            BitShift::RotateRight(1),
+            BitShift::RotateRight(8),
+            BitShift::ShiftRight(7),
+        ],
+        [
+            BitShift::RotateRight(19),
+            BitShift::RotateRight(61),
+            BitShift::ShiftRight(6),
+        ],
+    ];
+    /// `Σ0` and `Σ1` of the rounds.
+    const BIG_SIGMA: [[BitShift; 3]; 2] = [
+        [
+            BitShift::RotateRight(28),
+            BitShift::RotateRight(34),
+            BitShift::RotateRight(39),
+        ],
+        [
+            BitShift::RotateRight(14),
+            BitShift::RotateRight(18),
+            BitShift::RotateRight(41),
+        ],
+    ];
+}
+
+impl<F: FieldExt> MdCompression<F, 64> for Sha512Compression {
+    const STATE_WORDS: usize = 8;
+    const BLOCK_BYTES: usize = native::BLOCK_BYTES;
+    const ENDIANNESS: Endianness = Endianness::Big;
+
+    fn digest_words(&self) -> usize {
+        self.digest_words
+    }
+
+    fn shifts() -> Vec<Vec<BitShift>> {
+        Self::SIGMA
+            .iter()
+            .chain(Self::BIG_SIGMA.iter())
+            .map(|shifts| shifts.to_vec())
+            .collect()
+    }
+
+    fn fns() -> Vec<BitFn> {
+        vec![BitFn::CH, BitFn::MAJ]
+    }
+
+    fn iv(&self) -> Vec<u64> {
+        self.iv.to_vec()
+    }
+
+    fn pad(&self, input: &[u8]) -> Vec<u8> {
+        native::pad(input)
+    }
+
+    fn compress_rounds(&self, state: &[u64], block: &[u8]) -> Vec<u64> {
+        let state = state.try_into().expect("the state has 8 words");
+        native::compress_rounds(&state, block).to_vec()
+    }
+
+    /// Three rows per word of the message schedule past the block, and seven
+    /// per round.
+    fn rounds_rows(&self) -> usize {
+        3 * (native::NUM_ROUNDS - 16) + 7 * native::NUM_ROUNDS
+    }
+
+    fn assign_rounds(
+        &self,
+        chip: &WordOpsChip<F, 64>,
+        ctx: &mut Context<'_, '_, F>,
+        state: &[WordCell<F>],
+        block: &[WordCell<F>],
+    ) -> Result<Vec<WordCell<F>>, Error> {
+        let mut w = block.to_vec();
+        for i in 16..native::NUM_ROUNDS {
+            let s0 = chip.shift(ctx, &Self::SIGMA[0], &w[i - 15])?;
+            let s1 = chip.shift(ctx, &Self::SIGMA[1], &w[i - 2])?;
+            w.push(chip.add(ctx, &[&w[i - 16], &s0, &w[i - 7], &s1], 0)?);
+        }
+
+        let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut h]: [WordCell<F>; 8] =
+            state.to_vec().try_into().expect("the state has 8 words");
+        for (k, w) in native::ROUND_CONSTANTS.iter().zip(w.iter()) {
+            let s1 = chip.shift(ctx, &Self::BIG_SIGMA[1], &e)?;
+            let ch = chip.apply(ctx, BitFn::CH, &[&e, &f, &g])?;
+            let temp1 = chip.add(ctx, &[&h, &s1, &ch, w], *k)?;
+            let s0 = chip.shift(ctx, &Self::BIG_SIGMA[0], &a)?;
+            let maj = chip.apply(ctx, BitFn::MAJ, &[&a, &b, &c])?;
+
+            h = g;
+            g = f;
+            f = e;
+            e = chip.add(ctx, &[&d, &temp1], 0)?;
+            d = c;
+            c = b;
+            b = a;
+            a = chip.add(ctx, &[&temp1, &s0, &maj], 0)?;
+        }
+        Ok(vec![a, b, c, d, e, f, g, h])
+    }
+}
+
+/// The config of the SHA-512 circuit, or of the SHA-384 circuit when
+/// `DIGEST_BYTES` is 48.
+#[derive(Clone, Debug)]
+pub struct Sha512Config<F, const DIGEST_BYTES: usize = 64> {
+    table: HashTableConfig,
+    md: MdConfig<64>,
+    slots: CallSlotsConfig,
+    _marker: PhantomData<F>,
+}
+
+impl<F: FieldExt, const DIGEST_BYTES: usize> Sha512Config<F, DIGEST_BYTES> {
+    /// The padding of the blocks: a `0x80` byte, zeros and the big-endian
+    /// length of the input in bits, in 16 bytes.
+    const PADDING: MdPadding = MdPadding {
+        len_bytes: 16,
+        endianness: Endianness::Big,
+    };
+
+    /// Configures the circuit over `table`, constructed with
+    /// `Sha512Table::construct(meta, DIGEST_BYTES)`, and a challenge of its
+    /// own for the RLCs of the table.
+    pub fn configure(meta: &mut ConstraintSystem<F>, table: Sha512Table) -> Self {
+        let challenge = meta.challenge_usable_after(FirstPhase);
+        let table = HashTableConfig::configure(meta, table, challenge);
+        let md = MdConfig::configure::<F, Sha512Compression>(meta);
+        let slots = CallSlotsConfig::configure(
+            meta,
+            &table,
+            native::BLOCK_BYTES,
+            DIGEST_BYTES,
+            SlotPadding::Md(Self::PADDING),
+        );
+        Self {
+            table,
+            md,
+            slots,
+            _marker: PhantomData,
+        }
+    }
+
+    /// The table of the calls.
+    pub fn table(&self) -> &Sha512Table {
+        &self.table.table
+    }
+
+    /// The challenge of the RLCs of [`Self::table`].
+    pub fn challenge(&self) -> Challenge {
+        self.table.challenge
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Sha512Witness<F> {
+    pub inputs: Vec<Vec<u8>>,
+    pub _marker: PhantomData<F>,
+}
+
+/// Chip of the SHA-512 circuit, or of the SHA-384 circuit when `DIGEST_BYTES`
+/// is 48.
+#[derive(Clone, Debug)]
+pub struct Sha512Chip<F, const DIGEST_BYTES: usize = 64> {
+    config: Sha512Config<F, DIGEST_BYTES>,
+    data: Sha512Witness<F>,
+}
+
+pub type Sha384Chip<F> = Sha512Chip<F, 48>;
+
+impl<F: FieldExt, const DIGEST_BYTES: usize> Sha512Chip<F, DIGEST_BYTES> {
+    /// Initial hash value of the variant.
+    pub const IV: [u64; 8] = match DIGEST_BYTES {
+        48 => native::SHA384_IV,
+        64 => native::SHA512_IV,
+        _ => panic!("DIGEST_BYTES must be 48 (SHA-384) or 64 (SHA-512)"),
+    };
+
+    pub fn construct(config: Sha512Config<F, DIGEST_BYTES>, data: Sha512Witness<F>) -> Self {
+        Self { config, data }
+    }
+
+    pub fn load(&self, layouter: &mut impl Layouter<F>) -> Result<(), Error> {
+        self.assign_calls(layouter).map(|_| ())
+    }
+
+    /// Loads the calls of the witness as [`Self::load`] does, and returns the
+    /// cells of their inputs and digests.
+    pub fn assign_calls(
+        &self,
+        layouter: &mut impl Layouter<F>,
+    ) -> Result<Vec<AssignedCall<F>>, Error> {
+        let inputs = &self.data.inputs;
+        let lens: Vec<_> = inputs.iter().map(Vec::len).collect();
+        let slots = &self.config.slots;
+        let blocks = lens.iter().map(|len| slots.blocks(*len)).sum();
+        let plan = slots.plan(&lens, blocks);
+        let (calls, cells) = assign_context(layouter, "sha512 blocks", |ctx| {
+            self.md_chip().assign_slots(ctx, inputs, &plan)
+        })?;
+        slots.assign(layouter, "sha512", &self.config.table, &lens, &plan, &cells)?;
+        let table_calls: Vec<_> = inputs
+            .iter()
+            .map(|input| (input.clone(), Self::digest(input)))
+            .collect();
+        self.config
+            .table
+            .assign(layouter, "sha512", inputs.len(), &table_calls)?;
+        Ok(calls)
+    }
+
+    /// The digest of `input`, of SHA-512 or SHA-384.
+    pub fn digest(input: &[u8]) -> Vec<u8> {
+        native::digest(input, Self::IV, DIGEST_BYTES)
+    }
+
+    fn md_chip(&self) -> MdChip<F, Sha512Compression, 64> {
+        MdChip::construct(
+            self.config.md.clone(),
+            Sha512Compression {
+                iv: Self::IV,
+                digest_words: DIGEST_BYTES / 8,
+            },
+        )
+    }
+}
+
+impl<F: FieldExt, const DIGEST_BYTES: usize> HashCircuitExt<F> for Sha512Chip<F, DIGEST_BYTES> {
+    type Config = Sha512Config<F, DIGEST_BYTES>;
+    type Witness = Sha512Witness<F>;
+
+    const DIGEST_BYTES: usize = DIGEST_BYTES;
+
+    /// Configures the circuit over its table, exposed in its instance
+    /// columns.
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let mut config = Self::configure_with_tables(meta, &mut FixedTables::default());
+        config.table.expose(meta);
+        config
+    }
+
+    /// Configures the circuit over its table, not exposed, the circuit
+    /// having no fixed tables.
+    fn configure_with_tables(
+        meta: &mut ConstraintSystem<F>,
+        _tables: &mut FixedTables,
+    ) -> Self::Config {
+        let table = Sha512Table::construct(meta, DIGEST_BYTES);
+        Sha512Config::configure(meta, table)
+    }
+
+    /// The calls of the inputs of `witness`, in their order.
+    fn public_inputs(witness: &Self::Witness) -> Vec<Vec<F>> {
+        let mut public_inputs = PublicInputs::new(
+            DIGEST_BYTES,
+            native::BLOCK_BYTES,
+            SlotPadding::Md(Sha512Config::<F, DIGEST_BYTES>::PADDING),
+        );
+        for input in &witness.inputs {
+            public_inputs.call(input, &Self::digest(input));
+        }
+        public_inputs.build()
+    }
+
+    fn construct(config: Self::Config, witness: Self::Witness) -> Self {
+        Sha512Chip::construct(config, witness)
+    }
+
+    fn load(&self, layouter: &mut impl Layouter<F>) -> Result<(), Error> {
+        Sha512Chip::load(self, layouter)
+    }
+
+    fn table_columns(config: &Self::Config) -> Vec<Column<Any>> {
+        config.table().columns()
+    }
+
+    fn annotations(config: &Self::Config) -> Vec<String> {
+        config.table().annotations()
+    }
+
+    fn assigned_digest(
+        &self,
+        layouter: &mut impl Layouter<F>,
+    ) -> Result<Vec<AssignedBytes<F>>, Error> {
+        let calls = self.assign_calls(layouter)?;
+        Ok(calls.into_iter().map(|call| call.digest).collect())
+    }
+}
+
+#[cfg(any(feature = "test", test))]
+pub mod dev {
+    use super::*;
+
+    use ethers_core::utils::hex::FromHex;
+    use halo2_proofs::{circuit::SimpleFloorPlanner, plonk::Circuit};
+
+    fn unzip_vectors(vectors: &[(&str, &str)]) -> (Vec<Vec<u8>>, Vec<Vec<u8>>) {
+        vectors
+            .iter()
+            .map(|(input, output)| {
+                (
+                    input.as_bytes().to_vec(),
+                    Vec::from_hex(output).expect("digest is hex-encoded"),
+                )
+            })
+            .unzip()
+    }
+
+    lazy_static::lazy_static! {
+        pub static ref INPUTS_OUTPUTS: (Vec<Vec<u8>>, Vec<Vec<u8>>) = unzip_vectors(&[
+            (
+                "",
+                "cf83e1357eefb8bdf1542850d66d8007d620e4050b5715dc83f4a921d36ce9ce47d0d13c5d85f2b0ff8318d2877eec2f63b931bd47417a81a538327af927da3e",
+            ),
+            (
+                "abc",
+                "ddaf35a193617abacc417349ae20413112e6fa4e89a97ea20a9eeee64b55d39a2192992a274fc1a836ba3c23a3feebbd454d4423643ce80e2a9ac94fa54ca49f",
+            ),
+            (
+                "abcdefghbcdefghicdefghijdefghijkefghijklfghijklmghijklmnhijklmnoijklmnopjklmnopqklmnopqrlmnopqrsmnopqrstnopqrstu",
+                "8e959b75dae313da8cf4f72814fc143f8f7779c6eb9f7fa17299aeadb6889018501d289e4900f7e4331b99dec4b5433ac7d329eeb6dd26545e96e55b874be909",
+            ),
+        ]);
+
+        pub static ref SHA384_INPUTS_OUTPUTS: (Vec<Vec<u8>>, Vec<Vec<u8>>) = unzip_vectors(&[
+            (
+                "",
+                "38b060a751ac96384cd9327eb1b1e36a21fdb71114be07434c0cc7bf63f6e1da274edebfe76f65fbd51ad2f14898b95b",
+            ),
+            (
+                "abc",
+                "cb00753f45a35e8bb5a03d699ac65007272c32ab0eded1631a8b605a43ff5bed8086072ba1e7cc2358baeca134c825a7",
+            ),
+            (
+                "abcdefghbcdefghicdefghijdefghijkefghijklfghijklmghijklmnhijklmnoijklmnopjklmnopqklmnopqrlmnopqrsmnopqrstnopqrstu",
+                "09330c33f71147e83d192fc782cd1b4753111b173b3b05d22fa08086e3b0f712fcc7c71a557e2db966c3e9fa91746039",
+            ),
+        ]);
+    }
+
+    #[derive(Default)]
+    pub struct Sha512TestCircuit<F, const DIGEST_BYTES: usize = 64> {
+        pub inputs: Vec<Vec<u8>>,
+        pub outputs: Vec<Vec<u8>>,
+        pub _marker: PhantomData<F>,
+    }
+
+    pub type Sha384TestCircuit<F> = Sha512TestCircuit<F, 48>;
+
+    impl<F: FieldExt, const DIGEST_BYTES: usize> Circuit<F> for Sha512TestCircuit<F, DIGEST_BYTES> {
+        type Config = Sha512Config<F, DIGEST_BYTES>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            let sha512_table = Sha512Table::construct(meta, DIGEST_BYTES);
+            Sha512Config::configure(meta, sha512_table)
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            let chip = Sha512Chip::<F, DIGEST_BYTES>::construct(
+                config,
+                Sha512Witness {
+                    inputs: self.inputs.clone(),
+                    _marker: PhantomData,
+                },
+            );
+            chip.load(&mut layouter)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use gadgets::{DigestCircuit, HashCircuitExt};
+    use halo2_proofs::{
+        arithmetic::FieldExt,
+        dev::MockProver,
+        halo2curves::{bn256::Fr, pasta::pallas},
+        plonk::ConstraintSystem,
+    };
+    use std::marker::PhantomData;
+
+    use crate::{
+        dev::{Sha384TestCircuit, Sha512TestCircuit, INPUTS_OUTPUTS, SHA384_INPUTS_OUTPUTS},
+        native, Sha384Chip, Sha512Chip, Sha512Witness,
+    };
+
+    #[test]
+    fn test_sha512_native() {
+        let (inputs, outputs) = INPUTS_OUTPUTS.clone();
+        for (input, output) in inputs.iter().zip(outputs) {
+            assert_eq!(native::sha512(input).to_vec(), output);
+        }
+
+        let (inputs, outputs) = SHA384_INPUTS_OUTPUTS.clone();
+        for (input, output) in inputs.iter().zip(outputs) {
+            assert_eq!(native::sha384(input).to_vec(), output);
+        }
+    }
+
+    fn test_sha512_circuit_over<F: FieldExt>() {
+        let (inputs, outputs) = INPUTS_OUTPUTS.clone();
+        let k = Sha512Chip::<F>::min_k(&Sha512Witness {
+            inputs: inputs.clone(),
+            _marker: PhantomData,
+        });
+
+        let circuit: Sha512TestCircuit<F> = Sha512TestCircuit {
+            inputs,
+            outputs,
+            _marker: PhantomData,
+        };
+
+        let prover = MockProver::run(k, &circuit, vec![]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+    }
+
+    fn test_sha384_circuit_over<F: FieldExt>() {
+        let (inputs, outputs) = SHA384_INPUTS_OUTPUTS.clone();
+        let k = Sha384Chip::<F>::min_k(&Sha512Witness {
+            inputs: inputs.clone(),
+            _marker: PhantomData,
+        });
+
+        let circuit: Sha384TestCircuit<F> = Sha384TestCircuit {
+            inputs,
+            outputs,
+            _marker: PhantomData,
+        };
+
+        let prover = MockProver::run(k, &circuit, vec![]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+    }
+
+    #[test]
+    fn test_sha512_assigned_digest() {
+        let (inputs, outputs) = SHA384_INPUTS_OUTPUTS.clone();
+        let witness = Sha512Witness::<Fr> {
+            inputs,
+            _marker: PhantomData,
+        };
+        let verify = |digests: Vec<Vec<u8>>| {
+            let circuit = DigestCircuit::<Fr, Sha384Chip<Fr>> {
+                witness: witness.clone(),
+                digests,
+                _marker: PhantomData,
+            };
+            let k = Sha384Chip::<Fr>::min_k(&witness);
+            let instances = Sha384Chip::<Fr>::public_inputs(&witness);
+            MockProver::run(k, &circuit, instances).unwrap().verify()
+        };
+        let mut digests = outputs;
+        assert_eq!(verify(digests.clone()), Ok(()));
+
+        // a byte of a truncated digest is not the one of the call.
+        digests[2][47] ^= 1;
+        assert!(verify(digests).is_err());
+    }
+
+    #[test]
+    fn test_sha512_hash_circuit_ext() {
+        let mut meta = ConstraintSystem::<Fr>::default();
+        let config = Sha512Chip::<Fr>::configure(&mut meta);
+        assert_eq!(
+            Sha512Chip::<Fr>::table_columns(&config).len(),
+            Sha512Chip::<Fr>::annotations(&config).len()
+        );
+        assert_eq!(Sha512Chip::<Fr>::DIGEST_BYTES, 64);
+        assert_eq!(Sha384Chip::<Fr>::DIGEST_BYTES, 48);
+    }
+
+    #[test]
+    fn test_sha512_circuit() {
+        test_sha512_circuit_over::<Fr>();
+        test_sha512_circuit_over::<pallas::Base>();
+    }
+
+    #[test]
+    fn test_sha384_circuit() {
+        test_sha384_circuit_over::<Fr>();
+        test_sha384_circuit_over::<pallas::Base>();
+    }
+}
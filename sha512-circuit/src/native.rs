@@ -0,0 +1,226 @@
+//! Native implementation of SHA-512 and SHA-384, used to compute the witness of
+//! the circuit and as the reference it is tested against.
+
+/// Size of a message block in bytes.
+pub const BLOCK_BYTES: usize = 128;
+
+/// Number of rounds of the compression function.
+pub const NUM_ROUNDS: usize = 80;
+
+/// Round constants, i.e. the first 64 bits of the fractional parts of the cube
+/// roots of the first 80 primes.
+pub const ROUND_CONSTANTS: [u64; NUM_ROUNDS] = [
+    0x428a2f98d728ae22,
+    0x7137449123ef65cd,
+    0xb5c0fbcfec4d3b2f,
+    0xe9b5dba58189dbbc,
+    0x3956c25bf348b538,
+    0x59f111f1b605d019,
+    0x923f82a4af194f9b,
+    0xab1c5ed5da6d8118,
+    0xd807aa98a3030242,
+    0x12835b0145706fbe,
+    0x243185be4ee4b28c,
+    0x550c7dc3d5ffb4e2,
+    0x72be5d74f27b896f,
+    0x80deb1fe3b1696b1,
+    0x9bdc06a725c71235,
+    0xc19bf174cf692694,
+    0xe49b69c19ef14ad2,
+    0xefbe4786384f25e3,
+    0x0fc19dc68b8cd5b5,
+    0x240ca1cc77ac9c65,
+    0x2de92c6f592b0275,
+    0x4a7484aa6ea6e483,
+    0x5cb0a9dcbd41fbd4,
+    0x76f988da831153b5,
+    0x983e5152ee66dfab,
+    0xa831c66d2db43210,
+    0xb00327c898fb213f,
+    0xbf597fc7beef0ee4,
+    0xc6e00bf33da88fc2,
+    0xd5a79147930aa725,
+    0x06ca6351e003826f,
+    0x142929670a0e6e70,
+    0x27b70a8546d22ffc,
+    0x2e1b21385c26c926,
+    0x4d2c6dfc5ac42aed,
+    0x53380d139d95b3df,
+    0x650a73548baf63de,
+    0x766a0abb3c77b2a8,
+    0x81c2c92e47edaee6,
+    0x92722c851482353b,
+    0xa2bfe8a14cf10364,
+    0xa81a664bbc423001,
+    0xc24b8b70d0f89791,
+    0xc76c51a30654be30,
+    0xd192e819d6ef5218,
+    0xd69906245565a910,
+    0xf40e35855771202a,
+    0x106aa07032bbd1b8,
+    0x19a4c116b8d2d0c8,
+    0x1e376c085141ab53,
+    0x2748774cdf8eeb99,
+    0x34b0bcb5e19b48a8,
+    0x391c0cb3c5c95a63,
+    0x4ed8aa4ae3418acb,
+    0x5b9cca4f7763e373,
+    0x682e6ff3d6b2b8a3,
+    0x748f82ee5defb2fc,
+    0x78a5636f43172f60,
+    0x84c87814a1f0ab72,
+    0x8cc702081a6439ec,
+    0x90befffa23631e28,
+    0xa4506cebde82bde9,
+    0xbef9a3f7b2c67915,
+    0xc67178f2e372532b,
+    0xca273eceea26619c,
+    0xd186b8c721c0c207,
+    0xeada7dd6cde0eb1e,
+    0xf57d4f7fee6ed178,
+    0x06f067aa72176fba,
+    0x0a637dc5a2c898a6,
+    0x113f9804bef90dae,
+    0x1b710b35131c471b,
+    0x28db77f523047d84,
+    0x32caab7b40c72493,
+    0x3c9ebe0a15c9bebc,
+    0x431d67c49c100d4c,
+    0x4cc5d4becb3e42b6,
+    0x597f299cfc657e2a,
+    0x5fcb6fab3ad6faec,
+    0x6c44198c4a475817,
+];
+
+/// Initial hash value of SHA-512.
+pub const SHA512_IV: [u64; 8] = [
+    0x6a09e667f3bcc908,
+    0xbb67ae8584caa73b,
+    0x3c6ef372fe94f82b,
+    0xa54ff53a5f1d36f1,
+    0x510e527fade682d1,
+    0x9b05688c2b3e6c1f,
+    0x1f83d9abfb41bd6b,
+    0x5be0cd19137e2179,
+];
+
+/// Initial hash value of SHA-384.
+pub const SHA384_IV: [u64; 8] = [
+    0xcbbb9d5dc1059ed8,
+    0x629a292a367cd507,
+    0x9159015a3070dd17,
+    0x152fecd8f70e5939,
+    0x67332667ffc00b31,
+    0x8eb44a8768581511,
+    0xdb0c2e0d64f98fa7,
+    0x47b5481dbefa4fa4,
+];
+
+/// Pads `input` to a multiple of [`BLOCK_BYTES`] bytes: a `1` bit, zeros, and
+/// the length of the input in bits as a 128-bit big-endian integer.
+pub fn pad(input: &[u8]) -> Vec<u8> {
+    let mut padded = input.to_vec();
+    padded.push(0x80);
+    padded.resize(
+        (padded.len() + 16).div_ceil(BLOCK_BYTES) * BLOCK_BYTES - 16,
+        0,
+    );
+    padded.extend_from_slice(&((input.len() as u128) * 8).to_be_bytes());
+    padded
+}
+
+/// Expands a block into the 80 words of the message schedule.
+pub fn message_schedule(block: &[u8]) -> [u64; NUM_ROUNDS] {
+    assert_eq!(block.len(), BLOCK_BYTES);
+    let mut w = [0u64; NUM_ROUNDS];
+    for (word, bytes) in w.iter_mut().zip(block.chunks(8)) {
+        *word = u64::from_be_bytes(bytes.try_into().expect("words are 8 bytes"));
+    }
+    for i in 16..NUM_ROUNDS {
+        let s0 = w[i - 15].rotate_right(1) ^ w[i - 15].rotate_right(8) ^ (w[i - 15] >> 7);
+        let s1 = w[i - 2].rotate_right(19) ^ w[i - 2].rotate_right(61) ^ (w[i - 2] >> 6);
+        w[i] = w[i - 16]
+            .wrapping_add(s0)
+            .wrapping_add(w[i - 7])
+            .wrapping_add(s1);
+    }
+    w
+}
+
+/// Applies the 80 rounds of the compression function to `state`, returning
+/// the working variables before they are added back to `state`.
+pub fn compress_rounds(state: &[u64; 8], block: &[u8]) -> [u64; 8] {
+    let w = message_schedule(block);
+    let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut h] = *state;
+    for (k, w) in ROUND_CONSTANTS.iter().zip(w) {
+        let s1 = e.rotate_right(14) ^ e.rotate_right(18) ^ e.rotate_right(41);
+        let ch = (e & f) ^ (!e & g);
+        let temp1 = h
+            .wrapping_add(s1)
+            .wrapping_add(ch)
+            .wrapping_add(*k)
+            .wrapping_add(w);
+        let s0 = a.rotate_right(28) ^ a.rotate_right(34) ^ a.rotate_right(39);
+        let maj = (a & b) ^ (a & c) ^ (b & c);
+        let temp2 = s0.wrapping_add(maj);
+
+        h = g;
+        g = f;
+        f = e;
+        e = d.wrapping_add(temp1);
+        d = c;
+        c = b;
+        b = a;
+        a = temp1.wrapping_add(temp2);
+    }
+    [a, b, c, d, e, f, g, h]
+}
+
+/// Compresses `block` into `state`.
+pub fn compress(state: &mut [u64; 8], block: &[u8]) {
+    let working = compress_rounds(state, block);
+    for (word, working) in state.iter_mut().zip(working) {
+        *word = word.wrapping_add(working);
+    }
+}
+
+/// Hashes `input` from the initial hash value `iv`, truncating the final
+/// state to `digest_bytes` bytes.
+pub fn digest(input: &[u8], iv: [u64; 8], digest_bytes: usize) -> Vec<u8> {
+    let mut state = iv;
+    for block in pad(input).chunks(BLOCK_BYTES) {
+        compress(&mut state, block);
+    }
+    state
+        .iter()
+        .flat_map(|word| word.to_be_bytes())
+        .take(digest_bytes)
+        .collect()
+}
+
+/// Computes the SHA-512 digest of `input`.
+pub fn sha512(input: &[u8]) -> [u8; 64] {
+    digest(input, SHA512_IV, 64)
+        .try_into()
+        .expect("SHA-512 digest is 64 bytes")
+}
+
+/// Computes the SHA-384 digest of `input`.
+pub fn sha384(input: &[u8]) -> [u8; 48] {
+    digest(input, SHA384_IV, 48)
+        .try_into()
+        .expect("SHA-384 digest is 48 bytes")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pad() {
+        assert_eq!(pad(&[]).len(), BLOCK_BYTES);
+        assert_eq!(pad(&[0; BLOCK_BYTES - 17]).len(), BLOCK_BYTES);
+        assert_eq!(pad(&[0; BLOCK_BYTES - 16]).len(), 2 * BLOCK_BYTES);
+        assert_eq!(pad(b"abc")[BLOCK_BYTES - 1], 24);
+    }
+}
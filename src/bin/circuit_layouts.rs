@@ -0,0 +1,157 @@
+//! Renders the layout of the residue-pattern, SHA2-256, BLAKE2F and
+//! RIPEMD-160 circuits, assigned with their test vectors, to PNG files: the
+//! regions, the columns they use and the cells they assign, to check how
+//! tightly a layout packs its rows while designing it.
+//!
+//! ```text
+//! cargo run --features dev-graph --bin circuit_layouts -- layouts
+//! ```
+//!
+//! The images are written to the directory given, `layouts` by default, one
+//! per circuit.
+
+use std::{env::args, error::Error, fs, marker::PhantomData, path::Path};
+
+use examples::{ResiduePatternChip, ResiduePatternConfig};
+use gadgets::HashCircuitExt;
+use halo2_proofs::{
+    arithmetic::Field,
+    circuit::{Layouter, SimpleFloorPlanner},
+    dev::CircuitLayout,
+    halo2curves::bn256::Fr,
+    plonk::{Circuit, ConstraintSystem, Error as PlonkError},
+};
+use plotters::prelude::{BitMapBackend, IntoDrawingArea, WHITE};
+
+use blake2f_circuit::{
+    dev::INPUTS_OUTPUTS as BLAKE2F_INPUTS_OUTPUTS, Blake2fChip, Blake2fTestCircuit,
+};
+use ripemd160_circuit::{
+    dev::INPUTS_OUTPUTS as RIPEMD160_INPUTS_OUTPUTS, Ripemd160Chip, Ripemd160TestCircuit,
+    Ripemd160Witness,
+};
+use sha2_256_circuit::{
+    dev::INPUTS_OUTPUTS as SHA2_INPUTS_OUTPUTS, Sha2Chip, Sha2TestCircuit, Sha2Witness,
+};
+
+/// Size of the images, in pixels.
+const SIZE: (u32, u32) = (1024, 768);
+
+/// Bits of the pattern of every value of the residue-pattern circuit.
+const LENGTH: usize = 64;
+
+/// The residue patterns of a few values, a row per bit.
+struct ResiduePatternCircuit {
+    values: Vec<Fr>,
+}
+
+impl Circuit<Fr> for ResiduePatternCircuit {
+    type Config = ResiduePatternConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self {
+            values: vec![Fr::zero(); self.values.len()],
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+        ResiduePatternConfig::configure(meta, nonresidue(), LENGTH)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<Fr>,
+    ) -> Result<(), PlonkError> {
+        let chip = ResiduePatternChip::new(config, nonresidue());
+        chip.assign(&mut layouter, &self.values)?;
+        Ok(())
+    }
+}
+
+fn nonresidue() -> Fr {
+    Fr::from(5)
+}
+
+/// Renders the layout of `circuit` at degree `k` to `path`, titled `name`.
+fn render<C: Circuit<Fr>>(
+    path: &Path,
+    name: &str,
+    k: u32,
+    circuit: &C,
+) -> Result<(), Box<dyn Error>> {
+    let root = BitMapBackend::new(path, SIZE).into_drawing_area();
+    root.fill(&WHITE)?;
+    let root = root.titled(name, ("sans-serif", 40))?;
+    CircuitLayout::default()
+        .show_labels(true)
+        .mark_equality_cells(true)
+        .render(k, circuit, &root)?;
+    root.present()?;
+    Ok(())
+}
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let dir = args().nth(1).unwrap_or_else(|| "layouts".to_string());
+    let dir = Path::new(&dir);
+    fs::create_dir_all(dir)?;
+
+    let values = [0, 2323, 3].map(Fr::from).to_vec();
+    // The rows of the patterns, and as many left for the blinding factors.
+    let k = (values.len() * LENGTH).next_power_of_two().trailing_zeros() + 1;
+    render(
+        &dir.join("residue_pattern.png"),
+        "Residue pattern",
+        k,
+        &ResiduePatternCircuit { values },
+    )?;
+
+    let (inputs, outputs) = SHA2_INPUTS_OUTPUTS.clone();
+    let k = Sha2Chip::<Fr>::min_k(&Sha2Witness {
+        inputs: inputs.clone(),
+        _marker: PhantomData,
+    });
+    render(
+        &dir.join("sha2_256.png"),
+        "SHA2-256",
+        k,
+        &Sha2TestCircuit::<Fr> {
+            inputs,
+            outputs,
+            _marker: PhantomData,
+        },
+    )?;
+
+    let (inputs, outputs) = BLAKE2F_INPUTS_OUTPUTS.clone();
+    let k = Blake2fChip::<Fr>::min_k(&inputs);
+    render(
+        &dir.join("blake2f.png"),
+        "BLAKE2F",
+        k,
+        &Blake2fTestCircuit::<Fr> {
+            inputs,
+            outputs,
+            _marker: PhantomData,
+        },
+    )?;
+
+    let (inputs, outputs) = RIPEMD160_INPUTS_OUTPUTS.clone();
+    let k = Ripemd160Chip::<Fr>::min_k(&Ripemd160Witness {
+        inputs: inputs.clone(),
+        _marker: PhantomData,
+    });
+    render(
+        &dir.join("ripemd160.png"),
+        "RIPEMD-160",
+        k,
+        &Ripemd160TestCircuit::<Fr> {
+            inputs,
+            outputs,
+            _marker: PhantomData,
+        },
+    )?;
+
+    println!("Wrote the layouts to {}", dir.display());
+    Ok(())
+}
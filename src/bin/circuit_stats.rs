@@ -0,0 +1,14 @@
+//! Prints the columns, gates, lookups and degree of every circuit of the
+//! workspace as a markdown table, to track their cost as constraints are
+//! added:
+//!
+//! ```text
+//! cargo run --bin circuit_stats
+//! ```
+
+use gadgets::CircuitStats;
+use zkp_mooc_halo2::budgets::circuit_stats;
+
+fn main() {
+    println!("{}", CircuitStats::report(&circuit_stats()));
+}
@@ -0,0 +1,100 @@
+//! Writes the constraint graph of every circuit of the workspace in the dot
+//! format of Graphviz, one file per circuit, for auditors to see which
+//! columns feed which gates and lookups, see [`gadgets::dot`]:
+//!
+//! ```text
+//! cargo run --features dev-graph --bin constraint_graphs -- graphs
+//! dot -Tsvg graphs/sha2_256.dot -o sha2_256.svg
+//! ```
+//!
+//! The graphs are written to the directory given, `graphs` by default.
+
+use std::{env::args, fs, io, path::Path};
+
+use gadgets::{dot::constraint_graph, HashCircuitExt};
+use halo2_proofs::halo2curves::bn256::Fr;
+
+use aggregation_circuit::AggregationConfig;
+use blake2f_circuit::Blake2fChip;
+use blake3_circuit::Blake3Chip;
+use hash160_circuit::Hash160Chip;
+use keccak256_circuit::KeccakChip;
+use md5_circuit::Md5Chip;
+use merkle_circuit::{MerklePathConfig, MerkleTable};
+use modexp_circuit::{ModExpConfig, ModExpTable};
+use poseidon_circuit::PoseidonChip;
+use precompile_super_circuit::SuperCircuitConfig;
+use ripemd160_circuit::Ripemd160Chip;
+use sha1_circuit::Sha1Chip;
+use sha2_256_circuit::Sha2Chip;
+use sha512_circuit::{Sha384Chip, Sha512Chip};
+
+fn main() -> io::Result<()> {
+    let dir = args().nth(1).unwrap_or_else(|| "graphs".to_string());
+    let dir = Path::new(&dir);
+    fs::create_dir_all(dir)?;
+
+    let graphs = [
+        ("blake2f", Blake2fChip::<Fr>::constraint_graph("BLAKE2F")),
+        ("blake3", Blake3Chip::<Fr>::constraint_graph("BLAKE3")),
+        ("hash160", Hash160Chip::<Fr>::constraint_graph("HASH160")),
+        (
+            "keccak256",
+            KeccakChip::<Fr>::constraint_graph("Keccak-256"),
+        ),
+        ("md5", Md5Chip::<Fr>::constraint_graph("MD5")),
+        (
+            "poseidon",
+            PoseidonChip::<Fr, 3>::constraint_graph("Poseidon"),
+        ),
+        (
+            "ripemd160",
+            Ripemd160Chip::<Fr>::constraint_graph("RIPEMD-160"),
+        ),
+        ("sha1", Sha1Chip::<Fr>::constraint_graph("SHA-1")),
+        ("sha2_256", Sha2Chip::<Fr>::constraint_graph("SHA2-256")),
+        ("sha384", Sha384Chip::<Fr>::constraint_graph("SHA-384")),
+        ("sha512", Sha512Chip::<Fr>::constraint_graph("SHA-512")),
+        (
+            "modexp",
+            constraint_graph::<Fr, _>("MODEXP", |meta| {
+                let table = ModExpTable::construct(meta);
+                ModExpConfig::configure(meta, table)
+            }),
+        ),
+        (
+            "merkle_poseidon",
+            constraint_graph::<Fr, _>("Merkle (Poseidon)", |meta| {
+                let table = MerkleTable::construct(meta);
+                MerklePathConfig::<Fr, PoseidonChip<Fr, 3>>::configure(meta, table)
+            }),
+        ),
+        (
+            "merkle_sha2_256",
+            constraint_graph::<Fr, _>("Merkle (SHA2-256)", |meta| {
+                let table = MerkleTable::construct(meta);
+                MerklePathConfig::<Fr, Sha2Chip<Fr>>::configure(meta, table)
+            }),
+        ),
+        (
+            "precompile_super_circuit",
+            constraint_graph(
+                "Precompile super circuit",
+                SuperCircuitConfig::<Fr>::configure,
+            ),
+        ),
+        (
+            "aggregation",
+            constraint_graph("Aggregation", AggregationConfig::configure),
+        ),
+    ];
+    for (name, graph) in &graphs {
+        fs::write(dir.join(format!("{name}.dot")), graph)?;
+    }
+    println!(
+        "Wrote the constraint graphs of {} circuits to {}",
+        graphs.len(),
+        dir.display()
+    );
+    Ok(())
+}
@@ -0,0 +1,64 @@
+//! The stats of every circuit of the workspace, and the budgets of their
+//! columns, lookups and degree.
+//!
+//! [`BUDGETS`] is the documented cost of each circuit, which the tests check
+//! [`circuit_stats`] against, so that a change to the constraints of a
+//! circuit exceeding its budget fails them, and its budget is only raised
+//! deliberately. The budgets leave little headroom over the current stats.
+
+use gadgets::{CircuitBudget, CircuitStats};
+
+use crate::circuits::CIRCUITS;
+
+/// The stats of every circuit of the workspace, by name.
+pub fn circuit_stats() -> Vec<(&'static str, CircuitStats)> {
+    CIRCUITS
+        .iter()
+        .map(|(name, configure)| (*name, CircuitStats::of(configure)))
+        .collect()
+}
+
+/// The budget of `advice` and `fixed` columns, lookups and degree.
+const fn budget(advice: usize, fixed: usize, lookups: usize, degree: usize) -> CircuitBudget {
+    CircuitBudget {
+        advice_columns: advice,
+        fixed_columns: fixed,
+        lookups,
+        degree,
+    }
+}
+
+/// The budget of every circuit of [`CIRCUITS`], by name: its most
+/// advice columns, fixed columns, lookups and degree.
+pub const BUDGETS: [(&str, CircuitBudget); 15] = [
+    ("blake2f", budget(237, 3, 2, 5)),
+    ("blake3", budget(135, 3, 2, 5)),
+    ("hash160", budget(271, 7, 5, 5)),
+    ("keccak256", budget(252, 3, 36, 5)),
+    ("md5", budget(134, 3, 2, 5)),
+    ("poseidon", budget(52, 4, 36, 6)),
+    ("ripemd160", budget(135, 3, 2, 5)),
+    ("sha1", budget(135, 3, 2, 5)),
+    ("sha2-256", budget(135, 3, 2, 5)),
+    ("sha384", budget(236, 3, 2, 5)),
+    ("sha512", budget(237, 3, 2, 5)),
+    ("modexp", budget(101, 2, 10, 5)),
+    ("merkle (poseidon)", budget(64, 4, 36, 6)),
+    ("merkle (sha2-256)", budget(150, 4, 3, 5)),
+    ("precompile super circuit", budget(506, 9, 9, 5)),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_circuit_budgets() {
+        let stats = circuit_stats();
+        assert_eq!(stats.len(), BUDGETS.len(), "a circuit without a budget");
+        for ((name, stats), (budget_name, budget)) in stats.iter().zip(&BUDGETS) {
+            assert_eq!(name, budget_name);
+            stats.assert_within(name, budget);
+        }
+    }
+}
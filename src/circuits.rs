@@ -0,0 +1,77 @@
+//! The configuration of every circuit of the workspace, by name, for the
+//! checks over all of them: the budgets of their stats, see
+//! [`crate::budgets`], and the golden files of their constraint systems, see
+//! [`crate::golden`].
+
+use gadgets::HashCircuitExt;
+use halo2_proofs::{halo2curves::bn256::Fr, plonk::ConstraintSystem};
+
+use blake2f_circuit::Blake2fChip;
+use blake3_circuit::Blake3Chip;
+use hash160_circuit::Hash160Chip;
+use keccak256_circuit::KeccakChip;
+use md5_circuit::Md5Chip;
+use merkle_circuit::{MerklePathConfig, MerkleTable};
+use modexp_circuit::{ModExpConfig, ModExpTable};
+use poseidon_circuit::PoseidonChip;
+use precompile_super_circuit::SuperCircuitConfig;
+use ripemd160_circuit::Ripemd160Chip;
+use sha1_circuit::Sha1Chip;
+use sha2_256_circuit::Sha2Chip;
+use sha512_circuit::{Sha384Chip, Sha512Chip};
+
+/// Configures a circuit over a constraint system.
+pub type Configure = fn(&mut ConstraintSystem<Fr>);
+
+/// Every circuit of the workspace, by name, but the aggregation circuit,
+/// whose constraints are those of the chips of `halo2wrong`.
+pub const CIRCUITS: [(&str, Configure); 15] = [
+    ("blake2f", |meta| {
+        Blake2fChip::<Fr>::configure(meta);
+    }),
+    ("blake3", |meta| {
+        Blake3Chip::<Fr>::configure(meta);
+    }),
+    ("hash160", |meta| {
+        Hash160Chip::<Fr>::configure(meta);
+    }),
+    ("keccak256", |meta| {
+        KeccakChip::<Fr>::configure(meta);
+    }),
+    ("md5", |meta| {
+        Md5Chip::<Fr>::configure(meta);
+    }),
+    ("poseidon", |meta| {
+        PoseidonChip::<Fr, 3>::configure(meta);
+    }),
+    ("ripemd160", |meta| {
+        Ripemd160Chip::<Fr>::configure(meta);
+    }),
+    ("sha1", |meta| {
+        Sha1Chip::<Fr>::configure(meta);
+    }),
+    ("sha2-256", |meta| {
+        Sha2Chip::<Fr>::configure(meta);
+    }),
+    ("sha384", |meta| {
+        Sha384Chip::<Fr>::configure(meta);
+    }),
+    ("sha512", |meta| {
+        Sha512Chip::<Fr>::configure(meta);
+    }),
+    ("modexp", |meta| {
+        let table = ModExpTable::construct(meta);
+        ModExpConfig::configure(meta, table);
+    }),
+    ("merkle (poseidon)", |meta| {
+        let table = MerkleTable::construct(meta);
+        MerklePathConfig::<Fr, PoseidonChip<Fr, 3>>::configure(meta, table);
+    }),
+    ("merkle (sha2-256)", |meta| {
+        let table = MerkleTable::construct(meta);
+        MerklePathConfig::<Fr, Sha2Chip<Fr>>::configure(meta, table);
+    }),
+    ("precompile super circuit", |meta| {
+        SuperCircuitConfig::<Fr>::configure(meta);
+    }),
+];
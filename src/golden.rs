@@ -0,0 +1,54 @@
+//! The golden files of the constraint systems of every circuit of
+//! [`CIRCUITS`], in `golden/`, which the tests compare the constraint systems
+//! with, see [`test_utils::golden`]. After an intended change to the
+//! constraints of a circuit, its golden file is written again with
+//!
+//! ```text
+//! UPDATE_GOLDEN=1 cargo test --lib golden
+//! ```
+//!
+//! and its diff is reviewed along with the change.
+
+use std::path::PathBuf;
+
+use crate::circuits::CIRCUITS;
+
+/// The golden file of the circuit `name`, e.g. `golden/merkle-poseidon.txt`
+/// for `merkle (poseidon)`.
+pub fn golden_path(name: &str) -> PathBuf {
+    let slug: String = name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+        .collect();
+    let slug: Vec<&str> = slug.split('-').filter(|part| !part.is_empty()).collect();
+    [
+        env!("CARGO_MANIFEST_DIR"),
+        "golden",
+        &format!("{}.txt", slug.join("-")),
+    ]
+    .iter()
+    .collect()
+}
+
+/// The golden files of all the circuits, by name.
+pub fn golden_paths() -> Vec<(&'static str, PathBuf)> {
+    CIRCUITS
+        .iter()
+        .map(|(name, _)| (*name, golden_path(name)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_utils::golden::{assert_golden, summary};
+
+    #[test]
+    fn test_golden_constraint_systems() {
+        for ((_, configure), (name, path)) in CIRCUITS.iter().zip(golden_paths()) {
+            assert!(path.ends_with(golden_path(name)));
+            assert_golden(&path, &summary(configure));
+        }
+        assert!(golden_path("merkle (sha2-256)").ends_with("golden/merkle-sha2-256.txt"));
+    }
+}
@@ -1,5 +1,45 @@
+pub mod budgets;
+pub mod circuits;
+pub mod golden;
+
+pub use aggregation_circuit;
+
 pub use blake2f_circuit;
 
+pub use blake3_circuit;
+
+pub use constraint_builder;
+
+pub use ecrecover_circuit;
+
+pub use gadgets;
+
+pub use hash160_circuit;
+
+pub use keccak256_circuit;
+
+pub use md5_circuit;
+
+pub use merkle_circuit;
+
+pub use modexp_circuit;
+
+pub use mutation_coverage;
+
+pub use non_native_field;
+
+pub use poseidon_circuit;
+
+pub use precompile_super_circuit;
+
+pub use precompile_table;
+
 pub use ripemd160_circuit;
 
+pub use rlc_chip;
+
+pub use sha1_circuit;
+
 pub use sha2_256_circuit;
+
+pub use sha512_circuit;
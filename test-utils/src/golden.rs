@@ -0,0 +1,171 @@
+//! Golden files of the constraint systems of the circuits, so that a change
+//! to the gates, queries or lookups of a circuit is a reviewable diff of its
+//! golden file rather than a silent change of its behavior.
+//!
+//! [`summary`] writes the constraint system of a configuration as text, a
+//! line per column kind, constraint, query, lookup and permutation column,
+//! which [`assert_golden`] compares with the golden file of the circuit. A
+//! test run with `UPDATE_GOLDEN=1` writes the golden files instead, for their
+//! diff to be committed along with the change to the constraints.
+
+use std::{fmt::Write as _, fs, path::Path};
+
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    plonk::{Any, Column, ConstraintSystem},
+};
+
+/// The variable which, when set, makes [`assert_golden`] write the golden
+/// files rather than compare with them.
+pub const UPDATE_GOLDEN: &str = "UPDATE_GOLDEN";
+
+/// The constraint system of the configuration built by `configure` over an
+/// empty constraint system, as the text of its golden file.
+pub fn summary<F: FieldExt, C>(configure: impl FnOnce(&mut ConstraintSystem<F>) -> C) -> String {
+    let mut meta = ConstraintSystem::default();
+    configure(&mut meta);
+
+    let mut summary = String::new();
+    let mut line = |line: String| {
+        summary.push_str(&line);
+        summary.push('\n');
+    };
+    line(format!("advice phases: {:?}", meta.advice_column_phase()));
+    line(format!("fixed columns: {}", meta.num_fixed_columns()));
+    line(format!("instance columns: {}", meta.num_instance_columns()));
+    line(format!("selectors: {}", meta.num_selectors()));
+    line(format!("challenge phases: {:?}", meta.challenge_phase()));
+    line(format!("degree: {}", meta.degree()));
+    for gate in meta.gates() {
+        for (i, polynomial) in gate.polynomials().iter().enumerate() {
+            line(format!(
+                "gate {:?}, constraint {:?}: {}",
+                gate.name(),
+                gate.constraint_name(i),
+                polynomial.identifier()
+            ));
+        }
+    }
+    let queries = meta
+        .advice_queries()
+        .iter()
+        .map(|(column, rotation)| (Column::<Any>::from(*column), rotation))
+        .chain(
+            meta.fixed_queries()
+                .iter()
+                .map(|(column, rotation)| ((*column).into(), rotation)),
+        )
+        .chain(
+            meta.instance_queries()
+                .iter()
+                .map(|(column, rotation)| ((*column).into(), rotation)),
+        );
+    for (column, rotation) in queries {
+        line(format!("query {} at {}", column_name(&column), rotation.0));
+    }
+    for lookup in meta.lookups() {
+        let mut pairs = String::new();
+        for (input, table) in lookup
+            .input_expressions()
+            .iter()
+            .zip(lookup.table_expressions())
+        {
+            write!(pairs, " {} in {};", input.identifier(), table.identifier()).unwrap();
+        }
+        line(format!("lookup {:?}:{}", lookup.name(), pairs));
+    }
+    for column in meta.permutation().get_columns() {
+        line(format!("permutation {}", column_name(&column)));
+    }
+    for column in meta.constants() {
+        line(format!("constant fixed {}", column.index()));
+    }
+    summary
+}
+
+fn column_name(column: &Column<Any>) -> String {
+    match column.column_type() {
+        Any::Advice(_) => format!("advice {}", column.index()),
+        Any::Fixed => format!("fixed {}", column.index()),
+        Any::Instance => format!("instance {}", column.index()),
+    }
+}
+
+/// Lines of `expected` missing from `actual`, prefixed with `-`, and lines
+/// of `actual` missing from `expected`, prefixed with `+`, or `None` if the
+/// texts are equal.
+pub fn golden_diff(expected: &str, actual: &str) -> Option<String> {
+    if expected == actual {
+        return None;
+    }
+    let (expected, actual): (Vec<_>, Vec<_>) =
+        (expected.lines().collect(), actual.lines().collect());
+    let mut diff = String::new();
+    for line in expected.iter().filter(|line| !actual.contains(line)) {
+        writeln!(diff, "-{line}").unwrap();
+    }
+    for line in actual.iter().filter(|line| !expected.contains(line)) {
+        writeln!(diff, "+{line}").unwrap();
+    }
+    if diff.is_empty() {
+        diff.push_str("the lines are the same, in another order\n");
+    }
+    Some(diff)
+}
+
+/// Asserts that `summary` is the content of the golden file at `path`, or
+/// writes it there if [`UPDATE_GOLDEN`] is set.
+pub fn assert_golden(path: &Path, summary: &str) {
+    if std::env::var_os(UPDATE_GOLDEN).is_some() {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).unwrap();
+        }
+        fs::write(path, summary).unwrap();
+        return;
+    }
+    let golden = fs::read_to_string(path).unwrap_or_else(|err| {
+        panic!(
+            "no golden file at {}: {err}, write it with {UPDATE_GOLDEN}=1",
+            path.display()
+        )
+    });
+    if let Some(diff) = golden_diff(&golden, summary) {
+        panic!(
+            "the constraint system differs from its golden file {}:\n{diff}if the change is \
+             intended, write the golden files again with {UPDATE_GOLDEN}=1",
+            path.display()
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gadgets::{tables::RangeTable, RangeCheckConfig};
+    use halo2_proofs::halo2curves::bn256::Fr;
+
+    #[test]
+    fn test_summary() {
+        let summary = summary::<Fr, _>(|meta| {
+            let table = RangeTable::<8>::construct(meta);
+            RangeCheckConfig::<8>::configure(meta, table)
+        });
+        let lines: Vec<&str> = summary.lines().collect();
+        assert_eq!(lines[0], "advice phases: [0]");
+        assert!(lines.contains(&"query advice 0 at 0"));
+        assert!(lines.contains(&"permutation advice 0"));
+        assert!(lines
+            .iter()
+            .any(|line| line.starts_with("lookup \"value is in range\": ")));
+    }
+
+    #[test]
+    fn test_golden_diff() {
+        assert_eq!(golden_diff("a\nb\n", "a\nb\n"), None);
+        assert_eq!(
+            golden_diff("a\nb\nc\n", "a\nd\nc\n"),
+            Some("-b\n+d\n".to_string())
+        );
+        assert!(golden_diff("a\nb\n", "b\na\n").is_some());
+    }
+}
@@ -0,0 +1,202 @@
+//! The test vectors of the precompile circuits, shared by their `dev`
+//! modules, their tests and benchmarks and the tests of the CLI, so that
+//! they all check the same calls.
+//!
+//! The fixtures of a circuit are a JSON array in `fixtures/<circuit>.json`,
+//! one object per call:
+//!
+//! ```json
+//! {"circuit": "sha2", "input": "616263", "output": "ba7816bf...", "k": 8}
+//! ```
+//!
+//! `circuit` is one of `sha2`, `blake2f` and `ripemd160`, as the `--circuit`
+//! of the `precompile-prover`; `input` and `output` are hex strings, with an
+//! optional `0x` prefix, the input of a BLAKE2F call being the 213 bytes of
+//! its EIP-152 encoding; `k` is the degree the call fits in, if the fixture
+//! needs one. The fixtures are embedded in the crate, so that the `dev`
+//! modules load them in the browser too, and [`parse_fixtures`] reads those
+//! of other files.
+//!
+//! The soak tests of the circuits, ignored by default, fill a circuit close
+//! to its capacity at [`SOAK_K`] with the calls of [`random_inputs`] and
+//! [`random_blake2f_input`], and prove and verify them, to check the
+//! capacity accounting and the row offsets on many more calls than the
+//! fixtures hold. They are run with `cargo test --release --lib -- --ignored
+//! soak`, `--lib` skipping the examples of the crate docs.
+//!
+//! The golden files of the constraint systems of the circuits are checked
+//! with [`golden`].
+
+pub mod golden;
+
+use std::io;
+
+use ethers_core::utils::hex;
+use rand_core::RngCore;
+use serde_json::Value;
+
+/// The circuits with fixtures.
+pub const CIRCUITS: [&str; 3] = ["sha2", "blake2f", "ripemd160"];
+
+/// A call of a precompile and its expected output.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Fixture {
+    pub circuit: String,
+    pub input: Vec<u8>,
+    pub output: Vec<u8>,
+    /// The degree the call fits in, if the fixture gives one.
+    pub k: Option<u32>,
+}
+
+fn invalid_data(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+/// The fixtures of the JSON array `json`, see the crate docs.
+pub fn parse_fixtures(json: &str) -> io::Result<Vec<Fixture>> {
+    let value = serde_json::from_str(json).map_err(|err| invalid_data(err.to_string()))?;
+    let fixtures = value
+        .as_array()
+        .ok_or_else(|| invalid_data("the fixtures are not a JSON array".to_string()))?;
+    fixtures
+        .iter()
+        .enumerate()
+        .map(|(i, fixture)| {
+            let field = |name| {
+                fixture
+                    .get(name)
+                    .and_then(Value::as_str)
+                    .ok_or_else(|| invalid_data(format!("fixture {i} has no string {name}")))
+            };
+            let bytes = |name| {
+                let text = field(name)?;
+                hex::decode(text.strip_prefix("0x").unwrap_or(text))
+                    .map_err(|err| invalid_data(format!("the {name} of fixture {i}: {err}")))
+            };
+            let k = match fixture.get("k") {
+                None => None,
+                Some(k) => Some(
+                    k.as_u64()
+                        .and_then(|k| u32::try_from(k).ok())
+                        .ok_or_else(|| invalid_data(format!("the k of fixture {i}: {k}")))?,
+                ),
+            };
+            Ok(Fixture {
+                circuit: field("circuit")?.to_string(),
+                input: bytes("input")?,
+                output: bytes("output")?,
+                k,
+            })
+        })
+        .collect()
+}
+
+/// The fixtures embedded for `circuit`, one of [`CIRCUITS`].
+///
+/// # Panics
+///
+/// If `circuit` has no fixtures, or they are malformed or of another
+/// circuit, which the tests of this crate rule out.
+pub fn fixtures(circuit: &str) -> Vec<Fixture> {
+    let json = match circuit {
+        "sha2" => include_str!("../fixtures/sha2.json"),
+        "blake2f" => include_str!("../fixtures/blake2f.json"),
+        "ripemd160" => include_str!("../fixtures/ripemd160.json"),
+        _ => panic!("no fixtures for the circuit {circuit}"),
+    };
+    let fixtures =
+        parse_fixtures(json).unwrap_or_else(|err| panic!("malformed fixtures of {circuit}: {err}"));
+    assert!(
+        fixtures.iter().all(|fixture| fixture.circuit == circuit),
+        "the fixtures of {circuit} are of another circuit"
+    );
+    fixtures
+}
+
+/// The inputs and the outputs of the fixtures of `circuit`, as the
+/// `INPUTS_OUTPUTS` of the `dev` modules take them.
+pub fn inputs_outputs(circuit: &str) -> (Vec<Vec<u8>>, Vec<Vec<u8>>) {
+    fixtures(circuit)
+        .into_iter()
+        .map(|fixture| (fixture.input, fixture.output))
+        .unzip()
+}
+
+/// The degree of the soak tests.
+pub const SOAK_K: u32 = 19;
+
+/// `calls` random inputs of at most `max_bytes` bytes in total, each of a
+/// random length of up to twice their average, so that they fill the
+/// capacity of a circuit of `calls` calls of `max_bytes` bytes but for the
+/// shortfall of the last ones.
+pub fn random_inputs(rng: &mut impl RngCore, calls: usize, max_bytes: usize) -> Vec<Vec<u8>> {
+    let max_len = 2 * max_bytes / calls.max(1);
+    let mut remaining = max_bytes;
+    (0..calls)
+        .map(|_| {
+            let len = (rng.next_u64() as usize % (max_len + 1)).min(remaining);
+            remaining -= len;
+            let mut input = vec![0; len];
+            rng.fill_bytes(&mut input);
+            input
+        })
+        .collect()
+}
+
+/// A random input of a BLAKE2F call of `rounds` rounds, in its EIP-152
+/// encoding.
+pub fn random_blake2f_input(rng: &mut impl RngCore, rounds: u32) -> Vec<u8> {
+    let mut input = vec![0; 213];
+    input[..4].copy_from_slice(&rounds.to_be_bytes());
+    rng.fill_bytes(&mut input[4..212]);
+    input[212] = (rng.next_u32() & 1) as u8;
+    input
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand_core::SeedableRng;
+
+    #[test]
+    fn test_fixtures() {
+        for circuit in CIRCUITS {
+            assert!(!fixtures(circuit).is_empty(), "{circuit}");
+        }
+        assert_eq!(fixtures("blake2f")[0].input.len(), 213);
+        assert_eq!(fixtures("sha2")[1].input, b"abc");
+    }
+
+    #[test]
+    fn test_random_inputs() {
+        let mut rng = rand_xorshift::XorShiftRng::from_seed([0x42; 16]);
+        let inputs = random_inputs(&mut rng, 100, 1000);
+        assert_eq!(inputs.len(), 100);
+        assert!(inputs.iter().map(Vec::len).sum::<usize>() <= 1000);
+        let input = random_blake2f_input(&mut rng, 12);
+        assert_eq!(input[..4], 12u32.to_be_bytes());
+        assert!(input[212] <= 1);
+    }
+
+    #[test]
+    fn test_parse_fixtures() {
+        let fixtures =
+            parse_fixtures(r#"[{"circuit": "sha2", "input": "0x61", "output": "ff", "k": 9}]"#)
+                .unwrap();
+        assert_eq!(
+            fixtures,
+            vec![Fixture {
+                circuit: "sha2".to_string(),
+                input: b"a".to_vec(),
+                output: vec![0xff],
+                k: Some(9),
+            }]
+        );
+        assert_eq!(
+            parse_fixtures(r#"[{"circuit": "sha2", "input": "", "output": ""}]"#).unwrap()[0].k,
+            None
+        );
+        assert!(parse_fixtures(r#"[{"circuit": "sha2", "input": "6"}]"#).is_err());
+        assert!(parse_fixtures(r#"{"circuit": "sha2"}"#).is_err());
+    }
+}
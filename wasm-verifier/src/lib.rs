@@ -0,0 +1,140 @@
+//! The verifier of the proofs of the SHA2-256, BLAKE2F and RIPEMD-160
+//! circuits for the browser, a `wasm-bindgen` wrapper of `verify_proof`
+//! built for `wasm32-unknown-unknown`:
+//!
+//! ```text
+//! cd wasm-verifier
+//! wasm-pack build --target web
+//! ```
+//!
+//! A page verifies the files written by the `precompile-prover` binary of
+//! the `cli` crate, read as bytes:
+//!
+//! ```text
+//! import init, { verify } from "./pkg/wasm_verifier.js";
+//! await init();
+//! const valid = verify("sha2", params, vk, proof, table, inputs);
+//! ```
+//!
+//! The instances are the two instance columns of the circuits, their table
+//! and the input bytes of their calls, laid out as specified in the
+//! `public_inputs` module of `gadgets`, 32 bytes per value in little-endian.
+//!
+//! The proofs are the SHPLONK proofs over the BLAKE2b transcript of its
+//! default, and the verifying keys in the raw bytes format, prefixed with
+//! the version of the constraint system of their circuit, so that a key of
+//! a circuit whose gates changed is refused. The keys are read by the `keys`
+//! module of the circuit crates, which are built without their `test`
+//! feature, whose fixtures are read from files, and with their `wasm`
+//! feature, which gives `rand` the randomness of the browser.
+
+use std::io;
+
+use halo2_proofs::{
+    halo2curves::{
+        bn256::{Bn256, Fr, G1Affine},
+        group::ff::PrimeField,
+    },
+    plonk::{verify_proof, VerifyingKey},
+    poly::{
+        commitment::{Params, ParamsProver},
+        kzg::{
+            commitment::{KZGCommitmentScheme, ParamsKZG},
+            multiopen::VerifierSHPLONK,
+            strategy::SingleStrategy,
+        },
+    },
+    transcript::{Blake2bRead, Challenge255, TranscriptReadBuffer},
+    SerdeFormat,
+};
+use wasm_bindgen::prelude::*;
+
+use blake2f_circuit::keys as blake2f;
+use ripemd160_circuit::keys as ripemd160;
+use sha2_256_circuit::keys as sha2;
+
+/// The values of an instance column of `instances`, 32 bytes each in
+/// little-endian, or `None` if one is not a field element.
+fn parse_instances(instances: &[u8]) -> Option<Vec<Fr>> {
+    if instances.len() % 32 != 0 {
+        return None;
+    }
+    instances
+        .chunks_exact(32)
+        .map(|repr| Option::from(Fr::from_repr(repr.try_into().ok()?)))
+        .collect()
+}
+
+/// Verifies `proof` with the verifying key `vk`, read by `read_vk`, the
+/// reader of the keys of its circuit, which checks their version.
+fn verify_with<'vk>(
+    read_vk: impl FnOnce(&mut &'vk [u8], SerdeFormat) -> io::Result<VerifyingKey<G1Affine>>,
+    params: &[u8],
+    vk: &'vk [u8],
+    proof: &[u8],
+    instances: &[&[Fr]],
+) -> Result<bool, JsError> {
+    let params = ParamsKZG::<Bn256>::read(&mut &params[..])?;
+    let vk = read_vk(&mut &vk[..], SerdeFormat::RawBytes)?;
+    let mut transcript = Blake2bRead::<_, G1Affine, Challenge255<_>>::init(proof);
+    Ok(verify_proof::<
+        KZGCommitmentScheme<Bn256>,
+        VerifierSHPLONK<'_, Bn256>,
+        Challenge255<G1Affine>,
+        Blake2bRead<&[u8], G1Affine, Challenge255<G1Affine>>,
+        SingleStrategy<'_, Bn256>,
+    >(
+        params.verifier_params(),
+        &vk,
+        SingleStrategy::new(&params),
+        &[instances],
+        &mut transcript,
+    )
+    .is_ok())
+}
+
+/// Whether `proof` of the circuit `circuit`, one of `sha2`, `blake2f` and
+/// `ripemd160`, is valid for the instances `table` and `inputs` of its two
+/// instance columns, verified with the parameters `params` and the
+/// verifying key `vk`. Errors if an argument cannot be read.
+#[wasm_bindgen]
+pub fn verify(
+    circuit: &str,
+    params: &[u8],
+    vk: &[u8],
+    proof: &[u8],
+    table: &[u8],
+    inputs: &[u8],
+) -> Result<bool, JsError> {
+    let parse = |instances: &[u8]| {
+        parse_instances(instances)
+            .ok_or_else(|| JsError::new("the instances are not 32-byte field elements"))
+    };
+    let (table, inputs) = (parse(table)?, parse(inputs)?);
+    let instances = [&table[..], &inputs[..]];
+    match circuit {
+        "sha2" => verify_with(sha2::read_vk, params, vk, proof, &instances),
+        "blake2f" => verify_with(blake2f::read_vk, params, vk, proof, &instances),
+        "ripemd160" => verify_with(ripemd160::read_vk, params, vk, proof, &instances),
+        _ => Err(JsError::new(&format!("unknown circuit {circuit}"))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_instances() {
+        let mut bytes = Fr::from(7).to_repr().to_vec();
+        bytes.extend(Fr::from(8).to_repr());
+        assert_eq!(
+            parse_instances(&bytes),
+            Some(vec![Fr::from(7), Fr::from(8)])
+        );
+        assert_eq!(parse_instances(&[]), Some(vec![]));
+        assert_eq!(parse_instances(&bytes[1..]), None);
+        // the modulus is not a field element.
+        assert_eq!(parse_instances(&[0xff; 32]), None);
+    }
+}